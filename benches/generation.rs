@@ -0,0 +1,105 @@
+//! Account generation throughput.
+//!
+//! Run with `cargo bench --bench generation`. Each benchmark generates a
+//! batch of 1,000 accounts per iteration and reports accounts per second.
+
+use borsh::BorshSerialize;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use solana_accountgen::fixture::FixtureWriter;
+use solana_accountgen::pool::BufferPool;
+use solana_accountgen::{AccountBuilder, AccountMap};
+use solana_pubkey::Pubkey;
+use std::hint::black_box;
+
+const BATCH: u64 = 1_000;
+
+#[derive(BorshSerialize)]
+struct Position {
+    owner: Pubkey,
+    market: Pubkey,
+    size: u64,
+    entry_price: u64,
+    open_orders: Vec<u64>,
+}
+
+fn position(index: u64) -> Position {
+    Position {
+        owner: Pubkey::new_from_array([index as u8; 32]),
+        market: Pubkey::new_from_array([7; 32]),
+        size: index,
+        entry_price: index * 3,
+        open_orders: vec![index; 4],
+    }
+}
+
+fn generation(c: &mut Criterion) {
+    let owner = Pubkey::new_unique();
+    let mut group = c.benchmark_group("generation");
+    group.throughput(Throughput::Elements(BATCH));
+
+    group.bench_function("build_empty", |b| {
+        b.iter(|| {
+            for _ in 0..BATCH {
+                black_box(AccountBuilder::new().balance(1_000).owner(owner).build());
+            }
+        })
+    });
+
+    group.bench_function("build_borsh", |b| {
+        b.iter(|| {
+            for i in 0..BATCH {
+                let builder = AccountBuilder::new().owner(owner).data(position(i));
+                black_box(builder.unwrap().build());
+            }
+        })
+    });
+
+    group.bench_function("build_borsh_pooled", |b| {
+        let mut pool = BufferPool::new();
+        b.iter(|| {
+            for i in 0..BATCH {
+                let builder = AccountBuilder::new()
+                    .owner(owner)
+                    .data_borsh_into(pool.take(), &position(i));
+                let account = black_box(builder.unwrap().build());
+                pool.recycle_account(account);
+            }
+        })
+    });
+
+    group.bench_function("account_map_insert", |b| {
+        b.iter(|| {
+            let mut map = AccountMap::new();
+            for i in 0..BATCH {
+                let mut key = [0; 32];
+                key[..8].copy_from_slice(&i.to_le_bytes());
+                map.set_account(
+                    Pubkey::new_from_array(key),
+                    AccountBuilder::new().balance(i).owner(owner).build(),
+                );
+            }
+            black_box(map)
+        })
+    });
+
+    group.bench_function("fixture_stream_pooled", |b| {
+        let mut writer = FixtureWriter::new(std::io::sink());
+        let mut pool = BufferPool::new();
+        b.iter(|| {
+            for i in 0..BATCH {
+                let account = AccountBuilder::new()
+                    .owner(owner)
+                    .data_borsh_into(pool.take(), &position(i))
+                    .unwrap()
+                    .build();
+                writer.append(&owner, &account).unwrap();
+                pool.recycle_account(account);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, generation);
+criterion_main!(benches);
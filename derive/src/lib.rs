@@ -0,0 +1,180 @@
+//! Derive macros for [solana-accountgen](https://docs.rs/solana-accountgen).
+//!
+//! Use these through the re-exports in `solana_accountgen` rather than
+//! depending on this crate directly: `AccountFixture` at the crate root
+//! and `InitSpace` in `solana_accountgen::extensions::anchor`.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{
+    Data, DeriveInput, Expr, Fields, GenericArgument, LitStr, PathArguments, Token, Type,
+    parse_macro_input,
+};
+
+/// Implements `solana_accountgen::AccountFixture` for a Borsh type.
+///
+/// The Anchor account name defaults to the type name and can be changed
+/// with `#[account_fixture(anchor_name = "...")]`.
+#[proc_macro_derive(AccountFixture, attributes(account_fixture))]
+pub fn derive_account_fixture(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_account_fixture(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_account_fixture(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut anchor_name = LitStr::new(&input.ident.to_string(), input.ident.span());
+    for attr in &input.attrs {
+        if !attr.path().is_ident("account_fixture") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("anchor_name") {
+                anchor_name = meta.value()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("expected `anchor_name = \"...\"`"))
+            }
+        })?;
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::solana_accountgen::AccountFixture for #name #ty_generics #where_clause {
+            const ANCHOR_NAME: &'static str = #anchor_name;
+        }
+    })
+}
+
+/// Implements `solana_accountgen::extensions::anchor::InitSpace`.
+///
+/// `String` and `Vec` fields need `#[max_len(...)]`, with one length per
+/// level of nesting, as in Anchor.
+#[proc_macro_derive(InitSpace, attributes(max_len))]
+pub fn derive_init_space(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_init_space(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_init_space(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let space = match &input.data {
+        Data::Struct(data) => fields_space(&data.fields)?,
+        Data::Enum(data) => {
+            // One tag byte plus the largest variant
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| fields_space(&variant.fields))
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                1 + {
+                    let mut max = 0;
+                    #(
+                        let space = #variants;
+                        if space > max {
+                            max = space;
+                        }
+                    )*
+                    max
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "InitSpace cannot be derived for unions",
+            ));
+        }
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::solana_accountgen::extensions::anchor::InitSpace for #name #ty_generics #where_clause {
+            const INIT_SPACE: usize = #space;
+        }
+    })
+}
+
+fn fields_space(fields: &Fields) -> syn::Result<proc_macro2::TokenStream> {
+    let spaces = fields
+        .iter()
+        .map(|field| {
+            let lens = match field
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("max_len"))
+            {
+                Some(attr) => attr
+                    .parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?
+                    .into_iter()
+                    .collect(),
+                None => Vec::new(),
+            };
+            type_space(&field.ty, &lens)
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok(quote! { 0 #(+ #spaces)* })
+}
+
+/// Returns the space of `ty`, consuming one `#[max_len]` entry per `String`
+/// or `Vec` on the way down.
+fn type_space(ty: &Type, lens: &[Expr]) -> syn::Result<proc_macro2::TokenStream> {
+    if let Type::Array(array) = ty {
+        let len = &array.len;
+        let elem = type_space(&array.elem, lens)?;
+        return Ok(quote! { (#len) * (#elem) });
+    }
+
+    if let Type::Path(path) = ty
+        && path.qself.is_none()
+        && let Some(segment) = path.path.segments.last()
+    {
+        let inner = match &segment.arguments {
+            PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(GenericArgument::Type(inner)) => Some(inner),
+                _ => None,
+            },
+            _ => None,
+        };
+        let ident = segment.ident.to_string();
+        match (ident.as_str(), inner) {
+            ("String", None) => {
+                let len = max_len(ty, lens)?;
+                return Ok(quote! { 4 + (#len) });
+            }
+            ("Vec", Some(inner)) => {
+                let len = max_len(ty, lens)?;
+                let elem = type_space(inner, &lens[1..])?;
+                return Ok(quote! { 4 + (#len) * (#elem) });
+            }
+            ("Option", Some(inner)) => {
+                let inner = type_space(inner, lens)?;
+                return Ok(quote! { 1 + (#inner) });
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(extra) = lens.first() {
+        return Err(syn::Error::new_spanned(
+            extra,
+            "too many `#[max_len]` lengths for this type",
+        ));
+    }
+    Ok(quote! { <#ty as ::solana_accountgen::extensions::anchor::InitSpace>::INIT_SPACE })
+}
+
+fn max_len<'a>(ty: &Type, lens: &'a [Expr]) -> syn::Result<&'a Expr> {
+    lens.first().ok_or_else(|| {
+        syn::Error::new_spanned(
+            ty,
+            "`String` and `Vec` fields need a `#[max_len(...)]` length",
+        )
+    })
+}
@@ -1,11 +1,19 @@
+use crate::cluster::{self, ClusterContext};
+use crate::config::AccountGenConfig;
 use crate::error::AccountGenError;
+use crate::fixture::record::encoded_data;
+use crate::serialization::borsh::serialize_data_into;
+use crate::serialization::schema::FixtureSchema;
+use crate::serialization::{DataEncoding, json};
+use crate::tracking;
+use crate::units::IntoLamports;
+use crate::well_known;
 use base64;
 use borsh::BorshSerialize;
 use serde::{Deserialize, Serialize};
 use solana_account::Account;
 use solana_pubkey::Pubkey;
-use solana_rent::Rent;
-use solana_sdk_ids::system_program;
+use std::str::FromStr;
 
 /// A builder for creating mock Solana accounts for testing purposes.
 ///
@@ -14,18 +22,91 @@ use solana_sdk_ids::system_program;
 ///
 /// # Defaults
 ///
-/// - **Owner**: System Program (`system_program::id()`) if not specified
+/// - **Owner**: System Program (`system_program::id()`) if not specified, or
+///   the [`AccountGenConfig`](crate::config::AccountGenConfig) default owner
+///   installed on the current thread
 /// - **Balance**: Rent-exempt amount based on data size if not explicitly set
-/// - **Executable**: `false`
+/// - **Executable**: `false`. Executable accounts must be owned by one of the
+///   [loaders](crate::well_known::LOADERS) and hold program data unless
+///   [`AccountBuilder::skip_executable_checks`] is set
 /// - **Rent Epoch**: `0`
+///
+/// When a [`ClusterContext`] is attached with [`AccountBuilder::cluster`],
+/// the balance and rent epoch defaults come from that cluster instead.
+///
+/// # Validation
+///
+/// In the default [`BuildMode::Lenient`] mode, only the executable checks
+/// run, so intentionally invalid accounts can be built for negative tests.
+/// [`BuildMode::Strict`] also rejects accounts that are neither empty nor
+/// rent-exempt and data over the runtime's size limit. Set the mode with
+/// [`AccountBuilder::mode`] or on the attached [`ClusterContext`], and opt
+/// out of single checks with [`AccountBuilder::skip_rent_check`],
+/// [`AccountBuilder::skip_size_limit`] and
+/// [`AccountBuilder::skip_executable_checks`].
+///
+/// # Serialization format
+///
+/// Builders serialize to a versioned object so they can be stored in
+/// manifests and replayed later. Unset fields are omitted, pubkeys are
+/// base58 strings, and data uses the fixture `[<payload>, <encoding>]` pair:
+///
+/// ```text
+/// {"version":1,"pubkey":"...","owner":"...","executable":false,"data":["AQID","base64"]}
+/// ```
+///
+/// Within a version the format only grows: new fields are optional and
+/// default to their unset value, and unknown fields are ignored, so older
+/// readers accept newer documents. Incompatible changes bump
+/// [`BUILDER_FORMAT_VERSION`], and documents with a newer version are
+/// rejected. The attached [`ClusterContext`] is not serialized.
+///
+/// Documents without a `version` are read as the unversioned format of
+/// earlier releases, which wrote the fields in snake_case with pubkeys and
+/// data as byte arrays:
+///
+/// ```text
+/// {"pubkey":[1,2,...],"lamports":42,"owner":null,"executable":false,"rent_epoch":0,"data":[1,2,3]}
+/// ```
+///
+/// They load with the rent epoch they recorded and are written back in the
+/// current format, so re-serializing a builder migrates it.
+///
+/// Telling the two formats apart needs the field names, so builders only
+/// deserialize from self-describing formats such as JSON and YAML.
+/// Non-self-describing formats like bincode are rejected, and error
+/// messages do not carry the line and column of the offending field.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(into = "BuilderRepr", try_from = "BuilderDocument")]
 pub struct AccountBuilder {
     pubkey: Option<Pubkey>,
     lamports: Option<u64>,
     owner: Option<Pubkey>,
     executable: bool,
-    rent_epoch: u64,
+    skip_executable_checks: bool,
+    enforce_size_limit: bool,
+    mode: Option<BuildMode>,
+    skip_rent_check: bool,
+    skip_size_limit: bool,
+    rent_epoch: Option<u64>,
     data: Vec<u8>,
+    cluster: Option<ClusterContext>,
+}
+
+/// The current version of the serialized [`AccountBuilder`] format.
+pub const BUILDER_FORMAT_VERSION: u32 = 1;
+
+/// How strictly [`AccountBuilder::try_build`] validates accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BuildMode {
+    /// Runs every check: rent exemption, the data size limit and the
+    /// executable rules. For fixtures meant to mirror on-chain state.
+    Strict,
+    /// Runs only the executable rules, and the size limit if
+    /// [`AccountBuilder::enforce_size_limit`] is called.
+    #[default]
+    Lenient,
 }
 
 impl AccountBuilder {
@@ -42,18 +123,20 @@ impl AccountBuilder {
         Self::default()
     }
 
-    /// Sets the account balance in lamports.
+    /// Sets the account balance, in lamports or as [`Sol`](crate::Sol).
     ///
     /// # Example
     ///
     /// ```
-    /// use solana_accountgen::AccountBuilder;
+    /// use solana_accountgen::{AccountBuilder, Sol};
     ///
     /// let builder = AccountBuilder::new()
     ///     .balance(1_000_000);
+    /// let rich = AccountBuilder::new()
+    ///     .balance(Sol(1.5));
     /// ```
-    pub fn balance(mut self, lamports: u64) -> Self {
-        self.lamports = Some(lamports);
+    pub fn balance(mut self, lamports: impl IntoLamports) -> Self {
+        self.lamports = Some(lamports.into_lamports());
         self
     }
 
@@ -89,6 +172,100 @@ impl AccountBuilder {
         self
     }
 
+    /// Allows executable accounts that are not owned by a loader or that have
+    /// no data.
+    ///
+    /// Such accounts cannot be invoked, so this is only useful for testing how
+    /// a program handles malformed program accounts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountBuilder;
+    ///
+    /// let bogus_program = AccountBuilder::new()
+    ///     .executable(true)
+    ///     .skip_executable_checks()
+    ///     .try_build()
+    ///     .unwrap();
+    /// assert!(bogus_program.executable);
+    /// ```
+    pub fn skip_executable_checks(mut self) -> Self {
+        self.skip_executable_checks = true;
+        self
+    }
+
+    /// Rejects data larger than the runtime's 10 MiB account limit when the
+    /// account is built.
+    ///
+    /// The check is off by default so tests can build oversized accounts on
+    /// purpose; enable it for fixtures meant to mirror on-chain state.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::{AccountBuilder, AccountGenError};
+    /// use solana_accountgen::cluster::MAX_PERMITTED_DATA_LENGTH;
+    ///
+    /// let result = AccountBuilder::new()
+    ///     .data_raw(vec![0; MAX_PERMITTED_DATA_LENGTH + 1])
+    ///     .enforce_size_limit()
+    ///     .try_build();
+    /// assert!(matches!(result, Err(AccountGenError::DataTooLarge { .. })));
+    /// ```
+    pub fn enforce_size_limit(mut self) -> Self {
+        self.enforce_size_limit = true;
+        self
+    }
+
+    /// Sets the build mode, overriding the attached [`ClusterContext`]'s.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::{AccountBuilder, AccountGenError, BuildMode};
+    ///
+    /// // Lenient builders accept rent-paying accounts
+    /// assert!(AccountBuilder::new().balance(1).try_build().is_ok());
+    ///
+    /// let result = AccountBuilder::new()
+    ///     .balance(1)
+    ///     .mode(BuildMode::Strict)
+    ///     .try_build();
+    /// assert!(matches!(result, Err(AccountGenError::InsufficientBalance { .. })));
+    /// ```
+    pub fn mode(mut self, mode: BuildMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Skips the rent exemption check of [`BuildMode::Strict`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::{AccountBuilder, BuildMode};
+    ///
+    /// let rent_paying = AccountBuilder::new()
+    ///     .balance(1)
+    ///     .mode(BuildMode::Strict)
+    ///     .skip_rent_check()
+    ///     .try_build();
+    /// assert!(rent_paying.is_ok());
+    /// ```
+    pub fn skip_rent_check(mut self) -> Self {
+        self.skip_rent_check = true;
+        self
+    }
+
+    /// Skips the data size limit of [`BuildMode::Strict`].
+    ///
+    /// [`AccountBuilder::enforce_size_limit`] takes precedence.
+    pub fn skip_size_limit(mut self) -> Self {
+        self.skip_size_limit = true;
+        self
+    }
+
     /// Sets the account rent epoch.
     ///
     /// # Example
@@ -100,7 +277,31 @@ impl AccountBuilder {
     ///     .rent_epoch(100);
     /// ```
     pub fn rent_epoch(mut self, rent_epoch: u64) -> Self {
-        self.rent_epoch = rent_epoch;
+        self.rent_epoch = Some(rent_epoch);
+        self
+    }
+
+    /// Sets the cluster context used to compute default values.
+    ///
+    /// Unless set explicitly, the balance defaults to the context's
+    /// rent-exempt minimum and the rent epoch to the value the modelled
+    /// cluster version would assign.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountBuilder;
+    /// use solana_accountgen::cluster::{ClusterContext, ClusterVersion};
+    ///
+    /// let context = ClusterContext::new(ClusterVersion::V1_17).at_slot(432_000 * 3);
+    /// let account = AccountBuilder::new()
+    ///     .cluster(context.clone())
+    ///     .build();
+    ///
+    /// assert_eq!(account.rent_epoch, context.epoch + 1);
+    /// ```
+    pub fn cluster(mut self, context: ClusterContext) -> Self {
+        self.cluster = Some(context);
         self
     }
 
@@ -146,6 +347,44 @@ impl AccountBuilder {
         Ok(self)
     }
 
+    /// Sets the account data by serializing `data` with Borsh into
+    /// `buffer`, which becomes the account's data.
+    ///
+    /// Unlike [`AccountBuilder::data`], this reuses the buffer's
+    /// allocation. Loops generating many accounts can take buffers from a
+    /// [`BufferPool`](crate::pool::BufferPool) and recycle the data of
+    /// accounts they are done with.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountBuilder;
+    /// use solana_accountgen::pool::BufferPool;
+    ///
+    /// let mut pool = BufferPool::new();
+    /// for value in 0u64..1_000 {
+    ///     let account = AccountBuilder::new()
+    ///         .data_borsh_into(pool.take(), &value)
+    ///         .unwrap()
+    ///         .build();
+    ///     assert_eq!(account.data, value.to_le_bytes());
+    ///     pool.recycle_account(account);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn data_borsh_into<T: BorshSerialize>(
+        mut self,
+        mut buffer: Vec<u8>,
+        data: &T,
+    ) -> Result<Self, AccountGenError> {
+        serialize_data_into(&mut buffer, data)?;
+        self.data = buffer;
+        Ok(self)
+    }
+
     /// Sets the account's pubkey.
     ///
     /// # Example
@@ -159,6 +398,7 @@ impl AccountBuilder {
     ///     .pubkey(pubkey);
     /// ```
     pub fn pubkey(mut self, pubkey: Pubkey) -> Self {
+        tracking::record(pubkey);
         self.pubkey = Some(pubkey);
         self
     }
@@ -189,6 +429,112 @@ impl AccountBuilder {
         Ok(self)
     }
 
+    /// Sets the account data using base58-encoded data, as returned by RPC
+    /// `getAccountInfo` with `"encoding": "base58"`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountBuilder;
+    ///
+    /// let account = AccountBuilder::new()
+    ///     .data_base58("Ldp")
+    ///     .unwrap()
+    ///     .build();
+    /// assert_eq!(account.data, vec![1, 2, 3]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if base58 decoding fails.
+    pub fn data_base58(mut self, base58_data: &str) -> Result<Self, AccountGenError> {
+        self.data = DataEncoding::Base58.decode(base58_data)?;
+        Ok(self)
+    }
+
+    /// Sets the account data from text in the given encoding.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountBuilder;
+    /// use solana_accountgen::serialization::DataEncoding;
+    ///
+    /// let account = AccountBuilder::new()
+    ///     .data_encoded("010203", DataEncoding::Hex)
+    ///     .unwrap()
+    ///     .build();
+    /// assert_eq!(account.data, vec![1, 2, 3]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if decoding fails.
+    pub fn data_encoded(
+        mut self,
+        data: &str,
+        encoding: DataEncoding,
+    ) -> Result<Self, AccountGenError> {
+        self.data = encoding.decode(data)?;
+        Ok(self)
+    }
+
+    /// Sets the account data from a JSON value.
+    ///
+    /// Without a schema the value is stored as JSON text, for programs that
+    /// keep JSON in their accounts. With a schema the value is encoded with
+    /// Borsh as the schema's root type, so fixture data can be written by
+    /// hand.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountBuilder;
+    /// use solana_accountgen::serialization::schema::FixtureSchema;
+    /// use serde_json::json;
+    ///
+    /// let schema: FixtureSchema = serde_json::from_value(json!({
+    ///     "version": 1,
+    ///     "types": ["Counter"],
+    ///     "definitions": {
+    ///         "Counter": {"kind": "struct", "fields": [{"name": "count", "type": "u64"}]},
+    ///         "u64": {"kind": "primitive", "size": 8}
+    ///     }
+    /// }))
+    /// .unwrap();
+    ///
+    /// let account = AccountBuilder::new()
+    ///     .data_json_value(json!({"count": 7}), Some(&schema))
+    ///     .unwrap()
+    ///     .build();
+    /// assert_eq!(account.data, 7u64.to_le_bytes());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value does not match the schema, or if the
+    /// schema does not define exactly one root type. To encode one of several
+    /// types, use [`FixtureSchema::encode`] with [`AccountBuilder::data_raw`].
+    pub fn data_json_value(
+        mut self,
+        value: serde_json::Value,
+        schema: Option<&FixtureSchema>,
+    ) -> Result<Self, AccountGenError> {
+        self.data = match schema {
+            None => json::serialize_account_data_json(&value)?,
+            Some(schema) => match schema.types.as_slice() {
+                [type_name] => schema.encode(type_name, &value)?,
+                types => {
+                    return Err(AccountGenError::InvalidDataFormat(format!(
+                        "schema defines {} root types, expected exactly one",
+                        types.len()
+                    )));
+                }
+            },
+        };
+        Ok(self)
+    }
+
     /// Builds the account with the configured properties.
     ///
     /// # Example
@@ -206,7 +552,7 @@ impl AccountBuilder {
     ///
     /// # Panics
     ///
-    /// Panics if the owner has not been set.
+    /// Panics if [`AccountBuilder::try_build`] would return an error.
     pub fn build(self) -> Account {
         self.try_build().expect("Failed to build account")
     }
@@ -268,6 +614,7 @@ impl AccountBuilder {
         data: impl BorshSerialize,
     ) -> Result<(Pubkey, u8, Account), AccountGenError> {
         let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
+        tracking::record(pda);
 
         let account = Self::new()
             .balance(balance)
@@ -278,11 +625,69 @@ impl AccountBuilder {
         Ok((pda, bump, account))
     }
 
+    /// Creates an account at the address derived from `base`, `seed` and
+    /// `owner` with [`Pubkey::create_with_seed`], owned by `owner`.
+    ///
+    /// This is the address `create_account_with_seed` and the other
+    /// `*_with_seed` system instructions use, for testing programs that rely
+    /// on seeded accounts rather than PDAs. Any owner set on the builder is
+    /// replaced by `owner`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountBuilder;
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// let base = Pubkey::new_unique();
+    /// let program_id = Pubkey::new_unique();
+    /// let (address, account) = AccountBuilder::new()
+    ///     .balance(1_000_000)
+    ///     .data_raw(vec![0; 16])
+    ///     .create_seeded(&base, "stake:0", &program_id)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(address, Pubkey::create_with_seed(&base, "stake:0", &program_id).unwrap());
+    /// assert_eq!(account.owner, program_id);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the seed is longer
+    /// than 32 bytes or `owner` is not a valid seed owner, and any error
+    /// [`AccountBuilder::try_build`] returns.
+    pub fn create_seeded(
+        self,
+        base: &Pubkey,
+        seed: &str,
+        owner: &Pubkey,
+    ) -> Result<(Pubkey, Account), AccountGenError> {
+        let address = Pubkey::create_with_seed(base, seed, owner).map_err(|e| {
+            AccountGenError::InvalidDataFormat(format!("invalid seeded address: {e}"))
+        })?;
+        tracking::record(address);
+        let account = self.owner(*owner).try_build()?;
+        Ok((address, account))
+    }
+
     /// Attempts to build the account, returning an error if required fields are missing.
     ///
-    /// If no owner is specified, defaults to the System Program.
+    /// If no owner is specified, defaults to the System Program, or the
+    /// default owner installed on the current thread.
     /// If no balance is specified, defaults to rent-exempt amount for the data size.
     ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidExecutable`] if the account is
+    /// executable but its owner is not a loader or its data is empty, unless
+    /// [`AccountBuilder::skip_executable_checks`] was called, and
+    /// [`AccountGenError::DataTooLarge`] if
+    /// [`AccountBuilder::enforce_size_limit`] was called or the builder is
+    /// in [`BuildMode::Strict`], and the data exceeds
+    /// [`MAX_PERMITTED_DATA_LENGTH`](crate::cluster::MAX_PERMITTED_DATA_LENGTH).
+    /// In strict mode, also returns [`AccountGenError::InsufficientBalance`]
+    /// if the account holds lamports but is not rent-exempt.
+    ///
     /// # Example
     ///
     /// ```
@@ -301,24 +706,70 @@ impl AccountBuilder {
     /// ```
     pub fn try_build(self) -> Result<Account, AccountGenError> {
         // Default to system program if owner not specified
-        let owner = self.owner.unwrap_or_else(system_program::id);
+        let defaults = AccountGenConfig::current();
+        let owner = self.owner.unwrap_or(defaults.default_owner);
+        let mode = self
+            .mode
+            .or(self.cluster.as_ref().map(|c| c.build_mode))
+            .unwrap_or(defaults.default_mode);
+        let strict = mode == BuildMode::Strict;
+
+        if self.enforce_size_limit || (strict && !self.skip_size_limit) {
+            cluster::check_data_len(self.data.len())?;
+        }
+
+        if self.executable && !self.skip_executable_checks {
+            if !well_known::is_loader(&owner) {
+                let owner_name = well_known::name_for(&owner)
+                    .map(|name| format!("{owner} ({name})"))
+                    .unwrap_or_else(|| owner.to_string());
+                return Err(AccountGenError::InvalidExecutable(format!(
+                    "owner {owner_name} is not a loader; programs must be owned by the BPF \
+                     loaders, loader v4, or the native loader"
+                )));
+            }
+            if self.data.is_empty() {
+                return Err(AccountGenError::InvalidExecutable(
+                    "program data is empty".to_string(),
+                ));
+            }
+        }
 
         // Calculate rent-exempt balance if not specified
         let lamports = match self.lamports {
             Some(lamports) => lamports,
             None => {
-                let rent = Rent::default();
+                let rent = self
+                    .cluster
+                    .as_ref()
+                    .map(|c| c.rent.clone())
+                    .unwrap_or_default();
                 rent.minimum_balance(self.data.len())
             }
         };
 
-        Ok(Account {
+        // Use the cluster's rent epoch semantics if a context is attached
+        let rent_epoch = match (self.rent_epoch, &self.cluster) {
+            (Some(rent_epoch), _) => rent_epoch,
+            (None, Some(cluster)) => cluster.default_rent_epoch(lamports, self.data.len()),
+            (None, None) => 0,
+        };
+
+        let account = Account {
             lamports,
             data: self.data,
             owner,
             executable: self.executable,
-            rent_epoch: self.rent_epoch,
-        })
+            rent_epoch,
+        };
+
+        if (strict || defaults.require_rent_exempt) && !self.skip_rent_check {
+            self.cluster
+                .unwrap_or_default()
+                .check_rent_exempt(&account)?;
+        }
+
+        Ok(account)
     }
 
     /// Creates an account with the given pubkey.
@@ -338,7 +789,144 @@ impl AccountBuilder {
     ///     .unwrap();
     /// ```
     pub fn create_with_pubkey(self, pubkey: Pubkey) -> Result<(Pubkey, Account), AccountGenError> {
+        tracking::record(pubkey);
         let account = self.try_build()?;
         Ok((pubkey, account))
     }
 }
+
+/// The on-disk representation of an [`AccountBuilder`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BuilderRepr {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pubkey: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lamports: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    #[serde(default)]
+    executable: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    skip_executable_checks: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    enforce_size_limit: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<BuildMode>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    skip_rent_check: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    skip_size_limit: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rent_epoch: Option<u64>,
+    #[serde(default, with = "encoded_data")]
+    data: Vec<u8>,
+}
+
+impl From<AccountBuilder> for BuilderRepr {
+    fn from(builder: AccountBuilder) -> Self {
+        Self {
+            version: BUILDER_FORMAT_VERSION,
+            pubkey: builder.pubkey.map(|pubkey| pubkey.to_string()),
+            lamports: builder.lamports,
+            owner: builder.owner.map(|owner| owner.to_string()),
+            executable: builder.executable,
+            skip_executable_checks: builder.skip_executable_checks,
+            enforce_size_limit: builder.enforce_size_limit,
+            mode: builder.mode,
+            skip_rent_check: builder.skip_rent_check,
+            skip_size_limit: builder.skip_size_limit,
+            rent_epoch: builder.rent_epoch,
+            data: builder.data,
+        }
+    }
+}
+
+/// The unversioned representation written by releases before
+/// [`BUILDER_FORMAT_VERSION`] 1, which derived `Serialize` on the builder.
+#[derive(Deserialize)]
+struct LegacyBuilderRepr {
+    pubkey: Option<Pubkey>,
+    lamports: Option<u64>,
+    owner: Option<Pubkey>,
+    executable: bool,
+    rent_epoch: u64,
+    data: Vec<u8>,
+}
+
+/// A serialized builder in either the versioned or the legacy format.
+enum BuilderDocument {
+    Versioned(BuilderRepr),
+    Legacy(LegacyBuilderRepr),
+}
+
+impl<'de> Deserialize<'de> for BuilderDocument {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if value.get("version").is_some() {
+            BuilderRepr::deserialize(value)
+                .map(Self::Versioned)
+                .map_err(D::Error::custom)
+        } else {
+            LegacyBuilderRepr::deserialize(value)
+                .map(Self::Legacy)
+                .map_err(|e| D::Error::custom(format!("unversioned legacy builder format: {e}")))
+        }
+    }
+}
+
+impl TryFrom<BuilderDocument> for AccountBuilder {
+    type Error = String;
+
+    fn try_from(document: BuilderDocument) -> Result<Self, Self::Error> {
+        match document {
+            BuilderDocument::Versioned(repr) => Self::try_from(repr),
+            BuilderDocument::Legacy(legacy) => Ok(Self {
+                pubkey: legacy.pubkey,
+                lamports: legacy.lamports,
+                owner: legacy.owner,
+                executable: legacy.executable,
+                rent_epoch: Some(legacy.rent_epoch),
+                data: legacy.data,
+                ..Self::default()
+            }),
+        }
+    }
+}
+
+impl TryFrom<BuilderRepr> for AccountBuilder {
+    type Error = String;
+
+    fn try_from(repr: BuilderRepr) -> Result<Self, Self::Error> {
+        if repr.version == 0 || repr.version > BUILDER_FORMAT_VERSION {
+            return Err(format!(
+                "unsupported builder format version {} (supported: 1..={})",
+                repr.version, BUILDER_FORMAT_VERSION
+            ));
+        }
+
+        let parse = |field: &str, value: Option<String>| {
+            value
+                .map(|s| Pubkey::from_str(&s).map_err(|e| format!("invalid {field} {s}: {e}")))
+                .transpose()
+        };
+
+        Ok(Self {
+            pubkey: parse("pubkey", repr.pubkey)?,
+            lamports: repr.lamports,
+            owner: parse("owner", repr.owner)?,
+            executable: repr.executable,
+            skip_executable_checks: repr.skip_executable_checks,
+            enforce_size_limit: repr.enforce_size_limit,
+            mode: repr.mode,
+            skip_rent_check: repr.skip_rent_check,
+            skip_size_limit: repr.skip_size_limit,
+            rent_epoch: repr.rent_epoch,
+            data: repr.data,
+            cluster: None,
+        })
+    }
+}
@@ -0,0 +1,207 @@
+use crate::cluster::check_data_len;
+use crate::error::AccountGenError;
+use crate::extensions::idl::Idl;
+use crate::serialization::raw::RawData;
+use crate::serialization::schema::FixtureSchema;
+use serde::Serialize;
+use serde_json::Value;
+use solana_account::Account;
+
+/// Edits the data of an existing account in place.
+///
+/// `AccountEditor` is for quick "what if this field were X" experiments on
+/// cloned accounts, where no Rust definition of the account type is at
+/// hand. Bytes can be overwritten by offset, and with a layout attached
+/// (a [`FixtureSchema`], an Anchor [`Idl`] or, with the `schema` feature, a
+/// `BorshSchema` type) single fields can be replaced by path. Only the
+/// patched field is re-encoded; the rest of the data is left untouched.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, AccountEditor};
+/// use solana_accountgen::extensions::idl::Idl;
+///
+/// let idl = Idl::from_json(r#"{
+///     "version": "0.1.0",
+///     "name": "game",
+///     "instructions": [],
+///     "accounts": [{
+///         "name": "Game",
+///         "type": { "kind": "struct", "fields": [
+///             { "name": "round", "type": "u32" },
+///             { "name": "player", "type": { "defined": "Player" } }
+///         ] }
+///     }],
+///     "types": [{
+///         "name": "Player",
+///         "type": { "kind": "struct", "fields": [
+///             { "name": "name", "type": "string" },
+///             { "name": "score", "type": "u64" }
+///         ] }
+///     }]
+/// }"#).unwrap();
+///
+/// let mut data = idl.account_discriminator(&idl.accounts[0]).to_vec();
+/// data.extend_from_slice(&1u32.to_le_bytes());
+/// data.extend_from_slice(&[3, 0, 0, 0]);
+/// data.extend_from_slice(b"ann");
+/// data.extend_from_slice(&10u64.to_le_bytes());
+/// let mut account = AccountBuilder::new().data_raw(data).build();
+///
+/// let mut editor = AccountEditor::new(&mut account).with_idl(&idl, "Game").unwrap();
+/// editor.patch_field("player.score", 9_000u64).unwrap();
+/// editor.patch_field("player.name", "annabel").unwrap();
+/// assert_eq!(editor.field("player.score").unwrap(), 9_000);
+///
+/// assert_eq!(account.data.len(), 8 + 4 + 4 + 7 + 8);
+/// assert_eq!(account.data[23..], 9_000u64.to_le_bytes());
+/// ```
+#[derive(Debug)]
+pub struct AccountEditor<'a> {
+    account: &'a mut Account,
+    layout: Option<(FixtureSchema, String)>,
+    data_offset: usize,
+}
+
+impl<'a> AccountEditor<'a> {
+    /// Creates an editor for `account` with no layout attached.
+    pub fn new(account: &'a mut Account) -> Self {
+        Self {
+            account,
+            layout: None,
+            data_offset: 0,
+        }
+    }
+
+    /// Describes the account data as `type_name` from `schema`.
+    pub fn with_schema(mut self, schema: FixtureSchema, type_name: impl Into<String>) -> Self {
+        self.layout = Some((schema, type_name.into()));
+        self
+    }
+
+    /// Describes the account data as the `BorshSchema` type `T`.
+    #[cfg(feature = "schema")]
+    pub fn with_type<T: borsh::BorshSchema + ?Sized>(self) -> Self {
+        let mut schema = FixtureSchema::new();
+        schema.register::<T>();
+        let type_name = schema.types[0].clone();
+        self.with_schema(schema, type_name)
+    }
+
+    /// Describes the account data as the Anchor account `account_type`
+    /// from `idl`, after its 8-byte discriminator.
+    pub fn with_idl(self, idl: &Idl, account_type: &str) -> Result<Self, AccountGenError> {
+        if !idl
+            .accounts
+            .iter()
+            .any(|account| account.name == account_type)
+        {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "IDL has no account type {account_type}"
+            )));
+        }
+        Ok(self
+            .with_schema(idl.to_fixture_schema()?, account_type)
+            .data_offset(8))
+    }
+
+    /// Sets where the typed value starts in the account data, for headers
+    /// such as Anchor's discriminator. [`with_idl`](Self::with_idl) sets it
+    /// to 8.
+    pub fn data_offset(mut self, offset: usize) -> Self {
+        self.data_offset = offset;
+        self
+    }
+
+    /// Overwrites `bytes.len()` bytes of the data starting at `offset`.
+    ///
+    /// The patch must fit inside the existing data. The editor also
+    /// implements [`RawData`] for writing single integers and pubkeys.
+    pub fn patch_bytes(
+        &mut self,
+        offset: usize,
+        bytes: &[u8],
+    ) -> Result<&mut Self, AccountGenError> {
+        self.account.data.write_bytes(offset, bytes)?;
+        Ok(self)
+    }
+
+    /// Replaces the field at `path` with `value`.
+    ///
+    /// The path syntax is that of [`FixtureSchema::field_range`], and the
+    /// value is given in the shape [`FixtureSchema::encode`] accepts. Fields
+    /// whose encoded length changes, such as strings and vectors, shift
+    /// the data after them and resize the account.
+    pub fn patch_field<T: Serialize>(
+        &mut self,
+        path: &str,
+        value: T,
+    ) -> Result<&mut Self, AccountGenError> {
+        let value = serde_json::to_value(value)
+            .map_err(|e| AccountGenError::InvalidDataFormat(e.to_string()))?;
+        let (schema, type_name) = self.layout()?;
+        let data = self.typed_data()?;
+        let (range, field_type) = schema.field_range(type_name, data, path)?;
+        let encoded = schema.encode(&field_type, &value)?;
+
+        let start = self.data_offset + range.start;
+        let end = self.data_offset + range.end;
+        check_data_len(self.account.data.len() - (end - start) + encoded.len())?;
+        self.account.data.splice(start..end, encoded);
+        Ok(self)
+    }
+
+    /// Decodes the field at `path`.
+    pub fn field(&self, path: &str) -> Result<Value, AccountGenError> {
+        let (schema, type_name) = self.layout()?;
+        let data = self.typed_data()?;
+        let (range, field_type) = schema.field_range(type_name, data, path)?;
+        schema.decode(&field_type, &data[range])
+    }
+
+    fn layout(&self) -> Result<(&FixtureSchema, &str), AccountGenError> {
+        self.layout
+            .as_ref()
+            .map(|(schema, type_name)| (schema, type_name.as_str()))
+            .ok_or_else(|| {
+                AccountGenError::InvalidDataFormat(
+                    "editing fields needs a layout; see AccountEditor::with_schema".to_string(),
+                )
+            })
+    }
+
+    fn typed_data(&self) -> Result<&[u8], AccountGenError> {
+        self.account.data.get(self.data_offset..).ok_or_else(|| {
+            AccountGenError::InvalidDataFormat(format!(
+                "data offset {} is past the end of {} bytes of data",
+                self.data_offset,
+                self.account.data.len()
+            ))
+        })
+    }
+}
+
+/// Reads and writes fields of the account data by offset, ignoring any
+/// attached layout and data offset.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::serialization::raw::RawData;
+/// use solana_accountgen::{AccountBuilder, AccountEditor};
+///
+/// let mut account = AccountBuilder::new().data_raw(vec![0; 16]).build();
+/// let mut editor = AccountEditor::new(&mut account);
+/// editor.write_u64_le(8, 500).unwrap();
+/// assert_eq!(editor.read_u64_le(8).unwrap(), 500);
+/// ```
+impl RawData for AccountEditor<'_> {
+    fn read_bytes(&self, offset: usize, len: usize) -> Result<&[u8], AccountGenError> {
+        self.account.data.read_bytes(offset, len)
+    }
+
+    fn write_bytes(&mut self, offset: usize, bytes: &[u8]) -> Result<(), AccountGenError> {
+        self.account.data.write_bytes(offset, bytes)
+    }
+}
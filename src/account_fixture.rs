@@ -0,0 +1,82 @@
+use crate::extensions::anchor::{create_anchor_account, get_account_discriminator};
+use crate::serialization::borsh::deserialize_account_data;
+use crate::{AccountBuilder, AccountGenError};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+/// Conversions between a Borsh type and the accounts that hold it.
+///
+/// Derive it with `#[derive(AccountFixture)]` instead of writing a builder
+/// chain for every fixture type. The Anchor account name defaults to the
+/// type name and can be changed with
+/// `#[account_fixture(anchor_name = "...")]`.
+///
+/// # Example
+///
+/// ```
+/// use borsh::{BorshDeserialize, BorshSerialize};
+/// use solana_accountgen::AccountFixture;
+/// use solana_pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize, BorshDeserialize, AccountFixture, Debug, PartialEq)]
+/// struct Counter {
+///     count: u64,
+/// }
+///
+/// let program_id = Pubkey::new_unique();
+/// let account = Counter { count: 7 }
+///     .into_anchor_account(program_id, 1_000_000)
+///     .unwrap();
+///
+/// assert_eq!(account.owner, program_id);
+/// assert_eq!(Counter::from_anchor_account(&account).unwrap(), Counter { count: 7 });
+/// ```
+pub trait AccountFixture: BorshSerialize + BorshDeserialize + Sized {
+    /// The account type name used for the Anchor discriminator.
+    const ANCHOR_NAME: &'static str;
+
+    /// Returns the Anchor discriminator of this type.
+    fn anchor_discriminator() -> [u8; 8] {
+        get_account_discriminator(Self::ANCHOR_NAME)
+    }
+
+    /// Creates an account holding the Borsh encoding of `self`.
+    fn into_account(self, owner: Pubkey, lamports: u64) -> Result<Account, AccountGenError> {
+        AccountBuilder::new()
+            .balance(lamports)
+            .owner(owner)
+            .data(self)?
+            .try_build()
+    }
+
+    /// Creates an Anchor account holding `self` behind its discriminator.
+    fn into_anchor_account(
+        self,
+        program_id: Pubkey,
+        lamports: u64,
+    ) -> Result<Account, AccountGenError> {
+        create_anchor_account(Self::ANCHOR_NAME, program_id, self, lamports)
+    }
+
+    /// Decodes an account created by [`into_account`](Self::into_account).
+    fn from_account(account: &Account) -> Result<Self, AccountGenError> {
+        deserialize_account_data(account)
+    }
+
+    /// Decodes an Anchor account, checking its discriminator first.
+    ///
+    /// Bytes after the encoded value are ignored, since Anchor accounts are
+    /// often allocated with spare space.
+    fn from_anchor_account(account: &Account) -> Result<Self, AccountGenError> {
+        let discriminator = Self::anchor_discriminator();
+        if account.data.get(..8) != Some(&discriminator[..]) {
+            return Err(AccountGenError::InvalidAnchorDiscriminator(format!(
+                "account data does not start with the {} discriminator",
+                Self::ANCHOR_NAME
+            )));
+        }
+        let mut data = &account.data[8..];
+        Self::deserialize(&mut data).map_err(AccountGenError::DeserializationError)
+    }
+}
@@ -1,29 +1,81 @@
+use crate::anonymize::AnonymizePolicy;
+use crate::references::PubkeyExtractor;
+use crate::rpc::RpcFilter;
+use crate::seal::SealedAccounts;
+use crate::serialization::convert::RpcAccount;
+use crate::stats::{AccountMapStats, DiscriminatorGroups};
+use crate::updates::{AccountUpdate, AccountUpdates};
+use serde::de::Error;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use solana_account::Account;
 use solana_pubkey::Pubkey;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::mpsc::Sender;
 
 /// A collection of accounts indexed by their pubkeys.
 ///
 /// This struct provides a convenient way to manage multiple accounts
 /// and their associated pubkeys.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default)]
 pub struct AccountMap {
     accounts: HashMap<Pubkey, Account>,
+    subscribers: Vec<Sender<AccountUpdate>>,
+    write_version: u64,
+}
+
+/// Clones the accounts only; subscribers stay with the original map.
+impl Clone for AccountMap {
+    fn clone(&self) -> Self {
+        Self {
+            accounts: self.accounts.clone(),
+            subscribers: Vec::new(),
+            write_version: 0,
+        }
+    }
 }
 
 impl AccountMap {
     /// Creates a new empty `AccountMap`.
     pub fn new() -> Self {
-        Self {
-            accounts: HashMap::new(),
-        }
+        Self::default()
     }
 
     /// Adds an account with its pubkey to the map.
     pub fn set_account(&mut self, pubkey: Pubkey, account: Account) {
+        if !self.subscribers.is_empty() {
+            self.notify(pubkey, Some(account.clone()));
+        }
         self.accounts.insert(pubkey, account);
     }
 
+    /// Subscribes to changes made to the map from now on.
+    ///
+    /// Every [`set_account`](Self::set_account) and
+    /// [`remove_account`](Self::remove_account), including those made by
+    /// [`add_with_builder`](Self::add_with_builder) and
+    /// [`merge`](Self::merge), sends an [`AccountUpdate`]. Changes made
+    /// through `get_account_mut` or `iter_mut` are not observed. See the
+    /// [`updates`](crate::updates) module for an example.
+    pub fn subscribe(&mut self) -> AccountUpdates {
+        let (sender, updates) = AccountUpdates::new();
+        self.subscribers.push(sender);
+        updates
+    }
+
+    fn notify(&mut self, pubkey: Pubkey, account: Option<Account>) {
+        self.write_version += 1;
+        let update = AccountUpdate {
+            pubkey,
+            account,
+            write_version: self.write_version,
+        };
+        // Forget subscribers whose receiver has been dropped
+        self.subscribers
+            .retain(|subscriber| subscriber.send(update.clone()).is_ok());
+    }
+
     /// Adds an account created with `AccountBuilder` to the map.
     pub fn add_with_builder(
         &mut self,
@@ -31,7 +83,8 @@ impl AccountMap {
         builder: crate::AccountBuilder,
     ) -> Result<&mut Self, crate::AccountGenError> {
         let account = builder.try_build()?;
-        self.accounts.insert(pubkey, account);
+        crate::tracking::record(pubkey);
+        self.set_account(pubkey, account);
         Ok(self)
     }
 
@@ -47,7 +100,11 @@ impl AccountMap {
 
     /// Removes an account from the map.
     pub fn remove_account(&mut self, pubkey: &Pubkey) -> Option<Account> {
-        self.accounts.remove(pubkey)
+        let removed = self.accounts.remove(pubkey);
+        if removed.is_some() && !self.subscribers.is_empty() {
+            self.notify(*pubkey, None);
+        }
+        removed
     }
 
     /// Returns an iterator over all (pubkey, account) pairs.
@@ -55,6 +112,19 @@ impl AccountMap {
         self.accounts.iter()
     }
 
+    /// Returns an iterator over all (pubkey, account) pairs, with mutable accounts.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Pubkey, &mut Account)> {
+        self.accounts.iter_mut()
+    }
+
+    /// Consumes the map and returns its (pubkey, account) pairs, sorted by
+    /// pubkey.
+    pub fn into_vec(self) -> Vec<(Pubkey, Account)> {
+        let mut accounts: Vec<_> = self.accounts.into_iter().collect();
+        accounts.sort_by_key(|(pubkey, _)| *pubkey);
+        accounts
+    }
+
     /// Returns the number of accounts in the map.
     pub fn len(&self) -> usize {
         self.accounts.len()
@@ -155,7 +225,308 @@ impl AccountMap {
             .map(|(pubkey, account)| (*pubkey, account.clone()))
             .collect::<HashMap<_, _>>();
 
-        Self { accounts }
+        Self {
+            accounts,
+            ..Self::default()
+        }
+    }
+
+    /// Removes every account that cannot be reached from `roots`, returning
+    /// the number of accounts removed.
+    ///
+    /// An account is reachable if it is a root, the owner of a reachable
+    /// account, or one of the pubkeys `references` returns for a reachable
+    /// account. This shrinks a fixture set cloned from a cluster to what a
+    /// test needs, with the roots being the account keys of the
+    /// transactions it sends.
+    /// [`PubkeyExtractor::references`](crate::references::PubkeyExtractor::references)
+    /// finds the references of common account types.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::{AccountBuilder, AccountMap};
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// let program_id = Pubkey::new_unique();
+    /// let (pool, mint, unrelated) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    ///
+    /// let mut map = AccountMap::new();
+    /// let pool_data = mint.to_bytes().to_vec();
+    /// map.add_with_builder(pool, AccountBuilder::new().owner(program_id).data_raw(pool_data))
+    ///     .unwrap();
+    /// map.add_with_builder(mint, AccountBuilder::new()).unwrap();
+    /// map.add_with_builder(unrelated, AccountBuilder::new()).unwrap();
+    ///
+    /// // Pools store their mint in the first 32 bytes
+    /// let removed = map.prune_unreachable([pool], |account| {
+    ///     account.data.get(..32).map(|bytes| Pubkey::try_from(bytes).unwrap())
+    /// });
+    /// assert_eq!(removed, 1);
+    /// assert!(map.get_account(&mint).is_some());
+    /// assert!(map.get_account(&unrelated).is_none());
+    /// ```
+    pub fn prune_unreachable<R, F, I>(&mut self, roots: R, mut references: F) -> usize
+    where
+        R: IntoIterator<Item = Pubkey>,
+        F: FnMut(&Account) -> I,
+        I: IntoIterator<Item = Pubkey>,
+    {
+        let mut reachable = HashSet::new();
+        let mut pending: Vec<Pubkey> = roots.into_iter().collect();
+        while let Some(pubkey) = pending.pop() {
+            if !reachable.insert(pubkey) {
+                continue;
+            }
+            if let Some(account) = self.accounts.get(&pubkey) {
+                pending.push(account.owner);
+                pending.extend(references(account));
+            }
+        }
+
+        let unreachable: Vec<Pubkey> = self
+            .accounts
+            .keys()
+            .filter(|pubkey| !reachable.contains(*pubkey))
+            .copied()
+            .collect();
+        for pubkey in &unreachable {
+            self.remove_account(pubkey);
+        }
+        unreachable.len()
+    }
+
+    /// Replaces anonymizable addresses with deterministic test addresses,
+    /// returning the mapping from each original address to its
+    /// replacement.
+    ///
+    /// Accounts are moved to their new addresses, and references to the
+    /// replaced addresses in owners and account data are rewritten to
+    /// match. See the [`anonymize`](crate::anonymize) module for which
+    /// addresses a policy replaces.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::anonymize::AnonymizePolicy;
+    /// use solana_accountgen::extensions::token::create_token_account;
+    /// use solana_accountgen::keys::{test_keypair, test_pubkey};
+    /// use solana_accountgen::well_known::TOKEN_PROGRAM;
+    /// use solana_accountgen::AccountMap;
+    /// use solana_pubkey::Pubkey;
+    /// use solana_signer::Signer;
+    ///
+    /// let (wallet, mint) = (test_pubkey("a real wallet"), Pubkey::new_unique());
+    /// let token_account = Pubkey::new_unique();
+    /// let mut map = AccountMap::new();
+    /// map.set_account(token_account, create_token_account(&mint, &wallet, 5, &TOKEN_PROGRAM).unwrap());
+    ///
+    /// let mapping = map.anonymize(&AnonymizePolicy::new().keep([mint]));
+    /// let replacement = mapping[&wallet];
+    /// assert_eq!(replacement, test_keypair("anon-0").pubkey());
+    /// assert_eq!(&map.get_account(&token_account).unwrap().data[32..64], replacement.as_ref());
+    /// ```
+    pub fn anonymize(&mut self, policy: &AnonymizePolicy) -> HashMap<Pubkey, Pubkey> {
+        let mapping = policy.mapping(self);
+        self.remap_pubkeys_with(&mapping, policy.pubkey_extractor());
+        mapping
+    }
+
+    /// Replaces pubkeys throughout the map: account addresses, owners, and
+    /// the pubkeys embedded in account data.
+    ///
+    /// This retargets fixtures captured against one program ID to a
+    /// locally deployed one. Embedded pubkeys are found with a default
+    /// [`PubkeyExtractor`], which also reports any occurrence of a key in
+    /// `mapping`; use [`remap_pubkeys_with`](Self::remap_pubkeys_with) to
+    /// restrict rewriting to the fields of a schema or IDL. PDAs are not
+    /// re-derived, so map them explicitly if their seeds or program change.
+    /// An account moved onto an address already in the map replaces it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::{AccountBuilder, AccountMap};
+    /// use solana_pubkey::Pubkey;
+    /// use std::collections::HashMap;
+    ///
+    /// let (mainnet_program, local_program) = (Pubkey::new_unique(), Pubkey::new_unique());
+    /// let config = Pubkey::new_unique();
+    /// let mut map = AccountMap::new();
+    /// map.add_with_builder(
+    ///     config,
+    ///     AccountBuilder::new()
+    ///         .owner(mainnet_program)
+    ///         .data_raw(mainnet_program.to_bytes().to_vec()),
+    /// )
+    /// .unwrap();
+    ///
+    /// map.remap_pubkeys(&HashMap::from([(mainnet_program, local_program)]));
+    /// let account = map.get_account(&config).unwrap();
+    /// assert_eq!(account.owner, local_program);
+    /// assert_eq!(account.data, local_program.to_bytes());
+    /// ```
+    pub fn remap_pubkeys(&mut self, mapping: &HashMap<Pubkey, Pubkey>) {
+        self.remap_pubkeys_with(mapping, &PubkeyExtractor::new());
+    }
+
+    /// Like [`remap_pubkeys`](Self::remap_pubkeys), finding embedded
+    /// pubkeys with `extractor`.
+    ///
+    /// Accounts whose owner has a schema or IDL registered only have the
+    /// pubkey fields of their type rewritten, so other bytes that happen
+    /// to match a mapped key are left alone. The layout is looked up by
+    /// the owner before remapping.
+    pub fn remap_pubkeys_with(
+        &mut self,
+        mapping: &HashMap<Pubkey, Pubkey>,
+        extractor: &PubkeyExtractor,
+    ) {
+        if mapping.is_empty() {
+            return;
+        }
+        let extractor = extractor.clone().known_keys(mapping.keys().copied());
+
+        let mut changed = Vec::new();
+        for (pubkey, account) in &self.accounts {
+            let mut account = account.clone();
+            let mut modified = false;
+            for (offset, embedded) in extractor.extract(&account) {
+                if let Some(replacement) = mapping.get(&embedded) {
+                    account.data[offset..offset + 32].copy_from_slice(replacement.as_ref());
+                    modified = true;
+                }
+            }
+            if let Some(owner) = mapping.get(&account.owner) {
+                account.owner = *owner;
+                modified = true;
+            }
+            let new_pubkey = mapping.get(pubkey).copied();
+            if modified || new_pubkey.is_some() {
+                changed.push((*pubkey, new_pubkey.unwrap_or(*pubkey), account));
+            }
+        }
+
+        // Remove first so an account moving onto another's old address
+        // is not removed afterwards
+        for (pubkey, new_pubkey, _) in &changed {
+            if pubkey != new_pubkey {
+                self.remove_account(pubkey);
+            }
+        }
+        for (_, new_pubkey, account) in changed {
+            self.set_account(new_pubkey, account);
+        }
+    }
+
+    /// Returns the accounts owned by `program_id` that satisfy every filter,
+    /// as `getProgramAccounts` would.
+    ///
+    /// This checks that fixtures would be found by the filters off-chain
+    /// code sends, without starting a server.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::{AccountBuilder, AccountMap};
+    /// use solana_accountgen::extensions::anchor::get_account_discriminator;
+    /// use solana_accountgen::rpc::RpcFilter;
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// let program_id = Pubkey::new_unique();
+    /// let mut data = get_account_discriminator("Pool").to_vec();
+    /// data.extend_from_slice(&[0; 32]);
+    ///
+    /// let pool = Pubkey::new_unique();
+    /// let mut map = AccountMap::new();
+    /// map.add_with_builder(pool, AccountBuilder::new().owner(program_id).data_raw(data))
+    ///     .unwrap();
+    ///
+    /// let matched = map.get_program_accounts(
+    ///     &program_id,
+    ///     &[RpcFilter::DataSize(40), RpcFilter::memcmp(0, get_account_discriminator("Pool"))],
+    /// );
+    /// assert!(matched.get_account(&pool).is_some());
+    /// ```
+    pub fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilter]) -> Self {
+        self.filter(|_, account| {
+            account.owner == *program_id
+                && filters.iter().all(|filter| filter.matches(&account.data))
+        })
+    }
+
+    /// Returns counts, lamports and data sizes per owner program.
+    ///
+    /// The result's `Display` output is a readable report.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::{AccountBuilder, AccountMap};
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// let program_id = Pubkey::new_unique();
+    /// let mut map = AccountMap::new();
+    /// for _ in 0..3 {
+    ///     map.add_with_builder(
+    ///         Pubkey::new_unique(),
+    ///         AccountBuilder::new().balance(10).owner(program_id).data_raw(vec![0; 100]),
+    ///     )
+    ///     .unwrap();
+    /// }
+    ///
+    /// let stats = map.stats();
+    /// assert_eq!(stats.owners[&program_id].accounts, 3);
+    /// assert_eq!(stats.total.lamports, 30);
+    /// println!("{}", stats);
+    /// ```
+    pub fn stats(&self) -> AccountMapStats {
+        AccountMapStats::collect(self)
+    }
+
+    /// Groups accounts by the first 8 bytes of their data, the account
+    /// type of Anchor programs.
+    ///
+    /// Name the groups with a
+    /// [`DiscriminatorRegistry`](crate::stats::DiscriminatorRegistry) to
+    /// count accounts per type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountMap;
+    /// use solana_accountgen::extensions::anchor::create_anchor_account;
+    /// use solana_accountgen::stats::DiscriminatorRegistry;
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// let program_id = Pubkey::new_unique();
+    /// let mut map = AccountMap::new();
+    /// for i in 0..3u64 {
+    ///     let account = create_anchor_account("Pool", program_id, i, 1_000_000).unwrap();
+    ///     map.set_account(Pubkey::new_unique(), account);
+    /// }
+    /// map.set_account(
+    ///     Pubkey::new_unique(),
+    ///     create_anchor_account("Config", program_id, 0u8, 1_000_000).unwrap(),
+    /// );
+    ///
+    /// let groups = map
+    ///     .group_by_discriminator()
+    ///     .named(&DiscriminatorRegistry::new().register("Pool"));
+    /// assert_eq!(groups.by_type()["Pool"], 3);
+    /// assert_eq!(groups.unnamed(), 1);
+    /// println!("{}", groups);
+    /// ```
+    pub fn group_by_discriminator(&self) -> DiscriminatorGroups {
+        DiscriminatorGroups::collect(self)
+    }
+
+    /// Records a hash of every account, to check later that accounts a
+    /// test did not expect to be written are unchanged.
+    ///
+    /// See the [`seal`](crate::seal) module for an example.
+    pub fn seal(&self) -> SealedAccounts {
+        SealedAccounts::collect(self)
     }
 }
 
@@ -168,6 +539,88 @@ impl IntoIterator for AccountMap {
     }
 }
 
+/// Creates an AccountMap holding the accounts of a `HashMap`.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountMap;
+/// use solana_account::Account;
+/// use solana_pubkey::Pubkey;
+/// use std::collections::HashMap;
+///
+/// let pubkey = Pubkey::new_unique();
+/// let accounts = HashMap::from([(pubkey, Account::default())]);
+///
+/// let account_map = AccountMap::from(accounts);
+/// assert!(account_map.get_account(&pubkey).is_some());
+///
+/// let accounts: HashMap<Pubkey, Account> = account_map.into();
+/// assert_eq!(accounts.len(), 1);
+/// ```
+impl<S> From<HashMap<Pubkey, Account, S>> for AccountMap {
+    fn from(accounts: HashMap<Pubkey, Account, S>) -> Self {
+        accounts.into_iter().collect()
+    }
+}
+
+/// Returns the accounts of an AccountMap; subscribers are dropped.
+impl From<AccountMap> for HashMap<Pubkey, Account> {
+    fn from(account_map: AccountMap) -> Self {
+        account_map.accounts
+    }
+}
+
+/// Serializes the accounts as a JSON object keyed by pubkey, each account
+/// in the form of a JSON RPC `getAccountInfo` value with base64 data.
+/// Pubkeys are written in order, so the same map always serializes the
+/// same way.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, AccountMap};
+/// use solana_pubkey::Pubkey;
+///
+/// let pubkey = Pubkey::new_unique();
+/// let mut accounts = AccountMap::new();
+/// accounts
+///     .add_with_builder(pubkey, AccountBuilder::new().balance(1_000).data_raw(vec![1, 2, 3]))
+///     .unwrap();
+///
+/// let json = serde_json::to_value(&accounts).unwrap();
+/// assert_eq!(json[pubkey.to_string()]["data"][0], "AQID");
+///
+/// let parsed: AccountMap = serde_json::from_value(json).unwrap();
+/// assert_eq!(parsed.get_account(&pubkey), accounts.get_account(&pubkey));
+/// ```
+impl Serialize for AccountMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<_> = self.accounts.iter().collect();
+        entries.sort_by_key(|(pubkey, _)| **pubkey);
+
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+        for (pubkey, account) in entries {
+            map.serialize_entry(&pubkey.to_string(), &RpcAccount::new(account))?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for AccountMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = BTreeMap::<String, RpcAccount>::deserialize(deserializer)?;
+        entries
+            .into_iter()
+            .map(|(pubkey, account)| {
+                let pubkey = Pubkey::from_str(&pubkey)
+                    .map_err(|e| D::Error::custom(format!("invalid pubkey {pubkey}: {e}")))?;
+                Ok((pubkey, Account::from(account)))
+            })
+            .collect()
+    }
+}
+
 impl FromIterator<(Pubkey, Account)> for AccountMap {
     fn from_iter<I: IntoIterator<Item = (Pubkey, Account)>>(iter: I) -> Self {
         let mut map = Self::new();
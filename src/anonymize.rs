@@ -0,0 +1,156 @@
+//! Scrubbing real addresses out of fixture sets.
+//!
+//! Fixtures cloned from mainnet carry the addresses of the wallets that
+//! happened to hold the cloned accounts. [`AccountMap::anonymize`] replaces
+//! them with deterministic test addresses and rewrites every reference to
+//! them, so such fixtures can be shared publicly.
+//!
+//! By default an address is replaced when it is on the ed25519 curve (a
+//! wallet rather than a PDA) and is not
+//!
+//! - a program: the owner of an account in the set, or an executable account,
+//! - a [well-known](crate::well_known) address,
+//! - the address of an account owned by a program other than the System
+//!   Program, such as a mint or a pool, or
+//! - listed with [`AnonymizePolicy::keep`].
+//!
+//! Addresses listed with [`AnonymizePolicy::rewrite`] are always replaced.
+//! Candidates are the set's addresses and the pubkeys the policy's
+//! [`PubkeyExtractor`] finds in account data. PDAs, including associated
+//! token accounts, keep their addresses and so no longer derive from the
+//! wallets they were derived from.
+//!
+//! The replaced addresses, in pubkey order, become
+//! [`test_keypair`]`("<prefix>-0")`, `"<prefix>-1"` and so on, so tests
+//! can sign as the anonymized wallets.
+
+use crate::AccountMap;
+use crate::keys::test_keypair;
+use crate::references::PubkeyExtractor;
+use crate::well_known;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Which addresses [`AccountMap::anonymize`] replaces, and with what.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::anonymize::AnonymizePolicy;
+/// use solana_accountgen::keys::test_pubkey;
+/// use solana_accountgen::{AccountBuilder, AccountMap};
+///
+/// let (treasury, whale) = (test_pubkey("treasury"), test_pubkey("whale"));
+/// let mut map = AccountMap::new();
+/// for wallet in [treasury, whale] {
+///     map.add_with_builder(wallet, AccountBuilder::new().balance(1_000)).unwrap();
+/// }
+///
+/// let policy = AnonymizePolicy::new().label_prefix("user").keep([treasury]);
+/// let mapping = map.anonymize(&policy);
+/// assert_eq!(mapping.len(), 1);
+/// assert!(map.get_account(&treasury).is_some());
+/// assert!(map.get_account(&test_pubkey("user-0")).is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct AnonymizePolicy {
+    prefix: String,
+    keep: HashSet<Pubkey>,
+    rewrite: HashSet<Pubkey>,
+    extractor: PubkeyExtractor,
+}
+
+impl Default for AnonymizePolicy {
+    fn default() -> Self {
+        Self {
+            prefix: "anon".to_string(),
+            keep: HashSet::new(),
+            rewrite: HashSet::new(),
+            extractor: PubkeyExtractor::new(),
+        }
+    }
+}
+
+impl AnonymizePolicy {
+    /// Creates the default policy, which labels replacements `anon-<n>`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the prefix of the labels replacement keypairs are derived from.
+    pub fn label_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Never replaces these addresses.
+    pub fn keep<I: IntoIterator<Item = Pubkey>>(mut self, pubkeys: I) -> Self {
+        self.keep.extend(pubkeys);
+        self
+    }
+
+    /// Always replaces these addresses, even PDAs and programs.
+    pub fn rewrite<I: IntoIterator<Item = Pubkey>>(mut self, pubkeys: I) -> Self {
+        self.rewrite.extend(pubkeys);
+        self
+    }
+
+    /// Uses `extractor`, for example one with a program's IDL registered,
+    /// to find addresses in account data.
+    pub fn extractor(mut self, extractor: PubkeyExtractor) -> Self {
+        self.extractor = extractor;
+        self
+    }
+
+    pub(crate) fn pubkey_extractor(&self) -> &PubkeyExtractor {
+        &self.extractor
+    }
+
+    /// Returns the replacement for every address in `accounts` this
+    /// policy would replace.
+    pub fn mapping(&self, accounts: &AccountMap) -> HashMap<Pubkey, Pubkey> {
+        if accounts.is_empty() {
+            return HashMap::new();
+        }
+        // Knowing the set's own addresses keeps the scan from reporting
+        // random-looking data, which must not be rewritten
+        let extractor = self
+            .extractor
+            .clone()
+            .known_keys(accounts.iter().map(|(pubkey, _)| *pubkey));
+
+        let mut programs = HashSet::new();
+        let mut candidates = BTreeSet::new();
+        for (pubkey, account) in accounts.iter() {
+            programs.insert(account.owner);
+            if account.executable {
+                programs.insert(*pubkey);
+            }
+            if account.owner == well_known::SYSTEM_PROGRAM {
+                candidates.insert(*pubkey);
+            }
+            candidates.extend(extractor.references(account));
+        }
+        candidates.extend(self.rewrite.iter().copied());
+
+        candidates
+            .into_iter()
+            .filter(|pubkey| {
+                self.rewrite.contains(pubkey)
+                    || (pubkey.is_on_curve()
+                        && !self.keep.contains(pubkey)
+                        && !programs.contains(pubkey)
+                        && well_known::name_for(pubkey).is_none()
+                        && accounts
+                            .get_account(pubkey)
+                            .is_none_or(|account| account.owner == well_known::SYSTEM_PROGRAM))
+            })
+            .enumerate()
+            .map(|(index, pubkey)| {
+                let label = format!("{}-{}", self.prefix, index);
+                (pubkey, test_keypair(&label).pubkey())
+            })
+            .collect()
+    }
+}
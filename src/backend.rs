@@ -0,0 +1,413 @@
+//! One interface over the environments tests run programs in.
+//!
+//! [`TestBackend`] is implemented for `ProgramTestContext`, which runs
+//! transactions in a full bank, for the in-process [`LocalHarness`], and,
+//! with the `litesvm` feature, for `LiteSVM`.
+//! Suites written against the trait, together with the helpers below, can
+//! move between backends without touching their fixtures:
+//!
+//! - [`load_accounts`] writes an [`AccountMap`], such as a
+//!   [`Scenario`](crate::extensions::scenarios::Scenario)'s accounts, into a
+//!   backend.
+//! - [`snapshot`] and [`diff_since`] read accounts back and report what a
+//!   transaction changed.
+//! - [`assert_accounts`] compares a backend's accounts to an expected set,
+//!   like [`assert_accounts_match!`](crate::assert_accounts_match).
+//! - [`verify_unchanged`] checks that accounts sealed with
+//!   [`AccountMap::seal`] were not written.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::backend::{self, TestBackend};
+//! use solana_accountgen::harness::LocalHarness;
+//! use solana_accountgen::{AccountBuilder, AccountMap};
+//! use solana_account_info::AccountInfo;
+//! use solana_instruction::{AccountMeta, Instruction};
+//! use solana_keypair::Keypair;
+//! use solana_program_error::ProgramResult;
+//! use solana_pubkey::Pubkey;
+//! use solana_signer::Signer;
+//! use solana_transaction::Transaction;
+//!
+//! fn process_instruction(_: &Pubkey, accounts: &[AccountInfo], _: &[u8]) -> ProgramResult {
+//!     accounts[0].try_borrow_mut_data()?[0] += 1;
+//!     Ok(())
+//! }
+//!
+//! // Runs unchanged against a `ProgramTestContext` with the program loaded
+//! async fn increment<B: TestBackend>(backend: &mut B, program_id: Pubkey) {
+//!     let (payer, counter) = (Keypair::new(), Pubkey::new_unique());
+//!     let mut accounts = AccountMap::new();
+//!     accounts.add_with_builder(payer.pubkey(), AccountBuilder::new().balance(1_000_000_000)).unwrap();
+//!     accounts.add_with_builder(counter, AccountBuilder::new().owner(program_id).data_raw(vec![0])).unwrap();
+//!     backend::load_accounts(backend, &accounts).unwrap();
+//!
+//!     let ix = Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(counter, false)]);
+//!     let blockhash = backend.latest_blockhash().await.unwrap();
+//!     let transaction =
+//!         Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+//!     backend.process_transaction(transaction).await.unwrap();
+//!
+//!     let diff = backend::diff_since(backend, &accounts).await.unwrap();
+//!     assert_eq!(diff.changed.len(), 1);
+//! }
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let program_id = Pubkey::new_unique();
+//! increment(&mut LocalHarness::new(program_id, process_instruction), program_id).await;
+//! # });
+//! ```
+
+use crate::diff::{AccountMapDiff, diff_maps};
+use crate::extensions::sysvars::SysvarBundle;
+use crate::harness::LocalHarness;
+use crate::seal::SealedAccounts;
+use crate::{AccountGenError, AccountMap, tracking};
+use solana_account::Account;
+use solana_clock::Clock;
+use solana_epoch_schedule::EpochSchedule;
+use solana_hash::Hash;
+use solana_instruction::error::InstructionError;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::sysvar;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+/// An environment that holds accounts and processes transactions.
+///
+/// Reads and transactions are async because a bank answers them from
+/// another task; writes and warps take effect immediately on every
+/// supported backend.
+// Backends are driven from one test task, so the futures need not be `Send`
+#[allow(async_fn_in_trait)]
+pub trait TestBackend {
+    /// Creates or replaces an account.
+    fn set_account(&mut self, pubkey: Pubkey, account: Account) -> Result<(), AccountGenError>;
+
+    /// Returns an account, or `None` if it does not exist.
+    async fn get_account(&mut self, pubkey: Pubkey) -> Result<Option<Account>, AccountGenError>;
+
+    /// Returns a blockhash transactions can be signed with.
+    async fn latest_blockhash(&mut self) -> Result<Hash, AccountGenError>;
+
+    /// Processes a signed transaction, committing its changes if it
+    /// succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::TransactionFailed`] if the transaction
+    /// failed, and [`AccountGenError::BackendError`] if it could not be
+    /// submitted.
+    async fn process_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<(), AccountGenError>;
+
+    /// Moves the Clock forward to `slot`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::BackendError`] if `slot` is not after the
+    /// current slot.
+    fn warp(&mut self, slot: u64) -> Result<(), AccountGenError>;
+}
+
+/// Writes every account in `accounts` into `backend`.
+///
+/// The addresses are recorded for [`tracking`], as
+/// [`ProgramTestExt::add_account_map`](crate::extensions::program_test::ProgramTestExt::add_account_map)
+/// records them.
+pub fn load_accounts<B: TestBackend>(
+    backend: &mut B,
+    accounts: &AccountMap,
+) -> Result<(), AccountGenError> {
+    for (pubkey, account) in accounts.iter() {
+        tracking::record(*pubkey);
+        backend.set_account(*pubkey, account.clone())?;
+    }
+    Ok(())
+}
+
+/// Reads the given accounts from `backend`, leaving out those that do not
+/// exist.
+pub async fn snapshot<B, I>(backend: &mut B, pubkeys: I) -> Result<AccountMap, AccountGenError>
+where
+    B: TestBackend,
+    I: IntoIterator<Item = Pubkey>,
+{
+    let mut accounts = AccountMap::new();
+    for pubkey in pubkeys {
+        if let Some(account) = backend.get_account(pubkey).await? {
+            accounts.set_account(pubkey, account);
+        }
+    }
+    Ok(accounts)
+}
+
+/// Compares `before` to the current state of the same accounts in
+/// `backend`.
+///
+/// Accounts that no longer exist are reported as removed. Accounts created
+/// at other addresses are not reported.
+pub async fn diff_since<B: TestBackend>(
+    backend: &mut B,
+    before: &AccountMap,
+) -> Result<AccountMapDiff, AccountGenError> {
+    let after = snapshot(backend, before.iter().map(|(pubkey, _)| *pubkey)).await?;
+    Ok(diff_maps(before, &after))
+}
+
+/// Checks that the accounts of `sealed` are unchanged in `backend`,
+/// skipping those in `except`, the accounts the test expected to write.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::ConstraintViolation`] listing every sealed
+/// account that changed or no longer exists, or the error of a failed read.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::backend;
+/// use solana_accountgen::harness::LocalHarness;
+/// use solana_accountgen::{AccountBuilder, AccountMap};
+/// use solana_account_info::AccountInfo;
+/// use solana_program_error::ProgramResult;
+/// use solana_pubkey::Pubkey;
+///
+/// fn process_instruction(_: &Pubkey, _: &[AccountInfo], _: &[u8]) -> ProgramResult {
+///     Ok(())
+/// }
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let program_id = Pubkey::new_unique();
+/// let mut harness = LocalHarness::new(program_id, process_instruction);
+/// let (vault, config) = (Pubkey::new_unique(), Pubkey::new_unique());
+/// let mut accounts = AccountMap::new();
+/// accounts.add_with_builder(vault, AccountBuilder::new().balance(1_000)).unwrap();
+/// accounts.add_with_builder(config, AccountBuilder::new().balance(1_000)).unwrap();
+/// backend::load_accounts(&mut harness, &accounts).unwrap();
+/// let sealed = accounts.seal();
+///
+/// harness.set_account(vault, AccountBuilder::new().balance(2_000).build());
+/// backend::verify_unchanged(&mut harness, &sealed, &[vault]).await.unwrap();
+/// assert!(backend::verify_unchanged(&mut harness, &sealed, &[]).await.is_err());
+/// # });
+/// ```
+pub async fn verify_unchanged<B: TestBackend>(
+    backend: &mut B,
+    sealed: &SealedAccounts,
+    except: &[Pubkey],
+) -> Result<(), AccountGenError> {
+    let pubkeys = sealed.pubkeys().filter(|pubkey| !except.contains(pubkey));
+    let current = snapshot(backend, pubkeys).await?;
+    sealed.verify(&current, except).into_result()
+}
+
+/// Asserts that the accounts of `expected` exist in `backend` with the
+/// expected contents.
+///
+/// # Panics
+///
+/// Panics, reporting every missing and changed account, if they do not
+/// match or cannot be read.
+pub async fn assert_accounts<B: TestBackend>(backend: &mut B, expected: &AccountMap) {
+    let diff = diff_since(backend, expected)
+        .await
+        .unwrap_or_else(|e| panic!("failed to read accounts: {}", e));
+    if !diff.is_empty() {
+        panic!(
+            "assertion failed: accounts do not match ({} missing, {} changed)\n\
+             (- missing, ~ expected -> actual)\n{}",
+            diff.removed.len(),
+            diff.changed.len(),
+            diff
+        );
+    }
+}
+
+fn banks_error(error: BanksClientError) -> AccountGenError {
+    match error {
+        BanksClientError::TransactionError(error)
+        | BanksClientError::SimulationError { err: error, .. } => {
+            AccountGenError::TransactionFailed(error)
+        }
+        error => AccountGenError::BackendError(error.to_string()),
+    }
+}
+
+impl TestBackend for ProgramTestContext {
+    fn set_account(&mut self, pubkey: Pubkey, account: Account) -> Result<(), AccountGenError> {
+        ProgramTestContext::set_account(self, &pubkey, &account.into());
+        Ok(())
+    }
+
+    async fn get_account(&mut self, pubkey: Pubkey) -> Result<Option<Account>, AccountGenError> {
+        self.banks_client
+            .get_account(pubkey)
+            .await
+            .map_err(banks_error)
+    }
+
+    async fn latest_blockhash(&mut self) -> Result<Hash, AccountGenError> {
+        self.banks_client
+            .get_latest_blockhash()
+            .await
+            .map_err(banks_error)
+    }
+
+    async fn process_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<(), AccountGenError> {
+        self.banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(banks_error)
+    }
+
+    fn warp(&mut self, slot: u64) -> Result<(), AccountGenError> {
+        self.warp_to_slot(slot)
+            .map_err(|e| AccountGenError::BackendError(e.to_string()))
+    }
+}
+
+/// The harness neither checks signatures nor charges fees, so any
+/// blockhash will do. Every instruction must target the harness's program,
+/// and a failing instruction rolls back the whole transaction. Warping
+/// writes the Clock and EpochSchedule sysvar accounts.
+impl TestBackend for LocalHarness {
+    fn set_account(&mut self, pubkey: Pubkey, account: Account) -> Result<(), AccountGenError> {
+        LocalHarness::set_account(self, pubkey, account);
+        Ok(())
+    }
+
+    async fn get_account(&mut self, pubkey: Pubkey) -> Result<Option<Account>, AccountGenError> {
+        Ok(LocalHarness::get_account(self, &pubkey).cloned())
+    }
+
+    async fn latest_blockhash(&mut self) -> Result<Hash, AccountGenError> {
+        Ok(Hash::default())
+    }
+
+    async fn process_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<(), AccountGenError> {
+        let instructions = decompile(&transaction).map_err(AccountGenError::TransactionFailed)?;
+        let before = self.accounts().clone();
+        for (index, instruction) in instructions.iter().enumerate() {
+            if let Err(error) = self.process_instruction(instruction) {
+                *self.accounts_mut() = before;
+                let error = InstructionError::from(u64::from(error));
+                return Err(AccountGenError::TransactionFailed(
+                    TransactionError::InstructionError(index as u8, error),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn warp(&mut self, slot: u64) -> Result<(), AccountGenError> {
+        let accounts = self.accounts_mut();
+        let sysvar_data = |pubkey: Pubkey| {
+            accounts
+                .get_account(&pubkey)
+                .map(|account| account.data.clone())
+        };
+        if let Some(data) = sysvar_data(sysvar::clock::id()) {
+            let clock: Clock = bincode::deserialize(&data)
+                .map_err(|e| AccountGenError::InvalidDataFormat(e.to_string()))?;
+            if slot <= clock.slot {
+                return Err(AccountGenError::BackendError(format!(
+                    "cannot warp to slot {} from slot {}",
+                    slot, clock.slot
+                )));
+            }
+        }
+        let epoch_schedule = match sysvar_data(sysvar::epoch_schedule::id()) {
+            Some(data) => bincode::deserialize(&data)
+                .map_err(|e| AccountGenError::InvalidDataFormat(e.to_string()))?,
+            None => EpochSchedule::without_warmup(),
+        };
+        SysvarBundle::at_slot(slot, epoch_schedule).apply_to(accounts);
+        Ok(())
+    }
+}
+
+/// LiteSVM checks signatures and blockhashes and charges fees like a bank,
+/// but processes transactions synchronously. Warping only moves the Clock's
+/// slot, as `LiteSVM::warp_to_slot` does.
+#[cfg(feature = "litesvm")]
+impl TestBackend for litesvm::LiteSVM {
+    fn set_account(&mut self, pubkey: Pubkey, account: Account) -> Result<(), AccountGenError> {
+        litesvm::LiteSVM::set_account(self, pubkey, account)
+            .map_err(|e| AccountGenError::BackendError(e.to_string()))
+    }
+
+    async fn get_account(&mut self, pubkey: Pubkey) -> Result<Option<Account>, AccountGenError> {
+        Ok(litesvm::LiteSVM::get_account(self, &pubkey))
+    }
+
+    async fn latest_blockhash(&mut self) -> Result<Hash, AccountGenError> {
+        Ok(litesvm::LiteSVM::latest_blockhash(self))
+    }
+
+    async fn process_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<(), AccountGenError> {
+        self.send_transaction(transaction)
+            .map(|_| ())
+            .map_err(|failed| AccountGenError::TransactionFailed(failed.err))
+    }
+
+    fn warp(&mut self, slot: u64) -> Result<(), AccountGenError> {
+        let current = self.get_sysvar::<Clock>().slot;
+        if slot <= current {
+            return Err(AccountGenError::BackendError(format!(
+                "cannot warp to slot {} from slot {}",
+                slot, current
+            )));
+        }
+        self.warp_to_slot(slot);
+        Ok(())
+    }
+}
+
+/// Expands a transaction's compiled instructions.
+pub(crate) fn decompile(transaction: &Transaction) -> Result<Vec<Instruction>, TransactionError> {
+    let message = &transaction.message;
+    let key = |index: u8| {
+        message
+            .account_keys
+            .get(index as usize)
+            .copied()
+            .ok_or(TransactionError::SanitizeFailure)
+    };
+    message
+        .instructions
+        .iter()
+        .map(|compiled| {
+            let accounts = compiled
+                .accounts
+                .iter()
+                .map(|&index| {
+                    Ok(AccountMeta {
+                        pubkey: key(index)?,
+                        is_signer: message.is_signer(index as usize),
+                        is_writable: message.is_maybe_writable(index as usize, None),
+                    })
+                })
+                .collect::<Result<_, TransactionError>>()?;
+            Ok(Instruction {
+                program_id: key(compiled.program_id_index)?,
+                accounts,
+                data: compiled.data.clone(),
+            })
+        })
+        .collect()
+}
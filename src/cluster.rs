@@ -0,0 +1,334 @@
+//! Cluster context for account defaults.
+//!
+//! Some account fields only make sense relative to a cluster: the minimum
+//! rent-exempt balance depends on the cluster's `Rent` parameters, and the
+//! meaning of `rent_epoch` changed when rent collection was removed. A
+//! [`ClusterContext`] captures that state so [`AccountBuilder`] defaults
+//! match the cluster a test is meant to model, and sets the [`BuildMode`]
+//! of the builders it is attached to.
+//!
+//! [`AccountBuilder`]: crate::AccountBuilder
+
+use crate::{AccountGenError, AccountMap, BuildMode};
+use solana_account::Account;
+use solana_account_info::MAX_PERMITTED_DATA_INCREASE;
+use solana_epoch_schedule::EpochSchedule;
+use solana_pubkey::Pubkey;
+use solana_rent::{Rent, RentDue};
+
+/// The `rent_epoch` value the runtime assigns to rent-exempt accounts.
+pub const RENT_EXEMPT_RENT_EPOCH: u64 = u64::MAX;
+
+/// The largest account data the runtime allows, 10 MiB.
+pub const MAX_PERMITTED_DATA_LENGTH: usize = 10 * 1024 * 1024;
+
+/// The most a single instruction may grow an account's data, 10 KiB.
+pub const MAX_REALLOC_INCREASE: usize = MAX_PERMITTED_DATA_INCREASE;
+
+/// Slots in a year at the default 400ms slot time, as used by rent collection.
+const SLOTS_PER_YEAR: f64 = 78_892_314.984;
+
+/// The cluster release whose account semantics should be modelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum ClusterVersion {
+    /// Rent is still collected; accounts record the epoch rent is next due.
+    V1_17,
+    /// Rent-exempt accounts carry `rent_epoch == u64::MAX`.
+    V1_18,
+    /// Rent collection is gone; every account carries `rent_epoch == u64::MAX`.
+    #[default]
+    V2_0,
+}
+
+impl ClusterVersion {
+    /// Returns true if the runtime still collects rent in this version.
+    pub fn collects_rent(&self) -> bool {
+        *self < ClusterVersion::V2_0
+    }
+}
+
+/// A snapshot of the cluster state that account defaults are derived from.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountBuilder;
+/// use solana_accountgen::cluster::{ClusterContext, ClusterVersion, RENT_EXEMPT_RENT_EPOCH};
+///
+/// let context = ClusterContext::new(ClusterVersion::V2_0).at_slot(1_000_000);
+/// let account = AccountBuilder::new()
+///     .data_raw(vec![0; 64])
+///     .cluster(context.clone())
+///     .build();
+///
+/// assert_eq!(account.rent_epoch, RENT_EXEMPT_RENT_EPOCH);
+/// assert_eq!(account.lamports, context.rent.minimum_balance(64));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterContext {
+    /// The cluster release being modelled.
+    pub version: ClusterVersion,
+    /// The current slot.
+    pub slot: u64,
+    /// The epoch containing `slot`.
+    pub epoch: u64,
+    /// The cluster's rent parameters.
+    pub rent: Rent,
+    /// The cluster's epoch schedule.
+    pub epoch_schedule: EpochSchedule,
+    /// How strictly builders using this context validate accounts, unless
+    /// they set a mode themselves.
+    pub build_mode: BuildMode,
+}
+
+impl Default for ClusterContext {
+    fn default() -> Self {
+        Self::new(ClusterVersion::default())
+    }
+}
+
+impl ClusterContext {
+    /// Creates a context at slot 0 with default rent and epoch schedule.
+    pub fn new(version: ClusterVersion) -> Self {
+        Self {
+            version,
+            slot: 0,
+            epoch: 0,
+            rent: Rent::default(),
+            epoch_schedule: EpochSchedule::default(),
+            build_mode: BuildMode::default(),
+        }
+    }
+
+    /// Moves the context to `slot`, deriving the epoch from the schedule.
+    pub fn at_slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self.epoch = self.epoch_schedule.get_epoch(slot);
+        self
+    }
+
+    /// Replaces the rent parameters.
+    pub fn with_rent(mut self, rent: Rent) -> Self {
+        self.rent = rent;
+        self
+    }
+
+    /// Replaces the epoch schedule, re-deriving the current epoch.
+    pub fn with_epoch_schedule(mut self, epoch_schedule: EpochSchedule) -> Self {
+        self.epoch = epoch_schedule.get_epoch(self.slot);
+        self.epoch_schedule = epoch_schedule;
+        self
+    }
+
+    /// Sets the build mode of builders using this context.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::{AccountBuilder, BuildMode};
+    /// use solana_accountgen::cluster::ClusterContext;
+    ///
+    /// let strict = ClusterContext::default().with_build_mode(BuildMode::Strict);
+    /// let underfunded = AccountBuilder::new().balance(1).cluster(strict).try_build();
+    /// assert!(underfunded.is_err());
+    /// ```
+    pub fn with_build_mode(mut self, build_mode: BuildMode) -> Self {
+        self.build_mode = build_mode;
+        self
+    }
+
+    /// Returns the minimum balance for an account with `data_len` bytes of data.
+    pub fn minimum_balance(&self, data_len: usize) -> u64 {
+        self.rent.minimum_balance(data_len)
+    }
+
+    /// Returns the `rent_epoch` the runtime would give a new account.
+    ///
+    /// Before rent collection was removed, a rent-paying account records the
+    /// next epoch in which rent is due; otherwise the runtime uses
+    /// [`RENT_EXEMPT_RENT_EPOCH`].
+    pub fn default_rent_epoch(&self, lamports: u64, data_len: usize) -> u64 {
+        match self.version {
+            ClusterVersion::V1_17 => self.epoch + 1,
+            ClusterVersion::V1_18 if !self.rent.is_exempt(lamports, data_len) => self.epoch + 1,
+            ClusterVersion::V1_18 | ClusterVersion::V2_0 => RENT_EXEMPT_RENT_EPOCH,
+        }
+    }
+
+    /// Checks that an account could exist on this cluster.
+    ///
+    /// An account must either hold zero lamports or be rent-exempt, an
+    /// executable account must always be rent-exempt, and data may not
+    /// exceed [`MAX_PERMITTED_DATA_LENGTH`].
+    pub fn check_account(&self, account: &Account) -> Result<(), AccountGenError> {
+        check_data_len(account.data.len())?;
+        self.check_rent_exempt(account)
+    }
+
+    /// Checks that an account holds zero lamports or is rent-exempt, and
+    /// that an executable account is always rent-exempt.
+    pub fn check_rent_exempt(&self, account: &Account) -> Result<(), AccountGenError> {
+        let required = self.minimum_balance(account.data.len());
+        let allowed_empty = account.lamports == 0 && !account.executable;
+
+        if account.lamports < required && !allowed_empty {
+            return Err(AccountGenError::InsufficientBalance {
+                required,
+                actual: account.lamports,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that `len` bytes of account data fit within
+/// [`MAX_PERMITTED_DATA_LENGTH`].
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountGenError;
+/// use solana_accountgen::cluster::{MAX_PERMITTED_DATA_LENGTH, check_data_len};
+///
+/// assert!(check_data_len(MAX_PERMITTED_DATA_LENGTH).is_ok());
+/// assert!(matches!(
+///     check_data_len(MAX_PERMITTED_DATA_LENGTH + 1),
+///     Err(AccountGenError::DataTooLarge { .. })
+/// ));
+/// ```
+pub fn check_data_len(len: usize) -> Result<(), AccountGenError> {
+    if len > MAX_PERMITTED_DATA_LENGTH {
+        return Err(AccountGenError::DataTooLarge {
+            len,
+            max: MAX_PERMITTED_DATA_LENGTH,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that one instruction could resize account data from `old_len` to
+/// `new_len` bytes.
+///
+/// Data may shrink freely but grow by at most [`MAX_REALLOC_INCREASE`] per
+/// instruction, and never past [`MAX_PERMITTED_DATA_LENGTH`].
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::cluster::{MAX_REALLOC_INCREASE, check_realloc};
+///
+/// assert!(check_realloc(100, 100 + MAX_REALLOC_INCREASE).is_ok());
+/// assert!(check_realloc(100, 101 + MAX_REALLOC_INCREASE).is_err());
+/// assert!(check_realloc(100_000, 0).is_ok());
+/// ```
+pub fn check_realloc(old_len: usize, new_len: usize) -> Result<(), AccountGenError> {
+    let max = old_len.saturating_add(MAX_REALLOC_INCREASE);
+    if new_len > max {
+        return Err(AccountGenError::DataTooLarge { len: new_len, max });
+    }
+    check_data_len(new_len)
+}
+
+/// The rent rules applied by [`simulate_rent_collection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RentRegime {
+    /// Rent is charged every epoch on accounts below the exemption
+    /// threshold, and accounts that cannot pay are deleted, as the runtime
+    /// did before rent collection was removed.
+    Collecting,
+    /// Only rent-exempt accounts may exist: accounts with a non-zero
+    /// balance below the threshold are deleted outright, matching what
+    /// current clusters allow.
+    StrictExempt,
+}
+
+/// The outcome of a [`simulate_rent_collection`] run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RentCollection {
+    /// Total lamports taken from accounts, including deleted ones.
+    pub collected: u64,
+    /// Accounts whose balance was reduced, ordered by pubkey.
+    pub charged: Vec<Pubkey>,
+    /// Accounts that were deleted, ordered by pubkey.
+    pub removed: Vec<Pubkey>,
+}
+
+/// Applies `epochs` epochs of rent to every account in a map.
+///
+/// Rent-exempt and executable accounts are never charged, and zero-lamport
+/// accounts are deleted, as the runtime purges them. Charged accounts have
+/// their `rent_epoch` advanced by `epochs`. Epochs are assumed to have the
+/// default length of 432,000 slots.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, AccountMap};
+/// use solana_accountgen::cluster::{RentRegime, simulate_rent_collection};
+/// use solana_pubkey::Pubkey;
+/// use solana_rent::Rent;
+///
+/// let rent = Rent::default();
+/// let (exempt, marginal) = (Pubkey::new_unique(), Pubkey::new_unique());
+///
+/// let mut accounts = AccountMap::new();
+/// accounts.add_with_builder(exempt, AccountBuilder::new().data_raw(vec![0; 100])).unwrap();
+/// accounts
+///     .add_with_builder(marginal, AccountBuilder::new().balance(1_000_000).data_raw(vec![0; 100]))
+///     .unwrap();
+///
+/// let report = simulate_rent_collection(&mut accounts, 10, &rent, RentRegime::Collecting);
+/// assert_eq!(report.charged, vec![marginal]);
+/// assert!(accounts.get_account(&marginal).unwrap().lamports < 1_000_000);
+///
+/// let report = simulate_rent_collection(&mut accounts, 0, &rent, RentRegime::StrictExempt);
+/// assert_eq!(report.removed, vec![marginal]);
+/// assert!(accounts.get_account(&exempt).is_some());
+/// ```
+pub fn simulate_rent_collection(
+    account_map: &mut AccountMap,
+    epochs: u64,
+    rent: &Rent,
+    regime: RentRegime,
+) -> RentCollection {
+    let slots_per_epoch = EpochSchedule::default().slots_per_epoch;
+    // In f64 so that a large epoch count cannot overflow the slot count
+    let years_elapsed = epochs as f64 * slots_per_epoch as f64 / SLOTS_PER_YEAR;
+    let mut report = RentCollection::default();
+
+    for (pubkey, account) in account_map.iter_mut() {
+        if account.lamports == 0 {
+            report.removed.push(*pubkey);
+            continue;
+        }
+        if account.executable {
+            continue;
+        }
+
+        let due = match rent.due(account.lamports, account.data.len(), years_elapsed) {
+            RentDue::Exempt => continue,
+            RentDue::Paying(due) => match regime {
+                RentRegime::Collecting => due,
+                RentRegime::StrictExempt => account.lamports,
+            },
+        };
+
+        if due >= account.lamports {
+            report.collected += account.lamports;
+            report.removed.push(*pubkey);
+        } else if due > 0 {
+            account.lamports -= due;
+            account.rent_epoch = account.rent_epoch.saturating_add(epochs);
+            report.collected += due;
+            report.charged.push(*pubkey);
+        }
+    }
+
+    for pubkey in &report.removed {
+        account_map.remove_account(pubkey);
+    }
+    report.charged.sort();
+    report.removed.sort();
+    report
+}
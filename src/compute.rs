@@ -0,0 +1,321 @@
+//! Compute unit baselines for catching performance regressions.
+//!
+//! A change that makes an instruction twice as expensive passes every
+//! functional test, and is found only when a transaction hits its compute
+//! limit on a cluster. [`CuBaseline`] records the compute units each named
+//! instruction consumes in simulation and saves them as a JSON file kept
+//! with the tests. Later runs measure again and compare with
+//! [`CuBaseline::assert_cu_within`], failing when an instruction drifts
+//! more than a tolerance from its baseline in either direction, so the
+//! file is updated along with the change that moved it.
+//!
+//! [`CuBaseline::check_file`] does both in one call: it writes the file
+//! when `ACCOUNTGEN_UPDATE_BASELINES` is set, and compares against it
+//! otherwise. A missing file is an error rather than a new baseline, so a
+//! deleted or misnamed file cannot make the check pass.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::compute::CuBaseline;
+//!
+//! let baseline = CuBaseline::from_json(r#"{ "deposit": 4000, "withdraw": 6000 }"#).unwrap();
+//!
+//! let mut measured = CuBaseline::new();
+//! measured.record("deposit", 4100);
+//! measured.record("withdraw", 6000);
+//! measured.assert_cu_within(&baseline, 0.05);
+//!
+//! measured.record("deposit", 5000);
+//! let report = measured.compare(&baseline, 0.05);
+//! assert_eq!(report.changed[0].name, "deposit");
+//! assert!(report.to_string().contains("deposit: 4000 -> 5000 (+25.0%)"));
+//! ```
+
+use crate::AccountGenError;
+use serde::{Deserialize, Serialize};
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_program_test::BanksClient;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// The environment variable that makes [`CuBaseline::check_file`] and
+/// [`check_logs_snapshot`](crate::logs::check_logs_snapshot) rewrite their
+/// files instead of checking them.
+pub const UPDATE_BASELINES_VAR: &str = "ACCOUNTGEN_UPDATE_BASELINES";
+
+/// Compute units consumed per named instruction.
+///
+/// Serializes as a JSON object from name to units, sorted by name so that
+/// baseline files diff cleanly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CuBaseline {
+    units: BTreeMap<String, u64>,
+}
+
+impl CuBaseline {
+    /// Creates an empty baseline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the units consumed by the instruction `name`, replacing any
+    /// earlier record.
+    pub fn record(&mut self, name: impl Into<String>, units: u64) -> &mut Self {
+        self.units.insert(name.into(), units);
+        self
+    }
+
+    /// Returns the units recorded for `name`.
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.units.get(name).copied()
+    }
+
+    /// Returns every (name, units) pair, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.units
+            .iter()
+            .map(|(name, units)| (name.as_str(), *units))
+    }
+
+    /// Returns the number of recorded instructions.
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Returns true if nothing is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Simulates `instruction` alone in a transaction and records the
+    /// units it consumed under `name`.
+    ///
+    /// `payer` pays for the transaction, and it is additionally signed by
+    /// whichever of `signers` the instruction requires. The bank's state
+    /// is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::TransactionFailed`] if the simulation
+    /// fails, or [`AccountGenError::BanksClientError`] if the bank cannot
+    /// be reached.
+    pub async fn measure(
+        &mut self,
+        banks_client: &mut BanksClient,
+        name: impl Into<String>,
+        instruction: Instruction,
+        payer: &Keypair,
+        signers: &[&Keypair],
+    ) -> Result<u64, AccountGenError> {
+        let mut keypairs = vec![payer];
+        keypairs.extend(signers.iter().copied().filter(|signer| {
+            signer.pubkey() != payer.pubkey()
+                && instruction
+                    .accounts
+                    .iter()
+                    .any(|meta| meta.is_signer && meta.pubkey == signer.pubkey())
+        }));
+        let blockhash = banks_client.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &keypairs,
+            blockhash,
+        );
+
+        let simulation = banks_client.simulate_transaction(transaction).await?;
+        if let Some(Err(e)) = simulation.result {
+            return Err(AccountGenError::TransactionFailed(e));
+        }
+        let units = simulation
+            .simulation_details
+            .ok_or_else(|| {
+                AccountGenError::BackendError("the bank returned no simulation details".into())
+            })?
+            .units_consumed;
+        self.record(name, units);
+        Ok(units)
+    }
+
+    /// Compares these measurements with `baseline`.
+    ///
+    /// An instruction fails the comparison if its units differ from the
+    /// baseline by more than `tolerance`, a fraction of the baseline such
+    /// as `0.05` for 5%, or if the baseline has no record of it.
+    /// Instructions in the baseline that were not measured are not
+    /// compared.
+    pub fn compare(&self, baseline: &CuBaseline, tolerance: f64) -> CuReport {
+        let mut report = CuReport {
+            tolerance,
+            ..CuReport::default()
+        };
+        for (name, measured) in self.iter() {
+            match baseline.get(name) {
+                None => report.unrecorded.push(name.to_string()),
+                Some(expected) => {
+                    let allowed = (expected as f64 * tolerance).ceil();
+                    if measured.abs_diff(expected) as f64 > allowed {
+                        report.changed.push(CuChange {
+                            name: name.to_string(),
+                            baseline: expected,
+                            measured,
+                        });
+                    }
+                }
+            }
+        }
+        report
+    }
+
+    /// Panics with a report if these measurements are not within
+    /// `tolerance` of `baseline`, as [`compare`](Self::compare) decides.
+    pub fn assert_cu_within(&self, baseline: &CuBaseline, tolerance: f64) {
+        let report = self.compare(baseline, tolerance);
+        if !report.is_ok() {
+            panic!("{}", report);
+        }
+    }
+
+    /// Compares these measurements with the baseline file at `path`, or
+    /// writes them into it.
+    ///
+    /// The file is written, keeping the records of instructions not
+    /// measured now, only when the [`UPDATE_BASELINES_VAR`] environment
+    /// variable is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::ConstraintViolation`] with the report if
+    /// the comparison fails or if the file does not exist and is not being
+    /// updated, or an error if the file cannot be read or written.
+    pub fn check_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        tolerance: f64,
+    ) -> Result<(), AccountGenError> {
+        let path = path.as_ref();
+        let update = std::env::var_os(UPDATE_BASELINES_VAR).is_some();
+        if !update {
+            if !path.exists() {
+                return Err(AccountGenError::ConstraintViolation(format!(
+                    "baseline {} missing; rerun with {}=1",
+                    path.display(),
+                    UPDATE_BASELINES_VAR
+                )));
+            }
+            return self.compare(&Self::load(path)?, tolerance).into_result();
+        }
+        let mut baseline = if path.exists() {
+            Self::load(path)?
+        } else {
+            Self::new()
+        };
+        baseline.units.extend(self.units.clone());
+        baseline.save(path)
+    }
+
+    /// Writes the baseline as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("baselines always serialize")
+    }
+
+    /// Reads a baseline from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if `json` is not an
+    /// object of instruction names to units.
+    pub fn from_json(json: &str) -> Result<Self, AccountGenError> {
+        serde_json::from_str(json).map_err(|e| {
+            AccountGenError::InvalidDataFormat(format!("invalid compute unit baseline: {e}"))
+        })
+    }
+
+    /// Writes the baseline to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AccountGenError> {
+        std::fs::write(path, self.to_json() + "\n")?;
+        Ok(())
+    }
+
+    /// Reads a baseline from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AccountGenError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// An instruction whose units moved outside the tolerance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CuChange {
+    /// The instruction name.
+    pub name: String,
+    /// The units in the baseline.
+    pub baseline: u64,
+    /// The units measured now.
+    pub measured: u64,
+}
+
+/// The result of [`CuBaseline::compare`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CuReport {
+    /// The tolerance compared with.
+    pub tolerance: f64,
+    /// Instructions outside the tolerance, sorted by name.
+    pub changed: Vec<CuChange>,
+    /// Measured instructions the baseline has no record of, sorted.
+    pub unrecorded: Vec<String>,
+}
+
+impl CuReport {
+    /// Returns true if every measured instruction is within tolerance.
+    pub fn is_ok(&self) -> bool {
+        self.changed.is_empty() && self.unrecorded.is_empty()
+    }
+
+    /// Converts the report into an error if any instruction failed.
+    pub fn into_result(self) -> Result<(), AccountGenError> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(AccountGenError::ConstraintViolation(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for CuReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(
+                f,
+                "compute units are within {:.1}% of the baseline",
+                self.tolerance * 100.0
+            );
+        }
+        write!(
+            f,
+            "compute units differ from the baseline by more than {:.1}%:",
+            self.tolerance * 100.0
+        )?;
+        for change in &self.changed {
+            let percent = if change.baseline == 0 {
+                f64::INFINITY
+            } else {
+                (change.measured as f64 - change.baseline as f64) / change.baseline as f64 * 100.0
+            };
+            write!(
+                f,
+                "\n  {}: {} -> {} ({:+.1}%)",
+                change.name, change.baseline, change.measured, percent
+            )?;
+        }
+        for name in &self.unrecorded {
+            write!(f, "\n  {}: not in the baseline", name)?;
+        }
+        write!(f, "\nset {} to update the baseline", UPDATE_BASELINES_VAR)
+    }
+}
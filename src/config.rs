@@ -0,0 +1,179 @@
+//! Crate-wide defaults for the current thread.
+//!
+//! Builders default the owner of new accounts to the System Program. Suites
+//! built around one program, such as Anchor programs, end up repeating
+//! `.owner(program_id)` in hundreds of builder calls. An installed
+//! [`AccountGenConfig`] changes that default for every
+//! [`AccountBuilder`](crate::AccountBuilder) on the thread until its guard
+//! is dropped. An owner set on a builder still wins.
+//!
+//! The configuration is per thread, so tests running in parallel do not
+//! see each other's defaults. Async tests must run on a current-thread
+//! runtime, which is the default for `#[tokio::test]`.
+//!
+//! ```
+//! use solana_accountgen::AccountBuilder;
+//! use solana_accountgen::config::AccountGenConfig;
+//! use solana_accountgen::well_known::SYSTEM_PROGRAM;
+//! use solana_pubkey::Pubkey;
+//!
+//! let program_id = Pubkey::new_unique();
+//! let config = AccountGenConfig::new().default_owner(program_id).install();
+//! assert_eq!(AccountBuilder::new().build().owner, program_id);
+//!
+//! drop(config);
+//! assert_eq!(AccountBuilder::new().build().owner, SYSTEM_PROGRAM);
+//! ```
+//!
+//! [`with_defaults`] scopes defaults to a closure instead, which suits
+//! table-driven tests that build dozens of accounts the same way:
+//!
+//! ```
+//! use solana_accountgen::config::with_defaults;
+//! use solana_accountgen::{AccountBuilder, AccountGenError};
+//! use solana_pubkey::Pubkey;
+//!
+//! let program_id = Pubkey::new_unique();
+//! with_defaults(|ctx| {
+//!     ctx.owner(program_id);
+//!     ctx.min_balance_rent_exempt();
+//!
+//!     assert_eq!(AccountBuilder::new().build().owner, program_id);
+//!     let result = AccountBuilder::new().balance(1).try_build();
+//!     assert!(matches!(result, Err(AccountGenError::InsufficientBalance { .. })));
+//! });
+//! assert!(AccountBuilder::new().balance(1).try_build().is_ok());
+//! ```
+
+use crate::BuildMode;
+use crate::well_known::SYSTEM_PROGRAM;
+use solana_pubkey::Pubkey;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+thread_local! {
+    static CONFIG: RefCell<AccountGenConfig> = RefCell::new(AccountGenConfig::new());
+}
+
+/// Defaults applied to builders on the current thread once installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountGenConfig {
+    /// The owner of accounts whose builder sets none.
+    pub default_owner: Pubkey,
+    /// The mode of builders that set none and have no
+    /// [`ClusterContext`](crate::cluster::ClusterContext) attached.
+    pub default_mode: BuildMode,
+    /// Whether every build checks rent exemption, as
+    /// [`BuildMode::Strict`] does, whatever its mode.
+    pub require_rent_exempt: bool,
+}
+
+impl Default for AccountGenConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccountGenConfig {
+    /// Creates the crate's built-in defaults.
+    pub fn new() -> Self {
+        Self {
+            default_owner: SYSTEM_PROGRAM,
+            default_mode: BuildMode::Lenient,
+            require_rent_exempt: false,
+        }
+    }
+
+    /// Returns the configuration installed on this thread.
+    pub fn current() -> Self {
+        CONFIG.with_borrow(Clone::clone)
+    }
+
+    /// Sets the owner of accounts whose builder sets none.
+    pub fn default_owner(mut self, owner: Pubkey) -> Self {
+        self.default_owner = owner;
+        self
+    }
+
+    /// Sets the mode of builders that set none.
+    pub fn default_mode(mut self, mode: BuildMode) -> Self {
+        self.default_mode = mode;
+        self
+    }
+
+    /// Makes every build check that the account is rent-exempt, unless its
+    /// builder calls [`skip_rent_check`](crate::AccountBuilder::skip_rent_check).
+    pub fn require_rent_exempt(mut self) -> Self {
+        self.require_rent_exempt = true;
+        self
+    }
+
+    /// Makes this the configuration of the current thread.
+    ///
+    /// The previous configuration is restored when the returned guard is
+    /// dropped, so installs can be nested.
+    pub fn install(self) -> ConfigGuard {
+        let previous = CONFIG.replace(self);
+        ConfigGuard {
+            previous: Some(previous),
+            _thread: PhantomData,
+        }
+    }
+}
+
+/// A guard that keeps an [`AccountGenConfig`] installed; see
+/// [`AccountGenConfig::install`].
+#[derive(Debug)]
+#[must_use = "the configuration is uninstalled when the guard is dropped"]
+pub struct ConfigGuard {
+    previous: Option<AccountGenConfig>,
+    // The configuration is per thread, so the guard must stay on its thread
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for ConfigGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            CONFIG.set(previous);
+        }
+    }
+}
+
+/// Runs `f` with defaults it sets through a [`DefaultsContext`], then
+/// restores the previous configuration, also if `f` panics.
+///
+/// The context starts from the configuration already installed, so calls
+/// nest, and each setting applies to builders created after it.
+pub fn with_defaults<R>(f: impl FnOnce(&mut DefaultsContext) -> R) -> R {
+    let mut ctx = DefaultsContext {
+        _guard: AccountGenConfig::current().install(),
+    };
+    f(&mut ctx)
+}
+
+/// Sets the defaults of a [`with_defaults`] scope.
+#[derive(Debug)]
+pub struct DefaultsContext {
+    _guard: ConfigGuard,
+}
+
+impl DefaultsContext {
+    /// Sets the owner of accounts whose builder sets none.
+    pub fn owner(&mut self, owner: Pubkey) -> &mut Self {
+        CONFIG.with_borrow_mut(|config| config.default_owner = owner);
+        self
+    }
+
+    /// Sets the mode of builders that set none.
+    pub fn mode(&mut self, mode: BuildMode) -> &mut Self {
+        CONFIG.with_borrow_mut(|config| config.default_mode = mode);
+        self
+    }
+
+    /// Rejects accounts below the rent-exempt minimum for their data.
+    /// Builders without a balance already default to that minimum.
+    pub fn min_balance_rent_exempt(&mut self) -> &mut Self {
+        CONFIG.with_borrow_mut(|config| config.require_rent_exempt = true);
+        self
+    }
+}
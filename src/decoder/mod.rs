@@ -0,0 +1,413 @@
+//! Instruction data decoding.
+//!
+//! A failing transaction is much easier to debug when its instructions read
+//! `Token Program: transfer {"amount": 500}` instead of a byte array. A
+//! [`DecoderRegistry`] maps program ids to decoders: the System, SPL Token,
+//! Token-2022, Memo and Compute Budget programs are known out of the box,
+//! and tests add their own programs from an Anchor IDL, a Borsh enum or a
+//! plain function.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::decoder::DecoderRegistry;
+//! use solana_accountgen::well_known::SYSTEM_PROGRAM;
+//! use solana_instruction::{AccountMeta, Instruction};
+//! use solana_pubkey::Pubkey;
+//! use serde_json::json;
+//!
+//! let mut data = 2u32.to_le_bytes().to_vec();
+//! data.extend_from_slice(&5_000u64.to_le_bytes());
+//! let instruction = Instruction::new_with_bytes(
+//!     SYSTEM_PROGRAM,
+//!     &data,
+//!     vec![
+//!         AccountMeta::new(Pubkey::new_unique(), true),
+//!         AccountMeta::new(Pubkey::new_unique(), false),
+//!     ],
+//! );
+//!
+//! let decoded = DecoderRegistry::new().decode_instruction(&instruction).unwrap();
+//! assert_eq!(decoded.name, "transfer");
+//! assert_eq!(decoded.args, json!({ "lamports": 5_000 }));
+//! assert_eq!(decoded.accounts[1].name.as_deref(), Some("destination"));
+//! ```
+
+mod native;
+
+use crate::extensions::idl::Idl;
+use crate::fixture::record::pubkey_string;
+use crate::{AccountGenError, well_known};
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use serde_json::{Value, json};
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// The program-specific part of a decoded instruction.
+///
+/// Custom decoders registered with [`DecoderRegistry::register`] return
+/// this; the registry adds the program name and the account metas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedData {
+    /// The instruction name.
+    pub name: String,
+    /// The instruction arguments, or `null` if it has none.
+    pub args: Value,
+    /// The names of the instruction's accounts, in order. Accounts beyond
+    /// the end of the list are left unnamed.
+    pub account_names: Vec<String>,
+}
+
+/// An instruction in a structured, printable form.
+///
+/// `Display` renders it over several lines:
+///
+/// ```text
+/// System Program: transfer
+///   args: {"lamports":5000}
+///   #0 source       4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM (signer, writable)
+///   #1 destination  8opHzTAnfzRpPEx21XtnrVTX28YQuCpAjcn1PczScKh (writable)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedInstruction {
+    /// The program id.
+    #[serde(with = "pubkey_string")]
+    pub program_id: Pubkey,
+    /// The program name, or its address if it is not known.
+    pub program: String,
+    /// The instruction name, or `unknown` if the program has no decoder.
+    pub name: String,
+    /// The decoded arguments; for unknown programs, the hex data.
+    pub args: Value,
+    /// The accounts passed to the instruction, in order.
+    pub accounts: Vec<DecodedAccount>,
+}
+
+/// An account passed to a [`DecodedInstruction`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedAccount {
+    /// The account's role in the instruction, if the decoder knows it.
+    pub name: Option<String>,
+    /// The account address.
+    #[serde(with = "pubkey_string")]
+    pub pubkey: Pubkey,
+    /// Whether the account signs the transaction.
+    pub is_signer: bool,
+    /// Whether the instruction may modify the account.
+    pub is_writable: bool,
+}
+
+impl fmt::Display for DecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}: {}", self.program, self.name)?;
+        if !self.args.is_null() {
+            writeln!(f, "  args: {}", self.args)?;
+        }
+        let width = self
+            .accounts
+            .iter()
+            .filter_map(|account| account.name.as_ref().map(String::len))
+            .max()
+            .unwrap_or(0);
+        for (i, account) in self.accounts.iter().enumerate() {
+            let name = account.name.as_deref().unwrap_or("");
+            let flags: Vec<_> = [
+                (account.is_signer, "signer"),
+                (account.is_writable, "writable"),
+            ]
+            .into_iter()
+            .filter_map(|(set, flag)| set.then_some(flag))
+            .collect();
+            write!(f, "  #{} {:<width$}  {}", i, name, account.pubkey)?;
+            if flags.is_empty() {
+                writeln!(f)?;
+            } else {
+                writeln!(f, " ({})", flags.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+type DecodeFn = dyn Fn(&[u8]) -> Result<DecodedData, AccountGenError> + Send + Sync;
+
+#[derive(Clone)]
+struct Decoder {
+    program: String,
+    decode: Arc<DecodeFn>,
+}
+
+/// Known instruction layouts, by program id.
+///
+/// Instructions of programs without a decoder still decode, with the
+/// instruction name `unknown` and the raw data as hex; instructions of a
+/// known program that do not match its layout are an error.
+#[derive(Clone)]
+pub struct DecoderRegistry {
+    decoders: HashMap<Pubkey, Decoder>,
+}
+
+impl fmt::Debug for DecoderRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.decoders
+                    .iter()
+                    .map(|(program_id, decoder)| (program_id, &decoder.program)),
+            )
+            .finish()
+    }
+}
+
+impl Default for DecoderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecoderRegistry {
+    /// Creates a registry that knows the System, SPL Token, Token-2022,
+    /// Memo and Compute Budget programs.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register(well_known::SYSTEM_PROGRAM, "System Program", native::system);
+        registry.register(well_known::TOKEN_PROGRAM, "Token Program", native::token);
+        registry.register(
+            well_known::TOKEN_2022_PROGRAM,
+            "Token-2022 Program",
+            native::token,
+        );
+        registry.register(well_known::MEMO_PROGRAM, "Memo Program", native::memo);
+        registry.register(well_known::MEMO_V1_PROGRAM, "Memo Program v1", native::memo);
+        registry.register(
+            well_known::COMPUTE_BUDGET_PROGRAM,
+            "Compute Budget Program",
+            native::compute_budget,
+        );
+        registry
+    }
+
+    /// Creates a registry with no decoders.
+    pub fn empty() -> Self {
+        Self {
+            decoders: HashMap::new(),
+        }
+    }
+
+    /// Registers a decoder for the instruction data of `program_id`,
+    /// replacing any existing one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::decoder::{DecodedData, DecoderRegistry};
+    /// use solana_instruction::Instruction;
+    /// use solana_pubkey::Pubkey;
+    /// use serde_json::json;
+    ///
+    /// let program_id = Pubkey::new_unique();
+    /// let mut registry = DecoderRegistry::new();
+    /// registry.register(program_id, "Counter", |data| {
+    ///     Ok(DecodedData {
+    ///         name: "increment".to_string(),
+    ///         args: json!({ "by": data[0] }),
+    ///         account_names: vec!["counter".to_string()],
+    ///     })
+    /// });
+    ///
+    /// let instruction = Instruction::new_with_bytes(program_id, &[3], vec![]);
+    /// let decoded = registry.decode_instruction(&instruction).unwrap();
+    /// assert_eq!(decoded.to_string(), "Counter: increment\n  args: {\"by\":3}\n");
+    /// ```
+    pub fn register<F>(&mut self, program_id: Pubkey, program: impl Into<String>, decode: F)
+    where
+        F: Fn(&[u8]) -> Result<DecodedData, AccountGenError> + Send + Sync + 'static,
+    {
+        self.decoders.insert(
+            program_id,
+            Decoder {
+                program: program.into(),
+                decode: Arc::new(decode),
+            },
+        );
+    }
+
+    /// Registers the instructions of an Anchor IDL for `program_id`.
+    ///
+    /// Instructions are told apart by their 8-byte discriminator and their
+    /// arguments decoded with the IDL's types.
+    pub fn register_anchor_idl(
+        &mut self,
+        program_id: Pubkey,
+        idl: &Idl,
+    ) -> Result<(), AccountGenError> {
+        let schema = idl.to_fixture_schema()?;
+        let instructions: Vec<_> = idl
+            .instructions
+            .iter()
+            .map(|instruction| {
+                (
+                    idl.instruction_discriminator(instruction),
+                    instruction.name.clone(),
+                    instruction.account_names(),
+                )
+            })
+            .collect();
+        let program = idl.program_name().unwrap_or("Anchor program").to_string();
+
+        self.register(program_id, program.clone(), move |data| {
+            let (discriminator, args) = data.split_at_checked(8).ok_or_else(|| {
+                AccountGenError::InvalidAnchorDiscriminator(format!(
+                    "{program} instruction data is shorter than a discriminator"
+                ))
+            })?;
+            let (_, name, account_names) = instructions
+                .iter()
+                .find(|(known, _, _)| known[..] == *discriminator)
+                .ok_or_else(|| {
+                    AccountGenError::InvalidAnchorDiscriminator(format!(
+                        "{program} has no instruction with discriminator {}",
+                        hex::encode(discriminator)
+                    ))
+                })?;
+            let args = schema.decode(&format!("instruction:{name}"), args)?;
+            Ok(DecodedData {
+                name: name.clone(),
+                args: match args {
+                    Value::Object(fields) if fields.is_empty() => Value::Null,
+                    args => args,
+                },
+                account_names: account_names.clone(),
+            })
+        });
+        Ok(())
+    }
+
+    /// Registers a Borsh instruction type for `program_id`.
+    ///
+    /// The data must decode as `T` exactly. `T`'s serde representation
+    /// gives the arguments; for an enum with the default externally tagged
+    /// representation, the variant name is the instruction name, and for
+    /// anything else the type name is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use borsh::{BorshDeserialize, BorshSerialize};
+    /// use solana_accountgen::decoder::DecoderRegistry;
+    /// use solana_instruction::Instruction;
+    /// use solana_pubkey::Pubkey;
+    /// use serde::Serialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(BorshSerialize, BorshDeserialize, Serialize)]
+    /// enum VaultInstruction {
+    ///     Deposit { amount: u64 },
+    ///     Withdraw { amount: u64 },
+    /// }
+    ///
+    /// let program_id = Pubkey::new_unique();
+    /// let mut registry = DecoderRegistry::new();
+    /// registry.register_borsh::<VaultInstruction>(program_id, "Vault");
+    ///
+    /// let instruction = Instruction::new_with_borsh(
+    ///     program_id,
+    ///     &VaultInstruction::Withdraw { amount: 10 },
+    ///     vec![],
+    /// );
+    /// let decoded = registry.decode_instruction(&instruction).unwrap();
+    /// assert_eq!(decoded.name, "Withdraw");
+    /// assert_eq!(decoded.args, json!({ "amount": 10 }));
+    /// ```
+    pub fn register_borsh<T>(&mut self, program_id: Pubkey, program: impl Into<String>)
+    where
+        T: BorshDeserialize + Serialize + 'static,
+    {
+        self.register(program_id, program, |data| {
+            let value = T::try_from_slice(data).map_err(AccountGenError::DeserializationError)?;
+            let value = serde_json::to_value(&value)
+                .map_err(|e| AccountGenError::InvalidDataFormat(e.to_string()))?;
+            let (name, args) = match value {
+                Value::String(variant) => (variant, Value::Null),
+                // Variants are CamelCase while struct fields are snake_case
+                Value::Object(object)
+                    if object.len() == 1
+                        && object.keys().all(|key| key.starts_with(char::is_uppercase)) =>
+                {
+                    object.into_iter().next().expect("one entry")
+                }
+                value => {
+                    let type_name = std::any::type_name::<T>();
+                    let name = type_name.rsplit("::").next().unwrap_or(type_name);
+                    (name.to_string(), value)
+                }
+            };
+            Ok(DecodedData {
+                name,
+                args,
+                account_names: Vec::new(),
+            })
+        });
+    }
+
+    /// Returns true if `program_id` has a decoder.
+    pub fn contains(&self, program_id: &Pubkey) -> bool {
+        self.decoders.contains_key(program_id)
+    }
+
+    /// Decodes an instruction with the decoder registered for its program.
+    pub fn decode_instruction(
+        &self,
+        instruction: &Instruction,
+    ) -> Result<DecodedInstruction, AccountGenError> {
+        let (program, data) = match self.decoders.get(&instruction.program_id) {
+            Some(decoder) => (
+                decoder.program.clone(),
+                (decoder.decode)(&instruction.data)?,
+            ),
+            None => (
+                well_known::name_for(&instruction.program_id)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| instruction.program_id.to_string()),
+                DecodedData {
+                    name: "unknown".to_string(),
+                    args: json!({ "data": hex::encode(&instruction.data) }),
+                    account_names: Vec::new(),
+                },
+            ),
+        };
+
+        let accounts = instruction
+            .accounts
+            .iter()
+            .enumerate()
+            .map(|(i, meta)| DecodedAccount {
+                name: data.account_names.get(i).cloned(),
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect();
+
+        Ok(DecodedInstruction {
+            program_id: instruction.program_id,
+            program,
+            name: data.name,
+            args: data.args,
+            accounts,
+        })
+    }
+}
+
+/// Decodes an instruction of one of the programs
+/// [`DecoderRegistry::new`] knows.
+pub fn decode_instruction(
+    instruction: &Instruction,
+) -> Result<DecodedInstruction, AccountGenError> {
+    DecoderRegistry::new().decode_instruction(instruction)
+}
@@ -0,0 +1,300 @@
+//! Decoders for the native programs and the SPL programs every test uses.
+//!
+//! Names follow the `jsonParsed` output of an RPC node: instructions and
+//! fields are camelCase and public keys are base58.
+
+use super::DecodedData;
+use crate::AccountGenError;
+use serde_json::{Map, Value, json};
+use solana_pubkey::Pubkey;
+
+/// Reads the fields of one instruction, in order.
+struct Fields<'a> {
+    program: &'static str,
+    data: &'a [u8],
+    args: Map<String, Value>,
+}
+
+impl<'a> Fields<'a> {
+    fn new(program: &'static str, data: &'a [u8]) -> Self {
+        Self {
+            program,
+            data,
+            args: Map::new(),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AccountGenError> {
+        if self.data.len() < len {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "{} instruction data is truncated",
+                self.program
+            )));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, AccountGenError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, AccountGenError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, AccountGenError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey, AccountGenError> {
+        Ok(Pubkey::new_from_array(self.take(32)?.try_into().unwrap()))
+    }
+
+    fn add(&mut self, name: &str, value: Value) -> &mut Self {
+        self.args.insert(name.to_string(), value);
+        self
+    }
+
+    fn add_u8(&mut self, name: &str) -> Result<&mut Self, AccountGenError> {
+        let value = self.u8()?;
+        Ok(self.add(name, json!(value)))
+    }
+
+    fn add_u32(&mut self, name: &str) -> Result<&mut Self, AccountGenError> {
+        let value = self.u32()?;
+        Ok(self.add(name, json!(value)))
+    }
+
+    fn add_u64(&mut self, name: &str) -> Result<&mut Self, AccountGenError> {
+        let value = self.u64()?;
+        Ok(self.add(name, json!(value)))
+    }
+
+    fn add_pubkey(&mut self, name: &str) -> Result<&mut Self, AccountGenError> {
+        let value = self.pubkey()?;
+        Ok(self.add(name, json!(value.to_string())))
+    }
+
+    /// A bincode string, with a `u64` length prefix.
+    fn add_string(&mut self, name: &str) -> Result<&mut Self, AccountGenError> {
+        let len = self.u64()? as usize;
+        let bytes = self.take(len)?;
+        let text = std::str::from_utf8(bytes).map_err(|e| {
+            AccountGenError::InvalidDataFormat(format!("{} string is not UTF-8: {e}", self.program))
+        })?;
+        Ok(self.add(name, json!(text)))
+    }
+
+    /// An SPL Token `COption<Pubkey>`: a one-byte flag, then the key if set.
+    fn add_optional_pubkey(&mut self, name: &str) -> Result<&mut Self, AccountGenError> {
+        let value = match self.u8()? {
+            0 => Value::Null,
+            1 => json!(self.pubkey()?.to_string()),
+            flag => {
+                return Err(AccountGenError::InvalidDataFormat(format!(
+                    "invalid {} option flag {flag}",
+                    self.program
+                )));
+            }
+        };
+        Ok(self.add(name, value))
+    }
+
+    fn finish(&mut self, name: &str, accounts: &[&str]) -> Result<DecodedData, AccountGenError> {
+        if !self.data.is_empty() {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "{} trailing bytes after {} {name}",
+                self.data.len(),
+                self.program
+            )));
+        }
+        Ok(DecodedData {
+            name: name.to_string(),
+            args: if self.args.is_empty() {
+                Value::Null
+            } else {
+                Value::Object(std::mem::take(&mut self.args))
+            },
+            account_names: accounts.iter().map(|name| name.to_string()).collect(),
+        })
+    }
+}
+
+fn unknown_tag(program: &str, tag: impl std::fmt::Display) -> AccountGenError {
+    AccountGenError::InvalidDataFormat(format!("unknown {program} instruction {tag}"))
+}
+
+/// Decodes a System Program instruction (bincode, `u32` tag).
+pub(super) fn system(data: &[u8]) -> Result<DecodedData, AccountGenError> {
+    let mut f = Fields::new("System Program", data);
+    match f.u32()? {
+        0 => f
+            .add_u64("lamports")?
+            .add_u64("space")?
+            .add_pubkey("owner")?
+            .finish("createAccount", &["source", "newAccount"]),
+        1 => f.add_pubkey("owner")?.finish("assign", &["account"]),
+        2 => f
+            .add_u64("lamports")?
+            .finish("transfer", &["source", "destination"]),
+        3 => f
+            .add_pubkey("base")?
+            .add_string("seed")?
+            .add_u64("lamports")?
+            .add_u64("space")?
+            .add_pubkey("owner")?
+            .finish("createAccountWithSeed", &["source", "newAccount", "base"]),
+        4 => f.finish(
+            "advanceNonce",
+            &["nonceAccount", "recentBlockhashesSysvar", "nonceAuthority"],
+        ),
+        5 => f.add_u64("lamports")?.finish(
+            "withdrawFromNonce",
+            &[
+                "nonceAccount",
+                "destination",
+                "recentBlockhashesSysvar",
+                "rentSysvar",
+                "nonceAuthority",
+            ],
+        ),
+        6 => f.add_pubkey("nonceAuthority")?.finish(
+            "initializeNonce",
+            &["nonceAccount", "recentBlockhashesSysvar", "rentSysvar"],
+        ),
+        7 => f
+            .add_pubkey("newAuthorized")?
+            .finish("authorizeNonce", &["nonceAccount", "nonceAuthority"]),
+        8 => f.add_u64("space")?.finish("allocate", &["account"]),
+        9 => f
+            .add_pubkey("base")?
+            .add_string("seed")?
+            .add_u64("space")?
+            .add_pubkey("owner")?
+            .finish("allocateWithSeed", &["account", "base"]),
+        10 => f
+            .add_pubkey("base")?
+            .add_string("seed")?
+            .add_pubkey("owner")?
+            .finish("assignWithSeed", &["account", "base"]),
+        11 => f
+            .add_u64("lamports")?
+            .add_string("sourceSeed")?
+            .add_pubkey("sourceOwner")?
+            .finish("transferWithSeed", &["source", "sourceBase", "destination"]),
+        12 => f.finish("upgradeNonce", &["nonceAccount"]),
+        tag => Err(unknown_tag("System Program", tag)),
+    }
+}
+
+/// Decodes an SPL Token or Token-2022 instruction (`u8` tag).
+///
+/// Token-2022 extension instructions are not decoded.
+pub(super) fn token(data: &[u8]) -> Result<DecodedData, AccountGenError> {
+    let mut f = Fields::new("Token Program", data);
+    match f.u8()? {
+        0 => f
+            .add_u8("decimals")?
+            .add_pubkey("mintAuthority")?
+            .add_optional_pubkey("freezeAuthority")?
+            .finish("initializeMint", &["mint", "rentSysvar"]),
+        1 => f.finish(
+            "initializeAccount",
+            &["account", "mint", "owner", "rentSysvar"],
+        ),
+        2 => f
+            .add_u8("m")?
+            .finish("initializeMultisig", &["multisig", "rentSysvar"]),
+        3 => f
+            .add_u64("amount")?
+            .finish("transfer", &["source", "destination", "authority"]),
+        4 => f
+            .add_u64("amount")?
+            .finish("approve", &["source", "delegate", "owner"]),
+        5 => f.finish("revoke", &["source", "owner"]),
+        6 => f
+            .add_u8("authorityType")?
+            .add_optional_pubkey("newAuthority")?
+            .finish("setAuthority", &["account", "authority"]),
+        7 => f
+            .add_u64("amount")?
+            .finish("mintTo", &["mint", "account", "mintAuthority"]),
+        8 => f
+            .add_u64("amount")?
+            .finish("burn", &["account", "mint", "authority"]),
+        9 => f.finish("closeAccount", &["account", "destination", "owner"]),
+        10 => f.finish("freezeAccount", &["account", "mint", "freezeAuthority"]),
+        11 => f.finish("thawAccount", &["account", "mint", "freezeAuthority"]),
+        12 => f.add_u64("amount")?.add_u8("decimals")?.finish(
+            "transferChecked",
+            &["source", "mint", "destination", "authority"],
+        ),
+        13 => f
+            .add_u64("amount")?
+            .add_u8("decimals")?
+            .finish("approveChecked", &["source", "mint", "delegate", "owner"]),
+        14 => f
+            .add_u64("amount")?
+            .add_u8("decimals")?
+            .finish("mintToChecked", &["mint", "account", "mintAuthority"]),
+        15 => f
+            .add_u64("amount")?
+            .add_u8("decimals")?
+            .finish("burnChecked", &["account", "mint", "authority"]),
+        16 => f
+            .add_pubkey("owner")?
+            .finish("initializeAccount2", &["account", "mint", "rentSysvar"]),
+        17 => f.finish("syncNative", &["account"]),
+        18 => f
+            .add_pubkey("owner")?
+            .finish("initializeAccount3", &["account", "mint"]),
+        19 => f.add_u8("m")?.finish("initializeMultisig2", &["multisig"]),
+        20 => f
+            .add_u8("decimals")?
+            .add_pubkey("mintAuthority")?
+            .add_optional_pubkey("freezeAuthority")?
+            .finish("initializeMint2", &["mint"]),
+        21 => f.finish("getAccountDataSize", &["mint"]),
+        22 => f.finish("initializeImmutableOwner", &["account"]),
+        23 => f.add_u64("amount")?.finish("amountToUiAmount", &["mint"]),
+        24 => {
+            let ui_amount = String::from_utf8_lossy(f.take(f.data.len())?).into_owned();
+            f.add("uiAmount", json!(ui_amount))
+                .finish("uiAmountToAmount", &["mint"])
+        }
+        tag => Err(unknown_tag("Token Program", tag)),
+    }
+}
+
+/// Decodes a Compute Budget instruction (Borsh, `u8` tag).
+pub(super) fn compute_budget(data: &[u8]) -> Result<DecodedData, AccountGenError> {
+    let mut f = Fields::new("Compute Budget Program", data);
+    match f.u8()? {
+        0 => f
+            .add_u32("units")?
+            .add_u32("additionalFee")?
+            .finish("requestUnits", &[]),
+        1 => f.add_u32("bytes")?.finish("requestHeapFrame", &[]),
+        2 => f.add_u32("units")?.finish("setComputeUnitLimit", &[]),
+        3 => f
+            .add_u64("microLamports")?
+            .finish("setComputeUnitPrice", &[]),
+        4 => f
+            .add_u32("bytes")?
+            .finish("setLoadedAccountsDataSizeLimit", &[]),
+        tag => Err(unknown_tag("Compute Budget Program", tag)),
+    }
+}
+
+/// Decodes a memo, which is the UTF-8 instruction data itself.
+pub(super) fn memo(data: &[u8]) -> Result<DecodedData, AccountGenError> {
+    let memo = std::str::from_utf8(data)
+        .map_err(|e| AccountGenError::InvalidDataFormat(format!("memo is not UTF-8: {e}")))?;
+    Ok(DecodedData {
+        name: "memo".to_string(),
+        args: json!({ "memo": memo }),
+        account_names: Vec::new(),
+    })
+}
@@ -0,0 +1,367 @@
+//! Comparing accounts and account data.
+//!
+//! When an account changes unexpectedly, knowing *which bytes* changed is
+//! usually more useful than seeing two long byte vectors side by side.
+//! [`diff_bytes`] reports changed byte ranges, [`diff_accounts`] extends
+//! that to every account field, and [`diff_maps`] compares whole sets of
+//! accounts. The `_with_regions` variants name changed bytes after the
+//! [regions](crate::regions) of the owner program's layout. Program
+//! accounts are compared by their [summaries](crate::program_summary)
+//! rather than their bytecode.
+
+use crate::AccountMap;
+use crate::display::{format_account, format_sol};
+use crate::program_summary::{ProgramSummary, summarize};
+use crate::regions::{RegionChange, RegionMap, RegionMaps};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::fmt;
+
+/// A contiguous range of bytes that differs between two buffers.
+///
+/// If the buffers have different lengths, the trailing bytes of the longer
+/// one are reported as a change whose `old` or `new` side is shorter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteRangeChange {
+    /// Offset of the first changed byte.
+    pub offset: usize,
+    /// The bytes in the original buffer.
+    pub old: Vec<u8>,
+    /// The bytes in the updated buffer.
+    pub new: Vec<u8>,
+}
+
+impl ByteRangeChange {
+    /// Returns the end offset of the changed range (exclusive).
+    pub fn end(&self) -> usize {
+        self.offset + self.old.len().max(self.new.len())
+    }
+}
+
+impl fmt::Display for ByteRangeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}..{}]: {} -> {}",
+            self.offset,
+            self.end(),
+            hex_or_empty(&self.old),
+            hex_or_empty(&self.new)
+        )
+    }
+}
+
+fn hex_or_empty(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        "(none)".to_string()
+    } else {
+        hex::encode(bytes)
+    }
+}
+
+/// Returns the byte ranges that differ between `old` and `new`.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::diff::{ByteRangeChange, diff_bytes};
+///
+/// let changes = diff_bytes(&[1, 2, 3, 4, 5], &[1, 9, 9, 4, 5, 6]);
+/// assert_eq!(
+///     changes,
+///     vec![
+///         ByteRangeChange { offset: 1, old: vec![2, 3], new: vec![9, 9] },
+///         ByteRangeChange { offset: 5, old: vec![], new: vec![6] },
+///     ]
+/// );
+/// ```
+pub fn diff_bytes(old: &[u8], new: &[u8]) -> Vec<ByteRangeChange> {
+    let mut changes: Vec<ByteRangeChange> = Vec::new();
+    let common = old.len().min(new.len());
+
+    let mut i = 0;
+    while i < common {
+        if old[i] == new[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < common && old[i] != new[i] {
+            i += 1;
+        }
+        changes.push(ByteRangeChange {
+            offset: start,
+            old: old[start..i].to_vec(),
+            new: new[start..i].to_vec(),
+        });
+    }
+
+    if old.len() != new.len() {
+        // Extend a change that runs right up to the end of the common prefix
+        match changes.last_mut() {
+            Some(last) if last.end() == common => {
+                last.old.extend_from_slice(&old[common..]);
+                last.new.extend_from_slice(&new[common..]);
+            }
+            _ => changes.push(ByteRangeChange {
+                offset: common,
+                old: old[common..].to_vec(),
+                new: new[common..].to_vec(),
+            }),
+        }
+    }
+
+    changes
+}
+
+/// The field-by-field difference between two accounts.
+///
+/// Each field is `Some((old, new))` only if it changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountDiff {
+    /// Change in balance.
+    pub lamports: Option<(u64, u64)>,
+    /// Change in owner.
+    pub owner: Option<(Pubkey, Pubkey)>,
+    /// Change in the executable flag.
+    pub executable: Option<(bool, bool)>,
+    /// Change in rent epoch.
+    pub rent_epoch: Option<(u64, u64)>,
+    /// Changed data regions, when diffed against a [`RegionMap`].
+    pub regions: Vec<RegionChange>,
+    /// Changed data ranges. When diffed against a [`RegionMap`], only the
+    /// changes outside its regions.
+    pub data: Vec<ByteRangeChange>,
+    /// Change in a program account, whose data is summarized instead of
+    /// being compared byte by byte.
+    pub program: Option<(ProgramSummary, ProgramSummary)>,
+}
+
+impl AccountDiff {
+    /// Returns true if the accounts were identical.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl fmt::Display for AccountDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((old, new)) = self.lamports {
+            writeln!(
+                f,
+                "lamports: {} ({} SOL) -> {} ({} SOL)",
+                old,
+                format_sol(old),
+                new,
+                format_sol(new)
+            )?;
+        }
+        if let Some((old, new)) = self.owner {
+            writeln!(f, "owner: {} -> {}", old, new)?;
+        }
+        if let Some((old, new)) = self.executable {
+            writeln!(f, "executable: {} -> {}", old, new)?;
+        }
+        if let Some((old, new)) = self.rent_epoch {
+            writeln!(f, "rent_epoch: {} -> {}", old, new)?;
+        }
+        for change in &self.regions {
+            writeln!(f, "{}", change)?;
+        }
+        for change in &self.data {
+            writeln!(f, "data{}", change)?;
+        }
+        if let Some((old, new)) = &self.program {
+            writeln!(f, "program: {} -> {}", old, new)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two accounts field by field.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, diff::diff_accounts};
+///
+/// let before = AccountBuilder::new().balance(100).data_raw(vec![0; 4]).build();
+/// let after = AccountBuilder::new().balance(50).data_raw(vec![0, 7, 0, 0]).build();
+///
+/// let diff = diff_accounts(&before, &after);
+/// assert_eq!(diff.lamports, Some((100, 50)));
+/// assert_eq!(diff.to_string().lines().last(), Some("data[1..2]: 00 -> 07"));
+/// ```
+pub fn diff_accounts(old: &Account, new: &Account) -> AccountDiff {
+    fn changed<T: PartialEq + Copy>(old: T, new: T) -> Option<(T, T)> {
+        (old != new).then_some((old, new))
+    }
+
+    // Byte ranges of a changed ELF are noise; compare the summaries
+    let program = match (summarize(old), summarize(new)) {
+        (Some(old_summary), Some(new_summary)) if old.data != new.data => {
+            Some((old_summary, new_summary))
+        }
+        _ => None,
+    };
+
+    AccountDiff {
+        lamports: changed(old.lamports, new.lamports),
+        owner: changed(old.owner, new.owner),
+        executable: changed(old.executable, new.executable),
+        rent_epoch: changed(old.rent_epoch, new.rent_epoch),
+        regions: Vec::new(),
+        data: match program {
+            Some(_) => Vec::new(),
+            None => diff_bytes(&old.data, &new.data),
+        },
+        program,
+    }
+}
+
+/// Compares two accounts field by field, reporting data changes by the
+/// regions of `regions` where they fall inside one.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountBuilder;
+/// use solana_accountgen::diff::diff_accounts_with_regions;
+/// use solana_accountgen::regions::{RegionFormat, RegionMap};
+///
+/// let regions = RegionMap::new().region("header.seq", 0, RegionFormat::U32);
+/// let before = AccountBuilder::new().data_raw(vec![5, 0, 0, 0, 0]).build();
+/// let after = AccountBuilder::new().data_raw(vec![6, 0, 0, 0, 1]).build();
+///
+/// let diff = diff_accounts_with_regions(&before, &after, &regions);
+/// assert_eq!(diff.to_string(), "header.seq: 5 -> 6\ndata[4..5]: 00 -> 01\n");
+/// ```
+pub fn diff_accounts_with_regions(
+    old: &Account,
+    new: &Account,
+    regions: &RegionMap,
+) -> AccountDiff {
+    let diff = diff_accounts(old, new);
+    if diff.program.is_some() {
+        return diff;
+    }
+    AccountDiff {
+        regions: regions.diff(&old.data, &new.data),
+        data: diff_bytes(&old.data, &regions.mask(&old.data, &new.data)),
+        ..diff
+    }
+}
+
+/// The difference between two sets of accounts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountMapDiff {
+    /// Accounts only present in the updated map, ordered by pubkey.
+    pub added: Vec<(Pubkey, Account)>,
+    /// Accounts only present in the original map, ordered by pubkey.
+    pub removed: Vec<(Pubkey, Account)>,
+    /// Accounts present in both maps that differ, ordered by pubkey.
+    pub changed: Vec<(Pubkey, AccountDiff)>,
+}
+
+impl AccountMapDiff {
+    /// Returns true if both maps held identical accounts.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for AccountMapDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn indented(f: &mut fmt::Formatter<'_>, body: &str) -> fmt::Result {
+            body.lines()
+                .try_for_each(|line| writeln!(f, "    {}", line))
+        }
+
+        let mut entries: Vec<(&Pubkey, char, String)> = Vec::new();
+        for (pubkey, account) in &self.removed {
+            entries.push((pubkey, '-', format_account(account)));
+        }
+        for (pubkey, account) in &self.added {
+            entries.push((pubkey, '+', format_account(account)));
+        }
+        for (pubkey, account_diff) in &self.changed {
+            entries.push((pubkey, '~', account_diff.to_string()));
+        }
+        entries.sort_by_key(|(pubkey, _, _)| **pubkey);
+
+        for (pubkey, marker, body) in entries {
+            writeln!(f, "{} {}", marker, pubkey)?;
+            indented(f, &body)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two account maps.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, AccountMap, diff::diff_maps};
+/// use solana_pubkey::Pubkey;
+///
+/// let pubkey = Pubkey::new_unique();
+/// let mut before = AccountMap::new();
+/// before.add_with_builder(pubkey, AccountBuilder::new().balance(10)).unwrap();
+///
+/// let mut after = before.clone();
+/// after.get_account_mut(&pubkey).unwrap().lamports = 20;
+/// after.add_with_builder(Pubkey::new_unique(), AccountBuilder::new()).unwrap();
+///
+/// let diff = diff_maps(&before, &after);
+/// assert_eq!(diff.added.len(), 1);
+/// assert_eq!(diff.changed[0].1.lamports, Some((10, 20)));
+/// ```
+pub fn diff_maps(old: &AccountMap, new: &AccountMap) -> AccountMapDiff {
+    diff_maps_by(old, new, diff_accounts)
+}
+
+/// Compares two account maps, reporting data changes by the regions of the
+/// owner program's map where one is registered.
+///
+/// The map is chosen by the account's updated owner.
+pub fn diff_maps_with_regions(
+    old: &AccountMap,
+    new: &AccountMap,
+    regions: &RegionMaps,
+) -> AccountMapDiff {
+    diff_maps_by(old, new, |before, after| match regions.get(&after.owner) {
+        Some(map) => diff_accounts_with_regions(before, after, map),
+        None => diff_accounts(before, after),
+    })
+}
+
+fn diff_maps_by(
+    old: &AccountMap,
+    new: &AccountMap,
+    diff_accounts: impl Fn(&Account, &Account) -> AccountDiff,
+) -> AccountMapDiff {
+    let mut diff = AccountMapDiff::default();
+
+    for (pubkey, before) in old.iter() {
+        match new.get_account(pubkey) {
+            Some(after) => {
+                let account_diff = diff_accounts(before, after);
+                if !account_diff.is_empty() {
+                    diff.changed.push((*pubkey, account_diff));
+                }
+            }
+            None => diff.removed.push((*pubkey, before.clone())),
+        }
+    }
+    for (pubkey, after) in new.iter() {
+        if old.get_account(pubkey).is_none() {
+            diff.added.push((*pubkey, after.clone()));
+        }
+    }
+
+    diff.added.sort_by_key(|(pubkey, _)| *pubkey);
+    diff.removed.sort_by_key(|(pubkey, _)| *pubkey);
+    diff.changed.sort_by_key(|(pubkey, _)| *pubkey);
+    diff
+}
@@ -0,0 +1,218 @@
+//! Human-readable formatting for accounts and instructions.
+//!
+//! `Account`'s `Debug` output prints data as a list of numbers and owners as
+//! raw base58, which is hard to read when a test fails. The functions in
+//! this module produce aligned dumps with SOL amounts, owner program names,
+//! a hexdump of the data, and the 8-byte discriminator when one is likely
+//! present. Program accounts show a [summary](crate::program_summary) of
+//! their ELF instead of a hexdump.
+//!
+//! ```text
+//! lamports:       1461600 (0.001461600 SOL)
+//! owner:          Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS
+//! executable:     false
+//! rent_epoch:     0
+//! data:           72 bytes
+//! discriminator:  d8926b5e684bb6b1
+//! 00000000  d8 92 6b 5e 68 4b b6 b1  2a 00 00 00 00 00 00 00  |..k^hK..*.......|
+//! ```
+
+use crate::decoder::DecoderRegistry;
+use crate::units::LAMPORTS_PER_SOL;
+use crate::{AccountMap, program_summary, well_known};
+use solana_account::Account;
+use solana_instruction::Instruction;
+use std::fmt::Write;
+
+/// Data beyond this many bytes is summarized rather than dumped.
+pub const MAX_DUMP_BYTES: usize = 512;
+
+/// Formats a single account as an aligned, multi-line dump.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, display::format_account};
+///
+/// let account = AccountBuilder::new()
+///     .balance(1_500_000_000)
+///     .data_raw(b"hello".to_vec())
+///     .build();
+///
+/// let dump = format_account(&account);
+/// assert!(dump.contains("1.500000000 SOL"));
+/// assert!(dump.contains("System Program"));
+/// assert!(dump.contains("|hello|"));
+/// ```
+pub fn format_account(account: &Account) -> String {
+    let mut out = String::new();
+
+    let owner = match well_known::name_for(&account.owner) {
+        Some(name) => format!("{} ({})", account.owner, name),
+        None => account.owner.to_string(),
+    };
+
+    field(
+        &mut out,
+        "lamports",
+        &format!(
+            "{} ({} SOL)",
+            account.lamports,
+            format_sol(account.lamports)
+        ),
+    );
+    field(&mut out, "owner", &owner);
+    field(&mut out, "executable", &account.executable.to_string());
+    field(&mut out, "rent_epoch", &account.rent_epoch.to_string());
+    field(&mut out, "data", &format!("{} bytes", account.data.len()));
+
+    // Program accounts are summarized rather than dumped
+    if let Some(summary) = program_summary::summarize(account) {
+        field(&mut out, "program", &summary.to_string());
+        return out;
+    }
+
+    if let Some(discriminator) = detect_discriminator(account) {
+        field(&mut out, "discriminator", &hex::encode(discriminator));
+    }
+
+    let shown = account.data.len().min(MAX_DUMP_BYTES);
+    out.push_str(&hexdump(&account.data[..shown]));
+    if account.data.len() > shown {
+        let _ = writeln!(out, "... {} more bytes", account.data.len() - shown);
+    }
+
+    out
+}
+
+/// Formats every account in a map, ordered by pubkey.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, AccountMap, display::format_map};
+/// use solana_pubkey::Pubkey;
+///
+/// let mut map = AccountMap::new();
+/// let pubkey = Pubkey::new_unique();
+/// map.add_with_builder(pubkey, AccountBuilder::new().balance(1)).unwrap();
+///
+/// assert!(format_map(&map).contains(&pubkey.to_string()));
+/// ```
+pub fn format_map(account_map: &AccountMap) -> String {
+    let mut entries: Vec<_> = account_map.iter().collect();
+    entries.sort_by_key(|(pubkey, _)| **pubkey);
+
+    let mut out = String::new();
+    for (i, (pubkey, account)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "Account {}", pubkey);
+        for line in format_account(account).lines() {
+            let _ = writeln!(out, "  {}", line);
+        }
+    }
+    out
+}
+
+/// Formats an instruction decoded with the built-in decoders.
+///
+/// Instructions that do not match their program's layout are shown with
+/// their raw data instead. Use a
+/// [`DecoderRegistry`](crate::decoder::DecoderRegistry) to include your own
+/// programs.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::display::format_instruction;
+/// use solana_accountgen::well_known::MEMO_PROGRAM;
+/// use solana_instruction::Instruction;
+///
+/// let instruction = Instruction::new_with_bytes(MEMO_PROGRAM, b"gm", vec![]);
+/// assert_eq!(
+///     format_instruction(&instruction),
+///     "Memo Program: memo\n  args: {\"memo\":\"gm\"}\n"
+/// );
+/// ```
+pub fn format_instruction(instruction: &Instruction) -> String {
+    format_decoded(&DecoderRegistry::new(), instruction)
+}
+
+/// Formats an instruction with `registry`, falling back to the raw data
+/// when it does not decode.
+pub fn format_decoded(registry: &DecoderRegistry, instruction: &Instruction) -> String {
+    match registry.decode_instruction(instruction) {
+        Ok(decoded) => decoded.to_string(),
+        Err(e) => DecoderRegistry::empty()
+            .decode_instruction(instruction)
+            .map(|raw| format!("{raw}  error: {e}\n"))
+            .unwrap_or_else(|_| format!("{e}\n")),
+    }
+}
+
+/// Formats a lamport amount as SOL with full precision.
+pub fn format_sol(lamports: u64) -> String {
+    format!(
+        "{}.{:09}",
+        lamports / LAMPORTS_PER_SOL,
+        lamports % LAMPORTS_PER_SOL
+    )
+}
+
+fn field(out: &mut String, name: &str, value: &str) {
+    let _ = writeln!(out, "{:<15} {}", format!("{}:", name), value);
+}
+
+/// Returns the leading 8 bytes of program-owned data, where Anchor and
+/// similar frameworks store a type discriminator.
+fn detect_discriminator(account: &Account) -> Option<&[u8]> {
+    let native = well_known::name_for(&account.owner).is_some();
+    if native || account.executable || account.data.len() < 8 {
+        return None;
+    }
+    Some(&account.data[..8])
+}
+
+/// Renders bytes as a canonical hexdump with an ASCII column.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::display::hexdump;
+///
+/// assert_eq!(
+///     hexdump(b"solana"),
+///     "00000000  73 6f 6c 61 6e 61                                 |solana|\n"
+/// );
+/// ```
+pub fn hexdump(data: &[u8]) -> String {
+    let mut out = String::new();
+    for (line, chunk) in data.chunks(16).enumerate() {
+        let _ = write!(out, "{:08x} ", line * 16);
+        for i in 0..16 {
+            if i == 8 {
+                out.push(' ');
+            }
+            match chunk.get(i) {
+                Some(byte) => {
+                    let _ = write!(out, " {:02x}", byte);
+                }
+                None => out.push_str("   "),
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|b| {
+                if b.is_ascii_graphic() || *b == b' ' {
+                    *b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        let _ = writeln!(out, "  |{}|", ascii);
+    }
+    out
+}
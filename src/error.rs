@@ -1,3 +1,6 @@
+use crate::extensions::banks_client::PollTimeout;
+use solana_program_test::BanksClientError;
+use solana_transaction_error::TransactionError;
 use thiserror::Error;
 
 /// Errors that can occur when using the AccountBuilder.
@@ -18,7 +21,7 @@ pub enum AccountGenError {
     /// An error occurred during deserialization.
     #[error("Failed to deserialize data: {0}")]
     DeserializationError(std::io::Error),
-    
+
     /// A generic IO error.
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -31,6 +34,14 @@ pub enum AccountGenError {
     #[error("Insufficient balance for rent exemption: required {required} but got {actual}")]
     InsufficientBalance { required: u64, actual: u64 },
 
+    /// Account data exceeds a runtime size limit.
+    #[error("Account data too large: {len} bytes exceeds the limit of {max}")]
+    DataTooLarge { len: usize, max: usize },
+
+    /// An executable account is not a plausible program account.
+    #[error("Invalid executable account: {0}")]
+    InvalidExecutable(String),
+
     /// Program file not found.
     #[error("Program file not found: {0}")]
     ProgramFileNotFound(String),
@@ -38,4 +49,48 @@ pub enum AccountGenError {
     /// Invalid Anchor discriminator.
     #[error("Invalid Anchor discriminator: {0}")]
     InvalidAnchorDiscriminator(String),
-} 
\ No newline at end of file
+
+    /// An RPC request failed or returned an unexpected response.
+    #[error("RPC error: {0}")]
+    RpcError(String),
+
+    /// A fixture database could not be read or written.
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+
+    /// A transaction failed on a test backend.
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(TransactionError),
+
+    /// A BanksClient request failed for a reason other than the
+    /// transaction, such as the connection to the bank being closed.
+    #[error("BanksClient error: {0}")]
+    BanksClientError(BanksClientError),
+
+    /// A polling helper gave up waiting.
+    #[error("Timed out: {0}")]
+    Timeout(PollTimeout),
+
+    /// A test backend could not carry out a request.
+    #[error("Backend error: {0}")]
+    BackendError(String),
+
+    /// A fixture account violates the constraints a program places on it.
+    #[error("Constraint violation: {0}")]
+    ConstraintViolation(String),
+}
+
+/// Failed and simulated-failed transactions become
+/// [`AccountGenError::TransactionFailed`], so callers can match on the
+/// [`TransactionError`] whichever way the transaction was sent.
+impl From<BanksClientError> for AccountGenError {
+    fn from(error: BanksClientError) -> Self {
+        match error {
+            BanksClientError::TransactionError(error)
+            | BanksClientError::SimulationError { err: error, .. } => {
+                AccountGenError::TransactionFailed(error)
+            }
+            error => AccountGenError::BanksClientError(error),
+        }
+    }
+}
@@ -6,6 +6,7 @@
 //! - Creating accounts with Anchor's 8-byte discriminator
 //! - Creating PDAs with proper discriminators
 //! - Building Anchor instructions with method discriminators
+//! - Listing an instruction's accounts by name with [`anchor_accounts!`](crate::anchor_accounts)
 //! - Deserializing Anchor account data
 //!
 //! # Anchor Discriminators
@@ -25,11 +26,17 @@
 //! - Building instructions that Anchor programs can properly decode
 //! - Extracting account data from Anchor accounts for verification
 
-use crate::{AccountBuilder, AccountGenError};
+use super::idl::snake_case;
+use crate::{AccountBuilder, AccountGenError, IntoLamports, well_known};
 use sha2::{Digest, Sha256};
 use solana_account::Account;
 use solana_instruction::{AccountMeta, Instruction};
 use solana_pubkey::Pubkey;
+use solana_sdk_ids::sysvar;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
 
 /// Creates an account with Anchor's discriminator prefix.
 ///
@@ -42,7 +49,7 @@ use solana_pubkey::Pubkey;
 /// * `account_type` - The name of the account type in your Anchor program
 /// * `program_id` - The program ID that owns this account
 /// * `data` - The account data (without discriminator)
-/// * `lamports` - The balance in lamports for this account
+/// * `lamports` - The balance for this account, in lamports or as [`Sol`](crate::Sol)
 ///
 /// # Returns
 ///
@@ -51,7 +58,7 @@ pub fn create_anchor_account<T: borsh::BorshSerialize>(
     account_type: &str,
     program_id: Pubkey,
     data: T,
-    lamports: u64,
+    lamports: impl IntoLamports,
 ) -> Result<Account, AccountGenError> {
     // Calculate Anchor's discriminator
     let discriminator = get_account_discriminator(account_type);
@@ -130,6 +137,194 @@ pub fn create_anchor_instruction<T: borsh::BorshSerialize>(
     })
 }
 
+/// Addresses that can be looked up by name, for building an instruction's
+/// account list with [`anchor_accounts!`](crate::anchor_accounts).
+///
+/// Implemented for maps from names to pubkeys and for
+/// [`Scenario`](super::scenarios::Scenario).
+pub trait AccountLabels {
+    /// Returns the address labeled `name`, if there is one.
+    fn account_label(&self, name: &str) -> Option<Pubkey>;
+}
+
+impl<K, S> AccountLabels for HashMap<K, Pubkey, S>
+where
+    K: Borrow<str> + Eq + Hash,
+    S: BuildHasher,
+{
+    fn account_label(&self, name: &str) -> Option<Pubkey> {
+        self.get(name).copied()
+    }
+}
+
+impl<K: Borrow<str> + Ord> AccountLabels for BTreeMap<K, Pubkey> {
+    fn account_label(&self, name: &str) -> Option<Pubkey> {
+        self.get(name).copied()
+    }
+}
+
+impl AccountLabels for [(&str, Pubkey)] {
+    fn account_label(&self, name: &str) -> Option<Pubkey> {
+        self.iter()
+            .find(|(label, _)| *label == name)
+            .map(|(_, pubkey)| *pubkey)
+    }
+}
+
+impl<const N: usize> AccountLabels for [(&str, Pubkey); N] {
+    fn account_label(&self, name: &str) -> Option<Pubkey> {
+        self.as_slice().account_label(name)
+    }
+}
+
+/// One entry of an instruction's account list, named as in the program's
+/// accounts struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountSpec<'a> {
+    /// The field name of the account.
+    pub name: &'a str,
+    /// Whether the account is marked `mut`.
+    pub is_writable: bool,
+    /// Whether the account must sign.
+    pub is_signer: bool,
+}
+
+impl<'a> AccountSpec<'a> {
+    /// Creates a read-only, non-signer entry.
+    pub const fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            is_writable: false,
+            is_signer: false,
+        }
+    }
+
+    /// Marks the account writable.
+    pub const fn writable(mut self) -> Self {
+        self.is_writable = true;
+        self
+    }
+
+    /// Marks the account as a signer.
+    pub const fn signer(mut self) -> Self {
+        self.is_signer = true;
+        self
+    }
+}
+
+/// Returns the address Anchor programs conventionally give the account
+/// `name`, for programs and sysvars that are the same in every test.
+fn well_known_account(name: &str) -> Option<Pubkey> {
+    Some(match name {
+        "system_program" => well_known::SYSTEM_PROGRAM,
+        "token_program" => well_known::TOKEN_PROGRAM,
+        "token_2022_program" => well_known::TOKEN_2022_PROGRAM,
+        "associated_token_program" => well_known::ASSOCIATED_TOKEN_PROGRAM,
+        "memo_program" => well_known::MEMO_PROGRAM,
+        "token_metadata_program" => well_known::TOKEN_METADATA_PROGRAM,
+        "rent" => sysvar::rent::id(),
+        "clock" => sysvar::clock::id(),
+        "instructions" | "instructions_sysvar" => sysvar::instructions::id(),
+        _ => return None,
+    })
+}
+
+/// Resolves `specs` into account metas, in the given order.
+///
+/// Each name is looked up in `labels` first; names such as
+/// `system_program`, `token_program` and `rent` fall back to their
+/// well-known addresses. Usually called through
+/// [`anchor_accounts!`](crate::anchor_accounts).
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] naming every account that
+/// could not be resolved.
+pub fn resolve_account_metas<L: AccountLabels + ?Sized>(
+    labels: &L,
+    specs: &[AccountSpec],
+) -> Result<Vec<AccountMeta>, AccountGenError> {
+    let mut metas = Vec::with_capacity(specs.len());
+    let mut missing = Vec::new();
+    for spec in specs {
+        let Some(pubkey) = labels
+            .account_label(spec.name)
+            .or_else(|| well_known_account(spec.name))
+        else {
+            missing.push(spec.name);
+            continue;
+        };
+        metas.push(AccountMeta {
+            pubkey,
+            is_signer: spec.is_signer,
+            is_writable: spec.is_writable,
+        });
+    }
+    if !missing.is_empty() {
+        return Err(AccountGenError::InvalidDataFormat(format!(
+            "no account labeled {}",
+            missing
+                .iter()
+                .map(|name| format!("{:?}", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    Ok(metas)
+}
+
+/// Builds an instruction's account metas from the names of its accounts.
+///
+/// Accounts are listed in the order of the program's accounts struct, each
+/// optionally followed by `(mut)`, `(signer)` or `(mut, signer)`, and
+/// looked up by name in anything implementing [`AccountLabels`]
+/// (see [`resolve_account_metas`]). Listing them by name next to their
+/// constraints makes a misordered or missing account easy to spot, where a
+/// list of `AccountMeta::new(...)` calls hides it.
+///
+/// Evaluates to a `Result<Vec<AccountMeta>, AccountGenError>`.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::anchor_accounts;
+/// use solana_accountgen::well_known::SYSTEM_PROGRAM;
+/// use solana_pubkey::Pubkey;
+/// use std::collections::HashMap;
+///
+/// let (game, player) = (Pubkey::new_unique(), Pubkey::new_unique());
+/// let labels = HashMap::from([("game", game), ("player", player)]);
+///
+/// let metas = anchor_accounts!(labels; game(mut), player(mut, signer), system_program).unwrap();
+/// assert_eq!(metas[0].pubkey, game);
+/// assert!(metas[0].is_writable && !metas[0].is_signer);
+/// assert!(metas[1].is_writable && metas[1].is_signer);
+/// assert_eq!(metas[2].pubkey, SYSTEM_PROGRAM);
+/// ```
+#[macro_export]
+macro_rules! anchor_accounts {
+    (@spec $spec:expr) => {
+        $spec
+    };
+    (@spec $spec:expr, mut $(, $rest:tt)*) => {
+        $crate::anchor_accounts!(@spec $spec.writable() $(, $rest)*)
+    };
+    (@spec $spec:expr, signer $(, $rest:tt)*) => {
+        $crate::anchor_accounts!(@spec $spec.signer() $(, $rest)*)
+    };
+    ($labels:expr; $($name:ident $(($($flag:tt),+ $(,)?))?),* $(,)?) => {
+        $crate::extensions::anchor::resolve_account_metas(
+            &$labels,
+            &[$(
+                $crate::anchor_accounts!(
+                    @spec $crate::extensions::anchor::AccountSpec::new(stringify!($name))
+                    $($(, $flag)+)?
+                )
+            ),*],
+        )
+    };
+}
+
 /// Deserializes an Anchor account, skipping the 8-byte discriminator.
 ///
 /// This function extracts the account data from an Anchor account,
@@ -182,7 +377,7 @@ pub fn deserialize_anchor_account<T: borsh::BorshDeserialize>(
 /// * `program_id` - The program ID that owns this account
 /// * `seeds` - The seeds used to derive the PDA
 /// * `data` - The account data (without discriminator)
-/// * `lamports` - The balance in lamports for this account
+/// * `lamports` - The balance for this account, in lamports or as [`Sol`](crate::Sol)
 ///
 /// # Returns
 ///
@@ -224,7 +419,7 @@ pub fn create_anchor_pda<T: borsh::BorshSerialize>(
     program_id: Pubkey,
     seeds: &[&[u8]],
     data: T,
-    lamports: u64,
+    lamports: impl IntoLamports,
 ) -> Result<(Pubkey, u8, Account), AccountGenError> {
     // Find the PDA
     let (pda, bump) = Pubkey::find_program_address(seeds, &program_id);
@@ -282,3 +477,254 @@ pub fn get_method_discriminator(method_name: &str) -> [u8; 8] {
     let hash = hasher.finalize();
     hash[..8].try_into().unwrap()
 }
+
+/// Calculates the Anchor event discriminator for a given event name.
+///
+/// The discriminator is the first 8 bytes of the SHA-256 hash of "event:{event_name}".
+///
+/// # Arguments
+///
+/// * `event_name` - The name of the event struct in your Anchor program
+///
+/// # Returns
+///
+/// An 8-byte array containing the discriminator
+pub fn get_event_discriminator(event_name: &str) -> [u8; 8] {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("event:{}", event_name).as_bytes());
+    let hash = hasher.finalize();
+    hash[..8].try_into().unwrap()
+}
+
+/// What a discriminator found by [`identify_discriminator`] belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiscriminatorKind {
+    /// An account type, hashed as `account:{name}`.
+    Account,
+    /// An instruction, hashed as `global:{name}`.
+    Instruction,
+    /// An event, hashed as `event:{name}`.
+    Event,
+}
+
+impl fmt::Display for DiscriminatorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Account => "account",
+            Self::Instruction => "instruction",
+            Self::Event => "event",
+        })
+    }
+}
+
+/// A name whose discriminator matched.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DiscriminatorMatch {
+    /// What the name is.
+    pub kind: DiscriminatorKind,
+    /// The name, as hashed.
+    pub name: String,
+}
+
+impl fmt::Display for DiscriminatorMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.name)
+    }
+}
+
+/// Finds the names among `names` whose account, instruction or event
+/// discriminator is `discriminator`.
+///
+/// Instruction names are tried both as given and in snake_case, since
+/// Anchor hashes the Rust function name. Use this to put a name to the
+/// first 8 bytes of unknown account or instruction data;
+/// [`Idl::identify_discriminator`](super::idl::Idl::identify_discriminator)
+/// checks an IDL instead.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::anchor::{
+///     DiscriminatorKind, get_method_discriminator, identify_discriminator,
+/// };
+///
+/// let discriminator = get_method_discriminator("initialize_pool");
+/// let matches = identify_discriminator(&discriminator, ["Pool", "initializePool"]);
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!(matches[0].kind, DiscriminatorKind::Instruction);
+/// assert_eq!(matches[0].name, "initialize_pool");
+/// ```
+pub fn identify_discriminator<'a, I>(discriminator: &[u8; 8], names: I) -> Vec<DiscriminatorMatch>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut matches = Vec::new();
+    let mut check = |kind, name: &str, candidate: [u8; 8]| {
+        let found = DiscriminatorMatch {
+            kind,
+            name: name.to_string(),
+        };
+        if candidate == *discriminator && !matches.contains(&found) {
+            matches.push(found);
+        }
+    };
+    for name in names {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        check(
+            DiscriminatorKind::Account,
+            name,
+            get_account_discriminator(name),
+        );
+        check(
+            DiscriminatorKind::Event,
+            name,
+            get_event_discriminator(name),
+        );
+        check(
+            DiscriminatorKind::Instruction,
+            name,
+            get_method_discriminator(name),
+        );
+        let snake = snake_case(name);
+        check(
+            DiscriminatorKind::Instruction,
+            &snake,
+            get_method_discriminator(&snake),
+        );
+    }
+    matches
+}
+
+/// The space a type takes up in an Anchor account, excluding the
+/// discriminator.
+///
+/// This mirrors Anchor's `Space` trait: the value is the largest Borsh
+/// encoding the type can have, which is what `#[account(init, space = ...)]`
+/// must allocate. Derive it with `#[derive(InitSpace)]`, giving `String`
+/// and `Vec` fields their maximum lengths with `#[max_len(...)]` as in
+/// Anchor. Use one length per level of nesting, so a `Vec<String>` of up to
+/// 10 names of up to 32 bytes is `#[max_len(10, 32)]`.
+///
+/// # Example
+///
+/// ```
+/// use borsh::BorshSerialize;
+/// use solana_accountgen::extensions::anchor::{InitSpace, anchor_space_for};
+/// use solana_pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize, InitSpace)]
+/// struct Profile {
+///     owner: Pubkey,
+///     #[max_len(32)]
+///     name: String,
+///     #[max_len(5, 16)]
+///     tags: Vec<String>,
+///     level: Option<u8>,
+/// }
+///
+/// assert_eq!(Profile::INIT_SPACE, 32 + (4 + 32) + (4 + 5 * (4 + 16)) + 2);
+/// assert_eq!(anchor_space_for::<Profile>(), 8 + Profile::INIT_SPACE);
+/// ```
+pub trait InitSpace {
+    /// The maximum encoded size in bytes.
+    const INIT_SPACE: usize;
+}
+
+pub use solana_accountgen_derive::InitSpace;
+
+macro_rules! impl_init_space {
+    ($($ty:ty => $space:expr),* $(,)?) => {
+        $(impl InitSpace for $ty {
+            const INIT_SPACE: usize = $space;
+        })*
+    };
+}
+
+impl_init_space! {
+    () => 0,
+    bool => 1,
+    u8 => 1,
+    i8 => 1,
+    u16 => 2,
+    i16 => 2,
+    u32 => 4,
+    i32 => 4,
+    f32 => 4,
+    u64 => 8,
+    i64 => 8,
+    f64 => 8,
+    u128 => 16,
+    i128 => 16,
+    Pubkey => 32,
+}
+
+impl<T: InitSpace> InitSpace for Option<T> {
+    const INIT_SPACE: usize = 1 + T::INIT_SPACE;
+}
+
+impl<T: InitSpace, const N: usize> InitSpace for [T; N] {
+    const INIT_SPACE: usize = N * T::INIT_SPACE;
+}
+
+/// Returns the space an Anchor `init` allocates for `T`: the 8-byte
+/// discriminator plus [`InitSpace::INIT_SPACE`].
+pub const fn anchor_space_for<T: InitSpace>() -> usize {
+    8 + T::INIT_SPACE
+}
+
+/// Creates an Anchor account sized exactly as `init` would size it.
+///
+/// The data is the discriminator and the Borsh encoding of `data`,
+/// zero-padded to [`anchor_space_for::<T>()`](anchor_space_for), and the
+/// balance is the rent-exempt minimum for that size. An encoding larger
+/// than `T::INIT_SPACE` means the `#[max_len]` annotations are too small
+/// and returns [`AccountGenError::DataTooLarge`], which would otherwise
+/// only show up as a failed transaction on a real cluster.
+///
+/// # Example
+///
+/// ```
+/// use borsh::BorshSerialize;
+/// use solana_accountgen::extensions::anchor::{InitSpace, create_anchor_account_with_space};
+/// use solana_pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize, InitSpace)]
+/// struct Note {
+///     #[max_len(4)]
+///     text: String,
+/// }
+///
+/// let program_id = Pubkey::new_unique();
+/// let account =
+///     create_anchor_account_with_space("Note", program_id, Note { text: "hi".into() }).unwrap();
+/// assert_eq!(account.data.len(), 8 + 4 + 4);
+///
+/// let err = create_anchor_account_with_space("Note", program_id, Note { text: "hello".into() });
+/// assert!(err.is_err());
+/// ```
+pub fn create_anchor_account_with_space<T: borsh::BorshSerialize + InitSpace>(
+    account_type: &str,
+    program_id: Pubkey,
+    data: T,
+) -> Result<Account, AccountGenError> {
+    let encoded = borsh::to_vec(&data)?;
+    if encoded.len() > T::INIT_SPACE {
+        return Err(AccountGenError::DataTooLarge {
+            len: encoded.len(),
+            max: T::INIT_SPACE,
+        });
+    }
+
+    let mut account_data = Vec::with_capacity(anchor_space_for::<T>());
+    account_data.extend_from_slice(&get_account_discriminator(account_type));
+    account_data.extend_from_slice(&encoded);
+    account_data.resize(anchor_space_for::<T>(), 0);
+
+    AccountBuilder::new()
+        .owner(program_id)
+        .data_raw(account_data)
+        .try_build()
+}
@@ -5,73 +5,288 @@
 //! ProgramTestBanksClientExt, this implementation adds additional
 //! methods and is designed to work seamlessly with solana-accountgen.
 
-use solana_banks_client::BanksClient;
-use solana_program::pubkey::Pubkey;
-use solana_sdk::{
-    hash::Hash,
-    signature::{Keypair, Signer},
-    transaction::Transaction,
-};
-use std::io;
+use crate::{AccountGenError, AccountMap};
+use solana_hash::Hash;
+use solana_program_test::{BanksClient, tokio};
+use solana_pubkey::Pubkey;
+use solana_transaction::Transaction;
+use std::fmt;
 use std::time::{Duration, Instant};
 
+/// How a polling helper of [`BanksClientExt`] retries.
+///
+/// The helper checks, sleeps for the current delay, and checks again,
+/// until it succeeds, has retried `max_retries` times, or `timeout` has
+/// passed. Each delay is the previous one times `backoff`, up to
+/// `max_delay`.
+///
+/// The default polls every 200ms for up to 5 seconds.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::banks_client::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new()
+///     .delay(Duration::from_millis(10))
+///     .backoff(2, Duration::from_millis(50))
+///     .max_retries(5);
+/// let delays: Vec<_> = (0..5).map(|retry| policy.delay_for(retry).as_millis()).collect();
+/// assert_eq!(delays, [10, 20, 40, 50, 50]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The most retries after the first attempt, or `None` to retry until
+    /// the timeout.
+    pub max_retries: Option<u32>,
+    /// The delay before the first retry.
+    pub delay: Duration,
+    /// The factor each delay is multiplied by for the next one.
+    pub backoff: u32,
+    /// The longest delay between two attempts.
+    pub max_delay: Duration,
+    /// How long to keep retrying.
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// Creates the default policy: every 200ms for up to 5 seconds.
+    pub fn new() -> Self {
+        Self {
+            max_retries: None,
+            delay: Duration::from_millis(200),
+            backoff: 1,
+            max_delay: Duration::from_millis(200),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Gives up after `retries` retries.
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = Some(retries);
+        self
+    }
+
+    /// Sets a fixed delay between attempts.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self.max_delay = self.max_delay.max(delay);
+        self
+    }
+
+    /// Multiplies the delay by `factor` after each retry, up to
+    /// `max_delay`.
+    pub fn backoff(mut self, factor: u32, max_delay: Duration) -> Self {
+        self.backoff = factor.max(1);
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Gives up once `timeout` has passed.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the delay before retry number `retry`, counting from 0.
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let factor = self.backoff.saturating_pow(retry);
+        self.delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Returns true if another attempt is allowed after `retries` retries
+    /// that took `elapsed` so far.
+    fn should_retry(&self, retries: u32, elapsed: Duration) -> bool {
+        elapsed < self.timeout && self.max_retries.is_none_or(|max| retries < max)
+    }
+}
+
+/// Why a polling helper gave up, carried by [`AccountGenError::Timeout`].
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::AccountGenError;
+/// use solana_accountgen::extensions::banks_client::{BanksClientExt, RetryPolicy};
+/// use solana_program_test::ProgramTest;
+///
+/// # async fn run() {
+/// let (mut banks_client, _payer, blockhash) = ProgramTest::default().start().await;
+/// let policy = RetryPolicy::new().max_retries(0);
+/// match banks_client.get_new_latest_blockhash_with_policy(&blockhash, &policy).await {
+///     Ok(blockhash) => println!("new blockhash {}", blockhash),
+///     Err(AccountGenError::Timeout(timeout)) => {
+///         println!("gave up after {} retries", timeout.retries)
+///     }
+///     Err(e) => panic!("the bank is gone: {}", e),
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PollTimeout {
+    /// What was being waited for.
+    pub waiting_for: String,
+    /// How long the helper polled.
+    pub elapsed: Duration,
+    /// How many times it retried after the first attempt.
+    pub retries: u32,
+}
+
+impl fmt::Display for PollTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "gave up waiting for {} after {}ms (retried {} times)",
+            self.waiting_for,
+            self.elapsed.as_millis(),
+            self.retries
+        )
+    }
+}
+
+impl std::error::Error for PollTimeout {}
+
 /// Extension trait for BanksClient to add useful testing methods.
-#[async_trait::async_trait]
+#[allow(async_fn_in_trait)]
 pub trait BanksClientExt {
     /// Get a new latest blockhash, similar to RpcClient::get_latest_blockhash()
     ///
     /// Note: This functionality is similar to Solana's ProgramTestBanksClientExt,
     /// but is included here for convenience and to provide a complete API.
-    async fn get_new_latest_blockhash(&mut self, blockhash: &Hash) -> io::Result<Hash>;
-    
+    async fn get_new_latest_blockhash(&mut self, blockhash: &Hash)
+    -> Result<Hash, AccountGenError>;
+
+    /// Gets a blockhash other than `blockhash`, polling as `policy` says.
+    ///
+    /// Returns [`AccountGenError::Timeout`] if `policy` gives up first.
+    async fn get_new_latest_blockhash_with_policy(
+        &mut self,
+        blockhash: &Hash,
+        policy: &RetryPolicy,
+    ) -> Result<Hash, AccountGenError>;
+
     /// Process a transaction and wait for confirmation.
     ///
     /// This method processes a transaction and returns an error if the transaction fails.
     /// It's a convenience wrapper around BanksClient::process_transaction that provides
-    /// better error handling.
+    /// better error handling: a failed transaction is an
+    /// [`AccountGenError::TransactionFailed`] holding its `TransactionError`,
+    /// and any other failure an [`AccountGenError::BanksClientError`].
+    ///
+    /// `BanksClient` has an inherent method of the same name, which takes
+    /// precedence in method call syntax; call this one as
+    /// `BanksClientExt::process_transaction_with_preflight(&mut client, tx)`.
     async fn process_transaction_with_preflight(
         &mut self,
         transaction: Transaction,
-    ) -> io::Result<()>;
+    ) -> Result<(), AccountGenError>;
+
+    /// Returns the accounts among `known_pubkeys` that exist and are owned
+    /// by `program_id`.
+    ///
+    /// BanksClient has no `getProgramAccounts`, so this only approximates
+    /// it: accounts at addresses that are not passed in are not found. The
+    /// pubkeys recorded by [`track_pubkeys`](crate::tracking::track_pubkeys)
+    /// cover every address the crate generated, which makes them a good set
+    /// to sweep at the end of a test.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use solana_accountgen::extensions::banks_client::BanksClientExt;
+    /// use solana_accountgen::tracking::track_pubkeys;
+    /// use solana_program_test::ProgramTest;
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// # async fn run(program_id: Pubkey) {
+    /// let tracking = track_pubkeys();
+    /// let (mut banks_client, _payer, _) = ProgramTest::default().start().await;
+    /// // ... run the test ...
+    /// let accounts = banks_client
+    ///     .get_accounts_by_owner(&program_id, tracking.pubkeys())
+    ///     .await
+    ///     .unwrap();
+    /// println!("the program owns {} accounts", accounts.len());
+    /// # }
+    /// ```
+    async fn get_accounts_by_owner<I>(
+        &mut self,
+        program_id: &Pubkey,
+        known_pubkeys: I,
+    ) -> Result<AccountMap, AccountGenError>
+    where
+        I: IntoIterator<Item = Pubkey>;
 }
 
-#[async_trait::async_trait]
 impl BanksClientExt for BanksClient {
-    async fn get_new_latest_blockhash(&mut self, blockhash: &Hash) -> io::Result<Hash> {
-        let mut num_retries = 0;
+    async fn get_new_latest_blockhash(
+        &mut self,
+        blockhash: &Hash,
+    ) -> Result<Hash, AccountGenError> {
+        self.get_new_latest_blockhash_with_policy(blockhash, &RetryPolicy::default())
+            .await
+    }
+
+    async fn get_new_latest_blockhash_with_policy(
+        &mut self,
+        blockhash: &Hash,
+        policy: &RetryPolicy,
+    ) -> Result<Hash, AccountGenError> {
+        let mut retries = 0;
         let start = Instant::now();
-        while start.elapsed().as_secs() < 5 {
+        loop {
             let new_blockhash = self.get_latest_blockhash().await?;
             if new_blockhash != *blockhash {
                 return Ok(new_blockhash);
             }
-            
-            tokio::time::sleep(Duration::from_millis(200)).await;
-            num_retries += 1;
+            if !policy.should_retry(retries, start.elapsed()) {
+                break;
+            }
+
+            tokio::time::sleep(policy.delay_for(retries)).await;
+            retries += 1;
         }
 
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!(
-                "Unable to get new blockhash after {}ms (retried {} times), stuck at {}",
-                start.elapsed().as_millis(),
-                num_retries,
-                blockhash
-            ),
-        ))
+        Err(AccountGenError::Timeout(PollTimeout {
+            waiting_for: format!("a blockhash other than {}", blockhash),
+            elapsed: start.elapsed(),
+            retries,
+        }))
     }
-    
+
     async fn process_transaction_with_preflight(
         &mut self,
         transaction: Transaction,
-    ) -> io::Result<()> {
-        self.process_transaction(transaction.clone()).await.map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Transaction failed: {:?}", e),
-            )
-        })?;
-        
-        Ok(())
+    ) -> Result<(), AccountGenError> {
+        Ok(self.process_transaction(transaction).await?)
     }
-} 
\ No newline at end of file
+
+    async fn get_accounts_by_owner<I>(
+        &mut self,
+        program_id: &Pubkey,
+        known_pubkeys: I,
+    ) -> Result<AccountMap, AccountGenError>
+    where
+        I: IntoIterator<Item = Pubkey>,
+    {
+        let mut accounts = AccountMap::new();
+        for pubkey in known_pubkeys {
+            if accounts.get_account(&pubkey).is_some() {
+                continue;
+            }
+            if let Some(account) = self.get_account(pubkey).await?
+                && account.owner == *program_id
+            {
+                accounts.set_account(pubkey, account);
+            }
+        }
+        Ok(accounts)
+    }
+}
@@ -0,0 +1,194 @@
+//! Fixtures for widely used community programs.
+//!
+//! # SPL Name Service
+//!
+//! Name records are accounts of the Name Service program holding a
+//! [`NameRecordHeader`] followed by free-form data. A record's address is a
+//! PDA of the hashed name, its class and its parent, so programs that
+//! resolve `.sol` domains derive the address themselves and only work
+//! against records at exactly that address. [`sol_domain`] and
+//! [`reverse_lookup`] create the records the Solana Name Service (SNS) uses
+//! for a domain and for resolving an owner back to the domain.
+//!
+//! ```
+//! use solana_accountgen::extensions::community::{NameRecordHeader, sol_domain, sol_domain_key};
+//! use solana_pubkey::Pubkey;
+//!
+//! let owner = Pubkey::new_unique();
+//! let (key, account) = sol_domain("alice.sol", &owner, 0).unwrap();
+//!
+//! assert_eq!(key, sol_domain_key("alice"));
+//! let header = NameRecordHeader::unpack(&account.data).unwrap();
+//! assert_eq!(header.owner, owner);
+//! ```
+//!
+//! # SPL Memo
+//!
+//! The Memo program has no accounts. Programs that require a memo, like
+//! Token-2022's memo transfer extension, look for a memo instruction right
+//! before their own in the Instructions sysvar. [`with_memo`] and
+//! [`memo_instructions_sysvar`] set up that pattern.
+
+use crate::extensions::sysvars::create_instructions_sysvar_account;
+use crate::instructions::memo;
+use crate::well_known::NAME_SERVICE_PROGRAM;
+use crate::{AccountBuilder, AccountGenError};
+use borsh::{BorshDeserialize, BorshSerialize};
+use sha2::{Digest, Sha256};
+use solana_account::Account;
+use solana_instruction::Instruction;
+use solana_pubkey::{Pubkey, pubkey};
+
+/// The prefix hashed with every name.
+pub const HASH_PREFIX: &str = "SPL Name Service";
+
+/// The `.sol` top-level domain record, the parent of every `.sol` domain.
+pub const SOL_TLD_AUTHORITY: Pubkey = pubkey!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx");
+
+/// The class of SNS reverse lookup records.
+pub const REVERSE_LOOKUP_CLASS: Pubkey = pubkey!("33m47vH6Eav6jr5Ry86XjhRft2jRBLDnDgPSHoquXi2Z");
+
+/// The header at the start of every name record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct NameRecordHeader {
+    /// The parent name record, or the default pubkey for a root name.
+    pub parent_name: Pubkey,
+    /// The account that may update or transfer the record.
+    pub owner: Pubkey,
+    /// The class, an optional key that must sign record creation, or the
+    /// default pubkey.
+    pub class: Pubkey,
+}
+
+impl NameRecordHeader {
+    /// Size of the header.
+    pub const LEN: usize = 96;
+
+    /// Reads the header from the start of a name record's data.
+    pub fn unpack(data: &[u8]) -> Result<Self, AccountGenError> {
+        let header = data.get(..Self::LEN).ok_or_else(|| {
+            AccountGenError::InvalidDataFormat(format!(
+                "name record data is {} bytes, shorter than its {}-byte header",
+                data.len(),
+                Self::LEN
+            ))
+        })?;
+        Self::try_from_slice(header).map_err(AccountGenError::DeserializationError)
+    }
+}
+
+/// Hashes a name as the Name Service does to derive its address.
+pub fn hashed_name(name: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(HASH_PREFIX.as_bytes());
+    hasher.update(name.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Derives the address of the name record for `hashed_name` with the given
+/// class and parent.
+pub fn name_account_key(
+    hashed_name: &[u8; 32],
+    class: Option<&Pubkey>,
+    parent: Option<&Pubkey>,
+) -> Pubkey {
+    let class = class.copied().unwrap_or_default();
+    let parent = parent.copied().unwrap_or_default();
+    let seeds: &[&[u8]] = &[hashed_name, class.as_ref(), parent.as_ref()];
+    Pubkey::find_program_address(seeds, &NAME_SERVICE_PROGRAM).0
+}
+
+/// Creates a rent-exempt name record holding `data` after the header.
+pub fn create_name_record(
+    header: &NameRecordHeader,
+    data: &[u8],
+) -> Result<Account, AccountGenError> {
+    let mut account_data = borsh::to_vec(header)?;
+    account_data.extend_from_slice(data);
+    AccountBuilder::new()
+        .owner(NAME_SERVICE_PROGRAM)
+        .data_raw(account_data)
+        .try_build()
+}
+
+/// Returns the address of a `.sol` domain, given with or without the
+/// `.sol` suffix.
+pub fn sol_domain_key(domain: &str) -> Pubkey {
+    let name = domain.strip_suffix(".sol").unwrap_or(domain);
+    name_account_key(&hashed_name(name), None, Some(&SOL_TLD_AUTHORITY))
+}
+
+/// Creates the name record of a `.sol` domain owned by `owner`, with
+/// `space` zeroed bytes of data after the header, as registration
+/// allocates.
+pub fn sol_domain(
+    domain: &str,
+    owner: &Pubkey,
+    space: usize,
+) -> Result<(Pubkey, Account), AccountGenError> {
+    let header = NameRecordHeader {
+        parent_name: SOL_TLD_AUTHORITY,
+        owner: *owner,
+        class: Pubkey::default(),
+    };
+    let account = create_name_record(&header, &vec![0; space])?;
+    Ok((sol_domain_key(domain), account))
+}
+
+/// Creates the SNS reverse lookup record that resolves the domain record
+/// at `domain_key` back to `domain`, without the `.sol` suffix.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::community::{NameRecordHeader, reverse_lookup, sol_domain_key};
+///
+/// let (_, account) = reverse_lookup(&sol_domain_key("alice"), "alice").unwrap();
+/// let name: String = borsh::from_slice(&account.data[NameRecordHeader::LEN..]).unwrap();
+/// assert_eq!(name, "alice");
+/// ```
+pub fn reverse_lookup(
+    domain_key: &Pubkey,
+    domain: &str,
+) -> Result<(Pubkey, Account), AccountGenError> {
+    let hashed = hashed_name(&domain_key.to_string());
+    let key = name_account_key(&hashed, Some(&REVERSE_LOOKUP_CLASS), None);
+    let header = NameRecordHeader {
+        parent_name: Pubkey::default(),
+        owner: REVERSE_LOOKUP_CLASS,
+        class: REVERSE_LOOKUP_CLASS,
+    };
+    let name = domain.strip_suffix(".sol").unwrap_or(domain);
+    let account = create_name_record(&header, &borsh::to_vec(name)?)?;
+    Ok((key, account))
+}
+
+/// Returns `instruction` preceded by a memo signed by `signers`, the order
+/// memo-checking programs expect.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::community::with_memo;
+/// use solana_accountgen::well_known::MEMO_PROGRAM;
+/// use solana_instruction::Instruction;
+/// use solana_pubkey::Pubkey;
+///
+/// let transfer = Instruction::new_with_bytes(Pubkey::new_unique(), &[3], vec![]);
+/// let instructions = with_memo("invoice 42", &[], transfer);
+/// assert_eq!(instructions[0].program_id, MEMO_PROGRAM);
+/// assert_eq!(instructions[0].data, b"invoice 42");
+/// ```
+pub fn with_memo(text: &str, signers: &[&Pubkey], instruction: Instruction) -> Vec<Instruction> {
+    vec![memo(text, signers), instruction]
+}
+
+/// Creates the Instructions sysvar a program sees when `instruction` runs
+/// right after a memo, for programs that check for one.
+pub fn memo_instructions_sysvar(
+    text: &str,
+    signers: &[&Pubkey],
+    instruction: Instruction,
+) -> Account {
+    create_instructions_sysvar_account(&with_memo(text, signers, instruction), 1)
+}
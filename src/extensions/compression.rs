@@ -0,0 +1,455 @@
+//! Helpers for creating SPL Account Compression Merkle tree accounts.
+//!
+//! Compressed NFTs live as leaves of a concurrent Merkle tree owned by the
+//! SPL Account Compression program. A tree account holds a header, the tree
+//! itself (a ring buffer of recent change logs and a proof of the rightmost
+//! leaf) and an optional canopy caching the top of the tree. This module
+//! builds such accounts in the program's zero-copy layout, either empty or
+//! with a list of leaves appended in order, so that programs reading trees
+//! can be tested without running the compression program first.
+
+use crate::AccountBuilder;
+use crate::AccountGenError;
+use crate::well_known::ACCOUNT_COMPRESSION_PROGRAM;
+use solana_account::Account;
+use solana_keccak_hasher::hashv;
+use solana_pubkey::Pubkey;
+
+/// A tree node: a leaf or the Keccak-256 hash of two children.
+pub type Node = [u8; 32];
+
+/// Size of the account type tag and the V1 tree header.
+pub const TREE_HEADER_LEN: usize = 2 + 54;
+
+/// The (max depth, max buffer size) pairs the compression program accepts.
+pub const SUPPORTED_TREE_SIZES: &[(u32, u32)] = &[
+    (3, 8),
+    (5, 8),
+    (6, 16),
+    (7, 16),
+    (8, 16),
+    (9, 16),
+    (10, 32),
+    (11, 32),
+    (12, 32),
+    (13, 32),
+    (14, 64),
+    (14, 256),
+    (14, 1024),
+    (14, 2048),
+    (15, 64),
+    (16, 64),
+    (17, 64),
+    (18, 64),
+    (19, 64),
+    (20, 64),
+    (20, 256),
+    (20, 1024),
+    (20, 2048),
+    (24, 64),
+    (24, 256),
+    (24, 512),
+    (24, 1024),
+    (24, 2048),
+    (26, 512),
+    (26, 1024),
+    (26, 2048),
+    (30, 512),
+    (30, 1024),
+    (30, 2048),
+];
+
+/// Account type tag of an initialized concurrent Merkle tree.
+const ACCOUNT_TYPE_CONCURRENT_MERKLE_TREE: u8 = 1;
+/// Header version tag of `ConcurrentMerkleTreeHeaderDataV1`.
+const HEADER_VERSION_V1: u8 = 0;
+
+/// Returns the size of a tree account with the given dimensions.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::compression::merkle_tree_account_size;
+///
+/// // The dimensions Bubblegum uses for a 16,384 leaf collection
+/// assert_eq!(merkle_tree_account_size(14, 64, 0), 31_800);
+/// ```
+pub fn merkle_tree_account_size(max_depth: u32, max_buffer_size: u32, canopy_depth: u32) -> usize {
+    let depth = max_depth as usize;
+    let change_log = 32 + 32 * depth + 8;
+    let rightmost_proof = 32 * depth + 32 + 8;
+    let tree = 24 + max_buffer_size as usize * change_log + rightmost_proof;
+    let canopy = ((1usize << (canopy_depth + 1)) - 2) * 32;
+    TREE_HEADER_LEN + tree + canopy
+}
+
+/// Returns the root of an empty subtree of the given height.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::compression::empty_node;
+///
+/// assert_eq!(empty_node(0), [0; 32]);
+/// assert_ne!(empty_node(1), [0; 32]);
+/// ```
+pub fn empty_node(height: u32) -> Node {
+    (0..height).fold([0; 32], |node, _| hash_pair(&node, &node))
+}
+
+/// Computes the root of a tree of `max_depth` holding `leaves` from index 0.
+///
+/// Missing leaves are empty. This is independent of the account layout,
+/// so it can be used to check the root an indexer or program computes.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] if there are more leaves
+/// than a tree of `max_depth` holds.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::compression::{empty_node, merkle_root};
+///
+/// assert_eq!(merkle_root(&[], 14).unwrap(), empty_node(14));
+/// assert!(merkle_root(&[[1; 32], [2; 32], [3; 32]], 1).is_err());
+/// ```
+pub fn merkle_root(leaves: &[Node], max_depth: u32) -> Result<Node, AccountGenError> {
+    check_capacity(leaves.len(), max_depth)?;
+    let levels = tree_levels(leaves, max_depth);
+    Ok(levels[max_depth as usize]
+        .first()
+        .copied()
+        .unwrap_or(empty_node(max_depth)))
+}
+
+/// A builder for concurrent Merkle tree accounts.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::compression::{
+///     MerkleTreeBuilder, merkle_root, merkle_tree_account_size,
+/// };
+/// use solana_accountgen::well_known::ACCOUNT_COMPRESSION_PROGRAM;
+/// use solana_pubkey::Pubkey;
+///
+/// let leaves = [[1; 32], [2; 32], [3; 32]];
+/// let builder = MerkleTreeBuilder::new(14, 64)
+///     .canopy_depth(3)
+///     .authority(Pubkey::new_unique())
+///     .leaves(leaves);
+///
+/// assert_eq!(builder.root().unwrap(), merkle_root(&leaves, 14).unwrap());
+///
+/// let account = builder.build().unwrap();
+/// assert_eq!(account.owner, ACCOUNT_COMPRESSION_PROGRAM);
+/// assert_eq!(account.data.len(), merkle_tree_account_size(14, 64, 3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MerkleTreeBuilder {
+    max_depth: u32,
+    max_buffer_size: u32,
+    canopy_depth: u32,
+    authority: Pubkey,
+    creation_slot: u64,
+    leaves: Vec<Node>,
+}
+
+impl MerkleTreeBuilder {
+    /// Creates a builder for an empty tree with the given dimensions.
+    ///
+    /// The authority defaults to the default pubkey, the creation slot to 0
+    /// and the canopy depth to 0.
+    pub fn new(max_depth: u32, max_buffer_size: u32) -> Self {
+        Self {
+            max_depth,
+            max_buffer_size,
+            canopy_depth: 0,
+            authority: Pubkey::default(),
+            creation_slot: 0,
+            leaves: Vec::new(),
+        }
+    }
+
+    /// Sets how many levels below the root are cached in the account.
+    pub fn canopy_depth(mut self, canopy_depth: u32) -> Self {
+        self.canopy_depth = canopy_depth;
+        self
+    }
+
+    /// Sets the tree authority, which may append to and modify the tree.
+    pub fn authority(mut self, authority: Pubkey) -> Self {
+        self.authority = authority;
+        self
+    }
+
+    /// Sets the slot at which the tree was created.
+    pub fn creation_slot(mut self, creation_slot: u64) -> Self {
+        self.creation_slot = creation_slot;
+        self
+    }
+
+    /// Appends a leaf.
+    pub fn leaf(mut self, leaf: Node) -> Self {
+        self.leaves.push(leaf);
+        self
+    }
+
+    /// Appends several leaves in order.
+    pub fn leaves(mut self, leaves: impl IntoIterator<Item = Node>) -> Self {
+        self.leaves.extend(leaves);
+        self
+    }
+
+    /// Returns the root of the tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if there are more
+    /// leaves than the tree holds.
+    pub fn root(&self) -> Result<Node, AccountGenError> {
+        merkle_root(&self.leaves, self.max_depth)
+    }
+
+    /// Returns the proof of the leaf at `index`, from the leaf's sibling up.
+    ///
+    /// Returns `None` if `index` is outside the tree.
+    pub fn proof(&self, index: u32) -> Option<Vec<Node>> {
+        if self.max_depth >= 32 || index as u64 >= 1u64 << self.max_depth {
+            return None;
+        }
+        let levels = tree_levels(&self.leaves, self.max_depth);
+        let proof = (0..self.max_depth as usize)
+            .map(|height| {
+                let sibling = ((index >> height) ^ 1) as usize;
+                levels[height]
+                    .get(sibling)
+                    .copied()
+                    .unwrap_or(empty_node(height as u32))
+            })
+            .collect();
+        Some(proof)
+    }
+
+    /// Builds the account data.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the dimensions are
+    /// not supported by the compression program, the canopy is not shallower
+    /// than the tree, or there are more leaves than the tree holds.
+    pub fn build_data(&self) -> Result<Vec<u8>, AccountGenError> {
+        self.validate()?;
+
+        let mut data = Vec::with_capacity(merkle_tree_account_size(
+            self.max_depth,
+            self.max_buffer_size,
+            self.canopy_depth,
+        ));
+
+        // ConcurrentMerkleTreeHeader
+        data.push(ACCOUNT_TYPE_CONCURRENT_MERKLE_TREE);
+        data.push(HEADER_VERSION_V1);
+        data.extend_from_slice(&self.max_buffer_size.to_le_bytes());
+        data.extend_from_slice(&self.max_depth.to_le_bytes());
+        data.extend_from_slice(self.authority.as_ref());
+        data.extend_from_slice(&self.creation_slot.to_le_bytes());
+        data.push(0); // is_batch_initialized
+        data.extend_from_slice(&[0; 5]);
+
+        // ConcurrentMerkleTree
+        let tree = self.tree_state();
+        data.extend_from_slice(&tree.sequence_number.to_le_bytes());
+        data.extend_from_slice(&tree.active_index.to_le_bytes());
+        data.extend_from_slice(&tree.buffer_size.to_le_bytes());
+        for change_log in &tree.change_logs {
+            data.extend_from_slice(&change_log.root);
+            change_log
+                .path
+                .iter()
+                .for_each(|node| data.extend_from_slice(node));
+            data.extend_from_slice(&change_log.index.to_le_bytes());
+            data.extend_from_slice(&[0; 4]);
+        }
+        tree.rightmost_proof
+            .iter()
+            .for_each(|node| data.extend_from_slice(node));
+        data.extend_from_slice(&tree.rightmost_leaf);
+        data.extend_from_slice(&tree.rightmost_index.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+
+        // Canopy, indexed like a binary heap without the root
+        let levels = tree_levels(&self.leaves, self.max_depth);
+        for heap_level in 1..=self.canopy_depth {
+            let height = (self.max_depth - heap_level) as usize;
+            for position in 0..1usize << heap_level {
+                let node = levels[height].get(position).copied().unwrap_or([0; 32]);
+                data.extend_from_slice(&node);
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Builds the tree account, owned by the compression program with a
+    /// rent-exempt balance.
+    pub fn build(&self) -> Result<Account, AccountGenError> {
+        AccountBuilder::new()
+            .owner(ACCOUNT_COMPRESSION_PROGRAM)
+            .data_raw(self.build_data()?)
+            .try_build()
+    }
+
+    fn validate(&self) -> Result<(), AccountGenError> {
+        if !SUPPORTED_TREE_SIZES.contains(&(self.max_depth, self.max_buffer_size)) {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "unsupported tree size: max depth {} with max buffer size {}",
+                self.max_depth, self.max_buffer_size
+            )));
+        }
+        if self.canopy_depth >= self.max_depth {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "canopy depth {} must be less than max depth {}",
+                self.canopy_depth, self.max_depth
+            )));
+        }
+        check_capacity(self.leaves.len(), self.max_depth)
+    }
+
+    /// Replays the compression program's `initialize` and `append` on the
+    /// change log buffer and rightmost proof.
+    fn tree_state(&self) -> TreeState {
+        let depth = self.max_depth as usize;
+        let empty: Vec<Node> = (0..self.max_depth).map(empty_node).collect();
+        let mut tree = TreeState {
+            sequence_number: 0,
+            active_index: 0,
+            buffer_size: 1,
+            change_logs: vec![
+                ChangeLog {
+                    root: [0; 32],
+                    path: vec![[0; 32]; depth],
+                    index: 0,
+                };
+                self.max_buffer_size as usize
+            ],
+            rightmost_proof: empty.clone(),
+            rightmost_leaf: [0; 32],
+            rightmost_index: 0,
+        };
+        tree.change_logs[0].root = empty_node(self.max_depth);
+        tree.change_logs[0].path = empty.clone();
+
+        for leaf in &self.leaves {
+            tree.append(*leaf, &empty, self.max_buffer_size as u64);
+        }
+        tree
+    }
+}
+
+#[derive(Clone)]
+struct ChangeLog {
+    root: Node,
+    path: Vec<Node>,
+    index: u32,
+}
+
+struct TreeState {
+    sequence_number: u64,
+    active_index: u64,
+    buffer_size: u64,
+    change_logs: Vec<ChangeLog>,
+    rightmost_proof: Vec<Node>,
+    rightmost_leaf: Node,
+    rightmost_index: u32,
+}
+
+impl TreeState {
+    fn append(&mut self, leaf: Node, empty: &[Node], max_buffer_size: u64) {
+        let index = self.rightmost_index;
+        let mut node = leaf;
+        let mut path = Vec::with_capacity(empty.len());
+
+        if index == 0 {
+            // The first append proves the leaf against the empty proof
+            for sibling in &self.rightmost_proof {
+                path.push(node);
+                node = hash_pair(&node, sibling);
+            }
+        } else {
+            let intersection = index.trailing_zeros() as usize;
+            let mut intersection_node = self.rightmost_leaf;
+            for (height, empty_sibling) in empty.iter().enumerate() {
+                path.push(node);
+                let previous_is_left = ((index - 1) >> height) & 1 == 0;
+                if height < intersection {
+                    intersection_node = if previous_is_left {
+                        hash_pair(&intersection_node, &self.rightmost_proof[height])
+                    } else {
+                        hash_pair(&self.rightmost_proof[height], &intersection_node)
+                    };
+                    node = hash_pair(&node, empty_sibling);
+                    self.rightmost_proof[height] = *empty_sibling;
+                } else if height == intersection {
+                    node = hash_pair(&intersection_node, &node);
+                    self.rightmost_proof[height] = intersection_node;
+                } else if previous_is_left {
+                    node = hash_pair(&node, &self.rightmost_proof[height]);
+                } else {
+                    node = hash_pair(&self.rightmost_proof[height], &node);
+                }
+            }
+        }
+
+        self.sequence_number += 1;
+        if self.buffer_size < max_buffer_size {
+            self.buffer_size += 1;
+        }
+        self.active_index = (self.active_index + 1) % max_buffer_size;
+        self.change_logs[self.active_index as usize] = ChangeLog {
+            root: node,
+            path,
+            index,
+        };
+        self.rightmost_index = index + 1;
+        self.rightmost_leaf = leaf;
+    }
+}
+
+fn hash_pair(left: &Node, right: &Node) -> Node {
+    hashv(&[left, right]).to_bytes()
+}
+
+/// Returns the non-empty nodes at each height, from the leaves (height 0)
+/// up to the root (height `max_depth`).
+fn check_capacity(leaves: usize, max_depth: u32) -> Result<(), AccountGenError> {
+    let fits = 1u64
+        .checked_shl(max_depth)
+        .is_none_or(|capacity| leaves as u64 <= capacity);
+    if !fits {
+        return Err(AccountGenError::InvalidDataFormat(format!(
+            "{} leaves do not fit in a tree of depth {}",
+            leaves, max_depth
+        )));
+    }
+    Ok(())
+}
+
+fn tree_levels(leaves: &[Node], max_depth: u32) -> Vec<Vec<Node>> {
+    let mut levels = vec![leaves.to_vec()];
+    for height in 0..max_depth {
+        let below = &levels[height as usize];
+        let level = below
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(left, right),
+                [left] => hash_pair(left, &empty_node(height)),
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(level);
+    }
+    levels
+}
@@ -0,0 +1,308 @@
+//! Checking fixture accounts against Anchor account constraints.
+//!
+//! A fixture that does not satisfy the constraints of the instruction it is
+//! passed to fails at runtime with an Anchor error code such as
+//! `ConstraintSeeds` or `AccountDiscriminatorMismatch`, which says little
+//! about what is wrong with the fixture. [`AccountConstraints`] runs the
+//! same checks as Anchor's `Account<'info, T>` and its `seeds`, `bump` and
+//! `rent_exempt` constraints when the fixture is set up, and reports every
+//! violation with the expected and actual values.
+//!
+//! # Example
+//!
+//! ```
+//! use borsh::{BorshDeserialize, BorshSerialize};
+//! use solana_accountgen::extensions::anchor::create_anchor_pda;
+//! use solana_accountgen::extensions::constraints::AccountConstraints;
+//! use solana_pubkey::Pubkey;
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! struct Vault {
+//!     authority: Pubkey,
+//! }
+//!
+//! let program_id = Pubkey::new_unique();
+//! let authority = Pubkey::new_unique();
+//! let seeds: &[&[u8]] = &[b"vault", authority.as_ref()];
+//! let (pda, _, account) =
+//!     create_anchor_pda("Vault", program_id, seeds, Vault { authority }, 10_000_000).unwrap();
+//!
+//! let constraints = AccountConstraints::new(program_id)
+//!     .account_type::<Vault>("Vault")
+//!     .seeds(seeds);
+//! assert!(constraints.check(&pda, &account).is_ok());
+//!
+//! // The same account at the wrong address
+//! let report = constraints.check(&Pubkey::new_unique(), &account);
+//! assert!(report.to_string().contains("ConstraintSeeds"));
+//! ```
+
+use crate::AccountGenError;
+use crate::extensions::anchor::get_account_discriminator;
+use crate::well_known;
+use borsh::BorshDeserialize;
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+use std::fmt;
+
+/// One way an account fails its constraints, named after the Anchor error
+/// it would cause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintViolation {
+    /// The account holds no lamports and is owned by the System Program.
+    AccountNotInitialized,
+    /// The account is owned by another program.
+    Owner { expected: Pubkey, actual: Pubkey },
+    /// The data is shorter than a discriminator.
+    DiscriminatorNotFound,
+    /// The data starts with another type's discriminator.
+    DiscriminatorMismatch { expected: [u8; 8], actual: [u8; 8] },
+    /// The data after the discriminator does not decode as the type.
+    DidNotDeserialize,
+    /// The balance is below the rent-exempt minimum.
+    RentExempt { required: u64, actual: u64 },
+    /// The address is not the PDA of the seeds and bump.
+    Seeds {
+        expected: Option<Pubkey>,
+        actual: Pubkey,
+    },
+}
+
+impl ConstraintViolation {
+    /// Returns the name of the Anchor error the violation causes.
+    pub fn anchor_error(&self) -> &'static str {
+        match self {
+            Self::AccountNotInitialized => "AccountNotInitialized",
+            Self::Owner { .. } => "AccountOwnedByWrongProgram",
+            Self::DiscriminatorNotFound => "AccountDiscriminatorNotFound",
+            Self::DiscriminatorMismatch { .. } => "AccountDiscriminatorMismatch",
+            Self::DidNotDeserialize => "AccountDidNotDeserialize",
+            Self::RentExempt { .. } => "ConstraintRentExempt",
+            Self::Seeds { .. } => "ConstraintSeeds",
+        }
+    }
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.anchor_error())?;
+        match self {
+            Self::AccountNotInitialized => {
+                write!(f, "the account has no lamports and no program owns it")
+            }
+            Self::Owner { expected, actual } => {
+                write!(f, "owner is {}, expected {}", actual, expected)
+            }
+            Self::DiscriminatorNotFound => write!(f, "data is shorter than 8 bytes"),
+            Self::DiscriminatorMismatch { expected, actual } => write!(
+                f,
+                "discriminator is {}, expected {}",
+                hex::encode(actual),
+                hex::encode(expected)
+            ),
+            Self::DidNotDeserialize => {
+                write!(f, "data after the discriminator does not decode")
+            }
+            Self::RentExempt { required, actual } => write!(
+                f,
+                "balance is {} lamports, rent exemption requires {}",
+                actual, required
+            ),
+            Self::Seeds {
+                expected: Some(expected),
+                actual,
+            } => write!(f, "address is {}, the seeds derive {}", actual, expected),
+            Self::Seeds {
+                expected: None,
+                actual,
+            } => write!(
+                f,
+                "address is {}, the seeds and bump derive no valid PDA",
+                actual
+            ),
+        }
+    }
+}
+
+/// The violations found by [`AccountConstraints::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintReport {
+    /// The address of the checked account.
+    pub pubkey: Pubkey,
+    /// Every violation, in the order Anchor checks them.
+    pub violations: Vec<ConstraintViolation>,
+}
+
+impl ConstraintReport {
+    /// Returns true if the account satisfies every constraint.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Converts the report into an error if there are violations.
+    pub fn into_result(self) -> Result<(), AccountGenError> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(AccountGenError::ConstraintViolation(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for ConstraintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "{} satisfies its constraints", self.pubkey);
+        }
+        write!(f, "{} violates its constraints:", self.pubkey)?;
+        for violation in &self.violations {
+            write!(f, "\n  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether account data, after the discriminator, decodes as a type.
+type Decodes = fn(&[u8]) -> bool;
+
+/// The constraints an Anchor program places on an account.
+///
+/// A new value checks what every `Account<'info, T>` requires: that the
+/// program owns the account. Add the type with
+/// [`AccountConstraints::account_type`], and the `seeds`, `bump` and
+/// `rent_exempt` constraints of the accounts struct with the matching
+/// methods.
+#[derive(Debug, Clone)]
+pub struct AccountConstraints {
+    program_id: Pubkey,
+    owner: Pubkey,
+    account_type: Option<(String, Decodes)>,
+    seeds: Option<Vec<Vec<u8>>>,
+    bump: Option<u8>,
+    rent: Option<Rent>,
+}
+
+impl AccountConstraints {
+    /// Creates constraints for an account of `program_id`.
+    pub fn new(program_id: Pubkey) -> Self {
+        Self {
+            program_id,
+            owner: program_id,
+            account_type: None,
+            seeds: None,
+            bump: None,
+            rent: None,
+        }
+    }
+
+    /// Requires another owner, as the `owner` constraint or an account of
+    /// another program's type, such as a token account, does.
+    pub fn owner(mut self, owner: Pubkey) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    /// Requires the discriminator of the account type `name` and data that
+    /// decodes as `T`, as `Account<'info, T>` does.
+    ///
+    /// Like Anchor, decoding ignores trailing bytes, such as the padding of
+    /// an account allocated with `space`.
+    pub fn account_type<T: BorshDeserialize>(mut self, name: &str) -> Self {
+        self.account_type = Some((name.to_string(), decodes::<T>));
+        self
+    }
+
+    /// Requires the address to be the PDA of `seeds` under the program,
+    /// found with the canonical bump unless [`AccountConstraints::bump`]
+    /// gives one.
+    pub fn seeds(mut self, seeds: &[&[u8]]) -> Self {
+        self.seeds = Some(seeds.iter().map(|seed| seed.to_vec()).collect());
+        self
+    }
+
+    /// Derives the PDA with `bump`, as `bump = <expr>` does.
+    pub fn bump(mut self, bump: u8) -> Self {
+        self.bump = Some(bump);
+        self
+    }
+
+    /// Requires a rent-exempt balance under `rent`, as
+    /// `rent_exempt = enforce` does.
+    pub fn rent_exempt(mut self, rent: Rent) -> Self {
+        self.rent = Some(rent);
+        self
+    }
+
+    /// Checks an account against the constraints.
+    pub fn check(&self, pubkey: &Pubkey, account: &Account) -> ConstraintReport {
+        let mut violations = Vec::new();
+
+        if account.lamports == 0 && account.owner == well_known::SYSTEM_PROGRAM {
+            violations.push(ConstraintViolation::AccountNotInitialized);
+        } else if account.owner != self.owner {
+            violations.push(ConstraintViolation::Owner {
+                expected: self.owner,
+                actual: account.owner,
+            });
+        }
+
+        if let Some((name, decodes)) = &self.account_type {
+            let expected = get_account_discriminator(name);
+            match account.data.get(..8) {
+                None => violations.push(ConstraintViolation::DiscriminatorNotFound),
+                Some(actual) if actual != expected => {
+                    violations.push(ConstraintViolation::DiscriminatorMismatch {
+                        expected,
+                        actual: actual.try_into().unwrap(),
+                    });
+                }
+                Some(_) if !decodes(&account.data[8..]) => {
+                    violations.push(ConstraintViolation::DidNotDeserialize);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(rent) = &self.rent {
+            let required = rent.minimum_balance(account.data.len());
+            if account.lamports < required {
+                violations.push(ConstraintViolation::RentExempt {
+                    required,
+                    actual: account.lamports,
+                });
+            }
+        }
+
+        if let Some(seeds) = &self.seeds {
+            let expected = self.derive(seeds);
+            if expected != Some(*pubkey) {
+                violations.push(ConstraintViolation::Seeds {
+                    expected,
+                    actual: *pubkey,
+                });
+            }
+        }
+
+        ConstraintReport {
+            pubkey: *pubkey,
+            violations,
+        }
+    }
+
+    fn derive(&self, seeds: &[Vec<u8>]) -> Option<Pubkey> {
+        let mut seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        match self.bump {
+            Some(bump) => {
+                let bump = [bump];
+                seeds.push(&bump);
+                Pubkey::create_program_address(&seeds, &self.program_id).ok()
+            }
+            None => Pubkey::try_find_program_address(&seeds, &self.program_id).map(|(pda, _)| pda),
+        }
+    }
+}
+
+fn decodes<T: BorshDeserialize>(mut data: &[u8]) -> bool {
+    T::deserialize(&mut data).is_ok()
+}
@@ -0,0 +1,123 @@
+//! Accounts at the edges of what the runtime allows.
+//!
+//! Input validation is easy to test with well-formed accounts and easy to
+//! get wrong for the odd ones: an account with data but no lamports, one at
+//! the largest size an account can have, a loader-owned account that is
+//! not executable. The functions here build such accounts directly, without
+//! the checks [`AccountBuilder`](crate::AccountBuilder) applies, and
+//! [`catalog`] returns all of them by name for table-driven tests.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_account::Account;
+//! use solana_accountgen::extensions::edge_cases;
+//! use solana_pubkey::Pubkey;
+//! use solana_rent::Rent;
+//!
+//! fn is_valid_vault(account: &Account, program_id: &Pubkey) -> bool {
+//!     account.owner == *program_id
+//!         && account.data.len() == 64
+//!         && Rent::default().is_exempt(account.lamports, account.data.len())
+//! }
+//!
+//! let program_id = Pubkey::new_unique();
+//! for (name, account) in edge_cases::catalog(&program_id) {
+//!     // Only the account with the unusual rent epoch is a valid vault
+//!     let expected = name == "rent_epoch_max";
+//!     assert_eq!(is_valid_vault(&account, &program_id), expected, "{}", name);
+//! }
+//! ```
+
+use crate::cluster::{MAX_PERMITTED_DATA_LENGTH, MAX_REALLOC_INCREASE, RENT_EXEMPT_RENT_EPOCH};
+use crate::well_known::BPF_LOADER;
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+
+/// Size of the data of the accounts that are not about size.
+const DATA_LEN: usize = 64;
+
+fn account(owner: &Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: *owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// An account with `len` bytes of zeroed data and no lamports, as left
+/// behind within a transaction by a program that drained an account
+/// without clearing it.
+pub fn zero_lamports_with_data(owner: &Pubkey, len: usize) -> Account {
+    Account {
+        lamports: 0,
+        ..account(owner, vec![0; len])
+    }
+}
+
+/// An account with `len` bytes of data one lamport short of the
+/// rent-exempt minimum for its size.
+pub fn below_rent_exempt(owner: &Pubkey, len: usize) -> Account {
+    let mut account = account(owner, vec![0; len]);
+    account.lamports = account.lamports.saturating_sub(1);
+    account
+}
+
+/// An account with the largest data the runtime allows,
+/// [`MAX_PERMITTED_DATA_LENGTH`] bytes.
+pub fn max_size_data(owner: &Pubkey) -> Account {
+    account(owner, vec![0; MAX_PERMITTED_DATA_LENGTH])
+}
+
+/// An account with exactly [`MAX_REALLOC_INCREASE`] bytes of data, the
+/// most an instruction may grow an empty account by.
+pub fn realloc_boundary(owner: &Pubkey) -> Account {
+    account(owner, vec![0; MAX_REALLOC_INCREASE])
+}
+
+/// An account one byte past [`realloc_boundary`], which no single
+/// instruction can have grown from empty.
+pub fn past_realloc_boundary(owner: &Pubkey) -> Account {
+    account(owner, vec![0; MAX_REALLOC_INCREASE + 1])
+}
+
+/// An account whose `rent_epoch` is `u64::MAX`, the value the runtime
+/// gives rent-exempt accounts. Programs that compute with the rent epoch
+/// must not overflow on it.
+pub fn rent_epoch_max(owner: &Pubkey) -> Account {
+    Account {
+        rent_epoch: RENT_EXEMPT_RENT_EPOCH,
+        ..account(owner, vec![0; DATA_LEN])
+    }
+}
+
+/// An account owned by the BPF loader that holds an ELF header but is not
+/// executable, which passes for a program with a program that checks
+/// only the owner.
+pub fn loader_owned_not_executable() -> Account {
+    let mut data = vec![0; DATA_LEN];
+    data[..4].copy_from_slice(b"\x7fELF");
+    account(&BPF_LOADER, data)
+}
+
+/// Returns every edge case with its name, for accounts owned by `owner`.
+///
+/// The maximum-size account alone holds 10 MiB of data, so build the
+/// catalog once per test rather than once per case.
+pub fn catalog(owner: &Pubkey) -> Vec<(&'static str, Account)> {
+    vec![
+        (
+            "zero_lamports_with_data",
+            zero_lamports_with_data(owner, DATA_LEN),
+        ),
+        ("below_rent_exempt", below_rent_exempt(owner, DATA_LEN)),
+        ("max_size_data", max_size_data(owner)),
+        ("realloc_boundary", realloc_boundary(owner)),
+        ("past_realloc_boundary", past_realloc_boundary(owner)),
+        ("rent_epoch_max", rent_epoch_max(owner)),
+        ("loader_owned_not_executable", loader_owned_not_executable()),
+    ]
+}
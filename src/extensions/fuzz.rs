@@ -0,0 +1,303 @@
+//! Random account data shaped by an Anchor IDL.
+//!
+//! Property tests of a program's account handling need many accounts that
+//! deserialize, with values all over their ranges. Writing a generator per
+//! account type duplicates the IDL and drifts from it. An [`IdlFuzzer`]
+//! reads the layout from the IDL instead and produces Borsh data that
+//! decodes as the account type: valid bools, enum tags and UTF-8 strings,
+//! arbitrary pubkeys, and vecs no longer than a configurable bound. The
+//! caller supplies the RNG, so a failing case replays from its seed.
+//!
+//! # Example
+//!
+//! ```
+//! use rand::SeedableRng;
+//! use rand::rngs::StdRng;
+//! use solana_accountgen::extensions::fuzz::IdlFuzzer;
+//! use solana_accountgen::extensions::idl::Idl;
+//!
+//! let idl = Idl::from_json(r#"{
+//!     "accounts": [{ "name": "Game", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }],
+//!     "types": [{
+//!         "name": "Game",
+//!         "type": { "kind": "struct", "fields": [
+//!             { "name": "host", "type": "pubkey" },
+//!             { "name": "players", "type": { "vec": "pubkey" } },
+//!             { "name": "started", "type": "bool" }
+//!         ] }
+//!     }]
+//! }"#).unwrap();
+//! let schema = idl.to_fixture_schema().unwrap();
+//!
+//! let fuzzer = IdlFuzzer::new(idl).max_len(4);
+//! let mut rng = StdRng::seed_from_u64(7);
+//! for _ in 0..100 {
+//!     let data = fuzzer.random_account("Game", &mut rng).unwrap();
+//!     assert_eq!(data[..8], [1, 2, 3, 4, 5, 6, 7, 8]);
+//!     let game = schema.decode("Game", &data[8..]).unwrap();
+//!     assert!(game["players"].as_array().unwrap().len() <= 4);
+//! }
+//! ```
+
+use crate::AccountGenError;
+use crate::extensions::idl::{Idl, IdlFields, IdlType, IdlTypeDefTy};
+use rand::Rng;
+use rand::distributions::{Alphanumeric, DistString};
+
+/// How deeply defined types nest before options become `None` and vecs
+/// empty, which keeps types that contain themselves small.
+const SHRINK_DEPTH: usize = 4;
+
+/// How deeply defined types may nest before generation gives up, which
+/// stops types that must contain themselves, such as through every
+/// variant of an enum.
+const MAX_DEPTH: usize = 32;
+
+/// The default of [`IdlFuzzer::max_len`].
+pub const DEFAULT_MAX_LEN: usize = 8;
+
+/// Generates random account data for the types of an Anchor IDL.
+///
+/// Integers are drawn from their whole range, with zero and the extremes
+/// each drawn about one time in sixteen, since that is where arithmetic
+/// goes wrong. Floats are drawn from `[0, 1)`.
+#[derive(Debug, Clone)]
+pub struct IdlFuzzer {
+    idl: Idl,
+    max_len: usize,
+}
+
+impl IdlFuzzer {
+    /// Creates a fuzzer for the types of `idl`.
+    pub fn new(idl: Idl) -> Self {
+        Self {
+            idl,
+            max_len: DEFAULT_MAX_LEN,
+        }
+    }
+
+    /// Sets the largest number of elements of a vec, and of bytes of a
+    /// `bytes` or `string` value. Defaults to [`DEFAULT_MAX_LEN`].
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Returns the IDL.
+    pub fn idl(&self) -> &Idl {
+        &self.idl
+    }
+
+    /// Generates the data of a random account of type `name`, starting
+    /// with its discriminator.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the IDL has no
+    /// account `name`, or its layout refers to an undefined type or an
+    /// unsupported primitive.
+    pub fn random_account<R: Rng + ?Sized>(
+        &self,
+        name: &str,
+        rng: &mut R,
+    ) -> Result<Vec<u8>, AccountGenError> {
+        let account = self
+            .idl
+            .accounts
+            .iter()
+            .find(|account| account.name == name)
+            .ok_or_else(|| {
+                AccountGenError::InvalidDataFormat(format!("IDL has no account {name}"))
+            })?;
+        let mut data = self.idl.account_discriminator(account).to_vec();
+        match &account.ty {
+            Some(ty) => self.write_def(&mut data, name, ty, rng, 0)?,
+            None => self.write_defined(&mut data, name, rng, 0)?,
+        }
+        Ok(data)
+    }
+
+    /// Generates the Borsh encoding of a random value of `ty`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if `ty` refers to an
+    /// undefined type or an unsupported primitive.
+    pub fn random_value<R: Rng + ?Sized>(
+        &self,
+        ty: &IdlType,
+        rng: &mut R,
+    ) -> Result<Vec<u8>, AccountGenError> {
+        let mut data = Vec::new();
+        self.write_type(&mut data, ty, rng, 0)?;
+        Ok(data)
+    }
+
+    fn write_type<R: Rng + ?Sized>(
+        &self,
+        out: &mut Vec<u8>,
+        ty: &IdlType,
+        rng: &mut R,
+        depth: usize,
+    ) -> Result<(), AccountGenError> {
+        match ty {
+            IdlType::Primitive(primitive) => self.write_primitive(out, primitive, rng)?,
+            IdlType::Option(inner) => {
+                let some = depth < SHRINK_DEPTH && rng.gen_bool(0.5);
+                out.push(some.into());
+                if some {
+                    self.write_type(out, inner, rng, depth)?;
+                }
+            }
+            IdlType::Vec(inner) => {
+                let max_len = if depth < SHRINK_DEPTH {
+                    self.max_len
+                } else {
+                    0
+                };
+                let len = rng.gen_range(0..=max_len);
+                out.extend_from_slice(&(len as u32).to_le_bytes());
+                for _ in 0..len {
+                    self.write_type(out, inner, rng, depth)?;
+                }
+            }
+            IdlType::Array(inner, len) => {
+                for _ in 0..*len {
+                    self.write_type(out, inner, rng, depth)?;
+                }
+            }
+            IdlType::Defined(name) => self.write_defined(out, name, rng, depth + 1)?,
+        }
+        Ok(())
+    }
+
+    fn write_defined<R: Rng + ?Sized>(
+        &self,
+        out: &mut Vec<u8>,
+        name: &str,
+        rng: &mut R,
+        depth: usize,
+    ) -> Result<(), AccountGenError> {
+        if depth > MAX_DEPTH {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "IDL type {name} nests more than {MAX_DEPTH} deep"
+            )));
+        }
+        let def = self
+            .idl
+            .types
+            .iter()
+            .find(|def| def.name == name)
+            .map(|def| &def.ty)
+            .or_else(|| {
+                self.idl
+                    .accounts
+                    .iter()
+                    .find(|account| account.name == name)
+                    .and_then(|account| account.ty.as_ref())
+            })
+            .ok_or_else(|| {
+                AccountGenError::InvalidDataFormat(format!("IDL type {name} is not defined"))
+            })?;
+        self.write_def(out, name, def, rng, depth)
+    }
+
+    fn write_def<R: Rng + ?Sized>(
+        &self,
+        out: &mut Vec<u8>,
+        name: &str,
+        def: &IdlTypeDefTy,
+        rng: &mut R,
+        depth: usize,
+    ) -> Result<(), AccountGenError> {
+        match def {
+            IdlTypeDefTy::Struct { fields } => self.write_fields(out, fields, rng, depth),
+            IdlTypeDefTy::Enum { variants } => {
+                if variants.is_empty() {
+                    return Err(AccountGenError::InvalidDataFormat(format!(
+                        "IDL enum {name} has no variants"
+                    )));
+                }
+                let tag = rng.gen_range(0..variants.len());
+                out.push(tag as u8);
+                self.write_fields(out, &variants[tag].fields, rng, depth)
+            }
+            IdlTypeDefTy::Type { alias } => self.write_type(out, alias, rng, depth),
+        }
+    }
+
+    fn write_fields<R: Rng + ?Sized>(
+        &self,
+        out: &mut Vec<u8>,
+        fields: &Option<IdlFields>,
+        rng: &mut R,
+        depth: usize,
+    ) -> Result<(), AccountGenError> {
+        match fields {
+            None => Ok(()),
+            Some(IdlFields::Named(fields)) => fields
+                .iter()
+                .try_for_each(|field| self.write_type(out, &field.ty, rng, depth)),
+            Some(IdlFields::Tuple(types)) => types
+                .iter()
+                .try_for_each(|ty| self.write_type(out, ty, rng, depth)),
+        }
+    }
+
+    fn write_primitive<R: Rng + ?Sized>(
+        &self,
+        out: &mut Vec<u8>,
+        primitive: &str,
+        rng: &mut R,
+    ) -> Result<(), AccountGenError> {
+        match primitive {
+            "bool" => out.push(rng.gen_bool(0.5).into()),
+            "u8" => out.push(random_int(rng, u8::MIN, u8::MAX)),
+            "i8" => out.extend(random_int(rng, i8::MIN, i8::MAX).to_le_bytes()),
+            "u16" => out.extend(random_int(rng, u16::MIN, u16::MAX).to_le_bytes()),
+            "i16" => out.extend(random_int(rng, i16::MIN, i16::MAX).to_le_bytes()),
+            "u32" => out.extend(random_int(rng, u32::MIN, u32::MAX).to_le_bytes()),
+            "i32" => out.extend(random_int(rng, i32::MIN, i32::MAX).to_le_bytes()),
+            "u64" => out.extend(random_int(rng, u64::MIN, u64::MAX).to_le_bytes()),
+            "i64" => out.extend(random_int(rng, i64::MIN, i64::MAX).to_le_bytes()),
+            "u128" => out.extend(random_int(rng, u128::MIN, u128::MAX).to_le_bytes()),
+            "i128" => out.extend(random_int(rng, i128::MIN, i128::MAX).to_le_bytes()),
+            "f32" => out.extend(rng.r#gen::<f32>().to_le_bytes()),
+            "f64" => out.extend(rng.r#gen::<f64>().to_le_bytes()),
+            "bytes" => {
+                let len = rng.gen_range(0..=self.max_len);
+                out.extend((len as u32).to_le_bytes());
+                out.extend((0..len).map(|_| rng.r#gen::<u8>()));
+            }
+            "string" => {
+                let len = rng.gen_range(0..=self.max_len);
+                let string = Alphanumeric.sample_string(rng, len);
+                out.extend((len as u32).to_le_bytes());
+                out.extend(string.as_bytes());
+            }
+            // Every 32 bytes are a valid pubkey, on the curve or not
+            "pubkey" | "publicKey" => out.extend(rng.r#gen::<[u8; 32]>()),
+            other => {
+                return Err(AccountGenError::InvalidDataFormat(format!(
+                    "IDL primitive {other} is not supported"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draws an integer from `min..=max`, returning `min`, zero or `max` about
+/// one time in sixteen each.
+fn random_int<T, R>(rng: &mut R, min: T, max: T) -> T
+where
+    T: rand::distributions::uniform::SampleUniform + Default + PartialOrd + Copy,
+    R: Rng + ?Sized,
+{
+    match rng.gen_range(0..16) {
+        0 => min,
+        1 => T::default(),
+        2 => max,
+        _ => rng.gen_range(min..=max),
+    }
+}
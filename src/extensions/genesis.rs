@@ -1,11 +1,120 @@
 //! Helpers for creating genesis accounts.
 //!
 //! This module provides utilities for creating accounts that should be
-//! included in the genesis config.
+//! included in the genesis config, including the identity, vote and stake
+//! accounts of validators that are staked from the first slot.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::extensions::genesis::GenesisAccounts;
+//! use solana_accountgen::serialization::bincode::legacy::deserialize_account_data;
+//! use solana_pubkey::Pubkey;
+//! use solana_stake_interface::state::StakeStateV2;
+//!
+//! let mut genesis = GenesisAccounts::new();
+//! let validators: Vec<_> = (0..3)
+//!     .map(|_| genesis.add_validator(Pubkey::new_unique(), 10, 1_000_000_000))
+//!     .collect();
+//! assert_eq!(genesis.len(), 9);
+//!
+//! let stake = genesis.get_account(&validators[0].stake).unwrap();
+//! let state: StakeStateV2 = deserialize_account_data(stake).unwrap();
+//! assert_eq!(state.delegation().unwrap().voter_pubkey, validators[0].vote);
+//! ```
 
 use crate::AccountMap;
-use solana_program::pubkey::Pubkey;
-use solana_sdk::account::Account;
+use crate::serialization::bincode::legacy::serialize_data_padded;
+use crate::units::LAMPORTS_PER_SOL;
+use crate::well_known::{STAKE_PROGRAM, SYSTEM_PROGRAM, VOTE_PROGRAM};
+use solana_account::Account;
+use solana_clock::Clock;
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+use solana_stake_interface::stake_flags::StakeFlags;
+use solana_stake_interface::state::{Authorized, Delegation, Lockup, Meta, Stake, StakeStateV2};
+use solana_vote_interface::state::{VoteInit, VoteState, VoteStateVersions};
+
+/// The balance of a validator identity created by
+/// [`GenesisAccounts::add_validator`], which pays for its votes. It is the
+/// default of `solana-genesis --bootstrap-validator-lamports`.
+pub const VALIDATOR_IDENTITY_LAMPORTS: u64 = 500 * LAMPORTS_PER_SOL;
+
+/// The addresses of a validator added by [`GenesisAccounts::add_validator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidatorAccounts {
+    /// The validator's identity, a funded system account.
+    pub identity: Pubkey,
+    /// The vote account, derived from the identity with the seed `"vote"`.
+    pub vote: Pubkey,
+    /// The stake account delegated to the vote account, derived from the
+    /// identity with the seed `"stake"`.
+    pub stake: Pubkey,
+}
+
+impl ValidatorAccounts {
+    /// Returns the addresses of the validator with `identity`.
+    pub fn new(identity: Pubkey) -> Self {
+        let derive = |seed, owner| {
+            Pubkey::create_with_seed(&identity, seed, owner)
+                .expect("seeds are short and owners are not PDA markers")
+        };
+        Self {
+            identity,
+            vote: derive("vote", &VOTE_PROGRAM),
+            stake: derive("stake", &STAKE_PROGRAM),
+        }
+    }
+}
+
+/// Creates a rent-exempt vote account holding `vote_state`.
+///
+/// # Panics
+///
+/// Panics if `vote_state` holds more votes or epoch credits than a vote
+/// account has room for.
+pub fn create_vote_account(vote_state: &VoteState) -> Account {
+    let data = serialize_data_padded(
+        &VoteStateVersions::new_current(vote_state.clone()),
+        VoteState::size_of(),
+    )
+    .expect("vote state does not fit in a vote account");
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: VOTE_PROGRAM,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Creates a stake account staked and withdrawn by `authority` that holds
+/// `delegation`, with the delegated lamports on top of its rent-exempt
+/// reserve.
+pub fn create_stake_account(authority: &Pubkey, delegation: &Delegation) -> Account {
+    let rent_exempt_reserve = Rent::default().minimum_balance(StakeStateV2::size_of());
+    let state = StakeStateV2::Stake(
+        Meta {
+            rent_exempt_reserve,
+            authorized: Authorized::auto(authority),
+            lockup: Lockup::default(),
+        },
+        Stake {
+            delegation: *delegation,
+            credits_observed: 0,
+        },
+        StakeFlags::empty(),
+    );
+    let data = serialize_data_padded(&state, StakeStateV2::size_of())
+        .expect("a stake state fits its account");
+    Account {
+        lamports: rent_exempt_reserve.saturating_add(delegation.stake),
+        data,
+        owner: STAKE_PROGRAM,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
 
 /// A collection of accounts to be included in genesis.
 #[derive(Debug, Default)]
@@ -20,13 +129,13 @@ impl GenesisAccounts {
             accounts: AccountMap::new(),
         }
     }
-    
+
     /// Adds an account to the genesis accounts.
     pub fn add_account(&mut self, pubkey: Pubkey, account: Account) -> &mut Self {
         self.accounts.set_account(pubkey, account);
         self
     }
-    
+
     /// Adds all accounts from an AccountMap to the genesis accounts.
     pub fn add_account_map(&mut self, account_map: AccountMap) -> &mut Self {
         for (pubkey, account) in account_map {
@@ -34,17 +143,62 @@ impl GenesisAccounts {
         }
         self
     }
-    
+
+    /// Adds a validator that is staked from genesis, and returns the
+    /// addresses of its accounts.
+    ///
+    /// Three accounts are added, all authorized by `identity`:
+    ///
+    /// - the identity, with [`VALIDATOR_IDENTITY_LAMPORTS`]
+    /// - a vote account of the identity with `vote_commission` percent
+    ///   commission
+    /// - a stake account delegating `stake_amount` lamports to the vote
+    ///   account as bootstrap stake, which is active in the first epoch
+    pub fn add_validator(
+        &mut self,
+        identity: Pubkey,
+        vote_commission: u8,
+        stake_amount: u64,
+    ) -> ValidatorAccounts {
+        let validator = ValidatorAccounts::new(identity);
+        let vote_state = VoteState::new(
+            &VoteInit {
+                node_pubkey: identity,
+                authorized_voter: identity,
+                authorized_withdrawer: identity,
+                commission: vote_commission,
+            },
+            &Clock::default(),
+        );
+        let delegation = Delegation::new(&validator.vote, stake_amount, u64::MAX);
+
+        self.add_account(
+            identity,
+            Account::new(VALIDATOR_IDENTITY_LAMPORTS, 0, &SYSTEM_PROGRAM),
+        );
+        self.add_account(validator.vote, create_vote_account(&vote_state));
+        self.add_account(
+            validator.stake,
+            create_stake_account(&identity, &delegation),
+        );
+        validator
+    }
+
+    /// Returns the account at `pubkey`, if any.
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get_account(pubkey)
+    }
+
     /// Returns an iterator over all (pubkey, account) pairs.
     pub fn iter(&self) -> impl Iterator<Item = (&Pubkey, &Account)> {
         self.accounts.iter()
     }
-    
+
     /// Returns the number of accounts.
     pub fn len(&self) -> usize {
         self.accounts.len()
     }
-    
+
     /// Returns true if there are no accounts.
     pub fn is_empty(&self) -> bool {
         self.accounts.is_empty()
@@ -58,4 +212,4 @@ impl IntoIterator for GenesisAccounts {
     fn into_iter(self) -> Self::IntoIter {
         self.accounts.into_iter()
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,841 @@
+//! Anchor IDL parsing and Rust code generation.
+//!
+//! Hand-written fixture structs drift from the program as fields are added.
+//! This module reads an Anchor IDL (both the 0.30+ format and the legacy
+//! one) and generates Borsh structs and enums for its types, with a
+//! `DISCRIMINATOR` constant on every account type, so fixtures stay in sync
+//! with the program they test.
+//!
+//! # Build script usage
+//!
+//! Generate the code from `build.rs`:
+//!
+//! ```no_run
+//! // in build.rs's main
+//! solana_accountgen::extensions::idl::build("idl/my_program.json").unwrap();
+//! ```
+//!
+//! and include it with [`accountgen_idl!`](crate::accountgen_idl), passing
+//! the same path:
+//!
+//! ```ignore
+//! mod my_program {
+//!     solana_accountgen::accountgen_idl!("idl/my_program.json");
+//! }
+//!
+//! let account = create_anchor_account("Pool", program_id, my_program::Pool { .. }, 1_000_000)?;
+//! assert_eq!(&account.data[..8], &my_program::Pool::DISCRIMINATOR);
+//! ```
+//!
+//! The generated code refers to `::solana_pubkey::Pubkey` for public keys,
+//! so the including crate needs `solana-pubkey` (with its `borsh` feature)
+//! as a dependency. Borsh is used through the re-export in this crate.
+
+use super::anchor::{
+    DiscriminatorKind, DiscriminatorMatch, get_account_discriminator, get_method_discriminator,
+};
+use crate::AccountGenError;
+use crate::serialization::schema::{EnumVariant, FixtureSchema, StructField, TypeDefinition};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// An Anchor IDL, reduced to the parts needed to describe account and
+/// instruction data.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Idl {
+    /// The program address (0.30+ IDLs).
+    #[serde(default)]
+    pub address: Option<String>,
+    /// The program name (legacy IDLs).
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The program metadata (0.30+ IDLs).
+    #[serde(default)]
+    pub metadata: Option<IdlMetadata>,
+    /// The account types.
+    #[serde(default)]
+    pub accounts: Vec<IdlAccount>,
+    /// The type definitions.
+    #[serde(default)]
+    pub types: Vec<IdlTypeDef>,
+    /// The instructions.
+    #[serde(default)]
+    pub instructions: Vec<IdlInstruction>,
+}
+
+/// An instruction declared in an IDL.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdlInstruction {
+    /// The instruction name.
+    pub name: String,
+    /// The discriminator (0.30+ IDLs).
+    #[serde(default)]
+    pub discriminator: Option<Vec<u8>>,
+    /// The accounts the instruction expects, in order.
+    #[serde(default)]
+    pub accounts: Vec<IdlInstructionAccount>,
+    /// The instruction arguments.
+    #[serde(default)]
+    pub args: Vec<IdlField>,
+}
+
+/// An account of an [`IdlInstruction`], or a group of them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdlInstructionAccount {
+    /// The account name.
+    pub name: String,
+    /// The member accounts if this is a composite group.
+    #[serde(default)]
+    pub accounts: Vec<IdlInstructionAccount>,
+}
+
+impl IdlInstruction {
+    /// Returns the names of the instruction's accounts in order, with
+    /// composite groups flattened.
+    pub fn account_names(&self) -> Vec<String> {
+        fn flatten(accounts: &[IdlInstructionAccount], names: &mut Vec<String>) {
+            for account in accounts {
+                if account.accounts.is_empty() {
+                    names.push(account.name.clone());
+                } else {
+                    flatten(&account.accounts, names);
+                }
+            }
+        }
+
+        let mut names = Vec::new();
+        flatten(&self.accounts, &mut names);
+        names
+    }
+}
+
+/// The metadata section of a 0.30+ IDL.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdlMetadata {
+    /// The program name.
+    pub name: String,
+}
+
+/// An account type declared in an IDL.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdlAccount {
+    /// The account type name.
+    pub name: String,
+    /// The discriminator (0.30+ IDLs).
+    #[serde(default)]
+    pub discriminator: Option<Vec<u8>>,
+    /// The inline layout (legacy IDLs); 0.30+ IDLs put it in `types`.
+    #[serde(default, rename = "type")]
+    pub ty: Option<IdlTypeDefTy>,
+}
+
+/// A named type definition.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdlTypeDef {
+    /// The type name.
+    pub name: String,
+    /// The layout.
+    #[serde(rename = "type")]
+    pub ty: IdlTypeDefTy,
+}
+
+/// The layout of a defined type.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum IdlTypeDefTy {
+    /// A struct with named or tuple fields.
+    Struct {
+        /// The fields; absent for unit structs.
+        #[serde(default)]
+        fields: Option<IdlFields>,
+    },
+    /// An enum.
+    Enum {
+        /// The variants, in tag order.
+        variants: Vec<IdlEnumVariant>,
+    },
+    /// An alias for another type.
+    Type {
+        /// The aliased type.
+        alias: IdlType,
+    },
+}
+
+/// The fields of a struct or enum variant.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "Vec<Value>")]
+pub enum IdlFields {
+    /// Named fields.
+    Named(Vec<IdlField>),
+    /// Tuple fields.
+    Tuple(Vec<IdlType>),
+}
+
+/// A named field.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdlField {
+    /// The field name.
+    pub name: String,
+    /// The field type.
+    #[serde(rename = "type")]
+    pub ty: IdlType,
+}
+
+/// An enum variant.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdlEnumVariant {
+    /// The variant name.
+    pub name: String,
+    /// The fields; absent for unit variants.
+    #[serde(default)]
+    pub fields: Option<IdlFields>,
+}
+
+/// A field type.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "Value")]
+pub enum IdlType {
+    /// A primitive such as `u64`, `string` or `pubkey`.
+    Primitive(String),
+    /// `Option<T>`.
+    Option(Box<IdlType>),
+    /// `Vec<T>`.
+    Vec(Box<IdlType>),
+    /// `[T; N]`.
+    Array(Box<IdlType>, usize),
+    /// A type defined in the IDL.
+    Defined(String),
+}
+
+/// The primitive type names an IDL may use.
+const PRIMITIVES: &[&str] = &[
+    "bool",
+    "u8",
+    "i8",
+    "u16",
+    "i16",
+    "u32",
+    "i32",
+    "f32",
+    "u64",
+    "i64",
+    "f64",
+    "u128",
+    "i128",
+    "bytes",
+    "string",
+    "pubkey",
+    "publicKey",
+];
+
+impl TryFrom<Value> for IdlType {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::String(name) = &value {
+            return if PRIMITIVES.contains(&name.as_str()) {
+                Ok(IdlType::Primitive(name.clone()))
+            } else {
+                Err(format!("unsupported IDL type {name}"))
+            };
+        }
+
+        let Some((kind, inner)) = value.as_object().and_then(|o| o.iter().next()) else {
+            return Err(format!("unsupported IDL type {value}"));
+        };
+        match (kind.as_str(), inner) {
+            ("option", inner) => Ok(IdlType::Option(Box::new(inner.clone().try_into()?))),
+            ("vec", inner) => Ok(IdlType::Vec(Box::new(inner.clone().try_into()?))),
+            ("array", Value::Array(pair)) if pair.len() == 2 => {
+                let len = pair[1]
+                    .as_u64()
+                    .ok_or_else(|| format!("unsupported array length {}", pair[1]))?;
+                Ok(IdlType::Array(
+                    Box::new(pair[0].clone().try_into()?),
+                    len as usize,
+                ))
+            }
+            // 0.30+ IDLs wrap the name in an object that may carry generics
+            ("defined", Value::String(name)) => Ok(IdlType::Defined(name.clone())),
+            ("defined", Value::Object(defined)) if !defined.contains_key("generics") => defined
+                .get("name")
+                .and_then(Value::as_str)
+                .map(|name| IdlType::Defined(name.to_string()))
+                .ok_or_else(|| format!("unsupported IDL type {value}")),
+            _ => Err(format!("unsupported IDL type {value}")),
+        }
+    }
+}
+
+impl TryFrom<Vec<Value>> for IdlFields {
+    type Error = String;
+
+    fn try_from(values: Vec<Value>) -> Result<Self, Self::Error> {
+        // Named fields are objects with a name; anything else is a tuple
+        // field type, which may itself be an object such as `{"vec": "u8"}`
+        if values
+            .iter()
+            .any(|value| value.get("name").is_some() && value.get("type").is_some())
+        {
+            values
+                .into_iter()
+                .map(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+                .collect::<Result<_, _>>()
+                .map(IdlFields::Named)
+        } else {
+            values
+                .into_iter()
+                .map(IdlType::try_from)
+                .collect::<Result<_, _>>()
+                .map(IdlFields::Tuple)
+        }
+    }
+}
+
+impl Idl {
+    /// Parses an IDL from JSON.
+    pub fn from_json(json: &str) -> Result<Self, AccountGenError> {
+        serde_json::from_str(json)
+            .map_err(|e| AccountGenError::InvalidDataFormat(format!("invalid IDL: {e}")))
+    }
+
+    /// Reads and parses an IDL file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AccountGenError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Returns the program name, from whichever IDL format is in use.
+    pub fn program_name(&self) -> Option<&str> {
+        self.metadata
+            .as_ref()
+            .map(|metadata| metadata.name.as_str())
+            .or(self.name.as_deref())
+    }
+
+    /// Returns the discriminator of an account type.
+    ///
+    /// 0.30+ IDLs list it explicitly; for legacy IDLs it is derived from
+    /// the account name.
+    pub fn account_discriminator(&self, account: &IdlAccount) -> [u8; 8] {
+        account
+            .discriminator
+            .as_deref()
+            .and_then(|d| d.try_into().ok())
+            .unwrap_or_else(|| get_account_discriminator(&account.name))
+    }
+
+    /// Returns the discriminator of an instruction.
+    ///
+    /// 0.30+ IDLs list it explicitly; for legacy IDLs it is derived from
+    /// the snake_case instruction name.
+    pub fn instruction_discriminator(&self, instruction: &IdlInstruction) -> [u8; 8] {
+        instruction
+            .discriminator
+            .as_deref()
+            .and_then(|d| d.try_into().ok())
+            .unwrap_or_else(|| get_method_discriminator(&snake_case(&instruction.name)))
+    }
+
+    /// Returns the account types and instructions of the IDL whose
+    /// discriminator is `discriminator`.
+    pub fn identify_discriminator(&self, discriminator: &[u8; 8]) -> Vec<DiscriminatorMatch> {
+        let accounts = self
+            .accounts
+            .iter()
+            .filter(|account| self.account_discriminator(account) == *discriminator)
+            .map(|account| DiscriminatorMatch {
+                kind: DiscriminatorKind::Account,
+                name: account.name.clone(),
+            });
+        let instructions = self
+            .instructions
+            .iter()
+            .filter(|instruction| self.instruction_discriminator(instruction) == *discriminator)
+            .map(|instruction| DiscriminatorMatch {
+                kind: DiscriminatorKind::Instruction,
+                name: instruction.name.clone(),
+            });
+        accounts.chain(instructions).collect()
+    }
+
+    /// Converts the IDL's types into a [`FixtureSchema`].
+    ///
+    /// The schema's root types are the account types, which decode the
+    /// data after the discriminator, and one struct per instruction named
+    /// `instruction:<name>`, which decodes the instruction arguments.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::extensions::idl::Idl;
+    /// use serde_json::json;
+    ///
+    /// let idl = Idl::from_json(r#"{
+    ///     "instructions": [{
+    ///         "name": "deposit",
+    ///         "accounts": [],
+    ///         "args": [{ "name": "amount", "type": "u64" }, { "name": "memo", "type": { "option": "string" } }]
+    ///     }]
+    /// }"#).unwrap();
+    ///
+    /// let schema = idl.to_fixture_schema().unwrap();
+    /// let mut args = 5u64.to_le_bytes().to_vec();
+    /// args.push(0);
+    /// assert_eq!(
+    ///     schema.decode("instruction:deposit", &args).unwrap(),
+    ///     json!({ "amount": 5, "memo": { "None": null } })
+    /// );
+    /// ```
+    pub fn to_fixture_schema(&self) -> Result<FixtureSchema, AccountGenError> {
+        let mut schema = FixtureSchema::new();
+        for def in &self.types {
+            self.define(&mut schema, &def.name, &def.ty)?;
+        }
+        for account in &self.accounts {
+            match &account.ty {
+                Some(ty) => self.define(&mut schema, &account.name, ty)?,
+                None if schema.definitions.contains_key(&account.name) => {}
+                None => {
+                    return Err(AccountGenError::InvalidDataFormat(format!(
+                        "IDL account {} has no type definition",
+                        account.name
+                    )));
+                }
+            }
+            schema.types.push(account.name.clone());
+        }
+        for instruction in &self.instructions {
+            let fields = instruction
+                .args
+                .iter()
+                .map(|arg| {
+                    Ok(StructField {
+                        name: arg.name.clone(),
+                        ty: self.schema_type(&mut schema, &arg.ty)?,
+                    })
+                })
+                .collect::<Result<_, AccountGenError>>()?;
+            let name = format!("instruction:{}", instruction.name);
+            schema
+                .definitions
+                .insert(name.clone(), TypeDefinition::Struct { fields });
+            schema.types.push(name);
+        }
+        Ok(schema)
+    }
+
+    fn define(
+        &self,
+        schema: &mut FixtureSchema,
+        name: &str,
+        ty: &IdlTypeDefTy,
+    ) -> Result<(), AccountGenError> {
+        let definition = match ty {
+            IdlTypeDefTy::Struct { fields } => self.fields_definition(schema, name, fields)?,
+            IdlTypeDefTy::Enum { variants } => {
+                let variants = variants
+                    .iter()
+                    .enumerate()
+                    .map(|(index, variant)| {
+                        let payload = format!("{name}::{}", variant.name);
+                        let definition =
+                            self.fields_definition(schema, &payload, &variant.fields)?;
+                        schema.definitions.insert(payload.clone(), definition);
+                        Ok(EnumVariant {
+                            discriminant: index as i64,
+                            name: variant.name.clone(),
+                            ty: payload,
+                        })
+                    })
+                    .collect::<Result<_, AccountGenError>>()?;
+                TypeDefinition::Enum {
+                    tag_width: 1,
+                    variants,
+                }
+            }
+            // Aliases are resolved where they are used
+            IdlTypeDefTy::Type { .. } => return Ok(()),
+        };
+        schema.definitions.insert(name.to_string(), definition);
+        Ok(())
+    }
+
+    fn fields_definition(
+        &self,
+        schema: &mut FixtureSchema,
+        name: &str,
+        fields: &Option<IdlFields>,
+    ) -> Result<TypeDefinition, AccountGenError> {
+        Ok(match fields {
+            None => TypeDefinition::TupleStruct {
+                elements: Vec::new(),
+            },
+            Some(IdlFields::Named(fields)) => TypeDefinition::Struct {
+                fields: fields
+                    .iter()
+                    .map(|field| {
+                        Ok(StructField {
+                            name: field.name.clone(),
+                            ty: self.schema_type(schema, &field.ty)?,
+                        })
+                    })
+                    .collect::<Result<_, AccountGenError>>()?,
+            },
+            Some(IdlFields::Tuple(types)) => TypeDefinition::TupleStruct {
+                elements: types
+                    .iter()
+                    .map(|ty| self.schema_type(schema, ty))
+                    .collect::<Result<_, _>>()
+                    .map_err(|e: AccountGenError| {
+                        AccountGenError::InvalidDataFormat(format!("{name}: {e}"))
+                    })?,
+            },
+        })
+    }
+
+    /// Returns the schema name of `ty`, adding its definition if needed.
+    fn schema_type(
+        &self,
+        schema: &mut FixtureSchema,
+        ty: &IdlType,
+    ) -> Result<String, AccountGenError> {
+        let (name, definition) = match ty {
+            IdlType::Primitive(primitive) => match primitive.as_str() {
+                "string" => ("String".to_string(), length_prefixed("u8".to_string())),
+                "bytes" => ("Vec<u8>".to_string(), length_prefixed("u8".to_string())),
+                "pubkey" | "publicKey" => {
+                    ("Pubkey".to_string(), TypeDefinition::Primitive { size: 32 })
+                }
+                other => {
+                    let size = primitive_size(other);
+                    (other.to_string(), TypeDefinition::Primitive { size })
+                }
+            },
+            IdlType::Option(inner) => {
+                let inner = self.schema_type(schema, inner)?;
+                schema
+                    .definitions
+                    .insert("()".to_string(), TypeDefinition::Primitive { size: 0 });
+                (
+                    format!("Option<{inner}>"),
+                    TypeDefinition::Enum {
+                        tag_width: 1,
+                        variants: vec![
+                            EnumVariant {
+                                discriminant: 0,
+                                name: "None".to_string(),
+                                ty: "()".to_string(),
+                            },
+                            EnumVariant {
+                                discriminant: 1,
+                                name: "Some".to_string(),
+                                ty: inner,
+                            },
+                        ],
+                    },
+                )
+            }
+            IdlType::Vec(inner) => {
+                let inner = self.schema_type(schema, inner)?;
+                (format!("Vec<{inner}>"), length_prefixed(inner))
+            }
+            IdlType::Array(inner, len) => {
+                let inner = self.schema_type(schema, inner)?;
+                (
+                    format!("[{inner}; {len}]"),
+                    TypeDefinition::Sequence {
+                        length_width: 0,
+                        length_range: (*len as u64, *len as u64),
+                        elements: inner,
+                    },
+                )
+            }
+            IdlType::Defined(name) => {
+                let def = self
+                    .types
+                    .iter()
+                    .find(|def| &def.name == name)
+                    .map(|def| &def.ty)
+                    .or_else(|| {
+                        self.accounts
+                            .iter()
+                            .find(|account| &account.name == name)
+                            .and_then(|account| account.ty.as_ref())
+                    })
+                    .ok_or_else(|| {
+                        AccountGenError::InvalidDataFormat(format!(
+                            "IDL type {name} is not defined"
+                        ))
+                    })?;
+                if let IdlTypeDefTy::Type { alias } = def {
+                    return self.schema_type(schema, alias);
+                }
+                return Ok(name.clone());
+            }
+        };
+        schema.definitions.entry(name.clone()).or_insert(definition);
+        Ok(name)
+    }
+
+    /// Generates Rust source for every type and account in the IDL.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::extensions::idl::Idl;
+    ///
+    /// let idl = Idl::from_json(r#"{
+    ///     "address": "11111111111111111111111111111111",
+    ///     "metadata": { "name": "counter" },
+    ///     "accounts": [{ "name": "Counter", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }],
+    ///     "types": [{
+    ///         "name": "Counter",
+    ///         "type": { "kind": "struct", "fields": [{ "name": "count", "type": "u64" }] }
+    ///     }]
+    /// }"#).unwrap();
+    ///
+    /// let code = idl.to_rust().unwrap();
+    /// assert!(code.contains("pub struct Counter {\n    pub count: u64,\n}"));
+    /// assert!(code.contains("pub const DISCRIMINATOR: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];"));
+    /// ```
+    pub fn to_rust(&self) -> Result<String, AccountGenError> {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "// @generated by solana-accountgen from the {} IDL. Do not edit.",
+            self.program_name().unwrap_or("program")
+        )
+        .unwrap();
+
+        for def in &self.types {
+            out.push('\n');
+            write_type_def(&mut out, &def.name, &def.ty);
+        }
+        for account in &self.accounts {
+            if let Some(ty) = &account.ty {
+                out.push('\n');
+                write_type_def(&mut out, &account.name, ty);
+            } else if !self.types.iter().any(|def| def.name == account.name) {
+                return Err(AccountGenError::InvalidDataFormat(format!(
+                    "IDL account {} has no type definition",
+                    account.name
+                )));
+            }
+
+            let discriminator = self.account_discriminator(account);
+            writeln!(
+                out,
+                "\nimpl {} {{\n    pub const DISCRIMINATOR: [u8; 8] = {:?};\n}}",
+                type_name(&account.name),
+                discriminator
+            )
+            .unwrap();
+        }
+
+        Ok(out)
+    }
+}
+
+/// Generates Rust code for an IDL from a build script.
+///
+/// `idl_path` is relative to the crate being built and must not contain
+/// `..`; see [`generate_file`]. The code is written
+/// under `OUT_DIR`, where [`accountgen_idl!`](crate::accountgen_idl) finds
+/// it when given the same path, and Cargo is told to rerun the build script
+/// when the IDL changes. Returns the path of the generated file.
+pub fn build<P: AsRef<Path>>(idl_path: P) -> Result<PathBuf, AccountGenError> {
+    let idl_path = idl_path.as_ref();
+    let out_dir = std::env::var_os("OUT_DIR").ok_or_else(|| {
+        AccountGenError::InvalidDataFormat("OUT_DIR is not set; call this from build.rs".into())
+    })?;
+    println!("cargo:rerun-if-changed={}", idl_path.display());
+    generate_file(idl_path, Path::new(&out_dir))
+}
+
+/// Generates Rust code for an IDL into `out_dir`.
+///
+/// The output path mirrors `idl_path` below `out_dir/accountgen_idl`, with
+/// `.rs` appended, which is the layout [`accountgen_idl!`](crate::accountgen_idl)
+/// expects.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] if `idl_path` is absolute
+/// or contains `..`. The macro pastes its path onto `OUT_DIR` unchanged, so
+/// such a path would point it outside the generated directory.
+pub fn generate_file(idl_path: &Path, out_dir: &Path) -> Result<PathBuf, AccountGenError> {
+    let mut out_path = out_dir.join("accountgen_idl");
+    for component in idl_path.components() {
+        match component {
+            Component::Normal(part) => out_path.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(AccountGenError::InvalidDataFormat(format!(
+                    "IDL path {} must be relative and must not contain `..`",
+                    idl_path.display()
+                )));
+            }
+        }
+    }
+    out_path.as_mut_os_string().push(".rs");
+    let code = Idl::from_file(idl_path)?.to_rust()?;
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&out_path, code)?;
+    Ok(out_path)
+}
+
+/// Includes the code generated by [`extensions::idl::build`] for an IDL.
+///
+/// Pass the same path the build script used. See the
+/// [`idl`](crate::extensions::idl) module for the full setup.
+///
+/// [`extensions::idl::build`]: crate::extensions::idl::build
+#[macro_export]
+macro_rules! accountgen_idl {
+    ($path:literal) => {
+        include!(concat!(env!("OUT_DIR"), "/accountgen_idl/", $path, ".rs"));
+    };
+}
+
+const DERIVES: &str = "#[derive(::solana_accountgen::borsh::BorshSerialize, ::solana_accountgen::borsh::BorshDeserialize, Debug, Clone, PartialEq)]\n#[borsh(crate = \"::solana_accountgen::borsh\")]\n";
+
+fn write_type_def(out: &mut String, name: &str, ty: &IdlTypeDefTy) {
+    let name = type_name(name);
+    match ty {
+        IdlTypeDefTy::Struct { fields } => {
+            out.push_str(DERIVES);
+            match fields {
+                None => writeln!(out, "pub struct {name};").unwrap(),
+                Some(IdlFields::Named(fields)) => {
+                    writeln!(out, "pub struct {name} {{").unwrap();
+                    for field in fields {
+                        writeln!(
+                            out,
+                            "    pub {}: {},",
+                            field_name(&field.name),
+                            rust_type(&field.ty)
+                        )
+                        .unwrap();
+                    }
+                    out.push_str("}\n");
+                }
+                Some(IdlFields::Tuple(types)) => {
+                    let types: Vec<_> = types
+                        .iter()
+                        .map(|ty| format!("pub {}", rust_type(ty)))
+                        .collect();
+                    writeln!(out, "pub struct {name}({});", types.join(", ")).unwrap();
+                }
+            }
+        }
+        IdlTypeDefTy::Enum { variants } => {
+            out.push_str(DERIVES);
+            writeln!(out, "pub enum {name} {{").unwrap();
+            for variant in variants {
+                let variant_name = type_name(&variant.name);
+                match &variant.fields {
+                    None => writeln!(out, "    {variant_name},").unwrap(),
+                    Some(IdlFields::Named(fields)) => {
+                        let fields: Vec<_> = fields
+                            .iter()
+                            .map(|f| format!("{}: {}", field_name(&f.name), rust_type(&f.ty)))
+                            .collect();
+                        writeln!(out, "    {variant_name} {{ {} }},", fields.join(", ")).unwrap();
+                    }
+                    Some(IdlFields::Tuple(types)) => {
+                        let types: Vec<_> = types.iter().map(rust_type).collect();
+                        writeln!(out, "    {variant_name}({}),", types.join(", ")).unwrap();
+                    }
+                }
+            }
+            out.push_str("}\n");
+        }
+        IdlTypeDefTy::Type { alias } => {
+            writeln!(out, "pub type {name} = {};", rust_type(alias)).unwrap();
+        }
+    }
+}
+
+/// A Borsh sequence with a `u32` length prefix.
+fn length_prefixed(elements: String) -> TypeDefinition {
+    TypeDefinition::Sequence {
+        length_width: 4,
+        length_range: (0, u32::MAX as u64),
+        elements,
+    }
+}
+
+fn primitive_size(name: &str) -> u8 {
+    match name {
+        "bool" | "u8" | "i8" => 1,
+        "u16" | "i16" => 2,
+        "u32" | "i32" | "f32" => 4,
+        "u64" | "i64" | "f64" => 8,
+        _ => 16,
+    }
+}
+
+fn rust_type(ty: &IdlType) -> String {
+    match ty {
+        IdlType::Primitive(name) => match name.as_str() {
+            "bytes" => "Vec<u8>".to_string(),
+            "string" => "String".to_string(),
+            "pubkey" | "publicKey" => "::solana_pubkey::Pubkey".to_string(),
+            other => other.to_string(),
+        },
+        IdlType::Option(inner) => format!("Option<{}>", rust_type(inner)),
+        IdlType::Vec(inner) => format!("Vec<{}>", rust_type(inner)),
+        IdlType::Array(inner, len) => format!("[{}; {}]", rust_type(inner), len),
+        IdlType::Defined(name) => type_name(name),
+    }
+}
+
+/// Converts an IDL type name to a Rust type name (`pool_state` -> `PoolState`).
+fn type_name(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Converts an IDL field name to a Rust field name (`lastUpdated` -> `last_updated`).
+fn field_name(name: &str) -> String {
+    let snake = snake_case(name);
+    if RUST_KEYWORDS.contains(&snake.as_str()) {
+        format!("r#{snake}")
+    } else {
+        snake
+    }
+}
+
+pub(crate) fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod",
+    "move", "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe",
+    "use", "where", "while", "yield",
+];
@@ -0,0 +1,631 @@
+//! SPL Token Lending accounts.
+//!
+//! The Token Lending program keeps its state in three packed account types:
+//! a [`LendingMarket`], a [`Reserve`] per asset, and an [`Obligation`] per
+//! borrower. Amounts and prices that are not whole token amounts are
+//! fixed-point decimals scaled by [`WAD`] (10^18), stored as `u128`.
+//!
+//! The structs here pack to exactly the program's layout, so protocols
+//! built on lending markets can be tested against reserves and obligations
+//! in any state. [`scenarios::lending_market`](crate::extensions::scenarios::lending_market)
+//! builds a complete market whose balances agree with each other.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::extensions::lending::{
+//!     RESERVE_LEN, Reserve, TOKEN_LENDING_PROGRAM, WAD, create_reserve_account,
+//! };
+//! use solana_pubkey::Pubkey;
+//!
+//! let mut reserve = Reserve::default();
+//! reserve.lending_market = Pubkey::new_unique();
+//! reserve.liquidity.available_amount = 600;
+//! reserve.liquidity.borrowed_amount_wads = 400 * WAD;
+//! reserve.collateral.mint_total_supply = 500;
+//!
+//! // 1000 liquidity tokens back 500 collateral tokens
+//! assert_eq!(reserve.collateral_to_liquidity(100), 200);
+//!
+//! let account = create_reserve_account(&reserve, &TOKEN_LENDING_PROGRAM).unwrap();
+//! assert_eq!(account.data.len(), RESERVE_LEN);
+//! assert_eq!(Reserve::unpack(&account.data).unwrap(), reserve);
+//! ```
+
+use crate::well_known::TOKEN_PROGRAM;
+use crate::{AccountBuilder, AccountGenError};
+use solana_account::Account;
+use solana_pubkey::{Pubkey, pubkey};
+
+/// The SPL Token Lending program deployed on mainnet and devnet.
+pub const TOKEN_LENDING_PROGRAM: Pubkey = pubkey!("LendZqTs7gn5CTSJU1jWKhKuVpjJGom45nnwPb2AMTi");
+
+/// The version byte the program writes at the start of every account.
+pub const PROGRAM_VERSION: u8 = 1;
+
+/// The scale of fixed-point decimals: 1.0 is stored as `WAD`.
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+/// Size of a lending market account.
+pub const LENDING_MARKET_LEN: usize = 258;
+
+/// Size of a reserve account.
+pub const RESERVE_LEN: usize = 571;
+
+/// Size of an obligation account.
+pub const OBLIGATION_LEN: usize = 916;
+
+/// The most reserves an obligation can deposit into and borrow from,
+/// together.
+pub const MAX_OBLIGATION_RESERVES: usize = 10;
+
+const OBLIGATION_COLLATERAL_LEN: usize = 56;
+const OBLIGATION_LIQUIDITY_LEN: usize = 80;
+/// Space for the deposits and borrows, after the fixed obligation fields.
+const OBLIGATION_FLAT_LEN: usize = 776;
+
+/// Returns `quote` as a lending market's quote currency: the ASCII symbol,
+/// such as `"USD"`, padded with zeros to 32 bytes.
+///
+/// # Panics
+///
+/// Panics if `quote` is longer than 32 bytes.
+pub fn quote_currency(quote: &str) -> [u8; 32] {
+    assert!(
+        quote.len() <= 32,
+        "quote currency {:?} is over 32 bytes",
+        quote
+    );
+    let mut currency = [0u8; 32];
+    currency[..quote.len()].copy_from_slice(quote.as_bytes());
+    currency
+}
+
+/// Derives the authority of a lending market: the PDA of the market's
+/// address, which owns the reserves' token accounts and collateral mints.
+pub fn lending_market_authority(lending_market: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[lending_market.as_ref()], program_id)
+}
+
+/// A lending market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LendingMarket {
+    /// The bump of [`lending_market_authority`].
+    pub bump_seed: u8,
+    /// The account that may add reserves and change their configuration.
+    pub owner: Pubkey,
+    /// The currency reserve prices are quoted in; see [`quote_currency`].
+    pub quote_currency: [u8; 32],
+    /// The token program of the market's mints and token accounts.
+    pub token_program_id: Pubkey,
+    /// The oracle program supplying reserve prices.
+    pub oracle_program_id: Pubkey,
+}
+
+impl Default for LendingMarket {
+    fn default() -> Self {
+        Self {
+            bump_seed: 0,
+            owner: Pubkey::default(),
+            quote_currency: quote_currency("USD"),
+            token_program_id: TOKEN_PROGRAM,
+            oracle_program_id: Pubkey::default(),
+        }
+    }
+}
+
+impl LendingMarket {
+    /// Packs the market into its account data.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(LENDING_MARKET_LEN);
+        data.push(PROGRAM_VERSION);
+        data.push(self.bump_seed);
+        data.extend_from_slice(self.owner.as_ref());
+        data.extend_from_slice(&self.quote_currency);
+        data.extend_from_slice(self.token_program_id.as_ref());
+        data.extend_from_slice(self.oracle_program_id.as_ref());
+        data.resize(LENDING_MARKET_LEN, 0);
+        data
+    }
+
+    /// Unpacks a market from account data.
+    pub fn unpack(data: &[u8]) -> Result<Self, AccountGenError> {
+        let mut reader = Reader::new("lending market", data, LENDING_MARKET_LEN)?;
+        Ok(Self {
+            bump_seed: reader.u8(),
+            owner: reader.pubkey(),
+            quote_currency: reader.array(),
+            token_program_id: reader.pubkey(),
+            oracle_program_id: reader.pubkey(),
+        })
+    }
+}
+
+/// The last slot a reserve or obligation was refreshed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LastUpdate {
+    /// The slot of the last refresh.
+    pub slot: u64,
+    /// Whether a change since the refresh requires another one before the
+    /// account can be used.
+    pub stale: bool,
+}
+
+/// The liquidity side of a reserve: the asset lent out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReserveLiquidity {
+    /// The mint of the asset.
+    pub mint: Pubkey,
+    /// The decimals of the mint.
+    pub mint_decimals: u8,
+    /// The token account holding the available liquidity.
+    pub supply: Pubkey,
+    /// The token account receiving borrow fees.
+    pub fee_receiver: Pubkey,
+    /// The oracle price account of the asset.
+    pub oracle: Pubkey,
+    /// Liquidity in `supply` that can be borrowed.
+    pub available_amount: u64,
+    /// Liquidity borrowed, including accrued interest, in wads.
+    pub borrowed_amount_wads: u128,
+    /// The product of every interest rate applied so far, in wads; starts
+    /// at 1.
+    pub cumulative_borrow_rate_wads: u128,
+    /// The price of one whole token in the quote currency, in wads.
+    pub market_price: u128,
+}
+
+impl Default for ReserveLiquidity {
+    fn default() -> Self {
+        Self {
+            mint: Pubkey::default(),
+            mint_decimals: 0,
+            supply: Pubkey::default(),
+            fee_receiver: Pubkey::default(),
+            oracle: Pubkey::default(),
+            available_amount: 0,
+            borrowed_amount_wads: 0,
+            cumulative_borrow_rate_wads: WAD,
+            market_price: WAD,
+        }
+    }
+}
+
+/// The collateral side of a reserve: the tokens minted to depositors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReserveCollateral {
+    /// The collateral mint, whose authority is the market authority.
+    pub mint: Pubkey,
+    /// The supply of the collateral mint.
+    pub mint_total_supply: u64,
+    /// The token account holding collateral deposited into obligations.
+    pub supply: Pubkey,
+}
+
+/// The risk and fee parameters of a reserve. Ratios and rates are whole
+/// percentages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReserveConfig {
+    /// The utilization rate at which the borrow rate is optimal.
+    pub optimal_utilization_rate: u8,
+    /// How much of a deposit's value can be borrowed against.
+    pub loan_to_value_ratio: u8,
+    /// The bonus liquidators receive on seized collateral.
+    pub liquidation_bonus: u8,
+    /// The borrowed share of a deposit's value at which an obligation can
+    /// be liquidated.
+    pub liquidation_threshold: u8,
+    /// The borrow rate at no utilization.
+    pub min_borrow_rate: u8,
+    /// The borrow rate at the optimal utilization rate.
+    pub optimal_borrow_rate: u8,
+    /// The borrow rate at full utilization.
+    pub max_borrow_rate: u8,
+    /// The fee charged on every borrow, in wads.
+    pub borrow_fee_wad: u64,
+    /// The fee charged on flash loans, in wads.
+    pub flash_loan_fee_wad: u64,
+    /// The share of fees paid to a host account, if one is given.
+    pub host_fee_percentage: u8,
+}
+
+impl Default for ReserveConfig {
+    /// The configuration of the program's own tests.
+    fn default() -> Self {
+        Self {
+            optimal_utilization_rate: 80,
+            loan_to_value_ratio: 50,
+            liquidation_bonus: 5,
+            liquidation_threshold: 55,
+            min_borrow_rate: 0,
+            optimal_borrow_rate: 4,
+            max_borrow_rate: 30,
+            borrow_fee_wad: 100_000_000_000_000,
+            flash_loan_fee_wad: 3_000_000_000_000_000,
+            host_fee_percentage: 20,
+        }
+    }
+}
+
+/// A reserve: one asset of a lending market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Reserve {
+    /// When the reserve was last refreshed.
+    pub last_update: LastUpdate,
+    /// The market the reserve belongs to.
+    pub lending_market: Pubkey,
+    /// The asset lent out.
+    pub liquidity: ReserveLiquidity,
+    /// The tokens minted to depositors.
+    pub collateral: ReserveCollateral,
+    /// Risk and fee parameters.
+    pub config: ReserveConfig,
+}
+
+impl Reserve {
+    /// Returns the liquidity the reserve holds or has lent out, in wads.
+    pub fn total_liquidity_wads(&self) -> u128 {
+        self.liquidity.available_amount as u128 * WAD + self.liquidity.borrowed_amount_wads
+    }
+
+    /// Converts collateral to the liquidity it can be redeemed for, rounded
+    /// down, at the exchange rate of total liquidity to collateral supply.
+    /// An empty reserve exchanges one to one.
+    pub fn collateral_to_liquidity(&self, collateral: u64) -> u64 {
+        if self.collateral.mint_total_supply == 0 {
+            return collateral;
+        }
+        let liquidity = collateral as u128 * self.total_liquidity_wads()
+            / self.collateral.mint_total_supply as u128
+            / WAD;
+        liquidity as u64
+    }
+
+    /// Returns the value of a liquidity amount, given in wads, in the quote
+    /// currency, in wads.
+    pub fn market_value(&self, amount_wads: u128) -> u128 {
+        let tokens_wads = amount_wads / 10u128.pow(self.liquidity.mint_decimals as u32);
+        mul_wads(tokens_wads, self.liquidity.market_price)
+    }
+
+    /// Packs the reserve into its account data.
+    pub fn pack(&self) -> Vec<u8> {
+        let liquidity = &self.liquidity;
+        let collateral = &self.collateral;
+        let config = &self.config;
+        let mut data = Vec::with_capacity(RESERVE_LEN);
+        data.push(PROGRAM_VERSION);
+        pack_last_update(&mut data, &self.last_update);
+        data.extend_from_slice(self.lending_market.as_ref());
+        data.extend_from_slice(liquidity.mint.as_ref());
+        data.push(liquidity.mint_decimals);
+        data.extend_from_slice(liquidity.supply.as_ref());
+        data.extend_from_slice(liquidity.fee_receiver.as_ref());
+        data.extend_from_slice(liquidity.oracle.as_ref());
+        data.extend_from_slice(&liquidity.available_amount.to_le_bytes());
+        data.extend_from_slice(&liquidity.borrowed_amount_wads.to_le_bytes());
+        data.extend_from_slice(&liquidity.cumulative_borrow_rate_wads.to_le_bytes());
+        data.extend_from_slice(&liquidity.market_price.to_le_bytes());
+        data.extend_from_slice(collateral.mint.as_ref());
+        data.extend_from_slice(&collateral.mint_total_supply.to_le_bytes());
+        data.extend_from_slice(collateral.supply.as_ref());
+        data.extend_from_slice(&[
+            config.optimal_utilization_rate,
+            config.loan_to_value_ratio,
+            config.liquidation_bonus,
+            config.liquidation_threshold,
+            config.min_borrow_rate,
+            config.optimal_borrow_rate,
+            config.max_borrow_rate,
+        ]);
+        data.extend_from_slice(&config.borrow_fee_wad.to_le_bytes());
+        data.extend_from_slice(&config.flash_loan_fee_wad.to_le_bytes());
+        data.push(config.host_fee_percentage);
+        data.resize(RESERVE_LEN, 0);
+        data
+    }
+
+    /// Unpacks a reserve from account data.
+    pub fn unpack(data: &[u8]) -> Result<Self, AccountGenError> {
+        let mut reader = Reader::new("reserve", data, RESERVE_LEN)?;
+        Ok(Self {
+            last_update: reader.last_update(),
+            lending_market: reader.pubkey(),
+            liquidity: ReserveLiquidity {
+                mint: reader.pubkey(),
+                mint_decimals: reader.u8(),
+                supply: reader.pubkey(),
+                fee_receiver: reader.pubkey(),
+                oracle: reader.pubkey(),
+                available_amount: reader.u64(),
+                borrowed_amount_wads: reader.u128(),
+                cumulative_borrow_rate_wads: reader.u128(),
+                market_price: reader.u128(),
+            },
+            collateral: ReserveCollateral {
+                mint: reader.pubkey(),
+                mint_total_supply: reader.u64(),
+                supply: reader.pubkey(),
+            },
+            config: ReserveConfig {
+                optimal_utilization_rate: reader.u8(),
+                loan_to_value_ratio: reader.u8(),
+                liquidation_bonus: reader.u8(),
+                liquidation_threshold: reader.u8(),
+                min_borrow_rate: reader.u8(),
+                optimal_borrow_rate: reader.u8(),
+                max_borrow_rate: reader.u8(),
+                borrow_fee_wad: reader.u64(),
+                flash_loan_fee_wad: reader.u64(),
+                host_fee_percentage: reader.u8(),
+            },
+        })
+    }
+}
+
+/// Collateral an obligation has deposited into one reserve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ObligationCollateral {
+    /// The reserve the collateral was minted by.
+    pub deposit_reserve: Pubkey,
+    /// The amount of collateral deposited.
+    pub deposited_amount: u64,
+    /// The value of the collateral in the quote currency, in wads.
+    pub market_value: u128,
+}
+
+/// Liquidity an obligation has borrowed from one reserve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObligationLiquidity {
+    /// The reserve the liquidity was borrowed from.
+    pub borrow_reserve: Pubkey,
+    /// The reserve's cumulative borrow rate when interest was last accrued,
+    /// in wads.
+    pub cumulative_borrow_rate_wads: u128,
+    /// The amount borrowed, including accrued interest, in wads.
+    pub borrowed_amount_wads: u128,
+    /// The value of the borrowed amount in the quote currency, in wads.
+    pub market_value: u128,
+}
+
+impl Default for ObligationLiquidity {
+    fn default() -> Self {
+        Self {
+            borrow_reserve: Pubkey::default(),
+            cumulative_borrow_rate_wads: WAD,
+            borrowed_amount_wads: 0,
+            market_value: 0,
+        }
+    }
+}
+
+/// A borrower's deposits and borrows in one lending market.
+///
+/// The values are as of the last refresh; the program recomputes them from
+/// the reserves before any borrow or withdrawal.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Obligation {
+    /// When the obligation was last refreshed.
+    pub last_update: LastUpdate,
+    /// The market the obligation belongs to.
+    pub lending_market: Pubkey,
+    /// The borrower.
+    pub owner: Pubkey,
+    /// Deposited collateral, at most one entry per reserve.
+    pub deposits: Vec<ObligationCollateral>,
+    /// Borrowed liquidity, at most one entry per reserve.
+    pub borrows: Vec<ObligationLiquidity>,
+    /// The total value of the deposits, in wads.
+    pub deposited_value: u128,
+    /// The total value of the borrows, in wads.
+    pub borrowed_value: u128,
+    /// The value that can be borrowed against the deposits, in wads.
+    pub allowed_borrow_value: u128,
+    /// The borrowed value at which the obligation can be liquidated, in
+    /// wads.
+    pub unhealthy_borrow_value: u128,
+}
+
+impl Obligation {
+    /// Packs the obligation into its account data.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if it has more than
+    /// [`MAX_OBLIGATION_RESERVES`] deposits and borrows together, or if they
+    /// do not fit in the account: a borrow takes more space than a deposit,
+    /// so at most 9 entries fit when any of them is a borrow.
+    pub fn pack(&self) -> Result<Vec<u8>, AccountGenError> {
+        let entries = self.deposits.len() + self.borrows.len();
+        if entries > MAX_OBLIGATION_RESERVES {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "obligation has {} deposits and borrows, at most {} fit",
+                entries, MAX_OBLIGATION_RESERVES
+            )));
+        }
+        let flat_len = self.deposits.len() * OBLIGATION_COLLATERAL_LEN
+            + self.borrows.len() * OBLIGATION_LIQUIDITY_LEN;
+        if flat_len > OBLIGATION_FLAT_LEN {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "obligation has {} deposits and {} borrows, which take {} bytes, at most {} fit",
+                self.deposits.len(),
+                self.borrows.len(),
+                flat_len,
+                OBLIGATION_FLAT_LEN
+            )));
+        }
+        let mut data = Vec::with_capacity(OBLIGATION_LEN);
+        data.push(PROGRAM_VERSION);
+        pack_last_update(&mut data, &self.last_update);
+        data.extend_from_slice(self.lending_market.as_ref());
+        data.extend_from_slice(self.owner.as_ref());
+        data.extend_from_slice(&self.deposited_value.to_le_bytes());
+        data.extend_from_slice(&self.borrowed_value.to_le_bytes());
+        data.extend_from_slice(&self.allowed_borrow_value.to_le_bytes());
+        data.extend_from_slice(&self.unhealthy_borrow_value.to_le_bytes());
+        data.push(self.deposits.len() as u8);
+        data.push(self.borrows.len() as u8);
+        for deposit in &self.deposits {
+            data.extend_from_slice(deposit.deposit_reserve.as_ref());
+            data.extend_from_slice(&deposit.deposited_amount.to_le_bytes());
+            data.extend_from_slice(&deposit.market_value.to_le_bytes());
+        }
+        for borrow in &self.borrows {
+            data.extend_from_slice(borrow.borrow_reserve.as_ref());
+            data.extend_from_slice(&borrow.cumulative_borrow_rate_wads.to_le_bytes());
+            data.extend_from_slice(&borrow.borrowed_amount_wads.to_le_bytes());
+            data.extend_from_slice(&borrow.market_value.to_le_bytes());
+        }
+        data.resize(OBLIGATION_LEN, 0);
+        Ok(data)
+    }
+
+    /// Unpacks an obligation from account data.
+    pub fn unpack(data: &[u8]) -> Result<Self, AccountGenError> {
+        let mut reader = Reader::new("obligation", data, OBLIGATION_LEN)?;
+        let last_update = reader.last_update();
+        let lending_market = reader.pubkey();
+        let owner = reader.pubkey();
+        let deposited_value = reader.u128();
+        let borrowed_value = reader.u128();
+        let allowed_borrow_value = reader.u128();
+        let unhealthy_borrow_value = reader.u128();
+        let deposits_len = reader.u8() as usize;
+        let borrows_len = reader.u8() as usize;
+        let flat_len =
+            deposits_len * OBLIGATION_COLLATERAL_LEN + borrows_len * OBLIGATION_LIQUIDITY_LEN;
+        if flat_len > OBLIGATION_LEN - reader.offset {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "obligation lists {} deposits and {} borrows, more than fit",
+                deposits_len, borrows_len
+            )));
+        }
+        let deposits = (0..deposits_len)
+            .map(|_| ObligationCollateral {
+                deposit_reserve: reader.pubkey(),
+                deposited_amount: reader.u64(),
+                market_value: reader.u128(),
+            })
+            .collect();
+        let borrows = (0..borrows_len)
+            .map(|_| ObligationLiquidity {
+                borrow_reserve: reader.pubkey(),
+                cumulative_borrow_rate_wads: reader.u128(),
+                borrowed_amount_wads: reader.u128(),
+                market_value: reader.u128(),
+            })
+            .collect();
+        Ok(Self {
+            last_update,
+            lending_market,
+            owner,
+            deposits,
+            borrows,
+            deposited_value,
+            borrowed_value,
+            allowed_borrow_value,
+            unhealthy_borrow_value,
+        })
+    }
+}
+
+/// Creates a rent-exempt lending market account owned by `program_id`.
+pub fn create_lending_market_account(
+    market: &LendingMarket,
+    program_id: &Pubkey,
+) -> Result<Account, AccountGenError> {
+    AccountBuilder::new()
+        .owner(*program_id)
+        .data_raw(market.pack())
+        .try_build()
+}
+
+/// Creates a rent-exempt reserve account owned by `program_id`.
+pub fn create_reserve_account(
+    reserve: &Reserve,
+    program_id: &Pubkey,
+) -> Result<Account, AccountGenError> {
+    AccountBuilder::new()
+        .owner(*program_id)
+        .data_raw(reserve.pack())
+        .try_build()
+}
+
+/// Creates a rent-exempt obligation account owned by `program_id`.
+pub fn create_obligation_account(
+    obligation: &Obligation,
+    program_id: &Pubkey,
+) -> Result<Account, AccountGenError> {
+    AccountBuilder::new()
+        .owner(*program_id)
+        .data_raw(obligation.pack()?)
+        .try_build()
+}
+
+/// Multiplies two wad decimals, splitting the operands so the product of
+/// realistic amounts and prices does not overflow.
+fn mul_wads(a: u128, b: u128) -> u128 {
+    let (whole, fraction) = (a / WAD, a % WAD);
+    whole * b + fraction * (b / WAD) + fraction * (b % WAD) / WAD
+}
+
+fn pack_last_update(data: &mut Vec<u8>, last_update: &LastUpdate) {
+    data.extend_from_slice(&last_update.slot.to_le_bytes());
+    data.push(last_update.stale as u8);
+}
+
+/// Reads fields in order from data already checked to be long enough.
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Checks the length and version and starts reading after the version.
+    fn new(kind: &str, data: &'a [u8], len: usize) -> Result<Self, AccountGenError> {
+        if data.len() != len {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "{} data is {} bytes, expected {}",
+                kind,
+                data.len(),
+                len
+            )));
+        }
+        if data[0] != PROGRAM_VERSION {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "{} has version {}, expected {}",
+                kind, data[0], PROGRAM_VERSION
+            )));
+        }
+        Ok(Self { data, offset: 1 })
+    }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        let bytes = self.data[self.offset..self.offset + N].try_into().unwrap();
+        self.offset += N;
+        bytes
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.array::<1>()[0]
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.array())
+    }
+
+    fn u128(&mut self) -> u128 {
+        u128::from_le_bytes(self.array())
+    }
+
+    fn pubkey(&mut self) -> Pubkey {
+        Pubkey::new_from_array(self.array())
+    }
+
+    fn last_update(&mut self) -> LastUpdate {
+        LastUpdate {
+            slot: self.u64(),
+            stale: self.u8() != 0,
+        }
+    }
+}
@@ -5,4 +5,19 @@
 
 pub mod token;
 pub mod program_test; 
-pub mod anchor;
\ No newline at end of file
+pub mod anchor;
+pub mod banks_client;
+pub mod community;
+pub mod compression;
+pub mod constraints;
+pub mod edge_cases;
+pub mod fuzz;
+pub mod genesis;
+pub mod idl;
+pub mod lending;
+pub mod multisig;
+pub mod openbook;
+pub mod precompiles;
+pub mod program_set;
+pub mod scenarios;
+pub mod sysvars;
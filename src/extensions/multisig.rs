@@ -0,0 +1,447 @@
+//! Multi-signer approval scenarios.
+//!
+//! Flows guarded by a multisig need a set of signers, the account that
+//! records them and a threshold, and transactions signed by some but not
+//! all of them. A [`SignerSet`] derives M-of-N signers from a label, the
+//! way [`test_keypair`] does, and builds both kinds of multisig accounts
+//! found on Solana:
+//!
+//! - an SPL Token [multisig](TokenMultisig), which can own token accounts
+//!   and mints, with the signers passed as extra signer accounts of each
+//!   token instruction
+//! - a [Squads v4 multisig](SquadsMultisig), where members approve proposals
+//!   with votes recorded on-chain
+//!
+//! [`SignerSet::partially_signed`] builds transactions with only some of
+//! the approvals, for testing that a program rejects too few, and
+//! [`missing_signers`] reports whose signatures a transaction still lacks.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::extensions::multisig::{SignerSet, TokenMultisig, missing_signers};
+//! use solana_accountgen::well_known::TOKEN_PROGRAM;
+//! use solana_hash::Hash;
+//! use solana_instruction::{AccountMeta, Instruction};
+//! use solana_keypair::Keypair;
+//! use solana_pubkey::Pubkey;
+//!
+//! let signers = SignerSet::new("treasury", 2, 3).unwrap();
+//! let multisig = signers.token_multisig_account(&TOKEN_PROGRAM).unwrap();
+//! assert_eq!(TokenMultisig::unpack(&multisig.data).unwrap().m, 2);
+//!
+//! // A token instruction authorized by the multisig lists the approving
+//! // signers after its own accounts
+//! let mut accounts = vec![AccountMeta::new_readonly(Pubkey::new_unique(), false)];
+//! accounts.extend(signers.signer_metas(&[0, 2]));
+//! let instruction = Instruction::new_with_bytes(TOKEN_PROGRAM, &[3], accounts);
+//!
+//! // Only the first signer has approved so far
+//! let payer = Keypair::new();
+//! let transaction = signers.partially_signed(&[instruction], &payer, &[0], Hash::default());
+//! assert_eq!(missing_signers(&transaction), vec![signers.pubkey(2)]);
+//! ```
+
+use crate::keys::test_keypair;
+use crate::{AccountBuilder, AccountGenError};
+use solana_account::Account;
+use solana_hash::Hash;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_pubkey::{Pubkey, pubkey};
+use solana_signature::Signature;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+use super::anchor::get_account_discriminator;
+
+/// Size of a packed SPL Token multisig.
+pub const TOKEN_MULTISIG_LEN: usize = 355;
+
+/// The most signers an SPL Token multisig can have.
+pub const TOKEN_MULTISIG_MAX_SIGNERS: usize = 11;
+
+/// The Squads v4 multisig program.
+pub const SQUADS_PROGRAM: Pubkey = pubkey!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+
+/// Squads member permission to create proposals.
+pub const PERMISSION_INITIATE: u8 = 1 << 0;
+/// Squads member permission to vote on proposals.
+pub const PERMISSION_VOTE: u8 = 1 << 1;
+/// Squads member permission to execute approved proposals.
+pub const PERMISSION_EXECUTE: u8 = 1 << 2;
+/// Every Squads member permission.
+pub const PERMISSION_ALL: u8 = PERMISSION_INITIATE | PERMISSION_VOTE | PERMISSION_EXECUTE;
+
+/// A set of N signers of which M must approve.
+#[derive(Debug)]
+pub struct SignerSet {
+    threshold: usize,
+    keypairs: Vec<Keypair>,
+}
+
+impl SignerSet {
+    /// Derives `count` signers from `label`, of which `threshold` must
+    /// approve. Signer `i` is the [`test_keypair`] of `"<label>/<i>"`, so
+    /// the set is the same in every run.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::ConstraintViolation`] if `threshold` is
+    /// zero or larger than `count`.
+    pub fn new(label: &str, threshold: usize, count: usize) -> Result<Self, AccountGenError> {
+        let keypairs = (0..count)
+            .map(|i| test_keypair(&format!("{label}/{i}")))
+            .collect();
+        Self::from_keypairs(threshold, keypairs)
+    }
+
+    /// Creates a set from existing keypairs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::ConstraintViolation`] if `threshold` is
+    /// zero or larger than the number of keypairs.
+    pub fn from_keypairs(
+        threshold: usize,
+        keypairs: Vec<Keypair>,
+    ) -> Result<Self, AccountGenError> {
+        if threshold == 0 || threshold > keypairs.len() {
+            return Err(AccountGenError::ConstraintViolation(format!(
+                "a threshold of {} is not possible with {} signers",
+                threshold,
+                keypairs.len()
+            )));
+        }
+        Ok(Self {
+            threshold,
+            keypairs,
+        })
+    }
+
+    /// Returns the number of approvals required.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Returns the number of signers.
+    pub fn len(&self) -> usize {
+        self.keypairs.len()
+    }
+
+    /// Returns true if the set has no signers, which a constructed set
+    /// never does.
+    pub fn is_empty(&self) -> bool {
+        self.keypairs.is_empty()
+    }
+
+    /// Returns signer `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    pub fn keypair(&self, index: usize) -> &Keypair {
+        &self.keypairs[index]
+    }
+
+    /// Returns the pubkey of signer `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    pub fn pubkey(&self, index: usize) -> Pubkey {
+        self.keypairs[index].pubkey()
+    }
+
+    /// Returns the pubkeys of every signer, in order.
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        self.keypairs.iter().map(Keypair::pubkey).collect()
+    }
+
+    /// Returns the signers at `indices`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index is out of range.
+    pub fn approvals(&self, indices: &[usize]) -> Vec<&Keypair> {
+        indices.iter().map(|i| &self.keypairs[*i]).collect()
+    }
+
+    /// Returns the first `threshold` signers, just enough to approve.
+    pub fn quorum(&self) -> Vec<&Keypair> {
+        self.keypairs[..self.threshold].iter().collect()
+    }
+
+    /// Returns read-only signer metas for the signers at `indices`, as SPL
+    /// Token instructions list them after a multisig authority.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index is out of range.
+    pub fn signer_metas(&self, indices: &[usize]) -> Vec<AccountMeta> {
+        indices
+            .iter()
+            .map(|i| AccountMeta::new_readonly(self.pubkey(*i), true))
+            .collect()
+    }
+
+    /// Builds a transaction signed by `payer` and the signers at
+    /// `approvals` only. The signatures of other required signers are left
+    /// as the default signature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an approval index is out of range, or the signer at it is
+    /// not required by `instructions`.
+    pub fn partially_signed(
+        &self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        approvals: &[usize],
+        blockhash: Hash,
+    ) -> Transaction {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+        let mut signers = vec![payer];
+        signers.extend(self.approvals(approvals));
+        transaction.partial_sign(&signers, blockhash);
+        transaction
+    }
+
+    /// Packs the set as an SPL Token multisig.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::ConstraintViolation`] if the set has more
+    /// than [`TOKEN_MULTISIG_MAX_SIGNERS`] signers.
+    pub fn token_multisig(&self) -> Result<TokenMultisig, AccountGenError> {
+        if self.len() > TOKEN_MULTISIG_MAX_SIGNERS {
+            return Err(AccountGenError::ConstraintViolation(format!(
+                "an SPL Token multisig has at most {} signers, not {}",
+                TOKEN_MULTISIG_MAX_SIGNERS,
+                self.len()
+            )));
+        }
+        Ok(TokenMultisig {
+            m: self.threshold as u8,
+            n: self.len() as u8,
+            is_initialized: true,
+            signers: self.pubkeys(),
+        })
+    }
+
+    /// Creates a rent-exempt SPL Token multisig account of the set, owned
+    /// by `token_program_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::ConstraintViolation`] if the set has more
+    /// than [`TOKEN_MULTISIG_MAX_SIGNERS`] signers.
+    pub fn token_multisig_account(
+        &self,
+        token_program_id: &Pubkey,
+    ) -> Result<Account, AccountGenError> {
+        AccountBuilder::new()
+            .owner(*token_program_id)
+            .data_raw(self.token_multisig()?.pack())
+            .try_build()
+    }
+
+    /// Returns a Squads v4 multisig of the set, created with `create_key`,
+    /// with every member holding every permission and no time lock.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::ConstraintViolation`] if the set has more
+    /// than `u16::MAX` signers.
+    pub fn squads_multisig(&self, create_key: Pubkey) -> Result<SquadsMultisig, AccountGenError> {
+        let threshold = u16::try_from(self.threshold).map_err(|_| {
+            AccountGenError::ConstraintViolation(format!(
+                "a Squads threshold is at most {}, not {}",
+                u16::MAX,
+                self.threshold
+            ))
+        })?;
+        let (_, bump) = SquadsMultisig::address(&create_key);
+        Ok(SquadsMultisig {
+            create_key,
+            config_authority: Pubkey::default(),
+            threshold,
+            time_lock: 0,
+            transaction_index: 0,
+            stale_transaction_index: 0,
+            rent_collector: None,
+            bump,
+            members: self
+                .pubkeys()
+                .into_iter()
+                .map(|key| (key, PERMISSION_ALL))
+                .collect(),
+        })
+    }
+}
+
+/// An unpacked SPL Token multisig.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMultisig {
+    /// The number of signers required.
+    pub m: u8,
+    /// The number of valid signers.
+    pub n: u8,
+    /// Whether the multisig is initialized.
+    pub is_initialized: bool,
+    /// The `n` signers.
+    pub signers: Vec<Pubkey>,
+}
+
+impl TokenMultisig {
+    /// Packs the multisig into the 355-byte SPL layout, zeroing the unused
+    /// signer slots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than [`TOKEN_MULTISIG_MAX_SIGNERS`]
+    /// signers.
+    pub fn pack(&self) -> Vec<u8> {
+        assert!(
+            self.signers.len() <= TOKEN_MULTISIG_MAX_SIGNERS,
+            "an SPL Token multisig has at most {} signers",
+            TOKEN_MULTISIG_MAX_SIGNERS
+        );
+        let mut data = Vec::with_capacity(TOKEN_MULTISIG_LEN);
+        data.extend_from_slice(&[self.m, self.n, self.is_initialized.into()]);
+        for signer in &self.signers {
+            data.extend_from_slice(signer.as_ref());
+        }
+        data.resize(TOKEN_MULTISIG_LEN, 0);
+        data
+    }
+
+    /// Unpacks the first 355 bytes of `data`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the data is too
+    /// short or `n` is out of range.
+    pub fn unpack(data: &[u8]) -> Result<Self, AccountGenError> {
+        if data.len() < TOKEN_MULTISIG_LEN {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "token multisig data is {} bytes, expected at least {}",
+                data.len(),
+                TOKEN_MULTISIG_LEN
+            )));
+        }
+        let n = data[1] as usize;
+        if n > TOKEN_MULTISIG_MAX_SIGNERS {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "token multisig has {} signers, at most {} fit",
+                n, TOKEN_MULTISIG_MAX_SIGNERS
+            )));
+        }
+        Ok(Self {
+            m: data[0],
+            n: data[1],
+            is_initialized: data[2] != 0,
+            signers: data[3..3 + n * 32]
+                .chunks_exact(32)
+                .map(|chunk| Pubkey::new_from_array(chunk.try_into().expect("32 bytes")))
+                .collect(),
+        })
+    }
+}
+
+/// A Squads v4 `Multisig` account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SquadsMultisig {
+    /// The key the multisig address is derived from.
+    pub create_key: Pubkey,
+    /// The authority that may change the configuration without a
+    /// proposal; the default pubkey makes the multisig autonomous.
+    pub config_authority: Pubkey,
+    /// The number of approvals a proposal needs.
+    pub threshold: u16,
+    /// Seconds between approval and execution.
+    pub time_lock: u32,
+    /// The index of the last transaction created.
+    pub transaction_index: u64,
+    /// Transactions up to this index can no longer be executed.
+    pub stale_transaction_index: u64,
+    /// Who may reclaim the rent of executed transactions.
+    pub rent_collector: Option<Pubkey>,
+    /// The bump of the multisig address.
+    pub bump: u8,
+    /// Every member with its permission mask, sorted by key when packed.
+    pub members: Vec<(Pubkey, u8)>,
+}
+
+impl SquadsMultisig {
+    /// Returns the multisig address derived from `create_key`, and its
+    /// bump.
+    pub fn address(create_key: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"multisig", b"multisig", create_key.as_ref()],
+            &SQUADS_PROGRAM,
+        )
+    }
+
+    /// Returns the size Squads allocates for a multisig of `members`
+    /// members, including the discriminator.
+    pub fn size(members: usize) -> usize {
+        8 + 32 + 32 + 2 + 4 + 8 + 8 + 33 + 1 + 4 + members * 33
+    }
+
+    /// Packs the multisig with its Anchor discriminator, padded to
+    /// [`size`](Self::size).
+    pub fn pack(&self) -> Vec<u8> {
+        let mut members = self.members.clone();
+        members.sort_by_key(|(key, _)| *key);
+
+        let mut data = get_account_discriminator("Multisig").to_vec();
+        data.extend_from_slice(self.create_key.as_ref());
+        data.extend_from_slice(self.config_authority.as_ref());
+        data.extend_from_slice(&self.threshold.to_le_bytes());
+        data.extend_from_slice(&self.time_lock.to_le_bytes());
+        data.extend_from_slice(&self.transaction_index.to_le_bytes());
+        data.extend_from_slice(&self.stale_transaction_index.to_le_bytes());
+        match self.rent_collector {
+            Some(rent_collector) => {
+                data.push(1);
+                data.extend_from_slice(rent_collector.as_ref());
+            }
+            None => data.push(0),
+        }
+        data.push(self.bump);
+        data.extend_from_slice(&(members.len() as u32).to_le_bytes());
+        for (key, permissions) in &members {
+            data.extend_from_slice(key.as_ref());
+            data.push(*permissions);
+        }
+        data.resize(Self::size(members.len()), 0);
+        data
+    }
+
+    /// Returns the multisig's address and a rent-exempt account holding
+    /// it, owned by the Squads program.
+    pub fn to_account(&self) -> Result<(Pubkey, Account), AccountGenError> {
+        let (address, _) = Self::address(&self.create_key);
+        let account = AccountBuilder::new()
+            .owner(SQUADS_PROGRAM)
+            .data_raw(self.pack())
+            .try_build()?;
+        Ok((address, account))
+    }
+}
+
+/// Returns the required signers of `transaction` whose signature is still
+/// missing, in the order the message lists them.
+pub fn missing_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    let required = transaction.message.header.num_required_signatures as usize;
+    transaction
+        .message
+        .account_keys
+        .iter()
+        .take(required)
+        .zip(&transaction.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(pubkey, _)| *pubkey)
+        .collect()
+}
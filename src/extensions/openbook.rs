@@ -0,0 +1,651 @@
+//! OpenBook (Serum DEX v3) market accounts.
+//!
+//! An OpenBook market is spread over several accounts of the DEX program:
+//! the [`Market`] state, an [`EventQueue`] of fills and cancellations, and
+//! one crit-bit tree [`Slab`] each for bids and asks. Every account is
+//! framed by the 5-byte `"serum"` head padding and the 7-byte `"padding"`
+//! tail, and starts with account flags naming its kind.
+//!
+//! The slabs are the part no one wants to write by hand: orders are leaves
+//! of a binary tree keyed by order ID, and inner nodes hold the length of
+//! the key prefix their children share. [`Slab::pack`] builds a valid tree
+//! from a list of orders, so the book can start with resting orders.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::extensions::openbook::{
+//!     OPENBOOK_PROGRAM, Order, Side, Slab, create_slab_account, slab_orders,
+//! };
+//! use solana_pubkey::Pubkey;
+//!
+//! let open_orders = Pubkey::new_unique();
+//! let asks = Slab::new(Side::Ask, 64)
+//!     .order(Order::new(105, 10, open_orders))
+//!     .order(Order::new(101, 5, open_orders));
+//! let account = create_slab_account(&asks, &OPENBOOK_PROGRAM).unwrap();
+//!
+//! // The best ask comes first
+//! let orders = slab_orders(&account.data).unwrap();
+//! assert_eq!(orders[0].1.price, 101);
+//! assert_eq!(orders[1].1.price, 105);
+//! ```
+
+use crate::{AccountBuilder, AccountGenError, RingBufferBuilder};
+use solana_account::Account;
+use solana_pubkey::{Pubkey, pubkey};
+
+/// The OpenBook DEX program.
+pub const OPENBOOK_PROGRAM: Pubkey = pubkey!("srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX");
+
+/// The bytes every DEX account starts with.
+pub const HEAD_PADDING: &[u8; 5] = b"serum";
+
+/// The bytes every DEX account ends with.
+pub const TAIL_PADDING: &[u8; 7] = b"padding";
+
+/// Account flag set on every initialized account.
+pub const FLAG_INITIALIZED: u64 = 1 << 0;
+/// Account flag of a market.
+pub const FLAG_MARKET: u64 = 1 << 1;
+/// Account flag of an open orders account.
+pub const FLAG_OPEN_ORDERS: u64 = 1 << 2;
+/// Account flag of a request queue.
+pub const FLAG_REQUEST_QUEUE: u64 = 1 << 3;
+/// Account flag of an event queue.
+pub const FLAG_EVENT_QUEUE: u64 = 1 << 4;
+/// Account flag of a bids slab.
+pub const FLAG_BIDS: u64 = 1 << 5;
+/// Account flag of an asks slab.
+pub const FLAG_ASKS: u64 = 1 << 6;
+
+/// Size of a market account.
+pub const MARKET_LEN: usize = 388;
+
+/// Size of an event in the event queue.
+pub const EVENT_LEN: usize = 88;
+
+/// Size of a slab node.
+pub const SLAB_NODE_LEN: usize = 72;
+
+const EVENT_QUEUE_HEADER_LEN: usize = 37;
+const SLAB_HEADER_LEN: usize = 45;
+
+/// Event flag of a fill.
+pub const EVENT_FILL: u8 = 1 << 0;
+/// Event flag of an order leaving the book.
+pub const EVENT_OUT: u8 = 1 << 1;
+/// Event flag of an event on the bid side.
+pub const EVENT_BID: u8 = 1 << 2;
+/// Event flag of an event for the maker of a fill.
+pub const EVENT_MAKER: u8 = 1 << 3;
+/// Event flag of an out event that releases the order's locked funds.
+pub const EVENT_RELEASE_FUNDS: u8 = 1 << 4;
+
+const NODE_INNER: u32 = 1;
+const NODE_LEAF: u32 = 2;
+
+/// The side of the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+/// Returns the ID of an order: the price in the high 64 bits and the
+/// sequence number in the low 64, inverted for bids so that earlier bids
+/// at a price sort after later ones and the tree's maximum is the best bid.
+pub fn order_id(side: Side, price: u64, seq_num: u64) -> u128 {
+    let seq_num = match side {
+        Side::Bid => !seq_num,
+        Side::Ask => seq_num,
+    };
+    (price as u128) << 64 | seq_num as u128
+}
+
+/// Finds the nonce of a market's vault signer, the program address that
+/// owns its vaults, and returns the signer with it.
+///
+/// Unlike a PDA, the signer is derived from the market and a `u64` nonce
+/// counting up from zero.
+pub fn vault_signer(market: &Pubkey, program_id: &Pubkey) -> (Pubkey, u64) {
+    (0u64..)
+        .find_map(|nonce| {
+            let seeds: &[&[u8]] = &[market.as_ref(), &nonce.to_le_bytes()];
+            Pubkey::create_program_address(seeds, program_id)
+                .ok()
+                .map(|signer| (signer, nonce))
+        })
+        .unwrap()
+}
+
+/// A market's state.
+///
+/// Sizes and prices are in lots: a base lot is `coin_lot_size` native base
+/// tokens, and a price is the number of `pc_lot_size` native quote tokens
+/// one base lot costs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Market {
+    /// The market's own address.
+    pub own_address: Pubkey,
+    /// The nonce of [`vault_signer`].
+    pub vault_signer_nonce: u64,
+    /// The base mint.
+    pub coin_mint: Pubkey,
+    /// The quote mint.
+    pub pc_mint: Pubkey,
+    /// The base token vault.
+    pub coin_vault: Pubkey,
+    /// Base tokens deposited in the vault.
+    pub coin_deposits_total: u64,
+    /// Base token fees collected.
+    pub coin_fees_accrued: u64,
+    /// The quote token vault.
+    pub pc_vault: Pubkey,
+    /// Quote tokens deposited in the vault.
+    pub pc_deposits_total: u64,
+    /// Quote token fees collected.
+    pub pc_fees_accrued: u64,
+    /// The quote amount below which fees are not swept.
+    pub pc_dust_threshold: u64,
+    /// The request queue.
+    pub request_queue: Pubkey,
+    /// The event queue.
+    pub event_queue: Pubkey,
+    /// The bids slab.
+    pub bids: Pubkey,
+    /// The asks slab.
+    pub asks: Pubkey,
+    /// Native base tokens per base lot.
+    pub coin_lot_size: u64,
+    /// Native quote tokens per quote lot.
+    pub pc_lot_size: u64,
+    /// The fee rate of the market.
+    pub fee_rate_bps: u64,
+    /// Referrer rebates owed.
+    pub referrer_rebates_accrued: u64,
+}
+
+impl Market {
+    /// Packs the market into its account data.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(MARKET_LEN);
+        data.extend_from_slice(HEAD_PADDING);
+        data.extend_from_slice(&(FLAG_INITIALIZED | FLAG_MARKET).to_le_bytes());
+        data.extend_from_slice(self.own_address.as_ref());
+        data.extend_from_slice(&self.vault_signer_nonce.to_le_bytes());
+        data.extend_from_slice(self.coin_mint.as_ref());
+        data.extend_from_slice(self.pc_mint.as_ref());
+        data.extend_from_slice(self.coin_vault.as_ref());
+        data.extend_from_slice(&self.coin_deposits_total.to_le_bytes());
+        data.extend_from_slice(&self.coin_fees_accrued.to_le_bytes());
+        data.extend_from_slice(self.pc_vault.as_ref());
+        data.extend_from_slice(&self.pc_deposits_total.to_le_bytes());
+        data.extend_from_slice(&self.pc_fees_accrued.to_le_bytes());
+        data.extend_from_slice(&self.pc_dust_threshold.to_le_bytes());
+        for address in [
+            &self.request_queue,
+            &self.event_queue,
+            &self.bids,
+            &self.asks,
+        ] {
+            data.extend_from_slice(address.as_ref());
+        }
+        data.extend_from_slice(&self.coin_lot_size.to_le_bytes());
+        data.extend_from_slice(&self.pc_lot_size.to_le_bytes());
+        data.extend_from_slice(&self.fee_rate_bps.to_le_bytes());
+        data.extend_from_slice(&self.referrer_rebates_accrued.to_le_bytes());
+        data.extend_from_slice(TAIL_PADDING);
+        data
+    }
+
+    /// Unpacks a market from account data.
+    pub fn unpack(data: &[u8]) -> Result<Self, AccountGenError> {
+        if data.len() != MARKET_LEN {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "market data is {} bytes, expected {}",
+                data.len(),
+                MARKET_LEN
+            )));
+        }
+        let mut rest = &unframe("market", data, FLAG_MARKET)?[8..];
+        Ok(Self {
+            own_address: take_pubkey(&mut rest),
+            vault_signer_nonce: take_u64(&mut rest),
+            coin_mint: take_pubkey(&mut rest),
+            pc_mint: take_pubkey(&mut rest),
+            coin_vault: take_pubkey(&mut rest),
+            coin_deposits_total: take_u64(&mut rest),
+            coin_fees_accrued: take_u64(&mut rest),
+            pc_vault: take_pubkey(&mut rest),
+            pc_deposits_total: take_u64(&mut rest),
+            pc_fees_accrued: take_u64(&mut rest),
+            pc_dust_threshold: take_u64(&mut rest),
+            request_queue: take_pubkey(&mut rest),
+            event_queue: take_pubkey(&mut rest),
+            bids: take_pubkey(&mut rest),
+            asks: take_pubkey(&mut rest),
+            coin_lot_size: take_u64(&mut rest),
+            pc_lot_size: take_u64(&mut rest),
+            fee_rate_bps: take_u64(&mut rest),
+            referrer_rebates_accrued: take_u64(&mut rest),
+        })
+    }
+}
+
+fn take_u64(rest: &mut &[u8]) -> u64 {
+    let (value, tail) = rest.split_at(8);
+    *rest = tail;
+    u64::from_le_bytes(value.try_into().unwrap())
+}
+
+fn take_pubkey(rest: &mut &[u8]) -> Pubkey {
+    let (value, tail) = rest.split_at(32);
+    *rest = tail;
+    Pubkey::try_from(value).unwrap()
+}
+
+/// An event in the event queue, waiting to be consumed by the crank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Event {
+    /// A combination of the `EVENT_*` flags.
+    pub flags: u8,
+    /// The order's slot in its open orders account.
+    pub owner_slot: u8,
+    /// The fee tier of the order's owner.
+    pub fee_tier: u8,
+    /// Native tokens released to the owner.
+    pub native_qty_released: u64,
+    /// Native tokens the owner paid.
+    pub native_qty_paid: u64,
+    /// The fee charged, or rebate paid, in native quote tokens.
+    pub native_fee_or_rebate: u64,
+    /// The ID of the order.
+    pub order_id: u128,
+    /// The open orders account of the order's owner.
+    pub owner: Pubkey,
+    /// The ID the owner gave the order.
+    pub client_order_id: u64,
+}
+
+impl Event {
+    /// Packs the event into its slot in the queue.
+    pub fn pack(&self) -> [u8; EVENT_LEN] {
+        let mut data = [0u8; EVENT_LEN];
+        data[0] = self.flags;
+        data[1] = self.owner_slot;
+        data[2] = self.fee_tier;
+        data[8..16].copy_from_slice(&self.native_qty_released.to_le_bytes());
+        data[16..24].copy_from_slice(&self.native_qty_paid.to_le_bytes());
+        data[24..32].copy_from_slice(&self.native_fee_or_rebate.to_le_bytes());
+        data[32..48].copy_from_slice(&self.order_id.to_le_bytes());
+        data[48..80].copy_from_slice(self.owner.as_ref());
+        data[80..88].copy_from_slice(&self.client_order_id.to_le_bytes());
+        data
+    }
+}
+
+/// Returns the size of an event queue account holding `capacity` events.
+pub fn event_queue_len(capacity: usize) -> usize {
+    EVENT_QUEUE_HEADER_LEN + capacity * EVENT_LEN + TAIL_PADDING.len()
+}
+
+/// An event queue with room for a fixed number of events.
+///
+/// Mainnet markets allocate 262,156-byte queues, room for 2,978 events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventQueue {
+    capacity: usize,
+    head: usize,
+    seq_num: u64,
+    events: Vec<Event>,
+}
+
+impl EventQueue {
+    /// Creates an empty queue with room for `capacity` events.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            head: 0,
+            seq_num: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Sets the slot of the oldest event. Defaults to 0.
+    pub fn head(mut self, head: usize) -> Self {
+        self.head = head;
+        self
+    }
+
+    /// Appends an event waiting to be consumed.
+    ///
+    /// The queue's sequence number counts every event ever pushed, so it is
+    /// at least the number of events.
+    pub fn event(mut self, event: Event) -> Self {
+        self.events.push(event);
+        self.seq_num += 1;
+        self
+    }
+
+    /// Sets the sequence number, for a queue that has already had events
+    /// consumed. Events added afterwards still increment it.
+    pub fn seq_num(mut self, seq_num: u64) -> Self {
+        self.seq_num = seq_num;
+        self
+    }
+
+    /// Packs the queue into its account data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are more events than the capacity or the
+    /// head is not a slot of the queue.
+    pub fn pack(&self) -> Result<Vec<u8>, AccountGenError> {
+        if self.events.len() > self.capacity {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "{} events do not fit in an event queue of {}",
+                self.events.len(),
+                self.capacity
+            )));
+        }
+        let header = (
+            *HEAD_PADDING,
+            FLAG_INITIALIZED | FLAG_EVENT_QUEUE,
+            self.head as u64,
+            self.events.len() as u64,
+            self.seq_num,
+        );
+        let mut data = RingBufferBuilder::new(header)
+            .record_size(EVENT_LEN)
+            .capacity(self.capacity)
+            .head(self.head)
+            .records(self.events.iter().map(Event::pack))
+            .build()?;
+        data.extend_from_slice(TAIL_PADDING);
+        Ok(data)
+    }
+}
+
+/// An order resting on the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Order {
+    /// The price in lots.
+    pub price: u64,
+    /// The size in base lots.
+    pub quantity: u64,
+    /// The open orders account of the order's owner.
+    pub owner: Pubkey,
+    /// The order's slot in the open orders account.
+    pub owner_slot: u8,
+    /// The fee tier of the order's owner.
+    pub fee_tier: u8,
+    /// The ID the owner gave the order.
+    pub client_order_id: u64,
+}
+
+impl Order {
+    /// Creates an order in slot 0 of `owner` with the base fee tier and no
+    /// client order ID.
+    pub fn new(price: u64, quantity: u64, owner: Pubkey) -> Self {
+        Self {
+            price,
+            quantity,
+            owner,
+            owner_slot: 0,
+            fee_tier: 0,
+            client_order_id: 0,
+        }
+    }
+}
+
+/// Returns the size of a slab account with `capacity` nodes.
+///
+/// A book of `n` orders uses `2n - 1` nodes. Mainnet markets allocate
+/// 65,548-byte slabs, room for 909 nodes.
+pub fn slab_len(capacity: usize) -> usize {
+    SLAB_HEADER_LEN + capacity * SLAB_NODE_LEN + TAIL_PADDING.len()
+}
+
+/// One side of the order book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slab {
+    side: Side,
+    capacity: usize,
+    orders: Vec<Order>,
+}
+
+impl Slab {
+    /// Creates an empty side of the book with room for `capacity` nodes.
+    pub fn new(side: Side, capacity: usize) -> Self {
+        Self {
+            side,
+            capacity,
+            orders: Vec::new(),
+        }
+    }
+
+    /// Adds a resting order.
+    ///
+    /// Orders are given sequence numbers in the order they are added, so at
+    /// equal prices earlier orders fill first.
+    pub fn order(mut self, order: Order) -> Self {
+        self.orders.push(order);
+        self
+    }
+
+    /// Adds resting orders, in time priority.
+    pub fn orders<I: IntoIterator<Item = Order>>(mut self, orders: I) -> Self {
+        self.orders.extend(orders);
+        self
+    }
+
+    /// Packs the slab into its account data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree of the orders needs more nodes than the
+    /// capacity.
+    pub fn pack(&self) -> Result<Vec<u8>, AccountGenError> {
+        let needed = (2 * self.orders.len()).saturating_sub(1);
+        if needed > self.capacity {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "{} orders need {} slab nodes, the slab has {}",
+                self.orders.len(),
+                needed,
+                self.capacity
+            )));
+        }
+
+        let mut leaves: Vec<(u128, &Order)> = self
+            .orders
+            .iter()
+            .enumerate()
+            .map(|(seq_num, order)| (order_id(self.side, order.price, seq_num as u64), order))
+            .collect();
+        leaves.sort_by_key(|(key, _)| *key);
+        let mut nodes = Vec::with_capacity(needed);
+        let root = if leaves.is_empty() {
+            0
+        } else {
+            build_tree(&leaves, &mut nodes)
+        };
+
+        let flag = match self.side {
+            Side::Bid => FLAG_BIDS,
+            Side::Ask => FLAG_ASKS,
+        };
+        let mut data = Vec::with_capacity(slab_len(self.capacity));
+        data.extend_from_slice(HEAD_PADDING);
+        data.extend_from_slice(&(FLAG_INITIALIZED | flag).to_le_bytes());
+        data.extend_from_slice(&(nodes.len() as u64).to_le_bytes()); // bump index
+        data.extend_from_slice(&0u64.to_le_bytes()); // free list length
+        data.extend_from_slice(&0u32.to_le_bytes()); // free list head
+        data.extend_from_slice(&root.to_le_bytes());
+        data.extend_from_slice(&(leaves.len() as u64).to_le_bytes());
+        for node in &nodes {
+            data.extend_from_slice(node);
+        }
+        data.resize(slab_len(self.capacity) - TAIL_PADDING.len(), 0);
+        data.extend_from_slice(TAIL_PADDING);
+        Ok(data)
+    }
+}
+
+/// Appends the subtree of `leaves`, sorted by key, to `nodes` and returns
+/// the index of its root.
+fn build_tree(leaves: &[(u128, &Order)], nodes: &mut Vec<[u8; SLAB_NODE_LEN]>) -> u32 {
+    let index = nodes.len() as u32;
+    let mut node = [0u8; SLAB_NODE_LEN];
+    let (first, _) = leaves[0];
+    if let [(key, order)] = leaves {
+        node[..4].copy_from_slice(&NODE_LEAF.to_le_bytes());
+        node[4] = order.owner_slot;
+        node[5] = order.fee_tier;
+        node[8..24].copy_from_slice(&key.to_le_bytes());
+        node[24..56].copy_from_slice(order.owner.as_ref());
+        node[56..64].copy_from_slice(&order.quantity.to_le_bytes());
+        node[64..72].copy_from_slice(&order.client_order_id.to_le_bytes());
+        nodes.push(node);
+        return index;
+    }
+
+    // The keys are sorted, so the prefix they all share is the prefix of
+    // the first and last
+    let (last, _) = leaves[leaves.len() - 1];
+    let prefix_len = (first ^ last).leading_zeros();
+    let crit_bit = (1u128 << 127) >> prefix_len;
+    let split = leaves.partition_point(|(key, _)| key & crit_bit == 0);
+
+    nodes.push(node);
+    let left = build_tree(&leaves[..split], nodes);
+    let right = build_tree(&leaves[split..], nodes);
+    let node = &mut nodes[index as usize];
+    node[..4].copy_from_slice(&NODE_INNER.to_le_bytes());
+    node[4..8].copy_from_slice(&prefix_len.to_le_bytes());
+    node[8..24].copy_from_slice(&first.to_le_bytes());
+    node[24..28].copy_from_slice(&left.to_le_bytes());
+    node[28..32].copy_from_slice(&right.to_le_bytes());
+    index
+}
+
+/// Reads the orders of a slab account, with their order IDs, in ascending
+/// order of ID: best ask first, or best bid last.
+pub fn slab_orders(data: &[u8]) -> Result<Vec<(u128, Order)>, AccountGenError> {
+    let body = unframe("slab", data, FLAG_BIDS | FLAG_ASKS)?;
+    if body.len() < SLAB_HEADER_LEN - HEAD_PADDING.len() {
+        return Err(AccountGenError::InvalidDataFormat(format!(
+            "slab data is {} bytes, too short for the slab header",
+            data.len()
+        )));
+    }
+    let header = &body[8..];
+    let word = |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap());
+    let root = word(20);
+    let leaf_count = u64::from_le_bytes(header[24..32].try_into().unwrap());
+    let nodes = &header[32..];
+    let node = |index: u32| {
+        let offset = index as usize * SLAB_NODE_LEN;
+        nodes.get(offset..offset + SLAB_NODE_LEN).ok_or_else(|| {
+            AccountGenError::InvalidDataFormat(format!("slab node {} is out of bounds", index))
+        })
+    };
+
+    let mut orders = Vec::new();
+    if leaf_count == 0 {
+        return Ok(orders);
+    }
+    let mut stack = vec![root];
+    let mut visited = 0u64;
+    while let Some(index) = stack.pop() {
+        // A tree of n leaves has 2n - 1 nodes; more means a cycle
+        visited += 1;
+        if visited >= leaf_count.saturating_mul(2) {
+            return Err(AccountGenError::InvalidDataFormat(
+                "slab tree has more nodes than its leaves allow".to_string(),
+            ));
+        }
+        let node = node(index)?;
+        match u32::from_le_bytes(node[..4].try_into().unwrap()) {
+            NODE_INNER => {
+                // Right first so the left subtree is read first
+                stack.push(u32::from_le_bytes(node[28..32].try_into().unwrap()));
+                stack.push(u32::from_le_bytes(node[24..28].try_into().unwrap()));
+            }
+            NODE_LEAF => {
+                let key = u128::from_le_bytes(node[8..24].try_into().unwrap());
+                orders.push((
+                    key,
+                    Order {
+                        price: (key >> 64) as u64,
+                        quantity: u64::from_le_bytes(node[56..64].try_into().unwrap()),
+                        owner: Pubkey::try_from(&node[24..56]).unwrap(),
+                        owner_slot: node[4],
+                        fee_tier: node[5],
+                        client_order_id: u64::from_le_bytes(node[64..72].try_into().unwrap()),
+                    },
+                ));
+            }
+            tag => {
+                return Err(AccountGenError::InvalidDataFormat(format!(
+                    "slab node {} has tag {}, expected an inner node or leaf",
+                    index, tag
+                )));
+            }
+        }
+    }
+    Ok(orders)
+}
+
+/// Checks the padding and flags of a DEX account and returns the data
+/// between the paddings.
+fn unframe<'a>(kind: &str, data: &'a [u8], flags: u64) -> Result<&'a [u8], AccountGenError> {
+    let framed = data.len() >= HEAD_PADDING.len() + 8 + TAIL_PADDING.len()
+        && data.starts_with(HEAD_PADDING)
+        && data.ends_with(TAIL_PADDING);
+    if !framed {
+        return Err(AccountGenError::InvalidDataFormat(format!(
+            "{} data is not framed by the DEX account padding",
+            kind
+        )));
+    }
+    let body = &data[HEAD_PADDING.len()..data.len() - TAIL_PADDING.len()];
+    let account_flags = u64::from_le_bytes(body[..8].try_into().unwrap());
+    if account_flags & FLAG_INITIALIZED == 0 || account_flags & flags == 0 {
+        return Err(AccountGenError::InvalidDataFormat(format!(
+            "account flags {:#x} are not those of an initialized {}",
+            account_flags, kind
+        )));
+    }
+    Ok(body)
+}
+
+/// Creates a rent-exempt market account owned by `program_id`.
+pub fn create_market_account(
+    market: &Market,
+    program_id: &Pubkey,
+) -> Result<Account, AccountGenError> {
+    AccountBuilder::new()
+        .owner(*program_id)
+        .data_raw(market.pack())
+        .try_build()
+}
+
+/// Creates a rent-exempt event queue account owned by `program_id`.
+pub fn create_event_queue_account(
+    queue: &EventQueue,
+    program_id: &Pubkey,
+) -> Result<Account, AccountGenError> {
+    AccountBuilder::new()
+        .owner(*program_id)
+        .data_raw(queue.pack()?)
+        .try_build()
+}
+
+/// Creates a rent-exempt bids or asks account owned by `program_id`.
+pub fn create_slab_account(slab: &Slab, program_id: &Pubkey) -> Result<Account, AccountGenError> {
+    AccountBuilder::new()
+        .owner(*program_id)
+        .data_raw(slab.pack()?)
+        .try_build()
+}
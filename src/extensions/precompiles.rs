@@ -0,0 +1,177 @@
+//! Instructions for the ed25519 and secp256k1 signature precompiles.
+//!
+//! Programs that check signatures made off chain do not verify them
+//! themselves: the transaction carries a precompile instruction that the
+//! runtime verifies, and the program inspects it through the Instructions
+//! sysvar. The builders here produce valid precompile instructions, with
+//! the offsets table pointing into the instruction's own data, and
+//! [`create_instructions_sysvar_account`](crate::extensions::sysvars::create_instructions_sysvar_account)
+//! produces the matching sysvar for tests that call the program directly.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::extensions::precompiles::ed25519_instruction;
+//! use solana_accountgen::extensions::sysvars::create_instructions_sysvar_account;
+//! use solana_instruction::{AccountMeta, Instruction};
+//! use solana_keypair::Keypair;
+//! use solana_pubkey::Pubkey;
+//! use solana_sdk_ids::sysvar;
+//!
+//! let oracle = Keypair::new();
+//! let verify = ed25519_instruction(&oracle, b"price:42");
+//! let update = Instruction::new_with_bytes(
+//!     Pubkey::new_unique(),
+//!     b"price:42",
+//!     vec![AccountMeta::new_readonly(sysvar::instructions::id(), false)],
+//! );
+//!
+//! // The program runs as instruction 1 and looks back at instruction 0
+//! let sysvar_account = create_instructions_sysvar_account(&[verify, update], 1);
+//! ```
+
+use crate::error::AccountGenError;
+use solana_ed25519_program::{
+    Ed25519SignatureOffsets, SIGNATURE_OFFSETS_SERIALIZED_SIZE, SIGNATURE_OFFSETS_START,
+    offsets_to_ed25519_instruction,
+};
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_secp256k1_program::{
+    DATA_START as SECP256K1_DATA_START, HASHED_PUBKEY_SERIALIZED_SIZE, SecpSignatureOffsets,
+    construct_eth_pubkey, new_secp256k1_instruction,
+};
+use solana_signature::Signature;
+use solana_signer::Signer;
+
+/// The ed25519 offsets value meaning "the precompile instruction itself".
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Signs `message` with `signer` and builds an ed25519 precompile
+/// instruction verifying the signature.
+///
+/// The public key, signature and message are stored in the instruction, so
+/// it verifies at any position in the transaction.
+pub fn ed25519_instruction(signer: &Keypair, message: &[u8]) -> Instruction {
+    let signature = signer.sign_message(message);
+    ed25519_instruction_with_signatures(&[(signer.pubkey(), signature, message)])
+}
+
+/// Builds an ed25519 precompile instruction verifying several signatures.
+///
+/// The signatures are not checked here, so invalid ones can be used to test
+/// that a transaction carrying them is rejected.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::precompiles::ed25519_instruction_with_signatures;
+/// use solana_keypair::Keypair;
+/// use solana_signer::Signer;
+///
+/// let (alice, bob) = (Keypair::new(), Keypair::new());
+/// let instruction = ed25519_instruction_with_signatures(&[
+///     (alice.pubkey(), alice.sign_message(b"yes"), b"yes"),
+///     (bob.pubkey(), bob.sign_message(b"no"), b"no"),
+/// ]);
+/// assert_eq!(instruction.data[0], 2);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the instruction data would exceed the 16-bit offsets.
+pub fn ed25519_instruction_with_signatures(
+    signatures: &[(Pubkey, Signature, &[u8])],
+) -> Instruction {
+    let mut payload = Vec::new();
+    let mut offsets = Vec::with_capacity(signatures.len());
+    let header_len = SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SERIALIZED_SIZE * signatures.len();
+    let offset = |payload: &Vec<u8>| {
+        u16::try_from(header_len + payload.len()).expect("ed25519 instruction data too large")
+    };
+
+    for (pubkey, signature, message) in signatures {
+        let public_key_offset = offset(&payload);
+        payload.extend_from_slice(pubkey.as_ref());
+        let signature_offset = offset(&payload);
+        payload.extend_from_slice(signature.as_ref());
+        let message_data_offset = offset(&payload);
+        payload.extend_from_slice(message);
+        offsets.push(Ed25519SignatureOffsets {
+            signature_offset,
+            signature_instruction_index: CURRENT_INSTRUCTION,
+            public_key_offset,
+            public_key_instruction_index: CURRENT_INSTRUCTION,
+            message_data_offset,
+            message_data_size: u16::try_from(message.len()).expect("message too large"),
+            message_instruction_index: CURRENT_INSTRUCTION,
+        });
+    }
+
+    let mut instruction = offsets_to_ed25519_instruction(&offsets);
+    instruction.data.extend_from_slice(&payload);
+    instruction
+}
+
+/// Returns the Ethereum address of a secp256k1 secret key, the value
+/// programs compare against after secp256k1 verification.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] if `secret_key` is not a
+/// valid secp256k1 secret key.
+pub fn eth_address(
+    secret_key: &[u8; 32],
+) -> Result<[u8; HASHED_PUBKEY_SERIALIZED_SIZE], AccountGenError> {
+    let secret_key = parse_secret_key(secret_key)?;
+    Ok(construct_eth_pubkey(
+        &libsecp256k1::PublicKey::from_secret_key(&secret_key),
+    ))
+}
+
+/// Signs the Keccak-256 hash of `message` with a secp256k1 secret key and
+/// builds a secp256k1 precompile instruction verifying the signature.
+///
+/// Unlike ed25519, secp256k1 offsets must name the instruction holding the
+/// data, so `instruction_index` is the position the instruction will take
+/// in the transaction.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::precompiles::{eth_address, secp256k1_instruction};
+///
+/// let secret_key = [7u8; 32];
+/// let instruction = secp256k1_instruction(&secret_key, b"withdraw 10", 0).unwrap();
+/// let address = eth_address(&secret_key).unwrap();
+/// assert_eq!(instruction.data[12..32], address);
+/// ```
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] if `secret_key` is not a
+/// valid secp256k1 secret key.
+pub fn secp256k1_instruction(
+    secret_key: &[u8; 32],
+    message: &[u8],
+    instruction_index: u8,
+) -> Result<Instruction, AccountGenError> {
+    let mut instruction = new_secp256k1_instruction(&parse_secret_key(secret_key)?, message);
+
+    let offsets_bytes = &mut instruction.data[1..SECP256K1_DATA_START];
+    let mut offsets: SecpSignatureOffsets = bincode::deserialize(offsets_bytes)
+        .map_err(|e| AccountGenError::InvalidDataFormat(e.to_string()))?;
+    offsets.signature_instruction_index = instruction_index;
+    offsets.eth_address_instruction_index = instruction_index;
+    offsets.message_instruction_index = instruction_index;
+    bincode::serialize_into(offsets_bytes, &offsets)
+        .map_err(|e| AccountGenError::InvalidDataFormat(e.to_string()))?;
+    Ok(instruction)
+}
+
+fn parse_secret_key(secret_key: &[u8; 32]) -> Result<libsecp256k1::SecretKey, AccountGenError> {
+    libsecp256k1::SecretKey::parse(secret_key).map_err(|e| {
+        AccountGenError::InvalidDataFormat(format!("invalid secp256k1 secret key: {e:?}"))
+    })
+}
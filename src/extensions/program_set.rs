@@ -0,0 +1,329 @@
+//! Testing several programs together.
+//!
+//! Suites that test CPI between in-house programs register each program,
+//! keep track of its ID and build its instructions by hand, in every test.
+//! A [`ProgramSet`] holds the programs once, native processors and SBF
+//! shared objects alike, together with their Anchor IDLs. It builds a
+//! `ProgramTest` with all of them, builds instructions for each program by
+//! name, and builds a [`DecoderRegistry`] so logs and failures show decoded
+//! instructions of every program in the set.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_account_info::AccountInfo;
+//! use solana_accountgen::extensions::program_set::ProgramSet;
+//! use solana_program_error::ProgramResult;
+//! use solana_program_test::processor;
+//! use solana_pubkey::Pubkey;
+//!
+//! fn vault(_: &Pubkey, _: &[AccountInfo], _: &[u8]) -> ProgramResult {
+//!     Ok(())
+//! }
+//!
+//! fn router(_: &Pubkey, _: &[AccountInfo], _: &[u8]) -> ProgramResult {
+//!     Ok(())
+//! }
+//!
+//! let programs = ProgramSet::new()
+//!     .native("vault", Pubkey::new_unique(), processor!(vault))
+//!     .native("router", Pubkey::new_unique(), processor!(router));
+//!
+//! let instruction = programs
+//!     .program("router")
+//!     .anchor_instruction("swap", vec![], 5u64)
+//!     .unwrap();
+//! assert_eq!(instruction.program_id, programs.program_id("router"));
+//!
+//! let program_test = programs.program_test();
+//! ```
+
+use crate::decoder::DecoderRegistry;
+use crate::extensions::anchor::create_anchor_instruction;
+use crate::extensions::idl::{Idl, snake_case};
+use crate::serialization::schema::FixtureSchema;
+use crate::{AccountBuilder, AccountGenError, well_known};
+use serde_json::Value;
+use solana_account::Account;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_program_runtime::invoke_context::BuiltinFunctionWithContext;
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use std::path::Path;
+
+/// Where the code of a program in a [`ProgramSet`] comes from.
+#[derive(Clone)]
+enum ProgramSource {
+    /// A processor compiled into the test.
+    Native(Option<BuiltinFunctionWithContext>),
+    /// `<name>.so`, found the way `ProgramTest::add_program` finds it.
+    Sbf,
+    /// A program account built from an ELF read up front.
+    Account(Account),
+}
+
+#[derive(Clone)]
+struct ProgramEntry {
+    name: &'static str,
+    program_id: Pubkey,
+    source: ProgramSource,
+    idl: Option<(Idl, FixtureSchema)>,
+}
+
+/// A set of programs tested together.
+#[derive(Clone, Default)]
+pub struct ProgramSet {
+    programs: Vec<ProgramEntry>,
+}
+
+impl ProgramSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a program run natively from `processor`, as given by
+    /// `solana_program_test::processor!`.
+    ///
+    /// As with `ProgramTest::add_program`, `<name>.so` is used instead when
+    /// `BPF_OUT_DIR` or `SBF_OUT_DIR` points at a build of it.
+    pub fn native(
+        self,
+        name: &'static str,
+        program_id: Pubkey,
+        processor: Option<BuiltinFunctionWithContext>,
+    ) -> Self {
+        self.add(name, program_id, ProgramSource::Native(processor))
+    }
+
+    /// Adds the SBF program `<name>.so`, looked up in `BPF_OUT_DIR`,
+    /// `SBF_OUT_DIR`, `tests/fixtures` and the current directory when the
+    /// `ProgramTest` is built.
+    pub fn sbf(self, name: &'static str, program_id: Pubkey) -> Self {
+        self.add(name, program_id, ProgramSource::Sbf)
+    }
+
+    /// Adds an SBF program read from `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is empty.
+    pub fn sbf_file<P: AsRef<Path>>(
+        self,
+        name: &'static str,
+        program_id: Pubkey,
+        path: P,
+    ) -> Result<Self, AccountGenError> {
+        let account = AccountBuilder::new()
+            .owner(well_known::BPF_LOADER)
+            .executable(true)
+            .data_raw(std::fs::read(path)?)
+            .try_build()?;
+        Ok(self.add(name, program_id, ProgramSource::Account(account)))
+    }
+
+    /// Attaches the Anchor IDL of the program `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the IDL's types
+    /// cannot be converted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set has no program `name`.
+    pub fn idl(mut self, name: &str, idl: Idl) -> Result<Self, AccountGenError> {
+        let schema = idl.to_fixture_schema()?;
+        self.entry_mut(name).idl = Some((idl, schema));
+        Ok(self)
+    }
+
+    /// Returns the ID of the program `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set has no program `name`.
+    pub fn program_id(&self, name: &str) -> Pubkey {
+        self.entry(name).program_id
+    }
+
+    /// Returns an instruction builder for the program `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set has no program `name`.
+    pub fn program(&self, name: &str) -> SetProgram<'_> {
+        SetProgram {
+            entry: self.entry(name),
+        }
+    }
+
+    /// Returns every (name, program ID) pair, in the order added.
+    pub fn programs(&self) -> impl Iterator<Item = (&'static str, Pubkey)> + '_ {
+        self.programs
+            .iter()
+            .map(|entry| (entry.name, entry.program_id))
+    }
+
+    /// Builds a `ProgramTest` with every program in the set.
+    pub fn program_test(&self) -> ProgramTest {
+        let mut program_test = ProgramTest::default();
+        for entry in &self.programs {
+            match &entry.source {
+                ProgramSource::Native(processor) => {
+                    program_test.add_program(entry.name, entry.program_id, *processor);
+                }
+                ProgramSource::Sbf => program_test.add_program(entry.name, entry.program_id, None),
+                ProgramSource::Account(account) => {
+                    program_test.add_account(entry.program_id, account.clone());
+                }
+            }
+        }
+        program_test
+    }
+
+    /// Builds a decoder registry with the built-in decoders of
+    /// [`DecoderRegistry::new`] (System, SPL Token, Token-2022, Memo and
+    /// Compute Budget) and the IDL of every program in the set that has
+    /// one.
+    ///
+    /// Programs of the set without an IDL, including those added with a
+    /// native processor, get no decoder, so their instructions decode with
+    /// the name `unknown`.
+    pub fn decoder_registry(&self) -> Result<DecoderRegistry, AccountGenError> {
+        let mut registry = DecoderRegistry::new();
+        for entry in &self.programs {
+            if let Some((idl, _)) = &entry.idl {
+                registry.register_anchor_idl(entry.program_id, idl)?;
+            }
+        }
+        Ok(registry)
+    }
+
+    fn add(mut self, name: &'static str, program_id: Pubkey, source: ProgramSource) -> Self {
+        let entry = ProgramEntry {
+            name,
+            program_id,
+            source,
+            idl: None,
+        };
+        match self.programs.iter_mut().find(|entry| entry.name == name) {
+            Some(existing) => *existing = entry,
+            None => self.programs.push(entry),
+        }
+        self
+    }
+
+    fn entry(&self, name: &str) -> &ProgramEntry {
+        match self.programs.iter().find(|entry| entry.name == name) {
+            Some(entry) => entry,
+            None => panic!("{}", self.unknown_program(name)),
+        }
+    }
+
+    fn entry_mut(&mut self, name: &str) -> &mut ProgramEntry {
+        match self.programs.iter().position(|entry| entry.name == name) {
+            Some(index) => &mut self.programs[index],
+            None => panic!("{}", self.unknown_program(name)),
+        }
+    }
+
+    fn unknown_program(&self, name: &str) -> String {
+        format!(
+            "program set has no program {:?} (programs: {:?})",
+            name,
+            self.programs
+                .iter()
+                .map(|entry| entry.name)
+                .collect::<Vec<_>>()
+        )
+    }
+}
+
+/// Builds instructions for one program of a [`ProgramSet`].
+#[derive(Clone, Copy)]
+pub struct SetProgram<'a> {
+    entry: &'a ProgramEntry,
+}
+
+impl SetProgram<'_> {
+    /// Returns the program's name in the set.
+    pub fn name(&self) -> &'static str {
+        self.entry.name
+    }
+
+    /// Returns the program's ID.
+    pub fn id(&self) -> Pubkey {
+        self.entry.program_id
+    }
+
+    /// Builds an instruction from raw data.
+    pub fn instruction(&self, accounts: Vec<AccountMeta>, data: Vec<u8>) -> Instruction {
+        Instruction {
+            program_id: self.entry.program_id,
+            accounts,
+            data,
+        }
+    }
+
+    /// Builds an Anchor instruction, prefixing the Borsh-encoded `args`
+    /// with the discriminator of `method_name`.
+    pub fn anchor_instruction<T: borsh::BorshSerialize>(
+        &self,
+        method_name: &str,
+        accounts: Vec<AccountMeta>,
+        args: T,
+    ) -> Result<Instruction, AccountGenError> {
+        create_anchor_instruction(self.entry.program_id, method_name, accounts, args)
+    }
+
+    /// Builds the IDL instruction `name` with arguments given as JSON, in
+    /// the shapes [`FixtureSchema::encode`] accepts.
+    ///
+    /// The name may be given in snake_case or as it appears in the IDL.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the program has no
+    /// IDL or no such instruction, if the number of accounts differs from
+    /// the instruction's, or if the arguments do not encode.
+    pub fn idl_instruction(
+        &self,
+        name: &str,
+        accounts: Vec<AccountMeta>,
+        args: &Value,
+    ) -> Result<Instruction, AccountGenError> {
+        let Some((idl, schema)) = &self.entry.idl else {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "program {} has no IDL",
+                self.entry.name
+            )));
+        };
+        let instruction = idl
+            .instructions
+            .iter()
+            .find(|instruction| instruction.name == name || snake_case(&instruction.name) == name)
+            .ok_or_else(|| {
+                AccountGenError::InvalidDataFormat(format!(
+                    "the IDL of {} has no instruction {}",
+                    self.entry.name, name
+                ))
+            })?;
+
+        let expected = instruction.account_names();
+        if accounts.len() != expected.len() {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "{}.{} takes {} accounts ({}), got {}",
+                self.entry.name,
+                instruction.name,
+                expected.len(),
+                expected.join(", "),
+                accounts.len()
+            )));
+        }
+
+        let mut data = idl.instruction_discriminator(instruction).to_vec();
+        data.extend(schema.encode(&format!("instruction:{}", instruction.name), args)?);
+        Ok(self.instruction(accounts, data))
+    }
+}
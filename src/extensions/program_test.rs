@@ -7,10 +7,17 @@
 //! The `ProgramTestExt` trait extends Solana's `ProgramTest` with methods
 //! that work with solana-accountgen's `AccountBuilder` and `AccountMap`,
 //! as well as Anchor-specific account creation.
+//!
+//! `ProgramTestContextExt` moves a started test through slots with
+//! reproducible sysvars.
 
 use crate::extensions::anchor;
-use crate::{AccountBuilder, AccountGenError, AccountMap};
-use solana_program_test::ProgramTest;
+use crate::extensions::sysvars::SysvarBundle;
+use crate::tracking;
+use crate::{AccountBuilder, AccountGenError, AccountMap, IntoLamports};
+use solana_clock::Clock;
+use solana_hash::Hash;
+use solana_program_test::{ProgramTest, ProgramTestContext};
 use solana_pubkey::Pubkey;
 
 /// Extension trait for ProgramTest to add accounts using AccountBuilder.
@@ -106,7 +113,7 @@ pub trait ProgramTestExt {
         account_type: &str,
         program_id: Pubkey,
         data: T,
-        lamports: u64,
+        lamports: impl IntoLamports,
     ) -> Result<&mut Self, AccountGenError>;
 
     /// Adds an Anchor PDA account with discriminator to the test environment.
@@ -149,7 +156,7 @@ pub trait ProgramTestExt {
         program_id: Pubkey,
         seeds: &[&[u8]],
         data: T,
-        lamports: u64,
+        lamports: impl IntoLamports,
     ) -> Result<(Pubkey, u8, &mut Self), AccountGenError>;
 }
 
@@ -160,12 +167,14 @@ impl ProgramTestExt for ProgramTest {
         builder: AccountBuilder,
     ) -> Result<&mut Self, AccountGenError> {
         let account = builder.try_build()?;
+        tracking::record(pubkey);
         self.add_account(pubkey, account);
         Ok(self)
     }
 
     fn add_account_map(&mut self, account_map: AccountMap) -> &mut Self {
         for (pubkey, account) in account_map {
+            tracking::record(pubkey);
             self.add_account(pubkey, account);
         }
         self
@@ -177,9 +186,10 @@ impl ProgramTestExt for ProgramTest {
         account_type: &str,
         program_id: Pubkey,
         data: T,
-        lamports: u64,
+        lamports: impl IntoLamports,
     ) -> Result<&mut Self, AccountGenError> {
         let account = anchor::create_anchor_account(account_type, program_id, data, lamports)?;
+        tracking::record(pubkey);
         self.add_account(pubkey, account);
         Ok(self)
     }
@@ -190,11 +200,85 @@ impl ProgramTestExt for ProgramTest {
         program_id: Pubkey,
         seeds: &[&[u8]],
         data: T,
-        lamports: u64,
+        lamports: impl IntoLamports,
     ) -> Result<(Pubkey, u8, &mut Self), AccountGenError> {
         let (pda, bump, account) =
             anchor::create_anchor_pda(account_type, program_id, seeds, data, lamports)?;
+        tracking::record(pda);
         self.add_account(pda, account);
         Ok((pda, bump, self))
     }
 }
+
+/// Extension trait for ProgramTestContext to move through slots
+/// deterministically.
+///
+/// The bank behind a `ProgramTestContext` takes its Clock timestamps from
+/// the moment the test started, so a program that reads the time sees
+/// different values on every run. These methods warp the bank and then
+/// set the Clock, SlotHashes and SlotHistory from
+/// [`SysvarBundle::at_slot`], the same sysvars the local harness uses. They
+/// are written with `ProgramTestContext::set_sysvar`, so programs calling
+/// `Clock::get()` see them as well as clients, and a session that advances
+/// through the same slots sees the same sysvars on every run.
+///
+/// Blockhashes are not pinned: they come from the bank and cannot be
+/// chosen. Every advance moves `last_blockhash` to the new bank's, so a
+/// transaction rebuilt after advancing gets a new signature instead of
+/// failing as already processed, but signatures differ between runs.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::extensions::program_test::ProgramTestContextExt;
+/// use solana_program_test::ProgramTest;
+///
+/// # async fn run() {
+/// let mut context = ProgramTest::default().start_with_context().await;
+/// let blockhash = context.advance_slots(100).await.unwrap();
+/// assert_eq!(blockhash, context.last_blockhash);
+/// # }
+/// ```
+#[allow(async_fn_in_trait)]
+pub trait ProgramTestContextExt {
+    /// Returns the slot of the bank's Clock.
+    async fn current_slot(&mut self) -> Result<u64, AccountGenError>;
+
+    /// Warps to `slot`, writes the sysvars of that slot and returns the new
+    /// `last_blockhash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::BackendError`] if `slot` is not after the
+    /// current slot.
+    async fn advance_to_slot(&mut self, slot: u64) -> Result<Hash, AccountGenError>;
+
+    /// Advances `slots` slots past the current one; see
+    /// [`ProgramTestContextExt::advance_to_slot`].
+    async fn advance_slots(&mut self, slots: u64) -> Result<Hash, AccountGenError>;
+}
+
+impl ProgramTestContextExt for ProgramTestContext {
+    async fn current_slot(&mut self) -> Result<u64, AccountGenError> {
+        let clock: Clock = self
+            .banks_client
+            .get_sysvar()
+            .await
+            .map_err(|e| AccountGenError::BackendError(e.to_string()))?;
+        Ok(clock.slot)
+    }
+
+    async fn advance_to_slot(&mut self, slot: u64) -> Result<Hash, AccountGenError> {
+        self.warp_to_slot(slot).map_err(|e| {
+            AccountGenError::BackendError(format!("cannot warp to slot {slot}: {e}"))
+        })?;
+        let epoch_schedule = self.genesis_config().epoch_schedule.clone();
+        SysvarBundle::at_slot(slot, epoch_schedule).apply_to(self);
+        Ok(self.last_blockhash)
+    }
+
+    async fn advance_slots(&mut self, slots: u64) -> Result<Hash, AccountGenError> {
+        let slot = self.current_slot().await?.saturating_add(slots);
+        self.advance_to_slot(slot).await
+    }
+}
@@ -0,0 +1,937 @@
+//! Ready-made account sets for common DeFi patterns.
+//!
+//! Each preset returns a [`Scenario`]: an [`AccountMap`] holding every
+//! account the pattern needs, the keypairs of the participants, and a label
+//! for each address so tests can refer to `"maker_token_a"` rather than an
+//! anonymous pubkey. Program-specific state accounts (the escrow or vesting
+//! record itself) are left to the caller, since their layout depends on the
+//! program under test; the presets derive their addresses. Presets that do
+//! create them, such as [`constant_product_pool`] and [`vesting_with_state`],
+//! take a closure producing the account data.
+//!
+//! All addresses are derived from labels with [`test_pubkey`] and
+//! [`test_keypair`], so a preset produces the same accounts on every run.
+
+use crate::backend::{self, TestBackend};
+use crate::extensions::anchor::AccountLabels;
+use crate::extensions::genesis::{create_stake_account, create_vote_account};
+use crate::extensions::lending::{
+    LastUpdate, LendingMarket, Obligation, ObligationCollateral, ObligationLiquidity, Reserve,
+    ReserveCollateral, ReserveConfig, ReserveLiquidity, WAD, create_lending_market_account,
+    create_obligation_account, create_reserve_account, lending_market_authority,
+};
+use crate::extensions::sysvars::create_sysvar_account;
+use crate::extensions::token::{create_mint_account, create_token_account};
+use crate::keys::{test_keypair, test_pubkey};
+use crate::well_known::{SYSTEM_PROGRAM, TOKEN_PROGRAM};
+use crate::{AccountBuilder, AccountGenError, AccountMap};
+use borsh::BorshSerialize;
+use solana_account::Account;
+use solana_clock::{Clock, DEFAULT_MS_PER_SLOT};
+use solana_epoch_schedule::EpochSchedule;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::sysvar;
+use solana_signer::Signer;
+use solana_stake_interface::stake_history::{StakeHistory, StakeHistoryEntry};
+use solana_stake_interface::state::Delegation;
+use solana_sysvar::epoch_rewards::EpochRewards;
+use solana_vote_interface::state::{VoteInit, VoteState};
+use std::collections::BTreeMap;
+
+/// Lamports given to each participant's wallet.
+const WALLET_LAMPORTS: u64 = 10_000_000_000;
+
+/// A labeled set of accounts and keypairs.
+pub struct Scenario {
+    /// Every account in the scenario.
+    pub accounts: AccountMap,
+    labels: BTreeMap<&'static str, Pubkey>,
+    keypairs: BTreeMap<&'static str, Keypair>,
+}
+
+impl Scenario {
+    fn new() -> Self {
+        Self {
+            accounts: AccountMap::new(),
+            labels: BTreeMap::new(),
+            keypairs: BTreeMap::new(),
+        }
+    }
+
+    fn add(&mut self, label: &'static str, pubkey: Pubkey, account: Account) {
+        self.labels.insert(label, pubkey);
+        self.accounts.set_account(pubkey, account);
+    }
+
+    fn add_wallet(&mut self, label: &'static str, keypair: Keypair) -> Pubkey {
+        let pubkey = keypair.pubkey();
+        let wallet = AccountBuilder::new()
+            .balance(WALLET_LAMPORTS)
+            .owner(SYSTEM_PROGRAM)
+            .build();
+        self.add(label, pubkey, wallet);
+        self.keypairs.insert(label, keypair);
+        pubkey
+    }
+
+    /// Returns the address with the given label.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scenario has no such label.
+    pub fn pubkey(&self, label: &str) -> Pubkey {
+        match self.labels.get(label) {
+            Some(pubkey) => *pubkey,
+            None => panic!(
+                "scenario has no account labeled {:?} (labels: {:?})",
+                label,
+                self.labels.keys().collect::<Vec<_>>()
+            ),
+        }
+    }
+
+    /// Returns the keypair of the participant with the given label.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scenario has no keypair with that label.
+    pub fn keypair(&self, label: &str) -> &Keypair {
+        match self.keypairs.get(label) {
+            Some(keypair) => keypair,
+            None => panic!(
+                "scenario has no keypair labeled {:?} (keypairs: {:?})",
+                label,
+                self.keypairs.keys().collect::<Vec<_>>()
+            ),
+        }
+    }
+
+    /// Returns every (label, address) pair, ordered by label.
+    pub fn labels(&self) -> impl Iterator<Item = (&'static str, Pubkey)> + '_ {
+        self.labels.iter().map(|(label, pubkey)| (*label, *pubkey))
+    }
+
+    /// Writes the scenario's accounts into a test backend.
+    pub fn load_into<B: TestBackend>(&self, backend: &mut B) -> Result<(), AccountGenError> {
+        backend::load_accounts(backend, &self.accounts)
+    }
+
+    /// Sets the Clock sysvar account, labeled `"clock"`.
+    pub fn set_clock(&mut self, clock: &Clock) {
+        self.add("clock", sysvar::clock::id(), create_sysvar_account(clock));
+    }
+}
+
+impl AccountLabels for Scenario {
+    fn account_label(&self, name: &str) -> Option<Pubkey> {
+        self.labels.get(name).copied()
+    }
+}
+
+/// Builds a two-party token escrow.
+///
+/// The maker offers `offered` tokens of mint A in exchange for `requested`
+/// tokens of mint B held by the taker. Labels:
+///
+/// - `maker`, `taker`: funded wallets, with keypairs
+/// - `mint_a`, `mint_b`: SPL Token mints with 6 decimals
+/// - `maker_token_a`, `maker_token_b`, `taker_token_a`, `taker_token_b`:
+///   token accounts, of which only `maker_token_a` and `taker_token_b` are
+///   funded
+/// - `escrow`: the PDA `["escrow", maker]` of `program_id`, not created
+/// - `vault`: an empty mint A token account owned by `escrow`
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::scenarios::escrow;
+/// use solana_pubkey::Pubkey;
+///
+/// let program_id = Pubkey::new_unique();
+/// let scenario = escrow(&program_id, 1_000, 500).unwrap();
+///
+/// let escrow_pda = scenario.pubkey("escrow");
+/// assert!(scenario.accounts.get_account(&escrow_pda).is_none());
+/// assert!(scenario.accounts.get_account(&scenario.pubkey("vault")).is_some());
+/// ```
+pub fn escrow(
+    program_id: &Pubkey,
+    offered: u64,
+    requested: u64,
+) -> Result<Scenario, AccountGenError> {
+    let mut scenario = Scenario::new();
+
+    let maker = scenario.add_wallet("maker", test_keypair("escrow:maker"));
+    let taker = scenario.add_wallet("taker", test_keypair("escrow:taker"));
+    let mint_authority = test_pubkey("escrow:mint_authority");
+
+    let mint_a = test_pubkey("escrow:mint_a");
+    let mint_b = test_pubkey("escrow:mint_b");
+    scenario.add(
+        "mint_a",
+        mint_a,
+        create_mint_account(&mint_authority, offered, 6, &TOKEN_PROGRAM)?,
+    );
+    scenario.add(
+        "mint_b",
+        mint_b,
+        create_mint_account(&mint_authority, requested, 6, &TOKEN_PROGRAM)?,
+    );
+
+    let token_accounts = [
+        ("maker_token_a", &mint_a, &maker, offered),
+        ("maker_token_b", &mint_b, &maker, 0),
+        ("taker_token_a", &mint_a, &taker, 0),
+        ("taker_token_b", &mint_b, &taker, requested),
+    ];
+    for (label, mint, owner, amount) in token_accounts {
+        let pubkey = test_pubkey(&format!("escrow:{}", label));
+        let account = create_token_account(mint, owner, amount, &TOKEN_PROGRAM)?;
+        scenario.add(label, pubkey, account);
+    }
+
+    let (escrow, _) = Pubkey::find_program_address(&[b"escrow", maker.as_ref()], program_id);
+    scenario.labels.insert("escrow", escrow);
+    scenario.add(
+        "vault",
+        test_pubkey("escrow:vault"),
+        create_token_account(&mint_a, &escrow, 0, &TOKEN_PROGRAM)?,
+    );
+
+    Ok(scenario)
+}
+
+/// A linear vesting schedule with a cliff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VestingSchedule {
+    /// Unix timestamp at which vesting starts.
+    pub start: i64,
+    /// Unix timestamp before which nothing can be claimed.
+    pub cliff: i64,
+    /// Unix timestamp at which everything has vested.
+    pub end: i64,
+    /// Total tokens vested over the schedule.
+    pub total: u64,
+}
+
+impl VestingSchedule {
+    /// Returns the tokens vested at `unix_timestamp`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::extensions::scenarios::VestingSchedule;
+    ///
+    /// let schedule = VestingSchedule { start: 0, cliff: 25, end: 100, total: 1_000 };
+    /// assert_eq!(schedule.vested_at(10), 0);
+    /// assert_eq!(schedule.vested_at(50), 500);
+    /// assert_eq!(schedule.vested_at(200), 1_000);
+    /// ```
+    pub fn vested_at(&self, unix_timestamp: i64) -> u64 {
+        if unix_timestamp < self.cliff || unix_timestamp <= self.start {
+            return 0;
+        }
+        if unix_timestamp >= self.end {
+            return self.total;
+        }
+        let elapsed = (unix_timestamp - self.start) as u128;
+        let duration = (self.end - self.start) as u128;
+        (self.total as u128 * elapsed / duration) as u64
+    }
+}
+
+/// Builds a linear token vesting setup with the clock at the schedule start.
+///
+/// Labels:
+///
+/// - `beneficiary`: a funded wallet, with keypair
+/// - `mint`: an SPL Token mint with 6 decimals
+/// - `vesting`: the PDA `["vesting", beneficiary]` of `program_id`, not created
+/// - `vault`: a token account owned by `vesting` holding `schedule.total`
+/// - `beneficiary_token`: the beneficiary's empty token account
+/// - `clock`: the Clock sysvar at `schedule.start`
+///
+/// Move time forward with [`Scenario::set_clock`].
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::scenarios::{VestingSchedule, linear_vesting};
+/// use solana_clock::Clock;
+/// use solana_pubkey::Pubkey;
+///
+/// let schedule = VestingSchedule { start: 1_700_000_000, cliff: 1_700_000_000, end: 1_700_086_400, total: 1_000 };
+/// let mut scenario = linear_vesting(&Pubkey::new_unique(), schedule).unwrap();
+///
+/// scenario.set_clock(&Clock { unix_timestamp: schedule.end, ..Clock::default() });
+/// assert_eq!(schedule.vested_at(schedule.end), 1_000);
+/// ```
+pub fn linear_vesting(
+    program_id: &Pubkey,
+    schedule: VestingSchedule,
+) -> Result<Scenario, AccountGenError> {
+    let mut scenario = Scenario::new();
+
+    let beneficiary = scenario.add_wallet("beneficiary", test_keypair("vesting:beneficiary"));
+    let mint = test_pubkey("vesting:mint");
+    scenario.add(
+        "mint",
+        mint,
+        create_mint_account(
+            &test_pubkey("vesting:mint_authority"),
+            schedule.total,
+            6,
+            &TOKEN_PROGRAM,
+        )?,
+    );
+
+    let (vesting, _) =
+        Pubkey::find_program_address(&[b"vesting", beneficiary.as_ref()], program_id);
+    scenario.labels.insert("vesting", vesting);
+    scenario.add(
+        "vault",
+        test_pubkey("vesting:vault"),
+        create_token_account(&mint, &vesting, schedule.total, &TOKEN_PROGRAM)?,
+    );
+    scenario.add(
+        "beneficiary_token",
+        test_pubkey("vesting:beneficiary_token"),
+        create_token_account(&mint, &beneficiary, 0, &TOKEN_PROGRAM)?,
+    );
+
+    scenario.set_clock(&Clock {
+        unix_timestamp: schedule.start,
+        epoch_start_timestamp: schedule.start,
+        ..Clock::default()
+    });
+
+    Ok(scenario)
+}
+
+/// The state of a vesting setup at one point in time, from which a
+/// program's vesting record is derived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VestingState {
+    /// The schedule being vested.
+    pub schedule: VestingSchedule,
+    /// The current clock.
+    pub clock: Clock,
+    /// Tokens already claimed by the beneficiary.
+    pub claimed: u64,
+}
+
+impl VestingState {
+    /// Returns the tokens vested at the current clock.
+    pub fn vested(&self) -> u64 {
+        self.schedule.vested_at(self.clock.unix_timestamp)
+    }
+
+    /// Returns the tokens the beneficiary could claim now.
+    pub fn claimable(&self) -> u64 {
+        self.vested().saturating_sub(self.claimed)
+    }
+
+    /// Returns the tokens still held in the vault.
+    pub fn locked(&self) -> u64 {
+        self.schedule.total - self.claimed
+    }
+}
+
+/// A linear vesting setup whose accounts are kept consistent with a clock.
+///
+/// Created by [`vesting_with_state`]. Moving the clock or recording a claim
+/// rewrites the Clock sysvar, the vault and beneficiary token balances and
+/// the vesting record together.
+pub struct VestingFixture<F> {
+    /// The labeled accounts; see [`linear_vesting`] for the labels.
+    pub scenario: Scenario,
+    program_id: Pubkey,
+    state: VestingState,
+    encode: F,
+}
+
+impl<T, F> VestingFixture<F>
+where
+    T: BorshSerialize,
+    F: Fn(&Scenario, &VestingState) -> T,
+{
+    /// Returns the current vesting state.
+    pub fn state(&self) -> &VestingState {
+        &self.state
+    }
+
+    /// Returns the tokens the beneficiary should be able to claim now.
+    pub fn expected_claimable(&self) -> u64 {
+        self.state.claimable()
+    }
+
+    /// Sets the clock and recomputes the accounts.
+    pub fn set_clock(&mut self, clock: Clock) -> Result<(), AccountGenError> {
+        self.state.clock = clock;
+        self.sync()
+    }
+
+    /// Moves the clock forward by `seconds`, advancing the slot at the
+    /// default slot time and the epoch by the default epoch schedule, and
+    /// recomputes the accounts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the timestamp
+    /// would overflow.
+    pub fn advance_clock(&mut self, seconds: i64) -> Result<(), AccountGenError> {
+        let mut clock = self.state.clock.clone();
+        clock.unix_timestamp = clock.unix_timestamp.checked_add(seconds).ok_or_else(|| {
+            AccountGenError::InvalidDataFormat(format!(
+                "advancing the clock by {seconds} seconds overflows its timestamp"
+            ))
+        })?;
+        let slots = (seconds.max(0) as u64).saturating_mul(1_000) / DEFAULT_MS_PER_SLOT;
+        clock.slot = clock.slot.saturating_add(slots);
+        let epoch_schedule = EpochSchedule::default();
+        clock.epoch = epoch_schedule.get_epoch(clock.slot);
+        clock.leader_schedule_epoch = epoch_schedule.get_leader_schedule_epoch(clock.slot);
+        self.set_clock(clock)
+    }
+
+    /// Moves `amount` claimed tokens from the vault to the beneficiary, as
+    /// a successful claim would, and recomputes the accounts.
+    ///
+    /// Fails if more than [`expected_claimable`](Self::expected_claimable)
+    /// is claimed.
+    pub fn record_claim(&mut self, amount: u64) -> Result<(), AccountGenError> {
+        let claimable = self.state.claimable();
+        if amount > claimable {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "cannot claim {amount} tokens, only {claimable} claimable"
+            )));
+        }
+        self.state.claimed += amount;
+        self.sync()
+    }
+
+    fn sync(&mut self) -> Result<(), AccountGenError> {
+        let scenario = &mut self.scenario;
+        scenario.set_clock(&self.state.clock);
+
+        let mint = scenario.pubkey("mint");
+        let balances = [
+            ("vault", scenario.pubkey("vesting"), self.state.locked()),
+            (
+                "beneficiary_token",
+                scenario.pubkey("beneficiary"),
+                self.state.claimed,
+            ),
+        ];
+        for (label, owner, amount) in balances {
+            let account = create_token_account(&mint, &owner, amount, &TOKEN_PROGRAM)?;
+            scenario
+                .accounts
+                .set_account(scenario.pubkey(label), account);
+        }
+
+        let record = AccountBuilder::new()
+            .owner(self.program_id)
+            .data((self.encode)(scenario, &self.state))?
+            .try_build()?;
+        scenario
+            .accounts
+            .set_account(scenario.pubkey("vesting"), record);
+        Ok(())
+    }
+}
+
+/// Builds a linear vesting setup with the vesting record created and every
+/// balance derived from `clock` and the tokens already `claimed`.
+///
+/// The labels are those of [`linear_vesting`], except that `vesting` now
+/// holds the record produced by `state`, owned by `program_id`. The vault
+/// holds the unclaimed tokens and `beneficiary_token` the claimed ones.
+/// `state` is called again whenever the fixture changes.
+///
+/// # Example
+///
+/// ```
+/// use borsh::BorshSerialize;
+/// use solana_accountgen::extensions::scenarios::{VestingSchedule, vesting_with_state};
+/// use solana_clock::Clock;
+/// use solana_pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize)]
+/// struct Vesting {
+///     beneficiary: Pubkey,
+///     start: i64,
+///     end: i64,
+///     total: u64,
+///     claimed: u64,
+/// }
+///
+/// let schedule = VestingSchedule { start: 1_000, cliff: 1_000, end: 2_000, total: 1_000 };
+/// let clock = Clock { unix_timestamp: 1_500, ..Clock::default() };
+/// let mut fixture = vesting_with_state(&Pubkey::new_unique(), schedule, clock, 100, |s, state| {
+///     Vesting {
+///         beneficiary: s.pubkey("beneficiary"),
+///         start: state.schedule.start,
+///         end: state.schedule.end,
+///         total: state.schedule.total,
+///         claimed: state.claimed,
+///     }
+/// })
+/// .unwrap();
+/// assert_eq!(fixture.expected_claimable(), 400);
+///
+/// fixture.advance_clock(250).unwrap();
+/// assert_eq!(fixture.expected_claimable(), 650);
+///
+/// fixture.record_claim(650).unwrap();
+/// assert_eq!(fixture.expected_claimable(), 0);
+/// assert_eq!(fixture.state().locked(), 250);
+/// ```
+pub fn vesting_with_state<T, F>(
+    program_id: &Pubkey,
+    schedule: VestingSchedule,
+    clock: Clock,
+    claimed: u64,
+    state: F,
+) -> Result<VestingFixture<F>, AccountGenError>
+where
+    T: BorshSerialize,
+    F: Fn(&Scenario, &VestingState) -> T,
+{
+    let initial = VestingState {
+        schedule,
+        clock,
+        claimed,
+    };
+    if claimed > initial.vested() {
+        return Err(AccountGenError::InvalidDataFormat(format!(
+            "{} tokens claimed but only {} vested",
+            claimed,
+            initial.vested()
+        )));
+    }
+
+    let mut fixture = VestingFixture {
+        scenario: linear_vesting(program_id, schedule)?,
+        program_id: *program_id,
+        state: initial,
+        encode: state,
+    };
+    fixture.sync()?;
+    Ok(fixture)
+}
+
+/// Builds a constant-product (x * y = k) liquidity pool.
+///
+/// The pool state account is owned by `program_id` and its data is produced
+/// by `state`, which receives the scenario built so far so it can embed the
+/// vault and mint addresses in whatever layout the program expects. Labels:
+///
+/// - `mint_a`, `mint_b`: SPL Token mints with 6 decimals, whose supply is
+///   exactly the pool reserves
+/// - `pool`: the pool state account
+/// - `authority`: the PDA `["authority", pool]` of `program_id`, not created
+/// - `vault_a`, `vault_b`: token accounts owned by `authority` holding
+///   `reserve_a` and `reserve_b`
+/// - `lp_mint`: the LP token mint, with `authority` as mint authority and a
+///   supply of `isqrt(reserve_a * reserve_b)`
+/// - `provider`, `trader`: funded wallets, with keypairs
+/// - `provider_lp`: the provider's token account holding the whole LP supply
+/// - `trader_token_a`, `trader_token_b`: the trader's empty token accounts
+///
+/// # Example
+///
+/// ```
+/// use borsh::BorshSerialize;
+/// use solana_accountgen::extensions::scenarios::constant_product_pool;
+/// use solana_pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize)]
+/// struct Pool {
+///     vault_a: Pubkey,
+///     vault_b: Pubkey,
+///     lp_mint: Pubkey,
+///     fee_bps: u16,
+/// }
+///
+/// let program_id = Pubkey::new_unique();
+/// let scenario = constant_product_pool(&program_id, 1_000_000, 4_000_000, |s| Pool {
+///     vault_a: s.pubkey("vault_a"),
+///     vault_b: s.pubkey("vault_b"),
+///     lp_mint: s.pubkey("lp_mint"),
+///     fee_bps: 30,
+/// })
+/// .unwrap();
+///
+/// let pool = scenario.accounts.get_account(&scenario.pubkey("pool")).unwrap();
+/// assert_eq!(pool.owner, program_id);
+/// assert_eq!(pool.data.len(), 32 * 3 + 2);
+/// ```
+pub fn constant_product_pool<T, F>(
+    program_id: &Pubkey,
+    reserve_a: u64,
+    reserve_b: u64,
+    state: F,
+) -> Result<Scenario, AccountGenError>
+where
+    T: BorshSerialize,
+    F: FnOnce(&Scenario) -> T,
+{
+    let mut scenario = Scenario::new();
+
+    let provider = scenario.add_wallet("provider", test_keypair("amm:provider"));
+    let trader = scenario.add_wallet("trader", test_keypair("amm:trader"));
+    let mint_authority = test_pubkey("amm:mint_authority");
+
+    let pool = test_pubkey("amm:pool");
+    let (authority, _) = Pubkey::find_program_address(&[b"authority", pool.as_ref()], program_id);
+    scenario.labels.insert("pool", pool);
+    scenario.labels.insert("authority", authority);
+
+    let mint_a = test_pubkey("amm:mint_a");
+    let mint_b = test_pubkey("amm:mint_b");
+    let lp_mint = test_pubkey("amm:lp_mint");
+    let lp_supply = (reserve_a as u128 * reserve_b as u128).isqrt() as u64;
+    let mints = [
+        ("mint_a", mint_a, &mint_authority, reserve_a),
+        ("mint_b", mint_b, &mint_authority, reserve_b),
+        ("lp_mint", lp_mint, &authority, lp_supply),
+    ];
+    for (label, pubkey, authority, supply) in mints {
+        let account = create_mint_account(authority, supply, 6, &TOKEN_PROGRAM)?;
+        scenario.add(label, pubkey, account);
+    }
+
+    let token_accounts = [
+        ("vault_a", &mint_a, &authority, reserve_a),
+        ("vault_b", &mint_b, &authority, reserve_b),
+        ("provider_lp", &lp_mint, &provider, lp_supply),
+        ("trader_token_a", &mint_a, &trader, 0),
+        ("trader_token_b", &mint_b, &trader, 0),
+    ];
+    for (label, mint, owner, amount) in token_accounts {
+        let pubkey = test_pubkey(&format!("amm:{}", label));
+        let account = create_token_account(mint, owner, amount, &TOKEN_PROGRAM)?;
+        scenario.add(label, pubkey, account);
+    }
+
+    let pool_account = AccountBuilder::new()
+        .owner(*program_id)
+        .data(state(&scenario))?
+        .try_build()?;
+    scenario.accounts.set_account(pool, pool_account);
+
+    Ok(scenario)
+}
+
+/// Builds an SPL Token Lending market with one reserve and a borrower's
+/// obligation.
+///
+/// The user has deposited `deposit` liquidity tokens, received the same
+/// amount of collateral, deposited all of it into the obligation, and
+/// borrowed `borrow` tokens back, with no interest accrued yet. Every
+/// balance follows from that history: the reserve's amounts match its
+/// token accounts and collateral mint, and the obligation's values are
+/// those a refresh computes at the reserve's price of 1. Labels:
+///
+/// - `market_owner`, `user`: funded wallets, with keypairs
+/// - `lending_market`: the market, quoted in USD
+/// - `lending_market_authority`: the market's PDA of `program_id`, not
+///   created
+/// - `reserve`: the reserve, with the default [`ReserveConfig`]
+/// - `liquidity_mint`: an SPL Token mint with 6 decimals and a supply of
+///   `deposit`
+/// - `liquidity_supply`: the reserve's token account holding the
+///   `deposit - borrow` tokens available
+/// - `liquidity_fee_receiver`: the reserve's empty fee account
+/// - `oracle`: the reserve's price account, not created
+/// - `collateral_mint`: the collateral mint, with a supply of `deposit`
+/// - `collateral_supply`: the reserve's token account holding the
+///   obligation's collateral
+/// - `obligation`: the user's obligation
+/// - `user_liquidity`: the user's token account holding the borrowed tokens
+/// - `user_collateral`: the user's empty collateral token account
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] if `borrow` is more than
+/// the reserve's loan-to-value ratio allows against `deposit`.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::lending::{Obligation, Reserve, TOKEN_LENDING_PROGRAM};
+/// use solana_accountgen::extensions::scenarios::lending_market;
+///
+/// let scenario = lending_market(&TOKEN_LENDING_PROGRAM, 1_000_000, 400_000).unwrap();
+///
+/// let reserve = scenario.accounts.get_account(&scenario.pubkey("reserve")).unwrap();
+/// let reserve = Reserve::unpack(&reserve.data).unwrap();
+/// assert_eq!(reserve.liquidity.available_amount, 600_000);
+/// assert_eq!(reserve.collateral_to_liquidity(1_000_000), 1_000_000);
+///
+/// let obligation = scenario.accounts.get_account(&scenario.pubkey("obligation")).unwrap();
+/// let obligation = Obligation::unpack(&obligation.data).unwrap();
+/// assert_eq!(obligation.deposits[0].deposited_amount, 1_000_000);
+/// ```
+pub fn lending_market(
+    program_id: &Pubkey,
+    deposit: u64,
+    borrow: u64,
+) -> Result<Scenario, AccountGenError> {
+    const DECIMALS: u8 = 6;
+    let config = ReserveConfig::default();
+    if borrow as u128 * 100 > deposit as u128 * config.loan_to_value_ratio as u128 {
+        return Err(AccountGenError::InvalidDataFormat(format!(
+            "cannot borrow {} tokens against {}, the loan-to-value ratio is {}%",
+            borrow, deposit, config.loan_to_value_ratio
+        )));
+    }
+
+    let mut scenario = Scenario::new();
+
+    let market_owner = scenario.add_wallet("market_owner", test_keypair("lending:market_owner"));
+    let user = scenario.add_wallet("user", test_keypair("lending:user"));
+
+    let market = test_pubkey("lending:lending_market");
+    let (authority, bump_seed) = lending_market_authority(&market, program_id);
+    let market_state = LendingMarket {
+        bump_seed,
+        owner: market_owner,
+        ..LendingMarket::default()
+    };
+    scenario.add(
+        "lending_market",
+        market,
+        create_lending_market_account(&market_state, program_id)?,
+    );
+    scenario
+        .labels
+        .insert("lending_market_authority", authority);
+
+    let liquidity_mint = test_pubkey("lending:liquidity_mint");
+    let collateral_mint = test_pubkey("lending:collateral_mint");
+    let mint_authority = test_pubkey("lending:mint_authority");
+    let mints = [
+        ("liquidity_mint", liquidity_mint, &mint_authority),
+        ("collateral_mint", collateral_mint, &authority),
+    ];
+    for (label, pubkey, mint_authority) in mints {
+        let account = create_mint_account(mint_authority, deposit, DECIMALS, &TOKEN_PROGRAM)?;
+        scenario.add(label, pubkey, account);
+    }
+
+    let token_accounts = [
+        (
+            "liquidity_supply",
+            &liquidity_mint,
+            &authority,
+            deposit - borrow,
+        ),
+        ("liquidity_fee_receiver", &liquidity_mint, &authority, 0),
+        ("collateral_supply", &collateral_mint, &authority, deposit),
+        ("user_liquidity", &liquidity_mint, &user, borrow),
+        ("user_collateral", &collateral_mint, &user, 0),
+    ];
+    for (label, mint, owner, amount) in token_accounts {
+        let pubkey = test_pubkey(&format!("lending:{}", label));
+        let account = create_token_account(mint, owner, amount, &TOKEN_PROGRAM)?;
+        scenario.add(label, pubkey, account);
+    }
+
+    let oracle = test_pubkey("lending:oracle");
+    scenario.labels.insert("oracle", oracle);
+
+    let reserve_key = test_pubkey("lending:reserve");
+    let reserve = Reserve {
+        last_update: LastUpdate::default(),
+        lending_market: market,
+        liquidity: ReserveLiquidity {
+            mint: liquidity_mint,
+            mint_decimals: DECIMALS,
+            supply: scenario.pubkey("liquidity_supply"),
+            fee_receiver: scenario.pubkey("liquidity_fee_receiver"),
+            oracle,
+            available_amount: deposit - borrow,
+            borrowed_amount_wads: borrow as u128 * WAD,
+            ..ReserveLiquidity::default()
+        },
+        collateral: ReserveCollateral {
+            mint: collateral_mint,
+            mint_total_supply: deposit,
+            supply: scenario.pubkey("collateral_supply"),
+        },
+        config,
+    };
+    scenario.add(
+        "reserve",
+        reserve_key,
+        create_reserve_account(&reserve, program_id)?,
+    );
+
+    let deposited_value = reserve.market_value(deposit as u128 * WAD);
+    let borrowed_value = reserve.market_value(borrow as u128 * WAD);
+    let obligation = Obligation {
+        last_update: LastUpdate::default(),
+        lending_market: market,
+        owner: user,
+        deposits: vec![ObligationCollateral {
+            deposit_reserve: reserve_key,
+            deposited_amount: deposit,
+            market_value: deposited_value,
+        }],
+        borrows: vec![ObligationLiquidity {
+            borrow_reserve: reserve_key,
+            borrowed_amount_wads: borrow as u128 * WAD,
+            market_value: borrowed_value,
+            ..ObligationLiquidity::default()
+        }],
+        deposited_value,
+        borrowed_value,
+        allowed_borrow_value: deposited_value * config.loan_to_value_ratio as u128 / 100,
+        unhealthy_borrow_value: deposited_value * config.liquidation_threshold as u128 / 100,
+    };
+    scenario.add(
+        "obligation",
+        test_pubkey("lending:obligation"),
+        create_obligation_account(&obligation, program_id)?,
+    );
+
+    Ok(scenario)
+}
+
+/// Vote credits the validator of [`epoch_rewards`] earned in the epoch
+/// being rewarded.
+const EPOCH_REWARDS_CREDITS: u64 = 1_000;
+
+/// Builds a cluster paying out the rewards of epoch `epoch - 1` at the
+/// start of `epoch`, with stake accounts at set points of the warmup and
+/// cooldown curve.
+///
+/// The cluster has one validator and four stake accounts delegating
+/// `stake` lamports each to it. Its StakeHistory puts them at `epoch` at:
+///
+/// - `bootstrap_stake`: fully effective
+/// - `warming_stake`: activated in `epoch - 1`, half effective
+///   (rounded down) and half activating
+/// - `activating_stake`: activated in `epoch`, all activating
+/// - `deactivating_stake`: fully effective, deactivating in `epoch`
+///
+/// Only `bootstrap_stake` and `deactivating_stake` were effective in
+/// `epoch - 1`, so they alone earn rewards. The validator earned 1,000
+/// vote credits in that epoch and no stake account has observed them.
+///
+/// Other labels:
+///
+/// - `validator`: the validator identity, a funded wallet with keypair,
+///   which is also the authority of every stake account
+/// - `vote`: its vote account, with 10% commission
+/// - `clock`, `epoch_schedule`, `stake_history`, `epoch_rewards`: the
+///   sysvars, at the first block of `epoch` with the EpochRewards sysvar
+///   active and nothing of `total_rewards` distributed yet
+///
+/// Returns an error if `epoch` is 0, which has no epoch before it.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::scenarios::epoch_rewards;
+/// use solana_accountgen::serialization::bincode::legacy::deserialize_account_data;
+/// use solana_stake_interface::stake_history::StakeHistory;
+/// use solana_stake_interface::state::StakeStateV2;
+///
+/// let scenario = epoch_rewards(10, 1_000_000_000, 5_000_000).unwrap();
+///
+/// let history = scenario.accounts.get_account(&scenario.pubkey("stake_history")).unwrap();
+/// let history: StakeHistory = deserialize_account_data(history).unwrap();
+/// let stake = scenario.accounts.get_account(&scenario.pubkey("warming_stake")).unwrap();
+/// let stake: StakeStateV2 = deserialize_account_data(stake).unwrap();
+///
+/// let status = stake.delegation().unwrap().stake_activating_and_deactivating(10, &history, None);
+/// assert_eq!((status.effective, status.activating), (500_000_000, 500_000_000));
+/// ```
+pub fn epoch_rewards(
+    epoch: u64,
+    stake: u64,
+    total_rewards: u64,
+) -> Result<Scenario, AccountGenError> {
+    let Some(rewarded_epoch) = epoch.checked_sub(1) else {
+        return Err(AccountGenError::InvalidDataFormat(
+            "epoch 0 has no previous epoch to reward".to_string(),
+        ));
+    };
+    let mut scenario = Scenario::new();
+
+    let validator = scenario.add_wallet("validator", test_keypair("epoch_rewards:validator"));
+    let vote = test_pubkey("epoch_rewards:vote");
+    let mut vote_state = VoteState::new(
+        &VoteInit {
+            node_pubkey: validator,
+            authorized_voter: validator,
+            authorized_withdrawer: validator,
+            commission: 10,
+        },
+        &Clock::default(),
+    );
+    vote_state.increment_credits(rewarded_epoch, EPOCH_REWARDS_CREDITS);
+    scenario.add("vote", vote, create_vote_account(&vote_state));
+
+    let delegations = [
+        ("bootstrap_stake", u64::MAX, u64::MAX),
+        ("warming_stake", rewarded_epoch, u64::MAX),
+        ("activating_stake", epoch, u64::MAX),
+        ("deactivating_stake", u64::MAX, epoch),
+    ];
+    for (label, activation_epoch, deactivation_epoch) in delegations {
+        let delegation = Delegation {
+            deactivation_epoch,
+            ..Delegation::new(&vote, stake, activation_epoch)
+        };
+        let pubkey = test_pubkey(&format!("epoch_rewards:{}", label));
+        scenario.add(label, pubkey, create_stake_account(&validator, &delegation));
+    }
+
+    // With the bootstrap and deactivating stake effective in the previous
+    // epoch, a quarter of it is exactly half of the warming stake
+    let effective = stake.saturating_mul(2);
+    let mut stake_history = StakeHistory::default();
+    stake_history.add(
+        rewarded_epoch,
+        StakeHistoryEntry::with_effective_and_activating(effective, stake),
+    );
+
+    let epoch_schedule = EpochSchedule::without_warmup();
+    let slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+    scenario.set_clock(&Clock {
+        slot,
+        epoch,
+        leader_schedule_epoch: epoch + 1,
+        ..Clock::default()
+    });
+    scenario.add(
+        "epoch_schedule",
+        sysvar::epoch_schedule::id(),
+        create_sysvar_account(&epoch_schedule),
+    );
+    scenario.add(
+        "stake_history",
+        sysvar::stake_history::id(),
+        create_sysvar_account(&stake_history),
+    );
+    let rewards = EpochRewards {
+        distribution_starting_block_height: slot + 1,
+        num_partitions: 1,
+        total_points: u128::from(effective) * u128::from(EPOCH_REWARDS_CREDITS),
+        total_rewards,
+        active: true,
+        ..EpochRewards::default()
+    };
+    scenario.add(
+        "epoch_rewards",
+        sysvar::epoch_rewards::id(),
+        create_sysvar_account(&rewards),
+    );
+
+    Ok(scenario)
+}
@@ -1,17 +1,34 @@
 //! Helpers for creating Solana sysvar accounts.
 //!
 //! This module provides utilities for creating mock sysvar accounts
-//! for testing purposes.
+//! for testing purposes, and [`SysvarBundle`] for positioning the Clock at
+//! epoch boundaries and other edge cases. [`SysvarInjector`] adds the
+//! sysvar accounts a fixture's instructions reference but its accounts
+//! lack.
 
-use crate::AccountBuilder;
-use solana_program::pubkey::Pubkey;
-use solana_sdk::{
-    account::Account,
-    sysvar::{Sysvar, SysvarId},
-};
+use crate::AccountMap;
+use sha2::{Digest, Sha256};
+use solana_account::Account;
+use solana_clock::{Clock, DEFAULT_MS_PER_SLOT};
+use solana_epoch_schedule::EpochSchedule;
+use solana_hash::Hash;
+use solana_instruction::{BorrowedAccountMeta, BorrowedInstruction, Instruction};
+use solana_instructions_sysvar::{construct_instructions_data, store_current_index_checked};
+use solana_program_test::ProgramTestContext;
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+use solana_sdk_ids::sysvar;
+use solana_slot_hashes::SlotHashes;
+use solana_slot_history::SlotHistory;
+use solana_sysvar::Sysvar;
+use solana_sysvar::epoch_rewards::EpochRewards;
+use solana_sysvar::last_restart_slot::LastRestartSlot;
 
 /// Creates a sysvar account with the given data.
 ///
+/// The account is owned by the sysvar program and holds the rent-exempt
+/// minimum balance, as sysvar accounts do on a real cluster.
+///
 /// # Example
 ///
 /// ```
@@ -25,8 +42,413 @@ use solana_sdk::{
 /// let rent = Rent::default();
 /// let rent_account = create_sysvar_account(&rent);
 /// ```
-pub fn create_sysvar_account<S: Sysvar + SysvarId>(sysvar: &S) -> Account {
-    let mut account = Account::new(1, S::size_of(), &S::id());
-    sysvar.to_account_data(&mut account.data).unwrap();
-    account
-} 
\ No newline at end of file
+pub fn create_sysvar_account<S: Sysvar>(sysvar: &S) -> Account {
+    let data = bincode::serialize(sysvar).expect("sysvars always serialize");
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()).max(1),
+        data,
+        owner: sysvar::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Creates the Instructions sysvar account for a transaction made of
+/// `instructions`, with `current_index` as the executing instruction.
+///
+/// Programs that introspect their transaction, for example to check a
+/// preceding [precompile](crate::extensions::precompiles) instruction, read
+/// this account. The runtime builds it itself when transactions are
+/// processed; this is for calling a program's processor directly.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::sysvars::create_instructions_sysvar_account;
+/// use solana_instruction::Instruction;
+/// use solana_pubkey::Pubkey;
+///
+/// let instructions = [
+///     Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]),
+///     Instruction::new_with_bytes(Pubkey::new_unique(), &[2], vec![]),
+/// ];
+/// let account = create_instructions_sysvar_account(&instructions, 1);
+/// assert_eq!(account.data[account.data.len() - 2..], 1u16.to_le_bytes());
+/// ```
+pub fn create_instructions_sysvar_account(
+    instructions: &[Instruction],
+    current_index: u16,
+) -> Account {
+    let borrowed: Vec<BorrowedInstruction> = instructions
+        .iter()
+        .map(|instruction| BorrowedInstruction {
+            program_id: &instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| BorrowedAccountMeta {
+                    pubkey: &meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: &instruction.data,
+        })
+        .collect();
+    let mut data = construct_instructions_data(&borrowed);
+    store_current_index_checked(&mut data, current_index)
+        .expect("instructions sysvar data ends with the current index");
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()).max(1),
+        data,
+        owner: sysvar::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Unix timestamp of slot 0 used by [`SysvarBundle::at_slot`], the
+/// mainnet-beta genesis time.
+pub const GENESIS_UNIX_TIMESTAMP: i64 = 1_584_368_940;
+
+/// The last Unix timestamp that fits in an `i32`, after which programs that
+/// truncate timestamps to 32 bits wrap around.
+pub const Y2038_UNIX_TIMESTAMP: i64 = i32::MAX as i64;
+
+/// Clock, EpochSchedule, SlotHashes and SlotHistory sysvars that agree with
+/// each other.
+///
+/// Writing a Clock by hand makes it easy to pair a slot with the wrong epoch
+/// or leader schedule epoch, and a program that also reads SlotHashes may
+/// find no entry for the slot before the Clock's. A bundle derives every
+/// sysvar from the slot and the schedule, assuming one slot every
+/// [`DEFAULT_MS_PER_SLOT`] milliseconds since [`GENESIS_UNIX_TIMESTAMP`]
+/// and that no slot was skipped: SlotHashes holds the 512 slots before the
+/// Clock's, with hashes derived from the slot numbers, and SlotHistory marks
+/// every slot up to and including it.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountMap;
+/// use solana_accountgen::extensions::sysvars::at_epoch_boundary;
+/// use solana_sdk_ids::sysvar;
+///
+/// let bundle = at_epoch_boundary(5);
+/// assert_eq!(bundle.clock.epoch, 5);
+/// assert_eq!(bundle.clock.slot, 5 * 432_000);
+/// assert_eq!(bundle.clock.unix_timestamp, bundle.clock.epoch_start_timestamp);
+/// assert_eq!(bundle.slot_hashes.first().unwrap().0, bundle.clock.slot - 1);
+///
+/// let mut accounts = AccountMap::new();
+/// bundle.apply_to(&mut accounts);
+/// assert!(accounts.get_account(&sysvar::clock::id()).is_some());
+/// assert!(accounts.get_account(&sysvar::slot_hashes::id()).is_some());
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct SysvarBundle {
+    /// The Clock sysvar.
+    pub clock: Clock,
+    /// The EpochSchedule sysvar.
+    pub epoch_schedule: EpochSchedule,
+    /// The SlotHashes sysvar.
+    pub slot_hashes: SlotHashes,
+    /// The SlotHistory sysvar.
+    pub slot_history: SlotHistory,
+}
+
+// SlotHashes is not `Clone`
+impl Clone for SysvarBundle {
+    fn clone(&self) -> Self {
+        Self {
+            clock: self.clock.clone(),
+            epoch_schedule: self.epoch_schedule.clone(),
+            slot_hashes: SlotHashes::new(&self.slot_hashes),
+            slot_history: self.slot_history.clone(),
+        }
+    }
+}
+
+impl SysvarBundle {
+    /// Creates a bundle positioned at `slot`.
+    pub fn at_slot(slot: u64, epoch_schedule: EpochSchedule) -> Self {
+        let epoch = epoch_schedule.get_epoch(slot);
+        let epoch_start = epoch_schedule.get_first_slot_in_epoch(epoch);
+        let clock = Clock {
+            slot,
+            epoch_start_timestamp: slot_timestamp(epoch_start),
+            epoch,
+            leader_schedule_epoch: epoch_schedule.get_leader_schedule_epoch(slot),
+            unix_timestamp: slot_timestamp(slot),
+        };
+
+        let oldest_hashed = slot.saturating_sub(solana_slot_hashes::MAX_ENTRIES as u64);
+        let hashes: Vec<_> = (oldest_hashed..slot)
+            .map(|slot| (slot, slot_hash(slot)))
+            .collect();
+
+        // Adding a slot more than MAX_ENTRIES after the last one clears the
+        // history, so only the slots it can hold are added
+        let mut slot_history = SlotHistory::default();
+        let oldest_recorded = slot.saturating_sub(solana_slot_history::MAX_ENTRIES - 1);
+        for slot in oldest_recorded..=slot {
+            slot_history.add(slot);
+        }
+
+        Self {
+            clock,
+            epoch_schedule,
+            slot_hashes: SlotHashes::new(&hashes),
+            slot_history,
+        }
+    }
+
+    /// Creates a bundle at the first slot of `epoch`.
+    pub fn at_epoch_boundary(epoch: u64, epoch_schedule: EpochSchedule) -> Self {
+        Self::at_slot(
+            epoch_schedule.get_first_slot_in_epoch(epoch),
+            epoch_schedule,
+        )
+    }
+
+    /// Creates a bundle at the last slot of `epoch`, one slot before the
+    /// next boundary.
+    pub fn at_last_slot_of_epoch(epoch: u64, epoch_schedule: EpochSchedule) -> Self {
+        Self::at_slot(epoch_schedule.get_last_slot_in_epoch(epoch), epoch_schedule)
+    }
+
+    /// Shifts the Clock so its `unix_timestamp` is `unix_timestamp`.
+    ///
+    /// `epoch_start_timestamp` moves by the same amount, so the time elapsed
+    /// within the epoch is preserved. Use this with
+    /// [`Y2038_UNIX_TIMESTAMP`] to test timestamp wrap-around.
+    pub fn with_unix_timestamp(mut self, unix_timestamp: i64) -> Self {
+        let shift = unix_timestamp.saturating_sub(self.clock.unix_timestamp);
+        self.clock.unix_timestamp = unix_timestamp;
+        self.clock.epoch_start_timestamp = self.clock.epoch_start_timestamp.saturating_add(shift);
+        self
+    }
+
+    /// Returns the sysvar accounts of the bundle.
+    pub fn accounts(&self) -> Vec<(Pubkey, Account)> {
+        vec![
+            (sysvar::clock::id(), create_sysvar_account(&self.clock)),
+            (
+                sysvar::epoch_schedule::id(),
+                create_sysvar_account(&self.epoch_schedule),
+            ),
+            (
+                sysvar::slot_hashes::id(),
+                create_sysvar_account(&self.slot_hashes),
+            ),
+            (
+                sysvar::slot_history::id(),
+                create_sysvar_account(&self.slot_history),
+            ),
+        ]
+    }
+
+    /// Writes the sysvar accounts of the bundle into an [`AccountMap`] or a
+    /// running `ProgramTestContext`.
+    pub fn apply_to<T: SysvarTarget + ?Sized>(&self, target: &mut T) {
+        target.write_sysvar(&self.clock);
+        target.write_sysvar(&self.epoch_schedule);
+        target.write_sysvar(&self.slot_hashes);
+        target.write_sysvar(&self.slot_history);
+    }
+}
+
+/// Somewhere a [`SysvarBundle`] can be written.
+///
+/// `ProgramTest` is deliberately not a target: the bank rewrites the Clock,
+/// SlotHashes and SlotHistory when it starts, keeping only some of the
+/// fields written before, so a bundle is applied to the context returned by
+/// `start_with_context` instead. The context writes through
+/// `ProgramTestContext::set_sysvar`, which also refreshes the bank's sysvar
+/// cache, so programs calling `Clock::get()` see the bundle too.
+pub trait SysvarTarget {
+    /// Creates or replaces a sysvar.
+    fn write_sysvar<S: Sysvar>(&mut self, sysvar: &S);
+}
+
+impl SysvarTarget for AccountMap {
+    fn write_sysvar<S: Sysvar>(&mut self, sysvar: &S) {
+        self.set_account(S::id(), create_sysvar_account(sysvar));
+    }
+}
+
+impl SysvarTarget for ProgramTestContext {
+    fn write_sysvar<S: Sysvar>(&mut self, sysvar: &S) {
+        self.set_sysvar(sysvar);
+    }
+}
+
+/// Creates a [`SysvarBundle`] positioned at `slot` under `epoch_schedule`.
+pub fn at_slot(slot: u64, epoch_schedule: EpochSchedule) -> SysvarBundle {
+    SysvarBundle::at_slot(slot, epoch_schedule)
+}
+
+/// Creates a bundle at the first slot of `epoch` under a mainnet-style
+/// schedule of 432,000 slots per epoch without warmup.
+pub fn at_epoch_boundary(epoch: u64) -> SysvarBundle {
+    SysvarBundle::at_epoch_boundary(epoch, EpochSchedule::without_warmup())
+}
+
+/// Creates a bundle at the last slot of `epoch` under a mainnet-style
+/// schedule of 432,000 slots per epoch without warmup.
+pub fn at_last_slot_of_epoch(epoch: u64) -> SysvarBundle {
+    SysvarBundle::at_last_slot_of_epoch(epoch, EpochSchedule::without_warmup())
+}
+
+/// Adds the sysvar accounts that instructions reference but an account map
+/// lacks.
+///
+/// A bank provides every sysvar account, but an [`AccountMap`] loaded from
+/// a fixture and run through a
+/// [`LocalHarness`](crate::harness::LocalHarness) holds only what the
+/// fixture holds, so a program reading the Rent sysvar account fails on an
+/// empty account. The injector fills in the sysvar accounts referenced by
+/// the instructions' account metas: an override if one was given, else the
+/// sysvar's default value. The Instructions sysvar is built from the
+/// instructions themselves, with the first as the current one. Accounts
+/// already in the map are left as they are.
+///
+/// A `ProgramTest` bank creates its sysvars itself and needs no injection.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountMap;
+/// use solana_accountgen::extensions::sysvars::SysvarInjector;
+/// use solana_clock::Clock;
+/// use solana_instruction::{AccountMeta, Instruction};
+/// use solana_pubkey::Pubkey;
+/// use solana_sdk_ids::sysvar;
+///
+/// let instruction = Instruction::new_with_bytes(
+///     Pubkey::new_unique(),
+///     &[],
+///     vec![
+///         AccountMeta::new_readonly(sysvar::rent::id(), false),
+///         AccountMeta::new_readonly(sysvar::clock::id(), false),
+///     ],
+/// );
+///
+/// let clock = Clock { slot: 42, ..Clock::default() };
+/// let mut accounts = AccountMap::new();
+/// let injected = SysvarInjector::new()
+///     .with_sysvar(&clock)
+///     .inject(&mut accounts, [&instruction]);
+///
+/// assert_eq!(injected, vec![sysvar::rent::id(), sysvar::clock::id()]);
+/// let clock_account = accounts.get_account(&sysvar::clock::id()).unwrap();
+/// assert_eq!(bincode::deserialize::<Clock>(&clock_account.data).unwrap().slot, 42);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SysvarInjector {
+    overrides: AccountMap,
+}
+
+impl SysvarInjector {
+    /// Creates an injector that uses default sysvar values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Injects `sysvar` instead of its default value.
+    pub fn with_sysvar<S: Sysvar>(mut self, sysvar: &S) -> Self {
+        self.overrides
+            .set_account(S::id(), create_sysvar_account(sysvar));
+        self
+    }
+
+    /// Injects the sysvars of `bundle` instead of their default values.
+    pub fn with_bundle(mut self, bundle: &SysvarBundle) -> Self {
+        bundle.apply_to(&mut self.overrides);
+        self
+    }
+
+    /// Returns the account injected for the sysvar at `pubkey`, or `None`
+    /// if `pubkey` is not a sysvar this injector knows. The Instructions
+    /// sysvar depends on the instructions, so it is not returned here.
+    pub fn sysvar_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        if let Some(account) = self.overrides.get_account(pubkey) {
+            return Some(account.clone());
+        }
+        let account = match *pubkey {
+            sysvar::clock::ID => create_sysvar_account(&Clock::default()),
+            sysvar::rent::ID => create_sysvar_account(&Rent::default()),
+            sysvar::epoch_schedule::ID => create_sysvar_account(&EpochSchedule::default()),
+            sysvar::epoch_rewards::ID => create_sysvar_account(&EpochRewards::default()),
+            sysvar::last_restart_slot::ID => create_sysvar_account(&LastRestartSlot::default()),
+            sysvar::slot_hashes::ID => create_sysvar_account(&SlotHashes::default()),
+            sysvar::slot_history::ID => create_sysvar_account(&SlotHistory::default()),
+            #[allow(deprecated)]
+            sysvar::stake_history::ID => {
+                create_sysvar_account(&solana_sysvar::stake_history::StakeHistory::default())
+            }
+            _ => return None,
+        };
+        Some(account)
+    }
+
+    /// Adds the sysvar accounts referenced by `instructions` that are
+    /// missing from `accounts`, and returns their addresses in the order
+    /// they were first referenced.
+    pub fn inject<'a, I>(&self, accounts: &mut AccountMap, instructions: I) -> Vec<Pubkey>
+    where
+        I: IntoIterator<Item = &'a Instruction>,
+    {
+        let instructions: Vec<&Instruction> = instructions.into_iter().collect();
+        let mut injected = Vec::new();
+        let referenced = instructions
+            .iter()
+            .flat_map(|instruction| &instruction.accounts)
+            .map(|meta| meta.pubkey);
+        for pubkey in referenced {
+            if accounts.get_account(&pubkey).is_some() {
+                continue;
+            }
+            let account = if pubkey == sysvar::instructions::id() {
+                let instructions: Vec<Instruction> = instructions
+                    .iter()
+                    .map(|&instruction| instruction.clone())
+                    .collect();
+                create_instructions_sysvar_account(&instructions, 0)
+            } else {
+                match self.sysvar_account(&pubkey) {
+                    Some(account) => account,
+                    None => continue,
+                }
+            };
+            accounts.set_account(pubkey, account);
+            injected.push(pubkey);
+        }
+        injected
+    }
+}
+
+/// Adds default sysvar accounts for the sysvars `instructions` reference
+/// but `accounts` lacks, and returns their addresses.
+///
+/// This is [`SysvarInjector::inject`] without overrides.
+pub fn inject_sysvars<'a, I>(accounts: &mut AccountMap, instructions: I) -> Vec<Pubkey>
+where
+    I: IntoIterator<Item = &'a Instruction>,
+{
+    SysvarInjector::new().inject(accounts, instructions)
+}
+
+/// Returns the hash a bundle records for `slot`, a stand-in for the bank
+/// hash that differs from slot to slot.
+fn slot_hash(slot: u64) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(b"accountgen slot hash");
+    hasher.update(slot.to_le_bytes());
+    Hash::new_from_array(hasher.finalize().into())
+}
+
+fn slot_timestamp(slot: u64) -> i64 {
+    let elapsed_ms = slot.saturating_mul(DEFAULT_MS_PER_SLOT);
+    GENESIS_UNIX_TIMESTAMP.saturating_add((elapsed_ms / 1_000) as i64)
+}
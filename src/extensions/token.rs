@@ -1,31 +1,168 @@
 //! Helpers for creating SPL Token accounts.
 //!
 //! This module provides utilities for creating mock SPL Token accounts
-//! for testing purposes.
+//! for testing purposes. Account data uses the same packed layout as the
+//! SPL Token program, so the accounts can be passed to the real program.
+//! [`freeze`], [`thaw`], [`set_delegate`] and [`rotate_owner`] change an
+//! existing token account the way the corresponding token instructions
+//! would, for tests of authority transitions that start from fixtures.
+//! [`create_wrapped_sol_account`] and [`sync_native`] handle the rent
+//! reserve of wrapped SOL accounts.
 
-use crate::{AccountBuilder, AccountGenError};
-use borsh::{BorshDeserialize, BorshSerialize};
+use crate::{AccountBuilder, AccountGenError, well_known};
 use solana_account::Account;
 use solana_pubkey::Pubkey;
+use solana_rent::Rent;
 
-#[derive(BorshSerialize, BorshDeserialize)]
-struct TokenAccount {
-    mint: Pubkey,
-    owner: Pubkey,
-    amount: u64,
-    delegate: Option<Pubkey>,
-    state: u8,
-    is_native: Option<u64>,
-    delegated_amount: u64,
-    close_authority: Option<Pubkey>,
+/// Size of a packed SPL Token account.
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Size of a packed SPL Token mint.
+pub const MINT_LEN: usize = 82;
+
+/// The state of an SPL Token account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountState {
+    /// The account has not been initialized.
+    Uninitialized,
+    /// The account is initialized and can be used.
+    Initialized,
+    /// The account was frozen by the mint's freeze authority.
+    Frozen,
+}
+
+/// An unpacked SPL Token account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAccount {
+    /// The mint of the tokens held.
+    pub mint: Pubkey,
+    /// The owner, who may transfer the tokens.
+    pub owner: Pubkey,
+    /// The number of tokens held.
+    pub amount: u64,
+    /// The delegate allowed to transfer up to `delegated_amount` tokens.
+    pub delegate: Option<Pubkey>,
+    /// The account's state.
+    pub state: AccountState,
+    /// For wrapped SOL accounts, the rent-exempt reserve that is not
+    /// counted as tokens.
+    pub is_native: Option<u64>,
+    /// The number of tokens the delegate may still transfer.
+    pub delegated_amount: u64,
+    /// The authority allowed to close the account, if not the owner.
+    pub close_authority: Option<Pubkey>,
+}
+
+impl TokenAccount {
+    /// Packs the account into the 165-byte SPL layout.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(TOKEN_ACCOUNT_LEN);
+        data.extend_from_slice(self.mint.as_ref());
+        data.extend_from_slice(self.owner.as_ref());
+        data.extend_from_slice(&self.amount.to_le_bytes());
+        pack_option_pubkey(&mut data, self.delegate);
+        data.push(match self.state {
+            AccountState::Uninitialized => 0,
+            AccountState::Initialized => 1,
+            AccountState::Frozen => 2,
+        });
+        match self.is_native {
+            Some(reserve) => {
+                data.extend_from_slice(&1u32.to_le_bytes());
+                data.extend_from_slice(&reserve.to_le_bytes());
+            }
+            None => data.extend_from_slice(&[0; 12]),
+        }
+        data.extend_from_slice(&self.delegated_amount.to_le_bytes());
+        pack_option_pubkey(&mut data, self.close_authority);
+        data
+    }
+
+    /// Unpacks the first 165 bytes of `data`. Token-2022 extensions after
+    /// them are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the data is too
+    /// short or a tag is invalid.
+    pub fn unpack(data: &[u8]) -> Result<Self, AccountGenError> {
+        if data.len() < TOKEN_ACCOUNT_LEN {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "token account data is {} bytes, expected at least {}",
+                data.len(),
+                TOKEN_ACCOUNT_LEN
+            )));
+        }
+        let u64_at = |offset: usize| {
+            u64::from_le_bytes(data[offset..offset + 8].try_into().expect("8 bytes"))
+        };
+        let state = match data[108] {
+            0 => AccountState::Uninitialized,
+            1 => AccountState::Initialized,
+            2 => AccountState::Frozen,
+            other => {
+                return Err(AccountGenError::InvalidDataFormat(format!(
+                    "invalid token account state {}",
+                    other
+                )));
+            }
+        };
+        let is_native = match option_tag(data, 109)? {
+            true => Some(u64_at(113)),
+            false => None,
+        };
+        Ok(Self {
+            mint: pubkey_at(data, 0),
+            owner: pubkey_at(data, 32),
+            amount: u64_at(64),
+            delegate: unpack_option_pubkey(data, 72)?,
+            state,
+            is_native,
+            delegated_amount: u64_at(121),
+            close_authority: unpack_option_pubkey(data, 129)?,
+        })
+    }
+}
+
+fn pubkey_at(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().expect("32 bytes"))
+}
+
+/// Reads the 4-byte tag of an SPL `COption`.
+fn option_tag(data: &[u8], offset: usize) -> Result<bool, AccountGenError> {
+    match u32::from_le_bytes(data[offset..offset + 4].try_into().expect("4 bytes")) {
+        0 => Ok(false),
+        1 => Ok(true),
+        tag => Err(AccountGenError::InvalidDataFormat(format!(
+            "invalid COption tag {} at offset {}",
+            tag, offset
+        ))),
+    }
+}
+
+fn unpack_option_pubkey(data: &[u8], offset: usize) -> Result<Option<Pubkey>, AccountGenError> {
+    Ok(option_tag(data, offset)?.then(|| pubkey_at(data, offset + 4)))
+}
+
+/// Packs an SPL `COption<Pubkey>`: a 4-byte tag followed by the key.
+fn pack_option_pubkey(data: &mut Vec<u8>, value: Option<Pubkey>) {
+    match value {
+        Some(pubkey) => {
+            data.extend_from_slice(&1u32.to_le_bytes());
+            data.extend_from_slice(pubkey.as_ref());
+        }
+        None => data.extend_from_slice(&[0; 36]),
+    }
 }
 
 /// Creates a mock SPL Token account with the given parameters.
 ///
+/// The account holds the rent-exempt minimum balance.
+///
 /// # Example
 ///
 /// ```
-/// use solana_accountgen::extensions::token::create_token_account;
+/// use solana_accountgen::extensions::token::{TOKEN_ACCOUNT_LEN, create_token_account};
 /// use solana_pubkey::Pubkey;
 ///
 /// let mint = Pubkey::new_unique();
@@ -38,6 +175,7 @@ struct TokenAccount {
 ///     1000,
 ///     &token_program_id,
 /// ).unwrap();
+/// assert_eq!(account.data.len(), TOKEN_ACCOUNT_LEN);
 /// ```
 pub fn create_token_account(
     mint: &Pubkey,
@@ -50,15 +188,280 @@ pub fn create_token_account(
         owner: *owner,
         amount,
         delegate: None,
-        state: 1, // initialized
+        state: AccountState::Initialized,
         is_native: None,
         delegated_amount: 0,
         close_authority: None,
     };
 
     AccountBuilder::new()
-        .balance(1_000_000) // Rent exempt amount
         .owner(*token_program_id)
-        .data(token_account)?
+        .data_raw(token_account.pack())
+        .try_build()
+}
+
+/// Creates a mock SPL Token mint with the given parameters.
+///
+/// The mint has no freeze authority and holds the rent-exempt minimum
+/// balance.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::token::{MINT_LEN, create_mint_account};
+/// use solana_accountgen::well_known::TOKEN_PROGRAM;
+/// use solana_pubkey::Pubkey;
+///
+/// let authority = Pubkey::new_unique();
+/// let mint = create_mint_account(&authority, 1_000_000, 6, &TOKEN_PROGRAM).unwrap();
+/// assert_eq!(mint.data.len(), MINT_LEN);
+/// ```
+pub fn create_mint_account(
+    mint_authority: &Pubkey,
+    supply: u64,
+    decimals: u8,
+    token_program_id: &Pubkey,
+) -> Result<Account, AccountGenError> {
+    let mut data = Vec::with_capacity(MINT_LEN);
+    pack_option_pubkey(&mut data, Some(*mint_authority));
+    data.extend_from_slice(&supply.to_le_bytes());
+    data.push(decimals);
+    data.push(1); // initialized
+    pack_option_pubkey(&mut data, None);
+
+    AccountBuilder::new()
+        .owner(*token_program_id)
+        .data_raw(data)
+        .try_build()
+}
+
+/// Creates a wrapped SOL account holding `lamports` as tokens of the native
+/// mint.
+///
+/// The account's balance is the rent-exempt reserve plus `lamports`, and
+/// the reserve is recorded in `is_native` so the token program does not
+/// count it as tokens, just as after `SyncNative`.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] if the balance overflows.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::token::{
+///     TOKEN_ACCOUNT_LEN, TokenAccount, create_wrapped_sol_account, sync_native,
+/// };
+/// use solana_accountgen::well_known::NATIVE_MINT;
+/// use solana_pubkey::Pubkey;
+/// use solana_rent::Rent;
+///
+/// let mut account = create_wrapped_sol_account(&Pubkey::new_unique(), 1_000_000).unwrap();
+/// let reserve = Rent::default().minimum_balance(TOKEN_ACCOUNT_LEN);
+/// assert_eq!(account.lamports, reserve + 1_000_000);
+///
+/// let token_account = TokenAccount::unpack(&account.data).unwrap();
+/// assert_eq!(token_account.mint, NATIVE_MINT);
+/// assert_eq!(token_account.is_native, Some(reserve));
+///
+/// // A plain SOL transfer into the account only shows up after syncing
+/// account.lamports += 500;
+/// sync_native(&mut account).unwrap();
+/// assert_eq!(TokenAccount::unpack(&account.data).unwrap().amount, 1_000_500);
+/// ```
+pub fn create_wrapped_sol_account(
+    owner: &Pubkey,
+    lamports: u64,
+) -> Result<Account, AccountGenError> {
+    let reserve = Rent::default().minimum_balance(TOKEN_ACCOUNT_LEN);
+    let balance = reserve.checked_add(lamports).ok_or_else(|| {
+        AccountGenError::InvalidDataFormat(format!(
+            "{} lamports plus the rent-exempt reserve overflows",
+            lamports
+        ))
+    })?;
+    let token_account = TokenAccount {
+        mint: well_known::NATIVE_MINT,
+        owner: *owner,
+        amount: lamports,
+        delegate: None,
+        state: AccountState::Initialized,
+        is_native: Some(reserve),
+        delegated_amount: 0,
+        close_authority: None,
+    };
+
+    AccountBuilder::new()
+        .balance(balance)
+        .owner(well_known::TOKEN_PROGRAM)
+        .data_raw(token_account.pack())
         .try_build()
 }
+
+/// Updates the token amount of a wrapped SOL account to its balance above
+/// the rent-exempt reserve, as `SyncNative` does.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::ConstraintViolation`] if the account does not
+/// hold wrapped SOL, or if its balance is below the reserve plus the
+/// recorded amount, which the token program rejects.
+pub fn sync_native(account: &mut Account) -> Result<(), AccountGenError> {
+    let lamports = account.lamports;
+    update_token_account(account, |token_account| {
+        let Some(reserve) = token_account.is_native else {
+            return Err("token account does not hold wrapped SOL".to_string());
+        };
+        match lamports.checked_sub(reserve) {
+            Some(amount) if amount >= token_account.amount => {
+                token_account.amount = amount;
+                Ok(())
+            }
+            _ => Err(format!(
+                "balance of {} lamports is below the reserve of {} plus the {} tokens held",
+                lamports, reserve, token_account.amount
+            )),
+        }
+    })
+}
+
+/// Unpacks `account`, applies `change` and packs the result back in place,
+/// keeping any Token-2022 extension data after the base layout.
+fn update_token_account(
+    account: &mut Account,
+    change: impl FnOnce(&mut TokenAccount) -> Result<(), String>,
+) -> Result<(), AccountGenError> {
+    let mut token_account = TokenAccount::unpack(&account.data)?;
+    if token_account.state == AccountState::Uninitialized {
+        return Err(AccountGenError::ConstraintViolation(
+            "token account is not initialized".to_string(),
+        ));
+    }
+    change(&mut token_account).map_err(AccountGenError::ConstraintViolation)?;
+    account.data[..TOKEN_ACCOUNT_LEN].copy_from_slice(&token_account.pack());
+    Ok(())
+}
+
+/// Freezes a token account, as `FreezeAccount` does.
+///
+/// The mint's freeze authority is not checked; the fixture stands for a
+/// state the authority has already brought about.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::ConstraintViolation`] if the account is not
+/// initialized, already frozen or holds wrapped SOL, which the token
+/// program refuses to freeze.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::token::{
+///     AccountState, TokenAccount, create_token_account, freeze, thaw,
+/// };
+/// use solana_accountgen::well_known::TOKEN_PROGRAM;
+/// use solana_pubkey::Pubkey;
+///
+/// let mut account =
+///     create_token_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 100, &TOKEN_PROGRAM)
+///         .unwrap();
+/// freeze(&mut account).unwrap();
+/// assert_eq!(TokenAccount::unpack(&account.data).unwrap().state, AccountState::Frozen);
+/// assert!(freeze(&mut account).is_err());
+///
+/// thaw(&mut account).unwrap();
+/// assert_eq!(TokenAccount::unpack(&account.data).unwrap().state, AccountState::Initialized);
+/// ```
+pub fn freeze(account: &mut Account) -> Result<(), AccountGenError> {
+    update_token_account(account, |token_account| {
+        if token_account.is_native.is_some() {
+            return Err("wrapped SOL accounts cannot be frozen".to_string());
+        }
+        if token_account.state == AccountState::Frozen {
+            return Err("token account is already frozen".to_string());
+        }
+        token_account.state = AccountState::Frozen;
+        Ok(())
+    })
+}
+
+/// Thaws a frozen token account, as `ThawAccount` does.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::ConstraintViolation`] if the account is not
+/// frozen.
+pub fn thaw(account: &mut Account) -> Result<(), AccountGenError> {
+    update_token_account(account, |token_account| {
+        if token_account.state != AccountState::Frozen {
+            return Err("token account is not frozen".to_string());
+        }
+        token_account.state = AccountState::Initialized;
+        Ok(())
+    })
+}
+
+/// Sets the delegate of a token account and the amount it may transfer, as
+/// `Approve` does, or removes it when `delegate` is `None`, as `Revoke`
+/// does.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::ConstraintViolation`] if the account is not
+/// initialized or is frozen.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::token::{TokenAccount, create_token_account, set_delegate};
+/// use solana_accountgen::well_known::TOKEN_PROGRAM;
+/// use solana_pubkey::Pubkey;
+///
+/// let delegate = Pubkey::new_unique();
+/// let mut account =
+///     create_token_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 100, &TOKEN_PROGRAM)
+///         .unwrap();
+/// set_delegate(&mut account, Some((delegate, 40))).unwrap();
+///
+/// let token_account = TokenAccount::unpack(&account.data).unwrap();
+/// assert_eq!(token_account.delegate, Some(delegate));
+/// assert_eq!(token_account.delegated_amount, 40);
+/// ```
+pub fn set_delegate(
+    account: &mut Account,
+    delegate: Option<(Pubkey, u64)>,
+) -> Result<(), AccountGenError> {
+    update_token_account(account, |token_account| {
+        if token_account.state == AccountState::Frozen {
+            return Err("token account is frozen".to_string());
+        }
+        token_account.delegate = delegate.map(|(delegate, _)| delegate);
+        token_account.delegated_amount = delegate.map_or(0, |(_, amount)| amount);
+        Ok(())
+    })
+}
+
+/// Transfers ownership of a token account, as `SetAuthority` with the
+/// `AccountOwner` type does.
+///
+/// Like the token program, this removes any delegate and, for wrapped SOL
+/// accounts, the close authority.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::ConstraintViolation`] if the account is not
+/// initialized or is frozen.
+pub fn rotate_owner(account: &mut Account, new_owner: &Pubkey) -> Result<(), AccountGenError> {
+    update_token_account(account, |token_account| {
+        if token_account.state == AccountState::Frozen {
+            return Err("token account is frozen".to_string());
+        }
+        token_account.owner = *new_owner;
+        token_account.delegate = None;
+        token_account.delegated_amount = 0;
+        if token_account.is_native.is_some() {
+            token_account.close_authority = None;
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,252 @@
+//! Factories for fixture types, with a default for every field.
+//!
+//! Tests of a program usually need many accounts of the same few types,
+//! each differing from a sensible default in a field or two. Spelling out
+//! every field in every test buries the one that matters. The [`factory!`]
+//! macro declares a factory for an [`AccountFixture`] type: a builder that
+//! starts from per-field defaults, has a setter per field, and builds the
+//! value, an account holding it, or a PDA, through the [`Factory`] trait.
+//!
+//! # Example
+//!
+//! ```
+//! use borsh::{BorshDeserialize, BorshSerialize};
+//! use solana_accountgen::factories::Factory;
+//! use solana_accountgen::{AccountFixture, factory};
+//! use solana_pubkey::Pubkey;
+//!
+//! #[derive(BorshSerialize, BorshDeserialize, AccountFixture, Debug, Clone, PartialEq)]
+//! pub struct User {
+//!     authority: Pubkey,
+//!     score: u64,
+//!     level: u8,
+//! }
+//!
+//! factory! {
+//!     /// Builds `User` accounts.
+//!     pub struct UserFactory for User {
+//!         authority: Pubkey = Pubkey::new_from_array([1; 32]),
+//!         score: u64 = 0,
+//!         level: u8 = 1,
+//!     }
+//!     seeds(user) = [b"user", user.authority];
+//! }
+//!
+//! let program_id = Pubkey::new_unique();
+//! let (address, _, account) = UserFactory::new()
+//!     .score(100)
+//!     .level(3)
+//!     .build_pda(&program_id)
+//!     .unwrap();
+//!
+//! let authority = Pubkey::new_from_array([1; 32]);
+//! let (expected, _) = Pubkey::find_program_address(&[b"user", authority.as_ref()], &program_id);
+//! assert_eq!(address, expected);
+//! assert_eq!(account.owner, program_id);
+//! assert_eq!(User::from_account(&account).unwrap().score, 100);
+//! ```
+
+use crate::{AccountBuilder, AccountFixture, AccountGenError, tracking};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+/// How a factory builds accounts, shared by every factory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FactorySettings {
+    /// The balance of built accounts, or rent-exempt if `None`.
+    pub lamports: Option<u64>,
+    /// Whether built accounts start with the Anchor discriminator.
+    pub anchor: bool,
+}
+
+/// A builder of fixture values and accounts, declared with [`factory!`].
+pub trait Factory: Sized {
+    /// The type the factory builds.
+    type Output: AccountFixture;
+
+    /// Returns the value described by the factory's fields.
+    fn build(&self) -> Self::Output;
+
+    /// Returns the factory's account settings.
+    fn settings(&self) -> &FactorySettings;
+
+    /// Returns the factory's account settings for changing.
+    fn settings_mut(&mut self) -> &mut FactorySettings;
+
+    /// Returns the seeds of the PDA holding `value`.
+    ///
+    /// Factories declared without seeds use the type's Anchor name alone,
+    /// so every value built by them shares one address.
+    fn seeds(&self, value: &Self::Output) -> Vec<Vec<u8>> {
+        let _ = value;
+        vec![
+            <Self::Output as AccountFixture>::ANCHOR_NAME
+                .as_bytes()
+                .to_vec(),
+        ]
+    }
+
+    /// Sets the balance of built accounts. Defaults to rent-exempt.
+    fn lamports(mut self, lamports: u64) -> Self {
+        self.settings_mut().lamports = Some(lamports);
+        self
+    }
+
+    /// Prefixes the data of built accounts with the type's Anchor
+    /// discriminator.
+    fn anchor(mut self) -> Self {
+        self.settings_mut().anchor = true;
+        self
+    }
+
+    /// Builds an account owned by `owner` holding the value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be serialized, or any error
+    /// [`AccountBuilder::try_build`] returns.
+    fn build_account(&self, owner: &Pubkey) -> Result<Account, AccountGenError> {
+        account(&self.build(), owner, self.settings())
+    }
+
+    /// Builds an account owned by `program_id` holding the value, at the
+    /// PDA of the factory's [`seeds`](Self::seeds). Returns the address, its
+    /// bump and the account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be serialized, or any error
+    /// [`AccountBuilder::try_build`] returns.
+    fn build_pda(&self, program_id: &Pubkey) -> Result<(Pubkey, u8, Account), AccountGenError> {
+        let value = self.build();
+        let seeds = self.seeds(&value);
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        let (pda, bump) = Pubkey::find_program_address(&seeds, program_id);
+        tracking::record(pda);
+        Ok((pda, bump, account(&value, program_id, self.settings())?))
+    }
+}
+
+fn account<T: AccountFixture>(
+    value: &T,
+    owner: &Pubkey,
+    settings: &FactorySettings,
+) -> Result<Account, AccountGenError> {
+    let mut data = Vec::new();
+    if settings.anchor {
+        data.extend_from_slice(&T::anchor_discriminator());
+    }
+    value
+        .serialize(&mut data)
+        .map_err(AccountGenError::SerializationError)?;
+    let mut builder = AccountBuilder::new().owner(*owner).data_raw(data);
+    if let Some(lamports) = settings.lamports {
+        builder = builder.balance(lamports);
+    }
+    builder.try_build()
+}
+
+/// Declares a [`Factory`] for an [`AccountFixture`] type.
+///
+/// Every field of the type is listed with its default, which is evaluated
+/// each time the factory is created. The factory gets `new`, a `Default`
+/// impl and a setter per field. An optional `seeds` clause names the
+/// built value and gives the seeds of its PDA, each anything that is
+/// `AsRef<[u8]>`.
+///
+/// ```
+/// use borsh::{BorshDeserialize, BorshSerialize};
+/// use solana_accountgen::factories::Factory;
+/// use solana_accountgen::{AccountFixture, factory};
+/// use solana_pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize, BorshDeserialize, AccountFixture, Debug, Clone, PartialEq)]
+/// struct Pool {
+///     fee_bps: u16,
+///     paused: bool,
+/// }
+///
+/// factory! {
+///     struct PoolFactory for Pool {
+///         fee_bps: u16 = 30,
+///         paused: bool = false,
+///     }
+/// }
+///
+/// let pool = PoolFactory::new().paused(true).build();
+/// assert_eq!(pool, Pool { fee_bps: 30, paused: true });
+///
+/// let account = PoolFactory::new()
+///     .anchor()
+///     .lamports(5_000_000)
+///     .build_account(&Pubkey::new_unique())
+///     .unwrap();
+/// assert_eq!(account.lamports, 5_000_000);
+/// assert_eq!(account.data[..8], Pool::anchor_discriminator());
+/// ```
+#[macro_export]
+macro_rules! factory {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident for $target:path {
+            $($(#[$field_meta:meta])* $field:ident : $field_ty:ty = $default:expr),* $(,)?
+        }
+        $(seeds($value:ident) = [$($seed:expr),* $(,)?];)?
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        $vis struct $name {
+            $($field: $field_ty,)*
+            settings: $crate::factories::FactorySettings,
+        }
+
+        impl $name {
+            /// Creates a factory with every field at its default.
+            pub fn new() -> Self {
+                Self {
+                    $($field: $default,)*
+                    settings: $crate::factories::FactorySettings::default(),
+                }
+            }
+
+            $(
+                #[doc = concat!("Sets `", stringify!($field), "`.")]
+                $(#[$field_meta])*
+                pub fn $field(mut self, $field: $field_ty) -> Self {
+                    self.$field = $field;
+                    self
+                }
+            )*
+        }
+
+        impl ::std::default::Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl $crate::factories::Factory for $name {
+            type Output = $target;
+
+            fn build(&self) -> $target {
+                $target {
+                    $($field: ::std::clone::Clone::clone(&self.$field),)*
+                }
+            }
+
+            fn settings(&self) -> &$crate::factories::FactorySettings {
+                &self.settings
+            }
+
+            fn settings_mut(&mut self) -> &mut $crate::factories::FactorySettings {
+                &mut self.settings
+            }
+
+            $(
+                fn seeds(&self, $value: &$target) -> ::std::vec::Vec<::std::vec::Vec<u8>> {
+                    ::std::vec![$(::std::convert::AsRef::<[u8]>::as_ref(&$seed).to_vec()),*]
+                }
+            )?
+        }
+    };
+}
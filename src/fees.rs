@@ -0,0 +1,245 @@
+//! Funding fee payers for multi-transaction tests.
+//!
+//! A payer funded with a round number works for the first few transactions
+//! and then fails somewhere in the middle of a long test, or fails at once
+//! because paying the fee would leave it below the rent-exempt minimum.
+//! [`fund_for_fees`] tops a payer up for a number of single-signature
+//! transactions, and [`FeeEstimator`] works out the fee of actual
+//! transactions, including priority fees and precompile signatures, so
+//! [`FeeEstimator::fund_for_transactions`] can fund a payer for exactly the
+//! transactions a test sends. [`InstructionSet::fund_payer`] does the same
+//! for the transactions of an instruction replay.
+//!
+//! [`InstructionSet::fund_payer`]: crate::fixture::InstructionSet::fund_payer
+//!
+//! ```
+//! use solana_accountgen::AccountMap;
+//! use solana_accountgen::fees::{DEFAULT_LAMPORTS_PER_SIGNATURE, fund_for_fees};
+//! use solana_pubkey::Pubkey;
+//! use solana_rent::Rent;
+//!
+//! let payer = Pubkey::new_unique();
+//! let mut accounts = AccountMap::new();
+//! fund_for_fees(&mut accounts, &payer, 100, DEFAULT_LAMPORTS_PER_SIGNATURE);
+//!
+//! let balance = accounts.get_account(&payer).unwrap().lamports;
+//! assert_eq!(balance, Rent::default().minimum_balance(0) + 100 * 5_000);
+//! ```
+
+use crate::well_known::{
+    BPF_LOADER, BPF_LOADER_DEPRECATED, BPF_LOADER_UPGRADEABLE, COMPUTE_BUDGET_PROGRAM,
+    ED25519_PROGRAM, LOADER_V4, SECP256K1_PROGRAM, SYSTEM_PROGRAM, VOTE_PROGRAM,
+};
+use crate::{AccountGenError, AccountMap};
+use solana_account::Account;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+use solana_sdk_ids::system_program;
+use solana_transaction::Transaction;
+
+/// The base fee per signature on every cluster, in lamports.
+pub const DEFAULT_LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// The compute unit limit of an instruction when the transaction does not
+/// set one.
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+
+/// The compute unit limit of a builtin program instruction when the
+/// transaction does not set one.
+pub const BUILTIN_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 3_000;
+
+/// Programs implemented in the runtime, whose instructions reserve
+/// [`BUILTIN_INSTRUCTION_COMPUTE_UNIT_LIMIT`] compute units by default.
+/// Builtins being migrated to on-chain programs (stake, config and address
+/// lookup table) are treated as on-chain programs.
+const BUILTIN_PROGRAMS: &[Pubkey] = &[
+    SYSTEM_PROGRAM,
+    VOTE_PROGRAM,
+    COMPUTE_BUDGET_PROGRAM,
+    BPF_LOADER_DEPRECATED,
+    BPF_LOADER,
+    BPF_LOADER_UPGRADEABLE,
+    LOADER_V4,
+    ED25519_PROGRAM,
+    SECP256K1_PROGRAM,
+];
+
+/// The largest compute unit limit a transaction can have.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Adds fees for `num_transactions` single-signature transactions to the
+/// balance of `payer`, creating it as a system account if it is missing.
+///
+/// A payer must stay rent exempt after paying a fee, so the payer is first
+/// brought up to the rent-exempt minimum for its data size. Returns the
+/// payer's new balance.
+pub fn fund_for_fees(
+    account_map: &mut AccountMap,
+    payer: &Pubkey,
+    num_transactions: u64,
+    fee_per_sig: u64,
+) -> u64 {
+    fund(
+        account_map,
+        payer,
+        num_transactions.saturating_mul(fee_per_sig),
+    )
+}
+
+pub(crate) fn fund(account_map: &mut AccountMap, payer: &Pubkey, fees: u64) -> u64 {
+    let mut account = account_map.get_account(payer).cloned().unwrap_or(Account {
+        owner: system_program::id(),
+        ..Account::default()
+    });
+    let minimum = Rent::default().minimum_balance(account.data.len());
+    account.lamports = account.lamports.max(minimum).saturating_add(fees);
+    let balance = account.lamports;
+    account_map.set_account(*payer, account);
+    balance
+}
+
+/// Estimates transaction fees the way the runtime charges them.
+///
+/// A fee is the base fee for every signature, counting the signatures
+/// verified by ed25519 and secp256k1 precompile instructions, plus the
+/// priority fee: the compute unit price set with the Compute Budget program
+/// times the compute unit limit, rounded up to whole lamports. Without an
+/// explicit limit, each instruction counts for
+/// [`DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT`], or
+/// [`BUILTIN_INSTRUCTION_COMPUTE_UNIT_LIMIT`] for builtin programs such as
+/// the System and Compute Budget programs.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::fees::FeeEstimator;
+/// use solana_accountgen::instructions::{compute_budget, system};
+/// use solana_pubkey::Pubkey;
+///
+/// let (payer, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+/// let instructions = [
+///     compute_budget::set_compute_unit_limit(100_000),
+///     compute_budget::set_compute_unit_price(2_500),
+///     system::transfer(&payer, &to, 1),
+/// ];
+///
+/// // 5000 lamports for the signature and 100000 * 2500 micro-lamports
+/// let fee = FeeEstimator::default().estimate_instructions(&instructions, 1);
+/// assert_eq!(fee, 5_000 + 250);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimator {
+    /// The base fee per signature, in lamports.
+    pub lamports_per_signature: u64,
+}
+
+impl Default for FeeEstimator {
+    fn default() -> Self {
+        Self {
+            lamports_per_signature: DEFAULT_LAMPORTS_PER_SIGNATURE,
+        }
+    }
+}
+
+impl FeeEstimator {
+    /// Creates an estimator with the given base fee per signature.
+    pub fn new(lamports_per_signature: u64) -> Self {
+        Self {
+            lamports_per_signature,
+        }
+    }
+
+    /// Estimates the fee of a transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if an instruction's
+    /// program ID index is outside the message's account keys.
+    pub fn estimate(&self, transaction: &Transaction) -> Result<u64, AccountGenError> {
+        let message = &transaction.message;
+        let instructions = message
+            .instructions
+            .iter()
+            .map(|instruction| {
+                let index = instruction.program_id_index as usize;
+                let program_id = message.account_keys.get(index).ok_or_else(|| {
+                    AccountGenError::InvalidDataFormat(format!(
+                        "program ID index {} is out of bounds for {} account keys",
+                        index,
+                        message.account_keys.len()
+                    ))
+                })?;
+                Ok((program_id, instruction.data.as_slice()))
+            })
+            .collect::<Result<Vec<(&Pubkey, &[u8])>, AccountGenError>>()?;
+        Ok(self.estimate_parts(&instructions, message.header.num_required_signatures as u64))
+    }
+
+    /// Estimates the fee of a transaction made of `instructions` and signed
+    /// by `num_signers` signers, before it is built.
+    pub fn estimate_instructions(&self, instructions: &[Instruction], num_signers: u64) -> u64 {
+        let instructions: Vec<(&Pubkey, &[u8])> = instructions
+            .iter()
+            .map(|instruction| (&instruction.program_id, instruction.data.as_slice()))
+            .collect();
+        self.estimate_parts(&instructions, num_signers)
+    }
+
+    /// Adds the fees of `transactions` to the balance of `payer`, as
+    /// [`fund_for_fees`] does. Returns the payer's new balance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a transaction cannot be estimated; see
+    /// [`FeeEstimator::estimate`].
+    pub fn fund_for_transactions(
+        &self,
+        account_map: &mut AccountMap,
+        payer: &Pubkey,
+        transactions: &[Transaction],
+    ) -> Result<u64, AccountGenError> {
+        let mut fees = 0u64;
+        for transaction in transactions {
+            fees = fees.saturating_add(self.estimate(transaction)?);
+        }
+        Ok(fund(account_map, payer, fees))
+    }
+
+    fn estimate_parts(&self, instructions: &[(&Pubkey, &[u8])], num_signers: u64) -> u64 {
+        let mut signatures = num_signers;
+        let mut unit_limit = None;
+        let mut unit_price = 0u64;
+        let mut default_unit_limit = 0u32;
+
+        for (program_id, data) in instructions {
+            match **program_id {
+                ED25519_PROGRAM | SECP256K1_PROGRAM => {
+                    signatures += data.first().copied().unwrap_or_default() as u64;
+                }
+                COMPUTE_BUDGET_PROGRAM => match data.split_first() {
+                    Some((2, units)) => unit_limit = units.try_into().ok().map(u32::from_le_bytes),
+                    Some((3, price)) => {
+                        unit_price = price.try_into().map(u64::from_le_bytes).unwrap_or_default()
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+            default_unit_limit =
+                default_unit_limit.saturating_add(if BUILTIN_PROGRAMS.contains(program_id) {
+                    BUILTIN_INSTRUCTION_COMPUTE_UNIT_LIMIT
+                } else {
+                    DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT
+                });
+        }
+
+        let unit_limit = unit_limit
+            .unwrap_or(default_unit_limit)
+            .min(MAX_COMPUTE_UNIT_LIMIT);
+        let priority_fee = (unit_price as u128 * unit_limit as u128).div_ceil(1_000_000);
+        signatures
+            .saturating_mul(self.lamports_per_signature)
+            .saturating_add(priority_fee.try_into().unwrap_or(u64::MAX))
+    }
+}
@@ -0,0 +1,210 @@
+use super::FixtureInstruction;
+use crate::AccountMap;
+use crate::fees::{self, FeeEstimator};
+use serde::{Deserialize, Serialize};
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_program_test::{BanksClient, BanksClientError};
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use std::collections::BTreeSet;
+
+/// An ordered list of instructions that can be stored alongside accounts.
+///
+/// Together with an [`AccountMap`](crate::AccountMap), an `InstructionSet`
+/// describes a complete scenario that can be saved with
+/// [`save_scenario`](super::save_scenario) and replayed later.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::fixture::InstructionSet;
+/// use solana_instruction::{AccountMeta, Instruction};
+/// use solana_pubkey::Pubkey;
+///
+/// let mut instructions = InstructionSet::new();
+/// instructions.push(Instruction::new_with_bytes(
+///     Pubkey::new_unique(),
+///     &[1, 2, 3],
+///     vec![AccountMeta::new(Pubkey::new_unique(), false)],
+/// ));
+///
+/// let json = serde_json::to_string(&instructions).unwrap();
+/// let restored: InstructionSet = serde_json::from_str(&json).unwrap();
+/// assert_eq!(restored, instructions);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "Vec<FixtureInstruction>", from = "Vec<FixtureInstruction>")]
+pub struct InstructionSet {
+    instructions: Vec<Instruction>,
+}
+
+impl InstructionSet {
+    /// Creates an empty instruction set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an instruction.
+    pub fn push(&mut self, instruction: Instruction) {
+        self.instructions.push(instruction);
+    }
+
+    /// Returns an iterator over the instructions, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Instruction> {
+        self.instructions.iter()
+    }
+
+    /// Returns the number of instructions.
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Returns true if the set contains no instructions.
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Adds the fees [`InstructionSet::replay`] pays to the balance of
+    /// `payer` in `account_map`, as estimated by `estimator`, and returns the
+    /// payer's new balance.
+    ///
+    /// Fund the payer this way before loading the accounts into
+    /// `ProgramTest`, so a long replay does not run out of lamports part way
+    /// through.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountMap;
+    /// use solana_accountgen::fees::FeeEstimator;
+    /// use solana_accountgen::fixture::InstructionSet;
+    /// use solana_accountgen::instructions::memo;
+    /// use solana_pubkey::Pubkey;
+    /// use solana_rent::Rent;
+    ///
+    /// let payer = Pubkey::new_unique();
+    /// let instructions: InstructionSet = (0..3).map(|i| memo(&i.to_string(), &[])).collect();
+    ///
+    /// let mut accounts = AccountMap::new();
+    /// let balance = instructions.fund_payer(&mut accounts, &payer, &FeeEstimator::default());
+    /// assert_eq!(balance, Rent::default().minimum_balance(0) + 3 * 5_000);
+    /// ```
+    pub fn fund_payer(
+        &self,
+        account_map: &mut AccountMap,
+        payer: &Pubkey,
+        estimator: &FeeEstimator,
+    ) -> u64 {
+        let fees = self.instructions.iter().fold(0u64, |total, instruction| {
+            // The payer signs every transaction, plus each other signer once
+            let signers: BTreeSet<&Pubkey> = instruction
+                .accounts
+                .iter()
+                .filter(|meta| meta.is_signer && meta.pubkey != *payer)
+                .map(|meta| &meta.pubkey)
+                .collect();
+            let fee = estimator
+                .estimate_instructions(std::slice::from_ref(instruction), 1 + signers.len() as u64);
+            total.saturating_add(fee)
+        });
+        fees::fund(account_map, payer, fees)
+    }
+
+    /// Replays the instructions against a `BanksClient`, one transaction
+    /// per instruction, stopping at the first failure.
+    ///
+    /// `payer` pays for every transaction. Each transaction is additionally
+    /// signed by whichever of `signers` its instruction requires. Use
+    /// [`InstructionSet::fund_payer`] to give the payer enough for the fees.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use solana_accountgen::fixture::load_scenario;
+    /// use solana_accountgen::extensions::program_test::ProgramTestExt;
+    /// use solana_program_test::ProgramTest;
+    ///
+    /// # async fn run() {
+    /// let (accounts, instructions) = load_scenario("tests/fixtures/transfer.json").unwrap();
+    ///
+    /// let mut program_test = ProgramTest::default();
+    /// program_test.add_account_map(accounts);
+    /// let (mut banks_client, payer, _) = program_test.start().await;
+    ///
+    /// instructions.replay(&mut banks_client, &payer, &[]).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn replay(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        for instruction in &self.instructions {
+            let mut keypairs = vec![payer];
+            keypairs.extend(signers.iter().copied().filter(|signer| {
+                signer.pubkey() != payer.pubkey()
+                    && instruction
+                        .accounts
+                        .iter()
+                        .any(|meta| meta.is_signer && meta.pubkey == signer.pubkey())
+            }));
+
+            let blockhash = banks_client.get_latest_blockhash().await?;
+            let transaction = Transaction::new_signed_with_payer(
+                std::slice::from_ref(instruction),
+                Some(&payer.pubkey()),
+                &keypairs,
+                blockhash,
+            );
+            banks_client.process_transaction(transaction).await?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<Instruction>> for InstructionSet {
+    fn from(instructions: Vec<Instruction>) -> Self {
+        Self { instructions }
+    }
+}
+
+impl From<InstructionSet> for Vec<Instruction> {
+    fn from(set: InstructionSet) -> Self {
+        set.instructions
+    }
+}
+
+impl From<Vec<FixtureInstruction>> for InstructionSet {
+    fn from(records: Vec<FixtureInstruction>) -> Self {
+        records.into_iter().map(Instruction::from).collect()
+    }
+}
+
+impl From<InstructionSet> for Vec<FixtureInstruction> {
+    fn from(set: InstructionSet) -> Self {
+        set.instructions
+            .into_iter()
+            .map(FixtureInstruction::from)
+            .collect()
+    }
+}
+
+impl IntoIterator for InstructionSet {
+    type Item = Instruction;
+    type IntoIter = std::vec::IntoIter<Instruction>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.instructions.into_iter()
+    }
+}
+
+impl FromIterator<Instruction> for InstructionSet {
+    fn from_iter<I: IntoIterator<Item = Instruction>>(iter: I) -> Self {
+        Self {
+            instructions: iter.into_iter().collect(),
+        }
+    }
+}
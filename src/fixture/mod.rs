@@ -0,0 +1,157 @@
+//! Fixture file support.
+//!
+//! Fixture files store accounts as JSON Lines: one self-contained account
+//! record per line. The `data` field uses the same `[<payload>, <encoding>]`
+//! pair as a JSON RPC `getAccountInfo` response, so records can be copied
+//! between RPC dumps and fixture files without re-encoding. Data may be
+//! `base64`, `base58` or `hex`, and with the `zstd` feature also
+//! `base64+zstd`, which [`FixtureWriter::with_compression`] produces for
+//! large accounts.
+//!
+//! ```text
+//! {"pubkey":"...","lamports":1000,"owner":"1111...","executable":false,"rentEpoch":0,"data":["AQID","base64"]}
+//! ```
+//!
+//! Accounts cloned from a cluster can carry a `source` recording the
+//! cluster, the slot and the address they were fetched from, which
+//! `rpc::drift::check_drift` uses to find fixtures that no longer match the
+//! cluster.
+//!
+//! ```text
+//! {"pubkey":"...",...,"data":["AQID","base64"],"source":{"cluster":"https://...","slot":250000000,"pubkey":"..."}}
+//! ```
+//!
+//! Instructions can be stored in the same file as accounts, one
+//! [`FixtureInstruction`] per line, so a complete scenario lives in one
+//! place. Readers skip the records they are not asked for.
+//!
+//! ```text
+//! {"programId":"...","accounts":[{"pubkey":"...","isSigner":true,"isWritable":true}],"data":["AQID","base64"]}
+//! ```
+//!
+//! Because every line stands on its own, [`FixtureReader`] and
+//! [`FixtureWriter`] can process fixture sets of any size without loading
+//! them into an [`AccountMap`]. Use [`load_account_map`] and
+//! [`save_account_map`] when the whole set fits comfortably in memory.
+//! With the `sqlite` feature, `SqliteFixture` stores very large sets in a
+//! SQLite database indexed by owner and data size, and loads accounts on
+//! demand.
+//!
+//! Addresses in a fixture can also be written as an [`AccountRef`]: a
+//! content hash of another account, a named program, or a PDA derived from
+//! seeds at load time. Load such manifests with a [`RefResolver`], which
+//! binds program names to the IDs they have in the current environment.
+//!
+//! [`TempFixtureDir`] writes fixtures to a per-test temporary directory for
+//! tools that read files, such as `solana-test-validator`, and removes it
+//! afterwards.
+
+mod instruction_set;
+pub(crate) mod record;
+mod reference;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stream;
+mod temp_dir;
+
+pub use instruction_set::InstructionSet;
+pub use record::{FixtureAccount, FixtureAccountMeta, FixtureInstruction, FixtureSource};
+pub use reference::{AccountRef, PdaRef, RefResolver, Seed, content_hash};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteFixture;
+pub use stream::{
+    FixtureAccounts, FixtureInstructions, FixtureReader, FixtureRecords, FixtureWriter,
+};
+pub use temp_dir::TempFixtureDir;
+
+use crate::{AccountGenError, AccountMap};
+use std::path::Path;
+
+/// Loads every account in a fixture file into an `AccountMap`.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::fixture::load_account_map;
+///
+/// let accounts = load_account_map("tests/fixtures/accounts.json").unwrap();
+/// println!("loaded {} accounts", accounts.len());
+/// ```
+pub fn load_account_map<P: AsRef<Path>>(path: P) -> Result<AccountMap, AccountGenError> {
+    FixtureReader::open(path)?.accounts().collect()
+}
+
+/// Writes every account in an `AccountMap` to a fixture file.
+///
+/// Accounts are written in pubkey order so that the same map always
+/// produces the same file. An existing file at `path` is truncated.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::{AccountBuilder, AccountMap, fixture::save_account_map};
+/// use solana_pubkey::Pubkey;
+///
+/// let mut accounts = AccountMap::new();
+/// accounts
+///     .add_with_builder(Pubkey::new_unique(), AccountBuilder::new().balance(1_000))
+///     .unwrap();
+///
+/// save_account_map("tests/fixtures/accounts.json", &accounts).unwrap();
+/// ```
+pub fn save_account_map<P: AsRef<Path>>(
+    path: P,
+    account_map: &AccountMap,
+) -> Result<(), AccountGenError> {
+    let mut entries: Vec<_> = account_map.iter().collect();
+    entries.sort_by_key(|(pubkey, _)| **pubkey);
+
+    let mut writer = FixtureWriter::create(path)?;
+    for (pubkey, account) in entries {
+        writer.append(pubkey, account)?;
+    }
+    writer.flush()
+}
+
+/// Loads the accounts and instructions of a scenario fixture file.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::fixture::load_scenario;
+///
+/// let (accounts, instructions) = load_scenario("tests/fixtures/transfer.json").unwrap();
+/// println!("{} accounts, {} instructions", accounts.len(), instructions.len());
+/// ```
+pub fn load_scenario<P: AsRef<Path>>(
+    path: P,
+) -> Result<(AccountMap, InstructionSet), AccountGenError> {
+    let path = path.as_ref();
+    let accounts = load_account_map(path)?;
+    let instructions = FixtureReader::open(path)?
+        .instructions()
+        .collect::<Result<_, _>>()?;
+    Ok((accounts, instructions))
+}
+
+/// Writes accounts followed by instructions to a scenario fixture file.
+///
+/// Accounts are written in pubkey order and instructions in their original
+/// order. An existing file at `path` is truncated.
+pub fn save_scenario<P: AsRef<Path>>(
+    path: P,
+    account_map: &AccountMap,
+    instructions: &InstructionSet,
+) -> Result<(), AccountGenError> {
+    let mut entries: Vec<_> = account_map.iter().collect();
+    entries.sort_by_key(|(pubkey, _)| **pubkey);
+
+    let mut writer = FixtureWriter::create(path)?;
+    for (pubkey, account) in entries {
+        writer.append(pubkey, account)?;
+    }
+    for instruction in instructions.iter() {
+        writer.append_instruction(instruction)?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use solana_account::Account;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+/// A single account record as stored in a fixture file.
+///
+/// Records are built with [`FixtureAccount::new`] rather than a struct
+/// literal, so fields such as [`source`](FixtureAccount::source) can be
+/// added without breaking callers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct FixtureAccount {
+    /// The account address.
+    #[serde(with = "pubkey_string")]
+    pub pubkey: Pubkey,
+    /// The account balance in lamports.
+    pub lamports: u64,
+    /// The program that owns the account.
+    #[serde(with = "pubkey_string")]
+    pub owner: Pubkey,
+    /// Whether the account holds a loaded program.
+    pub executable: bool,
+    /// The epoch at which the account will next owe rent.
+    pub rent_epoch: u64,
+    /// The raw account data.
+    #[serde(with = "encoded_data")]
+    pub data: Vec<u8>,
+    /// Where the account was cloned from, if it came from a cluster.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<FixtureSource>,
+}
+
+/// Where a cloned account came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureSource {
+    /// The RPC URL of the cluster.
+    pub cluster: String,
+    /// The slot the cluster reported when the account was fetched.
+    pub slot: u64,
+    /// The account's address on the cluster, which differs from the
+    /// record's pubkey if the fixture was anonymized.
+    #[serde(with = "pubkey_string")]
+    pub pubkey: Pubkey,
+}
+
+impl FixtureAccount {
+    /// Creates a record from a pubkey and account.
+    pub fn new(pubkey: Pubkey, account: Account) -> Self {
+        Self {
+            pubkey,
+            lamports: account.lamports,
+            owner: account.owner,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            data: account.data,
+            source: None,
+        }
+    }
+
+    /// Records where the account was cloned from.
+    pub fn with_source(mut self, source: FixtureSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Splits the record into its pubkey and account.
+    pub fn into_parts(self) -> (Pubkey, Account) {
+        (
+            self.pubkey,
+            Account {
+                lamports: self.lamports,
+                data: self.data,
+                owner: self.owner,
+                executable: self.executable,
+                rent_epoch: self.rent_epoch,
+            },
+        )
+    }
+}
+
+/// A single instruction record as stored in a fixture file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureInstruction {
+    /// The program that processes the instruction.
+    #[serde(with = "pubkey_string")]
+    pub program_id: Pubkey,
+    /// The accounts passed to the program, in order.
+    pub accounts: Vec<FixtureAccountMeta>,
+    /// The instruction data.
+    #[serde(with = "encoded_data")]
+    pub data: Vec<u8>,
+}
+
+/// An account reference within a [`FixtureInstruction`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureAccountMeta {
+    /// The account address.
+    #[serde(with = "pubkey_string")]
+    pub pubkey: Pubkey,
+    /// Whether the transaction must be signed by this account.
+    pub is_signer: bool,
+    /// Whether the instruction may modify this account.
+    pub is_writable: bool,
+}
+
+impl From<Instruction> for FixtureInstruction {
+    fn from(instruction: Instruction) -> Self {
+        Self {
+            program_id: instruction.program_id,
+            accounts: instruction
+                .accounts
+                .into_iter()
+                .map(|meta| FixtureAccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: instruction.data,
+        }
+    }
+}
+
+impl From<FixtureInstruction> for Instruction {
+    fn from(record: FixtureInstruction) -> Self {
+        Instruction {
+            program_id: record.program_id,
+            accounts: record
+                .accounts
+                .into_iter()
+                .map(|meta| AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: record.data,
+        }
+    }
+}
+
+pub(crate) mod pubkey_string {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use solana_pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(pubkey)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Pubkey::from_str(&s).map_err(|e| D::Error::custom(format!("invalid pubkey {s}: {e}")))
+    }
+}
+
+pub(crate) mod encoded_data {
+    use crate::AccountGenError;
+    use crate::serialization::DataEncoding;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        (base64::encode(data), "base64").serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let (payload, encoding) = <(String, String)>::deserialize(deserializer)?;
+        if encoding == ZSTD_ENCODING {
+            let compressed = DataEncoding::Base64
+                .decode(&payload)
+                .map_err(D::Error::custom)?;
+            return decompress(&compressed).map_err(D::Error::custom);
+        }
+        let encoding: DataEncoding = encoding.parse().map_err(D::Error::custom)?;
+        encoding.decode(&payload).map_err(D::Error::custom)
+    }
+
+    /// The encoding label of zstd-compressed data, as used by the JSON RPC.
+    pub const ZSTD_ENCODING: &str = "base64+zstd";
+
+    /// Compresses data for a `base64+zstd` payload.
+    #[cfg(feature = "zstd")]
+    pub fn compress(data: &[u8]) -> Result<String, AccountGenError> {
+        let compressed = zstd::encode_all(data, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+        Ok(base64::encode(compressed))
+    }
+
+    #[cfg(feature = "zstd")]
+    fn decompress(compressed: &[u8]) -> Result<Vec<u8>, AccountGenError> {
+        use crate::cluster::MAX_PERMITTED_DATA_LENGTH;
+        use std::io::Read;
+
+        // Stop one byte past the largest possible account so a corrupt or
+        // hostile payload cannot expand without bound
+        let decoder = zstd::stream::read::Decoder::new(compressed)?;
+        let mut data = Vec::new();
+        decoder
+            .take(MAX_PERMITTED_DATA_LENGTH as u64 + 1)
+            .read_to_end(&mut data)?;
+        crate::cluster::check_data_len(data.len())?;
+        Ok(data)
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    fn decompress(_compressed: &[u8]) -> Result<Vec<u8>, AccountGenError> {
+        Err(AccountGenError::InvalidDataFormat(
+            "base64+zstd data requires the `zstd` feature".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,384 @@
+use super::record::pubkey_string;
+use super::{FixtureAccount, FixtureInstruction, InstructionSet};
+use crate::{AccountGenError, AccountMap};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use solana_account::Account;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Domain separator so content hashes do not collide with other digests.
+const CONTENT_DOMAIN: &[u8] = b"solana-accountgen:content:";
+
+/// Returns the content hash of an account.
+///
+/// The hash covers the balance, the executable flag and the data. The owner
+/// and rent epoch are left out, so an account keeps its hash when the
+/// program that owns it is deployed under a different ID.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountBuilder;
+/// use solana_accountgen::fixture::content_hash;
+/// use solana_pubkey::Pubkey;
+///
+/// let a = AccountBuilder::new().balance(1_000).owner(Pubkey::new_unique()).build();
+/// let b = AccountBuilder::new().balance(1_000).owner(Pubkey::new_unique()).build();
+/// assert_eq!(content_hash(&a), content_hash(&b));
+/// ```
+pub fn content_hash(account: &Account) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(CONTENT_DOMAIN)
+        .chain_update(account.lamports.to_le_bytes())
+        .chain_update([account.executable as u8])
+        .chain_update(&account.data)
+        .finalize()
+        .into()
+}
+
+/// A reference to an account that is resolved when a fixture is loaded.
+///
+/// Wherever a fixture record holds an address (an account's `pubkey` and
+/// `owner`, an instruction's `programId` and account metas), it may hold
+/// one of these instead of a literal pubkey:
+///
+/// ```text
+/// "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"
+/// {"contentHash":"5f2b...e1"}
+/// {"program":"my_program"}
+/// {"pda":{"program":"my_program","seeds":[{"utf8":"vault"},{"pubkey":{"contentHash":"5f2b...e1"}}]}}
+/// ```
+///
+/// Program names are looked up in the bindings of the [`RefResolver`] and
+/// fall back to being parsed as a pubkey, so one manifest can be loaded
+/// against a program deployed at a different ID in each environment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AccountRef {
+    /// A literal address.
+    Address(#[serde(with = "pubkey_string")] Pubkey),
+    /// The single account in the fixture whose [`content_hash`] matches.
+    ContentHash {
+        /// The content hash.
+        #[serde(rename = "contentHash", with = "hex_hash")]
+        content_hash: [u8; 32],
+    },
+    /// A program, by bound name or address.
+    Program {
+        /// The program name or address.
+        program: String,
+    },
+    /// A program derived address.
+    Pda {
+        /// The derivation.
+        pda: PdaRef,
+    },
+}
+
+/// The derivation of a program derived address.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PdaRef {
+    /// The program name or address.
+    pub program: String,
+    /// The seeds, in order.
+    pub seeds: Vec<Seed>,
+}
+
+/// A single PDA seed.
+///
+/// Integers are encoded little-endian, as `to_le_bytes` would in the
+/// program.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Seed {
+    /// The UTF-8 bytes of a string.
+    Utf8(String),
+    /// Raw bytes, hex-encoded in the fixture.
+    Hex(#[serde(with = "hex_bytes")] Vec<u8>),
+    /// The address of another referenced account.
+    Pubkey(AccountRef),
+    /// A single byte.
+    U8(u8),
+    /// A little-endian `u16`.
+    U16(u16),
+    /// A little-endian `u32`.
+    U32(u32),
+    /// A little-endian `u64`.
+    U64(u64),
+}
+
+impl AccountRef {
+    /// Creates a reference to the account whose contents match `account`.
+    pub fn content_of(account: &Account) -> Self {
+        AccountRef::ContentHash {
+            content_hash: content_hash(account),
+        }
+    }
+
+    /// Creates a reference to a program by name or address.
+    pub fn program(program: impl Into<String>) -> Self {
+        AccountRef::Program {
+            program: program.into(),
+        }
+    }
+
+    /// Creates a reference to a program derived address.
+    pub fn pda(program: impl Into<String>, seeds: Vec<Seed>) -> Self {
+        AccountRef::Pda {
+            pda: PdaRef {
+                program: program.into(),
+                seeds,
+            },
+        }
+    }
+}
+
+impl From<Pubkey> for AccountRef {
+    fn from(pubkey: Pubkey) -> Self {
+        AccountRef::Address(pubkey)
+    }
+}
+
+/// Resolves [`AccountRef`]s and loads fixtures that contain them.
+///
+/// Account records are resolved in file order, so a content hash in an
+/// account record can only point at an account earlier in the file.
+/// Instructions are resolved once every account is loaded.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::fixture::{AccountRef, RefResolver, Seed};
+/// use solana_accountgen::AccountMap;
+/// use solana_pubkey::Pubkey;
+///
+/// let program_id = Pubkey::new_unique();
+/// let resolver = RefResolver::new().bind_program("vault_program", program_id);
+///
+/// let vault = AccountRef::pda("vault_program", vec![Seed::Utf8("vault".to_string())]);
+/// let address = resolver.resolve(&vault, &AccountMap::new()).unwrap();
+/// assert_eq!(address, Pubkey::find_program_address(&[b"vault"], &program_id).0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RefResolver {
+    programs: HashMap<String, Pubkey>,
+}
+
+impl RefResolver {
+    /// Creates a resolver with no program bindings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds a program name to the address it has in this environment.
+    ///
+    /// Bindings take precedence over parsing the name as an address, so a
+    /// manifest that names programs by their devnet IDs can be rebound to
+    /// a local deployment.
+    pub fn bind_program(mut self, name: impl Into<String>, program_id: Pubkey) -> Self {
+        self.programs.insert(name.into(), program_id);
+        self
+    }
+
+    /// Resolves a reference against a set of loaded accounts.
+    pub fn resolve(
+        &self,
+        reference: &AccountRef,
+        accounts: &AccountMap,
+    ) -> Result<Pubkey, AccountGenError> {
+        match reference {
+            AccountRef::Address(pubkey) => Ok(*pubkey),
+            AccountRef::ContentHash { content_hash: hash } => {
+                let mut matches = accounts
+                    .iter()
+                    .filter(|(_, account)| content_hash(account) == *hash)
+                    .map(|(pubkey, _)| *pubkey);
+                match (matches.next(), matches.next()) {
+                    (Some(pubkey), None) => Ok(pubkey),
+                    (None, _) => Err(AccountGenError::InvalidDataFormat(format!(
+                        "no account has content hash {}",
+                        hex::encode(hash)
+                    ))),
+                    (Some(_), Some(_)) => Err(AccountGenError::InvalidDataFormat(format!(
+                        "more than one account has content hash {}",
+                        hex::encode(hash)
+                    ))),
+                }
+            }
+            AccountRef::Program { program } => self.program_id(program),
+            AccountRef::Pda { pda } => {
+                let program_id = self.program_id(&pda.program)?;
+                let seeds = pda
+                    .seeds
+                    .iter()
+                    .map(|seed| self.seed_bytes(seed, accounts))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+                Pubkey::try_find_program_address(&seeds, &program_id)
+                    .map(|(pubkey, _)| pubkey)
+                    .ok_or_else(|| {
+                        AccountGenError::InvalidDataFormat(format!(
+                            "seeds do not derive a valid PDA for program {}",
+                            pda.program
+                        ))
+                    })
+            }
+        }
+    }
+
+    /// Loads every account in a fixture file, resolving references.
+    pub fn load_account_map<P: AsRef<Path>>(&self, path: P) -> Result<AccountMap, AccountGenError> {
+        Ok(self.load_scenario(path)?.0)
+    }
+
+    /// Loads the accounts and instructions of a fixture file, resolving
+    /// references.
+    pub fn load_scenario<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(AccountMap, InstructionSet), AccountGenError> {
+        self.read_scenario(File::open(path)?)
+    }
+
+    /// Reads the accounts and instructions of a fixture from any source,
+    /// resolving references.
+    pub fn read_scenario<R: Read>(
+        &self,
+        reader: R,
+    ) -> Result<(AccountMap, InstructionSet), AccountGenError> {
+        let mut accounts = AccountMap::new();
+        let mut pending_instructions = Vec::new();
+
+        for (index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let line_number = index + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut record: Value = serde_json::from_str(&line)
+                .map_err(|e| invalid_line(line_number, e.to_string()))?;
+            if record.get("programId").is_some() {
+                pending_instructions.push((line_number, record));
+                continue;
+            }
+
+            self.resolve_field(&mut record, "pubkey", &accounts)
+                .and_then(|_| self.resolve_field(&mut record, "owner", &accounts))
+                .map_err(|e| invalid_line(line_number, e.to_string()))?;
+            let account: FixtureAccount = serde_json::from_value(record)
+                .map_err(|e| invalid_line(line_number, e.to_string()))?;
+            let (pubkey, account) = account.into_parts();
+            accounts.set_account(pubkey, account);
+        }
+
+        let mut instructions = InstructionSet::new();
+        for (line_number, mut record) in pending_instructions {
+            self.resolve_instruction(&mut record, &accounts)
+                .map_err(|e| invalid_line(line_number, e.to_string()))?;
+            let instruction: FixtureInstruction = serde_json::from_value(record)
+                .map_err(|e| invalid_line(line_number, e.to_string()))?;
+            instructions.push(Instruction::from(instruction));
+        }
+
+        Ok((accounts, instructions))
+    }
+
+    fn resolve_instruction(
+        &self,
+        record: &mut Value,
+        accounts: &AccountMap,
+    ) -> Result<(), AccountGenError> {
+        self.resolve_field(record, "programId", accounts)?;
+        if let Some(metas) = record.get_mut("accounts").and_then(Value::as_array_mut) {
+            for meta in metas {
+                self.resolve_field(meta, "pubkey", accounts)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces a reference in `record[field]` with the address it resolves
+    /// to. Literal addresses are left for the record parser to check.
+    fn resolve_field(
+        &self,
+        record: &mut Value,
+        field: &str,
+        accounts: &AccountMap,
+    ) -> Result<(), AccountGenError> {
+        let Some(value) = record.get_mut(field) else {
+            return Ok(());
+        };
+        if value.is_string() {
+            return Ok(());
+        }
+        let reference = AccountRef::deserialize(&*value).map_err(|e| {
+            AccountGenError::InvalidDataFormat(format!("invalid reference in {field}: {e}"))
+        })?;
+        *value = Value::String(self.resolve(&reference, accounts)?.to_string());
+        Ok(())
+    }
+
+    fn program_id(&self, program: &str) -> Result<Pubkey, AccountGenError> {
+        if let Some(program_id) = self.programs.get(program) {
+            return Ok(*program_id);
+        }
+        Pubkey::from_str(program).map_err(|_| {
+            AccountGenError::InvalidDataFormat(format!(
+                "unknown program {program}: bind it with RefResolver::bind_program"
+            ))
+        })
+    }
+
+    fn seed_bytes(&self, seed: &Seed, accounts: &AccountMap) -> Result<Vec<u8>, AccountGenError> {
+        Ok(match seed {
+            Seed::Utf8(s) => s.as_bytes().to_vec(),
+            Seed::Hex(bytes) => bytes.clone(),
+            Seed::Pubkey(reference) => self.resolve(reference, accounts)?.to_bytes().to_vec(),
+            Seed::U8(n) => vec![*n],
+            Seed::U16(n) => n.to_le_bytes().to_vec(),
+            Seed::U32(n) => n.to_le_bytes().to_vec(),
+            Seed::U64(n) => n.to_le_bytes().to_vec(),
+        })
+    }
+}
+
+fn invalid_line(line_number: usize, message: String) -> AccountGenError {
+    AccountGenError::InvalidDataFormat(format!("fixture line {}: {}", line_number, message))
+}
+
+mod hex_hash {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(hash: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(hash))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 32], D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let mut hash = [0u8; 32];
+        hex::decode_to_slice(&s, &mut hash)
+            .map_err(|e| D::Error::custom(format!("invalid content hash {s}: {e}")))?;
+        Ok(hash)
+    }
+}
+
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(&s).map_err(|e| D::Error::custom(format!("invalid hex seed {s}: {e}")))
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{AccountGenError, AccountMap};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS accounts (
+        pubkey BLOB PRIMARY KEY NOT NULL,
+        lamports INTEGER NOT NULL,
+        owner BLOB NOT NULL,
+        executable INTEGER NOT NULL,
+        rent_epoch INTEGER NOT NULL,
+        data BLOB NOT NULL,
+        data_len INTEGER NOT NULL
+    ) WITHOUT ROWID;
+    CREATE INDEX IF NOT EXISTS accounts_owner ON accounts (owner);
+    CREATE INDEX IF NOT EXISTS accounts_data_len ON accounts (data_len);
+";
+
+const COLUMNS: &str = "pubkey, lamports, owner, executable, rent_epoch, data";
+
+/// A fixture set stored in a SQLite database.
+///
+/// JSON fixture files have to be read from the start to find an account,
+/// and an [`AccountMap`] holds every account in memory. Corpora of millions
+/// of accounts, such as those used to test indexers, outgrow both. A
+/// `SqliteFixture` keeps accounts on disk, indexed by pubkey, owner and data
+/// size, and loads only the accounts a test asks for.
+///
+/// Requires the `sqlite` feature.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, AccountMap};
+/// use solana_accountgen::fixture::SqliteFixture;
+/// use solana_pubkey::Pubkey;
+///
+/// let program_id = Pubkey::new_unique();
+/// let mut accounts = AccountMap::new();
+/// for _ in 0..3 {
+///     let builder = AccountBuilder::new().owner(program_id).data_raw(vec![0; 64]);
+///     accounts.add_with_builder(Pubkey::new_unique(), builder).unwrap();
+/// }
+///
+/// let mut fixture = SqliteFixture::open_in_memory().unwrap();
+/// fixture.insert_accounts(accounts.iter()).unwrap();
+///
+/// assert_eq!(fixture.len().unwrap(), 3);
+/// assert_eq!(fixture.accounts_by_owner(&program_id).unwrap().len(), 3);
+/// assert!(fixture.accounts_by_data_len(..64).unwrap().is_empty());
+/// ```
+#[derive(Debug)]
+pub struct SqliteFixture {
+    connection: Connection,
+}
+
+impl SqliteFixture {
+    /// Opens the database at `path`, creating it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, AccountGenError> {
+        Self::with_connection(Connection::open(path).map_err(db_error)?)
+    }
+
+    /// Opens a database that lives in memory until it is dropped.
+    pub fn open_in_memory() -> Result<Self, AccountGenError> {
+        Self::with_connection(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, AccountGenError> {
+        connection.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(Self { connection })
+    }
+
+    /// Stores an account, replacing any account at the same pubkey.
+    pub fn insert(&mut self, pubkey: &Pubkey, account: &Account) -> Result<(), AccountGenError> {
+        self.insert_accounts([(pubkey, account)]).map(|_| ())
+    }
+
+    /// Stores accounts in a single transaction, replacing any accounts at
+    /// the same pubkeys. Returns the number of accounts stored.
+    ///
+    /// Inserting in large batches is much faster than calling
+    /// [`insert`](Self::insert) for each account.
+    pub fn insert_accounts<'a, I>(&mut self, accounts: I) -> Result<usize, AccountGenError>
+    where
+        I: IntoIterator<Item = (&'a Pubkey, &'a Account)>,
+    {
+        let transaction = self.connection.transaction().map_err(db_error)?;
+        let mut count = 0;
+        {
+            let mut statement = transaction
+                .prepare_cached(&format!(
+                    "INSERT OR REPLACE INTO accounts ({COLUMNS}, data_len) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
+                ))
+                .map_err(db_error)?;
+            for (pubkey, account) in accounts {
+                statement
+                    .execute(params![
+                        pubkey.as_ref(),
+                        // SQLite integers are signed; the bits round-trip
+                        account.lamports as i64,
+                        account.owner.as_ref(),
+                        account.executable,
+                        account.rent_epoch as i64,
+                        account.data,
+                        account.data.len() as i64,
+                    ])
+                    .map_err(db_error)?;
+                count += 1;
+            }
+        }
+        transaction.commit().map_err(db_error)?;
+        Ok(count)
+    }
+
+    /// Loads the account at `pubkey`.
+    pub fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>, AccountGenError> {
+        self.connection
+            .prepare_cached(&format!("SELECT {COLUMNS} FROM accounts WHERE pubkey = ?1"))
+            .map_err(db_error)?
+            .query_row([pubkey.as_ref()], read_account)
+            .optional()
+            .map_err(db_error)
+            .map(|row| row.map(|(_, account)| account))
+    }
+
+    /// Removes the account at `pubkey`, returning true if it existed.
+    pub fn remove_account(&mut self, pubkey: &Pubkey) -> Result<bool, AccountGenError> {
+        self.connection
+            .execute("DELETE FROM accounts WHERE pubkey = ?1", [pubkey.as_ref()])
+            .map(|removed| removed > 0)
+            .map_err(db_error)
+    }
+
+    /// Returns the number of stored accounts.
+    pub fn len(&self) -> Result<u64, AccountGenError> {
+        self.connection
+            .query_row("SELECT COUNT(*) FROM accounts", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|count| count as u64)
+            .map_err(db_error)
+    }
+
+    /// Returns true if no accounts are stored.
+    pub fn is_empty(&self) -> Result<bool, AccountGenError> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Returns the pubkeys of the accounts owned by `owner`, in order,
+    /// without loading their data.
+    pub fn pubkeys_by_owner(&self, owner: &Pubkey) -> Result<Vec<Pubkey>, AccountGenError> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT pubkey FROM accounts WHERE owner = ?1 ORDER BY pubkey")
+            .map_err(db_error)?;
+        let rows = statement
+            .query_map([owner.as_ref()], |row| read_pubkey(row, 0))
+            .map_err(db_error)?;
+        rows.collect::<Result<_, _>>().map_err(db_error)
+    }
+
+    /// Loads the accounts owned by `owner`.
+    pub fn accounts_by_owner(&self, owner: &Pubkey) -> Result<AccountMap, AccountGenError> {
+        self.query(
+            &format!("SELECT {COLUMNS} FROM accounts WHERE owner = ?1"),
+            [owner.as_ref()],
+        )
+    }
+
+    /// Loads the accounts whose data length falls in `range`.
+    pub fn accounts_by_data_len(
+        &self,
+        range: impl RangeBounds<usize>,
+    ) -> Result<AccountMap, AccountGenError> {
+        let min = match range.start_bound() {
+            Bound::Included(&start) => start as i64,
+            Bound::Excluded(&start) => start as i64 + 1,
+            Bound::Unbounded => 0,
+        };
+        let max = match range.end_bound() {
+            Bound::Included(&end) => end as i64,
+            Bound::Excluded(&end) => end as i64 - 1,
+            Bound::Unbounded => i64::MAX,
+        };
+        self.query(
+            &format!("SELECT {COLUMNS} FROM accounts WHERE data_len BETWEEN ?1 AND ?2"),
+            [min, max],
+        )
+    }
+
+    /// Loads every stored account into an `AccountMap`.
+    pub fn load_account_map(&self) -> Result<AccountMap, AccountGenError> {
+        self.query(&format!("SELECT {COLUMNS} FROM accounts"), [])
+    }
+
+    /// Calls `f` with every stored account in pubkey order, loading one
+    /// account at a time.
+    ///
+    /// Stops at the first error returned by `f`.
+    pub fn for_each_account<F>(&self, mut f: F) -> Result<(), AccountGenError>
+    where
+        F: FnMut(Pubkey, Account) -> Result<(), AccountGenError>,
+    {
+        let mut statement = self
+            .connection
+            .prepare(&format!("SELECT {COLUMNS} FROM accounts ORDER BY pubkey"))
+            .map_err(db_error)?;
+        let rows = statement.query_map([], read_account).map_err(db_error)?;
+        for row in rows {
+            let (pubkey, account) = row.map_err(db_error)?;
+            f(pubkey, account)?;
+        }
+        Ok(())
+    }
+
+    fn query<P: rusqlite::Params>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> Result<AccountMap, AccountGenError> {
+        let mut statement = self.connection.prepare_cached(sql).map_err(db_error)?;
+        let rows = statement
+            .query_map(params, read_account)
+            .map_err(db_error)?;
+        rows.collect::<Result<_, _>>().map_err(db_error)
+    }
+}
+
+fn read_pubkey(row: &Row<'_>, index: usize) -> rusqlite::Result<Pubkey> {
+    let bytes: Vec<u8> = row.get(index)?;
+    <[u8; 32]>::try_from(bytes)
+        .map(Pubkey::new_from_array)
+        .map_err(|bytes| {
+            rusqlite::Error::FromSqlConversionFailure(
+                index,
+                rusqlite::types::Type::Blob,
+                format!("expected a 32-byte pubkey, got {} bytes", bytes.len()).into(),
+            )
+        })
+}
+
+fn read_account(row: &Row<'_>) -> rusqlite::Result<(Pubkey, Account)> {
+    let account = Account {
+        lamports: row.get::<_, i64>(1)? as u64,
+        owner: read_pubkey(row, 2)?,
+        executable: row.get(3)?,
+        rent_epoch: row.get::<_, i64>(4)? as u64,
+        data: row.get(5)?,
+    };
+    Ok((read_pubkey(row, 0)?, account))
+}
+
+fn db_error(e: rusqlite::Error) -> AccountGenError {
+    AccountGenError::DatabaseError(e.to_string())
+}
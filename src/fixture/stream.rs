@@ -0,0 +1,292 @@
+use super::{FixtureAccount, FixtureInstruction};
+use crate::AccountGenError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use solana_account::Account;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Read, Write};
+use std::path::Path;
+
+/// Reads accounts from a fixture file one record at a time.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::fixture::FixtureReader;
+///
+/// let mut total_lamports = 0;
+/// for entry in FixtureReader::open("snapshot.json").unwrap().accounts() {
+///     let (_pubkey, account) = entry.unwrap();
+///     total_lamports += account.lamports;
+/// }
+/// ```
+pub struct FixtureReader<R> {
+    reader: BufReader<R>,
+}
+
+impl FixtureReader<File> {
+    /// Opens a fixture file for reading.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, AccountGenError> {
+        Ok(Self::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> FixtureReader<R> {
+    /// Creates a reader over any source of fixture lines.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+        }
+    }
+
+    /// Returns an iterator over the (pubkey, account) pairs in the fixture.
+    ///
+    /// Blank lines and instruction records are skipped. A malformed line
+    /// yields an error that names its line number; iteration can continue
+    /// past it.
+    pub fn accounts(self) -> FixtureAccounts<R> {
+        FixtureAccounts {
+            lines: self.reader.lines(),
+            line_number: 0,
+        }
+    }
+
+    /// Returns an iterator over the account records in the fixture, which
+    /// unlike [`accounts`](Self::accounts) keep the record's
+    /// [`source`](FixtureAccount::source).
+    ///
+    /// Blank lines and instruction records are skipped.
+    pub fn records(self) -> FixtureRecords<R> {
+        FixtureRecords {
+            lines: self.reader.lines(),
+            line_number: 0,
+        }
+    }
+
+    /// Returns an iterator over the instructions in the fixture.
+    ///
+    /// Blank lines and account records are skipped.
+    pub fn instructions(self) -> FixtureInstructions<R> {
+        FixtureInstructions {
+            lines: self.reader.lines(),
+            line_number: 0,
+        }
+    }
+}
+
+/// Iterator returned by [`FixtureReader::accounts`].
+pub struct FixtureAccounts<R> {
+    lines: Lines<BufReader<R>>,
+    line_number: usize,
+}
+
+impl<R: Read> Iterator for FixtureAccounts<R> {
+    type Item = Result<(Pubkey, Account), AccountGenError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_record::<_, FixtureAccount, FixtureInstruction>(&mut self.lines, &mut self.line_number)
+            .map(|record| record.map(FixtureAccount::into_parts))
+    }
+}
+
+/// Iterator returned by [`FixtureReader::records`].
+pub struct FixtureRecords<R> {
+    lines: Lines<BufReader<R>>,
+    line_number: usize,
+}
+
+impl<R: Read> Iterator for FixtureRecords<R> {
+    type Item = Result<FixtureAccount, AccountGenError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_record::<_, FixtureAccount, FixtureInstruction>(&mut self.lines, &mut self.line_number)
+    }
+}
+
+/// Iterator returned by [`FixtureReader::instructions`].
+pub struct FixtureInstructions<R> {
+    lines: Lines<BufReader<R>>,
+    line_number: usize,
+}
+
+impl<R: Read> Iterator for FixtureInstructions<R> {
+    type Item = Result<Instruction, AccountGenError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_record::<_, FixtureInstruction, FixtureAccount>(&mut self.lines, &mut self.line_number)
+            .map(|record| record.map(Instruction::from))
+    }
+}
+
+/// Reads lines until one parses as a `T`, skipping blank lines and lines
+/// that parse as the other record kind `U`.
+fn next_record<R: Read, T: DeserializeOwned, U: DeserializeOwned>(
+    lines: &mut Lines<BufReader<R>>,
+    line_number: &mut usize,
+) -> Option<Result<T, AccountGenError>> {
+    loop {
+        let line = match lines.next()? {
+            Ok(line) => line,
+            Err(e) => return Some(Err(AccountGenError::IoError(e))),
+        };
+        *line_number += 1;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<T>(&line) {
+            Ok(record) => return Some(Ok(record)),
+            Err(_) if serde_json::from_str::<U>(&line).is_ok() => continue,
+            Err(e) => {
+                return Some(Err(AccountGenError::InvalidDataFormat(format!(
+                    "fixture line {}: {}",
+                    line_number, e
+                ))));
+            }
+        }
+    }
+}
+
+/// Writes accounts and instructions to a fixture file one record at a time.
+///
+/// Output is buffered; call [`FixtureWriter::flush`] once all records have
+/// been appended to surface any write errors.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::{AccountBuilder, fixture::FixtureWriter};
+/// use solana_pubkey::Pubkey;
+///
+/// let mut writer = FixtureWriter::create("generated.json").unwrap();
+/// for _ in 0..1_000 {
+///     let account = AccountBuilder::new().balance(1_000_000).build();
+///     writer.append(&Pubkey::new_unique(), &account).unwrap();
+/// }
+/// writer.flush().unwrap();
+/// ```
+pub struct FixtureWriter<W: Write> {
+    writer: BufWriter<W>,
+    #[cfg(feature = "zstd")]
+    compress_min_len: Option<usize>,
+}
+
+impl FixtureWriter<File> {
+    /// Creates a fixture file, truncating it if it already exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, AccountGenError> {
+        Ok(Self::new(File::create(path)?))
+    }
+
+    /// Opens a fixture file for appending, creating it if necessary.
+    pub fn open_append<P: AsRef<Path>>(path: P) -> Result<Self, AccountGenError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(file))
+    }
+}
+
+impl<W: Write> FixtureWriter<W> {
+    /// Creates a writer over any byte sink.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            #[cfg(feature = "zstd")]
+            compress_min_len: None,
+        }
+    }
+
+    /// Compresses the data of accounts with at least `min_len` bytes of data
+    /// using zstd.
+    ///
+    /// Compressed records use the JSON RPC's `base64+zstd` encoding and are
+    /// decompressed transparently by [`FixtureReader`]. Small accounts
+    /// rarely benefit, so a threshold of a few kilobytes keeps the file
+    /// readable where it matters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::AccountBuilder;
+    /// use solana_accountgen::fixture::{FixtureReader, FixtureWriter};
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// let pubkey = Pubkey::new_unique();
+    /// let account = AccountBuilder::new().data_raw(vec![0; 100_000]).build();
+    ///
+    /// let mut writer = FixtureWriter::new(Vec::new()).with_compression(4096);
+    /// writer.append(&pubkey, &account).unwrap();
+    /// let bytes = writer.into_inner().unwrap();
+    /// assert!(bytes.len() < 1_000);
+    ///
+    /// let (_, loaded) = FixtureReader::new(bytes.as_slice())
+    ///     .accounts()
+    ///     .next()
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(loaded, account);
+    /// ```
+    #[cfg(feature = "zstd")]
+    pub fn with_compression(mut self, min_len: usize) -> Self {
+        self.compress_min_len = Some(min_len);
+        self
+    }
+
+    /// Appends a single account record.
+    pub fn append(&mut self, pubkey: &Pubkey, account: &Account) -> Result<(), AccountGenError> {
+        self.append_record(&FixtureAccount::new(*pubkey, account.clone()))
+    }
+
+    /// Appends a pre-built fixture record.
+    pub fn append_record(&mut self, record: &FixtureAccount) -> Result<(), AccountGenError> {
+        #[cfg(feature = "zstd")]
+        if let Some(min_len) = self.compress_min_len
+            && record.data.len() >= min_len
+        {
+            use super::record::encoded_data;
+
+            let mut value = serde_json::to_value(record).map_err(|e| {
+                AccountGenError::SerializationError(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e,
+                ))
+            })?;
+            value["data"] = serde_json::json!([
+                encoded_data::compress(&record.data)?,
+                encoded_data::ZSTD_ENCODING
+            ]);
+            return self.write_line(&value);
+        }
+        self.write_line(record)
+    }
+
+    /// Appends a single instruction record.
+    pub fn append_instruction(&mut self, instruction: &Instruction) -> Result<(), AccountGenError> {
+        self.write_line(&FixtureInstruction::from(instruction.clone()))
+    }
+
+    fn write_line<T: Serialize>(&mut self, record: &T) -> Result<(), AccountGenError> {
+        serde_json::to_writer(&mut self.writer, record).map_err(|e| {
+            AccountGenError::SerializationError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                e,
+            ))
+        })?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Flushes buffered records to the underlying sink.
+    pub fn flush(&mut self) -> Result<(), AccountGenError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Flushes buffered records and returns the underlying sink.
+    pub fn into_inner(self) -> Result<W, AccountGenError> {
+        self.writer
+            .into_inner()
+            .map_err(|e| AccountGenError::IoError(e.into_error()))
+    }
+}
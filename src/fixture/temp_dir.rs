@@ -0,0 +1,140 @@
+use crate::fixture::save_account_map;
+use crate::serialization::convert::to_validator_json;
+use crate::{AccountGenError, AccountMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Distinguishes the directories one process creates.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A temporary directory holding fixture files, removed when dropped.
+///
+/// Some tools only read accounts from files, `solana-test-validator` among
+/// them. [`TempFixtureDir::write_accounts`] writes one file per account in
+/// the format the validator's `--account-dir` option loads, and
+/// [`TempFixtureDir::write_fixture`] writes a fixture file.
+///
+/// Directory names combine a label, the process ID and a per-process
+/// counter, so tests running in parallel, in one `cargo test` process or
+/// several, never share a directory.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::fixture::TempFixtureDir;
+/// use solana_accountgen::{AccountBuilder, AccountMap};
+/// use solana_pubkey::Pubkey;
+///
+/// let pubkey = Pubkey::new_unique();
+/// let mut accounts = AccountMap::new();
+/// accounts.add_with_builder(pubkey, AccountBuilder::new().balance(1_000)).unwrap();
+///
+/// let dir = TempFixtureDir::with_accounts("validator", &accounts).unwrap();
+/// assert!(dir.path().join(format!("{}.json", pubkey)).exists());
+/// // solana-test-validator --account-dir <dir>
+/// assert_eq!(dir.validator_args()[0], "--account-dir");
+///
+/// let path = dir.path().to_path_buf();
+/// drop(dir);
+/// assert!(!path.exists());
+/// ```
+#[derive(Debug)]
+pub struct TempFixtureDir {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempFixtureDir {
+    /// Creates an empty directory under the system temporary directory.
+    ///
+    /// `label`, typically the test's name, makes the directory easy to find
+    /// when it is kept; characters other than ASCII letters, digits, `-`
+    /// and `_` are replaced with `-`.
+    pub fn new(label: &str) -> Result<Self, AccountGenError> {
+        let label: String = label
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        loop {
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            let path = std::env::temp_dir().join(format!(
+                "accountgen-{}-{}-{}",
+                label,
+                std::process::id(),
+                id
+            ));
+            // A directory left behind by an earlier process with the same ID
+            // is skipped rather than reused
+            match std::fs::create_dir(&path) {
+                Ok(()) => return Ok(Self { path, keep: false }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Creates a directory and writes `accounts` to it with
+    /// [`TempFixtureDir::write_accounts`].
+    pub fn with_accounts(label: &str, accounts: &AccountMap) -> Result<Self, AccountGenError> {
+        let dir = Self::new(label)?;
+        dir.write_accounts(accounts)?;
+        Ok(dir)
+    }
+
+    /// Returns the directory's path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes each account to `<pubkey>.json`, in the JSON format of
+    /// `solana account --output json`, which `solana-test-validator
+    /// --account-dir` loads.
+    pub fn write_accounts(&self, accounts: &AccountMap) -> Result<(), AccountGenError> {
+        for (pubkey, account) in accounts.iter() {
+            let json = to_validator_json(pubkey, account);
+            let path = self.path.join(format!("{}.json", pubkey));
+            std::fs::write(path, json.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Writes `accounts` to a fixture file named `name` in the directory
+    /// and returns its path.
+    pub fn write_fixture(
+        &self,
+        name: &str,
+        accounts: &AccountMap,
+    ) -> Result<PathBuf, AccountGenError> {
+        let path = self.path.join(name);
+        save_account_map(&path, accounts)?;
+        Ok(path)
+    }
+
+    /// Returns the `solana-test-validator` arguments that load the
+    /// accounts written by [`TempFixtureDir::write_accounts`].
+    pub fn validator_args(&self) -> Vec<String> {
+        vec!["--account-dir".to_string(), self.path.display().to_string()]
+    }
+
+    /// Keeps the directory when the value is dropped, for inspecting a
+    /// failing test's fixtures.
+    pub fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.path.clone()
+    }
+}
+
+impl Drop for TempFixtureDir {
+    fn drop(&mut self) {
+        if !self.keep {
+            // Nothing useful can be done about a failure during drop
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Recording cross-program invocations made under [`LocalHarness`].
+//!
+//! Off-chain, `solana_program::program::invoke` and `invoke_signed` are
+//! routed through the `program_stubs` syscall table. The harness installs a
+//! stub that, while an instruction is being processed on the current
+//! thread, records each invocation as a [`RecordedCpi`] and returns success
+//! without executing anything. Calls from other threads fall through to
+//! whatever stubs were installed before, such as those of
+//! `solana-program-test`.
+//!
+//! `solana-program-test` installs its own stubs when it sets up its first
+//! bank, replacing any installed earlier, so the harness checks that its
+//! stubs are still active before every instruction and wraps the current
+//! ones again if not.
+//!
+//! Programs that call `solana_cpi` directly bypass the stubs table; they can
+//! call [`invoke`] and [`invoke_signed`] from this module in test builds.
+//!
+//! [`LocalHarness`]: super::LocalHarness
+
+use solana_account_info::AccountInfo;
+use solana_instruction::Instruction;
+use solana_program_error::ProgramResult;
+use solana_pubkey::{Pubkey, PubkeyError};
+use solana_sysvar::program_stubs::{self, SyscallStubs, set_syscall_stubs};
+use std::cell::RefCell;
+use std::sync::{Mutex, PoisonError};
+
+/// A cross-program invocation attempted by the program under test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedCpi {
+    /// The program that made the invocation.
+    pub caller: Pubkey,
+    /// The instruction passed to `invoke` or `invoke_signed`.
+    pub instruction: Instruction,
+    /// The seeds passed to `invoke_signed`, one set per signing PDA.
+    pub signers_seeds: Vec<Vec<Vec<u8>>>,
+}
+
+impl RecordedCpi {
+    /// Returns the program that was invoked.
+    pub fn program_id(&self) -> Pubkey {
+        self.instruction.program_id
+    }
+
+    /// Returns the PDAs that the caller signed for, derived from the seeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a set of seeds does not produce a valid PDA,
+    /// which would make the invocation fail on-chain.
+    pub fn signer_pdas(&self) -> Result<Vec<Pubkey>, PubkeyError> {
+        self.signers_seeds
+            .iter()
+            .map(|seeds| {
+                let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+                Pubkey::create_program_address(&seeds, &self.caller)
+            })
+            .collect()
+    }
+}
+
+thread_local! {
+    static RECORDING: RefCell<Option<(Pubkey, Vec<RecordedCpi>)>> = const { RefCell::new(None) };
+}
+
+/// Records invocations made on the current thread until dropped.
+pub(crate) struct Recording(());
+
+impl Recording {
+    /// Starts recording invocations made by `caller`.
+    pub(crate) fn start(caller: Pubkey) -> Self {
+        install();
+        RECORDING.with(|recording| *recording.borrow_mut() = Some((caller, Vec::new())));
+        Recording(())
+    }
+
+    /// Stops recording and returns the invocations made.
+    pub(crate) fn finish(self) -> Vec<RecordedCpi> {
+        RECORDING
+            .with(|recording| recording.borrow_mut().take())
+            .map(|(_, cpis)| cpis)
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        RECORDING.with(|recording| recording.borrow_mut().take());
+    }
+}
+
+/// The vote address that asks [`RecordingStubs`] to identify themselves
+/// through `sol_get_epoch_stake`. Other stubs may read it as a pubkey.
+static PROBE: [u8; 32] = *b"solana-accountgen recording stub";
+
+/// What [`RecordingStubs`] answer to [`PROBE`].
+const PROBE_REPLY: u64 = u64::from_le_bytes(*b"accntgen");
+
+/// Installs the recording stubs on top of the current ones, unless they
+/// are already active.
+fn install() {
+    static INSTALL: Mutex<()> = Mutex::new(());
+    let _guard = INSTALL.lock().unwrap_or_else(PoisonError::into_inner);
+    if program_stubs::sol_get_epoch_stake(PROBE.as_ptr()) == PROBE_REPLY {
+        return;
+    }
+    let fallback = set_syscall_stubs(Box::new(RecordingStubs { fallback: None }));
+    set_syscall_stubs(Box::new(RecordingStubs {
+        fallback: Some(fallback),
+    }));
+}
+
+/// Records the invocation if the current thread is recording.
+fn record(instruction: &Instruction, signers_seeds: &[&[&[u8]]]) -> bool {
+    RECORDING.with(|recording| match recording.borrow_mut().as_mut() {
+        Some((caller, cpis)) => {
+            cpis.push(RecordedCpi {
+                caller: *caller,
+                instruction: instruction.clone(),
+                signers_seeds: signers_seeds
+                    .iter()
+                    .map(|seeds| seeds.iter().map(|seed| seed.to_vec()).collect())
+                    .collect(),
+            });
+            true
+        }
+        None => false,
+    })
+}
+
+/// Records a cross-program invocation, or forwards it to the installed
+/// syscall stubs when no harness is running on this thread.
+pub fn invoke(instruction: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    invoke_signed(instruction, account_infos, &[])
+}
+
+/// Records a signed cross-program invocation, or forwards it to the
+/// installed syscall stubs when no harness is running on this thread.
+pub fn invoke_signed(
+    instruction: &Instruction,
+    account_infos: &[AccountInfo],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if record(instruction, signers_seeds) {
+        return Ok(());
+    }
+    program_stubs::sol_invoke_signed(instruction, account_infos, signers_seeds)
+}
+
+/// Syscall stubs that record invocations and forward everything else.
+struct RecordingStubs {
+    fallback: Option<Box<dyn SyscallStubs>>,
+}
+
+impl RecordingStubs {
+    fn fallback(&self) -> &dyn SyscallStubs {
+        self.fallback.as_deref().unwrap_or(&DefaultStubs)
+    }
+}
+
+/// The trait's default behavior, used while the real stubs are swapped in.
+struct DefaultStubs;
+
+impl SyscallStubs for DefaultStubs {}
+
+impl SyscallStubs for RecordingStubs {
+    fn sol_log(&self, message: &str) {
+        self.fallback().sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        self.fallback().sol_log_compute_units()
+    }
+    fn sol_remaining_compute_units(&self) -> u64 {
+        self.fallback().sol_remaining_compute_units()
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if record(instruction, signers_seeds) {
+            return Ok(());
+        }
+        self.fallback()
+            .sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_sysvar(
+        &self,
+        sysvar_id_addr: *const u8,
+        var_addr: *mut u8,
+        offset: u64,
+        length: u64,
+    ) -> u64 {
+        self.fallback()
+            .sol_get_sysvar(sysvar_id_addr, var_addr, offset, length)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.fallback().sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.fallback().sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.fallback().sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.fallback().sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.fallback().sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        self.fallback().sol_get_last_restart_slot(var_addr)
+    }
+    fn sol_get_epoch_stake(&self, vote_address: *const u8) -> u64 {
+        if std::ptr::eq(vote_address, PROBE.as_ptr()) {
+            return PROBE_REPLY;
+        }
+        self.fallback().sol_get_epoch_stake(vote_address)
+    }
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        unsafe { self.fallback().sol_memcpy(dst, src, n) }
+    }
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        unsafe { self.fallback().sol_memmove(dst, src, n) }
+    }
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        unsafe { self.fallback().sol_memcmp(s1, s2, n, result) }
+    }
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        unsafe { self.fallback().sol_memset(s, c, n) }
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.fallback().sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.fallback().sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        self.fallback().sol_log_data(fields)
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.fallback().sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.fallback().sol_get_stack_height()
+    }
+}
@@ -0,0 +1,229 @@
+use crate::AccountMap;
+use solana_account::Account;
+use solana_account_info::{AccountInfo, MAX_PERMITTED_DATA_INCREASE};
+use solana_instruction::AccountMeta;
+use solana_program_error::{ProgramError, ProgramResult};
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+
+// An account is stored the way the runtime serializes it for a program, so
+// that `AccountInfo::realloc` and `AccountInfo::assign`, which write through
+// the pointers they are given, stay within memory owned by the frame:
+//
+//   [0..4)    padding
+//   [4..8)    original data length (u32), read from just before the key
+//   [8..40)   key
+//   [40..72)  owner
+//   [72..80)  lamports (u64)
+//   [80..88)  current data length (u64), updated by realloc
+//   [88..)    data, followed by MAX_PERMITTED_DATA_INCREASE spare bytes
+const ORIGINAL_DATA_LEN_OFFSET: usize = 4;
+const KEY_OFFSET: usize = 8;
+const OWNER_OFFSET: usize = 40;
+const LAMPORTS_OFFSET: usize = 72;
+const DATA_LEN_OFFSET: usize = 80;
+const DATA_OFFSET: usize = 88;
+
+/// One account laid out in runtime-compatible memory.
+struct AccountFrame {
+    // u64 words keep the lamports and length fields 8-byte aligned
+    buffer: Vec<u64>,
+    executable: bool,
+    rent_epoch: u64,
+}
+
+impl AccountFrame {
+    fn new(pubkey: &Pubkey, account: &Account) -> Self {
+        let len = DATA_OFFSET + account.data.len() + MAX_PERMITTED_DATA_INCREASE;
+        let mut frame = Self {
+            buffer: vec![0; len.div_ceil(8)],
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        };
+
+        let data_len = account.data.len();
+        let bytes = frame.bytes_mut();
+        bytes[ORIGINAL_DATA_LEN_OFFSET..KEY_OFFSET]
+            .copy_from_slice(&(data_len as u32).to_ne_bytes());
+        bytes[KEY_OFFSET..OWNER_OFFSET].copy_from_slice(pubkey.as_ref());
+        bytes[OWNER_OFFSET..LAMPORTS_OFFSET].copy_from_slice(account.owner.as_ref());
+        bytes[LAMPORTS_OFFSET..DATA_LEN_OFFSET].copy_from_slice(&account.lamports.to_ne_bytes());
+        bytes[DATA_LEN_OFFSET..DATA_OFFSET].copy_from_slice(&(data_len as u64).to_ne_bytes());
+        bytes[DATA_OFFSET..DATA_OFFSET + data_len].copy_from_slice(&account.data);
+        frame
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: any u64 buffer is also a valid, initialized byte buffer
+        unsafe { std::slice::from_raw_parts(self.buffer.as_ptr().cast(), self.buffer.len() * 8) }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        // SAFETY: as above, and the exclusive borrow is carried over
+        unsafe {
+            std::slice::from_raw_parts_mut(self.buffer.as_mut_ptr().cast(), self.buffer.len() * 8)
+        }
+    }
+
+    fn read_u64(&self, offset: usize) -> u64 {
+        u64::from_ne_bytes(self.bytes()[offset..offset + 8].try_into().unwrap())
+    }
+
+    fn to_account(&self) -> Account {
+        let data_len = self.read_u64(DATA_LEN_OFFSET) as usize;
+        let bytes = self.bytes();
+        Account {
+            lamports: self.read_u64(LAMPORTS_OFFSET),
+            data: bytes[DATA_OFFSET..DATA_OFFSET + data_len].to_vec(),
+            owner: Pubkey::try_from(&bytes[OWNER_OFFSET..LAMPORTS_OFFSET]).unwrap(),
+            executable: self.executable,
+            rent_epoch: self.rent_epoch,
+        }
+    }
+
+    fn account_info(&mut self, is_signer: bool, is_writable: bool) -> AccountInfo<'_> {
+        let data_len = self.read_u64(DATA_LEN_OFFSET) as usize;
+        let base: *mut u8 = self.buffer.as_mut_ptr().cast();
+        // SAFETY: the regions below are disjoint, in bounds, suitably aligned
+        // (the buffer is u64-aligned and every u64 field is at a multiple of
+        // 8), and borrowed for no longer than the exclusive borrow of `self`.
+        // `Pubkey` is a transparent wrapper around `[u8; 32]`.
+        unsafe {
+            AccountInfo::new(
+                &*base.add(KEY_OFFSET).cast::<Pubkey>(),
+                is_signer,
+                is_writable,
+                &mut *base.add(LAMPORTS_OFFSET).cast::<u64>(),
+                std::slice::from_raw_parts_mut(base.add(DATA_OFFSET), data_len),
+                &*base.add(OWNER_OFFSET).cast::<Pubkey>(),
+                self.executable,
+                self.rent_epoch,
+            )
+        }
+    }
+}
+
+/// Owned copies of an instruction's accounts, laid out for direct
+/// processor calls.
+///
+/// Each distinct pubkey gets one frame in the same memory layout the
+/// runtime uses, so `AccountInfo::realloc` and `AccountInfo::assign` work
+/// as they do on-chain. Duplicate account metas share a frame, just as they
+/// share memory in the runtime.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, AccountMap, harness::AccountFrames};
+/// use solana_instruction::AccountMeta;
+/// use solana_pubkey::Pubkey;
+///
+/// let pubkey = Pubkey::new_unique();
+/// let mut accounts = AccountMap::new();
+/// accounts.add_with_builder(pubkey, AccountBuilder::new().data_raw(vec![1])).unwrap();
+///
+/// let mut frames = AccountFrames::load(&accounts, &[AccountMeta::new(pubkey, false)]);
+/// {
+///     let infos = frames.account_infos();
+///     infos[0].realloc(4, true).unwrap();
+///     infos[0].try_borrow_mut_data().unwrap()[3] = 9;
+/// }
+/// frames.commit(&mut accounts).unwrap();
+///
+/// assert_eq!(accounts.get_account(&pubkey).unwrap().data, vec![1, 0, 0, 9]);
+/// ```
+pub struct AccountFrames {
+    pubkeys: Vec<Pubkey>,
+    frames: Vec<AccountFrame>,
+    metas: Vec<(usize, bool, bool)>,
+}
+
+impl AccountFrames {
+    /// Copies the accounts referenced by `metas` out of `account_map`,
+    /// using an empty, system-owned account for any that are missing.
+    pub fn load(account_map: &AccountMap, metas: &[AccountMeta]) -> Self {
+        let mut index: HashMap<Pubkey, usize> = HashMap::new();
+        let mut frames = Self {
+            pubkeys: Vec::new(),
+            frames: Vec::new(),
+            metas: Vec::with_capacity(metas.len()),
+        };
+
+        for meta in metas {
+            let i = *index.entry(meta.pubkey).or_insert_with(|| {
+                let account = account_map
+                    .get_account(&meta.pubkey)
+                    .cloned()
+                    .unwrap_or_default();
+                frames.pubkeys.push(meta.pubkey);
+                frames
+                    .frames
+                    .push(AccountFrame::new(&meta.pubkey, &account));
+                frames.frames.len() - 1
+            });
+            frames.metas.push((i, meta.is_signer, meta.is_writable));
+        }
+        frames
+    }
+
+    /// Returns one `AccountInfo` per account meta, in instruction order.
+    pub fn account_infos(&mut self) -> Vec<AccountInfo<'_>> {
+        let infos: Vec<AccountInfo<'_>> = self
+            .frames
+            .iter_mut()
+            .map(|frame| frame.account_info(false, false))
+            .collect();
+
+        self.metas
+            .iter()
+            .map(|&(i, is_signer, is_writable)| {
+                let mut info = infos[i].clone();
+                info.is_signer = is_signer;
+                info.is_writable = is_writable;
+                info
+            })
+            .collect()
+    }
+
+    /// Returns the current state of every distinct account.
+    pub fn accounts(&self) -> impl Iterator<Item = (Pubkey, Account)> + '_ {
+        self.pubkeys
+            .iter()
+            .zip(&self.frames)
+            .map(|(pubkey, frame)| (*pubkey, frame.to_account()))
+    }
+
+    /// Writes changed accounts back to `account_map`.
+    ///
+    /// Nothing is written if any account that no meta marked writable has
+    /// changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProgramError::Immutable` if a read-only account changed.
+    pub fn commit(&self, account_map: &mut AccountMap) -> ProgramResult {
+        let mut changed = Vec::new();
+        for (i, (pubkey, account)) in self.accounts().enumerate() {
+            let unchanged = match account_map.get_account(&pubkey) {
+                Some(before) => before == &account,
+                None => account == Account::default(),
+            };
+            if unchanged {
+                continue;
+            }
+            let writable = self
+                .metas
+                .iter()
+                .any(|&(frame, _, is_writable)| frame == i && is_writable);
+            if !writable {
+                return Err(ProgramError::Immutable);
+            }
+            changed.push((pubkey, account));
+        }
+
+        for (pubkey, account) in changed {
+            account_map.set_account(pubkey, account);
+        }
+        Ok(())
+    }
+}
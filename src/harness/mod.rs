@@ -0,0 +1,192 @@
+//! Running native instruction processors directly against an `AccountMap`.
+//!
+//! Starting a `ProgramTest` bank for every test is slow, and most program
+//! logic does not need a bank at all. [`LocalHarness`] builds `AccountInfo`s
+//! from an [`AccountMap`], calls a program's `process_instruction` function
+//! in-process, and writes the results back to the map.
+//!
+//! Accounts are laid out the way the runtime lays them out for a program,
+//! so `AccountInfo::realloc` and `AccountInfo::assign` behave as they do
+//! on-chain. The harness does not charge compute units, verify signatures,
+//! or enforce ownership rules beyond rejecting changes to accounts that were
+//! not passed as writable. Cross-program invocations are recorded rather
+//! than executed; see the [`cpi`] module.
+
+pub mod cpi;
+mod frame;
+
+pub use frame::AccountFrames;
+
+use crate::{AccountBuilder, AccountGenError, AccountMap};
+use cpi::{RecordedCpi, Recording};
+use solana_account::Account;
+use solana_account_info::AccountInfo;
+use solana_instruction::Instruction;
+use solana_program_error::{ProgramError, ProgramResult};
+use solana_pubkey::Pubkey;
+
+/// The signature of a native program's entrypoint.
+pub type ProcessInstruction = fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult;
+
+/// Runs a program's instruction processor directly, without a bank.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountBuilder;
+/// use solana_accountgen::harness::LocalHarness;
+/// use solana_account_info::AccountInfo;
+/// use solana_instruction::{AccountMeta, Instruction};
+/// use solana_program_error::ProgramResult;
+/// use solana_pubkey::Pubkey;
+///
+/// fn process_instruction(_: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+///     accounts[0].try_borrow_mut_data()?[0] += data[0];
+///     Ok(())
+/// }
+///
+/// let program_id = Pubkey::new_unique();
+/// let counter = Pubkey::new_unique();
+///
+/// let mut harness = LocalHarness::new(program_id, process_instruction);
+/// harness
+///     .add_with_builder(counter, AccountBuilder::new().owner(program_id).data_raw(vec![0]))
+///     .unwrap();
+///
+/// let ix = Instruction::new_with_bytes(program_id, &[5], vec![AccountMeta::new(counter, false)]);
+/// harness.process_instruction(&ix).unwrap();
+///
+/// assert_eq!(harness.get_account(&counter).unwrap().data, vec![5]);
+/// ```
+pub struct LocalHarness {
+    program_id: Pubkey,
+    processor: ProcessInstruction,
+    accounts: AccountMap,
+    cpis: Vec<RecordedCpi>,
+}
+
+impl LocalHarness {
+    /// Creates a harness for the program with the given entrypoint.
+    pub fn new(program_id: Pubkey, processor: ProcessInstruction) -> Self {
+        Self {
+            program_id,
+            processor,
+            accounts: AccountMap::new(),
+            cpis: Vec::new(),
+        }
+    }
+
+    /// Replaces the harness's accounts.
+    pub fn with_accounts(mut self, accounts: AccountMap) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    /// Returns the program ID the harness invokes.
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    /// Sets an account.
+    pub fn set_account(&mut self, pubkey: Pubkey, account: Account) {
+        self.accounts.set_account(pubkey, account);
+    }
+
+    /// Builds and sets an account.
+    pub fn add_with_builder(
+        &mut self,
+        pubkey: Pubkey,
+        builder: AccountBuilder,
+    ) -> Result<&mut Self, AccountGenError> {
+        self.accounts.add_with_builder(pubkey, builder)?;
+        Ok(self)
+    }
+
+    /// Returns an account by pubkey.
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get_account(pubkey)
+    }
+
+    /// Returns the harness's accounts.
+    pub fn accounts(&self) -> &AccountMap {
+        &self.accounts
+    }
+
+    /// Returns the harness's accounts for modification.
+    pub fn accounts_mut(&mut self) -> &mut AccountMap {
+        &mut self.accounts
+    }
+
+    /// Returns the cross-program invocations attempted so far, in order.
+    ///
+    /// Invocations are recorded even if the instruction later failed.
+    pub fn cpis(&self) -> &[RecordedCpi] {
+        &self.cpis
+    }
+
+    /// Returns and clears the recorded cross-program invocations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::harness::LocalHarness;
+    /// use solana_account_info::AccountInfo;
+    /// use solana_instruction::Instruction;
+    /// use solana_program_error::ProgramResult;
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// const MEMO: Pubkey = solana_accountgen::well_known::MEMO_PROGRAM;
+    ///
+    /// fn process_instruction(_: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    ///     let memo = Instruction::new_with_bytes(MEMO, data, vec![]);
+    ///     solana_accountgen::harness::cpi::invoke(&memo, accounts)
+    /// }
+    ///
+    /// let program_id = Pubkey::new_unique();
+    /// let mut harness = LocalHarness::new(program_id, process_instruction);
+    /// harness
+    ///     .process_instruction(&Instruction::new_with_bytes(program_id, b"hi", vec![]))
+    ///     .unwrap();
+    ///
+    /// let cpis = harness.take_cpis();
+    /// assert_eq!(cpis[0].program_id(), MEMO);
+    /// assert_eq!(cpis[0].instruction.data, b"hi");
+    /// assert!(harness.cpis().is_empty());
+    /// ```
+    pub fn take_cpis(&mut self) -> Vec<RecordedCpi> {
+        std::mem::take(&mut self.cpis)
+    }
+
+    /// Consumes the harness and returns its accounts.
+    pub fn into_accounts(self) -> AccountMap {
+        self.accounts
+    }
+
+    /// Processes an instruction and commits the resulting account changes.
+    ///
+    /// Accounts missing from the map are passed as empty, system-owned
+    /// accounts. If the processor fails, no changes are committed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the processor's error, `ProgramError::IncorrectProgramId` if
+    /// the instruction targets another program, or `ProgramError::Immutable`
+    /// if an account passed as read-only was modified.
+    pub fn process_instruction(&mut self, instruction: &Instruction) -> ProgramResult {
+        if instruction.program_id != self.program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut frames = AccountFrames::load(&self.accounts, &instruction.accounts);
+        let result = {
+            let account_infos = frames.account_infos();
+            let recording = Recording::start(self.program_id);
+            let result = (self.processor)(&self.program_id, &account_infos, &instruction.data);
+            self.cpis.extend(recording.finish());
+            result
+        };
+        result?;
+
+        frames.commit(&mut self.accounts)
+    }
+}
@@ -0,0 +1,43 @@
+//! Compute Budget program instructions.
+//!
+//! Instruction data is a one-byte variant tag followed by the little-endian
+//! argument. None of the instructions take accounts.
+
+use crate::well_known::COMPUTE_BUDGET_PROGRAM;
+use solana_instruction::Instruction;
+
+fn instruction(tag: u8, argument: &[u8]) -> Instruction {
+    let mut data = Vec::with_capacity(1 + argument.len());
+    data.push(tag);
+    data.extend_from_slice(argument);
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM, &data, vec![])
+}
+
+/// Requests a heap of `bytes` bytes, a multiple of 1024 up to 256 KiB.
+pub fn request_heap_frame(bytes: u32) -> Instruction {
+    instruction(1, &bytes.to_le_bytes())
+}
+
+/// Sets the transaction's compute unit limit.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::instructions::compute_budget::set_compute_unit_limit;
+///
+/// let instruction = set_compute_unit_limit(1_400_000);
+/// assert_eq!(instruction.data, [2, 0xc0, 0x5c, 0x15, 0x00]);
+/// ```
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    instruction(2, &units.to_le_bytes())
+}
+
+/// Sets the priority fee, in micro-lamports per compute unit.
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    instruction(3, &micro_lamports.to_le_bytes())
+}
+
+/// Limits the total size of the accounts the transaction loads.
+pub fn set_loaded_accounts_data_size_limit(bytes: u32) -> Instruction {
+    instruction(4, &bytes.to_le_bytes())
+}
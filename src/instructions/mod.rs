@@ -0,0 +1,56 @@
+//! Builders for common native program instructions.
+//!
+//! Test transactions often need a transfer, a memo or a compute budget
+//! request next to the instruction under test. These builders encode them
+//! without pulling in the system, memo and compute budget interface crates,
+//! and produce exactly what those crates would.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::instructions::{compute_budget, memo, system};
+//! use solana_accountgen::sol;
+//! use solana_pubkey::Pubkey;
+//!
+//! let (payer, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+//! let instructions = [
+//!     compute_budget::set_compute_unit_limit(200_000),
+//!     compute_budget::set_compute_unit_price(1_000),
+//!     system::transfer(&payer, &recipient, sol!(1)),
+//!     memo("invoice 42", &[&payer]),
+//! ];
+//! ```
+
+pub mod compute_budget;
+pub mod system;
+
+use crate::well_known::MEMO_PROGRAM;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+/// Builds an SPL Memo instruction.
+///
+/// The memo program checks that every account in `signers` signed the
+/// transaction.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::instructions::memo;
+/// use solana_pubkey::Pubkey;
+///
+/// let signer = Pubkey::new_unique();
+/// let instruction = memo("hello", &[&signer]);
+/// assert_eq!(instruction.data, b"hello");
+/// assert!(instruction.accounts[0].is_signer);
+/// ```
+pub fn memo(memo: &str, signers: &[&Pubkey]) -> Instruction {
+    Instruction::new_with_bytes(
+        MEMO_PROGRAM,
+        memo.as_bytes(),
+        signers
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(**signer, true))
+            .collect(),
+    )
+}
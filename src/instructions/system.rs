@@ -0,0 +1,104 @@
+//! System program instructions.
+//!
+//! Instruction data is the bincode encoding of `SystemInstruction`: a
+//! four-byte little-endian variant tag followed by the fields, with strings
+//! prefixed by an eight-byte length.
+
+use crate::well_known::SYSTEM_PROGRAM;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+fn instruction(tag: u32, fields: &[&[u8]], accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = tag.to_le_bytes().to_vec();
+    for field in fields {
+        data.extend_from_slice(field);
+    }
+    Instruction::new_with_bytes(SYSTEM_PROGRAM, &data, accounts)
+}
+
+/// Creates a new account at `to` owned by `owner` with `space` bytes of
+/// data, funded by `from`. Both accounts must sign.
+pub fn create_account(
+    from: &Pubkey,
+    to: &Pubkey,
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+) -> Instruction {
+    instruction(
+        0,
+        &[
+            &lamports.to_le_bytes(),
+            &space.to_le_bytes(),
+            owner.as_ref(),
+        ],
+        vec![AccountMeta::new(*from, true), AccountMeta::new(*to, true)],
+    )
+}
+
+/// Assigns `pubkey` to `owner`. The account must sign.
+pub fn assign(pubkey: &Pubkey, owner: &Pubkey) -> Instruction {
+    instruction(1, &[owner.as_ref()], vec![AccountMeta::new(*pubkey, true)])
+}
+
+/// Transfers `lamports` from `from`, which must sign, to `to`.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::instructions::system::transfer;
+/// use solana_pubkey::Pubkey;
+///
+/// let instruction = transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 5);
+/// assert_eq!(instruction.data, [2, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0]);
+/// ```
+pub fn transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+    instruction(
+        2,
+        &[&lamports.to_le_bytes()],
+        vec![AccountMeta::new(*from, true), AccountMeta::new(*to, false)],
+    )
+}
+
+/// Creates a new account at the address derived from `base` and `seed`, as
+/// computed by [`Pubkey::create_with_seed`] and
+/// [`AccountBuilder::create_seeded`](crate::AccountBuilder::create_seeded).
+///
+/// `from` funds the account and `base` authorizes the address; both must
+/// sign, and may be the same account.
+pub fn create_account_with_seed(
+    from: &Pubkey,
+    to: &Pubkey,
+    base: &Pubkey,
+    seed: &str,
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*from, true),
+        AccountMeta::new(*to, false),
+        AccountMeta::new_readonly(*base, true),
+    ];
+    instruction(
+        3,
+        &[
+            base.as_ref(),
+            &(seed.len() as u64).to_le_bytes(),
+            seed.as_bytes(),
+            &lamports.to_le_bytes(),
+            &space.to_le_bytes(),
+            owner.as_ref(),
+        ],
+        accounts,
+    )
+}
+
+/// Allocates `space` bytes of data for `pubkey`. The account must sign.
+pub fn allocate(pubkey: &Pubkey, space: u64) -> Instruction {
+    instruction(
+        8,
+        &[&space.to_le_bytes()],
+        vec![AccountMeta::new(*pubkey, true)],
+    )
+}
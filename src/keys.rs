@@ -0,0 +1,410 @@
+//! Deterministic and readable test keys.
+//!
+//! `Pubkey::new_unique()` and `Keypair::new()` produce addresses that change
+//! from run to run and are impossible to tell apart in logs. The helpers in
+//! this module derive keys from human-chosen labels instead, so the same
+//! test always uses the same addresses and failures point at `alice`
+//! rather than `7xKX...`.
+//!
+//! [`IdentityTree`] derives a whole cast of participants from one seed and
+//! exports it as JSON, so test suites in other languages can use the same
+//! keys.
+
+use crate::{AccountGenError, tracking};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_keypair::{Keypair, keypair_from_seed};
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use std::path::Path;
+
+/// Domain separator so labels do not collide with other seeded derivations.
+const LABEL_DOMAIN: &[u8] = b"solana-accountgen:test_keypair:";
+
+/// The characters allowed in a base58-encoded pubkey.
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// How many fillers [`vanity_pubkey`] tries before giving up on a prefix.
+/// Any prefix that can start an address matches within a few attempts.
+const VANITY_ATTEMPTS: u32 = 256;
+
+/// Derives a keypair from a label.
+///
+/// The same label always yields the same keypair, on every machine and in
+/// every run. Labels are hashed, so any string works.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::keys::test_keypair;
+/// use solana_signer::Signer;
+///
+/// let alice = test_keypair("alice");
+/// assert_eq!(alice.pubkey(), test_keypair("alice").pubkey());
+/// assert_ne!(alice.pubkey(), test_keypair("bob").pubkey());
+/// ```
+pub fn test_keypair(label: &str) -> Keypair {
+    let seed = Sha256::new()
+        .chain_update(LABEL_DOMAIN)
+        .chain_update(label.as_bytes())
+        .finalize();
+    let keypair = keypair_from_seed(&seed).expect("a 32-byte seed is always valid");
+    tracking::record(keypair.pubkey());
+    keypair
+}
+
+/// Returns the pubkey of [`test_keypair`] for a label.
+pub fn test_pubkey(label: &str) -> Pubkey {
+    test_keypair(label).pubkey()
+}
+
+/// Returns a deterministic pubkey whose base58 form starts with `prefix`.
+///
+/// The address is constructed rather than ground out of random keypairs, so
+/// even long prefixes are instant. There is no private key for it: use it for
+/// accounts that never sign, such as program IDs, mints, and PDAs' stand-ins.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::keys::vanity_pubkey;
+///
+/// let pool = vanity_pubkey("PooL1");
+/// assert!(pool.to_string().starts_with("PooL1"));
+/// assert_eq!(pool, vanity_pubkey("PooL1"));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `prefix` contains characters outside the base58 alphabet
+/// (`0`, `O`, `I` and `l` are not allowed), is longer than 40 characters, or
+/// cannot start any 32-byte address, such as 33 leading `1`s.
+pub fn vanity_pubkey(prefix: &str) -> Pubkey {
+    if let Some(c) = prefix.bytes().find(|c| !BASE58_ALPHABET.contains(c)) {
+        panic!(
+            "vanity prefix {:?} contains {:?}, which is not a base58 character",
+            prefix, c as char
+        );
+    }
+    assert!(prefix.len() <= 40, "vanity prefix {:?} is too long", prefix);
+
+    // Fill the rest of the address with characters derived from the prefix
+    // and keep the first candidate that encodes to exactly 32 bytes. Each
+    // leading '1' stands for a zero byte and shortens the encoding, so every
+    // length a 32-byte address can have is tried.
+    for attempt in 0..VANITY_ATTEMPTS {
+        let filler = Sha256::new()
+            .chain_update(prefix.as_bytes())
+            .chain_update(attempt.to_le_bytes())
+            .finalize();
+
+        for len in (prefix.len().max(32)..=44).rev() {
+            let mut candidate = prefix.to_string();
+            candidate.extend(
+                filler
+                    .iter()
+                    .cycle()
+                    .take(len - prefix.len())
+                    .map(|b| BASE58_ALPHABET[*b as usize % BASE58_ALPHABET.len()] as char),
+            );
+
+            let Ok(bytes) = bs58::decode(&candidate).into_vec() else {
+                continue;
+            };
+            if let Ok(bytes) = <[u8; 32]>::try_from(bytes) {
+                let pubkey = Pubkey::new_from_array(bytes);
+                if pubkey.to_string() == candidate {
+                    tracking::record(pubkey);
+                    return pubkey;
+                }
+            }
+        }
+    }
+    panic!("vanity prefix {:?} cannot start a 32-byte address", prefix)
+}
+
+/// Number of admins [`IdentityTree::from_seed`] derives.
+pub const DEFAULT_ADMINS: usize = 1;
+
+/// Number of users [`IdentityTree::from_seed`] derives.
+pub const DEFAULT_USERS: usize = 3;
+
+/// Number of mints [`IdentityTree::from_seed`] derives.
+pub const DEFAULT_MINTS: usize = 2;
+
+/// A named set of keypairs derived from one seed: a payer, admins, users
+/// and mint keypairs.
+///
+/// Each keypair is the [`test_keypair`] of its path under the seed:
+/// `"<seed>/payer"`, `"<seed>/admins/<i>"`, `"<seed>/users/<i>"` and
+/// `"<seed>/mints/<i>"`. Growing a group never changes the keys already in
+/// it, and other languages can reproduce any key from the seed, although
+/// [`IdentityTree::to_json`] is the simpler way to share them: the file
+/// lists every keypair as a `pubkey` and a 64-byte `secretKey`, the array
+/// `Keypair.fromSecretKey` in `@solana/web3.js` takes.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::keys::IdentityTree;
+/// use solana_signer::Signer;
+///
+/// let identities = IdentityTree::from_seed("lending-suite").users(5);
+/// assert_eq!(identities.users_len(), 5);
+/// assert_eq!(
+///     identities.user(0).pubkey(),
+///     IdentityTree::from_seed("lending-suite").user(0).pubkey()
+/// );
+///
+/// let json = identities.to_json();
+/// let restored = IdentityTree::from_json(&json).unwrap();
+/// assert_eq!(restored.payer().pubkey(), identities.payer().pubkey());
+/// assert_eq!(restored.get("users/4").unwrap().pubkey(), identities.user(4).pubkey());
+/// ```
+#[derive(Debug)]
+pub struct IdentityTree {
+    seed: String,
+    payer: Keypair,
+    admins: Vec<Keypair>,
+    users: Vec<Keypair>,
+    mints: Vec<Keypair>,
+}
+
+impl IdentityTree {
+    /// Derives a payer and [`DEFAULT_ADMINS`] admins, [`DEFAULT_USERS`]
+    /// users and [`DEFAULT_MINTS`] mints from `seed`.
+    pub fn from_seed(seed: &str) -> Self {
+        let mut tree = Self {
+            seed: seed.to_string(),
+            payer: test_keypair(&format!("{}/payer", seed)),
+            admins: Vec::new(),
+            users: Vec::new(),
+            mints: Vec::new(),
+        };
+        resize_group(seed, "admins", &mut tree.admins, DEFAULT_ADMINS);
+        resize_group(seed, "users", &mut tree.users, DEFAULT_USERS);
+        resize_group(seed, "mints", &mut tree.mints, DEFAULT_MINTS);
+        tree
+    }
+
+    /// Resizes the admins to `count`, deriving any new ones.
+    pub fn admins(mut self, count: usize) -> Self {
+        resize_group(&self.seed, "admins", &mut self.admins, count);
+        self
+    }
+
+    /// Resizes the users to `count`, deriving any new ones.
+    pub fn users(mut self, count: usize) -> Self {
+        resize_group(&self.seed, "users", &mut self.users, count);
+        self
+    }
+
+    /// Resizes the mints to `count`, deriving any new ones.
+    pub fn mints(mut self, count: usize) -> Self {
+        resize_group(&self.seed, "mints", &mut self.mints, count);
+        self
+    }
+
+    /// Returns the seed the tree was derived from.
+    pub fn seed(&self) -> &str {
+        &self.seed
+    }
+
+    /// Returns the payer.
+    pub fn payer(&self) -> &Keypair {
+        &self.payer
+    }
+
+    /// Returns admin `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such admin.
+    pub fn admin(&self, index: usize) -> &Keypair {
+        group_member("admins", &self.admins, index)
+    }
+
+    /// Returns user `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such user.
+    pub fn user(&self, index: usize) -> &Keypair {
+        group_member("users", &self.users, index)
+    }
+
+    /// Returns mint keypair `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such mint.
+    pub fn mint(&self, index: usize) -> &Keypair {
+        group_member("mints", &self.mints, index)
+    }
+
+    /// Returns the number of admins.
+    pub fn admins_len(&self) -> usize {
+        self.admins.len()
+    }
+
+    /// Returns the number of users.
+    pub fn users_len(&self) -> usize {
+        self.users.len()
+    }
+
+    /// Returns the number of mints.
+    pub fn mints_len(&self) -> usize {
+        self.mints.len()
+    }
+
+    /// Returns the keypair at a path such as `"payer"` or `"users/2"`.
+    pub fn get(&self, path: &str) -> Option<&Keypair> {
+        if path == "payer" {
+            return Some(&self.payer);
+        }
+        let (group, index) = path.split_once('/')?;
+        let index: usize = index.parse().ok()?;
+        match group {
+            "admins" => self.admins.get(index),
+            "users" => self.users.get(index),
+            "mints" => self.mints.get(index),
+            _ => None,
+        }
+    }
+
+    /// Returns every (path, keypair) pair: the payer, then the admins,
+    /// users and mints in order.
+    pub fn iter(&self) -> impl Iterator<Item = (String, &Keypair)> + '_ {
+        let groups = [
+            ("admins", &self.admins),
+            ("users", &self.users),
+            ("mints", &self.mints),
+        ];
+        std::iter::once(("payer".to_string(), &self.payer)).chain(groups.into_iter().flat_map(
+            |(group, keypairs)| {
+                keypairs
+                    .iter()
+                    .enumerate()
+                    .map(move |(i, keypair)| (format!("{}/{}", group, i), keypair))
+            },
+        ))
+    }
+
+    /// Serializes the tree, secret keys included, to pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        let entries = |keypairs: &[Keypair]| keypairs.iter().map(IdentityEntry::new).collect();
+        let file = IdentityFile {
+            seed: self.seed.clone(),
+            payer: IdentityEntry::new(&self.payer),
+            admins: entries(&self.admins),
+            users: entries(&self.users),
+            mints: entries(&self.mints),
+        };
+        serde_json::to_string_pretty(&file).expect("identity files always serialize")
+    }
+
+    /// Parses a tree written by [`IdentityTree::to_json`].
+    ///
+    /// The keypairs are taken from the file, so identities generated
+    /// elsewhere can be imported; groups grown afterwards derive their new
+    /// members from the file's seed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the JSON does not
+    /// parse or a secret key does not match its pubkey.
+    pub fn from_json(json: &str) -> Result<Self, AccountGenError> {
+        let file: IdentityFile = serde_json::from_str(json).map_err(|e| {
+            AccountGenError::InvalidDataFormat(format!("invalid identity file: {e}"))
+        })?;
+        let keypairs = |entries: Vec<IdentityEntry>| {
+            entries
+                .into_iter()
+                .map(IdentityEntry::into_keypair)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            seed: file.seed,
+            payer: file.payer.into_keypair()?,
+            admins: keypairs(file.admins)?,
+            users: keypairs(file.users)?,
+            mints: keypairs(file.mints)?,
+        })
+    }
+
+    /// Writes the tree to a JSON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AccountGenError> {
+        std::fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    /// Reads a tree from a JSON file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AccountGenError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Truncates `keypairs` to `count`, or derives members until it has
+/// `count`.
+fn resize_group(seed: &str, group: &str, keypairs: &mut Vec<Keypair>, count: usize) {
+    keypairs.truncate(count);
+    for i in keypairs.len()..count {
+        keypairs.push(test_keypair(&format!("{}/{}/{}", seed, group, i)));
+    }
+}
+
+fn group_member<'a>(group: &str, keypairs: &'a [Keypair], index: usize) -> &'a Keypair {
+    match keypairs.get(index) {
+        Some(keypair) => keypair,
+        None => panic!(
+            "identity tree has {} {}, no index {}",
+            keypairs.len(),
+            group,
+            index
+        ),
+    }
+}
+
+/// The JSON form of an [`IdentityTree`].
+#[derive(Serialize, Deserialize)]
+struct IdentityFile {
+    seed: String,
+    payer: IdentityEntry,
+    admins: Vec<IdentityEntry>,
+    users: Vec<IdentityEntry>,
+    mints: Vec<IdentityEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdentityEntry {
+    pubkey: String,
+    secret_key: Vec<u8>,
+}
+
+impl IdentityEntry {
+    fn new(keypair: &Keypair) -> Self {
+        Self {
+            pubkey: keypair.pubkey().to_string(),
+            secret_key: keypair.to_bytes().to_vec(),
+        }
+    }
+
+    fn into_keypair(self) -> Result<Keypair, AccountGenError> {
+        let keypair = Keypair::try_from(self.secret_key.as_slice()).map_err(|e| {
+            AccountGenError::InvalidDataFormat(format!(
+                "invalid secret key for {}: {}",
+                self.pubkey, e
+            ))
+        })?;
+        if keypair.pubkey().to_string() != self.pubkey {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "secret key belongs to {}, not {}",
+                keypair.pubkey(),
+                self.pubkey
+            )));
+        }
+        tracking::record(keypair.pubkey());
+        Ok(keypair)
+    }
+}
@@ -5,10 +5,42 @@
 //! ## Features
 //!
 //! - Create accounts with custom balances, owners, and data using a fluent API
+//! - `#[derive(AccountFixture)]` for converting data types to and from accounts
 //! - Serialize account data using Borsh (with JSON support for the bincode module)
 //! - Support for creating PDAs (Program Derived Addresses)
 //! - Integration with solana-program-test for end-to-end testing
 //! - Support for Anchor programs with discriminator handling
+//! - Streaming fixture files for storing and replaying large account sets
+//! - Cloning accounts from a live cluster (with the `rpc` feature)
+//! - Parsing program logs and decoding Anchor events
+//! - Running native instruction processors directly against an account map
+//! - Streaming account updates in a Geyser-like format
+//! - Laying out ring buffer and event queue accounts
+//! - Detecting accounts a program created that a test did not expect
+//! - Finding the pubkeys account data refers to, and pruning unreferenced accounts
+//! - Replacing real wallet addresses in cloned fixtures with test addresses
+//! - Per-thread defaults, such as the owner of new accounts
+//! - One backend trait over solana-program-test and the local harness
+//! - Reusing data buffers when generating accounts in bulk
+//! - SPL Token Lending market, reserve and obligation accounts
+//! - OpenBook markets, event queues and order book slabs
+//! - Keypair sets derived from a seed and shared as JSON
+//! - Recording the transactions of a test session for later inspection
+//! - Testing several programs and their IDLs together
+//! - Checking that a sequence of fixture states is reachable
+//! - Summarizing program accounts instead of dumping their bytecode
+//! - Bounds-checked field reads and writes on raw account data
+//! - Genesis validators with vote and stake accounts delegated from slot 0
+//! - Sealing accounts to detect writes outside a test's expected set
+//! - Running scripted test steps with named checkpoints
+//! - Random account data shaped by an Anchor IDL, for property tests
+//! - Compute unit baselines that catch performance regressions
+//! - Snapshot files of normalized program logs
+//! - M-of-N signer sets with SPL Token and Squads multisig accounts
+//! - Address lookup tables and checking how v0 transactions resolve them
+//! - Factories with per-field defaults for fixture types
+//! - Provenance of cloned fixtures, and detecting drift from the cluster
+//! - Counting accounts per discriminator and account type
 //!
 //! ## Example
 //!
@@ -73,14 +105,56 @@
 //! ```
 
 mod account_builder;
+mod account_editor;
+mod account_fixture;
 mod account_map;
+pub mod anonymize;
+pub mod backend;
+pub mod cluster;
+pub mod compute;
+pub mod config;
+pub mod decoder;
+pub mod diff;
+pub mod display;
 mod error;
 pub mod extensions;
+pub mod factories;
+pub mod fees;
+pub mod fixture;
+pub mod harness;
+pub mod instructions;
+pub mod keys;
+pub mod logs;
+pub mod lookup_tables;
+#[doc(hidden)]
+pub mod macros;
+pub mod pool;
+pub mod program_summary;
+pub mod references;
+pub mod regions;
+mod ring_buffer;
+pub mod rpc;
+pub mod runner;
+pub mod seal;
 pub mod serialization;
-
-pub use account_builder::AccountBuilder;
+pub mod session;
+mod shared_account_map;
+pub mod stats;
+pub mod tracking;
+pub mod transitions;
+pub mod units;
+pub mod updates;
+pub mod well_known;
+
+pub use account_builder::{AccountBuilder, BUILDER_FORMAT_VERSION, BuildMode};
+pub use account_editor::AccountEditor;
+pub use account_fixture::AccountFixture;
 pub use account_map::AccountMap;
 pub use error::AccountGenError;
+pub use ring_buffer::RingBufferBuilder;
+pub use shared_account_map::SharedAccountMap;
+pub use solana_accountgen_derive::AccountFixture;
+pub use units::{IntoLamports, Sol};
 
 // Re-export dependencies that users will likely need
 pub use borsh;
@@ -164,17 +238,53 @@ mod tests {
 
     #[test]
     fn test_account_builder_executable() {
-        let program_id = Pubkey::new_unique();
-
         let account = AccountBuilder::new()
             .balance(100_000)
-            .owner(program_id)
+            .owner(well_known::BPF_LOADER)
             .executable(true)
+            .data_raw(vec![0x7f, b'E', b'L', b'F'])
             .build();
 
         assert_eq!(account.executable, true);
     }
 
+    #[test]
+    fn test_account_builder_executable_guardrails() {
+        let system_owned = AccountBuilder::new()
+            .executable(true)
+            .data_raw(vec![1])
+            .try_build();
+        match system_owned {
+            Err(AccountGenError::InvalidExecutable(message)) => {
+                assert!(message.contains("System Program"), "{message}");
+            }
+            other => panic!("expected InvalidExecutable, got {other:?}"),
+        }
+
+        let empty = AccountBuilder::new()
+            .owner(well_known::BPF_LOADER_UPGRADEABLE)
+            .executable(true)
+            .try_build();
+        assert!(matches!(empty, Err(AccountGenError::InvalidExecutable(_))));
+
+        let overridden = AccountBuilder::new()
+            .owner(Pubkey::new_unique())
+            .executable(true)
+            .skip_executable_checks()
+            .try_build();
+        assert!(overridden.unwrap().executable);
+
+        let json = serde_json::to_value(
+            AccountBuilder::new()
+                .executable(true)
+                .skip_executable_checks(),
+        )
+        .unwrap();
+        assert_eq!(json["skipExecutableChecks"], true);
+        let restored: AccountBuilder = serde_json::from_value(json).unwrap();
+        assert!(restored.try_build().is_ok());
+    }
+
     #[test]
     fn test_account_builder_rent_epoch() {
         let program_id = Pubkey::new_unique();
@@ -248,6 +358,28 @@ mod tests {
         assert_eq!(deserialized, test_data);
     }
 
+    #[test]
+    fn test_bincode_legacy_stake_state() {
+        use crate::serialization::bincode::legacy;
+        use solana_program::stake::state::{Authorized, Lockup, Meta, StakeStateV2};
+
+        let meta = Meta {
+            rent_exempt_reserve: 2_282_880,
+            authorized: Authorized::auto(&Pubkey::new_unique()),
+            lockup: Lockup::default(),
+        };
+        let state = StakeStateV2::Initialized(meta);
+        let data = legacy::serialize_data_padded(&state, StakeStateV2::size_of()).unwrap();
+        assert_eq!(data.len(), 200);
+
+        let account = AccountBuilder::new()
+            .owner(solana_sdk_ids::stake::id())
+            .data_raw(data)
+            .build();
+        let decoded: StakeStateV2 = legacy::deserialize_account_data(&account).unwrap();
+        assert_eq!(decoded, state);
+    }
+
     #[test]
     fn test_account_base64_encoding() {
         let program_id = Pubkey::new_unique();
@@ -469,6 +601,744 @@ mod tests {
         assert_eq!(account.owner, custom_owner);
         assert_eq!(account.lamports, custom_balance);
     }
+
+    #[test]
+    fn test_account_builder_cluster_defaults() {
+        use crate::cluster::{ClusterContext, ClusterVersion, RENT_EXEMPT_RENT_EPOCH};
+
+        let rent = Rent {
+            lamports_per_byte_year: 1,
+            ..Rent::default()
+        };
+        let context = ClusterContext::new(ClusterVersion::V1_18)
+            .with_rent(rent.clone())
+            .at_slot(500_000);
+
+        // Defaults to the context's rent-exempt balance and rent epoch
+        let exempt = AccountBuilder::new()
+            .data_raw(vec![0; 10])
+            .cluster(context.clone())
+            .build();
+        assert_eq!(exempt.lamports, rent.minimum_balance(10));
+        assert_eq!(exempt.rent_epoch, RENT_EXEMPT_RENT_EPOCH);
+
+        // Rent-paying accounts record the next epoch rent is due
+        let rent_paying = AccountBuilder::new()
+            .balance(1)
+            .data_raw(vec![0; 10])
+            .cluster(context.clone())
+            .build();
+        assert_eq!(rent_paying.rent_epoch, context.epoch + 1);
+
+        // Explicit values still win
+        let explicit = AccountBuilder::new().rent_epoch(7).cluster(context).build();
+        assert_eq!(explicit.rent_epoch, 7);
+    }
+
+    #[test]
+    fn test_cluster_context_check_account() {
+        use crate::cluster::ClusterContext;
+
+        let context = ClusterContext::default();
+        let exempt = AccountBuilder::new().data_raw(vec![0; 10]).build();
+        assert!(context.check_account(&exempt).is_ok());
+
+        let empty = AccountBuilder::new().balance(0).build();
+        assert!(context.check_account(&empty).is_ok());
+
+        let rent_paying = AccountBuilder::new().balance(1).build();
+        assert!(matches!(
+            context.check_account(&rent_paying),
+            Err(AccountGenError::InsufficientBalance { actual: 1, .. })
+        ));
+
+        let empty_program = AccountBuilder::new()
+            .balance(0)
+            .executable(true)
+            .skip_executable_checks()
+            .build();
+        assert!(context.check_account(&empty_program).is_err());
+
+        let oversized = AccountBuilder::new()
+            .data_raw(vec![0; cluster::MAX_PERMITTED_DATA_LENGTH + 1])
+            .build();
+        assert!(matches!(
+            context.check_account(&oversized),
+            Err(AccountGenError::DataTooLarge { max, .. }) if max == cluster::MAX_PERMITTED_DATA_LENGTH
+        ));
+    }
+
+    #[test]
+    fn test_account_builder_build_modes() {
+        use crate::cluster::ClusterContext;
+
+        let strict = || AccountBuilder::new().mode(BuildMode::Strict);
+        assert!(strict().data_raw(vec![0; 10]).try_build().is_ok());
+        assert!(strict().balance(0).try_build().is_ok());
+        assert!(matches!(
+            strict().balance(1).try_build(),
+            Err(AccountGenError::InsufficientBalance { actual: 1, .. })
+        ));
+        assert!(matches!(
+            strict()
+                .balance(u64::MAX)
+                .data_raw(vec![0; cluster::MAX_PERMITTED_DATA_LENGTH + 1])
+                .try_build(),
+            Err(AccountGenError::DataTooLarge { .. })
+        ));
+        assert!(strict().balance(1).skip_rent_check().try_build().is_ok());
+        assert!(
+            strict()
+                .balance(u64::MAX)
+                .data_raw(vec![0; cluster::MAX_PERMITTED_DATA_LENGTH + 1])
+                .skip_size_limit()
+                .try_build()
+                .is_ok()
+        );
+
+        // The context sets the mode, and uses its own rent for the check
+        let context = ClusterContext::default()
+            .with_rent(Rent {
+                lamports_per_byte_year: 0,
+                ..Rent::default()
+            })
+            .with_build_mode(BuildMode::Strict);
+        assert!(
+            AccountBuilder::new()
+                .balance(1)
+                .cluster(context)
+                .try_build()
+                .is_ok()
+        );
+        let strict_context = ClusterContext::default().with_build_mode(BuildMode::Strict);
+        assert!(
+            AccountBuilder::new()
+                .balance(1)
+                .cluster(strict_context.clone())
+                .try_build()
+                .is_err()
+        );
+        assert!(
+            AccountBuilder::new()
+                .balance(1)
+                .cluster(strict_context)
+                .mode(BuildMode::Lenient)
+                .try_build()
+                .is_ok()
+        );
+
+        let json = serde_json::to_value(strict().skip_rent_check()).unwrap();
+        assert_eq!(json["mode"], "strict");
+        assert_eq!(json["skipRentCheck"], true);
+        let restored: AccountBuilder = serde_json::from_value(json).unwrap();
+        assert!(restored.balance(1).try_build().is_ok());
+    }
+
+    #[test]
+    fn test_configured_default_owner() {
+        use crate::config::AccountGenConfig;
+
+        let (program_id, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let outer = AccountGenConfig::new().default_owner(program_id).install();
+        assert_eq!(AccountBuilder::new().build().owner, program_id);
+        assert_eq!(AccountBuilder::new().owner(other).build().owner, other);
+
+        // Installs nest, and each guard restores what it replaced
+        let inner = AccountGenConfig::new().default_owner(other).install();
+        assert_eq!(AccountGenConfig::current().default_owner, other);
+        drop(inner);
+        assert_eq!(AccountBuilder::new().build().owner, program_id);
+        drop(outer);
+        assert_eq!(AccountBuilder::new().build().owner, system_program::id());
+
+        // Other threads keep the built-in default
+        let _config = AccountGenConfig::new().default_owner(program_id).install();
+        let owner = std::thread::spawn(|| AccountBuilder::new().build().owner)
+            .join()
+            .unwrap();
+        assert_eq!(owner, system_program::id());
+    }
+
+    #[test]
+    fn test_scoped_defaults() {
+        use crate::config::{AccountGenConfig, with_defaults};
+
+        let (program_id, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let owners = with_defaults(|ctx| {
+            ctx.owner(program_id).mode(BuildMode::Strict);
+            let outer = AccountBuilder::new().build().owner;
+
+            // Nested scopes start from the enclosing defaults
+            let inner = with_defaults(|ctx| {
+                ctx.owner(other);
+                assert_eq!(AccountGenConfig::current().default_mode, BuildMode::Strict);
+                AccountBuilder::new().build().owner
+            });
+            assert!(AccountBuilder::new().balance(1).try_build().is_err());
+            (outer, inner, AccountBuilder::new().build().owner)
+        });
+        assert_eq!(owners, (program_id, other, program_id));
+        assert_eq!(AccountGenConfig::current(), AccountGenConfig::new());
+
+        // Rent exemption alone leaves the other strict checks off
+        with_defaults(|ctx| {
+            ctx.min_balance_rent_exempt();
+            assert!(AccountBuilder::new().balance(1).try_build().is_err());
+            assert!(AccountBuilder::new().balance(0).try_build().is_ok());
+            assert!(
+                AccountBuilder::new()
+                    .balance(1)
+                    .skip_rent_check()
+                    .try_build()
+                    .is_ok()
+            );
+        });
+
+        // A panicking scope still restores the defaults
+        let result = std::panic::catch_unwind(|| {
+            with_defaults(|ctx| {
+                ctx.owner(program_id);
+                panic!("test failed");
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(AccountBuilder::new().build().owner, system_program::id());
+    }
+
+    #[test]
+    fn test_data_borsh_into_reuses_pooled_buffers() {
+        use crate::pool::BufferPool;
+
+        let test_data = TestBorshData {
+            value: 7,
+            name: "pooled".to_string(),
+        };
+        let mut pool = BufferPool::with_max_buffers(1);
+        pool.recycle(Vec::with_capacity(256));
+
+        let buffer = pool.take();
+        let allocation = buffer.as_ptr();
+        let account = AccountBuilder::new()
+            .data_borsh_into(buffer, &test_data)
+            .unwrap()
+            .build();
+        assert_eq!(account.data, borsh::to_vec(&test_data).unwrap());
+        assert_eq!(account.data.as_ptr(), allocation);
+
+        // Recycled buffers come back empty, and a full pool drops the rest
+        pool.recycle_account(account);
+        pool.recycle(vec![1]);
+        assert_eq!(pool.len(), 1);
+        let buffer = pool.take();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.as_ptr(), allocation);
+    }
+
+    #[test]
+    fn test_account_builder_serde_round_trip() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let builder = AccountBuilder::new()
+            .pubkey(pubkey)
+            .owner(owner)
+            .balance(42)
+            .data_raw(vec![1, 2, 3]);
+
+        let json = serde_json::to_value(&builder).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": BUILDER_FORMAT_VERSION,
+                "pubkey": pubkey.to_string(),
+                "lamports": 42,
+                "owner": owner.to_string(),
+                "executable": false,
+                "data": ["AQID", "base64"],
+            })
+        );
+
+        let restored: AccountBuilder = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.build_with_pubkey(), builder.build_with_pubkey());
+    }
+
+    #[test]
+    fn test_account_builder_serde_defaults_and_unknown_fields() {
+        // Only the version is required; later additions are ignored
+        let builder: AccountBuilder =
+            serde_json::from_str(r#"{"version":1,"futureField":true}"#).unwrap();
+        let account = builder.build();
+
+        assert_eq!(account.owner, system_program::id());
+        assert_eq!(account.lamports, Rent::default().minimum_balance(0));
+        assert!(account.data.is_empty());
+    }
+
+    #[test]
+    fn test_account_builder_serde_rejects_unsupported_version() {
+        for json in [r#"{"version":0}"#, r#"{"version":99}"#, r#"{}"#] {
+            assert!(
+                serde_json::from_str::<AccountBuilder>(json).is_err(),
+                "{json}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_account_builder_serde_reads_legacy_format() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let legacy = serde_json::json!({
+            "pubkey": pubkey.to_bytes(),
+            "lamports": 42,
+            "owner": owner.to_bytes(),
+            "executable": false,
+            "rent_epoch": 7,
+            "data": [1, 2, 3],
+        });
+
+        let builder: AccountBuilder = serde_json::from_value(legacy).unwrap();
+        let (address, account) = builder.clone().build_with_pubkey();
+        assert_eq!(address, pubkey);
+        assert_eq!(account.owner, owner);
+        assert_eq!(account.lamports, 42);
+        assert_eq!(account.rent_epoch, 7);
+        assert_eq!(account.data, vec![1, 2, 3]);
+
+        // Writing the builder back migrates it to the current format
+        let json = serde_json::to_value(&builder).unwrap();
+        assert_eq!(json["version"], BUILDER_FORMAT_VERSION);
+        assert_eq!(json["rentEpoch"], 7);
+
+        let error = serde_json::from_str::<AccountBuilder>(r#"{"lamports":1}"#).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("unversioned legacy builder format: missing field `executable`"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_account_builder_serde_requires_self_describing_format() {
+        // The version is detected from the field names, which bincode does not write
+        let bytes = bincode::serialize(&AccountBuilder::new().balance(1)).unwrap();
+        assert!(bincode::deserialize::<AccountBuilder>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_simulate_rent_collection_deletes_unpayable_accounts() {
+        use crate::cluster::{RentRegime, simulate_rent_collection};
+
+        let rent = Rent::default();
+        let broke = Pubkey::new_unique();
+        let empty = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+
+        let mut accounts = AccountMap::new();
+        accounts
+            .add_with_builder(
+                broke,
+                AccountBuilder::new().balance(10).data_raw(vec![0; 10]),
+            )
+            .unwrap()
+            .add_with_builder(empty, AccountBuilder::new().balance(0))
+            .unwrap()
+            .add_with_builder(
+                program,
+                AccountBuilder::new()
+                    .balance(1)
+                    .owner(well_known::BPF_LOADER)
+                    .executable(true)
+                    .data_raw(vec![1]),
+            )
+            .unwrap();
+
+        let report = simulate_rent_collection(&mut accounts, 1, &rent, RentRegime::Collecting);
+
+        let mut removed = vec![broke, empty];
+        removed.sort();
+        assert_eq!(report.removed, removed);
+        assert_eq!(report.collected, 10);
+        assert!(report.charged.is_empty());
+        // Executable accounts are never charged
+        assert_eq!(accounts.get_account(&program).unwrap().lamports, 1);
+    }
+
+    #[test]
+    fn test_simulate_rent_collection_handles_huge_epoch_counts() {
+        use crate::cluster::{RentRegime, simulate_rent_collection};
+
+        let payer = Pubkey::new_unique();
+        let mut accounts = AccountMap::new();
+        accounts
+            .add_with_builder(
+                payer,
+                AccountBuilder::new().balance(10_000).data_raw(vec![0; 10]),
+            )
+            .unwrap();
+
+        let report = simulate_rent_collection(
+            &mut accounts,
+            u64::MAX,
+            &Rent::default(),
+            RentRegime::Collecting,
+        );
+
+        assert_eq!(report.removed, vec![payer]);
+        assert_eq!(report.collected, 10_000);
+    }
+
+    #[test]
+    fn test_account_editor_patch_bytes() {
+        let mut account = AccountBuilder::new().data_raw(vec![0; 8]).build();
+        let mut editor = AccountEditor::new(&mut account);
+        editor.patch_bytes(2, &[1, 2]).unwrap();
+        assert!(editor.patch_bytes(7, &[1, 2]).is_err());
+        assert!(editor.patch_bytes(usize::MAX, &[1]).is_err());
+        // Fields need a layout
+        assert!(editor.field("value").is_err());
+        assert_eq!(account.data, vec![0, 0, 1, 2, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_raw_data_accessors() {
+        use serialization::raw::RawData;
+
+        let mut account = AccountBuilder::new().data_raw(vec![0; 40]).build();
+        let pubkey = Pubkey::new_unique();
+        let mut editor = AccountEditor::new(&mut account);
+        editor.write_u16_be(0, 0x0102).unwrap();
+        editor.write_i32_le(2, -2).unwrap();
+        editor.write_bool(6, true).unwrap();
+        editor.write_pubkey(8, &pubkey).unwrap();
+        assert!(editor.write_u64_le(33, 1).is_err());
+        assert!(editor.read_bytes(usize::MAX, 2).is_err());
+
+        assert_eq!(account.data[..7], [1, 2, 0xfe, 0xff, 0xff, 0xff, 1]);
+        assert_eq!(account.data.read_u16_le(0).unwrap(), 0x0201);
+        assert_eq!(account.data.read_i32_le(2).unwrap(), -2);
+        assert!(account.data.read_bool(6).unwrap());
+        assert!(account.data.read_bool(1).is_err());
+        assert_eq!(account.data.read_pubkey(8).unwrap(), pubkey);
+        assert_eq!(
+            account.data.read_u64_be(32).unwrap().to_be_bytes(),
+            pubkey.as_ref()[24..]
+        );
+    }
+
+    #[test]
+    fn test_account_editor_patch_nested_fields() {
+        use serialization::schema::FixtureSchema;
+
+        let schema: FixtureSchema = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "types": ["Vault"],
+            "definitions": {
+                "Vault": {"kind": "struct", "fields": [
+                    {"name": "entries", "type": "Vec<u16>"},
+                    {"name": "status", "type": "Status"}
+                ]},
+                "Vec<u16>": {"kind": "sequence", "lengthWidth": 4, "lengthRange": [0, 4294967295u64], "elements": "u16"},
+                "Status": {"kind": "enum", "tagWidth": 1, "variants": [
+                    {"discriminant": 0, "name": "Idle", "type": "()"},
+                    {"discriminant": 1, "name": "Active", "type": "Active"}
+                ]},
+                "Active": {"kind": "struct", "fields": [{"name": "since", "type": "i64"}]},
+                "()": {"kind": "primitive", "size": 0},
+                "u16": {"kind": "primitive", "size": 2},
+                "i64": {"kind": "primitive", "size": 8}
+            }
+        }))
+        .unwrap();
+
+        let data = schema
+            .encode(
+                "Vault",
+                &serde_json::json!({"entries": [1, 2, 3], "status": {"Active": {"since": 5}}}),
+            )
+            .unwrap();
+        let mut account = AccountBuilder::new().data_raw(data).build();
+        let mut editor = AccountEditor::new(&mut account).with_schema(schema.clone(), "Vault");
+
+        editor.patch_field("entries.1", 700u16).unwrap();
+        editor.patch_field("status.Active.since", -9i64).unwrap();
+        assert!(editor.patch_field("status.Idle", ()).is_err());
+        assert!(editor.patch_field("entries.3", 1u16).is_err());
+        assert!(editor.patch_field("missing", 1u16).is_err());
+
+        // Shrinking a sequence moves the fields after it
+        editor.patch_field("entries", [4u16]).unwrap();
+        assert_eq!(
+            editor.field("").unwrap(),
+            serde_json::json!({"entries": [4], "status": {"Active": {"since": -9}}})
+        );
+        assert_eq!(account.data.len(), 4 + 2 + 1 + 8);
+    }
+
+    #[test]
+    fn test_account_map_stats() {
+        let program_id = Pubkey::new_unique();
+        let mut accounts = AccountMap::new();
+        accounts
+            .add_with_builder(
+                Pubkey::new_unique(),
+                AccountBuilder::new().balance(1_000_000_000),
+            )
+            .unwrap()
+            .add_with_builder(
+                Pubkey::new_unique(),
+                AccountBuilder::new()
+                    .balance(5)
+                    .owner(program_id)
+                    .data_raw(vec![0; 200]),
+            )
+            .unwrap()
+            .add_with_builder(
+                Pubkey::new_unique(),
+                AccountBuilder::new()
+                    .balance(7)
+                    .owner(program_id)
+                    .data_raw(vec![0; 2_000_000]),
+            )
+            .unwrap();
+
+        let stats = accounts.stats();
+        assert_eq!(stats.total.accounts, 3);
+        assert_eq!(stats.total.lamports, 1_000_000_012);
+        assert_eq!(stats.total.data_bytes, 2_000_200);
+
+        let program = &stats.owners[&program_id];
+        assert_eq!(program.accounts, 2);
+        assert_eq!(program.lamports, 12);
+        assert_eq!(program.size_histogram, vec![0, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(stats.owners[&system_program::id()].size_histogram[0], 1);
+
+        let report = stats.to_string();
+        assert!(report.starts_with("3 accounts, 1.000000012 SOL, 2000200 bytes of data\n"));
+        // Owners with more accounts come first
+        let program_at = report.find(&program_id.to_string()).unwrap();
+        let system_at = report.find("(System Program)").unwrap();
+        assert!(program_at < system_at);
+        assert!(report.contains("  sizes:        129 B - 1 KiB: 1, > 1 MiB: 1\n"));
+    }
+
+    #[test]
+    fn test_account_map_group_by_discriminator() {
+        use crate::extensions::anchor::create_anchor_account;
+        use crate::extensions::idl::Idl;
+        use crate::stats::DiscriminatorRegistry;
+
+        let (program_a, program_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = AccountMap::new();
+        for program_id in [program_a, program_a, program_b] {
+            accounts.set_account(
+                Pubkey::new_unique(),
+                create_anchor_account("Pool", program_id, 7u64, 1_000).unwrap(),
+            );
+        }
+        accounts.set_account(
+            Pubkey::new_unique(),
+            AccountBuilder::new()
+                .owner(program_a)
+                .data_raw(vec![9; 10])
+                .build(),
+        );
+        accounts.set_account(
+            Pubkey::new_unique(),
+            AccountBuilder::new().data_raw(vec![1, 2]).build(),
+        );
+
+        let groups = accounts.group_by_discriminator();
+        let pool = extensions::anchor::get_account_discriminator("Pool");
+        assert_eq!(groups.groups.len(), 2);
+        assert_eq!(groups.count(&pool), 3);
+        assert_eq!(groups.groups[&pool].data_bytes, 3 * 16);
+        assert_eq!(groups.groups[&pool].owners.len(), 2);
+        assert_eq!(groups.count(&[9; 8]), 1);
+        assert_eq!(groups.short, 1);
+        assert!(groups.by_type().is_empty());
+
+        // An IDL names its account types, with explicit discriminators
+        let idl = Idl::from_json(
+            r#"{ "accounts": [{ "name": "Pool" }, { "name": "Legacy", "discriminator": [9, 9, 9, 9, 9, 9, 9, 9] }] }"#,
+        )
+        .unwrap();
+        let groups = groups.named(&DiscriminatorRegistry::from_idl(&idl));
+        assert_eq!(groups.by_type()["Pool"], 3);
+        assert_eq!(groups.by_type()["Legacy"], 1);
+        assert_eq!(groups.unnamed(), 0);
+
+        let report = groups.to_string();
+        assert!(report.starts_with("5 accounts in 2 discriminator groups\n"));
+        assert!(report.contains(&format!(
+            "  {}  Pool    3 accounts, 48 bytes\n",
+            hex::encode(pool)
+        )));
+        assert!(report.ends_with("  1 accounts have less than 8 bytes of data"));
+    }
+
+    #[test]
+    fn test_ring_buffer_wraps_records() {
+        let data = RingBufferBuilder::new(7u16)
+            .header_size(8)
+            .record_size(4)
+            .capacity(3)
+            .head(2)
+            .records([1u16, 2, 3, 4])
+            .build()
+            .unwrap();
+        assert_eq!(data.len(), 8 + 3 * 4);
+        assert_eq!(data[..8], [7, 0, 0, 0, 0, 0, 0, 0]);
+        // Slots 2, 0, 1 then 2 again: the oldest record is overwritten
+        assert_eq!(data[8..], [2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0]);
+
+        let builder = RingBufferBuilder::new(()).record_size(16);
+        assert_eq!(builder.record_offset(3), 48);
+
+        let too_large = RingBufferBuilder::new(()).record_size(4).records([0u64]);
+        assert!(matches!(
+            too_large.build(),
+            Err(AccountGenError::InvalidDataFormat(_))
+        ));
+        let bad_head = RingBufferBuilder::new(()).capacity(2).head(2);
+        assert!(bad_head.build().is_err());
+        let huge = RingBufferBuilder::new(())
+            .record_size(1 << 20)
+            .capacity(1 << 10);
+        assert!(matches!(
+            huge.build(),
+            Err(AccountGenError::DataTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sol_denominations() {
+        assert_eq!(Sol(3).lamports(), 3 * units::LAMPORTS_PER_SOL);
+        assert_eq!(Sol(1.5).lamports(), 1_500_000_000);
+        // Rounds to the nearest lamport rather than truncating
+        assert_eq!(Sol(0.1 + 0.2).lamports(), 300_000_000);
+        assert_eq!(sol!(0.000_000_001_4), 1);
+
+        let account = AccountBuilder::new().balance(Sol(2)).build();
+        assert_eq!(account.lamports, sol!(2));
+        let (_, _, pda) = crate::extensions::anchor::create_anchor_pda(
+            "vault",
+            Pubkey::new_unique(),
+            &[b"vault"],
+            0u8,
+            Sol(0.5),
+        )
+        .unwrap();
+        assert_eq!(pda.lamports, 500_000_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a valid lamport balance")]
+    fn test_sol_rejects_negative_amounts() {
+        Sol(-1.0).lamports();
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows")]
+    fn test_sol_rejects_overflow() {
+        Sol(u64::MAX).lamports();
+    }
+
+    #[test]
+    fn test_create_seeded() {
+        let base = Pubkey::new_unique();
+        let (address, account) = AccountBuilder::new()
+            .balance(500)
+            .create_seeded(&base, "wallet", &system_program::id())
+            .unwrap();
+        assert_eq!(
+            address,
+            Pubkey::create_with_seed(&base, "wallet", &system_program::id()).unwrap()
+        );
+        assert_eq!(account.owner, system_program::id());
+        assert_eq!(account.lamports, 500);
+
+        let long_seed = "s".repeat(33);
+        assert!(matches!(
+            AccountBuilder::new().create_seeded(&base, &long_seed, &system_program::id()),
+            Err(AccountGenError::InvalidDataFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_prune_unreachable_follows_references_and_owners() {
+        let program_id = Pubkey::new_unique();
+        let [root, child, grandchild, orphan] = std::array::from_fn(|_| Pubkey::new_unique());
+        let refers_to = |target: Pubkey| {
+            AccountBuilder::new()
+                .owner(program_id)
+                .data_raw(target.to_bytes().to_vec())
+        };
+
+        let mut accounts = AccountMap::new();
+        accounts.add_with_builder(root, refers_to(child)).unwrap();
+        accounts
+            .add_with_builder(child, refers_to(grandchild))
+            .unwrap();
+        // A cycle back to the root must not loop forever
+        accounts
+            .add_with_builder(grandchild, refers_to(root))
+            .unwrap();
+        accounts.add_with_builder(orphan, refers_to(root)).unwrap();
+        accounts
+            .add_with_builder(program_id, AccountBuilder::new())
+            .unwrap();
+        let updates = accounts.subscribe();
+
+        let removed = accounts.prune_unreachable([root], |account| {
+            Pubkey::try_from(account.data.as_slice()).ok()
+        });
+
+        assert_eq!(removed, 1);
+        assert_eq!(accounts.len(), 4);
+        assert!(accounts.get_account(&orphan).is_none());
+        assert!(accounts.get_account(&program_id).is_some());
+        assert_eq!(updates.drain().len(), 1);
+    }
+
+    #[test]
+    fn test_remap_pubkeys_swaps_and_follows_idl() {
+        use crate::extensions::anchor::create_anchor_account;
+        use crate::extensions::idl::Idl;
+        use crate::references::PubkeyExtractor;
+        use std::collections::HashMap;
+
+        let idl = Idl::from_json(
+            r#"{"accounts": [{"name": "Config", "type": {"kind": "struct", "fields": [
+                {"name": "admin", "type": "pubkey"},
+                {"name": "blob", "type": {"array": ["u8", 32]}}
+            ]}}]}"#,
+        )
+        .unwrap();
+        let (old_program, new_program) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let [config, admin, other] = std::array::from_fn(|_| Pubkey::new_unique());
+        // The blob happens to hold the admin's bytes, but is not a pubkey
+        let account =
+            create_anchor_account("Config", old_program, (admin, admin.to_bytes()), 1).unwrap();
+
+        let mut accounts = AccountMap::new();
+        accounts.set_account(config, account);
+        accounts.set_account(admin, AccountBuilder::new().balance(1).build());
+        accounts.set_account(other, AccountBuilder::new().balance(2).build());
+
+        // Swapping two addresses must keep both accounts
+        let mapping = HashMap::from([(old_program, new_program), (admin, other), (other, admin)]);
+        let extractor = PubkeyExtractor::new().idl(old_program, &idl).unwrap();
+        accounts.remap_pubkeys_with(&mapping, &extractor);
+
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts.get_account(&other).unwrap().lamports, 1);
+        assert_eq!(accounts.get_account(&admin).unwrap().lamports, 2);
+        let config = accounts.get_account(&config).unwrap();
+        assert_eq!(config.owner, new_program);
+        assert_eq!(&config.data[8..40], other.as_ref());
+        assert_eq!(&config.data[40..72], admin.as_ref());
+    }
 }
 
 /// Creates an account with the given pubkey and properties.
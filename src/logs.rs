@@ -0,0 +1,526 @@
+//! Capturing and inspecting program logs.
+//!
+//! The runtime reports what a transaction did as a flat list of log lines.
+//! [`Logs`] parses those lines into [`LogEntry`] values that record which
+//! program emitted each line and at what invocation depth, so tests can
+//! assert on messages and decode Anchor events instead of matching raw
+//! strings.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::logs::{LogKind, Logs};
+//!
+//! let logs = Logs::from_messages(vec![
+//!     "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+//!     "Program log: hello".to_string(),
+//!     "Program 11111111111111111111111111111111 success".to_string(),
+//! ]);
+//!
+//! assert_eq!(logs.messages().collect::<Vec<_>>(), vec!["hello"]);
+//! assert_eq!(logs.entries()[1].depth, 1);
+//! assert!(matches!(logs.entries()[2].kind, LogKind::Success));
+//! ```
+
+use crate::AccountGenError;
+use crate::compute::UPDATE_BASELINES_VAR;
+use crate::extensions::anchor::get_event_discriminator;
+use crate::well_known;
+use borsh::BorshDeserialize;
+use solana_program_test::{BanksClient, BanksClientError};
+use solana_pubkey::Pubkey;
+use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction_error::TransactionResult;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// What a single log line reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogKind {
+    /// The program was invoked.
+    Invoke,
+    /// A `msg!` message.
+    Message(String),
+    /// Binary data emitted with `sol_log_data`, such as Anchor events.
+    Data(Vec<Vec<u8>>),
+    /// Data set with `set_return_data`.
+    Return(Vec<u8>),
+    /// Compute units consumed by the invocation.
+    Consumed {
+        /// Units used.
+        units: u64,
+        /// Units available to the invocation.
+        limit: u64,
+    },
+    /// The invocation succeeded.
+    Success,
+    /// The invocation failed with the given error.
+    Failed(String),
+    /// A line that does not follow any known format.
+    Other(String),
+}
+
+/// A parsed log line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Invocation depth, starting at 1 for top-level instructions and 0
+    /// outside of any invocation.
+    pub depth: usize,
+    /// The program executing when the line was written.
+    pub program_id: Option<Pubkey>,
+    /// What the line reported.
+    pub kind: LogKind,
+}
+
+/// The logs of a transaction, both raw and parsed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Logs {
+    raw: Vec<String>,
+    entries: Vec<LogEntry>,
+}
+
+impl Logs {
+    /// Parses log lines as returned by the runtime.
+    pub fn from_messages(raw: Vec<String>) -> Self {
+        let entries = parse_logs(&raw);
+        Self { raw, entries }
+    }
+
+    /// Returns the unparsed log lines.
+    pub fn raw(&self) -> &[String] {
+        &self.raw
+    }
+
+    /// Returns the parsed log entries.
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    /// Returns every `msg!` message, in order.
+    pub fn messages(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().filter_map(|entry| match &entry.kind {
+            LogKind::Message(message) => Some(message.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Returns every `sol_log_data` payload, in order.
+    pub fn data(&self) -> impl Iterator<Item = &[Vec<u8>]> {
+        self.entries.iter().filter_map(|entry| match &entry.kind {
+            LogKind::Data(data) => Some(data.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Returns true if any raw log line contains `needle`.
+    pub fn contains(&self, needle: &str) -> bool {
+        self.raw.iter().any(|line| line.contains(needle))
+    }
+
+    /// Decodes every Anchor event with the given name.
+    ///
+    /// Anchor emits events as `Program data:` lines holding the 8-byte
+    /// discriminator of `event:<name>` followed by the Borsh-encoded event.
+    /// Data lines with other discriminators are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a matching event cannot be decoded as `T`.
+    pub fn events<T: BorshDeserialize>(&self, event_name: &str) -> Result<Vec<T>, AccountGenError> {
+        let discriminator = get_event_discriminator(event_name);
+        self.data()
+            .filter_map(|data| data.first())
+            .filter(|payload| payload.starts_with(&discriminator))
+            .map(|payload| {
+                T::try_from_slice(&payload[8..]).map_err(AccountGenError::DeserializationError)
+            })
+            .collect()
+    }
+
+    /// Decodes every Anchor event with the given name, panicking if there
+    /// are none or any fails to decode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::extensions::anchor::get_event_discriminator;
+    /// use solana_accountgen::logs::Logs;
+    /// use borsh::{BorshDeserialize, BorshSerialize};
+    ///
+    /// #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    /// struct ScoreChanged { score: u64 }
+    ///
+    /// let mut payload = get_event_discriminator("ScoreChanged").to_vec();
+    /// payload.extend(borsh::to_vec(&ScoreChanged { score: 7 }).unwrap());
+    ///
+    /// let logs = Logs::from_messages(vec![
+    ///     format!("Program data: {}", base64::encode(payload)),
+    /// ]);
+    /// assert_eq!(
+    ///     logs.expect_events::<ScoreChanged>("ScoreChanged"),
+    ///     vec![ScoreChanged { score: 7 }]
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn expect_events<T: BorshDeserialize>(&self, event_name: &str) -> Vec<T> {
+        match self.events(event_name) {
+            Ok(events) if !events.is_empty() => events,
+            Ok(_) => panic!("no {} events were emitted\nlogs:\n{}", event_name, self),
+            Err(e) => panic!(
+                "failed to decode {} event: {}\nlogs:\n{}",
+                event_name, e, self
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Logs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.raw
+            .iter()
+            .try_for_each(|line| writeln!(f, "  {}", line))
+    }
+}
+
+/// Processes a transaction and captures its logs.
+///
+/// Returns the transaction result together with the logs, so failed
+/// transactions can be inspected as well.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::logs::process_transaction_with_logs;
+/// use solana_program_test::ProgramTest;
+/// # use solana_transaction::Transaction;
+///
+/// # async fn run(transaction: Transaction) {
+/// let (mut banks_client, _payer, _) = ProgramTest::default().start().await;
+/// let (result, logs) = process_transaction_with_logs(&mut banks_client, transaction)
+///     .await
+///     .unwrap();
+/// assert!(result.is_ok(), "transaction failed:\n{}", logs);
+/// # }
+/// ```
+pub async fn process_transaction_with_logs(
+    banks_client: &mut BanksClient,
+    transaction: impl Into<VersionedTransaction>,
+) -> Result<(TransactionResult<()>, Logs), BanksClientError> {
+    let outcome = banks_client
+        .process_transaction_with_metadata(transaction)
+        .await?;
+    let raw = outcome
+        .metadata
+        .map(|metadata| metadata.log_messages)
+        .unwrap_or_default();
+    Ok((outcome.result, Logs::from_messages(raw)))
+}
+
+/// Rewrites log lines that change from run to run, so that logs can be
+/// compared with a snapshot.
+///
+/// By default, pubkeys other than those of well-known programs become
+/// `<pubkey N>`, numbered by first appearance, and compute unit counts
+/// become `<cu>`. Pubkeys given a name with [`name`](Self::name) become
+/// `<name>` instead. `Program data:` and `Program return:` lines are kept
+/// as they are.
+#[derive(Debug, Clone)]
+pub struct LogNormalizer {
+    strip_pubkeys: bool,
+    strip_compute_units: bool,
+    names: HashMap<Pubkey, String>,
+}
+
+impl Default for LogNormalizer {
+    fn default() -> Self {
+        Self {
+            strip_pubkeys: true,
+            strip_compute_units: true,
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl LogNormalizer {
+    /// Creates a normalizer that strips pubkeys and compute units.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether pubkeys are replaced by placeholders.
+    pub fn strip_pubkeys(mut self, strip: bool) -> Self {
+        self.strip_pubkeys = strip;
+        self
+    }
+
+    /// Sets whether compute unit counts are replaced by `<cu>`.
+    pub fn strip_compute_units(mut self, strip: bool) -> Self {
+        self.strip_compute_units = strip;
+        self
+    }
+
+    /// Writes `pubkey` as `<name>`, whether or not other pubkeys are
+    /// stripped.
+    pub fn name(mut self, pubkey: Pubkey, name: impl Into<String>) -> Self {
+        self.names.insert(pubkey, name.into());
+        self
+    }
+
+    /// Returns the normalized log lines, each ending in a newline.
+    pub fn normalize(&self, logs: &Logs) -> String {
+        let mut placeholders = self.names.clone();
+        let mut normalized = String::new();
+        for line in logs.raw() {
+            let mut line =
+                if line.starts_with("Program data: ") || line.starts_with("Program return: ") {
+                    line.clone()
+                } else {
+                    self.replace_pubkeys(line, &mut placeholders)
+                };
+            if self.strip_compute_units {
+                line = strip_compute_units(&line);
+            }
+            normalized.push_str(&line);
+            normalized.push('\n');
+        }
+        normalized
+    }
+
+    fn replace_pubkeys(&self, line: &str, placeholders: &mut HashMap<Pubkey, String>) -> String {
+        let is_base58 = |c: char| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l');
+        let mut out = String::with_capacity(line.len());
+        let mut rest = line;
+        while let Some(start) = rest.find(is_base58) {
+            out.push_str(&rest[..start]);
+            let word = &rest[start..];
+            let end = word.find(|c| !is_base58(c)).unwrap_or(word.len());
+            let (word, tail) = word.split_at(end);
+            rest = tail;
+
+            let pubkey = (32..=44)
+                .contains(&word.len())
+                .then(|| Pubkey::from_str(word).ok())
+                .flatten()
+                .filter(|pubkey| well_known::name_for(pubkey).is_none());
+            let placeholder = match pubkey {
+                Some(pubkey) if self.strip_pubkeys || self.names.contains_key(&pubkey) => {
+                    let next = placeholders.len() - self.names.len() + 1;
+                    Some(
+                        placeholders
+                            .entry(pubkey)
+                            .or_insert_with(|| format!("pubkey {}", next))
+                            .clone(),
+                    )
+                }
+                _ => None,
+            };
+            match placeholder {
+                Some(placeholder) => write!(out, "<{}>", placeholder).unwrap(),
+                None => out.push_str(word),
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// Replaces the counts in `consumed X of Y compute units` and
+/// `consumption: X units remaining` lines with `<cu>`.
+fn strip_compute_units(line: &str) -> String {
+    if let Some((head, rest)) = line.split_once(" consumed ")
+        && let Some(counts) = rest.strip_suffix(" compute units")
+        && counts.split_once(" of ").is_some()
+    {
+        return format!("{} consumed <cu> of <cu> compute units", head);
+    }
+    if let Some((head, rest)) = line.split_once("consumption: ")
+        && rest.ends_with(" units remaining")
+    {
+        return format!("{}consumption: <cu> units remaining", head);
+    }
+    line.to_string()
+}
+
+/// Compares normalized logs with the snapshot file at `path`, or writes
+/// them into it.
+///
+/// The file is written, along with its directory, only when the
+/// [`UPDATE_BASELINES_VAR`] environment variable is set, so that a change
+/// in what a program logs shows up as a diff of the file in review, and a
+/// deleted or misnamed snapshot cannot make the check pass.
+/// [`assert_logs_snapshot!`](crate::assert_logs_snapshot) panics with the
+/// error instead.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::ConstraintViolation`] with a line diff if
+/// the logs differ from the snapshot, or if the snapshot does not exist and
+/// is not being updated, or an error if the file cannot be read or written.
+pub fn check_logs_snapshot<P: AsRef<Path>>(
+    logs: &Logs,
+    path: P,
+    normalizer: &LogNormalizer,
+) -> Result<(), AccountGenError> {
+    let path = path.as_ref();
+    let actual = normalizer.normalize(logs);
+    if std::env::var_os(UPDATE_BASELINES_VAR).is_some() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, actual)?;
+        return Ok(());
+    }
+    if !path.exists() {
+        return Err(AccountGenError::ConstraintViolation(format!(
+            "snapshot missing; rerun with {}=1 to write {}",
+            UPDATE_BASELINES_VAR,
+            path.display()
+        )));
+    }
+
+    let expected = std::fs::read_to_string(path)?;
+    if expected == actual {
+        return Ok(());
+    }
+    Err(AccountGenError::ConstraintViolation(format!(
+        "logs differ from the snapshot {} (- snapshot, + actual):\n{}set {} to update the snapshot",
+        path.display(),
+        line_diff(&expected, &actual),
+        UPDATE_BASELINES_VAR
+    )))
+}
+
+/// Diffs two texts line by line, marking removed lines with `-` and added
+/// ones with `+`.
+fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Lengths of the longest common subsequences of the suffixes
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            writeln!(diff, "  {}", old[i]).unwrap();
+            (i, j) = (i + 1, j + 1);
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            writeln!(diff, "- {}", old[i]).unwrap();
+            i += 1;
+        } else {
+            writeln!(diff, "+ {}", new[j]).unwrap();
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// Parses runtime log lines into structured entries.
+pub fn parse_logs<S: AsRef<str>>(lines: &[S]) -> Vec<LogEntry> {
+    let mut stack: Vec<Pubkey> = Vec::new();
+    let mut entries = Vec::with_capacity(lines.len());
+
+    for line in lines {
+        let line = line.as_ref();
+        let current = stack.last().copied();
+        let depth = stack.len();
+        let entry = |kind| LogEntry {
+            depth,
+            program_id: current,
+            kind,
+        };
+
+        if let Some(message) = line.strip_prefix("Program log: ") {
+            entries.push(entry(LogKind::Message(message.to_string())));
+        } else if let Some(encoded) = line.strip_prefix("Program data: ") {
+            let data: Result<Vec<_>, _> = encoded.split_whitespace().map(base64::decode).collect();
+            entries.push(entry(match data {
+                Ok(data) => LogKind::Data(data),
+                Err(_) => LogKind::Other(line.to_string()),
+            }));
+        } else if let Some(rest) = line.strip_prefix("Program return: ") {
+            let data = rest
+                .split_once(' ')
+                .and_then(|(_, encoded)| base64::decode(encoded).ok());
+            entries.push(entry(match data {
+                Some(data) => LogKind::Return(data),
+                None => LogKind::Other(line.to_string()),
+            }));
+        } else if let Some(parsed) = parse_program_line(line) {
+            match parsed {
+                (program_id, ProgramLine::Invoke(depth)) => {
+                    stack.push(program_id);
+                    entries.push(LogEntry {
+                        depth,
+                        program_id: Some(program_id),
+                        kind: LogKind::Invoke,
+                    });
+                }
+                (program_id, ProgramLine::Consumed(units, limit)) => {
+                    entries.push(LogEntry {
+                        depth,
+                        program_id: Some(program_id),
+                        kind: LogKind::Consumed { units, limit },
+                    });
+                }
+                (program_id, ProgramLine::Exit(kind)) => {
+                    stack.pop();
+                    entries.push(LogEntry {
+                        depth,
+                        program_id: Some(program_id),
+                        kind,
+                    });
+                }
+            }
+        } else {
+            entries.push(entry(LogKind::Other(line.to_string())));
+        }
+    }
+
+    entries
+}
+
+enum ProgramLine {
+    Invoke(usize),
+    Consumed(u64, u64),
+    Exit(LogKind),
+}
+
+/// Parses `Program <id> ...` lines that mark invocation boundaries.
+fn parse_program_line(line: &str) -> Option<(Pubkey, ProgramLine)> {
+    let rest = line.strip_prefix("Program ")?;
+    let (program_id, rest) = rest.split_once(' ')?;
+    let program_id = Pubkey::from_str(program_id).ok()?;
+
+    let parsed = if let Some(depth) = rest
+        .strip_prefix("invoke [")
+        .and_then(|depth| depth.strip_suffix(']'))
+    {
+        ProgramLine::Invoke(depth.parse().ok()?)
+    } else if rest == "success" {
+        ProgramLine::Exit(LogKind::Success)
+    } else if let Some(error) = rest.strip_prefix("failed: ") {
+        ProgramLine::Exit(LogKind::Failed(error.to_string()))
+    } else if let Some(units) = rest
+        .strip_prefix("consumed ")
+        .and_then(|units| units.strip_suffix(" compute units"))
+    {
+        let (units, limit) = units.split_once(" of ")?;
+        ProgramLine::Consumed(units.parse().ok()?, limit.parse().ok()?)
+    } else {
+        return None;
+    };
+    Some((program_id, parsed))
+}
@@ -0,0 +1,397 @@
+//! Address lookup tables and checking how v0 transactions use them.
+//!
+//! A v0 transaction loads some of its accounts from lookup tables by index
+//! instead of listing them. A wrong index, a table missing from the test's
+//! accounts or an address both listed and loaded only shows up as an
+//! opaque error when the transaction is sent. [`LookupTable`] lays out table
+//! accounts to put in an [`AccountMap`], and [`check_lookups`] resolves a
+//! transaction's lookups against those tables before submission, reporting
+//! which account metas come from which table and every lookup that would
+//! fail to resolve.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::AccountMap;
+//! use solana_accountgen::lookup_tables::{LookupTable, check_lookups, lookup_table_accounts};
+//! use solana_hash::Hash;
+//! use solana_instruction::{AccountMeta, Instruction};
+//! use solana_message::{VersionedMessage, v0};
+//! use solana_pubkey::Pubkey;
+//! use solana_transaction::versioned::VersionedTransaction;
+//!
+//! let (table, vault, payer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+//! let mut accounts = AccountMap::new();
+//! accounts.set_account(table, LookupTable::new(None, vec![vault]).to_account().unwrap());
+//!
+//! let instruction = Instruction::new_with_bytes(
+//!     Pubkey::new_unique(),
+//!     &[],
+//!     vec![AccountMeta::new(vault, false)],
+//! );
+//! let message = v0::Message::try_compile(
+//!     &payer,
+//!     &[instruction],
+//!     &lookup_table_accounts(&accounts),
+//!     Hash::default(),
+//! )
+//! .unwrap();
+//! let transaction = VersionedTransaction {
+//!     signatures: vec![Default::default()],
+//!     message: VersionedMessage::V0(message),
+//! };
+//!
+//! let report = check_lookups(&transaction, &accounts);
+//! assert!(report.is_ok());
+//! assert_eq!(report.loaded[0].pubkey, vault);
+//! assert!(report.loaded[0].writable);
+//! ```
+
+use crate::well_known::ADDRESS_LOOKUP_TABLE_PROGRAM;
+use crate::{AccountBuilder, AccountGenError, AccountMap};
+use solana_account::Account;
+use solana_message::AddressLookupTableAccount;
+use solana_pubkey::Pubkey;
+use solana_transaction::versioned::VersionedTransaction;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Size of a lookup table's header, before its addresses.
+pub const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+/// The most addresses a lookup table can hold.
+pub const LOOKUP_TABLE_MAX_ADDRESSES: usize = 256;
+
+/// The state tag of an initialized lookup table.
+const LOOKUP_TABLE_TAG: u32 = 1;
+
+/// An unpacked address lookup table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupTable {
+    /// The slot the table was deactivated in, or `u64::MAX` if it is
+    /// active.
+    pub deactivation_slot: u64,
+    /// The slot the table was last extended in.
+    pub last_extended_slot: u64,
+    /// The number of addresses before the last extension.
+    pub last_extended_slot_start_index: u8,
+    /// Who may extend, freeze or close the table; `None` if it is frozen.
+    pub authority: Option<Pubkey>,
+    /// The addresses, by index.
+    pub addresses: Vec<Pubkey>,
+}
+
+impl LookupTable {
+    /// Creates an active table holding `addresses`, last extended at slot
+    /// 0 so that every address can be loaded.
+    pub fn new(authority: Option<Pubkey>, addresses: Vec<Pubkey>) -> Self {
+        Self {
+            deactivation_slot: u64::MAX,
+            last_extended_slot: 0,
+            last_extended_slot_start_index: 0,
+            authority,
+            addresses,
+        }
+    }
+
+    /// Returns true if the table has not been deactivated.
+    pub fn is_active(&self) -> bool {
+        self.deactivation_slot == u64::MAX
+    }
+
+    /// Packs the table in the lookup table program's layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::ConstraintViolation`] if the table holds
+    /// more than [`LOOKUP_TABLE_MAX_ADDRESSES`] addresses.
+    pub fn pack(&self) -> Result<Vec<u8>, AccountGenError> {
+        if self.addresses.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+            return Err(AccountGenError::ConstraintViolation(format!(
+                "a lookup table holds at most {} addresses, not {}",
+                LOOKUP_TABLE_MAX_ADDRESSES,
+                self.addresses.len()
+            )));
+        }
+        let mut data = Vec::with_capacity(LOOKUP_TABLE_META_SIZE + self.addresses.len() * 32);
+        data.extend_from_slice(&LOOKUP_TABLE_TAG.to_le_bytes());
+        data.extend_from_slice(&self.deactivation_slot.to_le_bytes());
+        data.extend_from_slice(&self.last_extended_slot.to_le_bytes());
+        data.push(self.last_extended_slot_start_index);
+        match self.authority {
+            Some(authority) => {
+                data.push(1);
+                data.extend_from_slice(authority.as_ref());
+            }
+            None => data.push(0),
+        }
+        data.resize(LOOKUP_TABLE_META_SIZE, 0);
+        for address in &self.addresses {
+            data.extend_from_slice(address.as_ref());
+        }
+        Ok(data)
+    }
+
+    /// Unpacks a table from account data.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the data is not an
+    /// initialized lookup table.
+    pub fn unpack(data: &[u8]) -> Result<Self, AccountGenError> {
+        if data.len() < LOOKUP_TABLE_META_SIZE {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "lookup table data is {} bytes, expected at least {}",
+                data.len(),
+                LOOKUP_TABLE_META_SIZE
+            )));
+        }
+        let tag = u32::from_le_bytes(data[0..4].try_into().expect("4 bytes"));
+        if tag != LOOKUP_TABLE_TAG {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "lookup table state is {tag}, not an initialized table"
+            )));
+        }
+        if !(data.len() - LOOKUP_TABLE_META_SIZE).is_multiple_of(32) {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "lookup table addresses take {} bytes, not a multiple of 32",
+                data.len() - LOOKUP_TABLE_META_SIZE
+            )));
+        }
+        let authority = match data[21] {
+            0 => None,
+            1 => Some(Pubkey::new_from_array(
+                data[22..54].try_into().expect("32 bytes"),
+            )),
+            other => {
+                return Err(AccountGenError::InvalidDataFormat(format!(
+                    "invalid lookup table authority tag {other}"
+                )));
+            }
+        };
+        Ok(Self {
+            deactivation_slot: u64::from_le_bytes(data[4..12].try_into().expect("8 bytes")),
+            last_extended_slot: u64::from_le_bytes(data[12..20].try_into().expect("8 bytes")),
+            last_extended_slot_start_index: data[20],
+            authority,
+            addresses: data[LOOKUP_TABLE_META_SIZE..]
+                .chunks_exact(32)
+                .map(|chunk| Pubkey::new_from_array(chunk.try_into().expect("32 bytes")))
+                .collect(),
+        })
+    }
+
+    /// Creates a rent-exempt account holding the table, owned by the
+    /// lookup table program.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::ConstraintViolation`] if the table holds
+    /// more than [`LOOKUP_TABLE_MAX_ADDRESSES`] addresses.
+    pub fn to_account(&self) -> Result<Account, AccountGenError> {
+        AccountBuilder::new()
+            .owner(ADDRESS_LOOKUP_TABLE_PROGRAM)
+            .data_raw(self.pack()?)
+            .try_build()
+    }
+}
+
+/// Returns every active lookup table in `accounts`, in the form
+/// `v0::Message::try_compile` takes.
+///
+/// Accounts owned by the lookup table program that do not unpack are
+/// skipped.
+pub fn lookup_table_accounts(accounts: &AccountMap) -> Vec<AddressLookupTableAccount> {
+    tables(accounts)
+        .into_iter()
+        .filter_map(|(key, table)| table.ok().map(|table| (key, table)))
+        .filter(|(_, table)| table.is_active())
+        .map(|(key, table)| AddressLookupTableAccount {
+            key,
+            addresses: table.addresses,
+        })
+        .collect()
+}
+
+/// Resolves the lookups of `transaction` against the tables in `accounts`.
+///
+/// The report lists each loaded account with the table and index it comes
+/// from, and every lookup that would fail: a table that is missing,
+/// invalid or deactivated, an index past the end of a table, or an address
+/// loaded twice. It also lists static accounts that could have been loaded
+/// from one of the tables but were not, which is allowed but makes the
+/// transaction larger than it needs to be. Signers and invoked programs
+/// cannot be loaded, so they are never listed.
+///
+/// Tables are considered deactivated as soon as their deactivation slot is
+/// set, although the runtime keeps them usable for a short cooldown.
+pub fn check_lookups(transaction: &VersionedTransaction, accounts: &AccountMap) -> LookupReport {
+    let message = &transaction.message;
+    let tables = tables(accounts);
+    let mut report = LookupReport::default();
+    let mut seen: BTreeSet<Pubkey> = message.static_account_keys().iter().copied().collect();
+
+    for lookup in message.address_table_lookups().unwrap_or_default() {
+        let table = match tables.get(&lookup.account_key) {
+            None => {
+                report
+                    .problems
+                    .push(LookupProblem::MissingTable(lookup.account_key));
+                continue;
+            }
+            Some(Err(_)) => {
+                report
+                    .problems
+                    .push(LookupProblem::NotATable(lookup.account_key));
+                continue;
+            }
+            Some(Ok(table)) => table,
+        };
+        if !table.is_active() {
+            report
+                .problems
+                .push(LookupProblem::Deactivated(lookup.account_key));
+        }
+        let indexes = lookup
+            .writable_indexes
+            .iter()
+            .map(|index| (*index, true))
+            .chain(lookup.readonly_indexes.iter().map(|index| (*index, false)));
+        for (index, writable) in indexes {
+            let Some(pubkey) = table.addresses.get(index as usize) else {
+                report.problems.push(LookupProblem::IndexOutOfRange {
+                    table: lookup.account_key,
+                    index,
+                    len: table.addresses.len(),
+                });
+                continue;
+            };
+            if !seen.insert(*pubkey) {
+                report.problems.push(LookupProblem::LoadedTwice(*pubkey));
+            }
+            report.loaded.push(LoadedMeta {
+                pubkey: *pubkey,
+                table: lookup.account_key,
+                index,
+                writable,
+            });
+        }
+    }
+
+    let in_tables: BTreeSet<Pubkey> = lookup_table_accounts(accounts)
+        .into_iter()
+        .flat_map(|table| table.addresses)
+        .collect();
+    let signers = message.header().num_required_signatures as usize;
+    for (i, pubkey) in message.static_account_keys().iter().enumerate() {
+        if i >= signers && !message.is_invoked(i) && in_tables.contains(pubkey) {
+            report.uncompressed.push(*pubkey);
+        }
+    }
+    report
+}
+
+/// Unpacks every account in `accounts` owned by the lookup table program.
+fn tables(accounts: &AccountMap) -> BTreeMap<Pubkey, Result<LookupTable, AccountGenError>> {
+    accounts
+        .iter()
+        .filter(|(_, account)| account.owner == ADDRESS_LOOKUP_TABLE_PROGRAM)
+        .map(|(pubkey, account)| (*pubkey, LookupTable::unpack(&account.data)))
+        .collect()
+}
+
+/// An account meta loaded from a lookup table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedMeta {
+    /// The loaded address.
+    pub pubkey: Pubkey,
+    /// The table it was loaded from.
+    pub table: Pubkey,
+    /// Its index in the table.
+    pub index: u8,
+    /// Whether it was loaded as writable.
+    pub writable: bool,
+}
+
+/// A lookup that would fail to resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupProblem {
+    /// The table is not in the accounts.
+    MissingTable(Pubkey),
+    /// The account is not an initialized lookup table.
+    NotATable(Pubkey),
+    /// The table has been deactivated.
+    Deactivated(Pubkey),
+    /// The index is past the end of the table.
+    IndexOutOfRange {
+        /// The table looked up.
+        table: Pubkey,
+        /// The index looked up.
+        index: u8,
+        /// The number of addresses in the table.
+        len: usize,
+    },
+    /// The address is already a static account or was loaded before.
+    LoadedTwice(Pubkey),
+}
+
+impl fmt::Display for LookupProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTable(table) => write!(f, "lookup table {} is missing", table),
+            Self::NotATable(table) => write!(f, "{} is not a lookup table", table),
+            Self::Deactivated(table) => write!(f, "lookup table {} is deactivated", table),
+            Self::IndexOutOfRange { table, index, len } => write!(
+                f,
+                "index {} is past the end of lookup table {}, which holds {} addresses",
+                index, table, len
+            ),
+            Self::LoadedTwice(pubkey) => write!(f, "{} is loaded twice", pubkey),
+        }
+    }
+}
+
+/// The result of [`check_lookups`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LookupReport {
+    /// The accounts loaded from tables, writable before read-only within
+    /// each table, in the order the transaction looks them up.
+    pub loaded: Vec<LoadedMeta>,
+    /// Static accounts that one of the tables holds.
+    pub uncompressed: Vec<Pubkey>,
+    /// Lookups that would fail to resolve.
+    pub problems: Vec<LookupProblem>,
+}
+
+impl LookupReport {
+    /// Returns true if every lookup resolves.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Converts the report into an error if any lookup fails to resolve.
+    pub fn into_result(self) -> Result<(), AccountGenError> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(AccountGenError::ConstraintViolation(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for LookupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            write!(f, "{} accounts load from lookup tables", self.loaded.len())?;
+        } else {
+            write!(f, "lookups fail to resolve:")?;
+            for problem in &self.problems {
+                write!(f, "\n  {}", problem)?;
+            }
+        }
+        for pubkey in &self.uncompressed {
+            write!(f, "\n  {}: in a lookup table but listed", pubkey)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+//! Assertion macros for accounts and logs.
+//!
+//! These macros compare accounts the way `assert_eq!` compares values, but
+//! report failures as a field-by-field diff instead of two `Debug` dumps.
+
+use borsh::BorshDeserialize;
+
+/// Asserts that two accounts are equal, printing a diff on failure.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, assert_account_eq};
+///
+/// let account = AccountBuilder::new().balance(100).build();
+/// assert_account_eq!(account, AccountBuilder::new().balance(100).build());
+/// ```
+///
+/// A failing assertion shows only what changed:
+///
+/// ```text
+/// assertion `left == right` failed: accounts differ
+/// lamports: 100 (0.000000100 SOL) -> 50 (0.000000050 SOL)
+/// data[4..8]: 00000000 -> 2a000000
+/// ```
+#[macro_export]
+macro_rules! assert_account_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if left != right {
+                    panic!(
+                        "assertion `left == right` failed: accounts differ\n{}",
+                        $crate::diff::diff_accounts(left, right)
+                    );
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if left != right {
+                    panic!(
+                        "assertion `left == right` failed: {}\n{}",
+                        format_args!($($arg)+),
+                        $crate::diff::diff_accounts(left, right)
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// Asserts that a field of an account's Borsh-encoded data has a value.
+///
+/// The data is decoded as the given type and the (possibly nested) field is
+/// compared with the expected value. Prefix the type with `anchor` to skip
+/// the 8-byte Anchor discriminator. Trailing bytes after the decoded value
+/// are ignored, so accounts with padding or reserved space still decode.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, assert_account_field_eq};
+/// use solana_accountgen::extensions::anchor::create_anchor_account;
+/// use borsh::{BorshSerialize, BorshDeserialize};
+/// use solana_pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// struct GameState {
+///     player: Pubkey,
+///     score: u64,
+/// }
+///
+/// let player = Pubkey::new_unique();
+/// let state = GameState { player, score: 100 };
+///
+/// let account = AccountBuilder::new().data(&state).unwrap().build();
+/// assert_account_field_eq!(account, GameState, score, 100);
+///
+/// let anchor_account =
+///     create_anchor_account("game", Pubkey::new_unique(), state, 1_000_000).unwrap();
+/// assert_account_field_eq!(anchor_account, anchor GameState, player, player);
+/// ```
+#[macro_export]
+macro_rules! assert_account_field_eq {
+    ($account:expr, anchor $ty:ty, $($field:ident).+, $expected:expr $(,)?) => {
+        $crate::assert_account_field_eq!(@decode $account, $ty, 8, $($field).+, $expected)
+    };
+    ($account:expr, $ty:ty, $($field:ident).+, $expected:expr $(,)?) => {
+        $crate::assert_account_field_eq!(@decode $account, $ty, 0, $($field).+, $expected)
+    };
+    (@decode $account:expr, $ty:ty, $offset:expr, $($field:ident).+, $expected:expr) => {{
+        let value: $ty =
+            $crate::macros::decode_account_data(&$account.data, $offset, stringify!($ty));
+        let actual = &value.$($field).+;
+        let expected = $expected;
+        if !(*actual == expected) {
+            panic!(
+                "assertion failed: {}.{} does not match\n  actual: {:?}\nexpected: {:?}",
+                stringify!($ty),
+                stringify!($($field).+),
+                actual,
+                expected
+            );
+        }
+    }};
+}
+
+/// Asserts that a set of accounts matches an expected set.
+///
+/// On failure, every missing, unexpected, and changed account is reported.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, AccountMap, assert_accounts_match};
+/// use solana_pubkey::Pubkey;
+///
+/// let mut expected = AccountMap::new();
+/// expected
+///     .add_with_builder(Pubkey::new_unique(), AccountBuilder::new().balance(1))
+///     .unwrap();
+///
+/// let actual = expected.clone();
+/// assert_accounts_match!(actual, expected);
+/// ```
+#[macro_export]
+macro_rules! assert_accounts_match {
+    ($actual:expr, $expected:expr $(,)?) => {{
+        let diff = $crate::diff::diff_maps(&$expected, &$actual);
+        if !diff.is_empty() {
+            panic!(
+                "assertion failed: accounts do not match \
+                 ({} missing, {} unexpected, {} changed)\n\
+                 (- missing, + unexpected, ~ expected -> actual)\n{}",
+                diff.removed.len(),
+                diff.added.len(),
+                diff.changed.len(),
+                diff
+            );
+        }
+    }};
+}
+
+/// Asserts that a transaction's logs contain a substring.
+///
+/// On failure, every log line is printed.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{assert_log_contains, logs::Logs};
+///
+/// let logs = Logs::from_messages(vec!["Program log: Instruction: Initialize".to_string()]);
+/// assert_log_contains!(logs, "Instruction: Initialize");
+/// ```
+#[macro_export]
+macro_rules! assert_log_contains {
+    ($logs:expr, $needle:expr $(,)?) => {{
+        let logs: &$crate::logs::Logs = &$logs;
+        let needle: &str = &$needle;
+        if !logs.contains(needle) {
+            panic!(
+                "assertion failed: logs do not contain {:?}\nlogs:\n{}",
+                needle, logs
+            );
+        }
+    }};
+}
+
+/// Asserts that logs match a snapshot file.
+///
+/// The path is relative to the calling crate's manifest directory. Logs
+/// are normalized with a [`LogNormalizer`](crate::logs::LogNormalizer),
+/// the default one unless given as a third argument. Set
+/// `ACCOUNTGEN_UPDATE_BASELINES` to write new snapshots or rewrite them
+/// after an intended change; without it, a missing snapshot fails the
+/// assertion. See [`check_logs_snapshot`](crate::logs::check_logs_snapshot).
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::assert_logs_snapshot;
+/// use solana_accountgen::logs::{LogNormalizer, Logs};
+/// # let (vault, logs) = (solana_pubkey::Pubkey::new_unique(), Logs::default());
+///
+/// assert_logs_snapshot!(logs, "snapshots/init_logs.txt");
+/// assert_logs_snapshot!(
+///     logs,
+///     "snapshots/init_logs_named.txt",
+///     LogNormalizer::new().name(vault, "vault")
+/// );
+/// ```
+///
+/// A failing assertion shows a diff against the snapshot:
+///
+/// ```text
+/// logs differ from the snapshot snapshots/init_logs.txt (- snapshot, + actual):
+///   Program <pubkey 1> invoke [1]
+/// - Program log: Instruction: Initialize
+/// + Program log: Instruction: InitializeV2
+///   Program <pubkey 1> consumed <cu> of <cu> compute units
+/// ```
+#[macro_export]
+macro_rules! assert_logs_snapshot {
+    ($logs:expr, $path:expr $(,)?) => {
+        $crate::assert_logs_snapshot!($logs, $path, $crate::logs::LogNormalizer::default())
+    };
+    ($logs:expr, $path:expr, $normalizer:expr $(,)?) => {{
+        let logs: &$crate::logs::Logs = &$logs;
+        let path = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path);
+        match $crate::logs::check_logs_snapshot(logs, &path, &$normalizer) {
+            Ok(()) => {}
+            Err($crate::AccountGenError::ConstraintViolation(report)) => {
+                panic!("assertion failed: {}", report)
+            }
+            Err(e) => panic!("failed to check log snapshot {}: {}", path.display(), e),
+        }
+    }};
+}
+
+/// Decodes account data for the assertion macros, panicking on failure.
+#[doc(hidden)]
+pub fn decode_account_data<T: BorshDeserialize>(data: &[u8], offset: usize, type_name: &str) -> T {
+    let Some(mut bytes) = data.get(offset..) else {
+        panic!(
+            "account data is {} bytes, too short to decode {} at offset {}",
+            data.len(),
+            type_name,
+            offset
+        );
+    };
+    T::deserialize(&mut bytes)
+        .unwrap_or_else(|e| panic!("failed to decode account data as {}: {}", type_name, e))
+}
@@ -3,23 +3,61 @@
 //! This binary provides a CLI for generating mock Solana accounts
 //! for testing purposes. It allows users to create accounts with
 //! specific properties and output them in various formats.
+//!
+//! Every subcommand prints its results as JSON when given `--output json`,
+//! and failures as `{"error": "..."}` with a non-zero exit code.
+//! `completions` prints a completion script for bash, zsh, fish, elvish or
+//! PowerShell.
 
 use base64;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use hex;
 use serde_json;
-use solana_accountgen::AccountBuilder;
+use serde_json::{Value, json};
+use solana_accountgen::cluster::ClusterContext;
+use solana_accountgen::decoder::DecoderRegistry;
+use solana_accountgen::diff::AccountDiff;
+use solana_accountgen::extensions::anchor::identify_discriminator;
+use solana_accountgen::extensions::idl::Idl;
+use solana_accountgen::fixture::{FixtureAccount, InstructionSet};
+use solana_accountgen::regions::RegionMaps;
+use solana_accountgen::rpc::MockRpcServer;
+use solana_accountgen::runner::ScenarioRunner;
+use solana_accountgen::serialization::DataEncoding;
+use solana_accountgen::serialization::schema::FixtureSchema;
+use solana_accountgen::units::LAMPORTS_PER_SOL;
+use solana_accountgen::{
+    AccountBuilder, AccountGenError, AccountMap, diff, display, fixture, well_known,
+};
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_program_test::{ProgramTest, tokio};
 use solana_pubkey::Pubkey;
+use solana_signer::Signer;
 use std::str::FromStr;
 
 /// CLI for generating Solana test accounts
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    /// How to print results: text for people, or json for scripts
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// How results are printed
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// A single JSON document on stdout
+    Json,
+}
+
 /// Available commands
 #[derive(Subcommand)]
 enum Commands {
@@ -37,26 +75,259 @@ enum Commands {
         #[arg(short, long, default_value = "false")]
         executable: bool,
 
+        /// Allow an executable account that is not owned by a loader or
+        /// has no data
+        #[arg(long)]
+        skip_executable_checks: bool,
+
         /// Output format (json or base64)
         #[arg(short, long, default_value = "json")]
         format: String,
 
-        /// Account data as a hex string (e.g., "0102ABCD")
+        /// Account data as a hex string (e.g., "0102ABCD"), or in the
+        /// encoding given by --data-encoding
         #[arg(short, long)]
         data: Option<String>,
+
+        /// Encoding of --data (base58, base64 or hex)
+        #[arg(long, default_value = "hex")]
+        data_encoding: DataEncoding,
+    },
+
+    /// Print the accounts in a fixture file in a human-readable form
+    Inspect {
+        /// Path to the fixture file
+        path: String,
+
+        /// Only show the account with this pubkey
+        #[arg(short, long)]
+        pubkey: Option<String>,
+
+        /// Path to a JSON Borsh schema used to decode account data
+        #[arg(short, long, requires = "type_name")]
+        schema: Option<String>,
+
+        /// Type in the schema to decode account data as
+        #[arg(short = 't', long = "type", requires = "schema")]
+        type_name: Option<String>,
+
+        /// Path to an Anchor IDL used to decode the fixture's instructions
+        #[arg(long)]
+        idl: Vec<String>,
+
+        /// Path to a JSON file of per-owner data region maps
+        #[arg(long)]
+        regions: Option<String>,
+    },
+
+    /// Show the accounts that differ between two fixture files
+    Diff {
+        /// Path to the original fixture file
+        old: String,
+
+        /// Path to the updated fixture file
+        new: String,
+
+        /// Path to an Anchor IDL used to decode the fixtures' instructions
+        #[arg(long)]
+        idl: Vec<String>,
+
+        /// Path to a JSON file of per-owner data region maps used to name
+        /// changed bytes
+        #[arg(long)]
+        regions: Option<String>,
+    },
+
+    /// Anchor helpers
+    Anchor {
+        #[command(subcommand)]
+        command: AnchorCommands,
+    },
+
+    /// Curate fixture files
+    Fixtures {
+        #[command(subcommand)]
+        command: FixturesCommands,
+    },
+
+    /// Serve the accounts in a fixture file over a mock JSON RPC endpoint
+    ///
+    /// Answers getAccountInfo, getMultipleAccounts and getProgramAccounts
+    /// until the process is stopped.
+    Serve {
+        /// Path to the fixture file
+        path: String,
+
+        /// Port to listen on; 0 picks a free one
+        #[arg(short, long, default_value = "8899")]
+        port: u16,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Slot reported in response contexts
+        #[arg(long, default_value = "0")]
+        slot: u64,
+    },
+
+    /// Run a scenario file against solana-program-test
+    ///
+    /// The scenario is a list of steps in JSON, or in YAML if the file name
+    /// ends in .yaml or .yml. A failing step prints a report of the run and
+    /// exits with status 1.
+    Run {
+        /// Path to the scenario file
+        path: String,
+
+        /// Path to an SBF program to load; may be repeated, once per
+        /// --program-id
+        #[arg(long)]
+        program: Vec<String>,
+
+        /// ID of the program at the --program of the same position
+        #[arg(long)]
+        program_id: Vec<String>,
+
+        /// Keypair file of the payer, funded with 1,000 SOL; a new keypair
+        /// is used if not given
+        #[arg(long)]
+        payer: Option<String>,
+    },
+
+    /// Derive the address of an account created with a seed
+    Derive {
+        /// Base pubkey that signs for the account
+        #[arg(short, long)]
+        base: String,
+
+        /// Seed string, at most 32 bytes
+        #[arg(short, long)]
+        seed: String,
+
+        /// Program that will own the account
+        #[arg(short, long)]
+        owner: String,
+    },
+
+    /// Find a program derived address and its bump
+    Pda {
+        /// Program the address is derived for
+        #[arg(short, long)]
+        program_id: String,
+
+        /// A seed as kind:value, where kind is utf8, hex, pubkey, u8, u16,
+        /// u32 or u64 (little-endian); may be repeated, in seed order
+        #[arg(short, long, value_parser = parse_seed)]
+        seed: Vec<Vec<u8>>,
+    },
+
+    /// Show the minimum balance for an account to be rent-exempt
+    Rent {
+        /// Size of the account data in bytes
+        #[arg(short, long, default_value = "0")]
+        size: usize,
+    },
+
+    /// Print a shell completion script
+    ///
+    /// For example, `solana-accountgen completions bash >
+    /// /etc/bash_completion.d/solana-accountgen`.
+    Completions {
+        /// Shell to write the script for
+        #[arg(value_enum)]
+        shell: Shell,
     },
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// Fixture file subcommands
+#[derive(Subcommand)]
+enum FixturesCommands {
+    /// Combine fixture files into one
+    ///
+    /// An account in a later file replaces the same account in an earlier
+    /// one. Instructions are kept in file order.
+    Merge {
+        /// Paths to the fixture files to merge
+        #[arg(required = true)]
+        paths: Vec<String>,
+
+        /// Path to write the merged fixture file to
+        #[arg(short, long)]
+        out: String,
+    },
+
+    /// Write the accounts of a fixture file that match every given filter
+    ///
+    /// Instructions are kept unchanged.
+    Filter {
+        /// Path to the fixture file
+        path: String,
+
+        /// Keep accounts owned by this program; may be repeated to keep
+        /// accounts owned by any of them
+        #[arg(long)]
+        owner: Vec<String>,
+
+        /// Keep accounts holding at least this many lamports
+        #[arg(long)]
+        min_lamports: Option<u64>,
+
+        /// Path to write the filtered fixture file to
+        #[arg(short, long)]
+        out: String,
+    },
+}
+
+/// Anchor subcommands
+#[derive(Subcommand)]
+enum AnchorCommands {
+    /// Find the account, instruction or event an 8-byte discriminator
+    /// belongs to
+    Identify {
+        /// The discriminator as hex; longer data is cut to its first 8
+        /// bytes
+        #[arg(short, long)]
+        data: String,
+
+        /// Path to an Anchor IDL whose accounts and instructions to check
+        #[arg(long)]
+        idl: Vec<String>,
+
+        /// Path to a word list of candidate names, one per line
+        #[arg(short, long)]
+        words: Option<String>,
+    },
+}
+
+fn main() {
     let cli = Cli::parse();
+    let json = cli.output == OutputFormat::Json;
+
+    // Failures are reported in the same format as results, so scripts
+    // reading --output json always get a JSON document
+    if let Err(error) = run(cli) {
+        if json {
+            println!("{}", json!({ "error": error.to_string() }));
+        } else {
+            eprintln!("Error: {}", error);
+        }
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let json = cli.output == OutputFormat::Json;
 
     match cli.command {
         Commands::Generate {
             balance,
             owner,
             executable,
+            skip_executable_checks,
             format,
             data,
+            data_encoding,
         } => {
             let owner_pubkey = Pubkey::from_str(&owner)?;
 
@@ -65,38 +336,587 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .balance(balance)
                 .owner(owner_pubkey)
                 .executable(executable);
+            if skip_executable_checks {
+                builder = builder.skip_executable_checks();
+            }
 
             // Add data if provided
-            if let Some(hex_data) = data {
-                // Parse hex string to bytes
-                let data_bytes = hex::decode(&hex_data)?;
-                builder = builder.data_raw(data_bytes);
+            if let Some(data) = data {
+                builder = builder.data_encoded(&data, data_encoding)?;
             }
 
             // Build the account
-            let account = builder.build();
+            let account = builder.try_build()?;
+
+            let encoded = match format.as_str() {
+                "json" => serde_json::to_value(&account)?,
+                // Serialize using serde_json instead of bincode, then
+                // encode as base64
+                "base64" => Value::String(base64::encode(serde_json::to_vec(&account)?)),
+                _ => return Err(format!("Unsupported format: {}", format).into()),
+            };
+            if json {
+                print_json(&json!({ "format": format, "account": encoded }))?;
+            } else if let Value::String(base64_string) = encoded {
+                println!("{}", base64_string);
+            } else {
+                println!("{}", serde_json::to_string_pretty(&encoded)?);
+            }
+        }
+        Commands::Inspect {
+            path,
+            pubkey,
+            schema,
+            type_name,
+            idl,
+            regions,
+        } => {
+            let (mut accounts, instructions) = fixture::load_scenario(&path)?;
 
-            match format.as_str() {
-                "json" => {
-                    println!("{}", serde_json::to_string_pretty(&account)?);
+            // Narrow down to a single account if requested
+            if let Some(pubkey) = pubkey {
+                let pubkey = Pubkey::from_str(&pubkey)?;
+                accounts = accounts.filter(|key, _| *key == pubkey);
+                if accounts.is_empty() {
+                    return Err(format!("Account {} not found in {}", pubkey, path).into());
                 }
-                "base64" => {
-                    // Serialize using serde_json instead of bincode
-                    let json_bytes = serde_json::to_vec(&account)?;
+            }
+            let regions = region_maps(regions.as_deref())?;
+            let registry = decoder_registry(&idl)?;
 
-                    // Encode as base64
-                    let base64_string = base64::encode(&json_bytes);
+            // Decode the data of every account with the given schema
+            let mut decoded = Vec::new();
+            if let (Some(schema), Some(type_name)) = (schema, type_name) {
+                let schema: FixtureSchema =
+                    serde_json::from_str(&std::fs::read_to_string(&schema)?)?;
+                for (pubkey, account) in accounts.iter() {
+                    let value = schema.decode(&type_name, &account.data)?;
+                    decoded.push((*pubkey, type_name.clone(), value));
+                }
+            }
 
-                    // Print the result
-                    println!("{}", base64_string);
+            if json {
+                let accounts: Vec<Value> = sorted(&accounts)
+                    .into_iter()
+                    .map(|(pubkey, account)| {
+                        let mut value = json!(FixtureAccount::new(pubkey, account.clone()));
+                        if let Some(map) = regions.get(&account.owner) {
+                            value["regions"] = map
+                                .regions()
+                                .iter()
+                                .map(|region| {
+                                    (region.name.clone(), json!(region.value(&account.data)))
+                                })
+                                .collect();
+                        }
+                        if let Some((_, _, data)) = decoded.iter().find(|(key, ..)| *key == pubkey)
+                        {
+                            value["decoded"] = data.clone();
+                        }
+                        value
+                    })
+                    .collect();
+                let instructions: Vec<Value> = instructions
+                    .iter()
+                    .map(|instruction| decoded_json(&registry, instruction))
+                    .collect();
+                print_json(&json!({ "accounts": accounts, "instructions": instructions }))?;
+                return Ok(());
+            }
+
+            print!("{}", display::format_map(&accounts));
+
+            // Show named data regions for owners with a region map
+            let annotated = regions.format_map(&accounts);
+            if !annotated.is_empty() {
+                print!("\n{}", annotated);
+            }
+
+            for (pubkey, type_name, value) in &decoded {
+                println!("\n{} as {}:", pubkey, type_name);
+                println!("{}", serde_json::to_string_pretty(value)?);
+            }
+
+            // Decode the scenario's instructions, if any
+            for (i, instruction) in instructions.iter().enumerate() {
+                println!("\nInstruction #{}", i);
+                print_indented(&display::format_decoded(&registry, instruction), 2);
+            }
+        }
+        Commands::Diff {
+            old,
+            new,
+            idl,
+            regions,
+        } => {
+            let (old_accounts, old_instructions) = fixture::load_scenario(&old)?;
+            let (new_accounts, new_instructions) = fixture::load_scenario(&new)?;
+
+            let regions = region_maps(regions.as_deref())?;
+            let map_diff = diff::diff_maps_with_regions(&old_accounts, &new_accounts, &regions);
+
+            // Find instructions that differ at the same position
+            let registry = decoder_registry(&idl)?;
+            let old_instructions: Vec<_> = old_instructions.iter().collect();
+            let new_instructions: Vec<_> = new_instructions.iter().collect();
+            let count = old_instructions.len().max(new_instructions.len());
+            let changed_instructions: Vec<_> = (0..count)
+                .map(|i| (i, old_instructions.get(i), new_instructions.get(i)))
+                .filter(|(_, before, after)| before != after)
+                .collect();
+
+            if json {
+                let records = |accounts: &[(Pubkey, _)]| -> Vec<Value> {
+                    accounts
+                        .iter()
+                        .map(|(pubkey, account)| {
+                            json!(FixtureAccount::new(*pubkey, Clone::clone(account)))
+                        })
+                        .collect()
+                };
+                let changed: Vec<Value> = map_diff
+                    .changed
+                    .iter()
+                    .map(|(pubkey, account_diff)| account_diff_json(pubkey, account_diff))
+                    .collect();
+                let instructions: Vec<Value> = changed_instructions
+                    .iter()
+                    .map(|(i, before, after)| {
+                        json!({
+                            "index": i,
+                            "old": before.map(|instruction| decoded_json(&registry, instruction)),
+                            "new": after.map(|instruction| decoded_json(&registry, instruction)),
+                        })
+                    })
+                    .collect();
+                print_json(&json!({
+                    "added": records(&map_diff.added),
+                    "removed": records(&map_diff.removed),
+                    "changed": changed,
+                    "instructions": instructions,
+                }))?;
+            } else {
+                print!("{}", map_diff);
+                for (i, before, after) in &changed_instructions {
+                    if let Some(instruction) = before {
+                        println!("- Instruction #{}", i);
+                        print_indented(&display::format_decoded(&registry, instruction), 4);
+                    }
+                    if let Some(instruction) = after {
+                        println!("+ Instruction #{}", i);
+                        print_indented(&display::format_decoded(&registry, instruction), 4);
+                    }
+                }
+            }
+
+            // Exit with a non-zero status like diff(1) when anything changed
+            if !map_diff.is_empty() || !changed_instructions.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Anchor {
+            command: AnchorCommands::Identify { data, idl, words },
+        } => {
+            if idl.is_empty() && words.is_none() {
+                return Err("Give an IDL with --idl or a word list with --words".into());
+            }
+            let bytes = hex::decode(data.trim_start_matches("0x"))?;
+            let Some(discriminator) = bytes.get(..8) else {
+                return Err(format!("Discriminator must be 8 bytes, got {}", bytes.len()).into());
+            };
+            let discriminator: [u8; 8] = discriminator.try_into()?;
+
+            // Each match with the program it was found in, if any
+            let mut matches = Vec::new();
+            for path in &idl {
+                let idl = Idl::from_file(path)?;
+                let program = idl.program_name().unwrap_or(path).to_string();
+                for found_match in idl.identify_discriminator(&discriminator) {
+                    matches.push((found_match, Some(program.clone())));
                 }
-                _ => {
-                    eprintln!("Unsupported format: {}", format);
-                    std::process::exit(1);
+            }
+            if let Some(path) = words {
+                let words = std::fs::read_to_string(path)?;
+                for found_match in identify_discriminator(&discriminator, words.lines()) {
+                    matches.push((found_match, None));
+                }
+            }
+
+            if json {
+                let matches: Vec<Value> = matches
+                    .iter()
+                    .map(|(found_match, program)| {
+                        json!({
+                            "kind": found_match.kind.to_string(),
+                            "name": found_match.name,
+                            "program": program,
+                        })
+                    })
+                    .collect();
+                print_json(&json!({
+                    "discriminator": hex::encode(discriminator),
+                    "matches": matches,
+                }))?;
+            } else {
+                for (found_match, program) in &matches {
+                    match program {
+                        Some(program) => println!("{} ({})", found_match, program),
+                        None => println!("{}", found_match),
+                    }
+                }
+                if matches.is_empty() {
+                    eprintln!(
+                        "No name matches discriminator {}",
+                        hex::encode(discriminator)
+                    );
+                }
+            }
+            if matches.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Fixtures {
+            command: FixturesCommands::Merge { paths, out },
+        } => {
+            let mut accounts = AccountMap::new();
+            let mut instructions = InstructionSet::new();
+            let mut replaced = 0;
+            for path in &paths {
+                let (file_accounts, file_instructions) = fixture::load_scenario(path)?;
+                replaced += file_accounts
+                    .iter()
+                    .filter(|(pubkey, _)| accounts.get_account(pubkey).is_some())
+                    .count();
+                accounts.merge(file_accounts);
+                for instruction in file_instructions.iter() {
+                    instructions.push(instruction.clone());
+                }
+            }
+
+            fixture::save_scenario(&out, &accounts, &instructions)?;
+            if json {
+                print_json(&json!({
+                    "path": out,
+                    "files": paths.len(),
+                    "accounts": accounts.len(),
+                    "instructions": instructions.len(),
+                    "replaced": replaced,
+                }))?;
+                return Ok(());
+            }
+            println!(
+                "Merged {} accounts and {} instructions from {} files into {}",
+                accounts.len(),
+                instructions.len(),
+                paths.len(),
+                out
+            );
+            if replaced > 0 {
+                println!("{} accounts were replaced by later files", replaced);
+            }
+        }
+        Commands::Fixtures {
+            command:
+                FixturesCommands::Filter {
+                    path,
+                    owner,
+                    min_lamports,
+                    out,
+                },
+        } => {
+            let owners = owner
+                .iter()
+                .map(|owner| Pubkey::from_str(owner))
+                .collect::<Result<Vec<_>, _>>()?;
+            let (accounts, instructions) = fixture::load_scenario(&path)?;
+            let kept = accounts.filter(|_, account| {
+                (owners.is_empty() || owners.contains(&account.owner))
+                    && min_lamports.is_none_or(|min| account.lamports >= min)
+            });
+
+            fixture::save_scenario(&out, &kept, &instructions)?;
+            if json {
+                print_json(&json!({
+                    "path": out,
+                    "kept": kept.len(),
+                    "accounts": accounts.len(),
+                }))?;
+            } else {
+                println!(
+                    "Kept {} of {} accounts in {}",
+                    kept.len(),
+                    accounts.len(),
+                    out
+                );
+            }
+        }
+        Commands::Serve {
+            path,
+            port,
+            host,
+            slot,
+        } => {
+            let accounts = fixture::load_account_map(&path)?;
+            let count = accounts.len();
+            let server = MockRpcServer::bind((host.as_str(), port), accounts)?;
+            server.set_slot(slot);
+            if json {
+                // On one line, so scripts can read it while the server runs
+                println!(
+                    "{}",
+                    json!({ "path": path, "accounts": count, "url": server.url(), "slot": slot })
+                );
+            } else {
+                println!(
+                    "Serving {} accounts from {} at {}",
+                    count,
+                    path,
+                    server.url()
+                );
+            }
+
+            // The server answers requests on its own threads until the
+            // process is stopped
+            loop {
+                std::thread::park();
+            }
+        }
+        Commands::Run {
+            path,
+            program,
+            program_id,
+            payer,
+        } => {
+            if program.len() != program_id.len() {
+                return Err("Give one --program-id for every --program".into());
+            }
+            let scenario = std::fs::read_to_string(&path)?;
+            let runner = match std::path::Path::new(&path)
+                .extension()
+                .and_then(|extension| extension.to_str())
+            {
+                Some("yaml" | "yml") => ScenarioRunner::from_yaml(&scenario)?,
+                _ => ScenarioRunner::from_json(&scenario)?,
+            };
+            let payer = match payer {
+                Some(path) => solana_keypair::read_keypair_file(path)?,
+                None => Keypair::new(),
+            };
+
+            let mut program_test = ProgramTest::default();
+            for (path, program_id) in program.iter().zip(&program_id) {
+                let elf = std::fs::read(path)
+                    .map_err(|e| format!("cannot read program {}: {}", path, e))?;
+                let account = AccountBuilder::new()
+                    .owner(well_known::BPF_LOADER)
+                    .executable(true)
+                    .data_raw(elf)
+                    .try_build()?;
+                program_test.add_account(Pubkey::from_str(program_id)?, account);
+            }
+            program_test.add_account(
+                payer.pubkey(),
+                AccountBuilder::new()
+                    .balance(1_000 * LAMPORTS_PER_SOL)
+                    .owner(well_known::SYSTEM_PROGRAM)
+                    .try_build()?,
+            );
+
+            if !json {
+                println!(
+                    "Running {} steps of {} with payer {}",
+                    runner.steps().len(),
+                    path,
+                    payer.pubkey()
+                );
+            }
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let result = runtime.block_on(async {
+                let mut context = program_test.start_with_context().await;
+                runner.run(&mut context, &payer, &[]).await
+            });
+            let (checkpoints, report) = match result {
+                Ok(run) => (run.checkpoint_names().map(str::to_string).collect(), None),
+                Err(AccountGenError::ConstraintViolation(report)) => (Vec::new(), Some(report)),
+                Err(e) => return Err(e.into()),
+            };
+
+            if json {
+                print_json(&json!({
+                    "path": path,
+                    "payer": payer.pubkey().to_string(),
+                    "steps": runner.steps().len(),
+                    "passed": report.is_none(),
+                    "checkpoints": checkpoints,
+                    "report": report,
+                }))?;
+            } else if let Some(report) = &report {
+                eprintln!("{}", report);
+            } else {
+                println!("Passed {} steps", runner.steps().len());
+                for name in &checkpoints {
+                    println!("  checkpoint {:?}", name);
                 }
             }
+            if report.is_some() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Derive { base, seed, owner } => {
+            let (base, owner) = (Pubkey::from_str(&base)?, Pubkey::from_str(&owner)?);
+            let address = Pubkey::create_with_seed(&base, &seed, &owner)
+                .map_err(|e| format!("invalid seeded address: {}", e))?;
+            if json {
+                print_json(&json!({
+                    "base": base.to_string(),
+                    "seed": seed,
+                    "owner": owner.to_string(),
+                    "address": address.to_string(),
+                }))?;
+            } else {
+                println!("{}", address);
+            }
+        }
+        Commands::Pda { program_id, seed } => {
+            let program_id = Pubkey::from_str(&program_id)?;
+            let seeds: Vec<&[u8]> = seed.iter().map(Vec::as_slice).collect();
+            let Some((address, bump)) = Pubkey::try_find_program_address(&seeds, &program_id)
+            else {
+                return Err("No program derived address for these seeds".into());
+            };
+            if json {
+                print_json(&json!({
+                    "programId": program_id.to_string(),
+                    "seeds": seed.iter().map(hex::encode).collect::<Vec<_>>(),
+                    "address": address.to_string(),
+                    "bump": bump,
+                }))?;
+            } else {
+                println!("{} (bump {})", address, bump);
+            }
+        }
+        Commands::Rent { size } => {
+            let lamports = ClusterContext::default().minimum_balance(size);
+            if json {
+                print_json(&json!({
+                    "size": size,
+                    "lamports": lamports,
+                    "sol": display::format_sol(lamports),
+                }))?;
+            } else {
+                println!(
+                    "{} lamports ({} SOL) for {} bytes",
+                    lamports,
+                    display::format_sol(lamports),
+                    size
+                );
+            }
+        }
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "solana-accountgen",
+                &mut std::io::stdout(),
+            );
         }
     }
 
     Ok(())
 }
+
+/// Builds a decoder registry with the native programs and the given IDLs.
+fn decoder_registry(idls: &[String]) -> Result<DecoderRegistry, Box<dyn std::error::Error>> {
+    let mut registry = DecoderRegistry::new();
+    for path in idls {
+        let idl = Idl::from_file(path)?;
+        let address = idl
+            .address
+            .as_deref()
+            .ok_or_else(|| format!("IDL {} has no program address", path))?;
+        registry.register_anchor_idl(Pubkey::from_str(address)?, &idl)?;
+    }
+    Ok(registry)
+}
+
+/// Loads region maps from `path`, or returns an empty set.
+fn region_maps(path: Option<&str>) -> Result<RegionMaps, Box<dyn std::error::Error>> {
+    Ok(match path {
+        Some(path) => RegionMaps::from_file(path)?,
+        None => RegionMaps::new(),
+    })
+}
+
+/// Parses a `--seed` of the form `kind:value` into its bytes.
+fn parse_seed(seed: &str) -> Result<Vec<u8>, String> {
+    let (kind, value) = seed
+        .split_once(':')
+        .ok_or_else(|| format!("seed {:?} is not of the form kind:value", seed))?;
+    let number = |e: std::num::ParseIntError| format!("invalid {} seed {:?}: {}", kind, value, e);
+    Ok(match kind {
+        "utf8" => value.as_bytes().to_vec(),
+        "hex" => hex::decode(value.trim_start_matches("0x")).map_err(|e| e.to_string())?,
+        "pubkey" => Pubkey::from_str(value)
+            .map_err(|e| e.to_string())?
+            .to_bytes()
+            .to_vec(),
+        "u8" => vec![value.parse::<u8>().map_err(number)?],
+        "u16" => value.parse::<u16>().map_err(number)?.to_le_bytes().to_vec(),
+        "u32" => value.parse::<u32>().map_err(number)?.to_le_bytes().to_vec(),
+        "u64" => value.parse::<u64>().map_err(number)?.to_le_bytes().to_vec(),
+        _ => return Err(format!("unknown seed kind {:?}", kind)),
+    })
+}
+
+/// Returns the accounts of `accounts` ordered by pubkey.
+fn sorted(accounts: &AccountMap) -> Vec<(Pubkey, &solana_account::Account)> {
+    let mut entries: Vec<_> = accounts
+        .iter()
+        .map(|(pubkey, account)| (*pubkey, account))
+        .collect();
+    entries.sort_by_key(|(pubkey, _)| *pubkey);
+    entries
+}
+
+/// Decodes an instruction as JSON, falling back to its raw form with an
+/// `error` field when the registered decoder fails.
+fn decoded_json(registry: &DecoderRegistry, instruction: &Instruction) -> Value {
+    match registry.decode_instruction(instruction) {
+        Ok(decoded) => json!(decoded),
+        Err(e) => {
+            let mut raw = DecoderRegistry::empty()
+                .decode_instruction(instruction)
+                .map(|raw| json!(raw))
+                .unwrap_or_else(|_| json!({}));
+            raw["error"] = json!(e.to_string());
+            raw
+        }
+    }
+}
+
+/// Formats the change to one account as JSON, with `[old, new]` pairs for
+/// the fields that changed and the text diff of its data.
+fn account_diff_json(pubkey: &Pubkey, account_diff: &AccountDiff) -> Value {
+    let pair = |(old, new): (Pubkey, Pubkey)| json!([old.to_string(), new.to_string()]);
+    json!({
+        "pubkey": pubkey.to_string(),
+        "lamports": account_diff.lamports,
+        "owner": account_diff.owner.map(pair),
+        "executable": account_diff.executable,
+        "rentEpoch": account_diff.rent_epoch,
+        "changes": account_diff.to_string().lines().collect::<Vec<_>>(),
+    })
+}
+
+fn print_json(value: &Value) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_indented(text: &str, indent: usize) {
+    for line in text.lines() {
+        println!("{:indent$}{}", "", line);
+    }
+}
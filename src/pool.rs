@@ -0,0 +1,90 @@
+//! Reusing data buffers across generated accounts.
+//!
+//! Every account owns its data, so generating an account allocates. When
+//! accounts are generated in bulk and discarded soon after, for example
+//! when streaming millions of them to a fixture file, a [`BufferPool`]
+//! hands the allocations of finished accounts to the next ones.
+
+use solana_account::Account;
+
+/// The number of buffers a pool holds by default.
+pub const DEFAULT_MAX_BUFFERS: usize = 64;
+
+/// A pool of empty byte buffers that keep their capacity.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountBuilder;
+/// use solana_accountgen::fixture::FixtureWriter;
+/// use solana_accountgen::pool::BufferPool;
+/// use solana_pubkey::Pubkey;
+///
+/// let mut writer = FixtureWriter::new(Vec::new());
+/// let mut pool = BufferPool::new();
+/// for value in 0u64..10_000 {
+///     let account = AccountBuilder::new()
+///         .balance(1_000_000)
+///         .data_borsh_into(pool.take(), &value)
+///         .unwrap()
+///         .build();
+///     writer.append(&Pubkey::new_unique(), &account).unwrap();
+///     pool.recycle_account(account);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    buffers: Vec<Vec<u8>>,
+    max_buffers: usize,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BufferPool {
+    /// Creates an empty pool holding up to [`DEFAULT_MAX_BUFFERS`] buffers.
+    pub fn new() -> Self {
+        Self::with_max_buffers(DEFAULT_MAX_BUFFERS)
+    }
+
+    /// Creates an empty pool holding up to `max_buffers` buffers.
+    ///
+    /// Buffers recycled into a full pool are dropped.
+    pub fn with_max_buffers(max_buffers: usize) -> Self {
+        Self {
+            buffers: Vec::new(),
+            max_buffers,
+        }
+    }
+
+    /// Returns an empty buffer, reusing a recycled one if there is one.
+    pub fn take(&mut self) -> Vec<u8> {
+        self.buffers.pop().unwrap_or_default()
+    }
+
+    /// Returns a buffer to the pool.
+    pub fn recycle(&mut self, mut buffer: Vec<u8>) {
+        if self.buffers.len() < self.max_buffers && buffer.capacity() > 0 {
+            buffer.clear();
+            self.buffers.push(buffer);
+        }
+    }
+
+    /// Returns an account's data buffer to the pool.
+    pub fn recycle_account(&mut self, account: Account) {
+        self.recycle(account.data);
+    }
+
+    /// Returns the number of buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Returns true if the pool holds no buffers.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+}
@@ -0,0 +1,221 @@
+//! Summarizing loader-owned program accounts.
+//!
+//! A program account cloned from a cluster holds the program's ELF, often
+//! hundreds of kilobytes of it, and a hexdump or byte diff of it tells a
+//! reader nothing. [`summarize`] recognizes the accounts of every SBF
+//! loader and reports what matters instead: what kind of account it is,
+//! its upgrade authority, and the size and SHA-256 hash of the ELF. The
+//! [`display`](crate::display) and [`diff`](crate::diff) functions show
+//! these summaries in place of the data.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::AccountBuilder;
+//! use solana_accountgen::program_summary::{ProgramAccountKind, summarize};
+//! use solana_accountgen::well_known::BPF_LOADER_UPGRADEABLE;
+//! use solana_pubkey::Pubkey;
+//!
+//! let authority = Pubkey::new_unique();
+//! let mut data = 3u32.to_le_bytes().to_vec();
+//! data.extend_from_slice(&42u64.to_le_bytes());
+//! data.push(1);
+//! data.extend_from_slice(authority.as_ref());
+//! data.extend_from_slice(b"\x7fELF...");
+//!
+//! let program_data = AccountBuilder::new()
+//!     .owner(BPF_LOADER_UPGRADEABLE)
+//!     .data_raw(data)
+//!     .build();
+//! let summary = summarize(&program_data).unwrap();
+//! assert_eq!(
+//!     summary.kind,
+//!     ProgramAccountKind::ProgramData { slot: 42, upgrade_authority: Some(authority) }
+//! );
+//! assert_eq!(summary.elf_len, 7);
+//! ```
+
+use crate::well_known::{BPF_LOADER, BPF_LOADER_DEPRECATED, BPF_LOADER_UPGRADEABLE, LOADER_V4};
+use sha2::{Digest, Sha256};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::fmt;
+
+/// Size of the `ProgramData` header of the upgradeable loader.
+pub const PROGRAM_DATA_HEADER_LEN: usize = 45;
+
+/// Size of the `Buffer` header of the upgradeable loader.
+pub const BUFFER_HEADER_LEN: usize = 37;
+
+/// Size of the program header of loader v4.
+pub const LOADER_V4_HEADER_LEN: usize = 48;
+
+/// What a loader-owned account holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramAccountKind {
+    /// An executable program of the non-upgradeable loaders, holding the
+    /// ELF itself.
+    Program,
+    /// An upgradeable program, pointing at the account holding its ELF.
+    UpgradeableProgram { program_data: Pubkey },
+    /// The ELF of an upgradeable program.
+    ProgramData {
+        slot: u64,
+        upgrade_authority: Option<Pubkey>,
+    },
+    /// An ELF being written before a deploy or upgrade.
+    Buffer { authority: Option<Pubkey> },
+    /// A loader v4 program.
+    LoaderV4Program {
+        slot: u64,
+        authority: Pubkey,
+        status: u64,
+    },
+    /// An upgradeable loader account that is not initialized.
+    Uninitialized,
+}
+
+/// A summary of a loader-owned account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramSummary {
+    /// What the account holds.
+    pub kind: ProgramAccountKind,
+    /// Size of the ELF after its header, without trailing zero padding.
+    pub elf_len: usize,
+    /// SHA-256 of the ELF without trailing zero padding, the hash
+    /// `solana-verify` reports for a deployed program. `None` if the
+    /// account holds no ELF.
+    pub elf_hash: Option<[u8; 32]>,
+}
+
+impl fmt::Display for ProgramSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn authority(authority: &Option<Pubkey>) -> String {
+            authority.map_or_else(|| "none".to_string(), |authority| authority.to_string())
+        }
+
+        match &self.kind {
+            ProgramAccountKind::Program => write!(f, "program")?,
+            ProgramAccountKind::UpgradeableProgram { program_data } => {
+                write!(f, "upgradeable program, program data {}", program_data)?
+            }
+            ProgramAccountKind::ProgramData {
+                slot,
+                upgrade_authority,
+            } => write!(
+                f,
+                "program data, deployed at slot {}, upgrade authority {}",
+                slot,
+                authority(upgrade_authority)
+            )?,
+            ProgramAccountKind::Buffer { authority: buffer } => {
+                write!(f, "buffer, authority {}", authority(buffer))?
+            }
+            ProgramAccountKind::LoaderV4Program {
+                slot,
+                authority,
+                status,
+            } => write!(
+                f,
+                "loader v4 program, deployed at slot {}, authority {}, status {}",
+                slot, authority, status
+            )?,
+            ProgramAccountKind::Uninitialized => write!(f, "uninitialized")?,
+        }
+        if let Some(hash) = &self.elf_hash {
+            write!(
+                f,
+                ", ELF {} bytes, sha256 {}",
+                self.elf_len,
+                hex::encode(hash)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Summarizes an account owned by one of the SBF loaders, or returns
+/// `None` if `account` is not one or its header does not parse.
+///
+/// Builtin programs owned by the native loader hold no ELF and are not
+/// summarized.
+pub fn summarize(account: &Account) -> Option<ProgramSummary> {
+    let data = account.data.as_slice();
+    let (kind, header_len) = match account.owner {
+        owner if owner == BPF_LOADER || owner == BPF_LOADER_DEPRECATED => {
+            if !account.executable {
+                return None;
+            }
+            (ProgramAccountKind::Program, 0)
+        }
+        BPF_LOADER_UPGRADEABLE => upgradeable_kind(data)?,
+        LOADER_V4 => {
+            let kind = ProgramAccountKind::LoaderV4Program {
+                slot: u64_at(data, 0)?,
+                authority: pubkey_at(data, 8)?,
+                status: u64_at(data, 40)?,
+            };
+            (kind, LOADER_V4_HEADER_LEN)
+        }
+        _ => return None,
+    };
+
+    let elf = match kind {
+        ProgramAccountKind::UpgradeableProgram { .. } | ProgramAccountKind::Uninitialized => None,
+        _ => {
+            let elf = data.get(header_len..).unwrap_or_default();
+            let end = elf.iter().rposition(|&byte| byte != 0).map_or(0, |i| i + 1);
+            Some(&elf[..end])
+        }
+    };
+    Some(ProgramSummary {
+        kind,
+        elf_len: elf.map_or(0, <[u8]>::len),
+        elf_hash: elf.map(|elf| Sha256::digest(elf).into()),
+    })
+}
+
+/// Reads `UpgradeableLoaderState`, a bincode enum with a 4-byte tag.
+fn upgradeable_kind(data: &[u8]) -> Option<(ProgramAccountKind, usize)> {
+    // Bincode `Option`s have a 1-byte tag
+    let option_at = |offset: usize| match data.get(offset)? {
+        0 => Some(None),
+        1 => pubkey_at(data, offset + 1).map(Some),
+        _ => None,
+    };
+    match u32::from_le_bytes(data.get(..4)?.try_into().ok()?) {
+        0 => Some((ProgramAccountKind::Uninitialized, 4)),
+        1 => Some((
+            ProgramAccountKind::Buffer {
+                authority: option_at(4)?,
+            },
+            BUFFER_HEADER_LEN,
+        )),
+        2 => Some((
+            ProgramAccountKind::UpgradeableProgram {
+                program_data: pubkey_at(data, 4)?,
+            },
+            36,
+        )),
+        3 => Some((
+            ProgramAccountKind::ProgramData {
+                slot: u64_at(data, 4)?,
+                upgrade_authority: option_at(12)?,
+            },
+            PROGRAM_DATA_HEADER_LEN,
+        )),
+        _ => None,
+    }
+}
+
+fn u64_at(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn pubkey_at(data: &[u8], offset: usize) -> Option<Pubkey> {
+    Some(Pubkey::new_from_array(
+        data.get(offset..offset + 32)?.try_into().ok()?,
+    ))
+}
@@ -0,0 +1,296 @@
+//! Finding the pubkeys account data refers to.
+//!
+//! Accounts point at each other: a token account stores its mint and
+//! owner, a program account its program data account, a pool its vaults
+//! and oracle. [`extract_pubkeys`] finds those references so fixture sets
+//! can be pruned to what a test touches (see
+//! [`AccountMap::prune_unreachable`](crate::AccountMap::prune_unreachable))
+//! and cloning can follow them.
+//!
+//! Keys are located in one of three ways, most precise first:
+//!
+//! 1. Built-in layouts for SPL Token (and Token-2022) mints and accounts
+//!    and the upgradeable loader's program, program data and buffer
+//!    accounts.
+//! 2. A [`FixtureSchema`] or Anchor IDL registered for the account's owner
+//!    with [`PubkeyExtractor::schema`] or [`PubkeyExtractor::idl`].
+//! 3. A heuristic that scans the data. With
+//!    [`PubkeyExtractor::known_keys`], it reports every occurrence of a
+//!    known key; otherwise it reports 32-byte windows whose bytes look
+//!    random, which suits data cloned from a cluster but misses
+//!    low-entropy test keys such as `Pubkey::new_unique()`. A key right
+//!    after other random-looking bytes, such as an Anchor discriminator,
+//!    can be reported at the wrong offset, so register the program's IDL
+//!    where one exists.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::extensions::token::create_token_account;
+//! use solana_accountgen::references::extract_pubkeys;
+//! use solana_accountgen::well_known::TOKEN_PROGRAM;
+//! use solana_pubkey::Pubkey;
+//!
+//! let (mint, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+//! let account = create_token_account(&mint, &owner, 100, &TOKEN_PROGRAM).unwrap();
+//!
+//! assert_eq!(extract_pubkeys(&account), vec![(0, mint), (32, owner)]);
+//! ```
+
+use crate::AccountGenError;
+use crate::extensions::idl::Idl;
+use crate::extensions::token::{MINT_LEN, TOKEN_ACCOUNT_LEN};
+use crate::serialization::schema::FixtureSchema;
+use crate::well_known::{BPF_LOADER_UPGRADEABLE, TOKEN_2022_PROGRAM, TOKEN_PROGRAM};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+
+/// The fewest distinct byte values a 32-byte window needs for the
+/// heuristic to treat it as a pubkey. Random keys average about 30.
+pub const MIN_DISTINCT_BYTES: usize = 24;
+
+/// Returns the pubkeys embedded in an account's data with their offsets,
+/// using the built-in layouts and the heuristic.
+///
+/// This is [`PubkeyExtractor::extract`] on a default extractor.
+pub fn extract_pubkeys(account: &Account) -> Vec<(usize, Pubkey)> {
+    PubkeyExtractor::new().extract(account)
+}
+
+/// How the data of one owner's accounts is laid out.
+#[derive(Debug, Clone)]
+enum Layout {
+    /// Every account holds one schema type from the start of its data.
+    Schema {
+        schema: FixtureSchema,
+        type_name: String,
+    },
+    /// Anchor accounts: an 8-byte discriminator selects the type of the rest.
+    Anchor {
+        schema: FixtureSchema,
+        types: Vec<([u8; 8], String)>,
+    },
+}
+
+/// Finds the pubkeys embedded in account data, using type information
+/// where it has some.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::anchor::create_anchor_account;
+/// use solana_accountgen::extensions::idl::Idl;
+/// use solana_accountgen::references::PubkeyExtractor;
+/// use solana_pubkey::Pubkey;
+///
+/// let idl = Idl::from_json(r#"{
+///     "instructions": [],
+///     "accounts": [{
+///         "name": "Vault",
+///         "type": { "kind": "struct", "fields": [
+///             { "name": "bump", "type": "u8" },
+///             { "name": "authority", "type": "pubkey" }
+///         ] }
+///     }]
+/// }"#).unwrap();
+///
+/// let program_id = Pubkey::new_unique();
+/// let authority = Pubkey::new_unique();
+/// let vault = create_anchor_account("Vault", program_id, (7u8, authority), 1_000_000).unwrap();
+///
+/// let extractor = PubkeyExtractor::new().idl(program_id, &idl).unwrap();
+/// assert_eq!(extractor.extract(&vault), vec![(9, authority)]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PubkeyExtractor {
+    layouts: HashMap<Pubkey, Layout>,
+    known: HashSet<Pubkey>,
+}
+
+impl PubkeyExtractor {
+    /// Creates an extractor with only the built-in layouts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes accounts owned by `owner` as `type_name` from `schema`.
+    pub fn schema(
+        mut self,
+        owner: Pubkey,
+        schema: FixtureSchema,
+        type_name: impl Into<String>,
+    ) -> Self {
+        let type_name = type_name.into();
+        self.layouts
+            .insert(owner, Layout::Schema { schema, type_name });
+        self
+    }
+
+    /// Decodes accounts owned by `program_id` with the account types of an
+    /// Anchor IDL, chosen by discriminator.
+    pub fn idl(mut self, program_id: Pubkey, idl: &Idl) -> Result<Self, AccountGenError> {
+        let schema = idl.to_fixture_schema()?;
+        let types = idl
+            .accounts
+            .iter()
+            .map(|account| (idl.account_discriminator(account), account.name.clone()))
+            .collect();
+        self.layouts
+            .insert(program_id, Layout::Anchor { schema, types });
+        Ok(self)
+    }
+
+    /// Makes the heuristic report occurrences of these keys instead of
+    /// random-looking windows.
+    ///
+    /// Pass the keys of the fixture set being worked on, so that test
+    /// keys are found wherever they appear.
+    pub fn known_keys<I: IntoIterator<Item = Pubkey>>(mut self, keys: I) -> Self {
+        self.known.extend(keys);
+        self
+    }
+
+    /// Returns the pubkeys embedded in `account`'s data with their
+    /// offsets, in offset order.
+    ///
+    /// Accounts whose owner has a layout but whose data does not decode
+    /// with it fall back to the heuristic.
+    pub fn extract(&self, account: &Account) -> Vec<(usize, Pubkey)> {
+        self.extract_typed(account)
+            .unwrap_or_else(|| self.scan(&account.data))
+    }
+
+    /// Returns just the pubkeys, for use as the `references` callback of
+    /// [`AccountMap::prune_unreachable`](crate::AccountMap::prune_unreachable).
+    pub fn references(&self, account: &Account) -> Vec<Pubkey> {
+        self.extract(account)
+            .into_iter()
+            .map(|(_, pubkey)| pubkey)
+            .collect()
+    }
+
+    fn extract_typed(&self, account: &Account) -> Option<Vec<(usize, Pubkey)>> {
+        let data = account.data.as_slice();
+        match self.layouts.get(&account.owner) {
+            Some(Layout::Schema { schema, type_name }) => schema.pubkeys(type_name, data).ok(),
+            Some(Layout::Anchor { schema, types }) => {
+                let discriminator = data.get(..8)?;
+                let (_, type_name) = types.iter().find(|(d, _)| d == discriminator)?;
+                let pubkeys = schema.pubkeys(type_name, &data[8..]).ok()?;
+                Some(
+                    pubkeys
+                        .into_iter()
+                        .map(|(offset, pubkey)| (offset + 8, pubkey))
+                        .collect(),
+                )
+            }
+            None if account.owner == TOKEN_PROGRAM || account.owner == TOKEN_2022_PROGRAM => {
+                token_pubkeys(data)
+            }
+            None if account.owner == BPF_LOADER_UPGRADEABLE => loader_pubkeys(data),
+            None => None,
+        }
+    }
+
+    fn scan(&self, data: &[u8]) -> Vec<(usize, Pubkey)> {
+        let mut pubkeys = Vec::new();
+        let mut offset = 0;
+        while offset + 32 <= data.len() {
+            let pubkey = read_pubkey(data, offset).unwrap();
+            if self.known.is_empty() {
+                if looks_random(&data[offset..offset + 32]) {
+                    pubkeys.push((offset, pubkey));
+                    offset += 32;
+                    continue;
+                }
+            } else if self.known.contains(&pubkey) {
+                pubkeys.push((offset, pubkey));
+            }
+            offset += 1;
+        }
+        pubkeys
+    }
+}
+
+/// Random keys rarely contain a zero byte at either end or two zero bytes
+/// in a row, while the integers around them usually do, which keeps
+/// windows from straddling a key and its neighbours.
+fn looks_random(window: &[u8]) -> bool {
+    if window[0] == 0 || window[window.len() - 1] == 0 {
+        return false;
+    }
+    if window.windows(2).any(|pair| pair == [0, 0]) {
+        return false;
+    }
+    let mut seen = [false; 256];
+    let mut distinct = 0;
+    for &byte in window {
+        if !std::mem::replace(&mut seen[byte as usize], true) {
+            distinct += 1;
+        }
+    }
+    distinct >= MIN_DISTINCT_BYTES
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    let bytes: [u8; 32] = data.get(offset..offset + 32)?.try_into().ok()?;
+    Some(Pubkey::new_from_array(bytes))
+}
+
+/// Reads an SPL `COption<Pubkey>`: a 4-byte tag followed by the key.
+fn read_option_pubkey(data: &[u8], offset: usize, pubkeys: &mut Vec<(usize, Pubkey)>) {
+    if data.get(offset..offset + 4) == Some(&1u32.to_le_bytes()[..])
+        && let Some(pubkey) = read_pubkey(data, offset + 4)
+    {
+        pubkeys.push((offset + 4, pubkey));
+    }
+}
+
+/// Token-2022 stores the account type after the base account's length,
+/// padding mints with extensions to that length too.
+fn token_pubkeys(data: &[u8]) -> Option<Vec<(usize, Pubkey)>> {
+    const MINT_TYPE: u8 = 1;
+    const ACCOUNT_TYPE: u8 = 2;
+
+    let is_mint = match (data.len(), data.get(TOKEN_ACCOUNT_LEN)) {
+        (MINT_LEN, _) => true,
+        (TOKEN_ACCOUNT_LEN, _) => false,
+        (_, Some(&MINT_TYPE)) => true,
+        (_, Some(&ACCOUNT_TYPE)) => false,
+        _ => return None,
+    };
+
+    let mut pubkeys = Vec::new();
+    if is_mint {
+        read_option_pubkey(data, 0, &mut pubkeys);
+        read_option_pubkey(data, 46, &mut pubkeys);
+    } else {
+        pubkeys.push((0, read_pubkey(data, 0)?));
+        pubkeys.push((32, read_pubkey(data, 32)?));
+        read_option_pubkey(data, 72, &mut pubkeys);
+        read_option_pubkey(data, 129, &mut pubkeys);
+    }
+    Some(pubkeys)
+}
+
+/// Reads `UpgradeableLoaderState`, a bincode enum with a 4-byte tag.
+fn loader_pubkeys(data: &[u8]) -> Option<Vec<(usize, Pubkey)>> {
+    let tag = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+    // Bincode `Option`s have a 1-byte tag
+    let option_at = |offset: usize| match data.get(offset) {
+        Some(1) => read_pubkey(data, offset + 1).map(|pubkey| vec![(offset + 1, pubkey)]),
+        Some(0) => Some(Vec::new()),
+        _ => None,
+    };
+    match tag {
+        0 => Some(Vec::new()),
+        // Buffer { authority_address }
+        1 => option_at(4),
+        // Program { programdata_address }
+        2 => Some(vec![(4, read_pubkey(data, 4)?)]),
+        // ProgramData { slot, upgrade_authority_address }
+        3 => option_at(12),
+        _ => None,
+    }
+}
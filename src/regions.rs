@@ -0,0 +1,385 @@
+//! Named regions of account data.
+//!
+//! Byte offsets say little on their own: `data[16..24]: 0500.. -> 0600..`
+//! only makes sense next to the program's account layout. A [`RegionMap`]
+//! names the regions of an account's data (a discriminator, header fields,
+//! the body, padding) and how to show their values, so diffs and dumps can
+//! read `header.seq: 5 -> 6` instead.
+//!
+//! Layouts differ per program, so [`RegionMaps`] holds one map per owner
+//! program. Maps can be built in code or loaded from JSON, which is what
+//! the CLI's `--regions` option reads:
+//!
+//! ```json
+//! {
+//!   "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS": [
+//!     { "name": "discriminator", "offset": 0, "format": { "bytes": 8 } },
+//!     { "name": "header.seq", "offset": 8, "format": "u64" },
+//!     { "name": "header.authority", "offset": 16, "format": "pubkey" },
+//!     { "name": "padding", "offset": 48, "format": { "padding": 16 } }
+//!   ]
+//! }
+//! ```
+
+use crate::AccountMap;
+use crate::error::AccountGenError;
+use serde::{Deserialize, Serialize};
+use solana_pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+/// How the bytes of a region are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegionFormat {
+    /// Raw bytes of the given length, shown as hex.
+    Bytes(usize),
+    /// Unused bytes of the given length, shown as hex.
+    Padding(usize),
+    /// A little-endian unsigned integer.
+    U8,
+    /// A little-endian unsigned integer.
+    U16,
+    /// A little-endian unsigned integer.
+    U32,
+    /// A little-endian unsigned integer.
+    U64,
+    /// A little-endian unsigned integer.
+    U128,
+    /// A little-endian signed integer.
+    I8,
+    /// A little-endian signed integer.
+    I16,
+    /// A little-endian signed integer.
+    I32,
+    /// A little-endian signed integer.
+    I64,
+    /// A little-endian signed integer.
+    I128,
+    /// A single byte, `0` for false and anything else for true.
+    Bool,
+    /// A 32-byte public key, shown as base58.
+    Pubkey,
+}
+
+impl RegionFormat {
+    /// Returns the number of bytes a region in this format covers.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Bytes(len) | Self::Padding(len) => *len,
+            Self::U8 | Self::I8 | Self::Bool => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 => 4,
+            Self::U64 | Self::I64 => 8,
+            Self::U128 | Self::I128 => 16,
+            Self::Pubkey => 32,
+        }
+    }
+
+    /// Returns true if the format covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Formats `bytes`, which must be exactly [`len`](Self::len) long.
+    fn format(&self, bytes: &[u8]) -> String {
+        fn array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+            bytes.try_into().expect("region length checked by caller")
+        }
+
+        match self {
+            Self::Bytes(_) | Self::Padding(_) => hex::encode(bytes),
+            Self::U8 => bytes[0].to_string(),
+            Self::U16 => u16::from_le_bytes(array(bytes)).to_string(),
+            Self::U32 => u32::from_le_bytes(array(bytes)).to_string(),
+            Self::U64 => u64::from_le_bytes(array(bytes)).to_string(),
+            Self::U128 => u128::from_le_bytes(array(bytes)).to_string(),
+            Self::I8 => (bytes[0] as i8).to_string(),
+            Self::I16 => i16::from_le_bytes(array(bytes)).to_string(),
+            Self::I32 => i32::from_le_bytes(array(bytes)).to_string(),
+            Self::I64 => i64::from_le_bytes(array(bytes)).to_string(),
+            Self::I128 => i128::from_le_bytes(array(bytes)).to_string(),
+            Self::Bool => (bytes[0] != 0).to_string(),
+            Self::Pubkey => Pubkey::new_from_array(array(bytes)).to_string(),
+        }
+    }
+}
+
+/// A named region of account data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    /// The region's name, such as `header.seq`.
+    pub name: String,
+    /// The offset of the region's first byte.
+    pub offset: usize,
+    /// How the region's bytes are shown.
+    pub format: RegionFormat,
+}
+
+impl Region {
+    /// Returns the byte range the region covers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the region ends
+    /// past `usize::MAX`.
+    pub fn range(&self) -> Result<Range<usize>, AccountGenError> {
+        let end = self.offset.checked_add(self.format.len()).ok_or_else(|| {
+            AccountGenError::InvalidDataFormat(format!(
+                "region {} at offset {} overflows",
+                self.name, self.offset
+            ))
+        })?;
+        Ok(self.offset..end)
+    }
+
+    /// Formats the region's value in `data`, or returns `None` if the data
+    /// is too short to hold it.
+    pub fn value(&self, data: &[u8]) -> Option<String> {
+        data.get(self.range().ok()?)
+            .map(|bytes| self.format.format(bytes))
+    }
+}
+
+/// A change to one region of account data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionChange {
+    /// The name of the region.
+    pub name: String,
+    /// The bytes the region covers.
+    pub range: Range<usize>,
+    /// The original value, or `None` if the original data did not reach it.
+    pub old: Option<String>,
+    /// The updated value, or `None` if the updated data does not reach it.
+    pub new: Option<String>,
+}
+
+impl fmt::Display for RegionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "(none)".to_string());
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.name,
+            value(&self.old),
+            value(&self.new)
+        )
+    }
+}
+
+/// The named regions of one account layout.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::regions::{RegionFormat, RegionMap};
+///
+/// let regions = RegionMap::new()
+///     .region("discriminator", 0, RegionFormat::Bytes(8))
+///     .region("header.seq", 8, RegionFormat::U64)
+///     .region("header.flags", 16, RegionFormat::U8);
+///
+/// let mut old = vec![0; 17];
+/// old[8] = 5;
+/// let mut new = old.clone();
+/// new[8] = 6;
+///
+/// let changes = regions.diff(&old, &new);
+/// assert_eq!(changes[0].to_string(), "header.seq: 5 -> 6");
+/// assert_eq!(regions.annotate_offset(10), "header.seq+2");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Vec<Region>", into = "Vec<Region>")]
+pub struct RegionMap {
+    regions: Vec<Region>,
+}
+
+impl From<Vec<Region>> for RegionMap {
+    fn from(mut regions: Vec<Region>) -> Self {
+        regions.sort_by_key(|region| region.offset);
+        Self { regions }
+    }
+}
+
+impl From<RegionMap> for Vec<Region> {
+    fn from(map: RegionMap) -> Self {
+        map.regions
+    }
+}
+
+impl RegionMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a region named `name` starting at `offset`.
+    pub fn region(mut self, name: impl Into<String>, offset: usize, format: RegionFormat) -> Self {
+        self.regions.push(Region {
+            name: name.into(),
+            offset,
+            format,
+        });
+        self.regions.sort_by_key(|region| region.offset);
+        self
+    }
+
+    /// Returns the regions, ordered by offset.
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Returns the region containing the byte at `offset`.
+    pub fn region_at(&self, offset: usize) -> Option<&Region> {
+        self.regions
+            .iter()
+            .find(|region| region.range().is_ok_and(|range| range.contains(&offset)))
+    }
+
+    /// Names the byte at `offset` relative to its region, such as
+    /// `header.seq+2`, or as `[offset]` if no region contains it.
+    pub fn annotate_offset(&self, offset: usize) -> String {
+        match self.region_at(offset) {
+            Some(region) if region.offset == offset => region.name.clone(),
+            Some(region) => format!("{}+{}", region.name, offset - region.offset),
+            None => format!("[{}]", offset),
+        }
+    }
+
+    /// Returns the regions whose value differs between `old` and `new`.
+    pub fn diff(&self, old: &[u8], new: &[u8]) -> Vec<RegionChange> {
+        self.regions
+            .iter()
+            .filter_map(|region| {
+                // A region that overflows lies past the end of any data
+                let range = region.range().ok()?;
+                (old.get(range.clone()) != new.get(range.clone())).then(|| RegionChange {
+                    name: region.name.clone(),
+                    range,
+                    old: region.value(old),
+                    new: region.value(new),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns a copy of `new` with every region restored from `old`, so
+    /// that diffing it against `old` only shows changes outside the regions.
+    pub(crate) fn mask(&self, old: &[u8], new: &[u8]) -> Vec<u8> {
+        let mut masked = new.to_vec();
+        for region in &self.regions {
+            let Ok(range) = region.range() else {
+                continue;
+            };
+            if let (Some(before), Some(after)) = (old.get(range.clone()), masked.get_mut(range)) {
+                after.copy_from_slice(before);
+            }
+        }
+        masked
+    }
+
+    /// Formats the value of every region in `data`, one `name: value` line
+    /// per region. Regions past the end of the data are skipped.
+    pub fn format(&self, data: &[u8]) -> String {
+        self.regions
+            .iter()
+            .filter_map(|region| {
+                region
+                    .value(data)
+                    .map(|value| format!("{}: {}\n", region.name, value))
+            })
+            .collect()
+    }
+}
+
+/// Region maps for the accounts of several owner programs.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::regions::{RegionMaps, RegionFormat};
+/// use solana_pubkey::Pubkey;
+///
+/// let program_id = Pubkey::new_unique();
+/// let maps = RegionMaps::from_json(&format!(
+///     r#"{{ "{}": [{{ "name": "seq", "offset": 0, "format": "u32" }}] }}"#,
+///     program_id
+/// ))
+/// .unwrap();
+/// assert_eq!(maps.get(&program_id).unwrap().regions()[0].format, RegionFormat::U32);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegionMaps {
+    maps: BTreeMap<Pubkey, RegionMap>,
+}
+
+impl RegionMaps {
+    /// Creates an empty set of maps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `map` for the accounts owned by `owner`.
+    pub fn insert(&mut self, owner: Pubkey, map: RegionMap) -> &mut Self {
+        self.maps.insert(owner, map);
+        self
+    }
+
+    /// Returns the map for accounts owned by `owner`.
+    pub fn get(&self, owner: &Pubkey) -> Option<&RegionMap> {
+        self.maps.get(owner)
+    }
+
+    /// Returns true if no maps are registered.
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+
+    /// Parses maps from a JSON object keyed by base58 owner pubkey.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the JSON is not a
+    /// region map, an owner is not a pubkey, or a region overflows.
+    pub fn from_json(json: &str) -> Result<Self, AccountGenError> {
+        let raw: BTreeMap<String, RegionMap> = serde_json::from_str(json)
+            .map_err(|e| AccountGenError::InvalidDataFormat(format!("invalid region map: {e}")))?;
+        let mut maps = Self::new();
+        for (owner, map) in raw {
+            let owner = Pubkey::from_str(&owner).map_err(|e| {
+                AccountGenError::InvalidDataFormat(format!("invalid owner {owner}: {e}"))
+            })?;
+            for region in map.regions() {
+                region.range()?;
+            }
+            maps.insert(owner, map);
+        }
+        Ok(maps)
+    }
+
+    /// Reads maps from a JSON file; see [`from_json`](Self::from_json).
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AccountGenError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Formats the region values of every account in `account_map` whose
+    /// owner has a map, as a `pubkey:` line followed by indented values.
+    pub fn format_map(&self, account_map: &AccountMap) -> String {
+        let mut accounts: Vec<_> = account_map.iter().collect();
+        accounts.sort_by_key(|(pubkey, _)| **pubkey);
+
+        let mut out = String::new();
+        for (pubkey, account) in accounts {
+            if let Some(map) = self.get(&account.owner) {
+                out.push_str(&format!("{}:\n", pubkey));
+                for line in map.format(&account.data).lines() {
+                    out.push_str(&format!("  {}\n", line));
+                }
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,196 @@
+use crate::cluster::check_data_len;
+use crate::error::AccountGenError;
+use borsh::BorshSerialize;
+
+/// A builder for ring buffer and event queue account data.
+///
+/// Orderbooks, oracles and similar programs keep history in large accounts
+/// made of a small header followed by a fixed number of fixed-size record
+/// slots. `RingBufferBuilder` lays such data out without manual offset
+/// math: the header comes first, optionally padded to a fixed size, then
+/// `capacity` slots of `record_size` bytes each. Records are Borsh-encoded
+/// and zero-padded to the slot size; unused slots are zeroed.
+///
+/// Records are written starting at slot [`head`](Self::head) and wrap
+/// around, so writing more records than there are slots overwrites the
+/// oldest ones, as the program itself would.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, RingBufferBuilder};
+/// use borsh::BorshSerialize;
+/// use solana_pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize)]
+/// struct QueueHeader {
+///     head: u32,
+///     count: u32,
+/// }
+///
+/// #[derive(BorshSerialize)]
+/// struct Fill {
+///     price: u64,
+///     size: u64,
+/// }
+///
+/// let fills = (1..=3u64).map(|i| Fill { price: 100 * i, size: i });
+/// let data = RingBufferBuilder::new(QueueHeader { head: 0, count: 3 })
+///     .record_size(24)
+///     .capacity(8)
+///     .records(fills)
+///     .build()
+///     .unwrap();
+/// assert_eq!(data.len(), 8 + 8 * 24);
+/// assert_eq!(data[8 + 24..8 + 32], 200u64.to_le_bytes());
+///
+/// let account = AccountBuilder::new()
+///     .owner(Pubkey::new_unique())
+///     .data_raw(data)
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct RingBufferBuilder {
+    header: Vec<u8>,
+    header_size: Option<usize>,
+    record_size: Option<usize>,
+    capacity: Option<usize>,
+    head: usize,
+    records: Vec<Vec<u8>>,
+    error: Option<std::io::Error>,
+}
+
+impl RingBufferBuilder {
+    /// Creates a builder whose data starts with the Borsh encoding of
+    /// `header`.
+    ///
+    /// Serialization errors are reported by [`build`](Self::build).
+    pub fn new<H: BorshSerialize>(header: H) -> Self {
+        let mut builder = Self::default();
+        match borsh::to_vec(&header) {
+            Ok(header) => builder.header = header,
+            Err(e) => builder.error = Some(e),
+        }
+        builder
+    }
+
+    /// Pads the header with zeros to `size` bytes, for layouts that reserve
+    /// space after the header fields.
+    pub fn header_size(mut self, size: usize) -> Self {
+        self.header_size = Some(size);
+        self
+    }
+
+    /// Sets the size of each record slot in bytes.
+    ///
+    /// Defaults to the size of the largest encoded record.
+    pub fn record_size(mut self, size: usize) -> Self {
+        self.record_size = Some(size);
+        self
+    }
+
+    /// Sets the number of record slots.
+    ///
+    /// Defaults to the number of records.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Sets the slot the first record is written to. Defaults to 0.
+    pub fn head(mut self, slot: usize) -> Self {
+        self.head = slot;
+        self
+    }
+
+    /// Appends records, oldest first.
+    ///
+    /// Serialization errors are reported by [`build`](Self::build).
+    pub fn records<R, I>(mut self, records: I) -> Self
+    where
+        R: BorshSerialize,
+        I: IntoIterator<Item = R>,
+    {
+        for record in records {
+            match borsh::to_vec(&record) {
+                Ok(record) => self.records.push(record),
+                Err(e) => {
+                    self.error.get_or_insert(e);
+                }
+            }
+        }
+        self
+    }
+
+    /// Returns the offset of record slot `index` in the built data.
+    pub fn record_offset(&self, index: usize) -> usize {
+        self.header_len() + index * self.slot_len()
+    }
+
+    /// Builds the account data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header or a record failed to serialize, the
+    /// header or a record does not fit its size, the head is not a valid
+    /// slot, or the data exceeds the maximum account size.
+    pub fn build(self) -> Result<Vec<u8>, AccountGenError> {
+        if let Some(e) = self.error {
+            return Err(AccountGenError::SerializationError(e));
+        }
+        let header_len = self.header_len();
+        if header_len < self.header.len() {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "header of {} bytes does not fit in {header_len} bytes",
+                self.header.len()
+            )));
+        }
+        let slot_len = self.slot_len();
+        if let Some(record) = self.records.iter().find(|record| record.len() > slot_len) {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "record of {} bytes does not fit in {slot_len}-byte slots",
+                record.len()
+            )));
+        }
+        let capacity = self.slot_count();
+        if capacity == 0 && !self.records.is_empty() {
+            return Err(AccountGenError::InvalidDataFormat(
+                "ring buffer has records but no capacity".to_string(),
+            ));
+        }
+        if self.head >= capacity.max(1) {
+            return Err(AccountGenError::InvalidDataFormat(format!(
+                "head slot {} is out of range for capacity {capacity}",
+                self.head
+            )));
+        }
+
+        let len = slot_len
+            .checked_mul(capacity)
+            .and_then(|len| len.checked_add(header_len))
+            .unwrap_or(usize::MAX);
+        check_data_len(len)?;
+
+        let mut data = vec![0; len];
+        data[..self.header.len()].copy_from_slice(&self.header);
+        for (i, record) in self.records.iter().enumerate() {
+            let offset = self.record_offset((self.head + i) % capacity);
+            data[offset..offset + slot_len].fill(0);
+            data[offset..offset + record.len()].copy_from_slice(record);
+        }
+        Ok(data)
+    }
+
+    fn header_len(&self) -> usize {
+        self.header_size.unwrap_or(self.header.len())
+    }
+
+    fn slot_len(&self) -> usize {
+        self.record_size
+            .unwrap_or_else(|| self.records.iter().map(Vec::len).max().unwrap_or_default())
+    }
+
+    fn slot_count(&self) -> usize {
+        self.capacity.unwrap_or(self.records.len())
+    }
+}
@@ -0,0 +1,202 @@
+use super::{RpcFetcher, RpcFilter};
+use crate::references::PubkeyExtractor;
+use crate::serialization::convert::{KeyedRpcAccount, RpcAccount};
+use crate::{AccountGenError, AccountMap};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+/// Sends a JSON-RPC request and returns the `result` field of the response.
+pub(crate) fn send_request(
+    url: &str,
+    method: &str,
+    params: Value,
+) -> Result<Value, AccountGenError> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    let mut response: Value = ureq::post(url)
+        .send_json(request)
+        .map_err(|e| AccountGenError::RpcError(format!("{method} request failed: {e}")))?
+        .into_json()
+        .map_err(|e| AccountGenError::RpcError(format!("{method} returned invalid JSON: {e}")))?;
+
+    if let Some(error) = response.get("error") {
+        return Err(AccountGenError::RpcError(format!(
+            "{method} failed: {error}"
+        )));
+    }
+
+    response
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| AccountGenError::RpcError(format!("{method} response has no result")))
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(method: &str, value: Value) -> Result<T, AccountGenError> {
+    serde_json::from_value(value)
+        .map_err(|e| AccountGenError::RpcError(format!("unexpected {method} response: {e}")))
+}
+
+/// Fetches a single account from a cluster.
+///
+/// Returns `Ok(None)` if the account does not exist.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::rpc::clone_account;
+/// use solana_pubkey::Pubkey;
+/// use std::str::FromStr;
+///
+/// let usdc_mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+/// let account = clone_account("https://api.mainnet-beta.solana.com", &usdc_mint)
+///     .unwrap()
+///     .expect("mint exists");
+/// ```
+pub fn clone_account(url: &str, pubkey: &Pubkey) -> Result<Option<Account>, AccountGenError> {
+    let method = "getAccountInfo";
+    let result = send_request(
+        url,
+        method,
+        json!([pubkey.to_string(), { "encoding": "base64" }]),
+    )?;
+
+    let account: Option<RpcAccount> = parse(method, result["value"].clone())?;
+    Ok(account.map(Account::from))
+}
+
+/// Fetches several accounts from a cluster in a single request.
+///
+/// Accounts that do not exist are left out of the returned map.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::rpc::clone_accounts;
+/// use solana_pubkey::Pubkey;
+///
+/// let pubkeys = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+/// let accounts = clone_accounts("http://127.0.0.1:8899", &pubkeys).unwrap();
+/// ```
+pub fn clone_accounts(url: &str, pubkeys: &[Pubkey]) -> Result<AccountMap, AccountGenError> {
+    let (_, accounts) = get_multiple_accounts(url, pubkeys, None)?;
+    Ok(pubkeys
+        .iter()
+        .zip(accounts)
+        .filter_map(|(pubkey, account)| Some((*pubkey, account?)))
+        .collect())
+}
+
+/// Fetches an account together with the accounts it refers to, up to
+/// `depth` hops away.
+///
+/// This follows pubkeys found in account data, such as the mint of a token
+/// account or the program data of a program, so a fixture set pulls in
+/// everything the account depends on. See [`RpcFetcher::fetch_recursive`]
+/// for the details, and to add caching or type information for a
+/// program's accounts.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::rpc::clone_recursive;
+/// use solana_pubkey::Pubkey;
+/// use std::str::FromStr;
+///
+/// // A token account, its mint and the mint's authority
+/// let token_account = Pubkey::from_str("8JUjWjAyXTMB4ZXcV7nk3p6Gg1fWAAoSck7xekuyADKL").unwrap();
+/// let accounts = clone_recursive("https://api.mainnet-beta.solana.com", &token_account, 2).unwrap();
+/// ```
+pub fn clone_recursive(
+    url: &str,
+    pubkey: &Pubkey,
+    depth: usize,
+) -> Result<AccountMap, AccountGenError> {
+    RpcFetcher::new(url).fetch_recursive(&[*pubkey], depth, &PubkeyExtractor::new())
+}
+
+/// Calls `getMultipleAccounts`, returning the context slot and one entry per
+/// requested pubkey.
+pub(crate) fn get_multiple_accounts(
+    url: &str,
+    pubkeys: &[Pubkey],
+    min_context_slot: Option<u64>,
+) -> Result<(u64, Vec<Option<Account>>), AccountGenError> {
+    let method = "getMultipleAccounts";
+    let keys: Vec<String> = pubkeys.iter().map(Pubkey::to_string).collect();
+    let mut config = json!({ "encoding": "base64" });
+    if let Some(slot) = min_context_slot {
+        config["minContextSlot"] = json!(slot);
+    }
+    let result = send_request(url, method, json!([keys, config]))?;
+
+    let slot: u64 = parse(method, result["context"]["slot"].clone())?;
+    let accounts: Vec<Option<RpcAccount>> = parse(method, result["value"].clone())?;
+    if accounts.len() != pubkeys.len() {
+        return Err(AccountGenError::RpcError(format!(
+            "{method} returned {} accounts for {} pubkeys",
+            accounts.len(),
+            pubkeys.len()
+        )));
+    }
+
+    Ok((
+        slot,
+        accounts
+            .into_iter()
+            .map(|account| account.map(Account::from))
+            .collect(),
+    ))
+}
+
+/// Fetches every account owned by a program that matches all `filters`.
+///
+/// This wraps `getProgramAccounts`, so an entire program's state (for
+/// example every pool of an AMM) can be pulled into a fixture set at once.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::fixture::save_account_map;
+/// use solana_accountgen::rpc::{RpcFilter, clone_program_accounts};
+/// use solana_accountgen::extensions::anchor::get_account_discriminator;
+/// use solana_pubkey::Pubkey;
+///
+/// let amm_program = Pubkey::new_unique();
+/// let pools = clone_program_accounts(
+///     "https://api.mainnet-beta.solana.com",
+///     &amm_program,
+///     &[
+///         RpcFilter::DataSize(637),
+///         RpcFilter::memcmp(0, get_account_discriminator("Pool")),
+///     ],
+/// )
+/// .unwrap();
+///
+/// save_account_map("tests/fixtures/pools.json", &pools).unwrap();
+/// ```
+pub fn clone_program_accounts(
+    url: &str,
+    program_id: &Pubkey,
+    filters: &[RpcFilter],
+) -> Result<AccountMap, AccountGenError> {
+    let method = "getProgramAccounts";
+    let filters: Vec<Value> = filters.iter().map(RpcFilter::to_json).collect();
+    let result = send_request(
+        url,
+        method,
+        json!([program_id.to_string(), { "encoding": "base64", "filters": filters }]),
+    )?;
+
+    let accounts: Vec<KeyedRpcAccount> = parse(method, result)?;
+    Ok(accounts
+        .into_iter()
+        .map(|keyed| (keyed.pubkey, keyed.account.into()))
+        .collect())
+}
@@ -0,0 +1,229 @@
+//! Recording where fixtures were cloned from, and finding those that have
+//! drifted from the cluster since.
+//!
+//! A fixture cloned from mainnet is a snapshot: the program behind it keeps
+//! writing the real account, and after a while tests exercise state that
+//! no longer exists. [`clone_with_source`] clones accounts as fixture
+//! records that remember their cluster, slot and address, and
+//! [`check_drift`] fetches those addresses again and reports every fixture
+//! whose account changed or was closed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use solana_accountgen::fixture::FixtureWriter;
+//! use solana_accountgen::rpc::drift::{check_fixture_drift, clone_with_source};
+//! use solana_pubkey::Pubkey;
+//!
+//! let url = "https://api.mainnet-beta.solana.com";
+//! let pool = Pubkey::new_unique();
+//!
+//! let mut writer = FixtureWriter::create("tests/fixtures/pool.json").unwrap();
+//! for record in clone_with_source(url, &[pool]).unwrap() {
+//!     writer.append_record(&record).unwrap();
+//! }
+//! writer.flush().unwrap();
+//!
+//! // Later, for example in a scheduled CI job
+//! let report = check_fixture_drift(url, "tests/fixtures/pool.json").unwrap();
+//! if !report.is_ok() {
+//!     println!("{report}");
+//! }
+//! ```
+
+use super::MAX_MULTIPLE_ACCOUNTS;
+use super::client::get_multiple_accounts;
+use crate::AccountGenError;
+use crate::diff::{AccountDiff, diff_accounts};
+use crate::fixture::{FixtureAccount, FixtureReader, FixtureSource};
+use solana_pubkey::Pubkey;
+use std::fmt;
+use std::path::Path;
+
+/// Fetches `pubkeys` from the cluster at `url` as fixture records whose
+/// [`source`](FixtureAccount::source) is the cluster, the context slot of
+/// the request and the pubkey.
+///
+/// Accounts that do not exist are left out.
+pub fn clone_with_source(
+    url: &str,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<FixtureAccount>, AccountGenError> {
+    let mut records = Vec::new();
+    for batch in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let (slot, accounts) = get_multiple_accounts(url, batch, None)?;
+        for (pubkey, account) in batch.iter().zip(accounts) {
+            if let Some(account) = account {
+                records.push(
+                    FixtureAccount::new(*pubkey, account).with_source(FixtureSource {
+                        cluster: url.to_string(),
+                        slot,
+                        pubkey: *pubkey,
+                    }),
+                );
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Compares the records that have a source with the current state of
+/// their accounts on the cluster at `url`.
+///
+/// `url` is passed separately from the URL stored in each source, since
+/// fixtures are often cloned through one RPC provider and checked through
+/// another. Rent epochs are not compared, since RPC nodes report them
+/// inconsistently.
+///
+/// Records whose pubkey differs from their source's were anonymized, which
+/// rewrites the pubkeys embedded in their data, so they would never match
+/// the cluster. They are listed in [`DriftReport::anonymized`] instead of
+/// being checked.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::RpcError`] if the cluster cannot be queried.
+pub fn check_drift(url: &str, records: &[FixtureAccount]) -> Result<DriftReport, AccountGenError> {
+    let mut report = DriftReport::default();
+    let mut sourced = Vec::new();
+    for record in records {
+        match &record.source {
+            Some(source) if source.pubkey != record.pubkey => report.anonymized.push(record.pubkey),
+            Some(source) => sourced.push((record, source)),
+            None => report.untracked.push(record.pubkey),
+        }
+    }
+
+    for batch in sourced.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let pubkeys: Vec<Pubkey> = batch.iter().map(|(_, source)| source.pubkey).collect();
+        let (slot, accounts) = get_multiple_accounts(url, &pubkeys, None)?;
+        report.slot = report.slot.max(slot);
+        for ((record, source), current) in batch.iter().zip(accounts) {
+            report.checked += 1;
+            let Some(current) = current else {
+                report.closed.push(record.pubkey);
+                continue;
+            };
+            let (_, stored) = (*record).clone().into_parts();
+            let mut diff = diff_accounts(&stored, &current);
+            diff.rent_epoch = None;
+            if !diff.is_empty() {
+                report.stale.push(StaleFixture {
+                    pubkey: record.pubkey,
+                    source: (*source).clone(),
+                    diff,
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Runs [`check_drift`] on the account records of a fixture file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, or
+/// [`AccountGenError::RpcError`] if the cluster cannot be queried.
+pub fn check_fixture_drift<P: AsRef<Path>>(
+    url: &str,
+    path: P,
+) -> Result<DriftReport, AccountGenError> {
+    let records = FixtureReader::open(path)?
+        .records()
+        .collect::<Result<Vec<_>, _>>()?;
+    check_drift(url, &records)
+}
+
+/// A fixture whose account has changed on the cluster.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleFixture {
+    /// The pubkey of the fixture record.
+    pub pubkey: Pubkey,
+    /// Where the record was cloned from.
+    pub source: FixtureSource,
+    /// The change from the fixture to the account on the cluster.
+    pub diff: AccountDiff,
+}
+
+/// The result of [`check_drift`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DriftReport {
+    /// The slot the cluster reported, the latest across requests.
+    pub slot: u64,
+    /// The number of records compared with the cluster.
+    pub checked: usize,
+    /// Records whose account changed, in record order.
+    pub stale: Vec<StaleFixture>,
+    /// Records whose account no longer exists, in record order.
+    pub closed: Vec<Pubkey>,
+    /// Records without a source, which were not checked.
+    pub untracked: Vec<Pubkey>,
+    /// Records stored under a different pubkey than their source, which
+    /// were not checked.
+    pub anonymized: Vec<Pubkey>,
+}
+
+impl DriftReport {
+    /// Returns true if every checked fixture matches the cluster.
+    pub fn is_ok(&self) -> bool {
+        self.stale.is_empty() && self.closed.is_empty()
+    }
+
+    /// Converts the report into an error if any fixture drifted.
+    pub fn into_result(self) -> Result<(), AccountGenError> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(AccountGenError::ConstraintViolation(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            write!(
+                f,
+                "{} fixtures match the cluster at slot {}",
+                self.checked, self.slot
+            )?;
+        } else {
+            write!(
+                f,
+                "{} of {} fixtures drifted from the cluster by slot {}:",
+                self.stale.len() + self.closed.len(),
+                self.checked,
+                self.slot
+            )?;
+            for stale in &self.stale {
+                write!(
+                    f,
+                    "\n  {}: changed since slot {}",
+                    stale.pubkey, stale.source.slot
+                )?;
+                for line in stale.diff.to_string().lines() {
+                    write!(f, "\n    {}", line)?;
+                }
+            }
+            for pubkey in &self.closed {
+                write!(f, "\n  {}: closed", pubkey)?;
+            }
+        }
+        if !self.untracked.is_empty() {
+            write!(
+                f,
+                "\n{} fixtures have no source and were not checked",
+                self.untracked.len()
+            )?;
+        }
+        if !self.anonymized.is_empty() {
+            write!(
+                f,
+                "\n{} fixtures are anonymized and were not checked",
+                self.anonymized.len()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+use super::client::get_multiple_accounts;
+use crate::fixture::FixtureAccount;
+use crate::references::PubkeyExtractor;
+use crate::well_known;
+use crate::{AccountGenError, AccountMap};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The maximum number of pubkeys `getMultipleAccounts` accepts per request.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// A polite account fetcher for cloning many accounts from a cluster.
+///
+/// `RpcFetcher` batches lookups into `getMultipleAccounts` requests, spaces
+/// requests out to stay under a rate limit, and optionally keeps an on-disk
+/// cache so repeated test runs do not hit the network at all.
+///
+/// # Cache layout
+///
+/// Each fetched account is stored at `<cache_dir>/<pubkey>/<slot>.json`,
+/// where `slot` is the pinned slot (see [`RpcFetcher::slot`]) or, if none is
+/// pinned, the context slot reported by the RPC node. Accounts that do not
+/// exist are cached too, so misses are not retried on every run. Without a
+/// pinned slot the most recent cached entry for a pubkey is used.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::rpc::RpcFetcher;
+/// use solana_pubkey::Pubkey;
+///
+/// let pubkeys: Vec<Pubkey> = (0..250).map(|_| Pubkey::new_unique()).collect();
+///
+/// let mut fetcher = RpcFetcher::new("https://api.mainnet-beta.solana.com")
+///     .cache_dir("tests/fixtures/.rpc-cache")
+///     .requests_per_second(4);
+///
+/// // Three batched requests the first time, none afterwards
+/// let accounts = fetcher.fetch_accounts(&pubkeys).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RpcFetcher {
+    url: String,
+    cache_dir: Option<PathBuf>,
+    slot: Option<u64>,
+    batch_size: usize,
+    min_interval: Duration,
+    offline: bool,
+    last_request: Option<Instant>,
+}
+
+impl RpcFetcher {
+    /// Creates a fetcher for the given RPC URL with no cache and no rate limit.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            cache_dir: None,
+            slot: None,
+            batch_size: MAX_MULTIPLE_ACCOUNTS,
+            min_interval: Duration::ZERO,
+            offline: false,
+            last_request: None,
+        }
+    }
+
+    /// Enables the on-disk cache rooted at `dir`.
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Pins the slot used as the cache key.
+    ///
+    /// Requests are sent with `minContextSlot` set to this slot, so the node
+    /// must have reached it. RPC nodes only serve current state, so the slot
+    /// is a label for the snapshot rather than a historical query.
+    pub fn slot(mut self, slot: u64) -> Self {
+        self.slot = Some(slot);
+        self
+    }
+
+    /// Sets how many pubkeys are sent per request (at most 100).
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.clamp(1, MAX_MULTIPLE_ACCOUNTS);
+        self
+    }
+
+    /// Limits the number of requests sent per second.
+    pub fn requests_per_second(mut self, requests: u32) -> Self {
+        self.min_interval = if requests == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / requests
+        };
+        self
+    }
+
+    /// When set, cache misses return an error instead of hitting the network.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Fetches a single account, returning `Ok(None)` if it does not exist.
+    pub fn fetch_account(&mut self, pubkey: &Pubkey) -> Result<Option<Account>, AccountGenError> {
+        Ok(self.fetch_accounts(&[*pubkey])?.remove_account(pubkey))
+    }
+
+    /// Fetches several accounts, serving as many as possible from the cache.
+    ///
+    /// Accounts that do not exist are left out of the returned map.
+    pub fn fetch_accounts(&mut self, pubkeys: &[Pubkey]) -> Result<AccountMap, AccountGenError> {
+        let mut accounts = AccountMap::new();
+        let mut missing = Vec::new();
+
+        for pubkey in pubkeys {
+            match self.read_cache(pubkey)? {
+                Some(Some(account)) => accounts.set_account(*pubkey, account),
+                Some(None) => {}
+                None => missing.push(*pubkey),
+            }
+        }
+
+        if !missing.is_empty() && self.offline {
+            return Err(AccountGenError::RpcError(format!(
+                "{} accounts are not cached and the fetcher is offline (first missing: {})",
+                missing.len(),
+                missing[0]
+            )));
+        }
+
+        for batch in missing.chunks(self.batch_size) {
+            self.wait_for_rate_limit();
+            let (context_slot, fetched) = get_multiple_accounts(&self.url, batch, self.slot)?;
+            let slot = self.slot.unwrap_or(context_slot);
+
+            for (pubkey, account) in batch.iter().zip(fetched) {
+                self.write_cache(pubkey, slot, account.as_ref())?;
+                if let Some(account) = account {
+                    accounts.set_account(*pubkey, account);
+                }
+            }
+        }
+
+        Ok(accounts)
+    }
+
+    /// Fetches `roots` and, up to `depth` hops away, every account they
+    /// refer to.
+    ///
+    /// References are the pubkeys `extractor` finds in account data (the
+    /// mint of a token account, the program data of a program, an oracle a
+    /// pool points at) and each account's owner. Well-known programs and
+    /// sysvars are not followed, since test environments provide them. A
+    /// `depth` of 0 fetches just the roots. Each hop is one round of
+    /// batched requests; pubkeys that turn out not to exist are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use solana_accountgen::references::PubkeyExtractor;
+    /// use solana_accountgen::rpc::RpcFetcher;
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// let pool = Pubkey::new_unique();
+    /// let mut fetcher = RpcFetcher::new("https://api.mainnet-beta.solana.com")
+    ///     .cache_dir("tests/fixtures/.rpc-cache");
+    /// let accounts = fetcher
+    ///     .fetch_recursive(&[pool], 3, &PubkeyExtractor::new())
+    ///     .unwrap();
+    /// ```
+    pub fn fetch_recursive(
+        &mut self,
+        roots: &[Pubkey],
+        depth: usize,
+        extractor: &PubkeyExtractor,
+    ) -> Result<AccountMap, AccountGenError> {
+        let mut accounts = AccountMap::new();
+        let mut visited: HashSet<Pubkey> = roots.iter().copied().collect();
+        let mut pending: Vec<Pubkey> = visited.iter().copied().collect();
+
+        for hop in 0..=depth {
+            if pending.is_empty() {
+                break;
+            }
+            let fetched = self.fetch_accounts(&pending)?;
+            pending.clear();
+            for (pubkey, account) in fetched {
+                if hop < depth {
+                    let references = extractor
+                        .references(&account)
+                        .into_iter()
+                        .chain([account.owner]);
+                    for reference in references {
+                        if well_known::name_for(&reference).is_none()
+                            && reference != Pubkey::default()
+                            && visited.insert(reference)
+                        {
+                            pending.push(reference);
+                        }
+                    }
+                }
+                accounts.set_account(pubkey, account);
+            }
+        }
+
+        Ok(accounts)
+    }
+
+    fn wait_for_rate_limit(&mut self) {
+        if let Some(last) = self.last_request {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_interval {
+                thread::sleep(self.min_interval - elapsed);
+            }
+        }
+        self.last_request = Some(Instant::now());
+    }
+
+    /// Returns `None` on a cache miss and `Some(None)` for a cached
+    /// non-existent account.
+    fn read_cache(&self, pubkey: &Pubkey) -> Result<Option<Option<Account>>, AccountGenError> {
+        let Some(dir) = &self.cache_dir else {
+            return Ok(None);
+        };
+        let account_dir = dir.join(pubkey.to_string());
+
+        let slot = match self.slot {
+            Some(slot) => slot,
+            None => match newest_cached_slot(&account_dir)? {
+                Some(slot) => slot,
+                None => return Ok(None),
+            },
+        };
+
+        let path = account_dir.join(format!("{slot}.json"));
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        let record: Option<FixtureAccount> = serde_json::from_str(&contents).map_err(|e| {
+            AccountGenError::InvalidDataFormat(format!("{}: {}", path.display(), e))
+        })?;
+        Ok(Some(record.map(|record| record.into_parts().1)))
+    }
+
+    fn write_cache(
+        &self,
+        pubkey: &Pubkey,
+        slot: u64,
+        account: Option<&Account>,
+    ) -> Result<(), AccountGenError> {
+        let Some(dir) = &self.cache_dir else {
+            return Ok(());
+        };
+        let account_dir = dir.join(pubkey.to_string());
+        fs::create_dir_all(&account_dir)?;
+
+        let record = account.map(|account| FixtureAccount::new(*pubkey, account.clone()));
+        let contents = serde_json::to_string(&record).map_err(|e| {
+            AccountGenError::SerializationError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                e,
+            ))
+        })?;
+        fs::write(account_dir.join(format!("{slot}.json")), contents)?;
+        Ok(())
+    }
+}
+
+fn newest_cached_slot(account_dir: &Path) -> Result<Option<u64>, AccountGenError> {
+    if !account_dir.exists() {
+        return Ok(None);
+    }
+
+    let mut newest = None;
+    for entry in fs::read_dir(account_dir)? {
+        let path = entry?.path();
+        let slot = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u64>().ok());
+        if slot.is_some() && slot > newest {
+            newest = slot;
+        }
+    }
+    Ok(newest)
+}
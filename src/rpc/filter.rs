@@ -0,0 +1,118 @@
+use crate::AccountGenError;
+use crate::serialization::DataEncoding;
+use serde_json::{Value, json};
+
+/// A `getProgramAccounts` filter.
+///
+/// Filters are combined with a logical AND: an account is returned only if
+/// it satisfies every filter in the list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcFilter {
+    /// Matches accounts whose data is exactly this many bytes long.
+    DataSize(u64),
+    /// Matches accounts whose data contains `bytes` starting at `offset`.
+    Memcmp {
+        /// Byte offset into the account data.
+        offset: usize,
+        /// The bytes to compare against.
+        bytes: Vec<u8>,
+    },
+}
+
+impl RpcFilter {
+    /// Creates a memcmp filter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::rpc::RpcFilter;
+    /// use solana_accountgen::extensions::anchor::get_account_discriminator;
+    ///
+    /// // Match every Anchor account of type "pool"
+    /// let filter = RpcFilter::memcmp(0, get_account_discriminator("pool"));
+    /// ```
+    pub fn memcmp(offset: usize, bytes: impl AsRef<[u8]>) -> Self {
+        Self::Memcmp {
+            offset,
+            bytes: bytes.as_ref().to_vec(),
+        }
+    }
+
+    /// Returns the filter in the JSON form expected by the RPC API.
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::DataSize(size) => json!({ "dataSize": size }),
+            Self::Memcmp { offset, bytes } => json!({
+                "memcmp": {
+                    "offset": offset,
+                    "bytes": base64::encode(bytes),
+                    "encoding": "base64",
+                }
+            }),
+        }
+    }
+
+    /// Parses a filter from its JSON RPC form.
+    ///
+    /// Memcmp bytes default to base58, as they do on a real RPC node. This
+    /// lets tests check the exact filter JSON their off-chain code sends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::rpc::RpcFilter;
+    /// use serde_json::json;
+    ///
+    /// let filter = RpcFilter::from_json(&json!({ "memcmp": { "offset": 4, "bytes": "2" } })).unwrap();
+    /// assert_eq!(filter, RpcFilter::memcmp(4, [1]));
+    /// ```
+    pub fn from_json(value: &Value) -> Result<Self, AccountGenError> {
+        if let Some(size) = value.get("dataSize").and_then(Value::as_u64) {
+            return Ok(Self::DataSize(size));
+        }
+        let memcmp = value
+            .get("memcmp")
+            .ok_or_else(|| AccountGenError::InvalidDataFormat(format!("unknown filter {value}")))?;
+        let offset = memcmp
+            .get("offset")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| {
+                AccountGenError::InvalidDataFormat("memcmp has no offset".to_string())
+            })?;
+        let bytes = memcmp
+            .get("bytes")
+            .and_then(Value::as_str)
+            .ok_or_else(|| AccountGenError::InvalidDataFormat("memcmp has no bytes".to_string()))?;
+        let encoding: DataEncoding = memcmp
+            .get("encoding")
+            .and_then(Value::as_str)
+            .unwrap_or("base58")
+            .parse()?;
+        Ok(Self::Memcmp {
+            offset: offset as usize,
+            bytes: encoding.decode(bytes)?,
+        })
+    }
+
+    /// Returns true if account data satisfies the filter.
+    ///
+    /// A memcmp filter reaching past the end of the data does not match.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::rpc::RpcFilter;
+    ///
+    /// assert!(RpcFilter::DataSize(3).matches(&[1, 2, 3]));
+    /// assert!(RpcFilter::memcmp(1, [2, 3]).matches(&[1, 2, 3]));
+    /// assert!(!RpcFilter::memcmp(2, [3, 4]).matches(&[1, 2, 3]));
+    /// ```
+    pub fn matches(&self, data: &[u8]) -> bool {
+        match self {
+            Self::DataSize(size) => data.len() as u64 == *size,
+            Self::Memcmp { offset, bytes } => data
+                .get(*offset..)
+                .is_some_and(|rest| rest.starts_with(bytes)),
+        }
+    }
+}
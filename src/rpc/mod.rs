@@ -0,0 +1,42 @@
+//! Cloning accounts from a live cluster.
+//!
+//! This module fetches accounts over JSON RPC so that real on-chain state
+//! can be turned into local fixtures. The network client is only compiled
+//! with the `rpc` feature; the filter types are always available.
+//!
+//! ```toml
+//! solana-accountgen = { version = "0.1", features = ["rpc"] }
+//! ```
+//!
+//! [`clone_recursive`] follows the pubkeys stored in account data, pulling
+//! in an account's mint, program data or oracle along with it.
+//!
+//! [`replay::fetch_transaction`] goes one step further and pulls a
+//! confirmed transaction together with the accounts it touched, so it can
+//! be re-executed locally.
+//!
+//! [`drift`] records the cluster, slot and address cloned fixtures came
+//! from, and checks them against the cluster later to find stale ones.
+//!
+//! The other direction is covered too: [`MockRpcServer`] serves an
+//! [`AccountMap`](crate::AccountMap) over JSON RPC so off-chain clients can
+//! be tested against fixture state. It needs no extra dependencies and is
+//! always available.
+
+#[cfg(feature = "rpc")]
+mod client;
+#[cfg(feature = "rpc")]
+pub mod drift;
+#[cfg(feature = "rpc")]
+mod fetcher;
+mod filter;
+#[cfg(feature = "rpc")]
+pub mod replay;
+mod server;
+
+#[cfg(feature = "rpc")]
+pub use client::{clone_account, clone_accounts, clone_program_accounts, clone_recursive};
+#[cfg(feature = "rpc")]
+pub use fetcher::{MAX_MULTIPLE_ACCOUNTS, RpcFetcher};
+pub use filter::RpcFilter;
+pub use server::MockRpcServer;
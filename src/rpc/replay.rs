@@ -0,0 +1,187 @@
+//! Replaying confirmed transactions locally.
+//!
+//! [`fetch_transaction`] turns a transaction signature into everything
+//! needed to run it again under `solana-program-test`: the transaction
+//! itself and the accounts it touched. Swap in a locally built program,
+//! load the accounts and process the transaction to step through a mainnet
+//! failure.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use solana_accountgen::extensions::program_test::ProgramTestExt;
+//! use solana_accountgen::rpc::replay::fetch_transaction;
+//! use solana_program_test::ProgramTest;
+//! use solana_pubkey::Pubkey;
+//!
+//! # async fn run() {
+//! let (mut accounts, transaction) = fetch_transaction(
+//!     "https://api.mainnet-beta.solana.com",
+//!     "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW",
+//! )
+//! .unwrap();
+//!
+//! // Replace the cloned program with the local build
+//! let program_id = transaction.message.static_account_keys()[2];
+//! accounts.remove_account(&program_id);
+//!
+//! let mut program_test = ProgramTest::new("my_program", program_id, None);
+//! program_test.add_account_map(accounts);
+//! let (mut banks_client, _payer, _blockhash) = program_test.start().await;
+//! let result = banks_client.process_transaction(transaction).await;
+//! # }
+//! ```
+//!
+//! The transaction keeps its original recent blockhash and signatures.
+//! Depending on how the test bank is configured, the message may need a
+//! fresh blockhash, and then new signatures, before it is accepted.
+
+use super::MAX_MULTIPLE_ACCOUNTS;
+use super::client::{clone_accounts, send_request};
+use crate::{AccountGenError, AccountMap, well_known};
+use serde_json::{Value, json};
+use solana_pubkey::Pubkey;
+use solana_transaction::versioned::VersionedTransaction;
+use std::str::FromStr;
+
+/// Fetches a confirmed transaction and the accounts it touched.
+///
+/// The accounts are every static account key, every address loaded from a
+/// lookup table, the lookup tables themselves and the program data
+/// accounts of upgradeable programs. Missing accounts are left out.
+///
+/// RPC nodes only serve current account state, so the accounts are cloned
+/// as they are now rather than as they were when the transaction ran.
+/// Where the transaction's metadata records the prior state it is restored:
+/// lamports come from `preBalances` and SPL Token amounts from
+/// `preTokenBalances`. Other data may have changed since.
+pub fn fetch_transaction(
+    url: &str,
+    signature: &str,
+) -> Result<(AccountMap, VersionedTransaction), AccountGenError> {
+    let method = "getTransaction";
+    let result = send_request(
+        url,
+        method,
+        json!([
+            signature,
+            {
+                "encoding": "base64",
+                "commitment": "confirmed",
+                "maxSupportedTransactionVersion": 0,
+            }
+        ]),
+    )?;
+    if result.is_null() {
+        return Err(AccountGenError::RpcError(format!(
+            "transaction {signature} not found"
+        )));
+    }
+
+    let transaction = decode_transaction(&result["transaction"])?;
+    let meta = &result["meta"];
+
+    // Account keys in the order the metadata indexes them
+    let mut keys = transaction.message.static_account_keys().to_vec();
+    for list in ["writable", "readonly"] {
+        keys.extend(parse_pubkeys(&meta["loadedAddresses"][list])?);
+    }
+
+    let mut pubkeys = keys.clone();
+    if let Some(lookups) = transaction.message.address_table_lookups() {
+        pubkeys.extend(lookups.iter().map(|lookup| lookup.account_key));
+    }
+    pubkeys.sort();
+    pubkeys.dedup();
+    let mut accounts = fetch_all(url, &pubkeys)?;
+
+    // Upgradeable programs keep their code in a separate account
+    let program_data: Vec<Pubkey> = accounts
+        .iter()
+        .filter(|(_, account)| account.owner == well_known::BPF_LOADER_UPGRADEABLE)
+        .filter_map(|(_, account)| program_data_address(&account.data))
+        .filter(|address| accounts.get_account(address).is_none())
+        .collect();
+    accounts.merge(fetch_all(url, &program_data)?);
+
+    restore_pre_balances(&mut accounts, &keys, meta);
+    Ok((accounts, transaction))
+}
+
+fn decode_transaction(value: &Value) -> Result<VersionedTransaction, AccountGenError> {
+    let encoded = match value {
+        Value::Array(parts) if parts.get(1).and_then(Value::as_str) == Some("base64") => {
+            parts[0].as_str()
+        }
+        _ => None,
+    }
+    .ok_or_else(|| {
+        AccountGenError::RpcError("getTransaction did not return base64 data".to_string())
+    })?;
+    let bytes = base64::decode(encoded)
+        .map_err(|e| AccountGenError::RpcError(format!("invalid transaction base64: {e}")))?;
+    bincode::deserialize(&bytes)
+        .map_err(|e| AccountGenError::RpcError(format!("invalid transaction: {e}")))
+}
+
+fn parse_pubkeys(value: &Value) -> Result<Vec<Pubkey>, AccountGenError> {
+    let Some(values) = value.as_array() else {
+        return Ok(Vec::new());
+    };
+    values
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .and_then(|s| Pubkey::from_str(s).ok())
+                .ok_or_else(|| AccountGenError::RpcError(format!("invalid pubkey {value}")))
+        })
+        .collect()
+}
+
+fn fetch_all(url: &str, pubkeys: &[Pubkey]) -> Result<AccountMap, AccountGenError> {
+    let mut accounts = AccountMap::new();
+    for chunk in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        accounts.merge(clone_accounts(url, chunk)?);
+    }
+    Ok(accounts)
+}
+
+/// Returns the program data address stored in an upgradeable program
+/// account (`UpgradeableLoaderState::Program`).
+fn program_data_address(data: &[u8]) -> Option<Pubkey> {
+    if data.get(..4)? != 2u32.to_le_bytes() {
+        return None;
+    }
+    Some(Pubkey::new_from_array(data.get(4..36)?.try_into().ok()?))
+}
+
+fn restore_pre_balances(accounts: &mut AccountMap, keys: &[Pubkey], meta: &Value) {
+    if let Some(balances) = meta["preBalances"].as_array() {
+        for (pubkey, balance) in keys.iter().zip(balances) {
+            if let (Some(account), Some(lamports)) =
+                (accounts.get_account_mut(pubkey), balance.as_u64())
+            {
+                account.lamports = lamports;
+            }
+        }
+    }
+
+    for balance in meta["preTokenBalances"].as_array().into_iter().flatten() {
+        let index = balance["accountIndex"].as_u64().unwrap_or(u64::MAX) as usize;
+        let amount = balance["uiTokenAmount"]["amount"]
+            .as_str()
+            .and_then(|amount| amount.parse::<u64>().ok());
+        let (Some(pubkey), Some(amount)) = (keys.get(index), amount) else {
+            continue;
+        };
+        // The amount sits after the mint and owner in both token programs
+        if let Some(account) = accounts.get_account_mut(pubkey)
+            && (account.owner == well_known::TOKEN_PROGRAM
+                || account.owner == well_known::TOKEN_2022_PROGRAM)
+            && account.data.len() >= 72
+        {
+            account.data[64..72].copy_from_slice(&amount.to_le_bytes());
+        }
+    }
+}
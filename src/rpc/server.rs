@@ -0,0 +1,352 @@
+use super::RpcFilter;
+use crate::serialization::DataEncoding;
+use crate::{AccountGenError, AccountMap, SharedAccountMap};
+use serde_json::{Value, json};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+
+/// JSON-RPC error code for an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for malformed parameters.
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code for a body that is not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+/// The largest request body accepted, the same limit a validator's RPC
+/// service applies.
+const MAX_REQUEST_BODY_SIZE: usize = 50 * (1 << 10);
+
+/// An in-process JSON RPC server backed by an [`AccountMap`].
+///
+/// The server answers `getAccountInfo`, `getMultipleAccounts` and
+/// `getProgramAccounts` (with `dataSize` and `memcmp` filters) from the
+/// accounts it holds, so off-chain clients and SDKs can be tested against
+/// fixture state without running a validator. Every other method returns
+/// a "Method not found" error.
+///
+/// Account data is encoded as requested with `base58`, `base64` or, with
+/// the `zstd` feature, `base64+zstd`; `jsonParsed` falls back to `base64`
+/// as a real node does for accounts it cannot parse. Requests without an
+/// encoding get `base64`. `dataSlice` is honoured. Request bodies over
+/// 50 KiB are refused with `413 Payload Too Large`, as a real node does.
+///
+/// The server can be started from a [`SharedAccountMap`] so tests and
+/// other tasks change the served accounts through their own handle.
+///
+/// The server stops when it is dropped.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, AccountMap};
+/// use solana_accountgen::rpc::MockRpcServer;
+/// use solana_pubkey::Pubkey;
+///
+/// let mut accounts = AccountMap::new();
+/// accounts
+///     .add_with_builder(Pubkey::new_unique(), AccountBuilder::new().balance(1_000))
+///     .unwrap();
+///
+/// let server = MockRpcServer::start(accounts).unwrap();
+/// println!("point your client at {}", server.url());
+/// ```
+pub struct MockRpcServer {
+    local_addr: SocketAddr,
+    state: Arc<ServerState>,
+    handle: Option<JoinHandle<()>>,
+}
+
+struct ServerState {
+    accounts: SharedAccountMap,
+    slot: AtomicU64,
+    shutdown: AtomicBool,
+}
+
+impl MockRpcServer {
+    /// Starts a server on an ephemeral localhost port.
+    pub fn start(accounts: impl Into<SharedAccountMap>) -> Result<Self, AccountGenError> {
+        Self::bind("127.0.0.1:0", accounts)
+    }
+
+    /// Starts a server on the given address.
+    pub fn bind<A: ToSocketAddrs>(
+        addr: A,
+        accounts: impl Into<SharedAccountMap>,
+    ) -> Result<Self, AccountGenError> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let state = Arc::new(ServerState {
+            accounts: accounts.into(),
+            slot: AtomicU64::new(0),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let accept_state = Arc::clone(&state);
+        let handle = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accept_state.shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let state = Arc::clone(&accept_state);
+                thread::spawn(move || {
+                    // A client that hangs up mid-request is not the server's problem
+                    let _ = handle_connection(stream, &state);
+                });
+            }
+        });
+
+        Ok(Self {
+            local_addr,
+            state,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Returns the HTTP URL of the server.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.local_addr)
+    }
+
+    /// Sets the slot reported in response contexts (0 by default).
+    pub fn set_slot(&self, slot: u64) {
+        self.state.slot.store(slot, Ordering::SeqCst);
+    }
+
+    /// Adds or replaces an account while the server is running.
+    pub fn set_account(&self, pubkey: Pubkey, account: Account) {
+        self.state.accounts.set_account(pubkey, account);
+    }
+
+    /// Removes an account while the server is running.
+    pub fn remove_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.state.accounts.remove_account(pubkey)
+    }
+
+    /// Returns a copy of the accounts currently being served.
+    pub fn accounts(&self) -> AccountMap {
+        self.state.accounts.snapshot()
+    }
+
+    /// Returns a handle to the accounts being served; changes made through
+    /// it are seen by the next request.
+    pub fn shared_accounts(&self) -> SharedAccountMap {
+        self.state.accounts.clone()
+    }
+}
+
+impl Drop for MockRpcServer {
+    fn drop(&mut self) {
+        self.state.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.local_addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &ServerState) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line == "\r\n" || line == "\n" {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let mut stream = stream;
+    if content_length > MAX_REQUEST_BODY_SIZE {
+        return write_response(&mut stream, "413 Payload Too Large", "");
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let response = match serde_json::from_slice::<Value>(&body) {
+        Ok(Value::Array(requests)) => Value::Array(
+            requests
+                .iter()
+                .map(|request| handle_request(request, state))
+                .collect(),
+        ),
+        Ok(request) => handle_request(&request, state),
+        Err(e) => error_response(Value::Null, PARSE_ERROR, format!("Parse error: {e}")),
+    };
+    write_response(&mut stream, "200 OK", &response.to_string())
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn handle_request(request: &Value, state: &ServerState) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = request.get("params").cloned().unwrap_or(json!([]));
+
+    let accounts = state.accounts.read();
+    let slot = state.slot.load(Ordering::SeqCst);
+    let result = match method {
+        "getAccountInfo" => get_account_info(&accounts, slot, &params),
+        "getMultipleAccounts" => get_multiple_accounts(&accounts, slot, &params),
+        "getProgramAccounts" => get_program_accounts(&accounts, slot, &params),
+        _ => {
+            return error_response(id, METHOD_NOT_FOUND, "Method not found".to_string());
+        }
+    };
+
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error_response(id, INVALID_PARAMS, format!("Invalid params: {e}")),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn get_account_info(
+    accounts: &AccountMap,
+    slot: u64,
+    params: &Value,
+) -> Result<Value, AccountGenError> {
+    let pubkey = parse_pubkey(&params[0])?;
+    let config = &params[1];
+    let value = match accounts.get_account(&pubkey) {
+        Some(account) => encode_account(account, config)?,
+        None => Value::Null,
+    };
+    Ok(json!({ "context": { "slot": slot }, "value": value }))
+}
+
+fn get_multiple_accounts(
+    accounts: &AccountMap,
+    slot: u64,
+    params: &Value,
+) -> Result<Value, AccountGenError> {
+    let pubkeys = params[0]
+        .as_array()
+        .ok_or_else(|| invalid("expected an array of pubkeys"))?;
+    let config = &params[1];
+    let values = pubkeys
+        .iter()
+        .map(
+            |pubkey| match accounts.get_account(&parse_pubkey(pubkey)?) {
+                Some(account) => encode_account(account, config),
+                None => Ok(Value::Null),
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(json!({ "context": { "slot": slot }, "value": values }))
+}
+
+fn get_program_accounts(
+    accounts: &AccountMap,
+    slot: u64,
+    params: &Value,
+) -> Result<Value, AccountGenError> {
+    let program_id = parse_pubkey(&params[0])?;
+    let config = &params[1];
+    let filters = match config.get("filters") {
+        Some(Value::Array(filters)) => filters
+            .iter()
+            .map(RpcFilter::from_json)
+            .collect::<Result<Vec<_>, _>>()?,
+        Some(_) => return Err(invalid("filters must be an array")),
+        None => Vec::new(),
+    };
+
+    let program_accounts = accounts.get_program_accounts(&program_id, &filters);
+    let mut matching: Vec<_> = program_accounts.iter().collect();
+    matching.sort_by_key(|(pubkey, _)| **pubkey);
+
+    let values = matching
+        .into_iter()
+        .map(|(pubkey, account)| {
+            Ok(json!({
+                "pubkey": pubkey.to_string(),
+                "account": encode_account(account, config)?,
+            }))
+        })
+        .collect::<Result<Vec<_>, AccountGenError>>()?;
+
+    if config.get("withContext").and_then(Value::as_bool) == Some(true) {
+        Ok(json!({ "context": { "slot": slot }, "value": values }))
+    } else {
+        Ok(Value::Array(values))
+    }
+}
+
+fn encode_account(account: &Account, config: &Value) -> Result<Value, AccountGenError> {
+    let mut data = account.data.as_slice();
+    if let Some(slice) = config.get("dataSlice") {
+        let offset = slice["offset"].as_u64().unwrap_or(0) as usize;
+        let length = slice["length"].as_u64().unwrap_or(0) as usize;
+        let start = offset.min(data.len());
+        let end = start.saturating_add(length).min(data.len());
+        data = &data[start..end];
+    }
+
+    let encoding = config
+        .get("encoding")
+        .and_then(Value::as_str)
+        .unwrap_or("base64");
+    let data = match encoding {
+        "base58" => json!([DataEncoding::Base58.encode(data), "base58"]),
+        "base64" | "jsonParsed" => json!([DataEncoding::Base64.encode(data), "base64"]),
+        #[cfg(feature = "zstd")]
+        "base64+zstd" => json!([
+            crate::fixture::record::encoded_data::compress(data)?,
+            "base64+zstd"
+        ]),
+        other => return Err(invalid(&format!("unsupported encoding {other}"))),
+    };
+
+    Ok(json!({
+        "lamports": account.lamports,
+        "owner": account.owner.to_string(),
+        "data": data,
+        "executable": account.executable,
+        "rentEpoch": account.rent_epoch,
+        "space": account.data.len(),
+    }))
+}
+
+fn parse_pubkey(value: &Value) -> Result<Pubkey, AccountGenError> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| invalid("expected a base58 pubkey"))?;
+    Pubkey::from_str(s).map_err(|e| invalid(&format!("invalid pubkey {s}: {e}")))
+}
+
+fn invalid(message: &str) -> AccountGenError {
+    AccountGenError::InvalidDataFormat(message.to_string())
+}
@@ -0,0 +1,471 @@
+//! Running a test as a sequence of steps with named checkpoints.
+//!
+//! Integration tests of a program tend to follow one script: set up
+//! accounts, send a transaction, check what changed, move the clock, send
+//! another. A [`ScenarioRunner`] holds that script as a list of [`Step`]s
+//! and runs it against any [`TestBackend`]. A checkpoint step snapshots
+//! every account the script mentions, and later steps can assert which of
+//! them changed since. When a step fails, the error lists every step with
+//! its outcome and what changed since the last checkpoint, so a failure
+//! deep in a long script reads as a story rather than a bare assertion.
+//!
+//! Steps are serde types, so a script can also be kept as JSON next to the
+//! fixtures it uses, or as YAML with the `yaml` feature.
+//! Pubkeys and account data use the same encodings as fixture files.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::harness::LocalHarness;
+//! use solana_accountgen::runner::ScenarioRunner;
+//! use solana_accountgen::{AccountBuilder, AccountMap};
+//! use solana_account_info::AccountInfo;
+//! use solana_instruction::{AccountMeta, Instruction};
+//! use solana_keypair::Keypair;
+//! use solana_program_error::ProgramResult;
+//! use solana_pubkey::Pubkey;
+//! use solana_signer::Signer;
+//!
+//! fn process_instruction(_: &Pubkey, accounts: &[AccountInfo], _: &[u8]) -> ProgramResult {
+//!     accounts[0].try_borrow_mut_data()?[0] += 1;
+//!     Ok(())
+//! }
+//!
+//! let program_id = Pubkey::new_unique();
+//! let (payer, counter) = (Keypair::new(), Pubkey::new_unique());
+//! let mut accounts = AccountMap::new();
+//! accounts.add_with_builder(payer.pubkey(), AccountBuilder::new().balance(1_000_000_000)).unwrap();
+//! accounts
+//!     .add_with_builder(counter, AccountBuilder::new().owner(program_id).data_raw(vec![0]))
+//!     .unwrap();
+//! let increment =
+//!     Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(counter, false)]);
+//!
+//! let runner = ScenarioRunner::new()
+//!     .set_accounts(accounts)
+//!     .checkpoint("initialized")
+//!     .send(vec![increment])
+//!     .assert_changed("initialized", &[counter]);
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let mut harness = LocalHarness::new(program_id, process_instruction);
+//! let run = runner.run(&mut harness, &payer, &[]).await.unwrap();
+//! let before = run.checkpoint("initialized").unwrap();
+//! assert_eq!(before.get_account(&counter).unwrap().data, [0]);
+//! # });
+//! ```
+
+use crate::backend::{self, TestBackend};
+use crate::diff::{AccountMapDiff, diff_maps};
+use crate::fixture::FixtureInstruction;
+use crate::{AccountGenError, AccountMap};
+use serde::{Deserialize, Serialize};
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Write};
+
+/// One step of a [`ScenarioRunner`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "step",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Step {
+    /// Writes accounts into the backend.
+    SetAccounts { accounts: AccountMap },
+    /// Sends the instructions as one transaction, which must succeed
+    /// unless `expect_failure` is set.
+    Send {
+        instructions: Vec<FixtureInstruction>,
+        #[serde(default)]
+        expect_failure: bool,
+    },
+    /// Moves the Clock forward to `slot`.
+    Warp { slot: u64 },
+    /// Snapshots every account the scenario mentions under `name`.
+    Checkpoint { name: String },
+    /// Checks that exactly `accounts`, among those the scenario mentions,
+    /// changed, appeared or disappeared since the checkpoint `since`.
+    ///
+    /// The fee payer is left out of the check, since only backends that
+    /// charge fees change its balance.
+    AssertChanged {
+        since: String,
+        #[serde(with = "pubkey_strings")]
+        accounts: Vec<Pubkey>,
+    },
+    /// Checks that `accounts` exist in the backend with these contents.
+    AssertAccounts { accounts: AccountMap },
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SetAccounts { accounts } => write!(f, "set {} accounts", accounts.len()),
+            Self::Send {
+                instructions,
+                expect_failure,
+            } => write!(
+                f,
+                "send {} instructions{}",
+                instructions.len(),
+                if *expect_failure {
+                    ", expecting failure"
+                } else {
+                    ""
+                }
+            ),
+            Self::Warp { slot } => write!(f, "warp to slot {}", slot),
+            Self::Checkpoint { name } => write!(f, "checkpoint {:?}", name),
+            Self::AssertChanged { since, accounts } => {
+                write!(
+                    f,
+                    "assert {} accounts changed since {:?}",
+                    accounts.len(),
+                    since
+                )
+            }
+            Self::AssertAccounts { accounts } => write!(f, "assert {} accounts", accounts.len()),
+        }
+    }
+}
+
+/// A sequence of [`Step`]s run against a [`TestBackend`].
+///
+/// See the [`runner`](crate::runner) module for an example.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScenarioRunner {
+    steps: Vec<Step>,
+}
+
+/// The checkpoints of a successful [`ScenarioRunner::run`].
+#[derive(Debug, Clone, Default)]
+pub struct ScenarioRun {
+    checkpoints: BTreeMap<String, AccountMap>,
+}
+
+impl ScenarioRun {
+    /// Returns the accounts snapshotted at the checkpoint `name`.
+    pub fn checkpoint(&self, name: &str) -> Option<&AccountMap> {
+        self.checkpoints.get(name)
+    }
+
+    /// Returns the names of the checkpoints, in order.
+    pub fn checkpoint_names(&self) -> impl Iterator<Item = &str> {
+        self.checkpoints.keys().map(String::as_str)
+    }
+}
+
+impl ScenarioRunner {
+    /// Creates a runner with no steps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the steps from a JSON array.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if `json` is not a
+    /// list of steps.
+    pub fn from_json(json: &str) -> Result<Self, AccountGenError> {
+        serde_json::from_str(json)
+            .map_err(|e| AccountGenError::InvalidDataFormat(format!("invalid scenario: {e}")))
+    }
+
+    /// Reads the steps from a YAML list.
+    ///
+    /// Requires the `yaml` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if `yaml` is not a
+    /// list of steps.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Self, AccountGenError> {
+        serde_yaml::from_str(yaml)
+            .map_err(|e| AccountGenError::InvalidDataFormat(format!("invalid scenario: {e}")))
+    }
+
+    /// Writes the steps as a JSON array.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("scenario steps always serialize")
+    }
+
+    /// Returns the steps, in order.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Appends a step.
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Appends a step writing `accounts` into the backend.
+    pub fn set_accounts(self, accounts: AccountMap) -> Self {
+        self.step(Step::SetAccounts { accounts })
+    }
+
+    /// Appends a step sending `instructions` as one transaction that must
+    /// succeed.
+    pub fn send(self, instructions: Vec<Instruction>) -> Self {
+        self.step(Step::Send {
+            instructions: instructions.into_iter().map(Into::into).collect(),
+            expect_failure: false,
+        })
+    }
+
+    /// Appends a step sending `instructions` as one transaction that must
+    /// fail.
+    pub fn send_expecting_failure(self, instructions: Vec<Instruction>) -> Self {
+        self.step(Step::Send {
+            instructions: instructions.into_iter().map(Into::into).collect(),
+            expect_failure: true,
+        })
+    }
+
+    /// Appends a step moving the Clock forward to `slot`.
+    pub fn warp(self, slot: u64) -> Self {
+        self.step(Step::Warp { slot })
+    }
+
+    /// Appends a step snapshotting the accounts under `name`.
+    pub fn checkpoint(self, name: impl Into<String>) -> Self {
+        self.step(Step::Checkpoint { name: name.into() })
+    }
+
+    /// Appends a step checking that exactly `accounts` changed since the
+    /// checkpoint `since`, leaving out the fee payer; see
+    /// [`Step::AssertChanged`].
+    pub fn assert_changed(self, since: impl Into<String>, accounts: &[Pubkey]) -> Self {
+        self.step(Step::AssertChanged {
+            since: since.into(),
+            accounts: accounts.to_vec(),
+        })
+    }
+
+    /// Appends a step checking that `accounts` exist with these contents.
+    pub fn assert_accounts(self, accounts: AccountMap) -> Self {
+        self.step(Step::AssertAccounts { accounts })
+    }
+
+    /// Returns every account the steps mention, and the payer and
+    /// signers.
+    fn mentioned(&self, payer: &Keypair, signers: &[&Keypair]) -> BTreeSet<Pubkey> {
+        let mut pubkeys: BTreeSet<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+        pubkeys.insert(payer.pubkey());
+        for step in &self.steps {
+            match step {
+                Step::SetAccounts { accounts } | Step::AssertAccounts { accounts } => {
+                    pubkeys.extend(accounts.iter().map(|(pubkey, _)| *pubkey))
+                }
+                Step::Send { instructions, .. } => pubkeys.extend(
+                    instructions
+                        .iter()
+                        .flat_map(|instruction| &instruction.accounts)
+                        .map(|meta| meta.pubkey),
+                ),
+                Step::AssertChanged { accounts, .. } => pubkeys.extend(accounts),
+                Step::Warp { .. } | Step::Checkpoint { .. } => {}
+            }
+        }
+        pubkeys
+    }
+
+    /// Runs the steps in order against `backend`, stopping at the first
+    /// that fails.
+    ///
+    /// `payer` pays for every transaction, and each is additionally signed
+    /// by whichever of `signers` its instructions require.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::ConstraintViolation`] with a report of
+    /// every step run if one fails, or the error of a failed read.
+    pub async fn run<B: TestBackend>(
+        &self,
+        backend: &mut B,
+        payer: &Keypair,
+        signers: &[&Keypair],
+    ) -> Result<ScenarioRun, AccountGenError> {
+        let mentioned = self.mentioned(payer, signers);
+        let mut run = ScenarioRun::default();
+        let mut last_checkpoint: Option<&str> = None;
+
+        for (index, step) in self.steps.iter().enumerate() {
+            let failure = match step {
+                Step::SetAccounts { accounts } => {
+                    backend::load_accounts(backend, accounts)?;
+                    None
+                }
+                Step::Send {
+                    instructions,
+                    expect_failure,
+                } => {
+                    let instructions: Vec<Instruction> =
+                        instructions.iter().cloned().map(Into::into).collect();
+                    let result = send(backend, &instructions, payer, signers).await;
+                    match (result, expect_failure) {
+                        (Ok(()), false) | (Err(AccountGenError::TransactionFailed(_)), true) => {
+                            None
+                        }
+                        (Ok(()), true) => Some("the transaction succeeded".to_string()),
+                        (Err(AccountGenError::TransactionFailed(e)), false) => {
+                            Some(format!("the transaction failed: {}", e))
+                        }
+                        (Err(e), _) => return Err(e),
+                    }
+                }
+                Step::Warp { slot } => backend.warp(*slot).err().map(|e| e.to_string()),
+                Step::Checkpoint { name } => {
+                    let accounts = backend::snapshot(backend, mentioned.iter().copied()).await?;
+                    run.checkpoints.insert(name.clone(), accounts);
+                    last_checkpoint = Some(name);
+                    None
+                }
+                Step::AssertChanged { since, accounts } => match run.checkpoints.get(since) {
+                    None => Some(format!(
+                        "there is no checkpoint {:?} before this step",
+                        since
+                    )),
+                    Some(before) => {
+                        let after = backend::snapshot(backend, mentioned.iter().copied()).await?;
+                        let diff = diff_maps(before, &after);
+                        let mut changed = changed_pubkeys(&diff);
+                        let mut expected: BTreeSet<Pubkey> = accounts.iter().copied().collect();
+                        // Fees make the payer change on a bank but not on
+                        // the local harness
+                        changed.remove(&payer.pubkey());
+                        expected.remove(&payer.pubkey());
+                        (changed != expected).then(|| {
+                            format!(
+                                "expected changes to {}\nbut changed were {}\n{}",
+                                pubkey_list(&expected),
+                                pubkey_list(&changed),
+                                diff
+                            )
+                        })
+                    }
+                },
+                Step::AssertAccounts { accounts } => {
+                    let diff = backend::diff_since(backend, accounts).await?;
+                    (!diff.is_empty()).then(|| {
+                        format!(
+                            "accounts do not match (- missing, ~ expected -> actual)\n{}",
+                            diff
+                        )
+                    })
+                }
+            };
+
+            if let Some(failure) = failure {
+                let since = match last_checkpoint {
+                    Some(name) => {
+                        let after = backend::snapshot(backend, mentioned.iter().copied()).await?;
+                        Some((name, diff_maps(&run.checkpoints[name], &after)))
+                    }
+                    None => None,
+                };
+                return Err(AccountGenError::ConstraintViolation(report(
+                    &self.steps[..=index],
+                    &failure,
+                    since,
+                )));
+            }
+        }
+        Ok(run)
+    }
+}
+
+async fn send<B: TestBackend>(
+    backend: &mut B,
+    instructions: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> Result<(), AccountGenError> {
+    let mut keypairs = vec![payer];
+    keypairs.extend(signers.iter().copied().filter(|signer| {
+        signer.pubkey() != payer.pubkey()
+            && instructions
+                .iter()
+                .flat_map(|instruction| &instruction.accounts)
+                .any(|meta| meta.is_signer && meta.pubkey == signer.pubkey())
+    }));
+    let blockhash = backend.latest_blockhash().await?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &keypairs,
+        blockhash,
+    );
+    backend.process_transaction(transaction).await
+}
+
+fn changed_pubkeys(diff: &AccountMapDiff) -> BTreeSet<Pubkey> {
+    let added = diff.added.iter().map(|(pubkey, _)| *pubkey);
+    let removed = diff.removed.iter().map(|(pubkey, _)| *pubkey);
+    let changed = diff.changed.iter().map(|(pubkey, _)| *pubkey);
+    added.chain(removed).chain(changed).collect()
+}
+
+fn pubkey_list(pubkeys: &BTreeSet<Pubkey>) -> String {
+    if pubkeys.is_empty() {
+        return "no accounts".to_string();
+    }
+    pubkeys
+        .iter()
+        .map(Pubkey::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Describes a failed run: the steps up to the failing one, why it failed,
+/// and what changed since the last checkpoint.
+fn report(steps: &[Step], failure: &str, since: Option<(&str, AccountMapDiff)>) -> String {
+    let (failed, passed) = steps.split_last().expect("a step failed");
+    let mut report = format!("scenario failed at step {}: {}", steps.len(), failed);
+    for (index, step) in passed.iter().enumerate() {
+        let _ = write!(report, "\n  ok    {}. {}", index + 1, step);
+    }
+    let _ = write!(report, "\n  FAIL  {}. {}", steps.len(), failed);
+    for line in failure.lines() {
+        let _ = write!(report, "\n        {}", line);
+    }
+    if let Some((name, diff)) = since {
+        let _ = write!(report, "\nchanges since checkpoint {:?}:", name);
+        if diff.is_empty() {
+            report.push_str(" none");
+        }
+        for line in diff.to_string().lines() {
+            let _ = write!(report, "\n  {}", line);
+        }
+    }
+    report
+}
+
+mod pubkey_strings {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use solana_pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(pubkeys: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(pubkeys.iter().map(Pubkey::to_string))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Pubkey>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|pubkey| Pubkey::from_str(pubkey).map_err(D::Error::custom))
+            .collect()
+    }
+}
@@ -0,0 +1,149 @@
+//! Detecting changes to accounts a test did not expect to be written.
+//!
+//! A long scenario test runs many transactions, each of which should write
+//! only a few accounts. Asserting that the rest are untouched by keeping
+//! full copies and diffing them is slow and memory-hungry once the
+//! accounts hold megabytes of data. [`AccountMap::seal`] records a SHA-256
+//! hash per account instead, and [`SealedAccounts::verify`] or
+//! [`backend::verify_unchanged`](crate::backend::verify_unchanged) report
+//! every sealed account that changed or disappeared, except those the test
+//! expected to write.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::{AccountBuilder, AccountMap};
+//! use solana_pubkey::Pubkey;
+//!
+//! let (vault, config) = (Pubkey::new_unique(), Pubkey::new_unique());
+//! let mut accounts = AccountMap::new();
+//! accounts.add_with_builder(vault, AccountBuilder::new().balance(1_000)).unwrap();
+//! accounts.add_with_builder(config, AccountBuilder::new().data_raw(vec![1, 2])).unwrap();
+//! let sealed = accounts.seal();
+//!
+//! accounts.get_account_mut(&vault).unwrap().lamports = 500;
+//! assert!(sealed.verify(&accounts, &[vault]).is_ok());
+//!
+//! accounts.get_account_mut(&config).unwrap().data[0] = 9;
+//! let report = sealed.verify(&accounts, &[vault]);
+//! assert_eq!(report.changed, vec![config]);
+//! ```
+
+use crate::{AccountGenError, AccountMap};
+use sha2::{Digest, Sha256};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Hashes the lamports, owner, executable flag and data of an account.
+///
+/// The rent epoch is left out, since the runtime may rewrite it on any
+/// account a transaction loads.
+pub fn account_hash(account: &Account) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(account.lamports.to_le_bytes());
+    hasher.update(account.owner.as_ref());
+    hasher.update([u8::from(account.executable)]);
+    hasher.update((account.data.len() as u64).to_le_bytes());
+    hasher.update(&account.data);
+    hasher.finalize().into()
+}
+
+/// The hashes of a set of accounts, recorded by [`AccountMap::seal`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SealedAccounts {
+    hashes: BTreeMap<Pubkey, [u8; 32]>,
+}
+
+impl SealedAccounts {
+    /// Records a hash of every account in `accounts`.
+    pub fn collect(accounts: &AccountMap) -> Self {
+        Self {
+            hashes: accounts
+                .iter()
+                .map(|(pubkey, account)| (*pubkey, account_hash(account)))
+                .collect(),
+        }
+    }
+
+    /// Returns the number of sealed accounts.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Returns true if no accounts are sealed.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Returns the sealed addresses, in order.
+    pub fn pubkeys(&self) -> impl Iterator<Item = Pubkey> + '_ {
+        self.hashes.keys().copied()
+    }
+
+    /// Returns the recorded hash of an account, if it is sealed.
+    pub fn hash(&self, pubkey: &Pubkey) -> Option<&[u8; 32]> {
+        self.hashes.get(pubkey)
+    }
+
+    /// Checks the sealed accounts against `accounts`, skipping those in
+    /// `except`.
+    ///
+    /// Accounts in `accounts` that were not sealed are not checked.
+    pub fn verify(&self, accounts: &AccountMap, except: &[Pubkey]) -> SealReport {
+        let mut report = SealReport::default();
+        for (pubkey, hash) in &self.hashes {
+            if except.contains(pubkey) {
+                continue;
+            }
+            match accounts.get_account(pubkey) {
+                Some(account) if account_hash(account) == *hash => {}
+                Some(_) => report.changed.push(*pubkey),
+                None => report.removed.push(*pubkey),
+            }
+        }
+        report
+    }
+}
+
+/// The sealed accounts found changed by [`SealedAccounts::verify`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SealReport {
+    /// Accounts whose contents changed, in order.
+    pub changed: Vec<Pubkey>,
+    /// Accounts that no longer exist, in order.
+    pub removed: Vec<Pubkey>,
+}
+
+impl SealReport {
+    /// Returns true if every checked account is unchanged.
+    pub fn is_ok(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    /// Converts the report into an error if any account changed.
+    pub fn into_result(self) -> Result<(), AccountGenError> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(AccountGenError::ConstraintViolation(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for SealReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "every sealed account is unchanged");
+        }
+        write!(f, "sealed accounts were written:")?;
+        for pubkey in &self.changed {
+            write!(f, "\n  {}: changed", pubkey)?;
+        }
+        for pubkey in &self.removed {
+            write!(f, "\n  {}: removed", pubkey)?;
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
 //! Bincode serialization support.
 //!
 //! This module provides utilities for working with Bincode serialization,
-//! which is sometimes used for Solana account data.
+//! which is sometimes used for Solana account data. The [`legacy`]
+//! submodule encodes with serde-based bincode 1, the format of Solana's
+//! native account types.
+
+pub mod legacy;
 
 use crate::error::AccountGenError;
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,92 @@
+//! Serde-based bincode 1 serialization, as used by Solana's native programs.
+//!
+//! Nonce, stake, vote and sysvar accounts are encoded with bincode 1's
+//! default configuration: little-endian, fixed-width integers, and trailing
+//! bytes allowed so that fixed-size accounts can be padded with zeros.
+
+use crate::cluster::MAX_PERMITTED_DATA_LENGTH;
+use crate::error::AccountGenError;
+use ::bincode::Options;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use solana_account::Account;
+use std::io;
+
+fn options() -> impl Options {
+    ::bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .allow_trailing_bytes()
+        .with_limit(MAX_PERMITTED_DATA_LENGTH as u64)
+}
+
+/// Deserializes account data with bincode 1.
+///
+/// Trailing bytes are ignored, since native accounts are usually larger
+/// than the state they currently hold.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::extensions::sysvars::create_sysvar_account;
+/// use solana_accountgen::serialization::bincode::legacy::deserialize_account_data;
+/// use solana_clock::Clock;
+///
+/// let clock = Clock { slot: 42, ..Clock::default() };
+/// let account = create_sysvar_account(&clock);
+///
+/// let decoded: Clock = deserialize_account_data(&account).unwrap();
+/// assert_eq!(decoded.slot, 42);
+/// ```
+pub fn deserialize_account_data<T: DeserializeOwned>(
+    account: &Account,
+) -> Result<T, AccountGenError> {
+    options().deserialize(&account.data).map_err(|e| {
+        AccountGenError::DeserializationError(io::Error::new(io::ErrorKind::InvalidData, e))
+    })
+}
+
+/// Serializes data with bincode 1.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::serialization::bincode::legacy::serialize_data;
+///
+/// let data = serialize_data(&(1u32, 2u64)).unwrap();
+/// assert_eq!(data, [1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0]);
+/// ```
+pub fn serialize_data<T: Serialize>(data: &T) -> Result<Vec<u8>, AccountGenError> {
+    options().serialize(data).map_err(|e| {
+        AccountGenError::SerializationError(io::Error::new(io::ErrorKind::InvalidData, e))
+    })
+}
+
+/// Serializes data with bincode 1 and zero-pads it to `len` bytes.
+///
+/// Native programs allocate fixed-size accounts, such as the 200 bytes of a
+/// stake account, and leave the unused tail zeroed.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::serialization::bincode::legacy::serialize_data_padded;
+///
+/// let data = serialize_data_padded(&7u32, 8).unwrap();
+/// assert_eq!(data, [7, 0, 0, 0, 0, 0, 0, 0]);
+/// assert!(serialize_data_padded(&7u64, 4).is_err());
+/// ```
+pub fn serialize_data_padded<T: Serialize>(
+    data: &T,
+    len: usize,
+) -> Result<Vec<u8>, AccountGenError> {
+    let mut bytes = serialize_data(data)?;
+    if bytes.len() > len {
+        return Err(AccountGenError::DataTooLarge {
+            len: bytes.len(),
+            max: len,
+        });
+    }
+    bytes.resize(len, 0);
+    Ok(bytes)
+}
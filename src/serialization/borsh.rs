@@ -61,3 +61,120 @@ pub fn deserialize_account_data<T: BorshDeserialize>(
 pub fn serialize_data<T: BorshSerialize>(data: &T) -> Result<Vec<u8>, AccountGenError> {
     borsh::to_vec(data).map_err(|e| AccountGenError::SerializationError(e))
 }
+
+/// Serializes data using Borsh into `buffer`, replacing its contents.
+///
+/// The buffer keeps its capacity, so serializing many values through one
+/// buffer only allocates when a value is larger than every one before it.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::serialization::borsh::serialize_data_into;
+///
+/// let mut buffer = Vec::with_capacity(64);
+/// for value in 0u64..3 {
+///     serialize_data_into(&mut buffer, &value).unwrap();
+///     assert_eq!(buffer, value.to_le_bytes());
+/// }
+/// assert!(buffer.capacity() >= 64);
+/// ```
+pub fn serialize_data_into<T: BorshSerialize>(
+    buffer: &mut Vec<u8>,
+    data: &T,
+) -> Result<(), AccountGenError> {
+    buffer.clear();
+    data.serialize(buffer)
+        .map_err(AccountGenError::SerializationError)
+}
+
+/// Borsh data for an enum variant, built without the enum's Rust type.
+///
+/// Borsh writes an enum as a one-byte variant index followed by the
+/// variant's fields in order. When a program's instruction or state enum
+/// is not available as a dependency, `BorshEnum` produces the same bytes
+/// from the index and field values. It implements `BorshSerialize`, so it
+/// can be nested in another `BorshEnum`, passed to
+/// [`AccountBuilder::data`](crate::AccountBuilder::data) or to
+/// [`create_anchor_account`](crate::extensions::anchor::create_anchor_account).
+///
+/// # Example
+///
+/// ```
+/// use borsh::BorshSerialize;
+/// use solana_accountgen::serialization::borsh::BorshEnum;
+/// use solana_pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize)]
+/// enum Instruction {
+///     Initialize,
+///     Deposit { amount: u64 },
+///     SetAuthority { authority: Option<Pubkey>, expires: Option<i64> },
+/// }
+///
+/// let authority = Pubkey::new_unique();
+/// let data = BorshEnum::variant(2).some(authority).none().into_bytes();
+/// let expected = Instruction::SetAuthority { authority: Some(authority), expires: None };
+/// assert_eq!(data, borsh::to_vec(&expected).unwrap());
+///
+/// let deposit = BorshEnum::variant(1).field(500u64);
+/// assert_eq!(deposit.as_bytes(), borsh::to_vec(&Instruction::Deposit { amount: 500 }).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BorshEnum {
+    data: Vec<u8>,
+}
+
+impl BorshEnum {
+    /// Starts the variant with index `index`, the position of the variant
+    /// in the enum's declaration unless the enum sets its own indices.
+    pub fn variant(index: u8) -> Self {
+        Self { data: vec![index] }
+    }
+
+    /// Appends a field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value`'s `BorshSerialize` implementation fails, which the
+    /// implementations of the borsh crate never do when writing to memory.
+    pub fn field<T: BorshSerialize>(mut self, value: T) -> Self {
+        value
+            .serialize(&mut self.data)
+            .expect("borsh serialization into memory failed");
+        self
+    }
+
+    /// Appends an `Option` field holding `value`.
+    pub fn some<T: BorshSerialize>(self, value: T) -> Self {
+        self.field(Some(value))
+    }
+
+    /// Appends an `Option` field holding `None`.
+    pub fn none(mut self) -> Self {
+        self.data.push(0);
+        self
+    }
+
+    /// Appends bytes that are already serialized.
+    pub fn raw(mut self, bytes: &[u8]) -> Self {
+        self.data.extend_from_slice(bytes);
+        self
+    }
+
+    /// Returns the serialized variant.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the serialized variant.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl BorshSerialize for BorshEnum {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.data)
+    }
+}
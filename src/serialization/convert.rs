@@ -0,0 +1,188 @@
+//! Converting accounts between the JSON forms used across the ecosystem.
+//!
+//! The same account shows up as a JSON RPC `getAccountInfo` value, as a
+//! line of a [fixture file](crate::fixture), as a `solana account --output
+//! json` file that `solana-test-validator` loads, and as a raw data file.
+//! The functions here read and write each of them, so a tool that takes
+//! accounts in one form can hand them on in another.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::AccountBuilder;
+//! use solana_accountgen::serialization::DataEncoding;
+//! use solana_accountgen::serialization::convert::{
+//!     from_rpc_json, from_validator_json, to_rpc_json, to_validator_json,
+//! };
+//! use solana_pubkey::Pubkey;
+//!
+//! let pubkey = Pubkey::new_unique();
+//! let account = AccountBuilder::new().balance(1_000).data_raw(vec![1, 2, 3]).build();
+//!
+//! // An RPC response pasted into a test becomes a validator account file
+//! let rpc = to_rpc_json(&account, DataEncoding::Base58);
+//! assert_eq!(rpc["data"][1], "base58");
+//! let validator = to_validator_json(&pubkey, &from_rpc_json(&rpc).unwrap());
+//!
+//! assert_eq!(from_validator_json(&validator).unwrap(), (pubkey, account));
+//! ```
+
+use crate::fixture::FixtureAccount;
+use crate::fixture::record::{encoded_data, pubkey_string};
+use crate::serialization::DataEncoding;
+use crate::{AccountGenError, cluster};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::path::Path;
+
+/// An account as the JSON RPC returns it. `space` is optional when reading,
+/// since older nodes leave it out.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RpcAccount {
+    pub lamports: u64,
+    #[serde(with = "pubkey_string")]
+    pub owner: Pubkey,
+    #[serde(with = "encoded_data")]
+    pub data: Vec<u8>,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub space: Option<u64>,
+}
+
+impl RpcAccount {
+    pub fn new(account: &Account) -> Self {
+        Self {
+            lamports: account.lamports,
+            owner: account.owner,
+            data: account.data.clone(),
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            space: Some(account.data.len() as u64),
+        }
+    }
+}
+
+impl From<RpcAccount> for Account {
+    fn from(account: RpcAccount) -> Self {
+        Account {
+            lamports: account.lamports,
+            data: account.data,
+            owner: account.owner,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        }
+    }
+}
+
+/// An account with its address, as returned by `getProgramAccounts` and
+/// written by `solana account --output json`.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct KeyedRpcAccount {
+    #[serde(with = "pubkey_string")]
+    pub pubkey: Pubkey,
+    pub account: RpcAccount,
+}
+
+/// Writes an account as the `value` of a `getAccountInfo` response, with
+/// its data in `encoding`.
+pub fn to_rpc_json(account: &Account, encoding: DataEncoding) -> Value {
+    json!({
+        "lamports": account.lamports,
+        "owner": account.owner.to_string(),
+        "data": [encoding.encode(&account.data), encoding.as_str()],
+        "executable": account.executable,
+        "rentEpoch": account.rent_epoch,
+        "space": account.data.len(),
+    })
+}
+
+/// Reads the `value` of a `getAccountInfo` response.
+///
+/// The data may be in any encoding a [fixture file](crate::fixture)
+/// accepts.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] if `value` is not an
+/// account.
+pub fn from_rpc_json(value: &Value) -> Result<Account, AccountGenError> {
+    RpcAccount::deserialize(value)
+        .map(Account::from)
+        .map_err(|e| AccountGenError::InvalidDataFormat(format!("invalid RPC account: {e}")))
+}
+
+/// Writes an account in the format of `solana account --output json`,
+/// which `solana-test-validator --account` and `--account-dir` load.
+pub fn to_validator_json(pubkey: &Pubkey, account: &Account) -> Value {
+    json!({
+        "pubkey": pubkey.to_string(),
+        "account": to_rpc_json(account, DataEncoding::Base64),
+    })
+}
+
+/// Reads an account in the format of `solana account --output json`.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] if `value` is not a
+/// keyed account.
+pub fn from_validator_json(value: &Value) -> Result<(Pubkey, Account), AccountGenError> {
+    KeyedRpcAccount::deserialize(value)
+        .map(|keyed| (keyed.pubkey, keyed.account.into()))
+        .map_err(|e| AccountGenError::InvalidDataFormat(format!("invalid validator account: {e}")))
+}
+
+/// Writes an account as one line of a fixture file.
+pub fn to_fixture_json(pubkey: &Pubkey, account: &Account) -> String {
+    serde_json::to_string(&FixtureAccount::new(*pubkey, account.clone()))
+        .expect("fixture accounts always serialize")
+}
+
+/// Reads one account line of a fixture file.
+///
+/// # Errors
+///
+/// Returns [`AccountGenError::InvalidDataFormat`] if `line` is not an
+/// account record.
+pub fn from_fixture_json(line: &str) -> Result<(Pubkey, Account), AccountGenError> {
+    serde_json::from_str::<FixtureAccount>(line)
+        .map(FixtureAccount::into_parts)
+        .map_err(|e| AccountGenError::InvalidDataFormat(format!("invalid fixture account: {e}")))
+}
+
+/// Re-encodes account data, such as the base58 data of a small RPC
+/// account as base64.
+///
+/// # Errors
+///
+/// Returns an error if `text` is not valid in the `from` encoding.
+pub fn reencode(
+    text: &str,
+    from: DataEncoding,
+    to: DataEncoding,
+) -> Result<String, AccountGenError> {
+    Ok(to.encode(&from.decode(text)?))
+}
+
+/// Reads account data from a raw file, such as one written by
+/// `solana account --output-file`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is larger than an
+/// account can be.
+pub fn read_raw_data<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, AccountGenError> {
+    let data = std::fs::read(path)?;
+    cluster::check_data_len(data.len())?;
+    Ok(data)
+}
+
+/// Writes account data to a raw file.
+pub fn write_raw_data<P: AsRef<Path>>(path: P, data: &[u8]) -> Result<(), AccountGenError> {
+    std::fs::write(path, data)?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Text encodings for raw account data.
+
+use crate::error::AccountGenError;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// A text encoding for account data.
+///
+/// The names match the `encoding` strings used by Solana's JSON RPC, so
+/// data copied from an RPC response can be decoded with the encoding it was
+/// labeled with.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::serialization::DataEncoding;
+///
+/// let encoding: DataEncoding = "base58".parse().unwrap();
+/// assert_eq!(encoding.encode(&[1, 2, 3]), "Ldp");
+/// assert_eq!(encoding.decode("Ldp").unwrap(), vec![1, 2, 3]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataEncoding {
+    /// Bitcoin-alphabet base58, as used for pubkeys and by the RPC for
+    /// small accounts.
+    Base58,
+    /// Standard base64 with padding.
+    Base64,
+    /// Lowercase hexadecimal; decoding also accepts uppercase.
+    Hex,
+}
+
+impl DataEncoding {
+    /// Returns the name of the encoding.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataEncoding::Base58 => "base58",
+            DataEncoding::Base64 => "base64",
+            DataEncoding::Hex => "hex",
+        }
+    }
+
+    /// Encodes `data` as text.
+    pub fn encode(&self, data: &[u8]) -> String {
+        match self {
+            DataEncoding::Base58 => bs58::encode(data).into_string(),
+            DataEncoding::Base64 => base64::encode(data),
+            DataEncoding::Hex => hex::encode(data),
+        }
+    }
+
+    /// Decodes text into bytes.
+    pub fn decode(&self, text: &str) -> Result<Vec<u8>, AccountGenError> {
+        let result = match self {
+            DataEncoding::Base58 => bs58::decode(text).into_vec().map_err(|e| e.to_string()),
+            DataEncoding::Base64 => base64::decode(text).map_err(|e| e.to_string()),
+            DataEncoding::Hex => hex::decode(text).map_err(|e| e.to_string()),
+        };
+        result.map_err(|e| {
+            AccountGenError::SerializationError(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid {} data: {}", self.as_str(), e),
+            ))
+        })
+    }
+}
+
+impl fmt::Display for DataEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DataEncoding {
+    type Err = AccountGenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base58" => Ok(DataEncoding::Base58),
+            "base64" => Ok(DataEncoding::Base64),
+            "hex" => Ok(DataEncoding::Hex),
+            other => Err(AccountGenError::InvalidDataFormat(format!(
+                "unsupported data encoding: {other}"
+            ))),
+        }
+    }
+}
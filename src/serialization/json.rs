@@ -0,0 +1,59 @@
+//! JSON serialization support.
+//!
+//! Some programs store JSON in their accounts, and JSON is convenient for
+//! fixture data that people edit by hand. To turn hand-written JSON into the
+//! Borsh layout a program expects, see
+//! [`AccountBuilder::data_json_value`](crate::AccountBuilder::data_json_value).
+
+use crate::error::AccountGenError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use solana_account::Account;
+use std::io;
+
+/// Deserializes JSON account data.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::AccountBuilder;
+/// use solana_accountgen::serialization::json::deserialize_account_data_json;
+/// use serde_json::{Value, json};
+///
+/// let account = AccountBuilder::new()
+///     .data_json_value(json!({"name": "alice"}), None)
+///     .unwrap()
+///     .build();
+///
+/// let value: Value = deserialize_account_data_json(&account).unwrap();
+/// assert_eq!(value["name"], "alice");
+/// ```
+pub fn deserialize_account_data_json<T: DeserializeOwned>(
+    account: &Account,
+) -> Result<T, AccountGenError> {
+    serde_json::from_slice(&account.data).map_err(|e| {
+        AccountGenError::DeserializationError(io::Error::new(io::ErrorKind::InvalidData, e))
+    })
+}
+
+/// Serializes data as compact JSON.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::serialization::json::serialize_account_data_json;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Profile {
+///     name: String,
+/// }
+///
+/// let data = serialize_account_data_json(&Profile { name: "alice".into() }).unwrap();
+/// assert_eq!(data, br#"{"name":"alice"}"#);
+/// ```
+pub fn serialize_account_data_json<T: Serialize>(data: &T) -> Result<Vec<u8>, AccountGenError> {
+    serde_json::to_vec(data).map_err(|e| {
+        AccountGenError::SerializationError(io::Error::new(io::ErrorKind::InvalidData, e))
+    })
+}
@@ -1,7 +1,15 @@
 //! Serialization support for different formats.
 //!
 //! This module provides utilities for serializing and deserializing
-//! account data in different formats.
+//! account data in different formats, and [`convert`] moves whole accounts
+//! between the JSON forms other tools read and write.
 
 pub mod borsh;
-pub mod bincode; 
\ No newline at end of file
+pub mod bincode;
+pub mod convert;
+mod encoding;
+pub mod json;
+pub mod raw;
+pub mod schema;
+
+pub use encoding::DataEncoding;
@@ -0,0 +1,159 @@
+//! Bounds-checked reads and writes of fields in raw account data.
+//!
+//! Tests that tweak a cloned account often know only where a field sits,
+//! not the type that lays it out. Slicing the data by hand
+//! (`data[40..48].copy_from_slice(..)`) panics on a wrong offset and is
+//! easy to get wrong by a byte. [`RawData`] reads and writes integers,
+//! pubkeys and byte runs at an offset instead, returning an error when the
+//! field does not fit in the data. It is implemented for `[u8]`, so it
+//! works on `account.data` directly.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::AccountBuilder;
+//! use solana_accountgen::serialization::raw::RawData;
+//! use solana_pubkey::Pubkey;
+//!
+//! let mut account = AccountBuilder::new().data_raw(vec![0; 48]).build();
+//! let authority = Pubkey::new_unique();
+//!
+//! account.data.write_u64_le(0, 42).unwrap();
+//! account.data.write_pubkey(8, &authority).unwrap();
+//! account.data.write_u64_be(40, 7).unwrap();
+//!
+//! assert_eq!(account.data.read_u64_le(0).unwrap(), 42);
+//! assert_eq!(account.data.read_pubkey(8).unwrap(), authority);
+//! assert_eq!(account.data[47], 7);
+//!
+//! // A field past the end is an error, not a panic
+//! assert!(account.data.read_u64_le(41).is_err());
+//! ```
+
+use crate::error::AccountGenError;
+use solana_pubkey::Pubkey;
+use std::ops::Range;
+
+/// Returns the range of `len` bytes at `offset`, if it lies within
+/// `data_len` bytes.
+fn field_range(
+    offset: usize,
+    len: usize,
+    data_len: usize,
+) -> Result<Range<usize>, AccountGenError> {
+    offset
+        .checked_add(len)
+        .filter(|end| *end <= data_len)
+        .map(|end| offset..end)
+        .ok_or_else(|| {
+            AccountGenError::InvalidDataFormat(format!(
+                "{len} bytes at offset {offset} do not fit in {data_len} bytes of data"
+            ))
+        })
+}
+
+macro_rules! int_accessors {
+    ($($ty:ty => $read_le:ident, $read_be:ident, $write_le:ident, $write_be:ident;)*) => {
+        $(
+            #[doc = concat!("Reads a little-endian `", stringify!($ty), "` at `offset`.")]
+            fn $read_le(&self, offset: usize) -> Result<$ty, AccountGenError> {
+                Ok(<$ty>::from_le_bytes(self.read_array(offset)?))
+            }
+
+            #[doc = concat!("Reads a big-endian `", stringify!($ty), "` at `offset`.")]
+            fn $read_be(&self, offset: usize) -> Result<$ty, AccountGenError> {
+                Ok(<$ty>::from_be_bytes(self.read_array(offset)?))
+            }
+
+            #[doc = concat!("Writes a little-endian `", stringify!($ty), "` at `offset`.")]
+            fn $write_le(&mut self, offset: usize, value: $ty) -> Result<(), AccountGenError> {
+                self.write_bytes(offset, &value.to_le_bytes())
+            }
+
+            #[doc = concat!("Writes a big-endian `", stringify!($ty), "` at `offset`.")]
+            fn $write_be(&mut self, offset: usize, value: $ty) -> Result<(), AccountGenError> {
+                self.write_bytes(offset, &value.to_be_bytes())
+            }
+        )*
+    };
+}
+
+/// Reads and writes fields of raw data at byte offsets.
+///
+/// Every method returns [`AccountGenError::InvalidDataFormat`] if the
+/// field does not fit in the data; writes never resize it.
+pub trait RawData {
+    /// Returns the `len` bytes at `offset`.
+    fn read_bytes(&self, offset: usize, len: usize) -> Result<&[u8], AccountGenError>;
+
+    /// Overwrites `bytes.len()` bytes at `offset`.
+    fn write_bytes(&mut self, offset: usize, bytes: &[u8]) -> Result<(), AccountGenError>;
+
+    /// Returns the `N` bytes at `offset` as an array.
+    fn read_array<const N: usize>(&self, offset: usize) -> Result<[u8; N], AccountGenError> {
+        Ok(self
+            .read_bytes(offset, N)?
+            .try_into()
+            .expect("read_bytes returns the requested length"))
+    }
+
+    /// Reads a pubkey at `offset`.
+    fn read_pubkey(&self, offset: usize) -> Result<Pubkey, AccountGenError> {
+        self.read_array(offset).map(Pubkey::new_from_array)
+    }
+
+    /// Writes a pubkey at `offset`.
+    fn write_pubkey(&mut self, offset: usize, pubkey: &Pubkey) -> Result<(), AccountGenError> {
+        self.write_bytes(offset, pubkey.as_ref())
+    }
+
+    /// Reads a byte at `offset` as a bool. Bytes other than 0 and 1 are an
+    /// error, as they are for Borsh.
+    fn read_bool(&self, offset: usize) -> Result<bool, AccountGenError> {
+        match self.read_u8(offset)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(AccountGenError::InvalidDataFormat(format!(
+                "byte {byte} at offset {offset} is not a bool"
+            ))),
+        }
+    }
+
+    /// Writes a bool as one byte at `offset`.
+    fn write_bool(&mut self, offset: usize, value: bool) -> Result<(), AccountGenError> {
+        self.write_u8(offset, value.into())
+    }
+
+    /// Reads a byte at `offset`.
+    fn read_u8(&self, offset: usize) -> Result<u8, AccountGenError> {
+        Ok(self.read_bytes(offset, 1)?[0])
+    }
+
+    /// Writes a byte at `offset`.
+    fn write_u8(&mut self, offset: usize, value: u8) -> Result<(), AccountGenError> {
+        self.write_bytes(offset, &[value])
+    }
+
+    int_accessors! {
+        u16 => read_u16_le, read_u16_be, write_u16_le, write_u16_be;
+        u32 => read_u32_le, read_u32_be, write_u32_le, write_u32_be;
+        u64 => read_u64_le, read_u64_be, write_u64_le, write_u64_be;
+        u128 => read_u128_le, read_u128_be, write_u128_le, write_u128_be;
+        i16 => read_i16_le, read_i16_be, write_i16_le, write_i16_be;
+        i32 => read_i32_le, read_i32_be, write_i32_le, write_i32_be;
+        i64 => read_i64_le, read_i64_be, write_i64_le, write_i64_be;
+        i128 => read_i128_le, read_i128_be, write_i128_le, write_i128_be;
+    }
+}
+
+impl RawData for [u8] {
+    fn read_bytes(&self, offset: usize, len: usize) -> Result<&[u8], AccountGenError> {
+        Ok(&self[field_range(offset, len, self.len())?])
+    }
+
+    fn write_bytes(&mut self, offset: usize, bytes: &[u8]) -> Result<(), AccountGenError> {
+        let range = field_range(offset, bytes.len(), self.len())?;
+        self[range].copy_from_slice(bytes);
+        Ok(())
+    }
+}
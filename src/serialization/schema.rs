@@ -0,0 +1,861 @@
+//! Borsh schemas as JSON, for decoding fixture data outside of Rust.
+//!
+//! A [`FixtureSchema`] describes the Borsh layout of one or more account
+//! types in a JSON document that other tools can read. With the `schema`
+//! feature, schemas are generated from types deriving `BorshSchema`;
+//! without it, an existing schema document can still be loaded and used to
+//! decode account data into JSON, which is what the CLI's
+//! `inspect --schema` does.
+//!
+//! ```toml
+//! solana-accountgen = { version = "0.1", features = ["schema"] }
+//! ```
+//!
+//! # Format
+//!
+//! ```text
+//! {
+//!   "version": 1,
+//!   "types": ["Vault"],
+//!   "definitions": {
+//!     "Vault": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]},
+//!     "u64": {"kind": "primitive", "size": 8}
+//!   }
+//! }
+//! ```
+//!
+//! Definitions mirror `borsh::schema::Definition`: `primitive`,
+//! `sequence`, `tuple`, `enum`, `struct` and `tupleStruct`.
+
+use crate::error::AccountGenError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use solana_pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// The current version of the schema document format.
+pub const SCHEMA_FORMAT_VERSION: u32 = 1;
+
+/// Nesting depth at which decoding gives up, matching Borsh's own limit.
+const MAX_DEPTH: usize = 100;
+
+/// A set of Borsh type definitions with the names of its root types.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::serialization::schema::FixtureSchema;
+/// use serde_json::json;
+///
+/// let schema: FixtureSchema = serde_json::from_value(json!({
+///     "version": 1,
+///     "types": ["Counter"],
+///     "definitions": {
+///         "Counter": {"kind": "struct", "fields": [{"name": "count", "type": "u64"}]},
+///         "u64": {"kind": "primitive", "size": 8}
+///     }
+/// }))
+/// .unwrap();
+///
+/// let decoded = schema.decode("Counter", &7u64.to_le_bytes()).unwrap();
+/// assert_eq!(decoded, json!({"count": 7}));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureSchema {
+    /// The format version, [`SCHEMA_FORMAT_VERSION`] when written.
+    pub version: u32,
+    /// The registered root types.
+    pub types: Vec<String>,
+    /// Definitions of the root types and every type they reference.
+    pub definitions: BTreeMap<String, TypeDefinition>,
+}
+
+/// The layout of one type, mirroring `borsh::schema::Definition`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TypeDefinition {
+    /// A fixed-size value such as `u64` or `bool`.
+    Primitive {
+        /// Size in bytes.
+        size: u8,
+    },
+    /// A homogeneous sequence, optionally prefixed by its length.
+    #[serde(rename_all = "camelCase")]
+    Sequence {
+        /// Bytes in the little-endian length prefix, or 0 for fixed arrays.
+        length_width: u8,
+        /// Inclusive bounds on the number of elements.
+        length_range: (u64, u64),
+        /// The element type.
+        elements: String,
+    },
+    /// A fixed-size heterogeneous tuple.
+    Tuple {
+        /// The element types.
+        elements: Vec<String>,
+    },
+    /// A tagged union.
+    #[serde(rename_all = "camelCase")]
+    Enum {
+        /// Bytes in the little-endian discriminant.
+        tag_width: u8,
+        /// The possible variants.
+        variants: Vec<EnumVariant>,
+    },
+    /// A struct with named fields.
+    Struct {
+        /// The fields in declaration order.
+        fields: Vec<StructField>,
+    },
+    /// A struct with unnamed fields.
+    TupleStruct {
+        /// The field types in declaration order.
+        elements: Vec<String>,
+    },
+}
+
+/// One variant of a [`TypeDefinition::Enum`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumVariant {
+    /// The discriminant written before the variant's payload.
+    pub discriminant: i64,
+    /// The variant name.
+    pub name: String,
+    /// The payload type.
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// One field of a [`TypeDefinition::Struct`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructField {
+    /// The field name.
+    pub name: String,
+    /// The field type.
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+impl Default for FixtureSchema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FixtureSchema {
+    /// Creates an empty schema.
+    pub fn new() -> Self {
+        Self {
+            version: SCHEMA_FORMAT_VERSION,
+            types: Vec::new(),
+            definitions: BTreeMap::new(),
+        }
+    }
+
+    /// Adds `T` and every type it references to the schema.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use borsh::BorshSchema;
+    /// use solana_accountgen::serialization::schema::FixtureSchema;
+    ///
+    /// #[derive(BorshSchema)]
+    /// struct Vault {
+    ///     owner: [u8; 32],
+    ///     amount: u64,
+    /// }
+    ///
+    /// let mut schema = FixtureSchema::new();
+    /// schema.register::<Vault>();
+    ///
+    /// assert_eq!(schema.types, vec!["Vault".to_string()]);
+    /// assert!(schema.definitions.contains_key("[u8; 32]"));
+    /// ```
+    #[cfg(feature = "schema")]
+    pub fn register<T: borsh::BorshSchema + ?Sized>(&mut self) -> &mut Self {
+        let container = borsh::schema::BorshSchemaContainer::for_type::<T>();
+        let declaration = container.declaration().clone();
+        for (name, definition) in container.definitions() {
+            self.definitions
+                .insert(name.clone(), TypeDefinition::from(definition));
+        }
+        if !self.types.contains(&declaration) {
+            self.types.push(declaration);
+        }
+        self
+    }
+
+    /// Decodes Borsh-encoded `data` of type `type_name` into JSON.
+    ///
+    /// Structs become objects, tuples and sequences become arrays, and enum
+    /// values become `{"Variant": payload}`. `String` decodes to a JSON
+    /// string, 128-bit integers to decimal strings so they survive JSON
+    /// parsers that use doubles, a 32-byte `Pubkey` primitive to base58, and
+    /// primitives the decoder does not know to hex strings. All of `data` must be consumed.
+    pub fn decode(&self, type_name: &str, data: &[u8]) -> Result<Value, AccountGenError> {
+        let mut cursor = data;
+        let value = self.decode_value(type_name, &mut cursor, 0)?;
+        if !cursor.is_empty() {
+            return Err(invalid(format!(
+                "{} trailing bytes after decoding {}",
+                cursor.len(),
+                type_name
+            )));
+        }
+        Ok(value)
+    }
+
+    fn decode_value(
+        &self,
+        type_name: &str,
+        data: &mut &[u8],
+        depth: usize,
+    ) -> Result<Value, AccountGenError> {
+        if depth > MAX_DEPTH {
+            return Err(invalid(format!("type {type_name} nests too deeply")));
+        }
+        let definition = self
+            .definitions
+            .get(type_name)
+            .ok_or_else(|| invalid(format!("no definition for type {type_name}")))?;
+
+        match definition {
+            TypeDefinition::Primitive { size } => {
+                decode_primitive(type_name, take(data, *size as usize)?)
+            }
+            TypeDefinition::Sequence {
+                length_width,
+                length_range: (min, max),
+                elements,
+            } => {
+                let len = if *length_width == 0 {
+                    if min != max {
+                        return Err(invalid(format!("{type_name} has no fixed length")));
+                    }
+                    *min
+                } else {
+                    read_uint(take(data, *length_width as usize)?)
+                        .ok_or_else(|| invalid(format!("{type_name} length does not fit")))?
+                };
+                if len < *min || len > *max {
+                    return Err(invalid(format!("{type_name} length {len} out of range")));
+                }
+                if type_name == "String" && elements == "u8" {
+                    let bytes = take(data, len as usize)?;
+                    let text = std::str::from_utf8(bytes)
+                        .map_err(|e| invalid(format!("String is not UTF-8: {e}")))?;
+                    return Ok(Value::String(text.to_string()));
+                }
+                // Like Borsh, a length prefix must not describe zero-sized
+                // elements, so each one consumes input and a forged length
+                // runs out of data instead of looping
+                let mut values = Vec::new();
+                for _ in 0..len {
+                    let remaining = data.len();
+                    values.push(self.decode_value(elements, data, depth + 1)?);
+                    if *length_width != 0 && data.len() == remaining {
+                        return Err(invalid(format!("{type_name} has zero-sized elements")));
+                    }
+                }
+                Ok(Value::Array(values))
+            }
+            TypeDefinition::Tuple { elements } | TypeDefinition::TupleStruct { elements } => {
+                if elements.is_empty() {
+                    return Ok(Value::Null);
+                }
+                let values = elements
+                    .iter()
+                    .map(|element| self.decode_value(element, data, depth + 1))
+                    .collect::<Result<_, _>>()?;
+                Ok(Value::Array(values))
+            }
+            TypeDefinition::Enum {
+                tag_width,
+                variants,
+            } => {
+                let tag = read_uint(take(data, *tag_width as usize)?)
+                    .ok_or_else(|| invalid(format!("{type_name} tag does not fit")))?;
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.discriminant as i128 == tag as i128)
+                    .ok_or_else(|| invalid(format!("{type_name} has no variant {tag}")))?;
+                let payload = self.decode_value(&variant.ty, data, depth + 1)?;
+                Ok(json!({ variant.name.clone(): payload }))
+            }
+            TypeDefinition::Struct { fields } => {
+                let mut object = Map::new();
+                for field in fields {
+                    let value = self.decode_value(&field.ty, data, depth + 1)?;
+                    object.insert(field.name.clone(), value);
+                }
+                Ok(Value::Object(object))
+            }
+        }
+    }
+
+    /// Encodes a JSON `value` of type `type_name` with Borsh.
+    ///
+    /// This is the inverse of [`FixtureSchema::decode`] and accepts the same
+    /// shapes it produces. In addition, 128-bit integers may be given as JSON
+    /// numbers, and an enum variant without a payload may be given as its
+    /// name alone.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::serialization::schema::FixtureSchema;
+    /// use serde_json::json;
+    ///
+    /// let schema: FixtureSchema = serde_json::from_value(json!({
+    ///     "version": 1,
+    ///     "types": ["Counter"],
+    ///     "definitions": {
+    ///         "Counter": {"kind": "struct", "fields": [{"name": "count", "type": "u64"}]},
+    ///         "u64": {"kind": "primitive", "size": 8}
+    ///     }
+    /// }))
+    /// .unwrap();
+    ///
+    /// let data = schema.encode("Counter", &json!({"count": 7})).unwrap();
+    /// assert_eq!(data, 7u64.to_le_bytes());
+    /// ```
+    pub fn encode(&self, type_name: &str, value: &Value) -> Result<Vec<u8>, AccountGenError> {
+        let mut data = Vec::new();
+        self.encode_value(type_name, value, &mut data, 0)?;
+        Ok(data)
+    }
+
+    /// Locates a field inside encoded data.
+    ///
+    /// `path` is a dot-separated list of struct field names, tuple and
+    /// sequence indices, and enum variant names, such as `player.score`,
+    /// `entries.3` or `state.Active.since`; an empty path is the whole
+    /// value. Returns the byte range the field occupies in `data` and the
+    /// field's type name. Data after the root value is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::serialization::schema::FixtureSchema;
+    /// use serde_json::json;
+    ///
+    /// let schema: FixtureSchema = serde_json::from_value(json!({
+    ///     "version": 1,
+    ///     "types": ["Player"],
+    ///     "definitions": {
+    ///         "Player": {"kind": "struct", "fields": [
+    ///             {"name": "level", "type": "u8"},
+    ///             {"name": "score", "type": "u64"}
+    ///         ]},
+    ///         "u8": {"kind": "primitive", "size": 1},
+    ///         "u64": {"kind": "primitive", "size": 8}
+    ///     }
+    /// }))
+    /// .unwrap();
+    ///
+    /// let data = schema.encode("Player", &json!({"level": 3, "score": 900})).unwrap();
+    /// let (range, ty) = schema.field_range("Player", &data, "score").unwrap();
+    /// assert_eq!((range, ty.as_str()), (1..9, "u64"));
+    /// ```
+    pub fn field_range(
+        &self,
+        type_name: &str,
+        data: &[u8],
+        path: &str,
+    ) -> Result<(Range<usize>, String), AccountGenError> {
+        let segments: Vec<&str> = if path.is_empty() {
+            Vec::new()
+        } else {
+            path.split('.').collect()
+        };
+        let mut cursor = data;
+        let mut type_name = type_name.to_string();
+
+        for segment in &segments {
+            let definition = self
+                .definitions
+                .get(&type_name)
+                .ok_or_else(|| invalid(format!("no definition for type {type_name}")))?;
+            let index = || {
+                segment
+                    .parse::<usize>()
+                    .map_err(|_| invalid(format!("{type_name} has no field {segment}")))
+            };
+
+            type_name = match definition {
+                TypeDefinition::Struct { fields } => {
+                    let position = fields
+                        .iter()
+                        .position(|field| field.name == *segment)
+                        .ok_or_else(|| invalid(format!("{type_name} has no field {segment}")))?;
+                    for field in &fields[..position] {
+                        self.decode_value(&field.ty, &mut cursor, 0)?;
+                    }
+                    fields[position].ty.clone()
+                }
+                TypeDefinition::Tuple { elements } | TypeDefinition::TupleStruct { elements } => {
+                    let position = index()?;
+                    if position >= elements.len() {
+                        return Err(invalid(format!("{type_name} has no field {segment}")));
+                    }
+                    for element in &elements[..position] {
+                        self.decode_value(element, &mut cursor, 0)?;
+                    }
+                    elements[position].clone()
+                }
+                TypeDefinition::Sequence {
+                    length_width,
+                    length_range: (min, _),
+                    elements,
+                } => {
+                    let position = index()?;
+                    let len = if *length_width == 0 {
+                        *min
+                    } else {
+                        read_uint(take(&mut cursor, *length_width as usize)?)
+                            .ok_or_else(|| invalid(format!("{type_name} length does not fit")))?
+                    };
+                    if position as u64 >= len {
+                        return Err(invalid(format!(
+                            "index {position} out of bounds for {type_name} of length {len}"
+                        )));
+                    }
+                    for _ in 0..position {
+                        self.decode_value(elements, &mut cursor, 0)?;
+                    }
+                    elements.clone()
+                }
+                TypeDefinition::Enum {
+                    tag_width,
+                    variants,
+                } => {
+                    let tag = read_uint(take(&mut cursor, *tag_width as usize)?)
+                        .ok_or_else(|| invalid(format!("{type_name} tag does not fit")))?;
+                    let variant = variants
+                        .iter()
+                        .find(|variant| variant.discriminant as i128 == tag as i128)
+                        .ok_or_else(|| invalid(format!("{type_name} has no variant {tag}")))?;
+                    if variant.name != *segment {
+                        return Err(invalid(format!(
+                            "{type_name} holds variant {}, not {segment}",
+                            variant.name
+                        )));
+                    }
+                    variant.ty.clone()
+                }
+                TypeDefinition::Primitive { .. } => {
+                    return Err(invalid(format!("{type_name} has no field {segment}")));
+                }
+            };
+        }
+
+        let start = data.len() - cursor.len();
+        self.decode_value(&type_name, &mut cursor, 0)?;
+        let end = data.len() - cursor.len();
+        Ok((start..end, type_name))
+    }
+
+    /// Returns every `Pubkey` inside encoded data with its byte offset.
+    ///
+    /// A value counts as a pubkey when its type is named `Pubkey`, which is
+    /// how both IDL schemas and `solana-pubkey`'s `BorshSchema` name it.
+    /// Data after the root value is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::serialization::schema::FixtureSchema;
+    /// use solana_pubkey::Pubkey;
+    /// use serde_json::json;
+    ///
+    /// let schema: FixtureSchema = serde_json::from_value(json!({
+    ///     "version": 1,
+    ///     "types": ["Vault"],
+    ///     "definitions": {
+    ///         "Vault": {"kind": "struct", "fields": [
+    ///             {"name": "bump", "type": "u8"},
+    ///             {"name": "authority", "type": "Pubkey"}
+    ///         ]},
+    ///         "u8": {"kind": "primitive", "size": 1},
+    ///         "Pubkey": {"kind": "primitive", "size": 32}
+    ///     }
+    /// }))
+    /// .unwrap();
+    ///
+    /// let authority = Pubkey::new_unique();
+    /// let mut data = vec![254];
+    /// data.extend_from_slice(authority.as_ref());
+    /// assert_eq!(schema.pubkeys("Vault", &data).unwrap(), vec![(1, authority)]);
+    /// ```
+    pub fn pubkeys(
+        &self,
+        type_name: &str,
+        data: &[u8],
+    ) -> Result<Vec<(usize, Pubkey)>, AccountGenError> {
+        let mut cursor = data;
+        let mut pubkeys = Vec::new();
+        self.collect_pubkeys(type_name, data.len(), &mut cursor, &mut pubkeys, 0)?;
+        Ok(pubkeys)
+    }
+
+    fn collect_pubkeys(
+        &self,
+        type_name: &str,
+        total: usize,
+        data: &mut &[u8],
+        pubkeys: &mut Vec<(usize, Pubkey)>,
+        depth: usize,
+    ) -> Result<(), AccountGenError> {
+        if depth > MAX_DEPTH {
+            return Err(invalid(format!("type {type_name} nests too deeply")));
+        }
+        if type_name == "Pubkey" {
+            let offset = total - data.len();
+            let bytes: [u8; 32] = take(data, 32)?.try_into().unwrap();
+            pubkeys.push((offset, Pubkey::new_from_array(bytes)));
+            return Ok(());
+        }
+        let definition = self
+            .definitions
+            .get(type_name)
+            .ok_or_else(|| invalid(format!("no definition for type {type_name}")))?;
+
+        match definition {
+            TypeDefinition::Sequence {
+                length_width,
+                length_range: (min, _),
+                elements,
+            } if self.contains_pubkey(elements, depth + 1) => {
+                let len = if *length_width == 0 {
+                    *min
+                } else {
+                    read_uint(take(data, *length_width as usize)?)
+                        .ok_or_else(|| invalid(format!("{type_name} length does not fit")))?
+                };
+                for _ in 0..len {
+                    let remaining = data.len();
+                    self.collect_pubkeys(elements, total, data, pubkeys, depth + 1)?;
+                    if *length_width != 0 && data.len() == remaining {
+                        return Err(invalid(format!("{type_name} has zero-sized elements")));
+                    }
+                }
+            }
+            TypeDefinition::Tuple { elements } | TypeDefinition::TupleStruct { elements } => {
+                for element in elements {
+                    self.collect_pubkeys(element, total, data, pubkeys, depth + 1)?;
+                }
+            }
+            TypeDefinition::Struct { fields } => {
+                for field in fields {
+                    self.collect_pubkeys(&field.ty, total, data, pubkeys, depth + 1)?;
+                }
+            }
+            TypeDefinition::Enum {
+                tag_width,
+                variants,
+            } => {
+                let tag = read_uint(take(data, *tag_width as usize)?)
+                    .ok_or_else(|| invalid(format!("{type_name} tag does not fit")))?;
+                let variant = variants
+                    .iter()
+                    .find(|variant| variant.discriminant as i128 == tag as i128)
+                    .ok_or_else(|| invalid(format!("{type_name} has no variant {tag}")))?;
+                self.collect_pubkeys(&variant.ty, total, data, pubkeys, depth + 1)?;
+            }
+            // Skipping over values without keys, such as byte vectors,
+            // validates them the same way decoding does
+            _ => {
+                self.decode_value(type_name, data, depth)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a value of `type_name` can hold a `Pubkey`.
+    fn contains_pubkey(&self, type_name: &str, depth: usize) -> bool {
+        if type_name == "Pubkey" {
+            return true;
+        }
+        if depth > MAX_DEPTH {
+            return false;
+        }
+        match self.definitions.get(type_name) {
+            Some(TypeDefinition::Sequence { elements, .. }) => {
+                self.contains_pubkey(elements, depth + 1)
+            }
+            Some(TypeDefinition::Tuple { elements } | TypeDefinition::TupleStruct { elements }) => {
+                elements
+                    .iter()
+                    .any(|element| self.contains_pubkey(element, depth + 1))
+            }
+            Some(TypeDefinition::Struct { fields }) => fields
+                .iter()
+                .any(|field| self.contains_pubkey(&field.ty, depth + 1)),
+            Some(TypeDefinition::Enum { variants, .. }) => variants
+                .iter()
+                .any(|variant| self.contains_pubkey(&variant.ty, depth + 1)),
+            Some(TypeDefinition::Primitive { .. }) | None => false,
+        }
+    }
+
+    fn encode_value(
+        &self,
+        type_name: &str,
+        value: &Value,
+        data: &mut Vec<u8>,
+        depth: usize,
+    ) -> Result<(), AccountGenError> {
+        if depth > MAX_DEPTH {
+            return Err(invalid(format!("type {type_name} nests too deeply")));
+        }
+        let definition = self
+            .definitions
+            .get(type_name)
+            .ok_or_else(|| invalid(format!("no definition for type {type_name}")))?;
+        let mismatch = || invalid(format!("expected {type_name}, got {value}"));
+
+        match definition {
+            TypeDefinition::Primitive { size } => {
+                let bytes = encode_primitive(type_name, value).ok_or_else(mismatch)?;
+                if bytes.len() != *size as usize {
+                    return Err(invalid(format!(
+                        "{} has size {}, got {} bytes",
+                        type_name,
+                        size,
+                        bytes.len()
+                    )));
+                }
+                data.extend_from_slice(&bytes);
+            }
+            TypeDefinition::Sequence {
+                length_width,
+                length_range: (min, max),
+                elements,
+            } => {
+                let text = match value {
+                    Value::String(text) if type_name == "String" && elements == "u8" => Some(text),
+                    _ => None,
+                };
+                let len = match (text, value) {
+                    (Some(text), _) => text.len(),
+                    (None, Value::Array(values)) => values.len(),
+                    _ => return Err(mismatch()),
+                } as u64;
+                if len < *min || len > *max || (*length_width == 0 && min != max) {
+                    return Err(invalid(format!("{type_name} length {len} out of range")));
+                }
+                let width = *length_width as usize;
+                if width > 8 || (width > 0 && width < 8 && len >> (width * 8) != 0) {
+                    return Err(invalid(format!("{type_name} length does not fit")));
+                }
+                data.extend_from_slice(&len.to_le_bytes()[..width]);
+                match (text, value) {
+                    (Some(text), _) => data.extend_from_slice(text.as_bytes()),
+                    (None, Value::Array(values)) => {
+                        for element in values {
+                            self.encode_value(elements, element, data, depth + 1)?;
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            TypeDefinition::Tuple { elements } | TypeDefinition::TupleStruct { elements } => {
+                match value {
+                    Value::Null if elements.is_empty() => {}
+                    Value::Array(values) if values.len() == elements.len() => {
+                        for (element, value) in elements.iter().zip(values) {
+                            self.encode_value(element, value, data, depth + 1)?;
+                        }
+                    }
+                    _ => return Err(mismatch()),
+                }
+            }
+            TypeDefinition::Enum {
+                tag_width,
+                variants,
+            } => {
+                let (name, payload) = match value {
+                    Value::String(name) => (name, &Value::Null),
+                    Value::Object(object) if object.len() == 1 => {
+                        object.iter().next().expect("object has one entry")
+                    }
+                    _ => return Err(mismatch()),
+                };
+                let variant = variants
+                    .iter()
+                    .find(|variant| &variant.name == name)
+                    .ok_or_else(|| invalid(format!("{type_name} has no variant {name}")))?;
+                let width = *tag_width as usize;
+                if width > 8 {
+                    return Err(invalid(format!("{type_name} tag does not fit")));
+                }
+                data.extend_from_slice(&variant.discriminant.to_le_bytes()[..width]);
+                self.encode_value(&variant.ty, payload, data, depth + 1)?;
+            }
+            TypeDefinition::Struct { fields } => match value {
+                Value::Null if fields.is_empty() => {}
+                Value::Object(object) => {
+                    for field in fields {
+                        let value = object.get(&field.name).ok_or_else(|| {
+                            invalid(format!("{} is missing field {}", type_name, field.name))
+                        })?;
+                        self.encode_value(&field.ty, value, data, depth + 1)?;
+                    }
+                }
+                _ => return Err(mismatch()),
+            },
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "schema")]
+impl From<&borsh::schema::Definition> for TypeDefinition {
+    fn from(definition: &borsh::schema::Definition) -> Self {
+        use borsh::schema::{Definition, Fields};
+
+        match definition {
+            Definition::Primitive(size) => Self::Primitive { size: *size },
+            Definition::Sequence {
+                length_width,
+                length_range,
+                elements,
+            } => Self::Sequence {
+                length_width: *length_width,
+                length_range: (*length_range.start(), *length_range.end()),
+                elements: elements.clone(),
+            },
+            Definition::Tuple { elements } => Self::Tuple {
+                elements: elements.clone(),
+            },
+            Definition::Enum {
+                tag_width,
+                variants,
+            } => Self::Enum {
+                tag_width: *tag_width,
+                variants: variants
+                    .iter()
+                    .map(|(discriminant, name, ty)| EnumVariant {
+                        discriminant: *discriminant,
+                        name: name.clone(),
+                        ty: ty.clone(),
+                    })
+                    .collect(),
+            },
+            Definition::Struct { fields } => match fields {
+                Fields::NamedFields(fields) => Self::Struct {
+                    fields: fields
+                        .iter()
+                        .map(|(name, ty)| StructField {
+                            name: name.clone(),
+                            ty: ty.clone(),
+                        })
+                        .collect(),
+                },
+                Fields::UnnamedFields(elements) => Self::TupleStruct {
+                    elements: elements.clone(),
+                },
+                Fields::Empty => Self::Struct { fields: Vec::new() },
+            },
+        }
+    }
+}
+
+fn invalid(message: String) -> AccountGenError {
+    AccountGenError::InvalidDataFormat(message)
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], AccountGenError> {
+    if data.len() < len {
+        return Err(invalid(format!(
+            "unexpected end of data: needed {} more bytes, {} left",
+            len,
+            data.len()
+        )));
+    }
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Ok(head)
+}
+
+/// Reads a little-endian unsigned integer of up to 8 bytes.
+fn read_uint(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() > 8 {
+        return None;
+    }
+    let mut buf = [0u8; 8];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    Some(u64::from_le_bytes(buf))
+}
+
+fn decode_primitive(type_name: &str, bytes: &[u8]) -> Result<Value, AccountGenError> {
+    macro_rules! le {
+        ($ty:ty) => {
+            <$ty>::from_le_bytes(
+                bytes
+                    .try_into()
+                    .map_err(|_| invalid(format!("{} has size {}", type_name, bytes.len())))?,
+            )
+        };
+    }
+
+    let value = match type_name {
+        "()" => Value::Null,
+        "bool" => match bytes {
+            [0] => Value::Bool(false),
+            [1] => Value::Bool(true),
+            _ => return Err(invalid(format!("invalid bool {bytes:?}"))),
+        },
+        "u8" => json!(le!(u8)),
+        "u16" => json!(le!(u16)),
+        "u32" => json!(le!(u32)),
+        "u64" => json!(le!(u64)),
+        "u128" => json!(le!(u128).to_string()),
+        "i8" => json!(le!(i8)),
+        "i16" => json!(le!(i16)),
+        "i32" => json!(le!(i32)),
+        "i64" => json!(le!(i64)),
+        "i128" => json!(le!(i128).to_string()),
+        "f32" => json!(le!(f32)),
+        "f64" => json!(le!(f64)),
+        "Pubkey" => json!(bs58::encode(bytes).into_string()),
+        _ => json!(hex::encode(bytes)),
+    };
+    Ok(value)
+}
+
+fn encode_primitive(type_name: &str, value: &Value) -> Option<Vec<u8>> {
+    macro_rules! int {
+        ($ty:ty) => {
+            match value {
+                Value::Number(number) => number.to_string().parse::<$ty>().ok(),
+                Value::String(text) => text.parse::<$ty>().ok(),
+                _ => None,
+            }
+            .map(|int| int.to_le_bytes().to_vec())
+        };
+    }
+
+    match type_name {
+        "()" => value.is_null().then(Vec::new),
+        "bool" => value.as_bool().map(|b| vec![b as u8]),
+        "u8" => int!(u8),
+        "u16" => int!(u16),
+        "u32" => int!(u32),
+        "u64" => int!(u64),
+        "u128" => int!(u128),
+        "i8" => int!(i8),
+        "i16" => int!(i16),
+        "i32" => int!(i32),
+        "i64" => int!(i64),
+        "i128" => int!(i128),
+        "f32" => value.as_f64().map(|f| (f as f32).to_le_bytes().to_vec()),
+        "f64" => value.as_f64().map(|f| f.to_le_bytes().to_vec()),
+        "Pubkey" => value
+            .as_str()
+            .and_then(|text| bs58::decode(text).into_vec().ok()),
+        _ => value.as_str().and_then(|text| hex::decode(text).ok()),
+    }
+}
@@ -0,0 +1,233 @@
+//! Recording the transactions of a test session.
+//!
+//! A test that sends several transactions and fails on the fifth says
+//! little about what the first four did. [`SessionLog`] submits
+//! transactions on the test's behalf and keeps, for each one, its
+//! signature, instructions, result, logs and compute units. Dumping the log
+//! as JSON when a test ends lets a flaky failure be reconstructed from the
+//! file instead of re-run until it happens again.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use solana_accountgen::session::SessionLog;
+//! use solana_program_test::ProgramTest;
+//! # use solana_transaction::Transaction;
+//!
+//! # async fn run(transactions: Vec<Transaction>) {
+//! let (mut banks_client, _payer, _) = ProgramTest::default().start().await;
+//! let mut session = SessionLog::new();
+//! for transaction in transactions {
+//!     let result = session
+//!         .process_transaction(&mut banks_client, transaction)
+//!         .await
+//!         .unwrap();
+//!     if result.is_err() {
+//!         session.save("target/failed-session.json").unwrap();
+//!         panic!("transaction failed:\n{}", session);
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::AccountGenError;
+use crate::backend::decompile;
+use crate::fixture::FixtureInstruction;
+use crate::logs::Logs;
+use serde::{Deserialize, Serialize};
+use solana_program_test::{BanksClient, BanksClientError};
+use solana_signature::Signature;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionResult;
+use std::fmt;
+use std::path::Path;
+
+/// One transaction recorded by a [`SessionLog`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedTransaction {
+    /// The first signature of the transaction.
+    #[serde(with = "signature_string")]
+    pub signature: Signature,
+    /// The transaction's instructions, in order.
+    pub instructions: Vec<FixtureInstruction>,
+    /// The error the transaction failed with, or `None` if it succeeded.
+    pub error: Option<String>,
+    /// The raw log lines.
+    pub logs: Vec<String>,
+    /// Compute units consumed, if the bank reported them.
+    pub compute_units: Option<u64>,
+}
+
+impl RecordedTransaction {
+    /// Returns true if the transaction succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Parses the recorded log lines.
+    pub fn parsed_logs(&self) -> Logs {
+        Logs::from_messages(self.logs.clone())
+    }
+}
+
+/// The transactions processed during a test, in submission order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionLog {
+    transactions: Vec<RecordedTransaction>,
+}
+
+impl SessionLog {
+    /// Creates an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Processes a transaction and records it.
+    ///
+    /// Like [`process_transaction_with_logs`](crate::logs::process_transaction_with_logs),
+    /// a failed transaction is returned as the inner result, so it is
+    /// recorded too. Only a transaction the bank could not process at all,
+    /// such as one with an expired blockhash, returns an error and is not
+    /// recorded.
+    pub async fn process_transaction(
+        &mut self,
+        banks_client: &mut BanksClient,
+        transaction: Transaction,
+    ) -> Result<TransactionResult<()>, BanksClientError> {
+        let outcome = banks_client
+            .process_transaction_with_metadata(transaction.clone())
+            .await?;
+        let (logs, compute_units) = match outcome.metadata {
+            Some(metadata) => (metadata.log_messages, Some(metadata.compute_units_consumed)),
+            None => (Vec::new(), None),
+        };
+        self.record(&transaction, &outcome.result, logs, compute_units);
+        Ok(outcome.result)
+    }
+
+    /// Records a transaction processed some other way, such as through a
+    /// [`TestBackend`](crate::backend::TestBackend).
+    pub fn record(
+        &mut self,
+        transaction: &Transaction,
+        result: &TransactionResult<()>,
+        logs: Vec<String>,
+        compute_units: Option<u64>,
+    ) {
+        // A transaction that does not decompile never ran any instruction
+        let instructions = decompile(transaction)
+            .unwrap_or_default()
+            .into_iter()
+            .map(FixtureInstruction::from)
+            .collect();
+        self.transactions.push(RecordedTransaction {
+            signature: transaction.signatures.first().copied().unwrap_or_default(),
+            instructions,
+            error: result.as_ref().err().map(ToString::to_string),
+            logs,
+            compute_units,
+        });
+    }
+
+    /// Returns the recorded transactions in submission order.
+    pub fn transactions(&self) -> &[RecordedTransaction] {
+        &self.transactions
+    }
+
+    /// Returns the recorded transactions that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &RecordedTransaction> {
+        self.transactions
+            .iter()
+            .filter(|transaction| !transaction.is_ok())
+    }
+
+    /// Returns the number of recorded transactions.
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Returns true if no transaction has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Returns the compute units consumed by every recorded transaction.
+    pub fn total_compute_units(&self) -> u64 {
+        self.transactions
+            .iter()
+            .filter_map(|transaction| transaction.compute_units)
+            .sum()
+    }
+
+    /// Serializes the log to pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("session logs always serialize")
+    }
+
+    /// Parses a log written by [`SessionLog::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AccountGenError::InvalidDataFormat`] if the JSON does not
+    /// parse.
+    pub fn from_json(json: &str) -> Result<Self, AccountGenError> {
+        serde_json::from_str(json)
+            .map_err(|e| AccountGenError::InvalidDataFormat(format!("invalid session log: {e}")))
+    }
+
+    /// Writes the log to a JSON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AccountGenError> {
+        std::fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    /// Reads a log from a JSON file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AccountGenError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+impl fmt::Display for SessionLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, transaction) in self.transactions.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "#{} {} ({} instructions",
+                index,
+                transaction.signature,
+                transaction.instructions.len()
+            )?;
+            if let Some(units) = transaction.compute_units {
+                write!(f, ", {} CU", units)?;
+            }
+            match &transaction.error {
+                Some(error) => write!(f, "): failed: {}", error)?,
+                None => write!(f, "): ok")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+mod signature_string {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use solana_signature::Signature;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(
+        signature: &Signature,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(signature)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Signature, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Signature::from_str(&s).map_err(|e| D::Error::custom(format!("invalid signature {s}: {e}")))
+    }
+}
@@ -0,0 +1,183 @@
+use crate::rpc::RpcFilter;
+use crate::stats::{AccountMapStats, DiscriminatorGroups};
+use crate::updates::AccountUpdates;
+use crate::{AccountBuilder, AccountGenError, AccountMap};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// An [`AccountMap`] that can be shared between threads and async tasks.
+///
+/// Clones of a `SharedAccountMap` refer to the same accounts, so a test can
+/// hand one to a [`MockRpcServer`](crate::rpc::MockRpcServer) or to spawned
+/// tasks and keep another to inspect or change fixture state while they run.
+/// The methods mirror those of `AccountMap`, returning owned accounts
+/// instead of references; [`read`](Self::read) and [`write`](Self::write)
+/// give direct access to the map for anything else.
+///
+/// A panic while the map is locked does not poison it for other users.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::{AccountBuilder, SharedAccountMap};
+/// use solana_pubkey::Pubkey;
+/// use std::thread;
+///
+/// let accounts = SharedAccountMap::new();
+/// let workers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let accounts = accounts.clone();
+///         thread::spawn(move || {
+///             let builder = AccountBuilder::new().balance(1_000);
+///             accounts.add_with_builder(Pubkey::new_unique(), builder).unwrap();
+///         })
+///     })
+///     .collect();
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+/// assert_eq!(accounts.len(), 4);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedAccountMap {
+    inner: Arc<RwLock<AccountMap>>,
+}
+
+impl From<AccountMap> for SharedAccountMap {
+    fn from(account_map: AccountMap) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(account_map)),
+        }
+    }
+}
+
+impl SharedAccountMap {
+    /// Creates a new empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks the map for reading.
+    ///
+    /// Writers wait until the guard is dropped, so do not hold it across an
+    /// `.await`.
+    pub fn read(&self) -> RwLockReadGuard<'_, AccountMap> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Locks the map for writing, for changes that must not interleave with
+    /// other users.
+    ///
+    /// Other users wait until the guard is dropped, so do not hold it across
+    /// an `.await`.
+    pub fn write(&self) -> RwLockWriteGuard<'_, AccountMap> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns a copy of the accounts as they are now.
+    pub fn snapshot(&self) -> AccountMap {
+        self.read().clone()
+    }
+
+    /// Adds an account with its pubkey to the map.
+    pub fn set_account(&self, pubkey: Pubkey, account: Account) {
+        self.write().set_account(pubkey, account);
+    }
+
+    /// Adds an account created with `AccountBuilder` to the map.
+    pub fn add_with_builder(
+        &self,
+        pubkey: Pubkey,
+        builder: AccountBuilder,
+    ) -> Result<&Self, AccountGenError> {
+        self.write().add_with_builder(pubkey, builder)?;
+        Ok(self)
+    }
+
+    /// Returns a copy of the account at `pubkey`.
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.read().get_account(pubkey).cloned()
+    }
+
+    /// Changes the account at `pubkey` in place, holding the write lock so
+    /// no other change can interleave. Subscribers are notified of the
+    /// result. Returns false if there is no such account.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_accountgen::{AccountBuilder, SharedAccountMap};
+    /// use solana_pubkey::Pubkey;
+    ///
+    /// let vault = Pubkey::new_unique();
+    /// let accounts = SharedAccountMap::new();
+    /// accounts.add_with_builder(vault, AccountBuilder::new().balance(100)).unwrap();
+    ///
+    /// assert!(accounts.update_account(&vault, |account| account.lamports += 50));
+    /// assert_eq!(accounts.get_account(&vault).unwrap().lamports, 150);
+    /// ```
+    pub fn update_account<F>(&self, pubkey: &Pubkey, f: F) -> bool
+    where
+        F: FnOnce(&mut Account),
+    {
+        let mut map = self.write();
+        let Some(mut account) = map.get_account(pubkey).cloned() else {
+            return false;
+        };
+        f(&mut account);
+        map.set_account(*pubkey, account);
+        true
+    }
+
+    /// Removes an account from the map.
+    pub fn remove_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        self.write().remove_account(pubkey)
+    }
+
+    /// Returns the number of accounts in the map.
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Returns true if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Merges another AccountMap into this one; see [`AccountMap::merge`].
+    pub fn merge(&self, other: AccountMap) {
+        self.write().merge(other);
+    }
+
+    /// Returns a new AccountMap containing only the accounts that satisfy
+    /// the predicate.
+    pub fn filter<F>(&self, predicate: F) -> AccountMap
+    where
+        F: FnMut(&Pubkey, &Account) -> bool,
+    {
+        self.read().filter(predicate)
+    }
+
+    /// Returns the accounts owned by `program_id` that satisfy every filter;
+    /// see [`AccountMap::get_program_accounts`].
+    pub fn get_program_accounts(&self, program_id: &Pubkey, filters: &[RpcFilter]) -> AccountMap {
+        self.read().get_program_accounts(program_id, filters)
+    }
+
+    /// Returns counts, lamports and data sizes per owner program.
+    pub fn stats(&self) -> AccountMapStats {
+        self.read().stats()
+    }
+
+    /// Groups accounts by the first 8 bytes of their data; see
+    /// [`AccountMap::group_by_discriminator`].
+    pub fn group_by_discriminator(&self) -> DiscriminatorGroups {
+        self.read().group_by_discriminator()
+    }
+
+    /// Subscribes to changes made to the map from now on, by any user.
+    pub fn subscribe(&self) -> AccountUpdates {
+        self.write().subscribe()
+    }
+}
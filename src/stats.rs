@@ -0,0 +1,347 @@
+//! Summary statistics for account sets.
+//!
+//! Large fixture sets, especially ones cloned from a cluster, are easier
+//! to sanity-check from a summary than account by account. [`AccountMap::stats`]
+//! groups accounts by owner program and reports counts, lamports and data
+//! sizes for each.
+//!
+//! ```text
+//! 3 accounts, 0.003000000 SOL, 330 bytes of data
+//!
+//! TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA (Token Program)
+//!   accounts:     2
+//!   lamports:     2000000 (0.002000000 SOL)
+//!   data:         330 bytes
+//!   sizes:        129 B - 1 KiB: 2
+//! ```
+//!
+//! [`AccountMap::group_by_discriminator`] groups accounts by the first 8
+//! bytes of their data instead, which for Anchor programs is the account
+//! type. Naming the groups with a [`DiscriminatorRegistry`], filled from
+//! type names or an IDL, shows whether a cloned or generated set holds the
+//! account types intended.
+//!
+//! ```text
+//! 5 accounts in 2 discriminator groups
+//!   f19a6d0411b16dbc  Pool       3 accounts, 1911 bytes
+//!   e445a52e51cb9a1d  (unknown)  2 accounts, 400 bytes
+//! ```
+
+use crate::AccountMap;
+use crate::display::format_sol;
+use crate::extensions::anchor::get_account_discriminator;
+use crate::extensions::idl::Idl;
+use crate::well_known;
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+/// Upper bounds (inclusive) of the data size buckets, in bytes. Larger
+/// accounts fall into a final, unbounded bucket.
+pub const SIZE_BUCKETS: &[usize] = &[0, 128, 1024, 10 * 1024, 100 * 1024, 1024 * 1024];
+
+/// Statistics for a whole [`AccountMap`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountMapStats {
+    /// Totals over every account.
+    pub total: OwnerStats,
+    /// Statistics per owner program.
+    pub owners: BTreeMap<Pubkey, OwnerStats>,
+}
+
+/// Statistics for the accounts of one owner, or of a whole map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerStats {
+    /// The number of accounts.
+    pub accounts: usize,
+    /// The sum of their balances, saturating at `u64::MAX`.
+    pub lamports: u64,
+    /// The sum of their data lengths.
+    pub data_bytes: usize,
+    /// The number of executable accounts.
+    pub executable: usize,
+    /// The number of accounts per data size bucket: one entry per bound in
+    /// [`SIZE_BUCKETS`], plus one for larger accounts.
+    pub size_histogram: Vec<usize>,
+}
+
+impl Default for OwnerStats {
+    fn default() -> Self {
+        Self {
+            accounts: 0,
+            lamports: 0,
+            data_bytes: 0,
+            executable: 0,
+            size_histogram: vec![0; SIZE_BUCKETS.len() + 1],
+        }
+    }
+}
+
+impl OwnerStats {
+    fn add(&mut self, account: &Account) {
+        self.accounts += 1;
+        self.lamports = self.lamports.saturating_add(account.lamports);
+        self.data_bytes += account.data.len();
+        if account.executable {
+            self.executable += 1;
+        }
+        let bucket = SIZE_BUCKETS
+            .iter()
+            .position(|bound| account.data.len() <= *bound)
+            .unwrap_or(SIZE_BUCKETS.len());
+        self.size_histogram[bucket] += 1;
+    }
+
+    fn write_fields(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        field(f, "accounts", &self.accounts.to_string())?;
+        field(
+            f,
+            "lamports",
+            &format!("{} ({} SOL)", self.lamports, format_sol(self.lamports)),
+        )?;
+        field(f, "data", &format!("{} bytes", self.data_bytes))?;
+        if self.executable > 0 {
+            field(f, "executable", &self.executable.to_string())?;
+        }
+        let sizes: Vec<String> = self
+            .size_histogram
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(bucket, count)| format!("{}: {}", bucket_label(bucket), count))
+            .collect();
+        if !sizes.is_empty() {
+            field(f, "sizes", &sizes.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl AccountMapStats {
+    pub(crate) fn collect(account_map: &AccountMap) -> Self {
+        let mut stats = Self::default();
+        for (_, account) in account_map.iter() {
+            stats.total.add(account);
+            stats.owners.entry(account.owner).or_default().add(account);
+        }
+        stats
+    }
+}
+
+/// Prints the totals, then one block per owner with the most accounts first.
+impl fmt::Display for AccountMapStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} accounts, {} SOL, {} bytes of data",
+            self.total.accounts,
+            format_sol(self.total.lamports),
+            self.total.data_bytes
+        )?;
+
+        let mut owners: Vec<_> = self.owners.iter().collect();
+        owners.sort_by(|(a_key, a), (b_key, b)| b.accounts.cmp(&a.accounts).then(a_key.cmp(b_key)));
+        for (owner, stats) in owners {
+            writeln!(f)?;
+            match well_known::name_for(owner) {
+                Some(name) => writeln!(f, "{} ({})", owner, name)?,
+                None => writeln!(f, "{}", owner)?,
+            }
+            stats.write_fields(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn field(f: &mut fmt::Formatter<'_>, name: &str, value: &str) -> fmt::Result {
+    writeln!(f, "  {:<13} {}", format!("{}:", name), value)
+}
+
+fn bucket_label(bucket: usize) -> String {
+    fn size(bytes: usize) -> String {
+        match bytes {
+            0..1024 => format!("{} B", bytes),
+            1024..1_048_576 => format!("{} KiB", bytes / 1024),
+            _ => format!("{} MiB", bytes / 1_048_576),
+        }
+    }
+
+    match bucket {
+        0 => "0 B".to_string(),
+        _ if bucket == SIZE_BUCKETS.len() => format!("> {}", size(SIZE_BUCKETS[bucket - 1])),
+        _ => format!(
+            "{} - {}",
+            size(SIZE_BUCKETS[bucket - 1] + 1),
+            size(SIZE_BUCKETS[bucket])
+        ),
+    }
+}
+
+/// Names of account types, by discriminator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiscriminatorRegistry {
+    names: HashMap<[u8; 8], String>,
+}
+
+impl DiscriminatorRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry of the account types of an Anchor IDL.
+    pub fn from_idl(idl: &Idl) -> Self {
+        Self::new().register_idl(idl)
+    }
+
+    /// Registers the Anchor account type `name`, whose discriminator is
+    /// derived from the name.
+    pub fn register(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.register_discriminator(get_account_discriminator(&name), name)
+    }
+
+    /// Registers an account type with an explicit discriminator, replacing
+    /// any name it had.
+    pub fn register_discriminator(
+        mut self,
+        discriminator: [u8; 8],
+        name: impl Into<String>,
+    ) -> Self {
+        self.names.insert(discriminator, name.into());
+        self
+    }
+
+    /// Registers every account type of an Anchor IDL.
+    pub fn register_idl(self, idl: &Idl) -> Self {
+        idl.accounts.iter().fold(self, |registry, account| {
+            registry.register_discriminator(idl.account_discriminator(account), &account.name)
+        })
+    }
+
+    /// Returns the name registered for `discriminator`.
+    pub fn name_of(&self, discriminator: &[u8; 8]) -> Option<&str> {
+        self.names.get(discriminator).map(String::as_str)
+    }
+}
+
+/// The accounts of a map whose data starts with the same 8 bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiscriminatorGroup {
+    /// The account type, once [named](DiscriminatorGroups::named).
+    pub name: Option<String>,
+    /// The number of accounts.
+    pub accounts: usize,
+    /// The sum of their data lengths.
+    pub data_bytes: usize,
+    /// The programs that own them.
+    pub owners: BTreeSet<Pubkey>,
+}
+
+/// Accounts grouped by their first 8 bytes of data, returned by
+/// [`AccountMap::group_by_discriminator`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiscriminatorGroups {
+    /// The groups, by discriminator.
+    pub groups: BTreeMap<[u8; 8], DiscriminatorGroup>,
+    /// The number of accounts with less than 8 bytes of data, which are in
+    /// no group.
+    pub short: usize,
+}
+
+impl DiscriminatorGroups {
+    pub(crate) fn collect(account_map: &AccountMap) -> Self {
+        let mut groups = Self::default();
+        for (_, account) in account_map.iter() {
+            let Some(discriminator) = account.data.first_chunk::<8>() else {
+                groups.short += 1;
+                continue;
+            };
+            let group = groups.groups.entry(*discriminator).or_default();
+            group.accounts += 1;
+            group.data_bytes += account.data.len();
+            group.owners.insert(account.owner);
+        }
+        groups
+    }
+
+    /// Names every group whose discriminator is in `registry`.
+    pub fn named(mut self, registry: &DiscriminatorRegistry) -> Self {
+        for (discriminator, group) in &mut self.groups {
+            if let Some(name) = registry.name_of(discriminator) {
+                group.name = Some(name.to_string());
+            }
+        }
+        self
+    }
+
+    /// Returns the number of accounts whose data starts with
+    /// `discriminator`.
+    pub fn count(&self, discriminator: &[u8; 8]) -> usize {
+        self.groups
+            .get(discriminator)
+            .map_or(0, |group| group.accounts)
+    }
+
+    /// Returns the number of accounts per named account type. Groups
+    /// without a name are left out.
+    pub fn by_type(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for group in self.groups.values() {
+            if let Some(name) = &group.name {
+                *counts.entry(name.as_str()).or_default() += group.accounts;
+            }
+        }
+        counts
+    }
+
+    /// Returns the number of accounts in groups without a name.
+    pub fn unnamed(&self) -> usize {
+        self.groups
+            .values()
+            .filter(|group| group.name.is_none())
+            .map(|group| group.accounts)
+            .sum()
+    }
+}
+
+/// Prints one line per group with the most accounts first, then the
+/// accounts too short to have a discriminator.
+impl fmt::Display for DiscriminatorGroups {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let accounts: usize = self.groups.values().map(|group| group.accounts).sum();
+        write!(
+            f,
+            "{} accounts in {} discriminator groups",
+            accounts + self.short,
+            self.groups.len()
+        )?;
+
+        let mut groups: Vec<_> = self.groups.iter().collect();
+        groups.sort_by(|(a_key, a), (b_key, b)| b.accounts.cmp(&a.accounts).then(a_key.cmp(b_key)));
+        let width = groups
+            .iter()
+            .map(|(_, group)| group.name.as_deref().map_or(9, str::len))
+            .max()
+            .unwrap_or(0);
+        for (discriminator, group) in groups {
+            write!(
+                f,
+                "\n  {}  {:<width$}  {} accounts, {} bytes",
+                hex::encode(discriminator),
+                group.name.as_deref().unwrap_or("(unknown)"),
+                group.accounts,
+                group.data_bytes,
+            )?;
+        }
+        if self.short > 0 {
+            write!(
+                f,
+                "\n  {} accounts have less than 8 bytes of data",
+                self.short
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+//! Tracking the pubkeys a test expects to exist.
+//!
+//! A program that creates an account the test never mentions usually has a
+//! bug: a wrong seed, a leaked temporary account, an account created twice
+//! under different addresses. Such accounts do not show up in assertions on
+//! the accounts a test does know about. [`track_pubkeys`] starts recording
+//! every pubkey this crate generates or places an account at, and
+//! [`assert_no_unexpected_transaction_accounts`] then fails if the
+//! transactions a test ran left an account at any other address.
+//!
+//! BanksClient cannot list the accounts in the bank, so only the addresses
+//! the given transactions reference are checked. Accounts created by
+//! transactions the test does not pass in are not found.
+//!
+//! Pubkeys are recorded by [`keys`](crate::keys), by the `AccountBuilder`
+//! methods that take or derive a pubkey, by
+//! [`AccountMap::add_with_builder`](crate::AccountMap::add_with_builder) and
+//! by [`ProgramTestExt`](crate::extensions::program_test::ProgramTestExt).
+//! Other addresses, such as a plain `Keypair::new()`, can be added with
+//! [`KeyTracking::expect`].
+//!
+//! Recording is per thread, so tests running in parallel do not see each
+//! other's pubkeys. Async tests must run on a current-thread runtime, which
+//! is the default for `#[tokio::test]`.
+//!
+//! ```
+//! use solana_accountgen::keys::test_pubkey;
+//! use solana_accountgen::tracking::track_pubkeys;
+//! use solana_pubkey::Pubkey;
+//!
+//! let tracking = track_pubkeys();
+//! let alice = test_pubkey("alice");
+//! assert!(tracking.contains(&alice));
+//! assert!(!tracking.contains(&Pubkey::new_unique()));
+//! ```
+
+use crate::AccountMap;
+use solana_program_test::BanksClient;
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::{native_loader, sysvar};
+use solana_transaction::Transaction;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+
+thread_local! {
+    static EXPECTED: RefCell<Option<BTreeSet<Pubkey>>> = const { RefCell::new(None) };
+}
+
+/// Records `pubkey` as expected if tracking is active on this thread.
+pub(crate) fn record(pubkey: Pubkey) {
+    EXPECTED.with_borrow_mut(|expected| {
+        if let Some(expected) = expected {
+            expected.insert(pubkey);
+        }
+    });
+}
+
+/// Starts recording expected pubkeys on this thread.
+///
+/// Recording stops when the returned guard is dropped. Starting again while
+/// a guard is alive clears the pubkeys recorded so far.
+pub fn track_pubkeys() -> KeyTracking {
+    EXPECTED.with_borrow_mut(|expected| *expected = Some(BTreeSet::new()));
+    KeyTracking {
+        _thread: PhantomData,
+    }
+}
+
+/// A guard that keeps pubkey tracking active; see [`track_pubkeys`].
+#[derive(Debug)]
+pub struct KeyTracking {
+    // Recording is per thread, so the guard must stay on its thread
+    _thread: PhantomData<*const ()>,
+}
+
+impl KeyTracking {
+    /// Adds a pubkey the crate did not generate, such as a random keypair's.
+    pub fn expect(&self, pubkey: Pubkey) -> &Self {
+        record(pubkey);
+        self
+    }
+
+    /// Returns true if `pubkey` has been recorded.
+    pub fn contains(&self, pubkey: &Pubkey) -> bool {
+        EXPECTED.with_borrow(|expected| expected.as_ref().is_some_and(|e| e.contains(pubkey)))
+    }
+
+    /// Returns the pubkeys recorded so far.
+    pub fn pubkeys(&self) -> BTreeSet<Pubkey> {
+        EXPECTED.with_borrow(|expected| expected.clone().unwrap_or_default())
+    }
+
+    /// Returns the pubkeys of accounts in `account_map` that were not
+    /// recorded, in order.
+    pub fn unexpected_accounts(&self, account_map: &AccountMap) -> Vec<Pubkey> {
+        let mut unexpected: Vec<Pubkey> = account_map
+            .iter()
+            .filter(|(pubkey, account)| !account.executable && !self.contains(pubkey))
+            .map(|(pubkey, _)| *pubkey)
+            .collect();
+        unexpected.sort();
+        unexpected
+    }
+}
+
+impl Drop for KeyTracking {
+    fn drop(&mut self) {
+        EXPECTED.with_borrow_mut(|expected| *expected = None);
+    }
+}
+
+/// Asserts that no account exists at an address `transactions` reference
+/// unless it was recorded by [`track_pubkeys`].
+///
+/// Only the account keys of `transactions` are checked, not the whole bank,
+/// so accounts created by other transactions are not found. Fee payers,
+/// executable accounts and sysvars are skipped, since no program can create
+/// them.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_accountgen::tracking::{assert_no_unexpected_transaction_accounts, track_pubkeys};
+/// use solana_program_test::ProgramTest;
+/// # use solana_transaction::Transaction;
+///
+/// # async fn run(transaction: Transaction) {
+/// let tracking = track_pubkeys();
+/// let (mut banks_client, _payer, _) = ProgramTest::default().start().await;
+/// banks_client.process_transaction(transaction.clone()).await.unwrap();
+/// assert_no_unexpected_transaction_accounts(&mut banks_client, &[transaction]).await;
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// Panics if tracking is not active on this thread, if an account cannot be
+/// fetched, or if an unexpected account exists.
+pub async fn assert_no_unexpected_transaction_accounts(
+    banks_client: &mut BanksClient,
+    transactions: &[Transaction],
+) {
+    let expected = EXPECTED
+        .with_borrow(|expected| expected.clone())
+        .expect("pubkey tracking is not active; call track_pubkeys first");
+
+    let candidates: BTreeSet<Pubkey> = transactions
+        .iter()
+        .flat_map(|transaction| transaction.message.account_keys.iter().skip(1))
+        .filter(|pubkey| !expected.contains(pubkey))
+        .copied()
+        .collect();
+
+    let mut unexpected = Vec::new();
+    for pubkey in candidates {
+        let account = banks_client
+            .get_account(pubkey)
+            .await
+            .unwrap_or_else(|e| panic!("failed to fetch account {}: {}", pubkey, e));
+        if let Some(account) = account {
+            let skipped = account.executable
+                || account.owner == sysvar::id()
+                || account.owner == native_loader::id();
+            if !skipped {
+                unexpected.push(format!(
+                    "  {} (owner {}, {} bytes)",
+                    pubkey,
+                    account.owner,
+                    account.data.len()
+                ));
+            }
+        }
+    }
+
+    assert!(
+        unexpected.is_empty(),
+        "unexpected accounts exist:\n{}",
+        unexpected.join("\n")
+    );
+}
@@ -0,0 +1,312 @@
+//! Checking that a sequence of fixture states is reachable.
+//!
+//! Scenario files often describe a test as a series of account states: the
+//! setup, the state after the first transaction, the state after the next.
+//! Edited by hand, such a series easily encodes a transition no transaction
+//! could make, such as lamports appearing from nowhere or a program
+//! rewriting an account it does not own, and the test then asserts against
+//! a state the program under test can never produce. [`TransitionChecker`]
+//! checks consecutive states against the rules the runtime enforces on
+//! every transaction.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::transitions::TransitionChecker;
+//! use solana_accountgen::{AccountBuilder, AccountMap};
+//! use solana_pubkey::Pubkey;
+//!
+//! let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+//! let mut before = AccountMap::new();
+//! before.add_with_builder(alice, AccountBuilder::new().balance(1_000)).unwrap();
+//!
+//! // A transfer moves lamports without creating any
+//! let mut after = AccountMap::new();
+//! after.add_with_builder(alice, AccountBuilder::new().balance(400)).unwrap();
+//! after.add_with_builder(bob, AccountBuilder::new().balance(600)).unwrap();
+//!
+//! let checker = TransitionChecker::new();
+//! assert!(checker.check_sequence(&[before.clone(), after]).is_ok());
+//!
+//! // Bob's balance doubled, but nobody paid for it
+//! let mut minted = AccountMap::new();
+//! minted.add_with_builder(bob, AccountBuilder::new().balance(1_200)).unwrap();
+//! let report = checker.check(&before, &minted);
+//! assert!(report.to_string().contains("lamports not conserved"));
+//! ```
+
+use crate::cluster::{MAX_PERMITTED_DATA_LENGTH, MAX_REALLOC_INCREASE};
+use crate::well_known::SYSTEM_PROGRAM;
+use crate::{AccountGenError, AccountMap};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// One way a transition breaks a runtime rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionViolation {
+    /// The total balance changed by more than the allowed fees.
+    LamportsNotConserved { before: u128, after: u128 },
+    /// A program account's data, owner or executable flag changed.
+    ExecutableModified { pubkey: Pubkey },
+    /// Data grew by more than the realloc limit allows.
+    ReallocTooLarge {
+        pubkey: Pubkey,
+        old_len: usize,
+        new_len: usize,
+        max_increase: usize,
+    },
+    /// Data grew past the largest account the runtime allows.
+    DataTooLarge { pubkey: Pubkey, len: usize },
+    /// An account was debited, had its data changed or was reassigned by
+    /// a program that does not own it.
+    NotOwnerModified {
+        pubkey: Pubkey,
+        owner: Pubkey,
+        change: &'static str,
+    },
+}
+
+impl fmt::Display for TransitionViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LamportsNotConserved { before, after } => write!(
+                f,
+                "lamports not conserved: {} before, {} after",
+                before, after
+            ),
+            Self::ExecutableModified { pubkey } => {
+                write!(f, "{}: program account modified", pubkey)
+            }
+            Self::ReallocTooLarge {
+                pubkey,
+                old_len,
+                new_len,
+                max_increase,
+            } => write!(
+                f,
+                "{}: data grew from {} to {} bytes, more than the {} allowed",
+                pubkey, old_len, new_len, max_increase
+            ),
+            Self::DataTooLarge { pubkey, len } => write!(
+                f,
+                "{}: data is {} bytes, more than the {} allowed",
+                pubkey, len, MAX_PERMITTED_DATA_LENGTH
+            ),
+            Self::NotOwnerModified {
+                pubkey,
+                owner,
+                change,
+            } => write!(
+                f,
+                "{}: {}, but its owner {} did not run",
+                pubkey, change, owner
+            ),
+        }
+    }
+}
+
+/// The violations found by [`TransitionChecker::check`] or
+/// [`TransitionChecker::check_sequence`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransitionReport {
+    /// Every violation with the index of the state it leads to, in order.
+    pub violations: Vec<(usize, TransitionViolation)>,
+}
+
+impl TransitionReport {
+    /// Returns true if every transition is reachable.
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Converts the report into an error if there are violations.
+    pub fn into_result(self) -> Result<(), AccountGenError> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            Err(AccountGenError::ConstraintViolation(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for TransitionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "every transition is reachable");
+        }
+        write!(f, "unreachable transitions:")?;
+        for (index, violation) in &self.violations {
+            write!(f, "\n  state {}: {}", index, violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks consecutive account states against the runtime's rules.
+///
+/// By default a transition is one instruction that pays no fees:
+///
+/// - the total balance of all accounts stays the same
+/// - program accounts keep their data, owner and executable flag
+/// - existing data grows by at most [`MAX_REALLOC_INCREASE`] bytes and
+///   never past [`MAX_PERMITTED_DATA_LENGTH`]; accounts that were empty
+///   and owned by the System Program may be allocated at any size
+///
+/// Which program changed an account cannot be seen in the states. Name the
+/// programs that run with [`TransitionChecker::programs`] to also require
+/// that only an account's owner debits it, changes its data or assigns it
+/// a new owner.
+#[derive(Debug, Clone)]
+pub struct TransitionChecker {
+    instructions: usize,
+    fees: u64,
+    programs: Option<BTreeSet<Pubkey>>,
+}
+
+impl Default for TransitionChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransitionChecker {
+    /// Creates a checker for single-instruction transitions without fees.
+    pub fn new() -> Self {
+        Self {
+            instructions: 1,
+            fees: 0,
+            programs: None,
+        }
+    }
+
+    /// Allows each transition to be up to `count` instructions, each of
+    /// which may grow an account by [`MAX_REALLOC_INCREASE`] bytes.
+    pub fn instructions(mut self, count: usize) -> Self {
+        self.instructions = count.max(1);
+        self
+    }
+
+    /// Allows up to `lamports` to leave the accounts in each transition, as
+    /// transaction fees do.
+    pub fn fees(mut self, lamports: u64) -> Self {
+        self.fees = lamports;
+        self
+    }
+
+    /// Names the programs that run during each transition. The System
+    /// Program must be included for wallets to be debited.
+    pub fn programs(mut self, programs: &[Pubkey]) -> Self {
+        self.programs = Some(programs.iter().copied().collect());
+        self
+    }
+
+    /// Checks the transition from `before` to `after`.
+    ///
+    /// An account missing from either state is treated as an empty account
+    /// of the System Program, as the runtime treats it.
+    pub fn check(&self, before: &AccountMap, after: &AccountMap) -> TransitionReport {
+        TransitionReport {
+            violations: self
+                .violations(before, after)
+                .into_iter()
+                .map(|violation| (1, violation))
+                .collect(),
+        }
+    }
+
+    /// Checks every pair of consecutive states in `states`.
+    pub fn check_sequence(&self, states: &[AccountMap]) -> TransitionReport {
+        let violations = states
+            .windows(2)
+            .enumerate()
+            .flat_map(|(index, pair)| {
+                self.violations(&pair[0], &pair[1])
+                    .into_iter()
+                    .map(move |violation| (index + 1, violation))
+            })
+            .collect();
+        TransitionReport { violations }
+    }
+
+    fn violations(&self, before: &AccountMap, after: &AccountMap) -> Vec<TransitionViolation> {
+        let mut violations = Vec::new();
+
+        let total = |accounts: &AccountMap| -> u128 {
+            accounts
+                .iter()
+                .map(|(_, account)| u128::from(account.lamports))
+                .sum()
+        };
+        let (total_before, total_after) = (total(before), total(after));
+        if total_after > total_before || total_after + u128::from(self.fees) < total_before {
+            violations.push(TransitionViolation::LamportsNotConserved {
+                before: total_before,
+                after: total_after,
+            });
+        }
+
+        let pubkeys: BTreeSet<Pubkey> = before
+            .iter()
+            .chain(after.iter())
+            .map(|(pubkey, _)| *pubkey)
+            .collect();
+        let empty = Account::default();
+        for pubkey in pubkeys {
+            let old = before.get_account(&pubkey).unwrap_or(&empty);
+            let new = after.get_account(&pubkey).unwrap_or(&empty);
+            self.check_account(&pubkey, old, new, &mut violations);
+        }
+        violations
+    }
+
+    fn check_account(
+        &self,
+        pubkey: &Pubkey,
+        old: &Account,
+        new: &Account,
+        violations: &mut Vec<TransitionViolation>,
+    ) {
+        if old.executable && (!new.executable || new.owner != old.owner || new.data != old.data) {
+            violations.push(TransitionViolation::ExecutableModified { pubkey: *pubkey });
+        }
+
+        let allocated = old.data.is_empty() && old.owner == SYSTEM_PROGRAM;
+        let max_increase = MAX_REALLOC_INCREASE.saturating_mul(self.instructions);
+        if new.data.len() > MAX_PERMITTED_DATA_LENGTH {
+            violations.push(TransitionViolation::DataTooLarge {
+                pubkey: *pubkey,
+                len: new.data.len(),
+            });
+        } else if !allocated && new.data.len() > old.data.len().saturating_add(max_increase) {
+            violations.push(TransitionViolation::ReallocTooLarge {
+                pubkey: *pubkey,
+                old_len: old.data.len(),
+                new_len: new.data.len(),
+                max_increase,
+            });
+        }
+
+        let Some(programs) = &self.programs else {
+            return;
+        };
+        if programs.contains(&old.owner) {
+            return;
+        }
+        let change = if new.lamports < old.lamports {
+            "lamports were debited"
+        } else if new.owner != old.owner {
+            "the owner changed"
+        } else if new.data != old.data {
+            "data changed"
+        } else {
+            return;
+        };
+        violations.push(TransitionViolation::NotOwnerModified {
+            pubkey: *pubkey,
+            owner: old.owner,
+            change,
+        });
+    }
+}
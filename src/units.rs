@@ -0,0 +1,106 @@
+//! Lamport denominations.
+//!
+//! Balances are counted in lamports, a billionth of a SOL, and a fixture
+//! written as `.balance(2)` when two SOL were meant is off by a factor of
+//! 1e9 without any error. [`Sol`] and the [`sol!`](crate::sol) macro state
+//! the unit explicitly, and APIs that take a balance accept either through
+//! [`IntoLamports`]:
+//!
+//! ```
+//! use solana_accountgen::{AccountBuilder, Sol, sol};
+//!
+//! let whale = AccountBuilder::new().balance(Sol(2)).build();
+//! assert_eq!(whale.lamports, 2_000_000_000);
+//!
+//! let account = AccountBuilder::new().balance(Sol(0.25)).build();
+//! assert_eq!(account.lamports, sol!(0.25));
+//!
+//! // Plain integers are still lamports
+//! let dust = AccountBuilder::new().balance(5_000).build();
+//! assert_eq!(dust.lamports, 5_000);
+//! ```
+
+/// Number of lamports in one SOL.
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// An amount of SOL, either whole (`Sol(2)`) or fractional (`Sol(1.5)`).
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Sol<T = f64>(pub T);
+
+impl Sol<u64> {
+    /// Returns the amount in lamports.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the amount does not fit in a `u64` of lamports.
+    pub const fn lamports(self) -> u64 {
+        match self.0.checked_mul(LAMPORTS_PER_SOL) {
+            Some(lamports) => lamports,
+            None => panic!("SOL amount overflows u64 lamports"),
+        }
+    }
+}
+
+impl Sol<f64> {
+    /// Returns the amount in lamports, rounded to the nearest lamport.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the amount is negative, not finite, or does not fit in a
+    /// `u64` of lamports.
+    pub fn lamports(self) -> u64 {
+        let lamports = (self.0 * LAMPORTS_PER_SOL as f64).round();
+        assert!(
+            lamports.is_finite() && lamports >= 0.0 && lamports < u64::MAX as f64,
+            "SOL amount {} is not a valid lamport balance",
+            self.0
+        );
+        lamports as u64
+    }
+}
+
+/// A balance that can be converted to lamports.
+///
+/// Implemented for plain `u64` lamport amounts and for [`Sol`].
+pub trait IntoLamports {
+    /// Returns the balance in lamports.
+    fn into_lamports(self) -> u64;
+}
+
+impl IntoLamports for u64 {
+    fn into_lamports(self) -> u64 {
+        self
+    }
+}
+
+impl IntoLamports for Sol<u64> {
+    fn into_lamports(self) -> u64 {
+        self.lamports()
+    }
+}
+
+impl IntoLamports for Sol<f64> {
+    fn into_lamports(self) -> u64 {
+        self.lamports()
+    }
+}
+
+/// Converts an amount of SOL to lamports.
+///
+/// Whole amounts are converted in a `const` context.
+///
+/// # Example
+///
+/// ```
+/// use solana_accountgen::sol;
+///
+/// const STAKE: u64 = sol!(10);
+/// assert_eq!(STAKE, 10_000_000_000);
+/// assert_eq!(sol!(0.000_000_001), 1);
+/// ```
+#[macro_export]
+macro_rules! sol {
+    ($amount:expr) => {
+        $crate::units::Sol($amount).lamports()
+    };
+}
@@ -0,0 +1,153 @@
+//! Account update streams.
+//!
+//! [`AccountMap::subscribe`](crate::AccountMap::subscribe) returns an
+//! [`AccountUpdates`] receiver that sees every account set or removed from
+//! then on, in order. Indexers and other pipelines that consume Geyser
+//! plugin output can be fed the same events through
+//! [`AccountUpdate::to_geyser`], so they can be tested against synthetic
+//! streams instead of a validator.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::{AccountBuilder, AccountMap};
+//! use solana_pubkey::Pubkey;
+//!
+//! let mut accounts = AccountMap::new();
+//! let updates = accounts.subscribe();
+//!
+//! let pubkey = Pubkey::new_unique();
+//! accounts.set_account(pubkey, AccountBuilder::new().balance(1_000).build());
+//! accounts.remove_account(&pubkey);
+//!
+//! let events = updates.drain();
+//! assert_eq!(events.len(), 2);
+//! assert!(events[0].account.is_some());
+//! assert!(events[1].account.is_none());
+//! ```
+
+use crate::fixture::record::{encoded_data, pubkey_string};
+use serde::{Deserialize, Serialize};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::Duration;
+
+/// A single change to an account in an [`AccountMap`](crate::AccountMap).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountUpdate {
+    /// The address of the changed account.
+    pub pubkey: Pubkey,
+    /// The account after the change, or `None` if it was removed.
+    pub account: Option<Account>,
+    /// A per-map counter that increases with every change.
+    pub write_version: u64,
+}
+
+impl AccountUpdate {
+    /// Converts the update into the shape of a Geyser plugin account
+    /// notification at `slot`.
+    ///
+    /// Geyser has no removal event: a closed account is reported with zero
+    /// lamports, empty data and the System Program as owner, and so is a
+    /// removed one here.
+    pub fn to_geyser(&self, slot: u64) -> GeyserAccountUpdate {
+        let account = self.account.clone().unwrap_or_default();
+        GeyserAccountUpdate {
+            slot,
+            is_startup: false,
+            account: GeyserAccountInfo {
+                pubkey: self.pubkey,
+                lamports: account.lamports,
+                owner: account.owner,
+                executable: account.executable,
+                rent_epoch: account.rent_epoch,
+                data: account.data,
+                write_version: self.write_version,
+                txn_signature: None,
+            },
+        }
+    }
+}
+
+/// An account notification in the shape of a Geyser plugin's
+/// `update_account` call.
+///
+/// Serializes to camelCase JSON with base64 data, one notification per
+/// message:
+///
+/// ```text
+/// {"slot":1,"isStartup":false,"account":{"pubkey":"...","lamports":1000,"owner":"...","executable":false,"rentEpoch":0,"data":["AQID","base64"],"writeVersion":3,"txnSignature":null}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeyserAccountUpdate {
+    /// The slot the update belongs to.
+    pub slot: u64,
+    /// Whether the update is part of the startup snapshot.
+    pub is_startup: bool,
+    /// The account state after the update.
+    pub account: GeyserAccountInfo,
+}
+
+/// The account part of a [`GeyserAccountUpdate`], mirroring
+/// `ReplicaAccountInfo`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeyserAccountInfo {
+    /// The account address.
+    #[serde(with = "pubkey_string")]
+    pub pubkey: Pubkey,
+    /// The account balance in lamports.
+    pub lamports: u64,
+    /// The program that owns the account.
+    #[serde(with = "pubkey_string")]
+    pub owner: Pubkey,
+    /// Whether the account holds a loaded program.
+    pub executable: bool,
+    /// The epoch at which the account will next owe rent.
+    pub rent_epoch: u64,
+    /// The raw account data.
+    #[serde(with = "encoded_data")]
+    pub data: Vec<u8>,
+    /// The write version of the update.
+    pub write_version: u64,
+    /// The transaction that caused the update, if any.
+    pub txn_signature: Option<String>,
+}
+
+/// The receiving end of an account update subscription.
+///
+/// Updates queue up until they are read. Once the map is dropped, the
+/// blocking methods return `None` after the remaining updates are read.
+#[derive(Debug)]
+pub struct AccountUpdates {
+    receiver: Receiver<AccountUpdate>,
+}
+
+impl AccountUpdates {
+    pub(crate) fn new() -> (Sender<AccountUpdate>, Self) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        (sender, Self { receiver })
+    }
+
+    /// Returns the next queued update without blocking.
+    pub fn try_recv(&self) -> Option<AccountUpdate> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Waits for the next update, returning `None` once the map is dropped.
+    pub fn recv(&self) -> Option<AccountUpdate> {
+        self.receiver.recv().ok()
+    }
+
+    /// Waits up to `timeout` for the next update.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<AccountUpdate> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+
+    /// Returns every queued update without blocking.
+    pub fn drain(&self) -> Vec<AccountUpdate> {
+        self.receiver.try_iter().collect()
+    }
+}
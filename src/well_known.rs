@@ -0,0 +1,138 @@
+//! Addresses of well-known programs and sysvars.
+//!
+//! Tests that build token accounts or invoke the memo program otherwise end
+//! up copy-pasting base58 strings. This module collects the common ones as
+//! constants and provides [`name_for`] to turn an address back into a
+//! readable name, which the [`display`](crate::display) module uses.
+//!
+//! # Example
+//!
+//! ```
+//! use solana_accountgen::well_known::{self, TOKEN_PROGRAM};
+//!
+//! assert_eq!(well_known::name_for(&TOKEN_PROGRAM), Some("Token Program"));
+//! assert_eq!(well_known::pubkey_for("Token Program"), Some(TOKEN_PROGRAM));
+//! ```
+
+use solana_pubkey::{Pubkey, pubkey};
+use solana_sdk_ids::sysvar;
+
+/// The System Program.
+pub const SYSTEM_PROGRAM: Pubkey = solana_sdk_ids::system_program::ID;
+/// The SPL Token program.
+pub const TOKEN_PROGRAM: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+/// The SPL Token-2022 program.
+pub const TOKEN_2022_PROGRAM: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+/// The SPL Associated Token Account program.
+pub const ASSOCIATED_TOKEN_PROGRAM: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+/// The SPL Memo program (v2).
+pub const MEMO_PROGRAM: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+/// The original SPL Memo program (v1).
+pub const MEMO_V1_PROGRAM: Pubkey = pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo");
+/// The SPL Name Service program.
+pub const NAME_SERVICE_PROGRAM: Pubkey = pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+/// The Metaplex Token Metadata program.
+pub const TOKEN_METADATA_PROGRAM: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+/// The mint of wrapped SOL.
+pub const NATIVE_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+/// The SPL Account Compression program, which owns concurrent Merkle trees.
+pub const ACCOUNT_COMPRESSION_PROGRAM: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+/// The SPL Noop program, used by account compression to log changes.
+pub const NOOP_PROGRAM: Pubkey = pubkey!("noopb9bkMVfprPxZ8w8ZtmV9Y2iZnhtHNwdmLoiMKNn");
+/// The Stake program.
+pub const STAKE_PROGRAM: Pubkey = solana_sdk_ids::stake::ID;
+/// The Vote program.
+pub const VOTE_PROGRAM: Pubkey = solana_sdk_ids::vote::ID;
+/// The Config program.
+pub const CONFIG_PROGRAM: Pubkey = solana_sdk_ids::config::ID;
+/// The Compute Budget program.
+pub const COMPUTE_BUDGET_PROGRAM: Pubkey = solana_sdk_ids::compute_budget::ID;
+/// The Address Lookup Table program.
+pub const ADDRESS_LOOKUP_TABLE_PROGRAM: Pubkey = solana_sdk_ids::address_lookup_table::ID;
+/// The Ed25519 signature verification precompile.
+pub const ED25519_PROGRAM: Pubkey = solana_sdk_ids::ed25519_program::ID;
+/// The secp256k1 signature verification precompile.
+pub const SECP256K1_PROGRAM: Pubkey = solana_sdk_ids::secp256k1_program::ID;
+/// The native loader.
+pub const NATIVE_LOADER: Pubkey = solana_sdk_ids::native_loader::ID;
+/// The original, deprecated BPF loader.
+pub const BPF_LOADER_DEPRECATED: Pubkey = solana_sdk_ids::bpf_loader_deprecated::ID;
+/// The BPF loader (v2).
+pub const BPF_LOADER: Pubkey = solana_sdk_ids::bpf_loader::ID;
+/// The upgradeable BPF loader (v3).
+pub const BPF_LOADER_UPGRADEABLE: Pubkey = solana_sdk_ids::bpf_loader_upgradeable::ID;
+/// Loader v4.
+pub const LOADER_V4: Pubkey = solana_sdk_ids::loader_v4::ID;
+/// The incinerator, where lamports are burned.
+pub const INCINERATOR: Pubkey = solana_sdk_ids::incinerator::ID;
+
+/// The loaders that can own executable program accounts.
+pub const LOADERS: &[Pubkey] = &[
+    NATIVE_LOADER,
+    BPF_LOADER_DEPRECATED,
+    BPF_LOADER,
+    BPF_LOADER_UPGRADEABLE,
+    LOADER_V4,
+];
+
+/// Every address in this module with its display name.
+pub const ALL: &[(Pubkey, &str)] = &[
+    (SYSTEM_PROGRAM, "System Program"),
+    (TOKEN_PROGRAM, "Token Program"),
+    (TOKEN_2022_PROGRAM, "Token-2022 Program"),
+    (ASSOCIATED_TOKEN_PROGRAM, "Associated Token Program"),
+    (MEMO_PROGRAM, "Memo Program"),
+    (MEMO_V1_PROGRAM, "Memo Program (v1)"),
+    (NAME_SERVICE_PROGRAM, "Name Service Program"),
+    (TOKEN_METADATA_PROGRAM, "Token Metadata Program"),
+    (NATIVE_MINT, "Wrapped SOL Mint"),
+    (ACCOUNT_COMPRESSION_PROGRAM, "Account Compression Program"),
+    (NOOP_PROGRAM, "Noop Program"),
+    (STAKE_PROGRAM, "Stake Program"),
+    (VOTE_PROGRAM, "Vote Program"),
+    (CONFIG_PROGRAM, "Config Program"),
+    (COMPUTE_BUDGET_PROGRAM, "Compute Budget Program"),
+    (ADDRESS_LOOKUP_TABLE_PROGRAM, "Address Lookup Table Program"),
+    (ED25519_PROGRAM, "Ed25519 Program"),
+    (SECP256K1_PROGRAM, "Secp256k1 Program"),
+    (NATIVE_LOADER, "Native Loader"),
+    (BPF_LOADER_DEPRECATED, "BPF Loader (deprecated)"),
+    (BPF_LOADER, "BPF Loader"),
+    (BPF_LOADER_UPGRADEABLE, "BPF Upgradeable Loader"),
+    (LOADER_V4, "Loader v4"),
+    (INCINERATOR, "Incinerator"),
+    (sysvar::ID, "Sysvar Owner"),
+    (sysvar::clock::ID, "Clock Sysvar"),
+    (sysvar::epoch_rewards::ID, "Epoch Rewards Sysvar"),
+    (sysvar::epoch_schedule::ID, "Epoch Schedule Sysvar"),
+    (sysvar::fees::ID, "Fees Sysvar"),
+    (sysvar::instructions::ID, "Instructions Sysvar"),
+    (sysvar::last_restart_slot::ID, "Last Restart Slot Sysvar"),
+    (sysvar::recent_blockhashes::ID, "Recent Blockhashes Sysvar"),
+    (sysvar::rent::ID, "Rent Sysvar"),
+    (sysvar::rewards::ID, "Rewards Sysvar"),
+    (sysvar::slot_hashes::ID, "Slot Hashes Sysvar"),
+    (sysvar::slot_history::ID, "Slot History Sysvar"),
+    (sysvar::stake_history::ID, "Stake History Sysvar"),
+];
+
+/// Returns the display name of a well-known address.
+pub fn name_for(pubkey: &Pubkey) -> Option<&'static str> {
+    ALL.iter()
+        .find(|(id, _)| id == pubkey)
+        .map(|(_, name)| *name)
+}
+
+/// Returns the address with the given display name.
+pub fn pubkey_for(name: &str) -> Option<Pubkey> {
+    ALL.iter()
+        .find(|(_, candidate)| *candidate == name)
+        .map(|(id, _)| *id)
+}
+
+/// Returns true if `pubkey` is one of the program loaders.
+pub fn is_loader(pubkey: &Pubkey) -> bool {
+    LOADERS.contains(pubkey)
+}
@@ -57,3 +57,217 @@ fn test_create_anchor_instruction() {
     assert_eq!(ix.accounts, accounts);
     assert!(ix.data.len() > 8);
 }
+
+mod space {
+    use borsh::BorshSerialize;
+    use solana_accountgen::AccountGenError;
+    use solana_accountgen::extensions::anchor::{
+        InitSpace, anchor_space_for, create_anchor_account_with_space, get_account_discriminator,
+    };
+    use solana_pubkey::Pubkey;
+    use solana_rent::Rent;
+
+    #[derive(BorshSerialize, InitSpace)]
+    enum Status {
+        Playing { round: u32 },
+        Finished(Pubkey, u64),
+    }
+
+    #[derive(BorshSerialize, InitSpace)]
+    struct Game {
+        players: [Pubkey; 2],
+        status: Status,
+        #[max_len(10)]
+        moves: Vec<u8>,
+        #[max_len(3, 8)]
+        nicknames: Vec<String>,
+        #[max_len(16)]
+        winner_note: Option<String>,
+    }
+
+    #[test]
+    fn test_init_space_matches_anchor_rules() {
+        assert_eq!(Status::INIT_SPACE, 1 + 32 + 8);
+        assert_eq!(<Option<u8>>::INIT_SPACE, 2);
+        assert_eq!(
+            Game::INIT_SPACE,
+            2 * 32 + Status::INIT_SPACE + (4 + 10) + (4 + 3 * (4 + 8)) + (1 + 4 + 16)
+        );
+        assert_eq!(anchor_space_for::<Game>(), 8 + Game::INIT_SPACE);
+        assert_eq!(anchor_space_for::<Option<[u64; 4]>>(), 8 + 1 + 32);
+    }
+
+    #[test]
+    fn test_create_anchor_account_with_space() {
+        let program_id = Pubkey::new_unique();
+        let game = Game {
+            players: [Pubkey::new_unique(), Pubkey::new_unique()],
+            status: Status::Playing { round: 1 },
+            moves: vec![1, 2, 3],
+            nicknames: vec!["ann".to_string()],
+            winner_note: None,
+        };
+        let encoded = borsh::to_vec(&game).unwrap();
+
+        let account = create_anchor_account_with_space("Game", program_id, game).unwrap();
+        assert_eq!(account.data.len(), anchor_space_for::<Game>());
+        assert_eq!(&account.data[..8], &get_account_discriminator("Game"));
+        assert_eq!(&account.data[8..8 + encoded.len()], encoded.as_slice());
+        assert!(account.data[8 + encoded.len()..].iter().all(|b| *b == 0));
+        assert_eq!(
+            account.lamports,
+            Rent::default().minimum_balance(account.data.len())
+        );
+
+        // Far more moves than the program allocates for
+        let too_long = Game {
+            players: [Pubkey::new_unique(); 2],
+            status: Status::Finished(Pubkey::new_unique(), 9),
+            moves: vec![0; 200],
+            nicknames: vec![],
+            winner_note: None,
+        };
+        match create_anchor_account_with_space("Game", program_id, too_long) {
+            Err(AccountGenError::DataTooLarge { max, .. }) => assert_eq!(max, Game::INIT_SPACE),
+            other => panic!("expected DataTooLarge, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_anchor_constraints_report_violations() {
+    use solana_accountgen::AccountBuilder;
+    use solana_accountgen::extensions::anchor::create_anchor_pda;
+    use solana_accountgen::extensions::constraints::{AccountConstraints, ConstraintViolation};
+    use solana_rent::Rent;
+
+    let program_id = Pubkey::new_unique();
+    let seeds: &[&[u8]] = &[b"state"];
+    let (pda, bump, account) = create_anchor_pda(
+        "TestAccount",
+        program_id,
+        seeds,
+        TestAccount { value: 1 },
+        1,
+    )
+    .unwrap();
+    let constraints = AccountConstraints::new(program_id)
+        .account_type::<TestAccount>("TestAccount")
+        .seeds(seeds);
+    assert!(constraints.check(&pda, &account).is_ok());
+
+    // Everything wrong at once is reported at once
+    let report = constraints
+        .clone()
+        .rent_exempt(Rent::default())
+        .check(&Pubkey::new_unique(), &account);
+    assert_eq!(report.violations.len(), 2);
+    assert!(matches!(
+        report.violations[0],
+        ConstraintViolation::RentExempt { actual: 1, .. }
+    ));
+    assert_eq!(
+        report.violations[1],
+        ConstraintViolation::Seeds {
+            expected: Some(pda),
+            actual: report.pubkey,
+        }
+    );
+    let message = report.into_result().unwrap_err().to_string();
+    assert!(message.contains("ConstraintRentExempt") && message.contains("ConstraintSeeds"));
+
+    // Wrong type, truncated data, wrong owner, missing account
+    let other = create_anchor_account("Other", program_id, TestAccount { value: 1 }, 1).unwrap();
+    let truncated = create_anchor_account("TestAccount", program_id, 1u8, 1).unwrap();
+    let foreign = create_anchor_account("TestAccount", Pubkey::new_unique(), 1u64, 1).unwrap();
+    let missing = AccountBuilder::new().balance(0).build();
+    let first = |account| constraints.check(&pda, &account).violations[0].anchor_error();
+    assert_eq!(first(other), "AccountDiscriminatorMismatch");
+    assert_eq!(first(truncated), "AccountDidNotDeserialize");
+    assert_eq!(first(foreign), "AccountOwnedByWrongProgram");
+    assert_eq!(first(missing), "AccountNotInitialized");
+
+    // An explicit bump derives with that bump only
+    let with_bump = constraints.clone().bump(bump);
+    assert!(with_bump.check(&pda, &account).is_ok());
+    let wrong_bump = constraints.bump(bump.wrapping_sub(1));
+    assert!(!wrong_bump.check(&pda, &account).is_ok());
+}
+
+#[test]
+fn test_anchor_accounts_resolves_names_in_order() {
+    use solana_accountgen::anchor_accounts;
+    use solana_accountgen::extensions::scenarios::escrow;
+    use solana_accountgen::well_known::{SYSTEM_PROGRAM, TOKEN_PROGRAM};
+
+    let scenario = escrow(&Pubkey::new_unique(), 100, 50).unwrap();
+    let metas = anchor_accounts!(scenario;
+        maker(mut, signer),
+        maker_token_a(mut),
+        escrow(mut),
+        token_program,
+        system_program,
+    )
+    .unwrap();
+
+    assert_eq!(
+        metas,
+        vec![
+            AccountMeta::new(scenario.pubkey("maker"), true),
+            AccountMeta::new(scenario.pubkey("maker_token_a"), false),
+            AccountMeta::new(scenario.pubkey("escrow"), false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+        ]
+    );
+
+    // Labels win over the well-known fallbacks
+    let custom_token_program = Pubkey::new_unique();
+    let labels = [("token_program", custom_token_program)];
+    let metas = anchor_accounts!(labels; token_program, rent(signer)).unwrap();
+    assert_eq!(metas[0].pubkey, custom_token_program);
+    assert!(metas[1].is_signer && !metas[1].is_writable);
+
+    let err = anchor_accounts!(labels; game(mut), token_program, player).unwrap_err();
+    assert!(err.to_string().contains("\"game\", \"player\""));
+}
+
+#[test]
+fn test_anchor_account_with_enum_state() {
+    use solana_accountgen::serialization::borsh::BorshEnum;
+
+    #[derive(BorshDeserialize, Debug, PartialEq)]
+    enum Phase {
+        Open,
+        Closed { reason: u8 },
+    }
+
+    #[derive(BorshDeserialize, Debug, PartialEq)]
+    enum Auction {
+        Pending,
+        Live {
+            phase: Phase,
+            highest_bidder: Option<Pubkey>,
+            reserve: Option<u64>,
+        },
+    }
+
+    // The program's types are not a dependency; only their layout is known
+    let bidder = Pubkey::new_unique();
+    let state = BorshEnum::variant(1)
+        .field(BorshEnum::variant(1).field(3u8))
+        .some(bidder)
+        .none();
+    let program_id = Pubkey::new_unique();
+    let account = create_anchor_account("Auction", program_id, state, 1_000_000).unwrap();
+
+    let auction: Auction = deserialize_anchor_account(&account).unwrap();
+    assert_eq!(
+        auction,
+        Auction::Live {
+            phase: Phase::Closed { reason: 3 },
+            highest_bidder: Some(bidder),
+            reserve: None,
+        }
+    );
+}
@@ -0,0 +1,108 @@
+use solana_accountgen::anonymize::AnonymizePolicy;
+use solana_accountgen::extensions::token::{create_mint_account, create_token_account};
+use solana_accountgen::keys::test_pubkey;
+use solana_accountgen::well_known::TOKEN_PROGRAM;
+use solana_accountgen::{AccountBuilder, AccountMap};
+use solana_pubkey::Pubkey;
+
+/// A wallet, its token account (at a PDA) and the mint, all cloned from
+/// somewhere real.
+fn cloned_fixtures() -> (AccountMap, Pubkey, Pubkey, Pubkey) {
+    let (wallet, mint) = (test_pubkey("mainnet wallet"), test_pubkey("mainnet mint"));
+    let (token_account, _) =
+        Pubkey::find_program_address(&[wallet.as_ref(), mint.as_ref()], &TOKEN_PROGRAM);
+
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(wallet, AccountBuilder::new().balance(5_000_000))
+        .unwrap();
+    accounts.set_account(
+        mint,
+        create_mint_account(&test_pubkey("mint authority"), 100, 6, &TOKEN_PROGRAM).unwrap(),
+    );
+    accounts.set_account(
+        token_account,
+        create_token_account(&mint, &wallet, 100, &TOKEN_PROGRAM).unwrap(),
+    );
+    (accounts, wallet, mint, token_account)
+}
+
+#[test]
+fn test_anonymize_replaces_wallets_consistently() {
+    let (mut accounts, wallet, mint, token_account) = cloned_fixtures();
+    let authority = test_pubkey("mint authority");
+
+    let mapping = accounts.anonymize(&AnonymizePolicy::new());
+
+    // The wallet and the mint authority; the mint, PDA and program stay
+    assert_eq!(mapping.len(), 2);
+    assert!(mapping.contains_key(&wallet) && mapping.contains_key(&authority));
+    assert_eq!(accounts.len(), 3);
+    assert!(accounts.get_account(&wallet).is_none());
+    assert_eq!(
+        accounts.get_account(&mapping[&wallet]).unwrap().lamports,
+        5_000_000
+    );
+
+    let token_data = &accounts.get_account(&token_account).unwrap().data;
+    assert_eq!(&token_data[..32], mint.as_ref());
+    assert_eq!(&token_data[32..64], mapping[&wallet].as_ref());
+    let mint_data = &accounts.get_account(&mint).unwrap().data;
+    assert_eq!(&mint_data[4..36], mapping[&authority].as_ref());
+
+    // The same fixtures always anonymize the same way
+    let (mut again, ..) = cloned_fixtures();
+    assert_eq!(again.anonymize(&AnonymizePolicy::new()), mapping);
+}
+
+#[test]
+fn test_anonymize_keep_and_rewrite() {
+    let (mut accounts, wallet, mint, token_account) = cloned_fixtures();
+    let authority = test_pubkey("mint authority");
+
+    let policy = AnonymizePolicy::new()
+        .label_prefix("user")
+        .keep([authority])
+        .rewrite([token_account]);
+    let mapping = accounts.anonymize(&policy);
+
+    assert_eq!(mapping.len(), 2);
+    assert!(mapping.contains_key(&wallet));
+    assert!(!mapping.contains_key(&authority));
+    assert!(!mapping.contains_key(&mint));
+    assert!(
+        mapping
+            .values()
+            .all(|pubkey| [test_pubkey("user-0"), test_pubkey("user-1")].contains(pubkey))
+    );
+    assert!(accounts.get_account(&mapping[&token_account]).is_some());
+    assert!(accounts.get_account(&token_account).is_none());
+}
+
+#[test]
+fn test_anonymize_leaves_unknown_data_alone() {
+    let wallet = test_pubkey("depositor");
+    let program_id = Pubkey::new_unique();
+    // Random-looking bytes that are not an address in the set
+    let noise = test_pubkey("not a reference");
+    let mut data = noise.to_bytes().to_vec();
+    data.extend_from_slice(wallet.as_ref());
+
+    let mut accounts = AccountMap::new();
+    let vault = Pubkey::new_unique();
+    accounts
+        .add_with_builder(
+            vault,
+            AccountBuilder::new().owner(program_id).data_raw(data),
+        )
+        .unwrap();
+    accounts
+        .add_with_builder(wallet, AccountBuilder::new())
+        .unwrap();
+
+    let mapping = accounts.anonymize(&AnonymizePolicy::new());
+    assert_eq!(mapping.len(), 1);
+    let data = &accounts.get_account(&vault).unwrap().data;
+    assert_eq!(&data[..32], noise.as_ref());
+    assert_eq!(&data[32..], mapping[&wallet].as_ref());
+}
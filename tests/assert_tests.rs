@@ -0,0 +1,61 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_accountgen::{
+    AccountBuilder, AccountMap, assert_account_eq, assert_account_field_eq, assert_accounts_match,
+};
+use solana_pubkey::Pubkey;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+struct Stats {
+    level: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+struct GameState {
+    name: String,
+    score: u64,
+    stats: Stats,
+}
+
+fn game_account(score: u64) -> solana_account::Account {
+    AccountBuilder::new()
+        .balance(1_000)
+        .data(GameState {
+            name: "alice".to_string(),
+            score,
+            stats: Stats { level: 3 },
+        })
+        .unwrap()
+        .build()
+}
+
+#[test]
+fn test_assert_account_field_eq_nested() {
+    let account = game_account(100);
+    assert_account_field_eq!(account, GameState, score, 100);
+    assert_account_field_eq!(account, GameState, name, "alice");
+    assert_account_field_eq!(account, GameState, stats.level, 3);
+}
+
+#[test]
+#[should_panic(expected = "GameState.score does not match")]
+fn test_assert_account_field_eq_failure() {
+    assert_account_field_eq!(game_account(100), GameState, score, 101);
+}
+
+#[test]
+#[should_panic(expected = "data[")]
+fn test_assert_account_eq_failure_shows_diff() {
+    assert_account_eq!(game_account(1), game_account(2), "score should match");
+}
+
+#[test]
+#[should_panic(expected = "1 missing, 1 unexpected, 0 changed")]
+fn test_assert_accounts_match_failure() {
+    let mut expected = AccountMap::new();
+    expected.set_account(Pubkey::new_unique(), game_account(1));
+
+    let mut actual = AccountMap::new();
+    actual.set_account(Pubkey::new_unique(), game_account(1));
+
+    assert_accounts_match!(actual, expected);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::process_instruction;
+use solana_accountgen::backend::{self, TestBackend};
+use solana_accountgen::extensions::scenarios::escrow;
+use solana_accountgen::harness::LocalHarness;
+use solana_accountgen::{AccountBuilder, AccountGenError, AccountMap};
+use solana_clock::Clock;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_program_test::{ProgramTest, processor};
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::sysvar;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+async fn send<B: TestBackend>(
+    backend: &mut B,
+    payer: &Keypair,
+    instructions: &[Instruction],
+) -> Result<(), AccountGenError> {
+    let blockhash = backend.latest_blockhash().await?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        blockhash,
+    );
+    backend.process_transaction(transaction).await
+}
+
+/// The same suite, whatever the backend.
+async fn run_suite<B: TestBackend>(backend: &mut B, program_id: Pubkey) {
+    let (payer, counter) = (Keypair::new(), Pubkey::new_unique());
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(payer.pubkey(), AccountBuilder::new().balance(1_000_000_000))
+        .unwrap();
+    accounts
+        .add_with_builder(
+            counter,
+            AccountBuilder::new()
+                .balance(1_000_000)
+                .owner(program_id)
+                .data_raw(vec![0]),
+        )
+        .unwrap();
+    backend::load_accounts(backend, &accounts).unwrap();
+
+    let add = |amount: u8| {
+        Instruction::new_with_bytes(
+            program_id,
+            &[amount],
+            vec![AccountMeta::new(counter, false)],
+        )
+    };
+    send(backend, &payer, &[add(2), add(3)]).await.unwrap();
+
+    let diff = backend::diff_since(backend, &accounts).await.unwrap();
+    assert!(diff.changed.iter().any(|(pubkey, _)| *pubkey == counter));
+    let after = backend::snapshot(backend, [counter]).await.unwrap();
+    assert_eq!(after.get_account(&counter).unwrap().data, vec![5]);
+
+    // A failing instruction rolls back the whole transaction
+    let result = send(backend, &payer, &[add(1), add(0)]).await;
+    assert!(matches!(
+        result,
+        Err(AccountGenError::TransactionFailed(
+            TransactionError::InstructionError(1, _)
+        ))
+    ));
+    backend::assert_accounts(backend, &after).await;
+}
+
+#[tokio::test]
+async fn test_suite_on_local_harness() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = LocalHarness::new(program_id, process_instruction);
+    run_suite(&mut harness, program_id).await;
+
+    harness.warp(100).unwrap();
+    let clock = harness.get_account(&sysvar::clock::id()).unwrap();
+    let clock: Clock = bincode::deserialize(&clock.data).unwrap();
+    assert_eq!(clock.slot, 100);
+    assert!(harness.warp(10).is_err());
+}
+
+#[tokio::test]
+async fn test_suite_on_program_test() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("counter", program_id, processor!(process_instruction));
+    let mut context = program_test.start_with_context().await;
+    run_suite(&mut context, program_id).await;
+}
+
+#[cfg(feature = "litesvm")]
+#[tokio::test]
+async fn test_suite_on_litesvm() {
+    let program_id = Pubkey::new_unique();
+    let mut svm = litesvm::LiteSVM::new();
+    // Coerce through a closure so the entrypoint is generic over the VM's
+    // lifetime, as `add_builtin` requires
+    svm.add_builtin(program_id, |vm, a0, a1, a2, a3, a4| {
+        processor!(process_instruction).unwrap()(vm, a0, a1, a2, a3, a4)
+    });
+    // `add_builtin` leaves the program owned by the BPF loader, which does
+    // not dispatch to builtins; the runtime only does so for native programs
+    let program = solana_account::Account {
+        lamports: 1,
+        data: b"counter".to_vec(),
+        owner: solana_sdk_ids::native_loader::id(),
+        executable: true,
+        rent_epoch: 0,
+    };
+    TestBackend::set_account(&mut svm, program_id, program).unwrap();
+    run_suite(&mut svm, program_id).await;
+
+    svm.warp(100).unwrap();
+    assert_eq!(svm.get_sysvar::<Clock>().slot, 100);
+    assert!(svm.warp(10).is_err());
+}
+
+#[tokio::test]
+async fn test_scenario_loads_into_backend() {
+    let program_id = Pubkey::new_unique();
+    let scenario = escrow(&program_id, 1_000, 500).unwrap();
+    let mut harness = LocalHarness::new(program_id, process_instruction);
+    scenario.load_into(&mut harness).unwrap();
+
+    backend::assert_accounts(&mut harness, &scenario.accounts).await;
+    let vault = harness.get_account(&scenario.pubkey("vault"));
+    assert!(vault.is_some());
+}
@@ -0,0 +1,120 @@
+use solana_accountgen::extensions::banks_client::{BanksClientExt, RetryPolicy};
+use solana_accountgen::extensions::program_test::ProgramTestExt;
+use solana_accountgen::keys::test_pubkey;
+use solana_accountgen::tracking::track_pubkeys;
+use solana_accountgen::{AccountBuilder, AccountGenError};
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+
+#[tokio::test]
+async fn test_get_accounts_by_owner_sweeps_known_pubkeys() {
+    let tracking = track_pubkeys();
+    let program_id = Pubkey::new_unique();
+    let other_program = Pubkey::new_unique();
+    let (vault, config, foreign, missing) = (
+        test_pubkey("vault"),
+        test_pubkey("config"),
+        test_pubkey("foreign"),
+        test_pubkey("missing"),
+    );
+    let unknown = Pubkey::new_unique();
+
+    let mut program_test = ProgramTest::default();
+    for (pubkey, owner, lamports) in [
+        (vault, program_id, 5_000_000),
+        (config, program_id, 1_000_000),
+        (foreign, other_program, 1_000_000),
+    ] {
+        program_test
+            .add_account_with_builder(pubkey, AccountBuilder::new().balance(lamports).owner(owner))
+            .unwrap();
+    }
+    // Added without the crate, so tracking never sees it
+    program_test.add_account(
+        unknown,
+        AccountBuilder::new()
+            .balance(1_000_000)
+            .owner(program_id)
+            .build(),
+    );
+    let (mut banks_client, _, _) = program_test.start().await;
+
+    let known = tracking.pubkeys();
+    assert!(known.contains(&missing));
+    let accounts = banks_client
+        .get_accounts_by_owner(&program_id, known.iter().chain(&[vault]).copied())
+        .await
+        .unwrap();
+
+    assert_eq!(accounts.len(), 2);
+    assert_eq!(accounts.get_account(&vault).unwrap().lamports, 5_000_000);
+    assert!(accounts.get_account(&config).is_some());
+    assert!(accounts.get_account(&foreign).is_none());
+    // Not passed in, so not found even though the program owns it
+    assert!(accounts.get_account(&unknown).is_none());
+}
+
+#[tokio::test]
+async fn test_get_new_latest_blockhash_retry_policy() {
+    let (mut banks_client, _payer, blockhash) = ProgramTest::default().start().await;
+
+    // Any other blockhash is returned without retrying
+    let stale = solana_hash::Hash::new_unique();
+    let policy = RetryPolicy::new().max_retries(0);
+    let latest = banks_client
+        .get_new_latest_blockhash_with_policy(&stale, &policy)
+        .await
+        .unwrap();
+    assert_eq!(latest, blockhash);
+
+    // A single attempt gives up unless the bank advances in between
+    let latest = banks_client.get_latest_blockhash().await.unwrap();
+    let policy = RetryPolicy::new().max_retries(0);
+    let error = banks_client
+        .get_new_latest_blockhash_with_policy(&latest, &policy)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains(&latest.to_string()));
+    let AccountGenError::Timeout(timeout) = error else {
+        panic!("unexpected error: {}", error);
+    };
+    assert_eq!(timeout.retries, 0);
+
+    // The bank advances in the background, so the default policy finds a
+    // new blockhash
+    let new = banks_client
+        .get_new_latest_blockhash(&latest)
+        .await
+        .unwrap();
+    assert_ne!(new, latest);
+}
+
+#[tokio::test]
+async fn test_process_transaction_with_preflight_errors() {
+    use solana_instruction::error::InstructionError;
+    use solana_signer::Signer;
+    use solana_transaction::Transaction;
+    use solana_transaction_error::TransactionError;
+
+    let (mut banks_client, payer, blockhash) = ProgramTest::default().start().await;
+    let recipient = Pubkey::new_unique();
+
+    // Transferring more than the payer holds fails in the System Program
+    let balance = banks_client.get_balance(payer.pubkey()).await.unwrap();
+    let transfer =
+        solana_accountgen::instructions::system::transfer(&payer.pubkey(), &recipient, balance + 1);
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    // BanksClient has an inherent method of the same name
+    match BanksClientExt::process_transaction_with_preflight(&mut banks_client, transaction).await {
+        Err(AccountGenError::TransactionFailed(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(1),
+        ))) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -77,3 +77,907 @@ fn test_cli_base64_output_with_data() {
     assert_eq!(decoded_account.executable, false);
     assert_eq!(decoded_account.data, vec![1, 2, 3, 4, 5]); // check data
 }
+
+#[test]
+fn test_cli_inspect_fixture() {
+    use solana_accountgen::{AccountBuilder, AccountMap, fixture::save_account_map};
+
+    let pubkey = Pubkey::new_unique();
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(
+            pubkey,
+            AccountBuilder::new()
+                .balance(2_500_000_000)
+                .data_raw(b"fixture".to_vec()),
+        )
+        .unwrap();
+
+    let path = std::env::temp_dir().join(format!(
+        "solana-accountgen-inspect-{}.json",
+        std::process::id()
+    ));
+    save_account_map(&path, &accounts).unwrap();
+
+    let mut cmd = Command::cargo_bin("solana-accountgen").unwrap();
+    let output = cmd
+        .arg("inspect")
+        .arg(&path)
+        .output()
+        .expect("Failed to execute command");
+    std::fs::remove_file(&path).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains(&format!("Account {}", pubkey)));
+    assert!(stdout.contains("2.500000000 SOL"));
+    assert!(stdout.contains("System Program"));
+    assert!(stdout.contains("|fixture|"));
+}
+
+#[test]
+fn test_cli_inspect_with_schema() {
+    use solana_accountgen::{AccountBuilder, AccountMap, fixture::save_account_map};
+
+    let pubkey = Pubkey::new_unique();
+    let mut data = 42u64.to_le_bytes().to_vec();
+    data.push(1);
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(pubkey, AccountBuilder::new().data_raw(data))
+        .unwrap();
+
+    let dir = std::env::temp_dir();
+    let fixture_path = dir.join(format!(
+        "solana-accountgen-inspect-schema-{}.json",
+        std::process::id()
+    ));
+    let schema_path = dir.join(format!(
+        "solana-accountgen-schema-{}.json",
+        std::process::id()
+    ));
+    save_account_map(&fixture_path, &accounts).unwrap();
+    let schema = serde_json::json!({
+        "version": 1,
+        "types": ["Counter"],
+        "definitions": {
+            "Counter": {"kind": "struct", "fields": [
+                {"name": "count", "type": "u64"},
+                {"name": "active", "type": "bool"}
+            ]},
+            "u64": {"kind": "primitive", "size": 8},
+            "bool": {"kind": "primitive", "size": 1}
+        }
+    });
+    std::fs::write(&schema_path, schema.to_string()).unwrap();
+
+    let mut cmd = Command::cargo_bin("solana-accountgen").unwrap();
+    let output = cmd
+        .arg("inspect")
+        .arg(&fixture_path)
+        .arg("--schema")
+        .arg(&schema_path)
+        .arg("--type")
+        .arg("Counter")
+        .output()
+        .expect("Failed to execute command");
+    std::fs::remove_file(&fixture_path).unwrap();
+    std::fs::remove_file(&schema_path).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains(&format!("{} as Counter:", pubkey)));
+    assert!(stdout.contains("\"count\": 42"));
+    assert!(stdout.contains("\"active\": true"));
+}
+
+#[test]
+fn test_cli_data_encoding() {
+    let owner = "11111111111111111111111111111111";
+
+    for (data, encoding) in [("Ldp", "base58"), ("AQID", "base64"), ("010203", "hex")] {
+        let mut cmd = Command::cargo_bin("solana-accountgen").unwrap();
+        let output = cmd
+            .arg("generate")
+            .arg("--owner")
+            .arg(owner)
+            .arg("--data")
+            .arg(data)
+            .arg("--data-encoding")
+            .arg(encoding)
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(output.status.success(), "{encoding}");
+        let account: Account = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(account.data, vec![1, 2, 3], "{encoding}");
+    }
+
+    let mut cmd = Command::cargo_bin("solana-accountgen").unwrap();
+    cmd.arg("generate")
+        .arg("--owner")
+        .arg(owner)
+        .arg("--data")
+        .arg("0OIl")
+        .arg("--data-encoding")
+        .arg("base58")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid base58 data"));
+}
+
+#[test]
+fn test_cli_generate_executable_checks() {
+    let owner = "11111111111111111111111111111111";
+
+    let mut cmd = Command::cargo_bin("solana-accountgen").unwrap();
+    cmd.arg("generate")
+        .arg("--owner")
+        .arg(owner)
+        .arg("--executable")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not a loader"));
+
+    let mut cmd = Command::cargo_bin("solana-accountgen").unwrap();
+    let output = cmd
+        .arg("generate")
+        .arg("--owner")
+        .arg(owner)
+        .arg("--executable")
+        .arg("--skip-executable-checks")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let account: Account = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(account.executable);
+}
+
+#[test]
+fn test_cli_inspect_decodes_instructions() {
+    use solana_accountgen::extensions::anchor::get_method_discriminator;
+    use solana_accountgen::fixture::{InstructionSet, save_scenario};
+    use solana_accountgen::{AccountMap, well_known::SYSTEM_PROGRAM};
+    use solana_instruction::{AccountMeta, Instruction};
+
+    let program_id = Pubkey::new_unique();
+    let mut instructions = InstructionSet::new();
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&7_000u64.to_le_bytes());
+    instructions.push(Instruction::new_with_bytes(
+        SYSTEM_PROGRAM,
+        &data,
+        vec![
+            AccountMeta::new(Pubkey::new_unique(), true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ],
+    ));
+    let mut data = get_method_discriminator("bump").to_vec();
+    data.push(3);
+    instructions.push(Instruction::new_with_bytes(program_id, &data, vec![]));
+
+    let dir = std::env::temp_dir();
+    let fixture_path = dir.join(format!(
+        "solana-accountgen-inspect-instructions-{}.json",
+        std::process::id()
+    ));
+    let idl_path = dir.join(format!(
+        "solana-accountgen-inspect-idl-{}.json",
+        std::process::id()
+    ));
+    save_scenario(&fixture_path, &AccountMap::new(), &instructions).unwrap();
+    let idl = serde_json::json!({
+        "address": program_id.to_string(),
+        "metadata": { "name": "counter" },
+        "instructions": [{
+            "name": "bump",
+            "discriminator": get_method_discriminator("bump"),
+            "accounts": [],
+            "args": [{ "name": "by", "type": "u8" }]
+        }]
+    });
+    std::fs::write(&idl_path, idl.to_string()).unwrap();
+
+    let mut cmd = Command::cargo_bin("solana-accountgen").unwrap();
+    let output = cmd
+        .arg("inspect")
+        .arg(&fixture_path)
+        .arg("--idl")
+        .arg(&idl_path)
+        .output()
+        .expect("Failed to execute command");
+    std::fs::remove_file(&fixture_path).unwrap();
+    std::fs::remove_file(&idl_path).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(stdout.contains("Instruction #0\n  System Program: transfer\n"));
+    assert!(stdout.contains("args: {\"lamports\":7000}"));
+    assert!(stdout.contains("Instruction #1\n  counter: bump\n    args: {\"by\":3}\n"));
+}
+
+#[test]
+fn test_cli_diff_shows_changed_instructions() {
+    use solana_accountgen::fixture::{InstructionSet, save_scenario};
+    use solana_accountgen::{AccountMap, well_known::MEMO_PROGRAM};
+    use solana_instruction::Instruction;
+
+    let dir = std::env::temp_dir();
+    let mut paths = Vec::new();
+    for memo in ["before", "after"] {
+        let mut instructions = InstructionSet::new();
+        instructions.push(Instruction::new_with_bytes(
+            MEMO_PROGRAM,
+            memo.as_bytes(),
+            vec![],
+        ));
+        let path = dir.join(format!(
+            "solana-accountgen-diff-{}-{}.json",
+            memo,
+            std::process::id()
+        ));
+        save_scenario(&path, &AccountMap::new(), &instructions).unwrap();
+        paths.push(path);
+    }
+
+    let mut cmd = Command::cargo_bin("solana-accountgen").unwrap();
+    let output = cmd
+        .arg("diff")
+        .arg(&paths[0])
+        .arg(&paths[1])
+        .output()
+        .expect("Failed to execute command");
+    for path in &paths {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stdout.contains(
+            "- Instruction #0\n    Memo Program: memo\n      args: {\"memo\":\"before\"}\n"
+        )
+    );
+    assert!(
+        stdout.contains(
+            "+ Instruction #0\n    Memo Program: memo\n      args: {\"memo\":\"after\"}\n"
+        )
+    );
+}
+
+#[test]
+fn test_cli_diff_names_regions() {
+    use solana_accountgen::fixture::{InstructionSet, save_scenario};
+    use solana_accountgen::{AccountBuilder, AccountMap};
+
+    let dir = std::env::temp_dir();
+    let program_id = Pubkey::new_unique();
+    let pubkey = Pubkey::new_unique();
+    let mut paths = Vec::new();
+    for seq in [5u32, 6] {
+        let mut accounts = AccountMap::new();
+        let mut data = vec![0; 8];
+        data.extend_from_slice(&seq.to_le_bytes());
+        accounts
+            .add_with_builder(
+                pubkey,
+                AccountBuilder::new().owner(program_id).data_raw(data),
+            )
+            .unwrap();
+        let path = dir.join(format!(
+            "solana-accountgen-regions-{}-{}.json",
+            seq,
+            std::process::id()
+        ));
+        save_scenario(&path, &accounts, &InstructionSet::new()).unwrap();
+        paths.push(path);
+    }
+    let regions_path = dir.join(format!(
+        "solana-accountgen-regions-map-{}.json",
+        std::process::id()
+    ));
+    std::fs::write(
+        &regions_path,
+        format!(
+            r#"{{ "{}": [
+                {{ "name": "discriminator", "offset": 0, "format": {{ "bytes": 8 }} }},
+                {{ "name": "header.seq", "offset": 8, "format": "u32" }}
+            ] }}"#,
+            program_id
+        ),
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .arg("diff")
+        .arg(&paths[0])
+        .arg(&paths[1])
+        .arg("--regions")
+        .arg(&regions_path)
+        .output()
+        .expect("Failed to execute command");
+    let inspect = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .arg("inspect")
+        .arg(&paths[1])
+        .arg("--regions")
+        .arg(&regions_path)
+        .output()
+        .expect("Failed to execute command");
+    for path in paths.iter().chain([&regions_path]) {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout, format!("~ {}\n    header.seq: 5 -> 6\n", pubkey));
+
+    let stdout = String::from_utf8(inspect.stdout).unwrap();
+    assert!(inspect.status.success());
+    assert!(stdout.contains(&format!(
+        "{}:\n  discriminator: 0000000000000000\n  header.seq: 6\n",
+        pubkey
+    )));
+}
+
+#[test]
+fn test_cli_anchor_identify() {
+    use solana_accountgen::extensions::anchor::{
+        get_account_discriminator, get_method_discriminator,
+    };
+
+    let dir = std::env::temp_dir();
+    let idl_path = dir.join(format!(
+        "solana-accountgen-identify-idl-{}.json",
+        std::process::id()
+    ));
+    let words_path = dir.join(format!(
+        "solana-accountgen-identify-words-{}.txt",
+        std::process::id()
+    ));
+    let idl = serde_json::json!({
+        "metadata": { "name": "vault" },
+        "accounts": [{ "name": "Vault", "discriminator": get_account_discriminator("Vault") }],
+        "instructions": [{ "name": "deposit", "discriminator": get_method_discriminator("deposit") }]
+    });
+    std::fs::write(&idl_path, idl.to_string()).unwrap();
+    std::fs::write(&words_path, "withdraw\nclose_vault\n").unwrap();
+
+    let identify = |data: String| {
+        Command::cargo_bin("solana-accountgen")
+            .unwrap()
+            .args(["anchor", "identify", "--data", &data, "--idl"])
+            .arg(&idl_path)
+            .arg("--words")
+            .arg(&words_path)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    // Account data longer than the discriminator is cut to 8 bytes
+    let account = format!("{}ff00", hex::encode(get_account_discriminator("Vault")));
+    let output = identify(account);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "account Vault (vault)\n"
+    );
+
+    let output = identify(hex::encode(get_method_discriminator("close_vault")));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "instruction close_vault\n"
+    );
+
+    let output = identify("0000000000000000".to_string());
+    std::fs::remove_file(&idl_path).unwrap();
+    std::fs::remove_file(&words_path).unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("No name matches")
+    );
+}
+
+#[test]
+fn test_cli_fixtures_merge_and_filter() {
+    use solana_accountgen::fixture::{InstructionSet, load_scenario, save_scenario};
+    use solana_accountgen::{AccountBuilder, AccountMap, well_known::MEMO_PROGRAM};
+    use solana_instruction::Instruction;
+
+    let program_id = Pubkey::new_unique();
+    let shared = Pubkey::new_unique();
+    let (rich, poor, wallet) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+
+    let dir = std::env::temp_dir();
+    let path = |name: &str| {
+        dir.join(format!(
+            "solana-accountgen-fixtures-{}-{}.json",
+            name,
+            std::process::id()
+        ))
+    };
+    let (a, b, merged, filtered) = (path("a"), path("b"), path("merged"), path("filtered"));
+
+    let mut first = AccountMap::new();
+    first
+        .add_with_builder(shared, AccountBuilder::new().balance(1).owner(program_id))
+        .unwrap();
+    first
+        .add_with_builder(rich, AccountBuilder::new().balance(5_000).owner(program_id))
+        .unwrap();
+    let mut instructions = InstructionSet::new();
+    instructions.push(Instruction::new_with_bytes(MEMO_PROGRAM, b"a", vec![]));
+    save_scenario(&a, &first, &instructions).unwrap();
+
+    let mut second = AccountMap::new();
+    second
+        .add_with_builder(
+            shared,
+            AccountBuilder::new().balance(2_000).owner(program_id),
+        )
+        .unwrap();
+    second
+        .add_with_builder(poor, AccountBuilder::new().balance(10).owner(program_id))
+        .unwrap();
+    second
+        .add_with_builder(
+            wallet,
+            AccountBuilder::new()
+                .balance(9_000)
+                .owner(Pubkey::default()),
+        )
+        .unwrap();
+    save_scenario(&b, &second, &InstructionSet::new()).unwrap();
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["fixtures", "merge"])
+        .args([&a, &b])
+        .arg("-o")
+        .arg(&merged)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("1 accounts were replaced by later files")
+    );
+    let (accounts, instructions) = load_scenario(&merged).unwrap();
+    assert_eq!(accounts.len(), 4);
+    assert_eq!(accounts.get_account(&shared).unwrap().lamports, 2_000);
+    assert_eq!(instructions.len(), 1);
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["fixtures", "filter"])
+        .arg(&merged)
+        .args([
+            "--owner",
+            &program_id.to_string(),
+            "--min-lamports",
+            "1000",
+            "-o",
+        ])
+        .arg(&filtered)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let (accounts, instructions) = load_scenario(&filtered).unwrap();
+    for path in [&a, &b, &merged, &filtered] {
+        std::fs::remove_file(path).unwrap();
+    }
+    let mut kept: Vec<_> = accounts.iter().map(|(pubkey, _)| *pubkey).collect();
+    kept.sort();
+    let mut expected = vec![shared, rich];
+    expected.sort();
+    assert_eq!(kept, expected);
+    assert_eq!(instructions.len(), 1);
+}
+
+#[test]
+fn test_cli_serve() {
+    use serde_json::{Value, json};
+    use solana_accountgen::fixture::save_account_map;
+    use solana_accountgen::{AccountBuilder, AccountMap};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::process::Stdio;
+
+    let pubkey = Pubkey::new_unique();
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(pubkey, AccountBuilder::new().balance(4_200))
+        .unwrap();
+    let path = std::env::temp_dir().join(format!(
+        "solana-accountgen-serve-{}.json",
+        std::process::id()
+    ));
+    save_account_map(&path, &accounts).unwrap();
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("solana-accountgen"))
+        .arg("serve")
+        .arg(&path)
+        .args(["--port", "0", "--slot", "7"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+
+    let mut banner = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut banner)
+        .unwrap();
+    assert!(banner.starts_with("Serving 1 accounts from "));
+    let addr = banner.trim().rsplit("http://").next().unwrap().to_string();
+
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getAccountInfo",
+        "params": [pubkey.to_string()],
+    })
+    .to_string();
+    let mut stream = TcpStream::connect(&addr).unwrap();
+    write!(
+        stream,
+        "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    let response: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(response["result"]["context"]["slot"], 7);
+    assert_eq!(response["result"]["value"]["lamports"], 4_200);
+}
+
+#[test]
+fn test_cli_address_and_rent() {
+    use serde_json::Value;
+
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["pda", "--program-id", &program_id.to_string()])
+        .args(["--seed", "utf8:vault", "--seed"])
+        .arg(format!("pubkey:{}", authority))
+        .args(["--seed", "u64:7"])
+        .output()
+        .expect("Failed to execute command");
+    let (address, bump) = Pubkey::find_program_address(
+        &[b"vault", authority.as_ref(), &7u64.to_le_bytes()],
+        &program_id,
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("{} (bump {})\n", address, bump)
+    );
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["--output", "json", "derive", "--seed", "stake:0"])
+        .args(["--base", &authority.to_string()])
+        .args(["--owner", &program_id.to_string()])
+        .output()
+        .expect("Failed to execute command");
+    let derived: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        derived["address"],
+        Pubkey::create_with_seed(&authority, "stake:0", &program_id)
+            .unwrap()
+            .to_string()
+    );
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["rent", "--size", "165", "--output", "json"])
+        .output()
+        .expect("Failed to execute command");
+    let rent: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(rent["lamports"], 2_039_280);
+    assert_eq!(rent["sol"], "0.002039280");
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["pda", "--program-id", &program_id.to_string()])
+        .args(["--seed", "u8:256"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("invalid u8 seed")
+    );
+}
+
+#[test]
+fn test_cli_json_output() {
+    use serde_json::Value;
+    use solana_accountgen::fixture::{InstructionSet, save_scenario};
+    use solana_accountgen::{AccountBuilder, AccountMap, well_known::MEMO_PROGRAM};
+    use solana_instruction::Instruction;
+
+    let (kept, changed) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let dir = std::env::temp_dir();
+    let path = |name: &str| {
+        dir.join(format!(
+            "solana-accountgen-json-{}-{}.json",
+            name,
+            std::process::id()
+        ))
+    };
+    let (old, new, merged) = (path("old"), path("new"), path("merged"));
+
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(kept, AccountBuilder::new().balance(1_000))
+        .unwrap();
+    accounts
+        .add_with_builder(changed, AccountBuilder::new().balance(5))
+        .unwrap();
+    let mut instructions = InstructionSet::new();
+    instructions.push(Instruction::new_with_bytes(MEMO_PROGRAM, b"hi", vec![]));
+    save_scenario(&old, &accounts, &instructions).unwrap();
+    accounts.get_account_mut(&changed).unwrap().lamports = 6;
+    save_scenario(&new, &accounts, &instructions).unwrap();
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["--output", "json", "inspect"])
+        .arg(&old)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let inspected: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(inspected["accounts"].as_array().unwrap().len(), 2);
+    assert_eq!(inspected["instructions"][0]["args"]["memo"], "hi");
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .arg("diff")
+        .args([&old, &new])
+        .args(["--output", "json"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(1));
+    let diffed: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(diffed["changed"][0]["pubkey"], changed.to_string());
+    assert_eq!(diffed["changed"][0]["lamports"], serde_json::json!([5, 6]));
+    assert!(diffed["added"].as_array().unwrap().is_empty());
+    assert!(diffed["instructions"].as_array().unwrap().is_empty());
+
+    // The global flag reaches nested subcommands
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["fixtures", "merge"])
+        .args([&old, &new])
+        .arg("-o")
+        .arg(&merged)
+        .args(["--output", "json"])
+        .output()
+        .expect("Failed to execute command");
+    for path in [&old, &new, &merged] {
+        std::fs::remove_file(path).unwrap();
+    }
+    assert!(output.status.success());
+    let summary: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["accounts"], 2);
+    assert_eq!(summary["replaced"], 2);
+    assert_eq!(summary["instructions"], 2);
+}
+
+#[test]
+fn test_cli_json_error() {
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["--output", "json", "generate", "--owner", "not-a-pubkey"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    let error: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("Invalid"));
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .args(["generate", "--owner", "not-a-pubkey"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .starts_with("Error: ")
+    );
+}
+
+#[test]
+fn test_cli_completions() {
+    for (shell, expected) in [
+        ("bash", "complete -F _solana__accountgen"),
+        ("zsh", "#compdef solana-accountgen"),
+        ("fish", "complete -c solana-accountgen"),
+        ("elvish", "edit:completion:arg-completer[solana-accountgen]"),
+        ("powershell", "Register-ArgumentCompleter"),
+    ] {
+        let output = Command::cargo_bin("solana-accountgen")
+            .unwrap()
+            .args(["completions", shell])
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        let script = String::from_utf8(output.stdout).unwrap();
+        assert!(script.contains(expected), "{shell} script: {script}");
+        assert!(script.contains("merge"), "{shell} script lacks subcommands");
+    }
+}
+
+#[test]
+fn test_cli_run_scenario() {
+    use solana_accountgen::runner::ScenarioRunner;
+    use solana_accountgen::{AccountBuilder, AccountMap};
+    use solana_keypair::Keypair;
+    use solana_program::system_instruction;
+    use solana_signer::Signer;
+
+    let dir = std::env::temp_dir();
+    let payer_path = dir.join(format!(
+        "solana-accountgen-run-payer-{}.json",
+        std::process::id()
+    ));
+    let passing_path = dir.join(format!(
+        "solana-accountgen-run-pass-{}.json",
+        std::process::id()
+    ));
+    let failing_path = dir.join(format!(
+        "solana-accountgen-run-fail-{}.json",
+        std::process::id()
+    ));
+
+    let (payer, recipient) = (Keypair::new(), Pubkey::new_unique());
+    std::fs::write(&payer_path, format!("{:?}", payer.to_bytes())).unwrap();
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(recipient, AccountBuilder::new().balance(1_000_000_000))
+        .unwrap();
+    let transfer = system_instruction::transfer(&payer.pubkey(), &recipient, 5_000);
+    let scenario = ScenarioRunner::new()
+        .set_accounts(accounts)
+        .checkpoint("funded")
+        .send(vec![transfer]);
+    let passing = scenario
+        .clone()
+        .assert_changed("funded", &[payer.pubkey(), recipient]);
+    std::fs::write(&passing_path, passing.to_json()).unwrap();
+    let failing = scenario.assert_changed("funded", &[recipient]);
+    std::fs::write(&failing_path, failing.to_json()).unwrap();
+
+    let run = |path: &std::path::Path| {
+        Command::cargo_bin("solana-accountgen")
+            .unwrap()
+            .arg("run")
+            .arg(path)
+            .arg("--payer")
+            .arg(&payer_path)
+            .output()
+            .expect("Failed to execute command")
+    };
+    let passed = run(&passing_path);
+    let failed = run(&failing_path);
+    let missing_program = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .arg("run")
+        .arg(&passing_path)
+        .arg("--program")
+        .arg(dir.join("solana-accountgen-missing-program.so"))
+        .arg("--program-id")
+        .arg(Pubkey::new_unique().to_string())
+        .output()
+        .expect("Failed to execute command");
+    for path in [&payer_path, &passing_path, &failing_path] {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    let stdout = String::from_utf8(passed.stdout).unwrap();
+    assert!(passed.status.success());
+    assert!(stdout.contains(&format!("with payer {}", payer.pubkey())));
+    assert!(stdout.contains("Passed 4 steps\n  checkpoint \"funded\""));
+
+    let stderr = String::from_utf8(failed.stderr).unwrap();
+    assert_eq!(failed.status.code(), Some(1));
+    assert!(stderr.contains("scenario failed at step 4"));
+    assert!(stderr.contains(&format!("but changed were {}", {
+        let mut changed = [payer.pubkey(), recipient];
+        changed.sort();
+        format!("{}, {}", changed[0], changed[1])
+    })));
+
+    let stderr = String::from_utf8(missing_program.stderr).unwrap();
+    assert!(!missing_program.status.success());
+    assert!(stderr.contains("cannot read program"));
+}
+
+#[test]
+fn test_cli_run_yaml_scenario() {
+    use solana_keypair::Keypair;
+    use solana_signer::Signer;
+
+    let dir = std::env::temp_dir();
+    let payer_path = dir.join(format!(
+        "solana-accountgen-run-yaml-payer-{}.json",
+        std::process::id()
+    ));
+    let scenario_path = dir.join(format!("solana-accountgen-run-{}.yaml", std::process::id()));
+
+    let (payer, recipient) = (Keypair::new(), Pubkey::new_unique());
+    std::fs::write(&payer_path, format!("{:?}", payer.to_bytes())).unwrap();
+    let scenario = format!(
+        r#"# Fund a recipient, then pay it 5,000 lamports
+- step: setAccounts
+  accounts:
+    {recipient}:
+      lamports: 1000000000
+      owner: "11111111111111111111111111111111"
+      data: ["", base64]
+      executable: false
+      rentEpoch: 0
+- step: checkpoint
+  name: funded
+- step: send
+  instructions:
+    - programId: "11111111111111111111111111111111"
+      accounts:
+        - pubkey: {payer}
+          isSigner: true
+          isWritable: true
+        - pubkey: {recipient}
+          isSigner: false
+          isWritable: true
+      # SystemInstruction::Transfer {{ lamports: 5000 }}
+      data: [AgAAAIgTAAAAAAAA, base64]
+- step: assertChanged
+  since: funded
+  accounts:
+    - {payer}
+    - {recipient}
+"#,
+        payer = payer.pubkey(),
+    );
+    std::fs::write(&scenario_path, scenario).unwrap();
+
+    let output = Command::cargo_bin("solana-accountgen")
+        .unwrap()
+        .arg("run")
+        .arg(&scenario_path)
+        .arg("--payer")
+        .arg(&payer_path)
+        .output()
+        .expect("Failed to execute command");
+    for path in [&payer_path, &scenario_path] {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("Passed 4 steps\n  checkpoint \"funded\""));
+}
@@ -0,0 +1,12 @@
+use solana_account_info::AccountInfo;
+use solana_program_error::{ProgramError, ProgramResult};
+use solana_pubkey::Pubkey;
+
+/// Test program: adds the data byte to account 0's first byte, failing on 0.
+pub fn process_instruction(_: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data[0] == 0 {
+        return Err(ProgramError::Custom(7));
+    }
+    accounts[0].try_borrow_mut_data()?[0] += data[0];
+    Ok(())
+}
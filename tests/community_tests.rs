@@ -0,0 +1,71 @@
+use solana_account_info::AccountInfo;
+use solana_accountgen::extensions::community::{
+    NameRecordHeader, REVERSE_LOOKUP_CLASS, SOL_TLD_AUTHORITY, memo_instructions_sysvar,
+    reverse_lookup, sol_domain, sol_domain_key,
+};
+use solana_accountgen::well_known::{MEMO_PROGRAM, NAME_SERVICE_PROGRAM};
+use solana_instruction::Instruction;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_pubkey::{Pubkey, pubkey};
+use solana_sdk_ids::sysvar;
+
+#[test]
+fn test_sol_domain_matches_mainnet_address() {
+    // The address SNS resolves bonfida.sol to on mainnet
+    assert_eq!(
+        sol_domain_key("bonfida.sol"),
+        pubkey!("Crf8hzfthWGbGbLTVCiqRqV5MVnbpHB1L9KQMd6gsinb")
+    );
+}
+
+#[test]
+fn test_sol_domain_and_reverse_lookup() {
+    let owner = Pubkey::new_unique();
+    let (key, domain) = sol_domain("alice", &owner, 1_000).unwrap();
+    assert_eq!(domain.owner, NAME_SERVICE_PROGRAM);
+    assert_eq!(domain.data.len(), NameRecordHeader::LEN + 1_000);
+    assert_eq!(
+        NameRecordHeader::unpack(&domain.data).unwrap(),
+        NameRecordHeader {
+            parent_name: SOL_TLD_AUTHORITY,
+            owner,
+            class: Pubkey::default(),
+        }
+    );
+
+    let (reverse_key, reverse) = reverse_lookup(&key, "alice.sol").unwrap();
+    assert_ne!(reverse_key, key);
+    let header = NameRecordHeader::unpack(&reverse.data).unwrap();
+    assert_eq!(header.class, REVERSE_LOOKUP_CLASS);
+    let name: String = borsh::from_slice(&reverse.data[NameRecordHeader::LEN..]).unwrap();
+    assert_eq!(name, "alice");
+
+    assert!(NameRecordHeader::unpack(&[0; 10]).is_err());
+}
+
+#[test]
+fn test_memo_instructions_sysvar() {
+    let program_id = Pubkey::new_unique();
+    let signer = Pubkey::new_unique();
+    let transfer = Instruction::new_with_bytes(program_id, &[1], vec![]);
+    let mut account = memo_instructions_sysvar("order 7", &[&signer], transfer);
+
+    let key = sysvar::instructions::id();
+    let info = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut account.lamports,
+        &mut account.data,
+        &account.owner,
+        false,
+        0,
+    );
+    // What a memo-checking program does: look at the previous instruction
+    let current = load_current_index_checked(&info).unwrap();
+    assert_eq!(current, 1);
+    let previous = load_instruction_at_checked(current as usize - 1, &info).unwrap();
+    assert_eq!(previous.program_id, MEMO_PROGRAM);
+    assert_eq!(previous.data, b"order 7");
+    assert_eq!(previous.accounts[0].pubkey, signer);
+}
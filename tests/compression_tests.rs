@@ -0,0 +1,137 @@
+use solana_accountgen::extensions::compression::{
+    MerkleTreeBuilder, Node, TREE_HEADER_LEN, empty_node, merkle_root, merkle_tree_account_size,
+};
+use solana_pubkey::Pubkey;
+
+const DEPTH: usize = 5;
+const BUFFER: usize = 8;
+
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn node_at(data: &[u8], offset: usize) -> Node {
+    data[offset..offset + 32].try_into().unwrap()
+}
+
+fn leaves(count: u8) -> Vec<Node> {
+    (1..=count).map(|i| [i; 32]).collect()
+}
+
+/// Offsets into a depth 5, buffer 8 tree account.
+struct Layout;
+
+impl Layout {
+    const TREE: usize = TREE_HEADER_LEN;
+    const CHANGE_LOGS: usize = Self::TREE + 24;
+    const CHANGE_LOG_LEN: usize = 32 + 32 * DEPTH + 8;
+    const RIGHTMOST: usize = Self::CHANGE_LOGS + BUFFER * Self::CHANGE_LOG_LEN;
+    const CANOPY: usize = Self::RIGHTMOST + 32 * DEPTH + 40;
+
+    fn change_log_root(data: &[u8], index: usize) -> Node {
+        node_at(data, Self::CHANGE_LOGS + index * Self::CHANGE_LOG_LEN)
+    }
+}
+
+#[test]
+fn test_empty_tree_header_and_root() {
+    let authority = Pubkey::new_unique();
+    let data = MerkleTreeBuilder::new(5, 8)
+        .authority(authority)
+        .creation_slot(77)
+        .build_data()
+        .unwrap();
+
+    assert_eq!(data.len(), merkle_tree_account_size(5, 8, 0));
+    assert_eq!(&data[..2], &[1, 0]);
+    assert_eq!(&data[2..6], &8u32.to_le_bytes());
+    assert_eq!(&data[6..10], &5u32.to_le_bytes());
+    assert_eq!(&data[10..42], authority.as_ref());
+    assert_eq!(u64_at(&data, 42), 77);
+
+    assert_eq!(u64_at(&data, Layout::TREE), 0); // sequence number
+    assert_eq!(u64_at(&data, Layout::TREE + 16), 1); // buffer size
+    assert_eq!(Layout::change_log_root(&data, 0), empty_node(5));
+    assert_eq!(node_at(&data, Layout::RIGHTMOST), empty_node(0));
+}
+
+#[test]
+fn test_appended_leaves_match_reference_root_and_proof() {
+    for count in [1, 2, 3, 8, 11] {
+        let leaves = leaves(count);
+        let builder = MerkleTreeBuilder::new(5, 8).leaves(leaves.clone());
+        let data = builder.build_data().unwrap();
+
+        let sequence_number = u64_at(&data, Layout::TREE);
+        let active_index = u64_at(&data, Layout::TREE + 8) as usize;
+        let buffer_size = u64_at(&data, Layout::TREE + 16);
+        assert_eq!(sequence_number, count as u64);
+        assert_eq!(active_index, count as usize % BUFFER);
+        assert_eq!(buffer_size, (count as u64 + 1).min(BUFFER as u64));
+
+        let root = merkle_root(&leaves, 5).unwrap();
+        assert_eq!(builder.root().unwrap(), root);
+        assert_eq!(
+            Layout::change_log_root(&data, active_index),
+            root,
+            "{count}"
+        );
+
+        // The rightmost proof proves the last leaf
+        let last = count as u32 - 1;
+        let proof: Vec<Node> = (0..DEPTH)
+            .map(|i| node_at(&data, Layout::RIGHTMOST + 32 * i))
+            .collect();
+        assert_eq!(Some(proof), builder.proof(last), "{count}");
+        assert_eq!(
+            node_at(&data, Layout::RIGHTMOST + 32 * DEPTH),
+            *leaves.last().unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_canopy_caches_top_of_tree() {
+    let builder = MerkleTreeBuilder::new(5, 8)
+        .canopy_depth(2)
+        .leaves(leaves(3));
+    let data = builder.build_data().unwrap();
+    assert_eq!(data.len(), merkle_tree_account_size(5, 8, 2));
+
+    // Heap nodes 2..=7 are stored at canopy indices 0..=5. All leaves sit
+    // under node 2 (the left half) and node 4 (its left half); nodes over
+    // empty subtrees stay zeroed.
+    let canopy: Vec<Node> = (0..6)
+        .map(|i| node_at(&data, Layout::CANOPY + 32 * i))
+        .collect();
+    assert_eq!(canopy[0], merkle_root(&leaves(3), 4).unwrap());
+    assert_eq!(canopy[2], merkle_root(&leaves(3), 3).unwrap());
+    for empty in [1, 3, 4, 5] {
+        assert_eq!(canopy[empty], [0; 32]);
+    }
+}
+
+#[test]
+fn test_invalid_tree_dimensions() {
+    assert!(MerkleTreeBuilder::new(5, 9).build().is_err());
+    assert!(
+        MerkleTreeBuilder::new(5, 8)
+            .canopy_depth(5)
+            .build()
+            .is_err()
+    );
+    assert!(
+        MerkleTreeBuilder::new(3, 8)
+            .leaves(leaves(9))
+            .build()
+            .is_err()
+    );
+    assert!(
+        MerkleTreeBuilder::new(3, 8)
+            .leaves(leaves(9))
+            .root()
+            .is_err()
+    );
+    assert!(merkle_root(&leaves(9), 3).is_err());
+    assert!(merkle_root(&leaves(8), 3).is_ok());
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::process_instruction;
+use solana_accountgen::compute::{CuBaseline, CuChange};
+use solana_accountgen::{AccountBuilder, AccountGenError};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_program_test::{ProgramTest, processor};
+use solana_pubkey::Pubkey;
+use solana_transaction_error::TransactionError;
+
+#[tokio::test]
+async fn test_measure_compute_units() {
+    let program_id = Pubkey::new_unique();
+    let counter = Pubkey::new_unique();
+    let mut program_test =
+        ProgramTest::new("cu_counter", program_id, processor!(process_instruction));
+    program_test.add_account(
+        counter,
+        AccountBuilder::new()
+            .balance(1_000_000)
+            .owner(program_id)
+            .data_raw(vec![0])
+            .build(),
+    );
+    let (mut banks_client, payer, _) = program_test.start().await;
+    let add = |amount: u8| {
+        Instruction::new_with_bytes(
+            program_id,
+            &[amount],
+            vec![AccountMeta::new(counter, false)],
+        )
+    };
+
+    let mut measured = CuBaseline::new();
+    let units = measured
+        .measure(&mut banks_client, "add", add(3), &payer, &[])
+        .await
+        .unwrap();
+    assert!(units > 0);
+    assert_eq!(measured.get("add"), Some(units));
+
+    // Simulation leaves the bank unchanged
+    let account = banks_client.get_account(counter).await.unwrap().unwrap();
+    assert_eq!(account.data, [0]);
+
+    let error = measured
+        .measure(&mut banks_client, "fail", add(0), &payer, &[])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        AccountGenError::TransactionFailed(TransactionError::InstructionError(0, _))
+    ));
+    assert_eq!(measured.len(), 1);
+
+    // The same instruction measures the same the next time
+    let mut again = CuBaseline::new();
+    again
+        .measure(&mut banks_client, "add", add(3), &payer, &[])
+        .await
+        .unwrap();
+    again.assert_cu_within(&measured, 0.0);
+}
+
+#[test]
+fn test_compare_with_baseline() {
+    let mut baseline = CuBaseline::new();
+    baseline
+        .record("deposit", 1_000)
+        .record("withdraw", 2_000)
+        .record("close", 500);
+
+    let mut measured = CuBaseline::new();
+    measured
+        .record("deposit", 1_050)
+        .record("withdraw", 1_700)
+        .record("migrate", 300);
+    let report = measured.compare(&baseline, 0.1);
+    assert!(!report.is_ok());
+    assert_eq!(
+        report.changed,
+        vec![CuChange {
+            name: "withdraw".to_string(),
+            baseline: 2_000,
+            measured: 1_700,
+        }]
+    );
+    assert_eq!(report.unrecorded, ["migrate"]);
+    assert_eq!(
+        report.to_string(),
+        "compute units differ from the baseline by more than 10.0%:\n  \
+         withdraw: 2000 -> 1700 (-15.0%)\n  \
+         migrate: not in the baseline\n\
+         set ACCOUNTGEN_UPDATE_BASELINES to update the baseline"
+    );
+    assert!(matches!(
+        report.into_result(),
+        Err(AccountGenError::ConstraintViolation(_))
+    ));
+
+    // Exactly at the tolerance passes
+    let mut measured = CuBaseline::new();
+    measured.record("deposit", 1_100);
+    assert!(measured.compare(&baseline, 0.1).is_ok());
+
+    let result = std::panic::catch_unwind(|| measured.assert_cu_within(&baseline, 0.05));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_baseline_files() {
+    let path = std::env::temp_dir().join(format!(
+        "solana-accountgen-cu-baseline-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let mut measured = CuBaseline::new();
+    measured.record("withdraw", 2_000).record("deposit", 1_000);
+
+    // A missing file fails instead of being written
+    let error = measured.check_file(&path, 0.05).unwrap_err();
+    assert!(matches!(error, AccountGenError::ConstraintViolation(_)));
+    assert!(
+        error
+            .to_string()
+            .contains("missing; rerun with ACCOUNTGEN_UPDATE_BASELINES=1")
+    );
+    assert!(!path.exists());
+
+    // Saved files are sorted by name
+    measured.save(&path).unwrap();
+    let json = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(json, "{\n  \"deposit\": 1000,\n  \"withdraw\": 2000\n}\n");
+    assert_eq!(CuBaseline::load(&path).unwrap(), measured);
+
+    // An existing file is checked
+    measured.record("deposit", 1_020);
+    measured.check_file(&path, 0.05).unwrap();
+    measured.record("deposit", 2_000);
+    let error = measured.check_file(&path, 0.05).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(
+        error
+            .to_string()
+            .contains("deposit: 1000 -> 2000 (+100.0%)")
+    );
+
+    assert!(matches!(
+        CuBaseline::from_json(r#"{ "deposit": "many" }"#),
+        Err(AccountGenError::InvalidDataFormat(_))
+    ));
+}
@@ -0,0 +1,115 @@
+use serde_json::json;
+use solana_accountgen::fixture::TempFixtureDir;
+use solana_accountgen::serialization::DataEncoding;
+use solana_accountgen::serialization::convert::{
+    from_fixture_json, from_rpc_json, from_validator_json, read_raw_data, reencode,
+    to_fixture_json, to_rpc_json, write_raw_data,
+};
+use solana_accountgen::{AccountBuilder, AccountGenError, AccountMap};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn test_convert_between_formats() {
+    let pubkey = Pubkey::new_unique();
+    let account = AccountBuilder::new()
+        .balance(2_039_280)
+        .owner(Pubkey::new_unique())
+        .data_raw(vec![7; 100])
+        .build();
+
+    // A getAccountInfo value as a node returns it, without `space`
+    let rpc = json!({
+        "lamports": account.lamports,
+        "owner": account.owner.to_string(),
+        "data": [DataEncoding::Base64.encode(&account.data), "base64"],
+        "executable": false,
+        "rentEpoch": account.rent_epoch,
+    });
+    assert_eq!(from_rpc_json(&rpc).unwrap(), account);
+    let hex = to_rpc_json(&account, DataEncoding::Hex);
+    assert_eq!(hex["space"], 100);
+    assert_eq!(from_rpc_json(&hex).unwrap(), account);
+
+    let line = to_fixture_json(&pubkey, &account);
+    assert!(!line.contains('\n'));
+    assert_eq!(from_fixture_json(&line).unwrap(), (pubkey, account.clone()));
+
+    // The files written for solana-test-validator read back the same
+    let mut accounts = AccountMap::new();
+    accounts.set_account(pubkey, account.clone());
+    let dir = TempFixtureDir::with_accounts("convert", &accounts).unwrap();
+    let file = std::fs::read_to_string(dir.path().join(format!("{}.json", pubkey))).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&file).unwrap();
+    assert_eq!(
+        from_validator_json(&value).unwrap(),
+        (pubkey, account.clone())
+    );
+
+    let raw = dir.path().join("data.bin");
+    write_raw_data(&raw, &account.data).unwrap();
+    assert_eq!(read_raw_data(&raw).unwrap(), account.data);
+}
+
+#[test]
+fn test_convert_errors() {
+    assert_eq!(
+        reencode("Ldp", DataEncoding::Base58, DataEncoding::Hex).unwrap(),
+        "010203"
+    );
+    assert!(reencode("0OIl", DataEncoding::Base58, DataEncoding::Base64).is_err());
+
+    let missing_owner =
+        json!({"lamports": 1, "data": ["", "base64"], "executable": false, "rentEpoch": 0});
+    match from_rpc_json(&missing_owner) {
+        Err(AccountGenError::InvalidDataFormat(message)) => {
+            assert!(message.starts_with("invalid RPC account: missing field `owner`"))
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(from_validator_json(&json!({"pubkey": "nope"})).is_err());
+    assert!(from_fixture_json("{}").is_err());
+}
+
+#[test]
+fn test_account_map_serde_and_conversions() {
+    let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(b, AccountBuilder::new().balance(2).data_raw(vec![0xff; 4]))
+        .unwrap();
+    accounts
+        .add_with_builder(a, AccountBuilder::new().balance(1))
+        .unwrap();
+
+    // Pubkeys are written in order, so the output is stable
+    let text = serde_json::to_string(&accounts).unwrap();
+    assert_eq!(text, serde_json::to_string(&accounts.clone()).unwrap());
+    assert!(text.find(&a.to_string()) < text.find(&b.to_string()));
+
+    // Values are RPC accounts, so any data encoding is accepted back
+    let mut value: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(value[b.to_string()]["data"], json!(["/////w==", "base64"]));
+    value[b.to_string()]["data"] = json!(["ffffffff", "hex"]);
+    let parsed: AccountMap = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed.get_account(&b), accounts.get_account(&b));
+
+    let bad_key = serde_json::from_value::<AccountMap>(json!({"nope": to_rpc_json(
+        accounts.get_account(&a).unwrap(),
+        DataEncoding::Base64
+    )}));
+    assert!(
+        bad_key
+            .unwrap_err()
+            .to_string()
+            .contains("invalid pubkey nope")
+    );
+
+    let pairs = parsed.into_vec();
+    assert_eq!(
+        pairs.iter().map(|(pubkey, _)| *pubkey).collect::<Vec<_>>(),
+        vec![a, b]
+    );
+    let map: std::collections::HashMap<_, _> = pairs.into_iter().collect();
+    let accounts = AccountMap::from(map);
+    assert_eq!(std::collections::HashMap::from(accounts).len(), 2);
+}
@@ -0,0 +1,220 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+use serde_json::json;
+use solana_accountgen::decoder::{DecoderRegistry, decode_instruction};
+use solana_accountgen::display::format_decoded;
+use solana_accountgen::extensions::anchor::get_method_discriminator;
+use solana_accountgen::extensions::idl::Idl;
+use solana_accountgen::well_known::{COMPUTE_BUDGET_PROGRAM, SYSTEM_PROGRAM, TOKEN_PROGRAM};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn test_decode_system_create_account_with_seed() {
+    let base = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(base.as_ref());
+    data.extend_from_slice(&4u64.to_le_bytes());
+    data.extend_from_slice(b"seed");
+    data.extend_from_slice(&1_000u64.to_le_bytes());
+    data.extend_from_slice(&165u64.to_le_bytes());
+    data.extend_from_slice(owner.as_ref());
+
+    let payer = Pubkey::new_unique();
+    let instruction = Instruction::new_with_bytes(
+        SYSTEM_PROGRAM,
+        &data,
+        vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(base, true),
+        ],
+    );
+
+    let decoded = decode_instruction(&instruction).unwrap();
+    assert_eq!(decoded.program, "System Program");
+    assert_eq!(decoded.name, "createAccountWithSeed");
+    assert_eq!(
+        decoded.args,
+        json!({
+            "base": base.to_string(),
+            "seed": "seed",
+            "lamports": 1_000,
+            "space": 165,
+            "owner": owner.to_string(),
+        })
+    );
+    assert_eq!(decoded.accounts[0].name.as_deref(), Some("source"));
+    assert_eq!(decoded.accounts[2].name.as_deref(), Some("base"));
+
+    let text = decoded.to_string();
+    assert!(text.starts_with("System Program: createAccountWithSeed\n"));
+    assert!(text.contains(&format!("#0 source      {} (signer, writable)", payer)));
+}
+
+#[test]
+fn test_decode_token_instructions() {
+    let mut data = vec![12];
+    data.extend_from_slice(&2_500u64.to_le_bytes());
+    data.push(6);
+    let instruction = Instruction::new_with_bytes(TOKEN_PROGRAM, &data, vec![]);
+    let decoded = decode_instruction(&instruction).unwrap();
+    assert_eq!(decoded.name, "transferChecked");
+    assert_eq!(decoded.args, json!({ "amount": 2_500, "decimals": 6 }));
+
+    // initializeMint2 without a freeze authority
+    let authority = Pubkey::new_unique();
+    let mut data = vec![20, 9];
+    data.extend_from_slice(authority.as_ref());
+    data.push(0);
+    let instruction = Instruction::new_with_bytes(TOKEN_PROGRAM, &data, vec![]);
+    let decoded = decode_instruction(&instruction).unwrap();
+    assert_eq!(
+        decoded.args,
+        json!({
+            "decimals": 9,
+            "mintAuthority": authority.to_string(),
+            "freezeAuthority": null,
+        })
+    );
+}
+
+#[test]
+fn test_decode_compute_budget() {
+    let mut data = vec![3];
+    data.extend_from_slice(&10_000u64.to_le_bytes());
+    let instruction = Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM, &data, vec![]);
+    let decoded = decode_instruction(&instruction).unwrap();
+    assert_eq!(decoded.name, "setComputeUnitPrice");
+    assert_eq!(decoded.args, json!({ "microLamports": 10_000 }));
+}
+
+#[test]
+fn test_decode_rejects_malformed_native_data() {
+    let instruction = Instruction::new_with_bytes(SYSTEM_PROGRAM, &[2, 0, 0, 0, 1], vec![]);
+    assert!(decode_instruction(&instruction).is_err());
+
+    // Display falls back to the raw data
+    let text = format_decoded(&DecoderRegistry::new(), &instruction);
+    assert!(text.starts_with("System Program: unknown\n"));
+    assert!(text.contains("0200000001"));
+    assert!(text.contains("error: "));
+}
+
+#[test]
+fn test_decode_unknown_program() {
+    let program_id = Pubkey::new_unique();
+    let instruction = Instruction::new_with_bytes(program_id, &[0xab, 0xcd], vec![]);
+    let decoded = decode_instruction(&instruction).unwrap();
+    assert_eq!(decoded.program, program_id.to_string());
+    assert_eq!(decoded.name, "unknown");
+    assert_eq!(decoded.args, json!({ "data": "abcd" }));
+}
+
+#[test]
+fn test_decode_anchor_idl_instructions() {
+    let idl = Idl::from_json(
+        r#"{
+            "version": "0.1.0",
+            "name": "vault",
+            "instructions": [
+                {
+                    "name": "depositFunds",
+                    "accounts": [
+                        { "name": "vault", "isMut": true, "isSigner": false },
+                        { "name": "common", "accounts": [
+                            { "name": "owner", "isMut": false, "isSigner": true },
+                            { "name": "systemProgram", "isMut": false, "isSigner": false }
+                        ] }
+                    ],
+                    "args": [
+                        { "name": "amount", "type": "u64" },
+                        { "name": "kind", "type": { "defined": "DepositKind" } },
+                        { "name": "label", "type": { "option": "string" } }
+                    ]
+                },
+                { "name": "close", "accounts": [], "args": [] }
+            ],
+            "accounts": [],
+            "types": [{
+                "name": "DepositKind",
+                "type": { "kind": "enum", "variants": [
+                    { "name": "Locked", "fields": [{ "name": "until", "type": "i64" }] },
+                    { "name": "Free" }
+                ] }
+            }]
+        }"#,
+    )
+    .unwrap();
+
+    let program_id = Pubkey::new_unique();
+    let mut registry = DecoderRegistry::new();
+    registry.register_anchor_idl(program_id, &idl).unwrap();
+
+    let mut data = get_method_discriminator("deposit_funds").to_vec();
+    data.extend_from_slice(&50u64.to_le_bytes());
+    data.push(0);
+    data.extend_from_slice(&(-1i64).to_le_bytes());
+    data.extend_from_slice(&[1, 2, 0, 0, 0]);
+    data.extend_from_slice(b"hi");
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &data,
+        vec![
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), true),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+        ],
+    );
+
+    let decoded = registry.decode_instruction(&instruction).unwrap();
+    assert_eq!(decoded.program, "vault");
+    assert_eq!(decoded.name, "depositFunds");
+    assert_eq!(
+        decoded.args,
+        json!({
+            "amount": 50,
+            "kind": { "Locked": { "until": -1 } },
+            "label": { "Some": "hi" },
+        })
+    );
+    let names: Vec<_> = decoded
+        .accounts
+        .iter()
+        .map(|account| account.name.as_deref().unwrap())
+        .collect();
+    assert_eq!(names, ["vault", "owner", "systemProgram"]);
+
+    let close = Instruction::new_with_bytes(program_id, &get_method_discriminator("close"), vec![]);
+    let decoded = registry.decode_instruction(&close).unwrap();
+    assert_eq!(decoded.name, "close");
+    assert!(decoded.args.is_null());
+
+    let unknown = Instruction::new_with_bytes(program_id, &[0; 8], vec![]);
+    assert!(registry.decode_instruction(&unknown).is_err());
+}
+
+#[test]
+fn test_decode_borsh_struct() {
+    #[derive(BorshSerialize, BorshDeserialize, Serialize)]
+    struct SetFee {
+        basis_points: u16,
+    }
+
+    let program_id = Pubkey::new_unique();
+    let mut registry = DecoderRegistry::empty();
+    registry.register_borsh::<SetFee>(program_id, "Fees");
+    assert!(registry.contains(&program_id));
+    assert!(!registry.contains(&SYSTEM_PROGRAM));
+
+    let instruction = Instruction::new_with_borsh(program_id, &SetFee { basis_points: 30 }, vec![]);
+    let decoded = registry.decode_instruction(&instruction).unwrap();
+    assert_eq!(decoded.program, "Fees");
+    assert_eq!(decoded.name, "SetFee");
+    assert_eq!(decoded.args, json!({ "basis_points": 30 }));
+
+    // Trailing bytes do not decode
+    let instruction = Instruction::new_with_bytes(program_id, &[30, 0, 0], vec![]);
+    assert!(registry.decode_instruction(&instruction).is_err());
+}
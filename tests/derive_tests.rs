@@ -0,0 +1,87 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_accountgen::AccountFixture;
+use solana_accountgen::extensions::anchor::{
+    create_anchor_account, deserialize_anchor_account, get_account_discriminator,
+};
+use solana_pubkey::Pubkey;
+
+#[derive(BorshSerialize, BorshDeserialize, AccountFixture, Debug, Clone, PartialEq)]
+struct GameState {
+    player: Pubkey,
+    score: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, AccountFixture, Debug, PartialEq)]
+#[account_fixture(anchor_name = "game_config")]
+struct Config {
+    max_players: u8,
+}
+
+#[test]
+fn test_derived_into_and_from_account() {
+    let owner = Pubkey::new_unique();
+    let state = GameState {
+        player: Pubkey::new_unique(),
+        score: 42,
+    };
+
+    let account = state.clone().into_account(owner, 1_000_000).unwrap();
+    assert_eq!(account.owner, owner);
+    assert_eq!(account.lamports, 1_000_000);
+    assert_eq!(account.data, borsh::to_vec(&state).unwrap());
+    assert_eq!(GameState::from_account(&account).unwrap(), state);
+}
+
+#[test]
+fn test_derived_anchor_account_matches_helpers() {
+    let program_id = Pubkey::new_unique();
+    let state = GameState {
+        player: Pubkey::new_unique(),
+        score: 7,
+    };
+
+    let account = state
+        .clone()
+        .into_anchor_account(program_id, 5_000)
+        .unwrap();
+    let expected = create_anchor_account("GameState", program_id, state.clone(), 5_000).unwrap();
+    assert_eq!(account, expected);
+    assert_eq!(GameState::from_anchor_account(&account).unwrap(), state);
+    assert_eq!(
+        deserialize_anchor_account::<GameState>(&account).unwrap(),
+        state
+    );
+
+    // Spare space after the data is fine
+    let mut padded = account.clone();
+    padded.data.extend_from_slice(&[0; 16]);
+    assert_eq!(GameState::from_anchor_account(&padded).unwrap(), state);
+}
+
+#[test]
+fn test_derived_anchor_name_and_discriminator_check() {
+    assert_eq!(Config::ANCHOR_NAME, "game_config");
+    assert_eq!(
+        Config::anchor_discriminator(),
+        get_account_discriminator("game_config")
+    );
+
+    let program_id = Pubkey::new_unique();
+    let account = Config { max_players: 4 }
+        .into_anchor_account(program_id, 1_000)
+        .unwrap();
+    assert_eq!(
+        &account.data[..8],
+        &get_account_discriminator("game_config")
+    );
+
+    // A GameState account is not a Config account
+    let other = GameState {
+        player: Pubkey::new_unique(),
+        score: 1,
+    }
+    .into_anchor_account(program_id, 1_000)
+    .unwrap();
+    let err = Config::from_anchor_account(&other).unwrap_err();
+    assert!(err.to_string().contains("game_config discriminator"));
+}
@@ -0,0 +1,218 @@
+use solana_accountgen::AccountBuilder;
+use solana_accountgen::diff::{ByteRangeChange, diff_accounts, diff_bytes};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn test_diff_bytes_identical() {
+    assert!(diff_bytes(&[1, 2, 3], &[1, 2, 3]).is_empty());
+    assert!(diff_bytes(&[], &[]).is_empty());
+}
+
+#[test]
+fn test_diff_bytes_separate_ranges() {
+    let old = [0u8; 16];
+    let mut new = old;
+    new[2] = 1;
+    new[3] = 1;
+    new[10] = 1;
+
+    let changes = diff_bytes(&old, &new);
+    assert_eq!(changes.len(), 2);
+    assert_eq!((changes[0].offset, changes[0].end()), (2, 4));
+    assert_eq!((changes[1].offset, changes[1].end()), (10, 11));
+}
+
+#[test]
+fn test_diff_bytes_truncated() {
+    let changes = diff_bytes(&[1, 2, 3, 4], &[1, 2, 9]);
+    assert_eq!(
+        changes,
+        vec![ByteRangeChange {
+            offset: 2,
+            old: vec![3, 4],
+            new: vec![9],
+        }]
+    );
+    assert_eq!(changes[0].to_string(), "[2..4]: 0304 -> 09");
+}
+
+#[test]
+fn test_diff_accounts() {
+    let program_id = Pubkey::new_unique();
+    let before = AccountBuilder::new()
+        .balance(1_000)
+        .owner(program_id)
+        .data_raw(vec![0; 8])
+        .build();
+
+    assert!(diff_accounts(&before, &before.clone()).is_empty());
+
+    let mut after = before.clone();
+    after.owner = Pubkey::new_unique();
+    after.data[7] = 0xff;
+
+    let diff = diff_accounts(&before, &after);
+    assert_eq!(diff.lamports, None);
+    assert_eq!(diff.owner, Some((program_id, after.owner)));
+    assert_eq!(diff.data.len(), 1);
+    assert!(diff.to_string().contains("data[7..8]: 00 -> ff"));
+}
+
+#[test]
+fn test_diff_maps_with_regions() {
+    use solana_accountgen::AccountMap;
+    use solana_accountgen::diff::diff_maps_with_regions;
+    use solana_accountgen::regions::{RegionFormat, RegionMap, RegionMaps};
+
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let mut regions = RegionMaps::new();
+    regions.insert(
+        program_id,
+        RegionMap::new()
+            .region("header.authority", 9, RegionFormat::Pubkey)
+            .region("header.seq", 1, RegionFormat::U64)
+            .region("padding", 41, RegionFormat::Padding(3)),
+    );
+
+    let (mapped, unmapped) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut before = AccountMap::new();
+    for pubkey in [mapped, unmapped] {
+        let owner = if pubkey == mapped {
+            program_id
+        } else {
+            Pubkey::new_unique()
+        };
+        before
+            .add_with_builder(
+                pubkey,
+                AccountBuilder::new().owner(owner).data_raw(vec![0; 44]),
+            )
+            .unwrap();
+    }
+    let mut after = before.clone();
+    for pubkey in [mapped, unmapped] {
+        let data = &mut after.get_account_mut(&pubkey).unwrap().data;
+        data[0] = 0xff;
+        data[1..9].copy_from_slice(&6u64.to_le_bytes());
+        data[9..41].copy_from_slice(authority.as_ref());
+        data.truncate(42);
+    }
+
+    let diff = diff_maps_with_regions(&before, &after, &regions);
+    let account_diff = |pubkey| {
+        diff.changed
+            .iter()
+            .find(|(key, _)| *key == pubkey)
+            .map(|(_, diff)| diff.clone())
+            .unwrap()
+    };
+
+    let mapped_diff = account_diff(mapped);
+    let names: Vec<_> = mapped_diff
+        .regions
+        .iter()
+        .map(|change| change.name.as_str())
+        .collect();
+    assert_eq!(names, ["header.seq", "header.authority", "padding"]);
+    assert_eq!(
+        mapped_diff.to_string(),
+        format!(
+            "header.seq: 0 -> 6\nheader.authority: {} -> {}\npadding: 000000 -> (none)\n\
+             data[0..1]: 00 -> ff\ndata[42..44]: 0000 -> (none)\n",
+            Pubkey::default(),
+            authority
+        )
+    );
+
+    let unmapped_diff = account_diff(unmapped);
+    assert!(unmapped_diff.regions.is_empty());
+    assert_eq!(unmapped_diff.data[0].offset, 0);
+    assert_eq!(unmapped_diff.data.last().unwrap().end(), 44);
+}
+
+#[test]
+fn test_overflowing_regions() {
+    use solana_accountgen::regions::{RegionFormat, RegionMap, RegionMaps};
+
+    let regions = RegionMap::new().region("tail", usize::MAX, RegionFormat::U64);
+    let region = &regions.regions()[0];
+    assert!(region.range().is_err());
+    assert_eq!(region.value(&[0; 8]), None);
+    assert!(regions.region_at(usize::MAX).is_none());
+    assert!(regions.diff(&[0; 8], &[1; 8]).is_empty());
+
+    let json = format!(
+        r#"{{ "{}": [{{ "name": "tail", "offset": {}, "format": "u64" }}] }}"#,
+        Pubkey::new_unique(),
+        usize::MAX
+    );
+    assert!(RegionMaps::from_json(&json).is_err());
+}
+
+#[test]
+fn test_program_accounts_are_summarized() {
+    use solana_accountgen::display::format_account;
+    use solana_accountgen::program_summary::{
+        PROGRAM_DATA_HEADER_LEN, ProgramAccountKind, summarize,
+    };
+    use solana_accountgen::well_known::{BPF_LOADER, BPF_LOADER_UPGRADEABLE};
+
+    let program_data = |slot: u64, authority: Option<Pubkey>, elf: &[u8]| {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&slot.to_le_bytes());
+        match authority {
+            Some(authority) => {
+                data.push(1);
+                data.extend_from_slice(authority.as_ref());
+            }
+            None => data.extend_from_slice(&[0; 33]),
+        }
+        assert_eq!(data.len(), PROGRAM_DATA_HEADER_LEN);
+        data.extend_from_slice(elf);
+        // Program data is padded for future upgrades
+        data.extend_from_slice(&[0; 64 * 1024]);
+        AccountBuilder::new()
+            .owner(BPF_LOADER_UPGRADEABLE)
+            .data_raw(data)
+            .build()
+    };
+    let authority = Pubkey::new_unique();
+    let old = program_data(10, Some(authority), &[0x7f, b'E', b'L', b'F', 1, 2, 3]);
+    let new = program_data(20, None, &[0x7f, b'E', b'L', b'F', 4, 5]);
+
+    let summary = summarize(&old).unwrap();
+    assert_eq!(summary.elf_len, 7);
+    assert_eq!(
+        summary.kind,
+        ProgramAccountKind::ProgramData {
+            slot: 10,
+            upgrade_authority: Some(authority)
+        }
+    );
+    let dump = format_account(&old);
+    assert!(dump.contains(&format!("upgrade authority {}", authority)));
+    assert!(dump.contains("ELF 7 bytes, sha256 "));
+    assert!(!dump.contains("00000000"));
+
+    // An upgrade is one line, not a byte diff of the ELF
+    let diff = diff_accounts(&old, &new);
+    assert!(diff.data.is_empty());
+    let report = diff.to_string();
+    assert!(!report.contains("data["));
+    assert!(report.contains("program: program data, deployed at slot 10"));
+    assert!(report.contains("-> program data, deployed at slot 20, upgrade authority none"));
+
+    // Executable accounts of the other loaders hold the ELF itself
+    let program = AccountBuilder::new()
+        .owner(BPF_LOADER)
+        .executable(true)
+        .data_raw(vec![0x7f, b'E', b'L', b'F'])
+        .build();
+    assert_eq!(
+        summarize(&program).unwrap().kind,
+        ProgramAccountKind::Program
+    );
+    let not_a_program = AccountBuilder::new().data_raw(vec![3, 0, 0, 0]).build();
+    assert!(summarize(&not_a_program).is_none());
+}
@@ -0,0 +1,66 @@
+use solana_account::Account;
+use solana_accountgen::AccountMap;
+use solana_accountgen::cluster::{MAX_PERMITTED_DATA_LENGTH, check_data_len};
+use solana_accountgen::extensions::edge_cases;
+use solana_accountgen::program_summary::summarize;
+use solana_accountgen::transitions::{TransitionChecker, TransitionViolation};
+use solana_accountgen::well_known::LOADERS;
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+
+#[test]
+fn test_edge_case_catalog() {
+    let program_id = Pubkey::new_unique();
+    let catalog = edge_cases::catalog(&program_id);
+    assert_eq!(catalog.len(), 7);
+
+    let rent = Rent::default();
+    for (name, account) in &catalog {
+        let exempt = rent.is_exempt(account.lamports, account.data.len());
+        let expected_exempt = !matches!(*name, "zero_lamports_with_data" | "below_rent_exempt");
+        assert_eq!(exempt, expected_exempt, "{}", name);
+        assert!(check_data_len(account.data.len()).is_ok(), "{}", name);
+    }
+
+    let max_size = edge_cases::max_size_data(&program_id);
+    assert_eq!(max_size.data.len(), MAX_PERMITTED_DATA_LENGTH);
+    assert!(check_data_len(max_size.data.len() + 1).is_err());
+    assert_eq!(edge_cases::rent_epoch_max(&program_id).rent_epoch, u64::MAX);
+
+    // It looks like a program to an owner check, but is not one
+    let not_executable = edge_cases::loader_owned_not_executable();
+    assert!(LOADERS.contains(&not_executable.owner));
+    assert!(!not_executable.executable);
+    assert!(summarize(&not_executable).is_none());
+}
+
+#[test]
+fn test_realloc_boundary_cases() {
+    let program_id = Pubkey::new_unique();
+    let pubkey = Pubkey::new_unique();
+    let state = |account: Account| {
+        let mut accounts = AccountMap::new();
+        accounts.set_account(pubkey, account);
+        accounts
+    };
+    let mut before = state(Account::new(
+        Rent::default().minimum_balance(0),
+        0,
+        &program_id,
+    ));
+
+    // An empty program account grown within one instruction, with the
+    // lamports for the larger size already in place
+    let checker = TransitionChecker::new();
+    let mut grown = edge_cases::realloc_boundary(&program_id);
+    before.get_account_mut(&pubkey).unwrap().lamports = grown.lamports;
+    assert!(checker.check(&before, &state(grown.clone())).is_ok());
+
+    grown = edge_cases::past_realloc_boundary(&program_id);
+    before.get_account_mut(&pubkey).unwrap().lamports = grown.lamports;
+    let report = checker.check(&before, &state(grown));
+    assert!(matches!(
+        report.violations.as_slice(),
+        [(1, TransitionViolation::ReallocTooLarge { new_len, .. })] if *new_len == 10_241
+    ));
+}
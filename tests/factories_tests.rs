@@ -0,0 +1,120 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_accountgen::factories::Factory;
+use solana_accountgen::{AccountFixture, factory};
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+
+#[derive(BorshSerialize, BorshDeserialize, AccountFixture, Debug, Clone, PartialEq)]
+struct Player {
+    authority: Pubkey,
+    name: String,
+    score: u64,
+    items: Vec<u16>,
+}
+
+factory! {
+    struct PlayerFactory for Player {
+        authority: Pubkey = Pubkey::new_unique(),
+        name: String = "player".to_string(),
+        score: u64 = 0,
+        /// Item ids, empty by default.
+        items: Vec<u16> = vec![],
+    }
+    seeds(player) = [b"player".as_slice(), player.authority.as_ref(), &player.score.to_le_bytes()];
+}
+
+#[derive(BorshSerialize, BorshDeserialize, AccountFixture, Debug, Clone, PartialEq)]
+#[account_fixture(anchor_name = "GlobalConfig")]
+struct Config {
+    admin: Pubkey,
+}
+
+factory! {
+    struct ConfigFactory for Config {
+        admin: Pubkey = Pubkey::default(),
+    }
+}
+
+#[test]
+fn test_factory_defaults_and_setters() {
+    let player = PlayerFactory::new().build();
+    assert_eq!(player.name, "player");
+    assert_eq!(player.score, 0);
+    assert!(player.items.is_empty());
+
+    // Defaults are evaluated for each new factory
+    assert_ne!(PlayerFactory::new().build().authority, player.authority);
+
+    let authority = Pubkey::new_unique();
+    let player = PlayerFactory::default()
+        .authority(authority)
+        .score(250)
+        .items(vec![1, 2])
+        .build();
+    assert_eq!(
+        player,
+        Player {
+            authority,
+            name: "player".to_string(),
+            score: 250,
+            items: vec![1, 2],
+        }
+    );
+}
+
+#[test]
+fn test_factory_accounts() {
+    let owner = Pubkey::new_unique();
+    let factory = PlayerFactory::new().score(10);
+    let account = factory.build_account(&owner).unwrap();
+    assert_eq!(account.owner, owner);
+    assert_eq!(
+        account.lamports,
+        Rent::default().minimum_balance(account.data.len())
+    );
+    assert_eq!(Player::from_account(&account).unwrap(), factory.build());
+
+    let account = factory
+        .clone()
+        .anchor()
+        .lamports(42)
+        .build_account(&owner)
+        .unwrap();
+    assert_eq!(account.lamports, 42);
+    assert_eq!(Player::from_anchor_account(&account).unwrap().score, 10);
+}
+
+#[test]
+fn test_factory_pdas() {
+    let program_id = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let (address, bump, account) = PlayerFactory::new()
+        .authority(authority)
+        .score(7)
+        .build_pda(&program_id)
+        .unwrap();
+    assert_eq!(
+        (address, bump),
+        Pubkey::find_program_address(
+            &[b"player", authority.as_ref(), &7u64.to_le_bytes()],
+            &program_id
+        )
+    );
+    assert_eq!(account.owner, program_id);
+
+    // Without seeds, the PDA is derived from the Anchor name
+    let (address, _, account) = ConfigFactory::new()
+        .anchor()
+        .build_pda(&program_id)
+        .unwrap();
+    assert_eq!(
+        address,
+        Pubkey::find_program_address(&[b"GlobalConfig"], &program_id).0
+    );
+    assert_eq!(
+        Config::from_anchor_account(&account).unwrap(),
+        Config {
+            admin: Pubkey::default()
+        }
+    );
+}
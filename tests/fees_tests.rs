@@ -0,0 +1,146 @@
+use solana_accountgen::AccountMap;
+use solana_accountgen::extensions::precompiles::ed25519_instruction;
+use solana_accountgen::extensions::program_test::ProgramTestExt;
+use solana_accountgen::fees::{FeeEstimator, fund_for_fees};
+use solana_accountgen::fixture::InstructionSet;
+use solana_accountgen::instructions::{compute_budget, memo, system};
+use solana_keypair::Keypair;
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+#[tokio::test]
+async fn test_estimates_match_bank_fees() {
+    let (banks_client, payer, blockhash) = ProgramTest::default().start().await;
+    let cosigner = Keypair::new();
+    let cases = [
+        (vec![memo("one", &[])], vec![&payer]),
+        (
+            vec![
+                compute_budget::set_compute_unit_price(3_333),
+                memo("default limit", &[]),
+                memo("second", &[]),
+            ],
+            vec![&payer],
+        ),
+        (
+            vec![
+                compute_budget::set_compute_unit_price(7),
+                ed25519_instruction(&Keypair::new(), b"precompile"),
+                system::transfer(&payer.pubkey(), &payer.pubkey(), 1),
+            ],
+            vec![&payer],
+        ),
+        (
+            vec![
+                compute_budget::set_compute_unit_limit(50_000),
+                compute_budget::set_compute_unit_price(1_000_001),
+                ed25519_instruction(&cosigner, b"signed"),
+                memo("cosigned", &[&cosigner.pubkey()]),
+            ],
+            vec![&payer, &cosigner],
+        ),
+    ];
+    for (instructions, signers) in cases {
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &signers,
+            blockhash,
+        );
+        let expected = banks_client
+            .get_fee_for_message(transaction.message.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        let estimator = FeeEstimator::default();
+        assert_eq!(estimator.estimate(&transaction).unwrap(), expected);
+        assert_eq!(
+            estimator.estimate_instructions(&instructions, signers.len() as u64),
+            expected
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_funded_payer_covers_every_transaction() {
+    let payer = Keypair::new();
+    let mut transactions: Vec<Transaction> = (0..5)
+        .map(|i| {
+            let mut instructions = vec![memo(&format!("tx {i}"), &[])];
+            if i % 2 == 0 {
+                instructions.insert(0, compute_budget::set_compute_unit_price(10_000));
+            }
+            Transaction::new_with_payer(&instructions, Some(&payer.pubkey()))
+        })
+        .collect();
+
+    let mut accounts = AccountMap::new();
+    FeeEstimator::default()
+        .fund_for_transactions(&mut accounts, &payer.pubkey(), &transactions)
+        .unwrap();
+    let mut program_test = ProgramTest::default();
+    program_test.add_account_map(accounts);
+    let (banks_client, _, blockhash) = program_test.start().await;
+    for transaction in &mut transactions {
+        transaction.sign(&[&payer], blockhash);
+        banks_client
+            .process_transaction(transaction.clone())
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        banks_client.get_balance(payer.pubkey()).await.unwrap(),
+        Rent::default().minimum_balance(0)
+    );
+}
+
+#[tokio::test]
+async fn test_funded_payer_covers_replay() {
+    let payer = Keypair::new();
+    let cosigner = Keypair::new();
+    let mut instructions: InstructionSet =
+        (0..4).map(|i| memo(&format!("memo {i}"), &[])).collect();
+    instructions.push(compute_budget::set_compute_unit_price(10_000));
+    instructions.push(memo("cosigned", &[&cosigner.pubkey()]));
+
+    let mut accounts = AccountMap::new();
+    instructions.fund_payer(&mut accounts, &payer.pubkey(), &FeeEstimator::default());
+    let mut program_test = ProgramTest::default();
+    program_test.add_account_map(accounts);
+    let (mut banks_client, _, _) = program_test.start().await;
+
+    instructions
+        .replay(&mut banks_client, &payer, &[&cosigner])
+        .await
+        .unwrap();
+    assert_eq!(
+        banks_client.get_balance(payer.pubkey()).await.unwrap(),
+        Rent::default().minimum_balance(0)
+    );
+}
+
+#[test]
+fn test_estimate_rejects_out_of_bounds_program_index() {
+    let payer = Pubkey::new_unique();
+    let mut transaction = Transaction::new_with_payer(&[memo("hi", &[])], Some(&payer));
+    transaction.message.instructions[0].program_id_index = 9;
+    assert!(FeeEstimator::default().estimate(&transaction).is_err());
+}
+
+#[test]
+fn test_fund_for_fees_tops_up_existing_payer() {
+    let payer = Pubkey::new_unique();
+    let mut accounts = AccountMap::new();
+    let minimum = Rent::default().minimum_balance(0);
+    assert_eq!(
+        fund_for_fees(&mut accounts, &payer, 2, 5_000),
+        minimum + 10_000
+    );
+    assert_eq!(
+        fund_for_fees(&mut accounts, &payer, 1, 5_000),
+        minimum + 15_000
+    );
+}
@@ -0,0 +1,457 @@
+use solana_accountgen::extensions::program_test::ProgramTestExt;
+use solana_accountgen::fixture::{
+    AccountRef, FixtureAccount, FixtureReader, FixtureSource, FixtureWriter, InstructionSet,
+    RefResolver, Seed, TempFixtureDir, content_hash, load_account_map, load_scenario,
+    save_account_map, save_scenario,
+};
+use solana_accountgen::{AccountBuilder, AccountMap};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+use solana_signer::Signer;
+
+#[test]
+fn test_fixture_stream_round_trip() {
+    let program_id = Pubkey::new_unique();
+    let pubkey1 = Pubkey::new_unique();
+    let pubkey2 = Pubkey::new_unique();
+
+    let account1 = AccountBuilder::new()
+        .balance(100_000)
+        .owner(program_id)
+        .data_raw(vec![1, 2, 3])
+        .build();
+    let account2 = AccountBuilder::new()
+        .balance(200_000)
+        .rent_epoch(u64::MAX)
+        .build();
+
+    let mut writer = FixtureWriter::new(Vec::new());
+    writer.append(&pubkey1, &account1).unwrap();
+    writer.append(&pubkey2, &account2).unwrap();
+    let bytes = writer.into_inner().unwrap();
+
+    // One record per line
+    assert_eq!(bytes.iter().filter(|b| **b == b'\n').count(), 2);
+
+    let accounts: Vec<_> = FixtureReader::new(bytes.as_slice())
+        .accounts()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(accounts, vec![(pubkey1, account1), (pubkey2, account2)]);
+}
+
+#[test]
+fn test_fixture_source_round_trip() {
+    let (pubkey, cloned) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let account = AccountBuilder::new().balance(5_000).build();
+    let source = FixtureSource {
+        cluster: "https://api.devnet.solana.com".to_string(),
+        slot: 300_000_000,
+        pubkey: cloned,
+    };
+
+    let mut writer = FixtureWriter::new(Vec::new());
+    writer
+        .append_record(&FixtureAccount::new(pubkey, account.clone()).with_source(source.clone()))
+        .unwrap();
+    writer.append(&cloned, &account).unwrap();
+    let bytes = writer.into_inner().unwrap();
+    let text = String::from_utf8(bytes.clone()).unwrap();
+    assert!(text.lines().next().unwrap().contains(r#""slot":300000000"#));
+    assert!(!text.lines().nth(1).unwrap().contains("source"));
+
+    let records: Vec<_> = FixtureReader::new(bytes.as_slice())
+        .records()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(records[0].source, Some(source));
+    assert_eq!(records[1].source, None);
+
+    // Plain readers ignore the source
+    let accounts: Vec<_> = FixtureReader::new(bytes.as_slice())
+        .accounts()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(accounts[0], (pubkey, account));
+}
+
+#[test]
+fn test_fixture_reader_reports_bad_line() {
+    let input = "\n{\"not\": \"an account\"}\n";
+
+    let mut accounts = FixtureReader::new(input.as_bytes()).accounts();
+    let err = accounts.next().unwrap().unwrap_err();
+
+    assert!(err.to_string().contains("fixture line 2"));
+    assert!(accounts.next().is_none());
+}
+
+#[test]
+fn test_fixture_reader_accepts_rpc_encodings() {
+    let pubkey = Pubkey::new_unique();
+    let line = |data: &str, encoding: &str| {
+        format!(
+            "{{\"pubkey\":\"{pubkey}\",\"lamports\":1,\"owner\":\"{}\",\"executable\":false,\"rentEpoch\":0,\"data\":[\"{data}\",\"{encoding}\"]}}\n",
+            system_program::id()
+        )
+    };
+    let input = line("Ldp", "base58") + &line("AQID", "base64") + &line("010203", "hex");
+
+    let accounts: Vec<_> = FixtureReader::new(input.as_bytes())
+        .accounts()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(accounts.len(), 3);
+    assert!(
+        accounts
+            .iter()
+            .all(|(_, account)| account.data == [1, 2, 3])
+    );
+
+    let unsupported = line("AQID", "base32");
+    let mut accounts = FixtureReader::new(unsupported.as_bytes()).accounts();
+    let err = accounts.next().unwrap().unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("unsupported data encoding: base32")
+    );
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_fixture_compression_round_trip() {
+    let small = (
+        Pubkey::new_unique(),
+        AccountBuilder::new().data_raw(vec![7; 16]).build(),
+    );
+    let large = (
+        Pubkey::new_unique(),
+        AccountBuilder::new().data_raw(vec![7; 1 << 20]).build(),
+    );
+
+    let mut writer = FixtureWriter::new(Vec::new()).with_compression(1024);
+    writer.append(&small.0, &small.1).unwrap();
+    writer.append(&large.0, &large.1).unwrap();
+    let bytes = writer.into_inner().unwrap();
+    let text = String::from_utf8(bytes.clone()).unwrap();
+
+    let mut lines = text.lines();
+    assert!(lines.next().unwrap().contains("\"base64\"]"));
+    assert!(lines.next().unwrap().contains("\"base64+zstd\"]"));
+    assert!(bytes.len() < 10_000);
+
+    let accounts: Vec<_> = FixtureReader::new(bytes.as_slice())
+        .accounts()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(accounts, vec![small, large]);
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn test_fixture_compressed_data_requires_feature() {
+    let input = format!(
+        "{{\"pubkey\":\"{}\",\"lamports\":1,\"owner\":\"{}\",\"executable\":false,\"rentEpoch\":0,\"data\":[\"KLUv/QBYCQAAAQID\",\"base64+zstd\"]}}\n",
+        Pubkey::new_unique(),
+        system_program::id()
+    );
+
+    let err = FixtureReader::new(input.as_bytes())
+        .accounts()
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert!(err.to_string().contains("requires the `zstd` feature"));
+}
+
+#[test]
+fn test_fixture_file_round_trip() {
+    let path = std::env::temp_dir().join(format!(
+        "solana-accountgen-fixture-{}.json",
+        std::process::id()
+    ));
+
+    let mut account_map = AccountMap::new();
+    for lamports in [1, 2, 3] {
+        account_map
+            .add_with_builder(
+                Pubkey::new_unique(),
+                AccountBuilder::new().balance(lamports),
+            )
+            .unwrap();
+    }
+
+    save_account_map(&path, &account_map).unwrap();
+
+    let mut writer = FixtureWriter::open_append(&path).unwrap();
+    let extra = Pubkey::new_unique();
+    writer
+        .append(&extra, &AccountBuilder::new().balance(4).build())
+        .unwrap();
+    writer.flush().unwrap();
+
+    let loaded = load_account_map(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.len(), 4);
+    assert_eq!(loaded.get_account(&extra).unwrap().lamports, 4);
+    for (pubkey, account) in account_map.iter() {
+        assert_eq!(loaded.get_account(pubkey), Some(account));
+    }
+}
+
+#[test]
+fn test_temp_fixture_dir() {
+    let pubkey = Pubkey::new_unique();
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(
+            pubkey,
+            AccountBuilder::new().balance(42).data_raw(vec![1, 2, 3]),
+        )
+        .unwrap();
+
+    // Parallel tests with the same label get their own directories
+    let dir = TempFixtureDir::with_accounts("tests::temp dir", &accounts).unwrap();
+    let other = TempFixtureDir::new("tests::temp dir").unwrap();
+    assert_ne!(dir.path(), other.path());
+    assert!(
+        dir.path()
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("accountgen-tests--temp-dir-")
+    );
+
+    let file = std::fs::read_to_string(dir.path().join(format!("{}.json", pubkey))).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&file).unwrap();
+    assert_eq!(json["pubkey"], pubkey.to_string());
+    assert_eq!(json["account"]["lamports"], 42);
+    assert_eq!(json["account"]["data"][0], "AQID");
+    assert_eq!(json["account"]["space"], 3);
+
+    let fixture = dir.write_fixture("accounts.json", &accounts).unwrap();
+    assert_eq!(load_account_map(&fixture).unwrap().len(), 1);
+
+    let (path, other_path) = (dir.path().to_path_buf(), other.keep());
+    drop(dir);
+    assert!(!path.exists());
+    assert!(other_path.exists());
+    std::fs::remove_dir_all(other_path).unwrap();
+}
+
+fn transfer(from: Pubkey, to: Pubkey, lamports: u64) -> Instruction {
+    // SystemInstruction::Transfer
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction::new_with_bytes(
+        system_program::id(),
+        &data,
+        vec![AccountMeta::new(from, true), AccountMeta::new(to, false)],
+    )
+}
+
+#[test]
+fn test_scenario_file_round_trip() {
+    let path = std::env::temp_dir().join(format!(
+        "solana-accountgen-scenario-{}.json",
+        std::process::id()
+    ));
+
+    let from = Pubkey::new_unique();
+    let to = Pubkey::new_unique();
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(from, AccountBuilder::new().balance(1_000))
+        .unwrap();
+    let instructions: InstructionSet = vec![transfer(from, to, 10), transfer(to, from, 5)].into();
+
+    save_scenario(&path, &accounts, &instructions).unwrap();
+
+    // Account-only readers skip instruction records
+    let only_accounts = load_account_map(&path).unwrap();
+    let (loaded_accounts, loaded_instructions) = load_scenario(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(only_accounts.len(), 1);
+    assert_eq!(
+        loaded_accounts.get_account(&from),
+        accounts.get_account(&from)
+    );
+    assert_eq!(loaded_instructions, instructions);
+}
+
+#[tokio::test]
+async fn test_instruction_set_replay() {
+    let recipient = Pubkey::new_unique();
+
+    let mut program_test = ProgramTest::default();
+    program_test
+        .add_account_with_builder(recipient, AccountBuilder::new().balance(1_000_000))
+        .unwrap();
+    let (mut banks_client, payer, _) = program_test.start().await;
+
+    let instructions: InstructionSet = vec![
+        transfer(payer.pubkey(), recipient, 100),
+        transfer(payer.pubkey(), recipient, 200),
+    ]
+    .into();
+    instructions
+        .replay(&mut banks_client, &payer, &[])
+        .await
+        .unwrap();
+
+    let account = banks_client.get_account(recipient).await.unwrap().unwrap();
+    assert_eq!(account.lamports, 1_000_300);
+}
+
+#[test]
+fn test_fixture_references_follow_program_bindings() {
+    let mint = AccountBuilder::new()
+        .balance(1_000)
+        .data_raw(vec![7; 82])
+        .build();
+    let mint_hash = hex::encode(content_hash(&mint));
+
+    // The manifest names the program; each environment binds it to its own ID
+    let manifest = format!(
+        concat!(
+            r#"{{"pubkey":"{mint}","lamports":1000,"owner":{{"program":"vault"}},"executable":false,"rentEpoch":0,"data":["{data}","base64"]}}"#,
+            "\n",
+            r#"{{"pubkey":{{"pda":{{"program":"vault","seeds":[{{"utf8":"vault"}},{{"pubkey":{{"contentHash":"{hash}"}}}},{{"u8":1}}]}}}},"lamports":5000,"owner":{{"program":"vault"}},"executable":false,"rentEpoch":0,"data":["","base64"]}}"#,
+            "\n",
+            r#"{{"programId":{{"program":"vault"}},"accounts":[{{"pubkey":{{"contentHash":"{hash}"}},"isSigner":false,"isWritable":true}}],"data":["","base64"]}}"#,
+        ),
+        mint = Pubkey::new_unique(),
+        data = base64::encode(&mint.data),
+        hash = mint_hash,
+    );
+
+    for _ in 0..2 {
+        let program_id = Pubkey::new_unique();
+        let resolver = RefResolver::new().bind_program("vault", program_id);
+        let (accounts, instructions) = resolver.read_scenario(manifest.as_bytes()).unwrap();
+
+        let resolved_mint = resolver
+            .resolve(&AccountRef::content_of(&mint), &accounts)
+            .unwrap();
+        let (vault, _) =
+            Pubkey::find_program_address(&[b"vault", resolved_mint.as_ref(), &[1]], &program_id);
+        let vault_account = accounts.get_account(&vault).unwrap();
+        assert_eq!(vault_account.lamports, 5000);
+        assert_eq!(vault_account.owner, program_id);
+
+        let instruction = instructions.iter().next().unwrap();
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts[0].pubkey, resolved_mint);
+    }
+}
+
+#[test]
+fn test_fixture_reference_round_trip_and_errors() {
+    let reference = AccountRef::pda(
+        "vault",
+        vec![
+            Seed::Hex(vec![0xab, 0xcd]),
+            Seed::U64(42),
+            Seed::Pubkey(AccountRef::program("vault")),
+        ],
+    );
+    let json = serde_json::to_string(&reference).unwrap();
+    assert_eq!(
+        json,
+        r#"{"pda":{"program":"vault","seeds":[{"hex":"abcd"},{"u64":42},{"pubkey":{"program":"vault"}}]}}"#
+    );
+    assert_eq!(
+        serde_json::from_str::<AccountRef>(&json).unwrap(),
+        reference
+    );
+
+    // Unbound names that are not addresses cannot be resolved
+    let err = RefResolver::new()
+        .resolve(&reference, &AccountMap::new())
+        .unwrap_err();
+    assert!(err.to_string().contains("unknown program vault"));
+
+    // A content hash must identify exactly one account
+    let account = AccountBuilder::new().balance(1).build();
+    let mut accounts = AccountMap::new();
+    accounts.set_account(Pubkey::new_unique(), account.clone());
+    accounts.set_account(Pubkey::new_unique(), account.clone());
+    let err = RefResolver::new()
+        .resolve(&AccountRef::content_of(&account), &accounts)
+        .unwrap_err();
+    assert!(err.to_string().contains("more than one account"));
+
+    let line = r#"{"pubkey":{"contentHash":"00"},"lamports":1,"owner":"11111111111111111111111111111111","executable":false,"rentEpoch":0,"data":["","base64"]}"#;
+    let err = RefResolver::new()
+        .read_scenario(line.as_bytes())
+        .unwrap_err();
+    assert!(err.to_string().contains("fixture line 1"));
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_fixture_round_trip() {
+    use solana_accountgen::fixture::SqliteFixture;
+
+    let path = std::env::temp_dir().join(format!(
+        "solana-accountgen-fixture-{}.sqlite",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let program_id = Pubkey::new_unique();
+    let mut accounts = AccountMap::new();
+    for len in [0, 8, 100] {
+        let builder = AccountBuilder::new()
+            .balance(u64::MAX)
+            .owner(program_id)
+            .rent_epoch(u64::MAX)
+            .data_raw(vec![len as u8; len]);
+        accounts
+            .add_with_builder(Pubkey::new_unique(), builder)
+            .unwrap();
+    }
+    let wallet = Pubkey::new_unique();
+    accounts
+        .add_with_builder(wallet, AccountBuilder::new().balance(5))
+        .unwrap();
+
+    let mut fixture = SqliteFixture::open(&path).unwrap();
+    assert_eq!(fixture.insert_accounts(accounts.iter()).unwrap(), 4);
+    drop(fixture);
+
+    let mut fixture = SqliteFixture::open(&path).unwrap();
+    assert_eq!(fixture.len().unwrap(), 4);
+    assert_eq!(
+        fixture.get_account(&wallet).unwrap().as_ref(),
+        accounts.get_account(&wallet)
+    );
+    assert_eq!(fixture.pubkeys_by_owner(&program_id).unwrap().len(), 3);
+    assert_eq!(fixture.accounts_by_data_len(1..=8).unwrap().len(), 1);
+    assert_eq!(fixture.accounts_by_data_len(..8).unwrap().len(), 2);
+
+    let loaded = fixture.load_account_map().unwrap();
+    for (pubkey, account) in accounts.iter() {
+        assert_eq!(loaded.get_account(pubkey), Some(account));
+    }
+
+    let mut seen = Vec::new();
+    fixture
+        .for_each_account(|pubkey, _| {
+            seen.push(pubkey);
+            Ok(())
+        })
+        .unwrap();
+    assert!(seen.is_sorted());
+    assert_eq!(seen.len(), 4);
+
+    assert!(fixture.remove_account(&wallet).unwrap());
+    assert!(fixture.get_account(&wallet).unwrap().is_none());
+    std::fs::remove_file(&path).unwrap();
+}
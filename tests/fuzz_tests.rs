@@ -0,0 +1,173 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use solana_accountgen::AccountGenError;
+use solana_accountgen::extensions::anchor::get_account_discriminator;
+use solana_accountgen::extensions::fuzz::IdlFuzzer;
+use solana_accountgen::extensions::idl::{Idl, IdlType};
+
+const IDL: &str = r#"{
+    "address": "11111111111111111111111111111111",
+    "metadata": { "name": "arena", "version": "0.1.0", "spec": "0.1.0" },
+    "instructions": [],
+    "accounts": [{ "name": "Game", "discriminator": [27, 90, 166, 125, 74, 100, 121, 18] }],
+    "types": [
+        {
+            "name": "Game",
+            "type": {
+                "kind": "struct",
+                "fields": [
+                    { "name": "host", "type": "pubkey" },
+                    { "name": "players", "type": { "vec": { "defined": { "name": "Player" } } } },
+                    { "name": "phase", "type": { "defined": { "name": "Phase" } } },
+                    { "name": "prize", "type": { "option": "u128" } },
+                    { "name": "board", "type": { "array": ["i8", 9] } },
+                    { "name": "title", "type": "string" },
+                    { "name": "seed", "type": "bytes" },
+                    { "name": "odds", "type": "f64" },
+                    { "name": "round", "type": { "defined": { "name": "Round" } } }
+                ]
+            }
+        },
+        {
+            "name": "Player",
+            "type": {
+                "kind": "struct",
+                "fields": [
+                    { "name": "key", "type": "pubkey" },
+                    { "name": "score", "type": "i64" },
+                    { "name": "ready", "type": "bool" }
+                ]
+            }
+        },
+        {
+            "name": "Phase",
+            "type": {
+                "kind": "enum",
+                "variants": [
+                    { "name": "Lobby" },
+                    { "name": "Playing", "fields": [{ "name": "turn", "type": "u16" }] },
+                    { "name": "Finished", "fields": ["pubkey", "i32"] }
+                ]
+            }
+        },
+        { "name": "Round", "type": { "kind": "type", "alias": "u32" } },
+        {
+            "name": "Tree",
+            "type": {
+                "kind": "struct",
+                "fields": [
+                    { "name": "value", "type": "u8" },
+                    { "name": "children", "type": { "vec": { "defined": { "name": "Tree" } } } }
+                ]
+            }
+        },
+        {
+            "name": "Loop",
+            "type": {
+                "kind": "enum",
+                "variants": [{ "name": "Again", "fields": [{ "defined": { "name": "Loop" } }] }]
+            }
+        }
+    ]
+}"#;
+
+#[test]
+fn test_random_accounts_decode_as_their_type() {
+    let idl = Idl::from_json(IDL).unwrap();
+    let schema = idl.to_fixture_schema().unwrap();
+    let fuzzer = IdlFuzzer::new(idl).max_len(3);
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut phases = [0; 3];
+    for _ in 0..500 {
+        let data = fuzzer.random_account("Game", &mut rng).unwrap();
+        assert_eq!(data[..8], [27, 90, 166, 125, 74, 100, 121, 18]);
+        let game = schema.decode("Game", &data[8..]).unwrap();
+        assert!(game["players"].as_array().unwrap().len() <= 3);
+        assert!(game["title"].as_str().unwrap().len() <= 3);
+        let phase = game["phase"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        phases[["Lobby", "Playing", "Finished"]
+            .iter()
+            .position(|name| *name == phase)
+            .unwrap()] += 1;
+    }
+    // Every variant turns up
+    assert!(phases.iter().all(|count| *count > 0), "{:?}", phases);
+}
+
+#[test]
+fn test_random_accounts_replay_from_seed() {
+    let fuzzer = IdlFuzzer::new(Idl::from_json(IDL).unwrap());
+    let generate = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..10)
+            .map(|_| fuzzer.random_account("Game", &mut rng).unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(generate(1), generate(1));
+    assert_ne!(generate(1), generate(2));
+}
+
+#[test]
+fn test_random_values_and_legacy_accounts() {
+    let idl = Idl::from_json(IDL).unwrap();
+    let schema = idl.to_fixture_schema().unwrap();
+    let fuzzer = IdlFuzzer::new(idl).max_len(0);
+    let mut rng = StdRng::seed_from_u64(3);
+
+    // With no room for elements, vecs, strings and bytes are empty
+    let vec_of_u64 = IdlType::Vec(Box::new(IdlType::Primitive("u64".to_string())));
+    assert_eq!(fuzzer.random_value(&vec_of_u64, &mut rng).unwrap(), [0; 4]);
+    let player = IdlType::Defined("Player".to_string());
+    let data = fuzzer.random_value(&player, &mut rng).unwrap();
+    assert_eq!(data.len(), 32 + 8 + 1);
+    assert!(schema.decode("Player", &data).is_ok());
+
+    // Types that contain themselves stay finite, or are rejected when they
+    // cannot end
+    let tree = IdlType::Defined("Tree".to_string());
+    let fuzzer = fuzzer.max_len(8);
+    for _ in 0..20 {
+        let data = fuzzer.random_value(&tree, &mut rng).unwrap();
+        assert!(schema.decode("Tree", &data).is_ok());
+    }
+    let error = fuzzer
+        .random_value(&IdlType::Defined("Loop".to_string()), &mut rng)
+        .unwrap_err();
+    assert!(error.to_string().contains("Loop nests more than"));
+    assert!(matches!(
+        fuzzer.random_account("Missing", &mut rng),
+        Err(AccountGenError::InvalidDataFormat(_))
+    ));
+    // Primitives built by hand rather than parsed are checked too
+    let u256 = IdlType::Primitive("u256".to_string());
+    assert!(matches!(
+        fuzzer.random_value(&u256, &mut rng),
+        Err(AccountGenError::InvalidDataFormat(_))
+    ));
+
+    // Legacy IDLs keep the layout on the account and derive the
+    // discriminator from its name
+    let legacy = Idl::from_json(
+        r#"{
+            "name": "counter",
+            "instructions": [],
+            "accounts": [{
+                "name": "Counter",
+                "type": { "kind": "struct", "fields": [{ "name": "count", "type": "u64" }] }
+            }]
+        }"#,
+    )
+    .unwrap();
+    let data = IdlFuzzer::new(legacy)
+        .random_account("Counter", &mut rng)
+        .unwrap();
+    assert_eq!(data[..8], get_account_discriminator("Counter"));
+    assert_eq!(data.len(), 16);
+}
@@ -0,0 +1,51 @@
+use solana_accountgen::extensions::genesis::{
+    GenesisAccounts, VALIDATOR_IDENTITY_LAMPORTS, ValidatorAccounts,
+};
+use solana_accountgen::serialization::bincode::legacy::deserialize_account_data;
+use solana_accountgen::well_known::{STAKE_PROGRAM, SYSTEM_PROGRAM, VOTE_PROGRAM};
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+use solana_stake_interface::state::StakeStateV2;
+use solana_vote_interface::state::VoteStateVersions;
+
+#[test]
+fn test_genesis_validators_are_consistent() {
+    let mut genesis = GenesisAccounts::new();
+    let identities = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let validators: Vec<_> = identities
+        .iter()
+        .zip([5, 100])
+        .map(|(identity, commission)| genesis.add_validator(*identity, commission, 42_000_000))
+        .collect();
+    assert_eq!(genesis.len(), 6);
+    assert_eq!(validators[0], ValidatorAccounts::new(identities[0]));
+    assert_ne!(validators[0].vote, validators[1].vote);
+
+    for (validator, commission) in validators.iter().zip([5, 100]) {
+        let identity = genesis.get_account(&validator.identity).unwrap();
+        assert_eq!(identity.owner, SYSTEM_PROGRAM);
+        assert_eq!(identity.lamports, VALIDATOR_IDENTITY_LAMPORTS);
+
+        let vote = genesis.get_account(&validator.vote).unwrap();
+        assert_eq!(vote.owner, VOTE_PROGRAM);
+        assert!(Rent::default().is_exempt(vote.lamports, vote.data.len()));
+        let vote_state = deserialize_account_data::<VoteStateVersions>(vote)
+            .unwrap()
+            .convert_to_current();
+        assert_eq!(vote_state.node_pubkey, validator.identity);
+        assert_eq!(vote_state.authorized_withdrawer, validator.identity);
+        assert_eq!(vote_state.commission, commission);
+
+        let stake = genesis.get_account(&validator.stake).unwrap();
+        assert_eq!(stake.owner, STAKE_PROGRAM);
+        let StakeStateV2::Stake(meta, state, _) = deserialize_account_data(stake).unwrap() else {
+            panic!("stake account is not delegated");
+        };
+        assert_eq!(meta.authorized.staker, validator.identity);
+        assert_eq!(stake.lamports, meta.rent_exempt_reserve + 42_000_000);
+        assert_eq!(state.delegation.voter_pubkey, validator.vote);
+        assert_eq!(state.delegation.stake, 42_000_000);
+        // Bootstrap stake is active from the first epoch
+        assert_eq!(state.delegation.activation_epoch, u64::MAX);
+    }
+}
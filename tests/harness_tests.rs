@@ -0,0 +1,244 @@
+use solana_account_info::AccountInfo;
+use solana_accountgen::AccountBuilder;
+use solana_accountgen::harness::{AccountFrames, LocalHarness};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_program_error::{ProgramError, ProgramResult};
+use solana_pubkey::Pubkey;
+
+/// Test program: the first data byte selects the operation.
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let account = &accounts[0];
+    match data[0] {
+        // Move lamports from account 0 to account 1
+        0 => {
+            let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+            **account.try_borrow_mut_lamports()? -= amount;
+            **accounts[1].try_borrow_mut_lamports()? += amount;
+        }
+        // Grow the data and fill it
+        1 => {
+            let new_len = account.data_len() + data[1] as usize;
+            account.realloc(new_len, true)?;
+            account.try_borrow_mut_data()?.fill(7);
+        }
+        // Take ownership of an account
+        2 => account.assign(program_id),
+        // Write then fail
+        3 => {
+            account.try_borrow_mut_data()?[0] = 1;
+            return Err(ProgramError::Custom(42));
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
+
+fn setup() -> (LocalHarness, Pubkey, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let account = Pubkey::new_unique();
+    let mut harness = LocalHarness::new(program_id, process_instruction);
+    harness
+        .add_with_builder(
+            account,
+            AccountBuilder::new()
+                .balance(1_000)
+                .owner(program_id)
+                .data_raw(vec![0; 4]),
+        )
+        .unwrap();
+    (harness, program_id, account)
+}
+
+#[test]
+fn test_harness_transfers_lamports() {
+    let (mut harness, program_id, from) = setup();
+    let to = Pubkey::new_unique();
+
+    let mut data = vec![0];
+    data.extend_from_slice(&300u64.to_le_bytes());
+    let ix = Instruction::new_with_bytes(
+        program_id,
+        &data,
+        vec![AccountMeta::new(from, false), AccountMeta::new(to, false)],
+    );
+    harness.process_instruction(&ix).unwrap();
+
+    assert_eq!(harness.get_account(&from).unwrap().lamports, 700);
+    // Missing accounts start out empty and are created on write
+    assert_eq!(harness.get_account(&to).unwrap().lamports, 300);
+}
+
+#[test]
+fn test_harness_realloc_and_assign() {
+    let (mut harness, program_id, account) = setup();
+    let fresh = Pubkey::new_unique();
+
+    let grow =
+        Instruction::new_with_bytes(program_id, &[1, 12], vec![AccountMeta::new(account, false)]);
+    harness.process_instruction(&grow).unwrap();
+    assert_eq!(harness.get_account(&account).unwrap().data, vec![7; 16]);
+
+    let assign = Instruction::new_with_bytes(program_id, &[2], vec![AccountMeta::new(fresh, true)]);
+    harness.process_instruction(&assign).unwrap();
+    assert_eq!(harness.get_account(&fresh).unwrap().owner, program_id);
+}
+
+#[test]
+fn test_harness_rejects_readonly_writes_and_rolls_back() {
+    let (mut harness, program_id, account) = setup();
+
+    let readonly = Instruction::new_with_bytes(
+        program_id,
+        &[1, 4],
+        vec![AccountMeta::new_readonly(account, false)],
+    );
+    assert_eq!(
+        harness.process_instruction(&readonly),
+        Err(ProgramError::Immutable)
+    );
+
+    let failing =
+        Instruction::new_with_bytes(program_id, &[3], vec![AccountMeta::new(account, false)]);
+    assert_eq!(
+        harness.process_instruction(&failing),
+        Err(ProgramError::Custom(42))
+    );
+    assert_eq!(harness.get_account(&account).unwrap().data, vec![0; 4]);
+}
+
+#[test]
+fn test_harness_duplicate_accounts_share_state() {
+    let (mut harness, program_id, account) = setup();
+
+    // Transferring to yourself leaves the balance unchanged
+    let mut data = vec![0];
+    data.extend_from_slice(&100u64.to_le_bytes());
+    let ix = Instruction::new_with_bytes(
+        program_id,
+        &data,
+        vec![
+            AccountMeta::new(account, false),
+            AccountMeta::new(account, false),
+        ],
+    );
+    harness.process_instruction(&ix).unwrap();
+
+    assert_eq!(harness.get_account(&account).unwrap().lamports, 1_000);
+}
+
+/// Test program that transfers from a vault PDA via the system program.
+fn process_vault_withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (vault, bump) = Pubkey::find_program_address(&[b"vault"], program_id);
+    let mut transfer = 2u32.to_le_bytes().to_vec();
+    transfer.extend_from_slice(data);
+    let instruction = Instruction::new_with_bytes(
+        solana_sdk_ids::system_program::id(),
+        &transfer,
+        vec![
+            AccountMeta::new(vault, true),
+            AccountMeta::new(*accounts[1].key, false),
+        ],
+    );
+    solana_program::program::invoke_signed(&instruction, accounts, &[&[b"vault", &[bump]]])
+}
+
+#[test]
+fn test_harness_records_cpis() {
+    let program_id = Pubkey::new_unique();
+    let (vault, _) = Pubkey::find_program_address(&[b"vault"], &program_id);
+    let recipient = Pubkey::new_unique();
+
+    let mut harness = LocalHarness::new(program_id, process_vault_withdraw);
+    harness
+        .add_with_builder(vault, AccountBuilder::new().balance(1_000))
+        .unwrap();
+
+    let ix = Instruction::new_with_bytes(
+        program_id,
+        &500u64.to_le_bytes(),
+        vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient, false),
+        ],
+    );
+    harness.process_instruction(&ix).unwrap();
+
+    let cpis = harness.cpis();
+    assert_eq!(cpis.len(), 1);
+    assert_eq!(cpis[0].caller, program_id);
+    assert_eq!(cpis[0].program_id(), solana_sdk_ids::system_program::id());
+    assert_eq!(cpis[0].instruction.accounts[1].pubkey, recipient);
+    assert_eq!(cpis[0].signer_pdas().unwrap(), vec![vault]);
+
+    // The invocation was recorded, not executed
+    assert_eq!(harness.get_account(&vault).unwrap().lamports, 1_000);
+}
+
+#[tokio::test]
+async fn test_harness_records_cpis_after_program_test_starts() {
+    let program_id = Pubkey::new_unique();
+    let (vault, _) = Pubkey::find_program_address(&[b"vault"], &program_id);
+    let mut harness = LocalHarness::new(program_id, process_vault_withdraw);
+    harness
+        .add_with_builder(vault, AccountBuilder::new().balance(1_000))
+        .unwrap();
+    let ix = Instruction::new_with_bytes(
+        program_id,
+        &500u64.to_le_bytes(),
+        vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ],
+    );
+    harness.process_instruction(&ix).unwrap();
+    assert_eq!(harness.take_cpis().len(), 1);
+
+    // Setting up a bank replaces the syscall stubs the first time it runs
+    let _context = solana_program_test::ProgramTest::default()
+        .start_with_context()
+        .await;
+
+    harness.process_instruction(&ix).unwrap();
+    assert_eq!(harness.cpis().len(), 1);
+    assert_eq!(harness.cpis()[0].signer_pdas().unwrap(), vec![vault]);
+}
+
+#[test]
+fn test_account_frames_share_duplicates() {
+    let pubkey = Pubkey::new_unique();
+    let mut accounts = solana_accountgen::AccountMap::new();
+    accounts
+        .add_with_builder(pubkey, AccountBuilder::new().data_raw(vec![0; 2]))
+        .unwrap();
+
+    let mut frames = AccountFrames::load(
+        &accounts,
+        &[
+            AccountMeta::new(pubkey, true),
+            AccountMeta::new_readonly(pubkey, false),
+        ],
+    );
+    {
+        let infos = frames.account_infos();
+        assert!(infos[0].is_signer && !infos[1].is_writable);
+
+        infos[0].try_borrow_mut_data().unwrap()[1] = 3;
+        assert_eq!(infos[1].try_borrow_data().unwrap()[1], 3);
+        // Both infos share one RefCell, as in the runtime
+        assert!(infos[1].try_borrow_data().is_ok());
+        let _guard = infos[0].try_borrow_mut_data().unwrap();
+        assert!(infos[1].try_borrow_data().is_err());
+    }
+    assert_eq!(accounts.get_account(&pubkey).unwrap().data, vec![0, 0]);
+
+    frames.commit(&mut accounts).unwrap();
+    assert_eq!(accounts.get_account(&pubkey).unwrap().data, vec![0, 3]);
+}
@@ -0,0 +1,3 @@
+fn main() {
+    solana_accountgen::extensions::idl::build("idl/amm.json").unwrap();
+}
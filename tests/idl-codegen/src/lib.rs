@@ -0,0 +1,45 @@
+//! Builds the code generated for `idl/amm.json`, so changes to the
+//! generator that emit invalid Rust fail to compile here.
+
+pub mod amm {
+    solana_accountgen::accountgen_idl!("idl/amm.json");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::amm::{CurveType, Pool};
+    use solana_accountgen::extensions::anchor::{
+        create_anchor_account, deserialize_anchor_account, get_account_discriminator,
+    };
+    use solana_pubkey::Pubkey;
+
+    #[test]
+    fn test_generated_account_round_trips() {
+        let pool = Pool {
+            authority: Pubkey::new_unique(),
+            last_updated: -7,
+            r#type: CurveType::Offset(3, true),
+            fees: Some([1, 2, 3, 4]),
+            history: vec![10, 20],
+            name: "sol-usdc".to_string(),
+        };
+        assert_eq!(Pool::DISCRIMINATOR, get_account_discriminator("Pool"));
+
+        let account =
+            create_anchor_account("Pool", Pubkey::new_unique(), pool.clone(), 1_000_000).unwrap();
+        assert_eq!(account.data[..8], Pool::DISCRIMINATOR);
+        assert_eq!(deserialize_anchor_account::<Pool>(&account).unwrap(), pool);
+
+        let stable = Pool {
+            r#type: CurveType::Stable { amp: 100 },
+            fees: None,
+            ..pool
+        };
+        let account =
+            create_anchor_account("Pool", Pubkey::new_unique(), stable.clone(), 1).unwrap();
+        assert_eq!(
+            deserialize_anchor_account::<Pool>(&account).unwrap(),
+            stable
+        );
+    }
+}
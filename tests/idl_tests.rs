@@ -0,0 +1,185 @@
+use solana_accountgen::AccountGenError;
+use solana_accountgen::extensions::anchor::{
+    DiscriminatorKind, get_account_discriminator, get_event_discriminator,
+    get_method_discriminator, identify_discriminator,
+};
+use solana_accountgen::extensions::idl::{Idl, IdlType, generate_file};
+use std::path::Path;
+
+const IDL: &str = r#"{
+    "address": "11111111111111111111111111111111",
+    "metadata": { "name": "amm", "version": "0.1.0", "spec": "0.1.0" },
+    "instructions": [],
+    "accounts": [{ "name": "Pool", "discriminator": [241, 154, 109, 4, 17, 177, 109, 188] }],
+    "types": [
+        {
+            "name": "Pool",
+            "type": {
+                "kind": "struct",
+                "fields": [
+                    { "name": "authority", "type": "pubkey" },
+                    { "name": "lastUpdated", "type": "i64" },
+                    { "name": "type", "type": { "defined": { "name": "CurveType" } } },
+                    { "name": "fees", "type": { "option": { "array": ["u16", 4] } } },
+                    { "name": "history", "type": { "vec": "u64" } },
+                    { "name": "name", "type": "string" }
+                ]
+            }
+        },
+        {
+            "name": "CurveType",
+            "type": {
+                "kind": "enum",
+                "variants": [
+                    { "name": "ConstantProduct" },
+                    { "name": "Stable", "fields": [{ "name": "amp", "type": "u64" }] },
+                    { "name": "Offset", "fields": ["u64", "bool"] }
+                ]
+            }
+        }
+    ]
+}"#;
+
+const LEGACY_IDL: &str = r#"{
+    "version": "0.1.0",
+    "name": "counter",
+    "instructions": [],
+    "accounts": [{
+        "name": "Counter",
+        "type": { "kind": "struct", "fields": [
+            { "name": "count", "type": "u64" },
+            { "name": "owner", "type": "publicKey" },
+            { "name": "state", "type": { "defined": "CounterState" } }
+        ] }
+    }],
+    "types": [{ "name": "CounterState", "type": { "kind": "enum", "variants": [{ "name": "Open" }, { "name": "Closed" }] } }]
+}"#;
+
+#[test]
+fn test_idl_generates_structs_and_enums() {
+    let idl = Idl::from_json(IDL).unwrap();
+    assert_eq!(idl.program_name(), Some("amm"));
+
+    let code = idl.to_rust().unwrap();
+    assert!(code.starts_with("// @generated by solana-accountgen from the amm IDL."));
+    assert!(code.contains(
+        "pub struct Pool {\n    pub authority: ::solana_pubkey::Pubkey,\n    pub last_updated: i64,\n    pub r#type: CurveType,\n    pub fees: Option<[u16; 4]>,\n    pub history: Vec<u64>,\n    pub name: String,\n}"
+    ));
+    assert!(code.contains(
+        "pub enum CurveType {\n    ConstantProduct,\n    Stable { amp: u64 },\n    Offset(u64, bool),\n}"
+    ));
+    assert!(code.contains(
+        "impl Pool {\n    pub const DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];\n}"
+    ));
+    assert_eq!(
+        code.matches("#[borsh(crate = \"::solana_accountgen::borsh\")]")
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn test_legacy_idl_derives_discriminators() {
+    let idl = Idl::from_json(LEGACY_IDL).unwrap();
+    assert_eq!(idl.program_name(), Some("counter"));
+    assert_eq!(
+        idl.account_discriminator(&idl.accounts[0]),
+        get_account_discriminator("Counter")
+    );
+
+    let code = idl.to_rust().unwrap();
+    assert!(code.contains("pub struct Counter {\n    pub count: u64,\n    pub owner: ::solana_pubkey::Pubkey,\n    pub state: CounterState,\n}"));
+    assert!(code.contains(&format!(
+        "pub const DISCRIMINATOR: [u8; 8] = {:?};",
+        get_account_discriminator("Counter")
+    )));
+}
+
+#[test]
+fn test_idl_rejects_unsupported_types() {
+    let idl = r#"{ "types": [{ "name": "Big", "type": { "kind": "struct", "fields": [{ "name": "x", "type": "u256" }] } }] }"#;
+    let err = Idl::from_json(idl).unwrap_err();
+    assert!(err.to_string().contains("unsupported IDL type u256"));
+
+    let idl =
+        r#"{ "accounts": [{ "name": "Missing", "discriminator": [0, 0, 0, 0, 0, 0, 0, 0] }] }"#;
+    let err = Idl::from_json(idl).unwrap().to_rust().unwrap_err();
+    assert!(err.to_string().contains("Missing has no type definition"));
+
+    assert_eq!(
+        serde_json::from_str::<IdlType>(r#"{ "vec": { "defined": "Thing" } }"#).unwrap(),
+        IdlType::Vec(Box::new(IdlType::Defined("Thing".to_string())))
+    );
+}
+
+#[test]
+fn test_generate_file_mirrors_idl_path() {
+    let out_dir = std::env::temp_dir().join(format!("accountgen_idl_{}", std::process::id()));
+    let idl_path = Path::new("tests/idl-codegen/idl/amm.json");
+    let out_path = generate_file(idl_path, &out_dir).unwrap();
+    let code = std::fs::read_to_string(&out_path).unwrap();
+    std::fs::remove_dir_all(&out_dir).unwrap();
+
+    assert_eq!(
+        out_path,
+        out_dir.join("accountgen_idl/tests/idl-codegen/idl/amm.json.rs")
+    );
+    assert!(code.contains("pub struct Pool"));
+}
+
+#[test]
+fn test_generate_file_rejects_paths_leaving_out_dir() {
+    let out_dir = std::env::temp_dir().join("accountgen_idl_rejected");
+    for idl_path in ["../idl/amm.json", "/idl/amm.json", "idl/../../amm.json"] {
+        let err = generate_file(Path::new(idl_path), &out_dir).unwrap_err();
+        assert!(
+            matches!(err, AccountGenError::InvalidDataFormat(_)),
+            "{idl_path}"
+        );
+    }
+    assert!(!out_dir.exists());
+}
+
+#[test]
+fn test_idl_fixture_schema_decodes_accounts() {
+    let idl = Idl::from_json(LEGACY_IDL).unwrap();
+    let schema = idl.to_fixture_schema().unwrap();
+    assert_eq!(schema.types, ["Counter"]);
+
+    let owner = solana_pubkey::Pubkey::new_unique();
+    let mut data = 9u64.to_le_bytes().to_vec();
+    data.extend_from_slice(owner.as_ref());
+    data.push(1);
+    assert_eq!(
+        schema.decode("Counter", &data).unwrap(),
+        serde_json::json!({ "count": 9, "owner": owner.to_string(), "state": { "Closed": null } })
+    );
+}
+
+#[test]
+fn test_idl_identifies_discriminators() {
+    let idl = Idl::from_json(IDL).unwrap();
+    let matches = idl.identify_discriminator(&[241, 154, 109, 4, 17, 177, 109, 188]);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].to_string(), "account Pool");
+    assert!(idl.identify_discriminator(&[0; 8]).is_empty());
+
+    // Legacy IDLs derive them from the names
+    let legacy = Idl::from_json(LEGACY_IDL).unwrap();
+    let matches = legacy.identify_discriminator(&get_account_discriminator("Counter"));
+    assert_eq!(matches[0].kind, DiscriminatorKind::Account);
+}
+
+#[test]
+fn test_identify_discriminator_from_word_list() {
+    let words = "Counter\n\nCounterIncremented\nincrementCounter\n";
+    let event = get_event_discriminator("CounterIncremented");
+    let matches = identify_discriminator(&event, words.lines());
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].to_string(), "event CounterIncremented");
+
+    let method = get_method_discriminator("increment_counter");
+    let matches = identify_discriminator(&method, words.lines());
+    assert_eq!(matches[0].to_string(), "instruction increment_counter");
+    assert!(identify_discriminator(&[0; 8], words.lines()).is_empty());
+}
@@ -0,0 +1,93 @@
+use solana_accountgen::AccountBuilder;
+use solana_accountgen::decoder::DecoderRegistry;
+use solana_accountgen::instructions::{compute_budget, memo, system};
+use solana_keypair::Keypair;
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+#[test]
+fn test_instructions_decode() {
+    let registry = DecoderRegistry::new();
+    let (from, to, owner) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let cases = [
+        (
+            compute_budget::set_compute_unit_limit(300_000),
+            "setComputeUnitLimit",
+            r#"{"units":300000}"#,
+        ),
+        (
+            compute_budget::set_compute_unit_price(7),
+            "setComputeUnitPrice",
+            r#"{"microLamports":7}"#,
+        ),
+        (
+            compute_budget::request_heap_frame(64 * 1024),
+            "requestHeapFrame",
+            r#"{"bytes":65536}"#,
+        ),
+        (
+            system::create_account(&from, &to, 10, 64, &owner),
+            "createAccount",
+            &format!(r#"{{"lamports":10,"owner":"{owner}","space":64}}"#),
+        ),
+        (system::allocate(&to, 8), "allocate", r#"{"space":8}"#),
+        (
+            system::create_account_with_seed(&from, &to, &from, "seed", 1, 2, &owner),
+            "createAccountWithSeed",
+            &format!(
+                r#"{{"base":"{from}","lamports":1,"owner":"{owner}","seed":"seed","space":2}}"#
+            ),
+        ),
+        (memo("hi", &[&from]), "memo", r#"{"memo":"hi"}"#),
+    ];
+    for (instruction, name, args) in cases {
+        let decoded = registry.decode_instruction(&instruction).unwrap();
+        assert_eq!(decoded.name, name);
+        assert_eq!(decoded.args.to_string(), args);
+    }
+}
+
+#[tokio::test]
+async fn test_instructions_process() {
+    let (banks_client, payer, blockhash) = ProgramTest::default().start().await;
+    let (recipient, base) = (Pubkey::new_unique(), Keypair::new());
+    let owner = Pubkey::new_unique();
+    let (seeded, _) = AccountBuilder::new()
+        .create_seeded(&base.pubkey(), "vault", &owner)
+        .unwrap();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            compute_budget::set_compute_unit_limit(400_000),
+            compute_budget::set_compute_unit_price(1),
+            system::transfer(&payer.pubkey(), &recipient, 5_000_000),
+            system::create_account_with_seed(
+                &payer.pubkey(),
+                &seeded,
+                &base.pubkey(),
+                "vault",
+                10_000_000,
+                16,
+                &owner,
+            ),
+            memo("paid", &[&payer.pubkey()]),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &base],
+        blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(
+        banks_client.get_balance(recipient).await.unwrap(),
+        5_000_000
+    );
+    let account = banks_client.get_account(seeded).await.unwrap().unwrap();
+    assert_eq!((account.owner, account.data.len()), (owner, 16));
+}
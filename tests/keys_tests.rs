@@ -0,0 +1,150 @@
+use solana_accountgen::instructions::system;
+use solana_accountgen::keys::{IdentityTree, test_keypair, test_pubkey, vanity_pubkey};
+use solana_accountgen::sol;
+use solana_accountgen::tracking::{assert_no_unexpected_transaction_accounts, track_pubkeys};
+use solana_accountgen::{AccountBuilder, AccountMap};
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+#[test]
+fn test_keypair_is_stable_across_runs() {
+    // Pinned so an accidental change to the derivation is caught
+    assert_eq!(
+        test_pubkey("alice").to_string(),
+        "DsVUGTChVutX17tzWtGZf9DfiT18SbK4a3YTmbYT9EPA"
+    );
+    assert_eq!(test_pubkey("alice"), test_keypair("alice").pubkey());
+    assert_ne!(test_pubkey("alice"), test_pubkey("Alice"));
+}
+
+#[test]
+fn test_vanity_pubkey_prefixes() {
+    for prefix in ["AL1CE", "Bob", "zzz", "1", "", "Mint1111", "11111"] {
+        let pubkey = vanity_pubkey(prefix);
+        assert!(
+            pubkey.to_string().starts_with(prefix),
+            "{} does not start with {}",
+            pubkey,
+            prefix
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "cannot start a 32-byte address")]
+fn test_vanity_pubkey_rejects_impossible_prefixes() {
+    // 33 leading zero bytes do not fit in a pubkey
+    vanity_pubkey(&"1".repeat(33));
+}
+
+#[test]
+#[should_panic(expected = "not a base58 character")]
+fn test_vanity_pubkey_rejects_invalid_characters() {
+    vanity_pubkey("B0B");
+}
+
+#[test]
+fn test_tracking_records_generated_pubkeys() {
+    let untracked = test_pubkey("before");
+    let tracking = track_pubkeys();
+    let alice = test_pubkey("alice");
+    let owner = vanity_pubkey("Prog");
+    let (pda, _, _) = AccountBuilder::create_pda(&owner, &[b"vault"], 1, 0u8).unwrap();
+    let stray = Pubkey::new_unique();
+
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(alice, AccountBuilder::new().owner(owner))
+        .unwrap();
+    accounts.set_account(pda, AccountBuilder::new().owner(owner).build());
+    accounts.set_account(stray, AccountBuilder::new().owner(owner).build());
+
+    assert!(!tracking.contains(&untracked));
+    assert_eq!(tracking.unexpected_accounts(&accounts), vec![stray]);
+    tracking.expect(stray);
+    assert!(tracking.unexpected_accounts(&accounts).is_empty());
+
+    drop(tracking);
+    assert!(!track_pubkeys().contains(&alice));
+}
+
+async fn transfer_to(recipient: Pubkey) {
+    let (mut banks_client, payer, blockhash) = ProgramTest::default().start().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[system::transfer(&payer.pubkey(), &recipient, sol!(1))],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    banks_client
+        .process_transaction(transaction.clone())
+        .await
+        .unwrap();
+    assert_no_unexpected_transaction_accounts(&mut banks_client, &[transaction]).await;
+}
+
+#[tokio::test]
+async fn test_no_unexpected_accounts() {
+    let _tracking = track_pubkeys();
+    transfer_to(test_pubkey("bob")).await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "unexpected accounts exist")]
+async fn test_unexpected_account_is_reported() {
+    let _tracking = track_pubkeys();
+    transfer_to(Pubkey::new_unique()).await;
+}
+
+#[test]
+fn test_identity_tree_derivation() {
+    let tree = IdentityTree::from_seed("suite");
+    assert_eq!(tree.payer().pubkey(), test_pubkey("suite/payer"));
+    assert_eq!(tree.user(2).pubkey(), test_pubkey("suite/users/2"));
+    assert_eq!(tree.admins_len(), 1);
+    assert_eq!(tree.mints_len(), 2);
+
+    // Growing and shrinking keeps existing members
+    let grown = IdentityTree::from_seed("suite").users(10).admins(0);
+    assert_eq!(grown.user(2).pubkey(), tree.user(2).pubkey());
+    assert_eq!(grown.users_len(), 10);
+    assert!(grown.get("admins/0").is_none());
+    assert_eq!(grown.iter().count(), 1 + 10 + 2);
+    assert_ne!(
+        IdentityTree::from_seed("other").payer().pubkey(),
+        tree.payer().pubkey()
+    );
+}
+
+#[test]
+fn test_identity_tree_json_round_trip() {
+    let tree = IdentityTree::from_seed("json-suite").mints(3);
+    let json: serde_json::Value = serde_json::from_str(&tree.to_json()).unwrap();
+    assert_eq!(json["seed"], "json-suite");
+    assert_eq!(
+        json["mints"][2]["pubkey"],
+        tree.mint(2).pubkey().to_string()
+    );
+    assert_eq!(json["payer"]["secretKey"].as_array().unwrap().len(), 64);
+
+    let path = std::env::temp_dir().join(format!("identities-{}.json", std::process::id()));
+    tree.save(&path).unwrap();
+    let restored = IdentityTree::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    for ((path, expected), (_, actual)) in tree.iter().zip(restored.iter()) {
+        assert_eq!(expected.pubkey(), actual.pubkey(), "{}", path);
+    }
+
+    // A secret key that does not match its pubkey is rejected
+    let mut tampered = json.clone();
+    tampered["users"][0]["pubkey"] = tree.user(1).pubkey().to_string().into();
+    assert!(IdentityTree::from_json(&tampered.to_string()).is_err());
+}
+
+#[test]
+#[should_panic(expected = "identity tree has 3 users, no index 3")]
+fn test_identity_tree_missing_member() {
+    IdentityTree::from_seed("suite").user(3);
+}
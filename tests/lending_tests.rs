@@ -0,0 +1,81 @@
+use solana_accountgen::extensions::lending::{
+    LendingMarket, MAX_OBLIGATION_RESERVES, OBLIGATION_LEN, Obligation, ObligationCollateral,
+    ObligationLiquidity, WAD, quote_currency,
+};
+use solana_pubkey::Pubkey;
+
+#[test]
+fn test_obligation_round_trip() {
+    let reserve_a = Pubkey::new_unique();
+    let reserve_b = Pubkey::new_unique();
+    let obligation = Obligation {
+        lending_market: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        deposits: vec![ObligationCollateral {
+            deposit_reserve: reserve_a,
+            deposited_amount: 500,
+            market_value: 500 * WAD,
+        }],
+        borrows: vec![ObligationLiquidity {
+            borrow_reserve: reserve_b,
+            borrowed_amount_wads: 100 * WAD,
+            market_value: 200 * WAD,
+            ..ObligationLiquidity::default()
+        }],
+        deposited_value: 500 * WAD,
+        borrowed_value: 200 * WAD,
+        ..Obligation::default()
+    };
+
+    let data = obligation.pack().unwrap();
+    assert_eq!(data.len(), OBLIGATION_LEN);
+    // Deposits and borrows counts follow the header's four values
+    assert_eq!(&data[138..140], &[1, 1]);
+    // The borrow follows the deposit directly
+    assert_eq!(&data[140..172], reserve_a.as_ref());
+    assert_eq!(&data[196..228], reserve_b.as_ref());
+    assert_eq!(Obligation::unpack(&data).unwrap(), obligation);
+}
+
+#[test]
+fn test_obligation_too_many_reserves() {
+    let obligation = Obligation {
+        deposits: vec![ObligationCollateral::default(); MAX_OBLIGATION_RESERVES],
+        borrows: vec![ObligationLiquidity::default()],
+        ..Obligation::default()
+    };
+    assert!(obligation.pack().is_err());
+
+    // Ten borrows are within the count but overrun the account
+    let obligation = Obligation {
+        borrows: vec![ObligationLiquidity::default(); MAX_OBLIGATION_RESERVES],
+        ..Obligation::default()
+    };
+    assert!(obligation.pack().is_err());
+
+    let obligation = Obligation {
+        deposits: vec![ObligationCollateral::default()],
+        borrows: vec![ObligationLiquidity::default(); MAX_OBLIGATION_RESERVES - 1],
+        ..Obligation::default()
+    };
+    let data = obligation.pack().unwrap();
+    assert_eq!(Obligation::unpack(&data).unwrap(), obligation);
+}
+
+#[test]
+fn test_lending_market_layout() {
+    let market = LendingMarket {
+        bump_seed: 254,
+        owner: Pubkey::new_unique(),
+        ..LendingMarket::default()
+    };
+    let data = market.pack();
+    assert_eq!(&data[..2], &[1, 254]);
+    assert_eq!(&data[34..37], b"USD");
+    assert_eq!(&data[34..66], &quote_currency("USD"));
+    assert_eq!(LendingMarket::unpack(&data).unwrap(), market);
+
+    let mut data = data;
+    data[0] = 0;
+    assert!(LendingMarket::unpack(&data).is_err());
+}
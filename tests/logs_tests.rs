@@ -0,0 +1,218 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_accountgen::extensions::anchor::get_event_discriminator;
+use solana_accountgen::logs::{
+    LogKind, LogNormalizer, Logs, check_logs_snapshot, process_transaction_with_logs,
+};
+use solana_accountgen::{assert_log_contains, assert_logs_snapshot};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+struct Deposited {
+    amount: u64,
+}
+
+#[test]
+fn test_parse_nested_invocations() {
+    let outer = Pubkey::new_unique();
+    let inner = Pubkey::new_unique();
+    let logs = Logs::from_messages(vec![
+        format!("Program {} invoke [1]", outer),
+        "Program log: outer".to_string(),
+        format!("Program {} invoke [2]", inner),
+        "Program log: inner".to_string(),
+        format!("Program return: {} KgAAAA==", inner),
+        format!("Program {} consumed 100 of 200000 compute units", inner),
+        format!("Program {} failed: custom program error: 0x1", inner),
+        format!("Program {} success", outer),
+        "Log truncated".to_string(),
+    ]);
+
+    let entries = logs.entries();
+    assert_eq!(entries[1].program_id, Some(outer));
+    assert_eq!(entries[1].depth, 1);
+    assert_eq!(entries[3].program_id, Some(inner));
+    assert_eq!(entries[3].depth, 2);
+    assert_eq!(entries[4].kind, LogKind::Return(vec![42, 0, 0, 0]));
+    assert_eq!(
+        entries[5].kind,
+        LogKind::Consumed {
+            units: 100,
+            limit: 200_000
+        }
+    );
+    assert_eq!(
+        entries[6].kind,
+        LogKind::Failed("custom program error: 0x1".to_string())
+    );
+    assert_eq!(entries[7].depth, 1);
+    assert_eq!(entries[8].depth, 0);
+    assert_eq!(logs.messages().collect::<Vec<_>>(), vec!["outer", "inner"]);
+}
+
+#[test]
+fn test_events_skip_other_data() {
+    let mut event = get_event_discriminator("Deposited").to_vec();
+    event.extend(borsh::to_vec(&Deposited { amount: 5 }).unwrap());
+
+    let logs = Logs::from_messages(vec![
+        format!("Program data: {}", base64::encode([1u8; 12])),
+        format!("Program data: {}", base64::encode(&event)),
+    ]);
+
+    assert_eq!(
+        logs.events::<Deposited>("Deposited").unwrap(),
+        vec![Deposited { amount: 5 }]
+    );
+    assert!(logs.events::<Deposited>("Withdrawn").unwrap().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "logs do not contain")]
+fn test_assert_log_contains_failure() {
+    let logs = Logs::from_messages(vec!["Program log: hello".to_string()]);
+    assert_log_contains!(logs, "goodbye");
+}
+
+#[tokio::test]
+async fn test_capture_logs_from_banks_client() {
+    let (mut banks_client, payer, blockhash) = ProgramTest::default().start().await;
+
+    // SystemInstruction::Transfer
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+    let instruction = Instruction::new_with_bytes(
+        system_program::id(),
+        &data,
+        vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+
+    let (result, logs) = process_transaction_with_logs(&mut banks_client, transaction)
+        .await
+        .unwrap();
+
+    assert!(result.is_ok(), "transfer failed:\n{}", logs);
+    assert_log_contains!(logs, "11111111111111111111111111111111 success");
+    assert_eq!(logs.entries()[0].kind, LogKind::Invoke);
+}
+
+#[test]
+fn test_normalize_logs() {
+    let (program, vault, other) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let logs = Logs::from_messages(vec![
+        format!("Program {} invoke [1]", program),
+        format!("Program log: moving from {} to {}", vault, other),
+        format!("Program {} invoke [2]", system_program::id()),
+        format!("Program {} success", system_program::id()),
+        "Program consumption: 1234 units remaining".to_string(),
+        format!("Program data: {}", base64::encode(program)),
+        format!("Program log: again {}", other),
+        format!("Program {} consumed 2500 of 200000 compute units", program),
+        format!("Program {} success", program),
+    ]);
+
+    let normalizer = LogNormalizer::new().name(vault, "vault");
+    assert_eq!(
+        normalizer.normalize(&logs),
+        format!(
+            "Program <pubkey 1> invoke [1]\n\
+             Program log: moving from <vault> to <pubkey 2>\n\
+             Program 11111111111111111111111111111111 invoke [2]\n\
+             Program 11111111111111111111111111111111 success\n\
+             Program consumption: <cu> units remaining\n\
+             Program data: {}\n\
+             Program log: again <pubkey 2>\n\
+             Program <pubkey 1> consumed <cu> of <cu> compute units\n\
+             Program <pubkey 1> success\n",
+            base64::encode(program)
+        )
+    );
+
+    let kept = LogNormalizer::new()
+        .strip_pubkeys(false)
+        .strip_compute_units(false)
+        .name(vault, "vault")
+        .normalize(&logs);
+    assert!(kept.contains(&format!("moving from <vault> to {}", other)));
+    assert!(kept.contains("consumed 2500 of 200000 compute units"));
+}
+
+#[test]
+fn test_logs_snapshot_files() {
+    let dir = std::env::temp_dir().join(format!(
+        "solana-accountgen-log-snapshots-{}",
+        std::process::id()
+    ));
+    let path = dir.join("init_logs.txt");
+    let program = Pubkey::new_unique();
+    let logs = |message: &str| {
+        Logs::from_messages(vec![
+            format!("Program {} invoke [1]", program),
+            format!("Program log: {}", message),
+            format!("Program {} consumed 100 of 200000 compute units", program),
+            format!("Program {} success", program),
+        ])
+    };
+    let normalizer = LogNormalizer::new();
+
+    // A missing snapshot fails rather than being written
+    let error = check_logs_snapshot(&logs("Instruction: Initialize"), &path, &normalizer)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("snapshot missing; rerun with ACCOUNTGEN_UPDATE_BASELINES=1"));
+    let panic = std::panic::catch_unwind(|| {
+        assert_logs_snapshot!(logs("Instruction: Initialize"), &path);
+    })
+    .unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.contains("snapshot missing; rerun with ACCOUNTGEN_UPDATE_BASELINES=1"));
+    assert!(!path.exists());
+
+    std::fs::create_dir_all(&dir).unwrap();
+    let snapshot = normalizer.normalize(&logs("Instruction: Initialize"));
+    assert!(snapshot.starts_with("Program <pubkey 1> invoke [1]\n"));
+    std::fs::write(&path, snapshot).unwrap();
+
+    // Later runs compare, ignoring pubkeys and compute units
+    let mut rerun = logs("Instruction: Initialize").raw().to_vec();
+    rerun[2] = format!("Program {} consumed 150 of 200000 compute units", program);
+    check_logs_snapshot(&Logs::from_messages(rerun), &path, &normalizer).unwrap();
+    assert_logs_snapshot!(logs("Instruction: Initialize"), &path);
+
+    let error = check_logs_snapshot(&logs("Instruction: InitializeV2"), &path, &normalizer)
+        .unwrap_err()
+        .to_string();
+    let panic = std::panic::catch_unwind(|| {
+        assert_logs_snapshot!(
+            logs("Instruction: InitializeV2"),
+            &path,
+            LogNormalizer::new()
+        )
+    });
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(error.contains(
+        "  Program <pubkey 1> invoke [1]\n\
+         - Program log: Instruction: Initialize\n\
+         + Program log: Instruction: InitializeV2\n  \
+         Program <pubkey 1> consumed <cu> of <cu> compute units\n"
+    ));
+    assert!(error.contains("set ACCOUNTGEN_UPDATE_BASELINES to update the snapshot"));
+    assert!(panic.is_err());
+}
@@ -0,0 +1,220 @@
+use solana_account_info::AccountInfo;
+use solana_accountgen::lookup_tables::{
+    LOOKUP_TABLE_META_SIZE, LoadedMeta, LookupProblem, LookupTable, check_lookups,
+    lookup_table_accounts,
+};
+use solana_accountgen::well_known::ADDRESS_LOOKUP_TABLE_PROGRAM;
+use solana_accountgen::{AccountBuilder, AccountGenError, AccountMap};
+use solana_hash::Hash;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_message::v0::MessageAddressTableLookup;
+use solana_message::{Message, VersionedMessage, v0};
+use solana_program_error::ProgramResult;
+use solana_program_test::{ProgramTest, processor};
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::versioned::VersionedTransaction;
+
+/// Test program: copies the instruction data into account 0.
+fn process_instruction(_: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    accounts[0].try_borrow_mut_data()?.copy_from_slice(data);
+    Ok(())
+}
+
+fn unsigned(message: VersionedMessage) -> VersionedTransaction {
+    VersionedTransaction {
+        signatures: vec![Default::default(); message.header().num_required_signatures as usize],
+        message,
+    }
+}
+
+#[test]
+fn test_lookup_table_layout() {
+    let authority = Pubkey::new_unique();
+    let addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let table = LookupTable::new(Some(authority), addresses.clone());
+    let account = table.to_account().unwrap();
+    assert_eq!(account.owner, ADDRESS_LOOKUP_TABLE_PROGRAM);
+    assert_eq!(account.data.len(), LOOKUP_TABLE_META_SIZE + 64);
+    assert_eq!(LookupTable::unpack(&account.data).unwrap(), table);
+
+    let frozen = LookupTable::new(None, addresses);
+    assert_eq!(
+        LookupTable::unpack(&frozen.pack().unwrap()).unwrap(),
+        frozen
+    );
+
+    assert!(matches!(
+        LookupTable::unpack(&[0; LOOKUP_TABLE_META_SIZE]),
+        Err(AccountGenError::InvalidDataFormat(_))
+    ));
+    let too_many = LookupTable::new(None, vec![Pubkey::new_unique(); 257]);
+    assert!(too_many.pack().is_err());
+}
+
+#[test]
+fn test_check_lookups_reports_loaded_metas() {
+    let (table, vault, config, payer) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut accounts = AccountMap::new();
+    accounts.set_account(
+        table,
+        LookupTable::new(None, vec![config, vault])
+            .to_account()
+            .unwrap(),
+    );
+    let instruction = Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[1],
+        vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+    );
+    let message = v0::Message::try_compile(
+        &payer,
+        std::slice::from_ref(&instruction),
+        &lookup_table_accounts(&accounts),
+        Hash::default(),
+    )
+    .unwrap();
+
+    let report = check_lookups(&unsigned(VersionedMessage::V0(message)), &accounts);
+    assert!(report.is_ok(), "{}", report);
+    assert_eq!(
+        report.loaded,
+        vec![
+            LoadedMeta {
+                pubkey: vault,
+                table,
+                index: 1,
+                writable: true,
+            },
+            LoadedMeta {
+                pubkey: config,
+                table,
+                index: 0,
+                writable: false,
+            },
+        ]
+    );
+    assert!(report.uncompressed.is_empty());
+
+    // A legacy message lists both, though the table holds them
+    let legacy = Message::new(&[instruction], Some(&payer));
+    let report = check_lookups(&unsigned(VersionedMessage::Legacy(legacy)), &accounts);
+    assert!(report.is_ok());
+    assert!(report.loaded.is_empty());
+    assert_eq!(report.uncompressed.len(), 2);
+    assert!(report.uncompressed.contains(&vault));
+}
+
+#[test]
+fn test_check_lookups_reports_problems() {
+    let (table, deactivated, missing, vault, payer, program_id) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut accounts = AccountMap::new();
+    accounts.set_account(
+        table,
+        LookupTable::new(None, vec![vault]).to_account().unwrap(),
+    );
+    let mut old = LookupTable::new(None, vec![Pubkey::new_unique()]);
+    old.deactivation_slot = 10;
+    accounts.set_account(deactivated, old.to_account().unwrap());
+
+    let lookup = |account_key, writable_indexes: Vec<u8>| MessageAddressTableLookup {
+        account_key,
+        writable_indexes,
+        readonly_indexes: vec![],
+    };
+    let message = v0::Message {
+        header: solana_message::MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 1,
+        },
+        account_keys: vec![payer, program_id],
+        recent_blockhash: Hash::default(),
+        instructions: vec![],
+        address_table_lookups: vec![
+            lookup(table, vec![0, 0, 3]),
+            lookup(deactivated, vec![0]),
+            lookup(missing, vec![0]),
+        ],
+    };
+
+    let report = check_lookups(&unsigned(VersionedMessage::V0(message)), &accounts);
+    assert_eq!(
+        report.problems,
+        vec![
+            LookupProblem::LoadedTwice(vault),
+            LookupProblem::IndexOutOfRange {
+                table,
+                index: 3,
+                len: 1,
+            },
+            LookupProblem::Deactivated(deactivated),
+            LookupProblem::MissingTable(missing),
+        ]
+    );
+    assert_eq!(report.loaded.len(), 3);
+    let error = report.into_result().unwrap_err().to_string();
+    assert!(error.contains(&format!("lookup table {} is missing", missing)));
+}
+
+#[tokio::test]
+async fn test_lookup_table_resolves_in_program_test() {
+    let (program_id, table, counter) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut program_test =
+        ProgramTest::new("alt_counter", program_id, processor!(process_instruction));
+    program_test.add_account(
+        counter,
+        AccountBuilder::new()
+            .balance(1_000_000)
+            .owner(program_id)
+            .data_raw(vec![0])
+            .build(),
+    );
+    let mut accounts = AccountMap::new();
+    accounts.set_account(
+        table,
+        LookupTable::new(None, vec![counter]).to_account().unwrap(),
+    );
+    program_test.add_account(table, accounts.get_account(&table).unwrap().clone());
+    let (banks_client, payer, blockhash) = program_test.start().await;
+
+    let instruction =
+        Instruction::new_with_bytes(program_id, &[5], vec![AccountMeta::new(counter, false)]);
+    let message = v0::Message::try_compile(
+        &payer.pubkey(),
+        &[instruction],
+        &lookup_table_accounts(&accounts),
+        blockhash,
+    )
+    .unwrap();
+    let transaction =
+        VersionedTransaction::try_new(VersionedMessage::V0(message), &[&payer as &Keypair])
+            .unwrap();
+    check_lookups(&transaction, &accounts)
+        .into_result()
+        .unwrap();
+
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(counter).await.unwrap().unwrap();
+    assert_eq!(account.data, [5]);
+}
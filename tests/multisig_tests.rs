@@ -0,0 +1,158 @@
+use solana_accountgen::AccountGenError;
+use solana_accountgen::extensions::anchor::get_account_discriminator;
+use solana_accountgen::extensions::multisig::{
+    PERMISSION_ALL, SQUADS_PROGRAM, SignerSet, SquadsMultisig, TOKEN_MULTISIG_LEN, TokenMultisig,
+    missing_signers,
+};
+use solana_accountgen::extensions::token::{
+    TokenAccount, create_mint_account, create_token_account,
+};
+use solana_accountgen::keys::test_pubkey;
+use solana_accountgen::well_known::TOKEN_PROGRAM;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+
+#[test]
+fn test_signer_set_is_deterministic() {
+    let signers = SignerSet::new("council", 2, 3).unwrap();
+    let again = SignerSet::new("council", 2, 3).unwrap();
+    assert_eq!(signers.pubkeys(), again.pubkeys());
+    assert_eq!(signers.len(), 3);
+    assert_eq!(signers.threshold(), 2);
+
+    let quorum: Vec<Pubkey> = signers.quorum().iter().map(|k| k.pubkey()).collect();
+    assert_eq!(quorum, signers.pubkeys()[..2]);
+
+    assert!(matches!(
+        SignerSet::new("council", 0, 3),
+        Err(AccountGenError::ConstraintViolation(_))
+    ));
+    assert!(SignerSet::new("council", 4, 3).is_err());
+}
+
+#[test]
+fn test_token_multisig_layout() {
+    let signers = SignerSet::new("mint authority", 3, 5).unwrap();
+    let account = signers.token_multisig_account(&TOKEN_PROGRAM).unwrap();
+    assert_eq!(account.owner, TOKEN_PROGRAM);
+    assert_eq!(account.data.len(), TOKEN_MULTISIG_LEN);
+
+    let multisig = TokenMultisig::unpack(&account.data).unwrap();
+    assert_eq!((multisig.m, multisig.n), (3, 5));
+    assert!(multisig.is_initialized);
+    assert_eq!(multisig.signers, signers.pubkeys());
+    assert_eq!(multisig.pack(), account.data);
+
+    let too_many = SignerSet::new("crowd", 1, 12).unwrap();
+    assert!(matches!(
+        too_many.token_multisig(),
+        Err(AccountGenError::ConstraintViolation(_))
+    ));
+}
+
+#[test]
+fn test_squads_multisig_layout() {
+    let signers = SignerSet::new("squad", 2, 3).unwrap();
+    let create_key = test_pubkey("create key");
+    let (address, account) = signers
+        .squads_multisig(create_key)
+        .unwrap()
+        .to_account()
+        .unwrap();
+    assert_eq!(address, SquadsMultisig::address(&create_key).0);
+    assert_eq!(account.owner, SQUADS_PROGRAM);
+    assert_eq!(account.data.len(), SquadsMultisig::size(3));
+    assert_eq!(account.data[..8], get_account_discriminator("Multisig"));
+    assert_eq!(account.data[8..40], create_key.to_bytes());
+    assert_eq!(account.data[72..74], 2u16.to_le_bytes());
+
+    // Members follow the rent collector and bump, sorted by key
+    let mut members = signers.pubkeys();
+    members.sort();
+    let start = 8 + 32 + 32 + 2 + 4 + 8 + 8 + 1 + 1;
+    assert_eq!(account.data[start..start + 4], 3u32.to_le_bytes());
+    for (i, member) in members.iter().enumerate() {
+        let offset = start + 4 + i * 33;
+        assert_eq!(account.data[offset..offset + 32], member.to_bytes());
+        assert_eq!(account.data[offset + 32], PERMISSION_ALL);
+    }
+}
+
+#[test]
+fn test_missing_signers() {
+    let signers = SignerSet::new("approvers", 2, 3).unwrap();
+    let program_id = Pubkey::new_unique();
+    let instruction =
+        Instruction::new_with_bytes(program_id, &[0], signers.signer_metas(&[0, 1, 2]));
+    let payer = Keypair::new();
+    let blockhash = solana_hash::Hash::new_unique();
+
+    let transaction =
+        signers.partially_signed(std::slice::from_ref(&instruction), &payer, &[1], blockhash);
+    assert_eq!(
+        missing_signers(&transaction),
+        vec![signers.pubkey(0), signers.pubkey(2)]
+    );
+    let transaction = signers.partially_signed(&[instruction], &payer, &[0, 1, 2], blockhash);
+    assert!(missing_signers(&transaction).is_empty());
+    assert!(transaction.verify().is_ok());
+}
+
+#[tokio::test]
+async fn test_token_transfer_needs_threshold_approvals() {
+    let signers = SignerSet::new("vault", 2, 3).unwrap();
+    let (multisig, mint, source, destination) = (
+        test_pubkey("vault multisig"),
+        test_pubkey("vault mint"),
+        test_pubkey("vault tokens"),
+        test_pubkey("recipient tokens"),
+    );
+    let mut program_test = ProgramTest::default();
+    program_test.add_account(
+        multisig,
+        signers.token_multisig_account(&TOKEN_PROGRAM).unwrap(),
+    );
+    program_test.add_account(
+        mint,
+        create_mint_account(&multisig, 1_000, 0, &TOKEN_PROGRAM).unwrap(),
+    );
+    program_test.add_account(
+        source,
+        create_token_account(&mint, &multisig, 1_000, &TOKEN_PROGRAM).unwrap(),
+    );
+    program_test.add_account(
+        destination,
+        create_token_account(&mint, &test_pubkey("recipient"), 0, &TOKEN_PROGRAM).unwrap(),
+    );
+    let (banks_client, payer, blockhash) = program_test.start().await;
+
+    // Transfer: tag 3 and the amount, then source, destination, the
+    // multisig authority and the approving signers
+    let transfer = |approvals: &[usize], amount: u64| {
+        let mut data = vec![3];
+        data.extend_from_slice(&amount.to_le_bytes());
+        let mut accounts = vec![
+            AccountMeta::new(source, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(multisig, false),
+        ];
+        accounts.extend(signers.signer_metas(approvals));
+        Instruction::new_with_bytes(TOKEN_PROGRAM, &data, accounts)
+    };
+
+    let one = signers.partially_signed(&[transfer(&[1], 100)], &payer, &[1], blockhash);
+    assert!(banks_client.process_transaction(one).await.is_err());
+
+    let two = signers.partially_signed(&[transfer(&[0, 2], 100)], &payer, &[0, 2], blockhash);
+    banks_client.process_transaction(two).await.unwrap();
+
+    let account = banks_client
+        .get_account(destination)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(TokenAccount::unpack(&account.data).unwrap().amount, 100);
+}
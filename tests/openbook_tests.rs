@@ -0,0 +1,137 @@
+use solana_accountgen::extensions::openbook::{
+    EVENT_FILL, EVENT_LEN, EVENT_MAKER, Event, EventQueue, FLAG_ASKS, FLAG_BIDS, FLAG_EVENT_QUEUE,
+    FLAG_INITIALIZED, MARKET_LEN, Market, OPENBOOK_PROGRAM, Order, Side, Slab,
+    create_market_account, event_queue_len, order_id, slab_len, slab_orders, vault_signer,
+};
+use solana_pubkey::Pubkey;
+
+fn flags(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[5..13].try_into().unwrap())
+}
+
+#[test]
+fn test_market_layout() {
+    let own_address = Pubkey::new_unique();
+    let (_, nonce) = vault_signer(&own_address, &OPENBOOK_PROGRAM);
+    let market = Market {
+        own_address,
+        vault_signer_nonce: nonce,
+        coin_mint: Pubkey::new_unique(),
+        pc_mint: Pubkey::new_unique(),
+        bids: Pubkey::new_unique(),
+        asks: Pubkey::new_unique(),
+        coin_lot_size: 100,
+        pc_lot_size: 10,
+        ..Market::default()
+    };
+
+    let account = create_market_account(&market, &OPENBOOK_PROGRAM).unwrap();
+    assert_eq!(account.owner, OPENBOOK_PROGRAM);
+    assert_eq!(account.data.len(), MARKET_LEN);
+    assert_eq!(&account.data[..5], b"serum");
+    assert_eq!(&account.data[MARKET_LEN - 7..], b"padding");
+    assert_eq!(flags(&account.data), 0b11);
+    assert_eq!(&account.data[13..45], own_address.as_ref());
+    assert_eq!(Market::unpack(&account.data).unwrap(), market);
+
+    let signer = Pubkey::create_program_address(
+        &[own_address.as_ref(), &nonce.to_le_bytes()],
+        &OPENBOOK_PROGRAM,
+    );
+    assert!(signer.is_ok());
+}
+
+#[test]
+fn test_event_queue_layout() {
+    let fill = Event {
+        flags: EVENT_FILL | EVENT_MAKER,
+        native_qty_paid: 500,
+        order_id: order_id(Side::Ask, 10, 3),
+        owner: Pubkey::new_unique(),
+        ..Event::default()
+    };
+    let data = EventQueue::new(4)
+        .head(3)
+        .seq_num(10)
+        .event(fill)
+        .event(fill)
+        .pack()
+        .unwrap();
+
+    assert_eq!(data.len(), event_queue_len(4));
+    assert_eq!(flags(&data), FLAG_INITIALIZED | FLAG_EVENT_QUEUE);
+    let header = |i: usize| u64::from_le_bytes(data[13 + 8 * i..21 + 8 * i].try_into().unwrap());
+    assert_eq!((header(0), header(1), header(2)), (3, 2, 12));
+
+    // The second event wraps around to slot 0
+    let slot = |i: usize| &data[37 + i * EVENT_LEN..37 + (i + 1) * EVENT_LEN];
+    assert_eq!(slot(3), &fill.pack());
+    assert_eq!(slot(0), &fill.pack());
+    assert!(slot(1).iter().all(|&b| b == 0));
+    assert!(data.ends_with(b"padding"));
+
+    assert!(EventQueue::new(1).event(fill).event(fill).pack().is_err());
+}
+
+#[test]
+fn test_slab_orders_in_priority() {
+    let owner = Pubkey::new_unique();
+    let prices = [17u64, 3, 250, 17, 1 << 40, 9, 3, 64];
+    let bids = Slab::new(Side::Bid, 32).orders(
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, &price)| Order::new(price, i as u64 + 1, owner)),
+    );
+    let data = bids.pack().unwrap();
+    assert_eq!(data.len(), slab_len(32));
+    assert_eq!(flags(&data), FLAG_INITIALIZED | FLAG_BIDS);
+    // Leaf count
+    assert_eq!(u64::from_le_bytes(data[37..45].try_into().unwrap()), 8);
+
+    let orders = slab_orders(&data).unwrap();
+    assert_eq!(orders.len(), prices.len());
+    assert!(orders.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    // The best bid is the highest price, and at 17 the earlier order (size
+    // 1) has priority over the later one (size 4)
+    let best: Vec<_> = orders
+        .iter()
+        .rev()
+        .map(|(_, o)| (o.price, o.quantity))
+        .collect();
+    assert_eq!(&best[..4], &[(1 << 40, 5), (250, 3), (64, 8), (17, 1)]);
+    assert_eq!(best[4], (17, 4));
+
+    // 8 orders need 15 nodes
+    let orders = orders.iter().map(|(_, order)| *order);
+    assert!(Slab::new(Side::Bid, 14).orders(orders).pack().is_err());
+}
+
+#[test]
+fn test_empty_slab() {
+    let data = Slab::new(Side::Ask, 4).pack().unwrap();
+    assert_eq!(flags(&data), FLAG_INITIALIZED | FLAG_ASKS);
+    assert!(slab_orders(&data).unwrap().is_empty());
+
+    let single = Slab::new(Side::Ask, 1)
+        .order(Order::new(5, 1, Pubkey::new_unique()))
+        .pack()
+        .unwrap();
+    assert_eq!(
+        slab_orders(&single).unwrap()[0].0,
+        order_id(Side::Ask, 5, 0)
+    );
+    assert!(slab_orders(&single[1..]).is_err());
+
+    // Framed and flagged, but cut off before the header ends
+    let mut truncated = b"serum".to_vec();
+    truncated.extend_from_slice(&(FLAG_INITIALIZED | FLAG_BIDS).to_le_bytes());
+    truncated.extend_from_slice(&[0; 20]);
+    truncated.extend_from_slice(b"padding");
+    assert!(slab_orders(&truncated).is_err());
+
+    // A leaf count near u64::MAX must not overflow the node limit
+    let mut huge = single.clone();
+    huge[5 + 8 + 24..5 + 8 + 32].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert_eq!(slab_orders(&huge).unwrap().len(), 1);
+}
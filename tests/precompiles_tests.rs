@@ -0,0 +1,80 @@
+use solana_account_info::AccountInfo;
+use solana_accountgen::extensions::precompiles::{
+    ed25519_instruction, ed25519_instruction_with_signatures, secp256k1_instruction,
+};
+use solana_accountgen::extensions::sysvars::create_instructions_sysvar_account;
+use solana_instruction::Instruction;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_keypair::Keypair;
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::{ed25519_program, sysvar};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+async fn process(instructions: &[Instruction]) -> bool {
+    let (banks_client, payer, blockhash) = ProgramTest::default().start().await;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    banks_client.process_transaction(transaction).await.is_ok()
+}
+
+#[tokio::test]
+async fn test_precompile_instructions_verify() {
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let ed25519 = ed25519_instruction_with_signatures(&[
+        (alice.pubkey(), alice.sign_message(b"alice"), b"alice"),
+        (bob.pubkey(), bob.sign_message(b"bob"), b"bob"),
+    ]);
+    // The secp256k1 instruction names its own position in the transaction
+    let secp256k1 = secp256k1_instruction(&[3; 32], b"hello", 1).unwrap();
+    assert!(process(&[ed25519, secp256k1]).await);
+
+    let misplaced = secp256k1_instruction(&[3; 32], b"hello", 0).unwrap();
+    assert!(!process(&[ed25519_instruction(&alice, b"first"), misplaced]).await);
+}
+
+#[tokio::test]
+async fn test_precompile_rejects_wrong_signature() {
+    let alice = Keypair::new();
+    let forged =
+        ed25519_instruction_with_signatures(&[(alice.pubkey(), alice.sign_message(b"a"), b"b")]);
+    assert!(!process(&[forged]).await);
+}
+
+#[test]
+fn test_instructions_sysvar_matches_transaction() {
+    let program_id = Pubkey::new_unique();
+    let verify = ed25519_instruction(&Keypair::new(), b"price:42");
+    let update = Instruction::new_with_bytes(program_id, b"update", vec![]);
+    let mut account = create_instructions_sysvar_account(&[verify.clone(), update], 1);
+
+    let key = sysvar::instructions::id();
+    let info = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut account.lamports,
+        &mut account.data,
+        &account.owner,
+        false,
+        0,
+    );
+    assert_eq!(load_current_index_checked(&info).unwrap(), 1);
+    let loaded = load_instruction_at_checked(0, &info).unwrap();
+    assert_eq!(loaded.program_id, ed25519_program::id());
+    assert_eq!(loaded.data, verify.data);
+    assert_eq!(
+        load_instruction_at_checked(1, &info).unwrap().program_id,
+        program_id
+    );
+}
+
+#[test]
+fn test_secp256k1_rejects_invalid_secret_key() {
+    assert!(secp256k1_instruction(&[0; 32], b"hello", 0).is_err());
+}
@@ -0,0 +1,127 @@
+use serde_json::json;
+use solana_account_info::AccountInfo;
+use solana_accountgen::AccountBuilder;
+use solana_accountgen::extensions::idl::Idl;
+use solana_accountgen::extensions::program_set::ProgramSet;
+use solana_accountgen::instructions::memo;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke;
+use solana_program_error::ProgramResult;
+use solana_program_test::processor;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+const ROUTER_IDL: &str = r#"{
+    "metadata": { "name": "router", "version": "0.1.0", "spec": "0.1.0" },
+    "instructions": [{
+        "name": "route",
+        "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+        "accounts": [{ "name": "vault_program" }, { "name": "counter", "writable": true }],
+        "args": [{ "name": "amount", "type": "u8" }]
+    }],
+    "accounts": [],
+    "types": []
+}"#;
+
+/// Adds the data byte to the counter's first byte.
+fn vault(_: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    accounts[0].try_borrow_mut_data()?[0] += data[0];
+    Ok(())
+}
+
+/// Forwards its amount argument to the vault.
+fn router(_: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction = Instruction::new_with_bytes(
+        *accounts[0].key,
+        &data[8..],
+        vec![AccountMeta::new(*accounts[1].key, false)],
+    );
+    invoke(&instruction, &[accounts[1].clone(), accounts[0].clone()])
+}
+
+#[tokio::test]
+async fn test_program_set_runs_cpi_between_programs() {
+    let programs = ProgramSet::new()
+        .native("vault", Pubkey::new_unique(), processor!(vault))
+        .native("router", Pubkey::new_unique(), processor!(router))
+        .idl("router", Idl::from_json(ROUTER_IDL).unwrap())
+        .unwrap();
+    let vault_id = programs.program_id("vault");
+    assert_eq!(
+        programs
+            .programs()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        ["vault", "router"]
+    );
+
+    let counter = Pubkey::new_unique();
+    let mut program_test = programs.program_test();
+    program_test.add_account(
+        counter,
+        AccountBuilder::new()
+            .owner(vault_id)
+            .data_raw(vec![0])
+            .build(),
+    );
+    let (banks_client, payer, blockhash) = program_test.start().await;
+
+    let router = programs.program("router");
+    let accounts = vec![
+        AccountMeta::new_readonly(vault_id, false),
+        AccountMeta::new(counter, false),
+    ];
+    let instruction = router
+        .idl_instruction("route", accounts.clone(), &json!({ "amount": 7 }))
+        .unwrap();
+    assert_eq!(instruction.data, [1, 2, 3, 4, 5, 6, 7, 8, 7]);
+
+    let registry = programs.decoder_registry().unwrap();
+    let decoded = registry.decode_instruction(&instruction).unwrap();
+    assert_eq!(decoded.name, "route");
+    // Built-in programs decode, programs of the set without an IDL do not
+    let memo_instruction = memo("routed", &[]);
+    assert_eq!(
+        registry.decode_instruction(&memo_instruction).unwrap().name,
+        "memo"
+    );
+    let vault_instruction = Instruction::new_with_bytes(vault_id, &[0], vec![]);
+    assert_eq!(
+        registry
+            .decode_instruction(&vault_instruction)
+            .unwrap()
+            .name,
+        "unknown"
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(counter).await.unwrap().unwrap();
+    assert_eq!(account.data, [7]);
+
+    let err = router
+        .idl_instruction("route", accounts[..1].to_vec(), &json!({ "amount": 7 }))
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("takes 2 accounts (vault_program, counter), got 1")
+    );
+    assert!(
+        programs
+            .program("vault")
+            .idl_instruction("route", vec![], &json!({}))
+            .is_err()
+    );
+}
+
+#[test]
+#[should_panic(expected = "program set has no program \"escrow\"")]
+fn test_program_set_unknown_program_panics() {
+    ProgramSet::new().program_id("escrow");
+}
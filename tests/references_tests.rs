@@ -0,0 +1,151 @@
+use solana_accountgen::extensions::token::{create_mint_account, create_token_account};
+use solana_accountgen::keys::test_pubkey;
+use solana_accountgen::references::{PubkeyExtractor, extract_pubkeys};
+use solana_accountgen::serialization::schema::FixtureSchema;
+use solana_accountgen::well_known::{BPF_LOADER_UPGRADEABLE, TOKEN_2022_PROGRAM, TOKEN_PROGRAM};
+use solana_accountgen::{AccountBuilder, AccountMap};
+use solana_pubkey::Pubkey;
+
+fn loader_account(data: Vec<u8>) -> solana_account::Account {
+    AccountBuilder::new()
+        .owner(BPF_LOADER_UPGRADEABLE)
+        .data_raw(data)
+        .build()
+}
+
+#[test]
+fn test_extract_token_layouts() {
+    let authority = Pubkey::new_unique();
+    let mint = create_mint_account(&authority, 1_000, 6, &TOKEN_PROGRAM).unwrap();
+    assert_eq!(extract_pubkeys(&mint), vec![(4, authority)]);
+
+    // Token-2022 accounts with extensions are told apart by the type byte
+    let (mint_key, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut account = create_token_account(&mint_key, &owner, 5, &TOKEN_2022_PROGRAM).unwrap();
+    account.data.push(2);
+    account.data.extend_from_slice(&[0; 8]);
+    assert_eq!(extract_pubkeys(&account), vec![(0, mint_key), (32, owner)]);
+}
+
+#[test]
+fn test_extract_upgradeable_loader_layouts() {
+    let (program_data, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let mut program = 2u32.to_le_bytes().to_vec();
+    program.extend_from_slice(program_data.as_ref());
+    assert_eq!(
+        extract_pubkeys(&loader_account(program)),
+        vec![(4, program_data)]
+    );
+
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&42u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(&[0x7f, b'E', b'L', b'F']);
+    assert_eq!(
+        extract_pubkeys(&loader_account(data)),
+        vec![(13, authority)]
+    );
+
+    // Immutable programs have no upgrade authority
+    let mut immutable = 3u32.to_le_bytes().to_vec();
+    immutable.extend_from_slice(&[0; 9]);
+    assert!(extract_pubkeys(&loader_account(immutable)).is_empty());
+}
+
+#[test]
+fn test_extract_with_schema_falls_back_on_bad_data() {
+    let schema: FixtureSchema = serde_json::from_value(serde_json::json!({
+        "version": 1,
+        "types": ["Pool"],
+        "definitions": {
+            "Pool": {"kind": "struct", "fields": [
+                {"name": "vaults", "type": "Vec<Pubkey>"},
+                {"name": "fee", "type": "u16"}
+            ]},
+            "Vec<Pubkey>": {"kind": "sequence", "lengthWidth": 4, "lengthRange": [0, 4294967295u64], "elements": "Pubkey"},
+            "Pubkey": {"kind": "primitive", "size": 32},
+            "u16": {"kind": "primitive", "size": 2}
+        }
+    }))
+    .unwrap();
+    let program_id = Pubkey::new_unique();
+    let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let extractor = PubkeyExtractor::new().schema(program_id, schema, "Pool");
+
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(a.as_ref());
+    data.extend_from_slice(b.as_ref());
+    data.extend_from_slice(&30u16.to_le_bytes());
+    let pool = AccountBuilder::new()
+        .owner(program_id)
+        .data_raw(data)
+        .build();
+    assert_eq!(extractor.extract(&pool), vec![(4, a), (36, b)]);
+
+    // A truncated pool is scanned instead
+    let oracle = test_pubkey("oracle");
+    let mut truncated = 9u32.to_le_bytes().to_vec();
+    truncated.extend_from_slice(oracle.as_ref());
+    let broken = AccountBuilder::new()
+        .owner(program_id)
+        .data_raw(truncated)
+        .build();
+    assert_eq!(extractor.extract(&broken), vec![(4, oracle)]);
+}
+
+#[test]
+fn test_heuristic_scan() {
+    let (oracle, feed) = (test_pubkey("oracle"), test_pubkey("feed"));
+    let mut data = vec![0; 3];
+    data.extend_from_slice(oracle.as_ref());
+    data.extend_from_slice(&1_000u64.to_le_bytes());
+    data.extend_from_slice(feed.as_ref());
+    let account = AccountBuilder::new()
+        .owner(Pubkey::new_unique())
+        .data_raw(data)
+        .build();
+    assert_eq!(extract_pubkeys(&account), vec![(3, oracle), (43, feed)]);
+
+    // Low-entropy test keys are only found when known
+    let unique = Pubkey::new_unique();
+    let mut data = vec![9];
+    data.extend_from_slice(unique.as_ref());
+    let account = AccountBuilder::new()
+        .owner(Pubkey::new_unique())
+        .data_raw(data)
+        .build();
+    assert!(extract_pubkeys(&account).is_empty());
+    let extractor = PubkeyExtractor::new().known_keys([unique]);
+    assert_eq!(extractor.extract(&account), vec![(1, unique)]);
+}
+
+#[test]
+fn test_prune_unreachable_with_extractor() {
+    let (owner, mint, other_mint) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let token_account = Pubkey::new_unique();
+
+    let mut accounts = AccountMap::new();
+    accounts.set_account(
+        token_account,
+        create_token_account(&mint, &owner, 1, &TOKEN_PROGRAM).unwrap(),
+    );
+    for key in [mint, other_mint] {
+        accounts.set_account(
+            key,
+            create_mint_account(&owner, 0, 0, &TOKEN_PROGRAM).unwrap(),
+        );
+    }
+
+    let extractor = PubkeyExtractor::new();
+    let removed =
+        accounts.prune_unreachable([token_account], |account| extractor.references(account));
+    assert_eq!(removed, 1);
+    assert!(accounts.get_account(&mint).is_some());
+    assert!(accounts.get_account(&other_mint).is_none());
+}
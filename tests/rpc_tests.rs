@@ -0,0 +1,658 @@
+use serde_json::{Value, json};
+use solana_accountgen::rpc::{MockRpcServer, RpcFilter};
+use solana_accountgen::{AccountBuilder, AccountMap, SharedAccountMap};
+use solana_pubkey::Pubkey;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+/// Posts a JSON-RPC request to a mock server and returns the parsed response.
+fn post(server: &MockRpcServer, request: Value) -> Value {
+    let body = request.to_string();
+    let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+    write!(
+        stream,
+        "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    serde_json::from_str(body).unwrap()
+}
+
+#[test]
+fn test_rpc_filter_json() {
+    assert_eq!(
+        RpcFilter::DataSize(165).to_json(),
+        json!({ "dataSize": 165 })
+    );
+    assert_eq!(
+        RpcFilter::memcmp(32, [1, 2, 3]).to_json(),
+        json!({ "memcmp": { "offset": 32, "bytes": "AQID", "encoding": "base64" } })
+    );
+}
+
+#[test]
+fn test_mock_rpc_server_get_account_info() {
+    let pubkey = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(
+            pubkey,
+            AccountBuilder::new()
+                .balance(5_000)
+                .owner(owner)
+                .data_raw(vec![1, 2, 3, 4]),
+        )
+        .unwrap();
+
+    let server = MockRpcServer::start(accounts).unwrap();
+    server.set_slot(42);
+
+    let response = post(
+        &server,
+        json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "getAccountInfo",
+            "params": [pubkey.to_string(), { "encoding": "base58", "dataSlice": { "offset": 1, "length": 2 } }],
+        }),
+    );
+    assert_eq!(response["id"], 7);
+    assert_eq!(response["result"]["context"]["slot"], 42);
+    let value = &response["result"]["value"];
+    assert_eq!(value["lamports"], 5_000);
+    assert_eq!(value["owner"], owner.to_string());
+    assert_eq!(value["space"], 4);
+    assert_eq!(
+        value["data"],
+        json!([bs58::encode([2, 3]).into_string(), "base58"])
+    );
+
+    // Accounts added after start are served, missing ones are null
+    let missing = Pubkey::new_unique();
+    let response = post(
+        &server,
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "getAccountInfo", "params": [missing.to_string()] }),
+    );
+    assert_eq!(response["result"]["value"], Value::Null);
+    server.set_account(missing, AccountBuilder::new().balance(1).build());
+    let response = post(
+        &server,
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "getAccountInfo", "params": [missing.to_string()] }),
+    );
+    assert_eq!(response["result"]["value"]["lamports"], 1);
+}
+
+#[test]
+fn test_mock_rpc_server_errors() {
+    let server = MockRpcServer::start(AccountMap::new()).unwrap();
+
+    let response = post(
+        &server,
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "getBalance", "params": [] }),
+    );
+    assert_eq!(response["error"]["code"], -32601);
+
+    let response = post(
+        &server,
+        json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "getAccountInfo", "params": ["not a pubkey"] },
+            { "jsonrpc": "2.0", "id": 2, "method": "getMultipleAccounts", "params": [[]] },
+        ]),
+    );
+    assert_eq!(response[0]["error"]["code"], -32602);
+    assert_eq!(response[1]["result"]["value"], json!([]));
+
+    // An oversized body is refused before it is read or allocated
+    let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+    write!(
+        stream,
+        "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n",
+        usize::MAX
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+}
+
+#[test]
+fn test_filters_over_account_map() {
+    let program_id = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let mut matching_data = vec![0; 165];
+    matching_data[..32].copy_from_slice(mint.as_ref());
+
+    let holder = Pubkey::new_unique();
+    let other_mint = Pubkey::new_unique();
+    let short = Pubkey::new_unique();
+    let foreign = Pubkey::new_unique();
+    let mut accounts = AccountMap::new();
+    accounts
+        .add_with_builder(
+            holder,
+            AccountBuilder::new()
+                .owner(program_id)
+                .data_raw(matching_data.clone()),
+        )
+        .unwrap()
+        .add_with_builder(
+            other_mint,
+            AccountBuilder::new()
+                .owner(program_id)
+                .data_raw(vec![0; 165]),
+        )
+        .unwrap()
+        .add_with_builder(
+            short,
+            AccountBuilder::new()
+                .owner(program_id)
+                .data_raw(mint.to_bytes().to_vec()),
+        )
+        .unwrap()
+        .add_with_builder(foreign, AccountBuilder::new().data_raw(matching_data))
+        .unwrap();
+
+    // The filters a client would send, in their JSON form
+    let filters: Vec<RpcFilter> = [
+        json!({ "dataSize": 165 }),
+        json!({ "memcmp": { "offset": 0, "bytes": mint.to_string() } }),
+    ]
+    .iter()
+    .map(|filter| RpcFilter::from_json(filter).unwrap())
+    .collect();
+
+    let matched = accounts.get_program_accounts(&program_id, &filters);
+    assert_eq!(matched.len(), 1);
+    assert!(matched.get_account(&holder).is_some());
+
+    // Without the size filter the short account matches too
+    let matched = accounts.get_program_accounts(&program_id, &filters[1..]);
+    assert_eq!(matched.len(), 2);
+    assert!(matched.get_account(&short).is_some());
+
+    assert_eq!(accounts.get_program_accounts(&program_id, &[]).len(), 3);
+    assert!(RpcFilter::from_json(&json!({ "unknown": 1 })).is_err());
+}
+
+#[cfg(feature = "rpc")]
+mod client {
+    use serde_json::{Value, json};
+    use solana_accountgen::rpc::{RpcFetcher, RpcFilter, clone_account, clone_program_accounts};
+    use solana_pubkey::Pubkey;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// Serves a single JSON-RPC response and returns the request body it received.
+    fn serve_once(result: Value) -> (String, JoinHandle<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string();
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+
+            serde_json::from_slice(&body).unwrap()
+        });
+
+        (url, handle)
+    }
+
+    fn rpc_account(owner: &Pubkey, data: &str) -> Value {
+        json!({
+            "lamports": 1_000_000,
+            "owner": owner.to_string(),
+            "executable": false,
+            "rentEpoch": u64::MAX,
+            "space": 3,
+            "data": [data, "base64"],
+        })
+    }
+
+    #[test]
+    fn test_clone_account() {
+        let owner = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        let (url, server) = serve_once(json!({
+            "context": { "slot": 1 },
+            "value": rpc_account(&owner, "AQID"),
+        }));
+
+        let account = clone_account(&url, &pubkey).unwrap().unwrap();
+        let request = server.join().unwrap();
+
+        assert_eq!(request["method"], "getAccountInfo");
+        assert_eq!(request["params"][0], pubkey.to_string());
+        assert_eq!(account.owner, owner);
+        assert_eq!(account.data, vec![1, 2, 3]);
+        assert_eq!(account.rent_epoch, u64::MAX);
+    }
+
+    #[test]
+    fn test_clone_program_accounts_with_filters() {
+        let program_id = Pubkey::new_unique();
+        let pool1 = Pubkey::new_unique();
+        let pool2 = Pubkey::new_unique();
+        let (url, server) = serve_once(json!([
+            { "pubkey": pool1.to_string(), "account": rpc_account(&program_id, "AQID") },
+            { "pubkey": pool2.to_string(), "account": rpc_account(&program_id, "BAUG") },
+        ]));
+
+        let accounts = clone_program_accounts(
+            &url,
+            &program_id,
+            &[RpcFilter::DataSize(3), RpcFilter::memcmp(0, [1])],
+        )
+        .unwrap();
+        let request = server.join().unwrap();
+
+        assert_eq!(request["method"], "getProgramAccounts");
+        assert_eq!(request["params"][1]["filters"][0], json!({ "dataSize": 3 }));
+        assert_eq!(
+            request["params"][1]["filters"][1]["memcmp"]["bytes"],
+            "AQ=="
+        );
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts.get_account(&pool2).unwrap().data, vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn test_fetcher_serves_repeat_lookups_from_cache() {
+        let owner = Pubkey::new_unique();
+        let existing = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+        let cache_dir = std::env::temp_dir().join(format!(
+            "solana-accountgen-rpc-cache-{}",
+            std::process::id()
+        ));
+
+        let (url, server) = serve_once(json!({
+            "context": { "slot": 42 },
+            "value": [rpc_account(&owner, "AQID"), null],
+        }));
+
+        let mut fetcher = RpcFetcher::new(url).cache_dir(&cache_dir);
+        let accounts = fetcher.fetch_accounts(&[existing, missing]).unwrap();
+        let request = server.join().unwrap();
+
+        assert_eq!(request["method"], "getMultipleAccounts");
+        assert_eq!(accounts.len(), 1);
+        assert!(
+            cache_dir
+                .join(existing.to_string())
+                .join("42.json")
+                .exists()
+        );
+
+        // The mock server is gone, so these must come from the cache
+        let mut fetcher = fetcher.offline(true);
+        let cached = fetcher.fetch_account(&existing).unwrap().unwrap();
+        assert_eq!(cached.data, vec![1, 2, 3]);
+        assert!(fetcher.fetch_account(&missing).unwrap().is_none());
+        assert!(fetcher.fetch_account(&Pubkey::new_unique()).is_err());
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_clone_from_mock_rpc_server() {
+        use solana_accountgen::rpc::{MockRpcServer, clone_accounts};
+        use solana_accountgen::{AccountBuilder, AccountMap};
+
+        let program_id = Pubkey::new_unique();
+        let pool_a = Pubkey::new_unique();
+        let pool_b = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+
+        let mut accounts = AccountMap::new();
+        for (pubkey, tag) in [(pool_a, 1u8), (pool_b, 2u8)] {
+            accounts
+                .add_with_builder(
+                    pubkey,
+                    AccountBuilder::new()
+                        .balance(1_000_000)
+                        .owner(program_id)
+                        .data_raw(vec![tag, 0, 0, 0]),
+                )
+                .unwrap();
+        }
+        accounts
+            .add_with_builder(wallet, AccountBuilder::new().balance(10))
+            .unwrap();
+        let server = MockRpcServer::start(accounts.clone()).unwrap();
+
+        let account = clone_account(&server.url(), &pool_a).unwrap().unwrap();
+        assert_eq!(Some(&account), accounts.get_account(&pool_a));
+
+        let missing = Pubkey::new_unique();
+        let cloned = clone_accounts(&server.url(), &[wallet, missing]).unwrap();
+        assert_eq!(cloned.len(), 1);
+        assert_eq!(cloned.get_account(&wallet), accounts.get_account(&wallet));
+
+        let cloned = clone_program_accounts(
+            &server.url(),
+            &program_id,
+            &[RpcFilter::DataSize(4), RpcFilter::memcmp(0, [2])],
+        )
+        .unwrap();
+        assert_eq!(cloned.len(), 1);
+        assert_eq!(cloned.get_account(&pool_b), accounts.get_account(&pool_b));
+
+        let mut fetcher = RpcFetcher::new(server.url());
+        assert_eq!(
+            fetcher
+                .fetch_accounts(&[pool_a, pool_b, wallet])
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_clone_recursive_follows_references() {
+        use solana_accountgen::extensions::token::{create_mint_account, create_token_account};
+        use solana_accountgen::rpc::{MockRpcServer, clone_recursive};
+        use solana_accountgen::well_known::TOKEN_PROGRAM;
+        use solana_accountgen::{AccountBuilder, AccountMap};
+
+        let (token_account, mint, authority, owner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut accounts = AccountMap::new();
+        accounts.set_account(
+            token_account,
+            create_token_account(&mint, &owner, 10, &TOKEN_PROGRAM).unwrap(),
+        );
+        accounts.set_account(
+            mint,
+            create_mint_account(&authority, 10, 6, &TOKEN_PROGRAM).unwrap(),
+        );
+        accounts
+            .add_with_builder(authority, AccountBuilder::new().balance(5))
+            .unwrap();
+        accounts
+            .add_with_builder(Pubkey::new_unique(), AccountBuilder::new())
+            .unwrap();
+        let server = MockRpcServer::start(accounts).unwrap();
+
+        // The owner wallet does not exist and the token program is not followed
+        let cloned = clone_recursive(&server.url(), &token_account, 1).unwrap();
+        assert_eq!(cloned.len(), 2);
+        assert!(cloned.get_account(&mint).is_some());
+
+        let cloned = clone_recursive(&server.url(), &token_account, 2).unwrap();
+        assert_eq!(cloned.len(), 3);
+        assert!(cloned.get_account(&authority).is_some());
+
+        let roots_only = clone_recursive(&server.url(), &token_account, 0).unwrap();
+        assert_eq!(roots_only.len(), 1);
+    }
+
+    /// Serves `count` JSON-RPC requests, answering each with `handler`.
+    fn serve_with<F>(count: usize, handler: F) -> (String, JoinHandle<Vec<Value>>)
+    where
+        F: Fn(&Value) -> Value + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for _ in 0..count {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+
+                let response =
+                    json!({ "jsonrpc": "2.0", "id": 1, "result": handler(&request) }).to_string();
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn test_fetch_transaction() {
+        use solana_accountgen::rpc::replay::fetch_transaction;
+        use solana_accountgen::well_known::{BPF_LOADER_UPGRADEABLE, TOKEN_PROGRAM};
+        use solana_instruction::{AccountMeta, Instruction};
+        use solana_transaction::Transaction;
+        use solana_transaction::versioned::VersionedTransaction;
+        use std::collections::HashMap;
+
+        let payer = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let program_data = Pubkey::new_unique();
+        let transaction = VersionedTransaction::from(Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &[1],
+                vec![AccountMeta::new(token_account, false)],
+            )],
+            Some(&payer),
+        ));
+        let encoded = base64::encode(bincode::serialize(&transaction).unwrap());
+
+        let mut program = 2u32.to_le_bytes().to_vec();
+        program.extend_from_slice(program_data.as_ref());
+        let mut token_data = vec![0; 165];
+        token_data[64..72].copy_from_slice(&1_000u64.to_le_bytes());
+        let mut accounts = HashMap::new();
+        accounts.insert(payer, rpc_account(&Pubkey::default(), ""));
+        accounts.insert(
+            token_account,
+            rpc_account(&TOKEN_PROGRAM, &base64::encode(&token_data)),
+        );
+        accounts.insert(
+            program_id,
+            rpc_account(&BPF_LOADER_UPGRADEABLE, &base64::encode(&program)),
+        );
+        accounts.insert(program_data, rpc_account(&BPF_LOADER_UPGRADEABLE, "AQID"));
+
+        let keys = transaction.message.static_account_keys().to_vec();
+        let token_index = keys.iter().position(|key| *key == token_account).unwrap();
+        let pre_balances: Vec<u64> = (0..keys.len() as u64).map(|i| 10 + i).collect();
+        let (url, server) = serve_with(3, move |request| match request["method"].as_str() {
+            Some("getTransaction") => json!({
+                "slot": 100,
+                "transaction": [encoded, "base64"],
+                "meta": {
+                    "preBalances": pre_balances,
+                    "preTokenBalances": [
+                        { "accountIndex": token_index, "uiTokenAmount": { "amount": "42" } }
+                    ],
+                    "loadedAddresses": { "writable": [], "readonly": [] },
+                },
+            }),
+            Some("getMultipleAccounts") => {
+                let values: Vec<Value> = request["params"][0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|key| {
+                        let key: Pubkey = key.as_str().unwrap().parse().unwrap();
+                        accounts.get(&key).cloned().unwrap_or(Value::Null)
+                    })
+                    .collect();
+                json!({ "context": { "slot": 200 }, "value": values })
+            }
+            method => panic!("unexpected method {method:?}"),
+        });
+
+        let (cloned, fetched) = fetch_transaction(&url, "sig").unwrap();
+        let requests = server.join().unwrap();
+
+        assert_eq!(requests[0]["params"][0], "sig");
+        assert_eq!(fetched, transaction);
+        assert_eq!(cloned.len(), 4);
+        assert_eq!(
+            cloned.get_account(&program_data).unwrap().data,
+            vec![1, 2, 3]
+        );
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(cloned.get_account(key).unwrap().lamports, 10 + i as u64);
+        }
+        let token = cloned.get_account(&token_account).unwrap();
+        assert_eq!(token.data[64..72], 42u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_fetch_transaction_not_found() {
+        use solana_accountgen::rpc::replay::fetch_transaction;
+
+        let (url, server) = serve_once(Value::Null);
+        assert!(fetch_transaction(&url, "missing").is_err());
+        server.join().unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_shared_account_map_across_tasks() {
+    let vault = Pubkey::new_unique();
+    let accounts = SharedAccountMap::new();
+    accounts
+        .add_with_builder(vault, AccountBuilder::new().balance(0))
+        .unwrap();
+    let server = MockRpcServer::start(accounts.clone()).unwrap();
+    let updates = server.shared_accounts().subscribe();
+
+    let tasks: Vec<_> = (0..8)
+        .map(|_| {
+            let accounts = accounts.clone();
+            tokio::spawn(async move {
+                for _ in 0..100 {
+                    accounts.update_account(&vault, |account| account.lamports += 1);
+                    tokio::task::yield_now().await;
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let response = post(
+        &server,
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "getAccountInfo", "params": [vault.to_string()] }),
+    );
+    assert_eq!(response["result"]["value"]["lamports"], 800);
+    assert_eq!(server.accounts().get_account(&vault).unwrap().lamports, 800);
+    assert_eq!(updates.drain().len(), 800);
+}
+
+#[cfg(feature = "rpc")]
+#[test]
+fn test_fixture_drift() {
+    use solana_accountgen::fixture::FixtureAccount;
+    use solana_accountgen::rpc::drift::{check_drift, clone_with_source};
+
+    let (pool, vault, closed, local) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let accounts = SharedAccountMap::new();
+    for pubkey in [pool, vault, closed] {
+        accounts
+            .add_with_builder(
+                pubkey,
+                AccountBuilder::new().balance(1_000).data_raw(vec![0; 4]),
+            )
+            .unwrap();
+    }
+    let server = MockRpcServer::start(accounts.clone()).unwrap();
+    server.set_slot(100);
+
+    let mut records = clone_with_source(&server.url(), &[pool, vault, closed]).unwrap();
+    assert_eq!(records.len(), 3);
+    let source = records[0].source.clone().unwrap();
+    assert_eq!(
+        (source.cluster, source.slot, source.pubkey),
+        (server.url(), 100, pool)
+    );
+    records.push(FixtureAccount::new(
+        local,
+        AccountBuilder::new().balance(1).build(),
+    ));
+    // An anonymized copy keeps its source but not its data
+    let anonymized = Pubkey::new_unique();
+    let mut record = records[0].clone();
+    record.pubkey = anonymized;
+    record.data[0] = 7;
+    records.push(record);
+
+    let report = check_drift(&server.url(), &records).unwrap();
+    assert!(report.is_ok(), "{}", report);
+    assert_eq!(report.checked, 3);
+    assert_eq!(report.untracked, vec![local]);
+    assert_eq!(report.anonymized, vec![anonymized]);
+
+    server.set_slot(200);
+    accounts.update_account(&vault, |account| account.data[1] = 9);
+    accounts.remove_account(&closed);
+    let report = check_drift(&server.url(), &records).unwrap();
+    assert_eq!(report.slot, 200);
+    assert_eq!(report.stale.len(), 1);
+    assert_eq!(report.stale[0].pubkey, vault);
+    assert_eq!(report.closed, vec![closed]);
+    let message = report.into_result().unwrap_err().to_string();
+    assert!(message.contains(&format!("{}: changed since slot 100", vault)));
+    assert!(message.contains("data[1..2]: 00 -> 09"));
+}
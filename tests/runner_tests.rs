@@ -0,0 +1,254 @@
+mod common;
+
+use common::process_instruction;
+use solana_accountgen::harness::LocalHarness;
+use solana_accountgen::runner::{ScenarioRunner, Step};
+use solana_accountgen::{AccountBuilder, AccountGenError, AccountMap};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_program_test::{ProgramTest, processor};
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+
+struct Setup {
+    program_id: Pubkey,
+    payer: Keypair,
+    counter: Pubkey,
+    other: Pubkey,
+    accounts: AccountMap,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let (payer, counter, other) = (Keypair::new(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts = AccountMap::new();
+        accounts
+            .add_with_builder(payer.pubkey(), AccountBuilder::new().balance(1_000_000_000))
+            .unwrap();
+        for pubkey in [counter, other] {
+            accounts
+                .add_with_builder(
+                    pubkey,
+                    AccountBuilder::new()
+                        .balance(1_000_000)
+                        .owner(program_id)
+                        .data_raw(vec![0]),
+                )
+                .unwrap();
+        }
+        Self {
+            program_id,
+            payer,
+            counter,
+            other,
+            accounts,
+        }
+    }
+
+    fn add(&self, pubkey: Pubkey, amount: u8) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &[amount],
+            vec![AccountMeta::new(pubkey, false)],
+        )
+    }
+
+    fn account(&self, pubkey: Pubkey, value: u8) -> AccountMap {
+        let mut account = self.accounts.get_account(&pubkey).unwrap().clone();
+        account.data = vec![value];
+        let mut accounts = AccountMap::new();
+        accounts.set_account(pubkey, account);
+        accounts
+    }
+}
+
+#[tokio::test]
+async fn test_runner_checkpoints_and_assertions() {
+    let setup = Setup::new();
+    let mut harness = LocalHarness::new(setup.program_id, process_instruction);
+    let runner = ScenarioRunner::new()
+        .set_accounts(setup.accounts.clone())
+        .checkpoint("start")
+        .send(vec![setup.add(setup.counter, 2)])
+        .checkpoint("added")
+        .assert_changed("start", &[setup.counter])
+        .send_expecting_failure(vec![setup.add(setup.other, 1), setup.add(setup.other, 0)])
+        .assert_changed("added", &[])
+        .warp(100)
+        .assert_accounts(setup.account(setup.counter, 2));
+
+    let run = runner.run(&mut harness, &setup.payer, &[]).await.unwrap();
+    assert_eq!(
+        run.checkpoint_names().collect::<Vec<_>>(),
+        ["added", "start"]
+    );
+    let start = run.checkpoint("start").unwrap();
+    assert_eq!(start.get_account(&setup.counter).unwrap().data, [0]);
+    assert!(start.get_account(&setup.other).is_some());
+    let added = run.checkpoint("added").unwrap();
+    assert_eq!(added.get_account(&setup.counter).unwrap().data, [2]);
+    assert!(run.checkpoint("missing").is_none());
+}
+
+#[tokio::test]
+async fn test_runner_failure_report() {
+    let setup = Setup::new();
+    let mut harness = LocalHarness::new(setup.program_id, process_instruction);
+    let runner = ScenarioRunner::new()
+        .set_accounts(setup.accounts.clone())
+        .checkpoint("start")
+        .send(vec![setup.add(setup.counter, 1), setup.add(setup.other, 3)])
+        .assert_changed("start", &[setup.counter]);
+
+    let Err(AccountGenError::ConstraintViolation(report)) =
+        runner.run(&mut harness, &setup.payer, &[]).await
+    else {
+        panic!("the assertion should fail");
+    };
+    assert!(
+        report.starts_with("scenario failed at step 4: assert 1 accounts changed since \"start\"")
+    );
+    assert!(report.contains("ok    1. set 3 accounts"));
+    assert!(report.contains("ok    3. send 2 instructions"));
+    assert!(report.contains("FAIL  4."));
+    assert!(report.contains(&format!("expected changes to {}", setup.counter)));
+    assert!(report.contains("changes since checkpoint \"start\":"));
+    assert!(report.contains(&setup.other.to_string()));
+
+    // A failed transaction and a missing checkpoint are reported the same way
+    let runner = ScenarioRunner::new()
+        .set_accounts(setup.accounts.clone())
+        .send(vec![setup.add(setup.counter, 0)]);
+    let Err(AccountGenError::ConstraintViolation(report)) =
+        runner.run(&mut harness, &setup.payer, &[]).await
+    else {
+        panic!("the transaction should fail");
+    };
+    assert!(report.contains("the transaction failed"));
+    assert!(!report.contains("changes since checkpoint"));
+
+    let runner = ScenarioRunner::new().assert_changed("never", &[]);
+    let error = runner
+        .run(&mut harness, &setup.payer, &[])
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("there is no checkpoint \"never\"")
+    );
+}
+
+#[tokio::test]
+async fn test_runner_json_round_trip() {
+    let setup = Setup::new();
+    let runner = ScenarioRunner::new()
+        .set_accounts(setup.accounts.clone())
+        .checkpoint("start")
+        .send_expecting_failure(vec![setup.add(setup.counter, 0)])
+        .warp(50)
+        .assert_changed("start", &[setup.counter]);
+
+    let json = runner.to_json();
+    assert!(json.contains("\"step\": \"setAccounts\""));
+    assert!(json.contains("\"expectFailure\": true"));
+    let parsed = ScenarioRunner::from_json(&json).unwrap();
+    assert_eq!(parsed.to_json(), json);
+    assert!(matches!(parsed.steps()[3], Step::Warp { slot: 50 }));
+
+    // Handwritten scripts may leave out expectFailure
+    let script = format!(
+        r#"[{{"step": "checkpoint", "name": "a"}},
+            {{"step": "send", "instructions": {}}},
+            {{"step": "assertChanged", "since": "a", "accounts": ["{}"]}}]"#,
+        serde_json::to_string(&parsed.steps().iter().find_map(|step| match step {
+            Step::Send { instructions, .. } => Some(instructions),
+            _ => None,
+        }))
+        .unwrap(),
+        setup.counter
+    );
+    let parsed = ScenarioRunner::from_json(&script).unwrap();
+    assert!(matches!(
+        parsed.steps()[1],
+        Step::Send {
+            expect_failure: false,
+            ..
+        }
+    ));
+
+    let error = ScenarioRunner::from_json(r#"[{"step": "explode"}]"#).unwrap_err();
+    assert!(matches!(error, AccountGenError::InvalidDataFormat(_)));
+}
+
+#[cfg(feature = "yaml")]
+#[tokio::test]
+async fn test_runner_from_yaml() {
+    let setup = Setup::new();
+    let script = format!(
+        r#"
+# Increment the counter, then check that only it changed
+- step: setAccounts
+  accounts:
+    {counter}:
+      lamports: 1000000
+      owner: {program_id}
+      data: [AA==, base64]
+      executable: false
+      rentEpoch: 0
+- step: checkpoint
+  name: start
+- step: send
+  instructions:
+    - programId: {program_id}
+      accounts:
+        - pubkey: {counter}
+          isSigner: false
+          isWritable: true
+      data: [AwA=, base64] # only the first byte is read
+- step: warp
+  slot: 50
+- step: assertChanged
+  since: start
+  accounts:
+    - {counter}
+"#,
+        counter = setup.counter,
+        program_id = setup.program_id,
+    );
+    let runner = ScenarioRunner::from_yaml(&script).unwrap();
+    assert_eq!(runner.steps().len(), 5);
+    assert!(matches!(runner.steps()[3], Step::Warp { slot: 50 }));
+
+    let mut harness = LocalHarness::new(setup.program_id, process_instruction);
+    runner.run(&mut harness, &setup.payer, &[]).await.unwrap();
+    assert_eq!(harness.get_account(&setup.counter).unwrap().data, [3]);
+
+    let error = ScenarioRunner::from_yaml("- step: explode").unwrap_err();
+    assert!(matches!(error, AccountGenError::InvalidDataFormat(_)));
+}
+
+#[tokio::test]
+async fn test_runner_on_program_test() {
+    let setup = Setup::new();
+    let program_test = ProgramTest::new(
+        "runner_counter",
+        setup.program_id,
+        processor!(process_instruction),
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+
+    // The runtime marks the accounts it writes rent-exempt
+    let mut expected = setup.account(setup.counter, 5);
+    expected.get_account_mut(&setup.counter).unwrap().rent_epoch = u64::MAX;
+    let runner = ScenarioRunner::new()
+        .set_accounts(setup.account(setup.counter, 0))
+        .checkpoint("start")
+        .send(vec![setup.add(setup.counter, 4)])
+        .assert_changed("start", &[setup.counter])
+        .send(vec![setup.add(setup.counter, 1)])
+        .assert_accounts(expected);
+    runner.run(&mut context, &payer, &[]).await.unwrap();
+}
@@ -0,0 +1,304 @@
+use solana_accountgen::AccountGenError;
+use solana_accountgen::extensions::lending::{
+    LendingMarket, Obligation, Reserve, TOKEN_LENDING_PROGRAM, WAD, lending_market_authority,
+};
+use solana_accountgen::extensions::scenarios::{
+    VestingSchedule, constant_product_pool, epoch_rewards, escrow, lending_market, linear_vesting,
+    vesting_with_state,
+};
+use solana_accountgen::extensions::token::TOKEN_ACCOUNT_LEN;
+use solana_accountgen::serialization::bincode::legacy::deserialize_account_data;
+use solana_accountgen::well_known::TOKEN_PROGRAM;
+use solana_clock::Clock;
+use solana_pubkey::Pubkey;
+use solana_sdk_ids::sysvar;
+use solana_signer::Signer;
+
+fn token_amount(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[64..72].try_into().unwrap())
+}
+
+fn token_owner(data: &[u8]) -> Pubkey {
+    Pubkey::try_from(&data[32..64]).unwrap()
+}
+
+#[test]
+fn test_escrow_scenario_balances() {
+    let program_id = Pubkey::new_unique();
+    let scenario = escrow(&program_id, 1_000, 500).unwrap();
+
+    let maker = scenario.keypair("maker").pubkey();
+    assert_eq!(scenario.pubkey("maker"), maker);
+
+    let maker_a = scenario
+        .accounts
+        .get_account(&scenario.pubkey("maker_token_a"))
+        .unwrap();
+    assert_eq!(maker_a.owner, TOKEN_PROGRAM);
+    assert_eq!(maker_a.data.len(), TOKEN_ACCOUNT_LEN);
+    assert_eq!(token_amount(&maker_a.data), 1_000);
+    assert_eq!(token_owner(&maker_a.data), maker);
+
+    let taker_b = scenario
+        .accounts
+        .get_account(&scenario.pubkey("taker_token_b"))
+        .unwrap();
+    assert_eq!(token_amount(&taker_b.data), 500);
+
+    let vault = scenario
+        .accounts
+        .get_account(&scenario.pubkey("vault"))
+        .unwrap();
+    assert_eq!(token_owner(&vault.data), scenario.pubkey("escrow"));
+
+    // Presets are deterministic
+    let again = escrow(&program_id, 1_000, 500).unwrap();
+    assert_eq!(
+        again.labels().collect::<Vec<_>>(),
+        scenario.labels().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_vesting_scenario_clock_control() {
+    let schedule = VestingSchedule {
+        start: 1_000,
+        cliff: 1_500,
+        end: 2_000,
+        total: 10_000,
+    };
+    let mut scenario = linear_vesting(&Pubkey::new_unique(), schedule).unwrap();
+    assert_eq!(scenario.pubkey("clock"), sysvar::clock::id());
+
+    let clock_account = |scenario: &solana_accountgen::extensions::scenarios::Scenario| {
+        let account = scenario.accounts.get_account(&sysvar::clock::id()).unwrap();
+        bincode::deserialize::<Clock>(&account.data).unwrap()
+    };
+    assert_eq!(clock_account(&scenario).unix_timestamp, 1_000);
+
+    scenario.set_clock(&Clock {
+        unix_timestamp: 1_750,
+        ..Clock::default()
+    });
+    assert_eq!(clock_account(&scenario).unix_timestamp, 1_750);
+    assert_eq!(schedule.vested_at(1_750), 7_500);
+    assert_eq!(schedule.vested_at(1_499), 0);
+}
+
+#[test]
+fn test_vesting_state_follows_clock() {
+    let program_id = Pubkey::new_unique();
+    let schedule = VestingSchedule {
+        start: 1_000,
+        cliff: 1_500,
+        end: 2_000,
+        total: 10_000,
+    };
+    let clock = Clock {
+        slot: 10,
+        unix_timestamp: 1_200,
+        ..Clock::default()
+    };
+    let mut fixture = vesting_with_state(&program_id, schedule, clock, 0, |_, state| {
+        (state.claimed, state.claimable())
+    })
+    .unwrap();
+    let account = |fixture: &solana_accountgen::extensions::scenarios::VestingFixture<_>,
+                   label: &str| {
+        fixture
+            .scenario
+            .accounts
+            .get_account(&fixture.scenario.pubkey(label))
+            .unwrap()
+            .clone()
+    };
+
+    assert_eq!(fixture.expected_claimable(), 0);
+    let vesting = account(&fixture, "vesting");
+    assert_eq!(vesting.owner, program_id);
+    assert_eq!(vesting.data, [0u64, 0].map(u64::to_le_bytes).concat());
+    assert_eq!(token_amount(&account(&fixture, "vault").data), 10_000);
+    assert!(fixture.record_claim(1).is_err());
+
+    fixture.advance_clock(600).unwrap();
+    assert_eq!(fixture.state().clock.slot, 10 + 1_500);
+    // Warmup epochs of the default schedule start at slots 32, 96, 224,
+    // 480 and 992
+    assert_eq!(fixture.state().clock.epoch, 5);
+    assert_eq!(fixture.expected_claimable(), 8_000);
+    let clock = account(&fixture, "clock");
+    assert_eq!(
+        bincode::deserialize::<Clock>(&clock.data)
+            .unwrap()
+            .unix_timestamp,
+        1_800
+    );
+
+    fixture.record_claim(5_000).unwrap();
+    assert_eq!(fixture.expected_claimable(), 3_000);
+    assert_eq!(token_amount(&account(&fixture, "vault").data), 5_000);
+    assert_eq!(
+        token_amount(&account(&fixture, "beneficiary_token").data),
+        5_000
+    );
+    assert_eq!(
+        account(&fixture, "vesting").data,
+        [5_000u64, 3_000].map(u64::to_le_bytes).concat()
+    );
+    assert!(fixture.record_claim(3_001).is_err());
+    assert!(matches!(
+        fixture.advance_clock(i64::MAX),
+        Err(AccountGenError::InvalidDataFormat(_))
+    ));
+
+    assert!(vesting_with_state(&program_id, schedule, Clock::default(), 1, |_, _| ()).is_err());
+}
+
+fn mint_supply(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[36..44].try_into().unwrap())
+}
+
+#[test]
+fn test_constant_product_pool_balances() {
+    let program_id = Pubkey::new_unique();
+    let scenario = constant_product_pool(&program_id, 2_000, 8_000, |s| {
+        (s.pubkey("vault_a"), s.pubkey("vault_b"), 30u16)
+    })
+    .unwrap();
+    let account = |label: &str| {
+        scenario
+            .accounts
+            .get_account(&scenario.pubkey(label))
+            .unwrap()
+            .clone()
+    };
+
+    let authority = scenario.pubkey("authority");
+    assert_eq!(token_amount(&account("vault_a").data), 2_000);
+    assert_eq!(token_amount(&account("vault_b").data), 8_000);
+    assert_eq!(token_owner(&account("vault_a").data), authority);
+    assert_eq!(mint_supply(&account("mint_a").data), 2_000);
+    assert_eq!(mint_supply(&account("lp_mint").data), 4_000);
+    assert_eq!(token_amount(&account("provider_lp").data), 4_000);
+
+    let pool = account("pool");
+    assert_eq!(pool.owner, program_id);
+    assert_eq!(&pool.data[..32], scenario.pubkey("vault_a").as_ref());
+    assert_eq!(&pool.data[64..], &30u16.to_le_bytes());
+}
+
+#[test]
+fn test_lending_market_balances() {
+    let program_id = TOKEN_LENDING_PROGRAM;
+    let scenario = lending_market(&program_id, 1_000_000, 250_000).unwrap();
+    let account = |label: &str| {
+        scenario
+            .accounts
+            .get_account(&scenario.pubkey(label))
+            .unwrap()
+            .clone()
+    };
+
+    let market = LendingMarket::unpack(&account("lending_market").data).unwrap();
+    let (authority, bump_seed) =
+        lending_market_authority(&scenario.pubkey("lending_market"), &program_id);
+    assert_eq!(scenario.pubkey("lending_market_authority"), authority);
+    assert_eq!(market.bump_seed, bump_seed);
+    assert_eq!(market.owner, scenario.keypair("market_owner").pubkey());
+
+    let reserve = Reserve::unpack(&account("reserve").data).unwrap();
+    assert_eq!(account("reserve").owner, program_id);
+    assert_eq!(
+        reserve.liquidity.supply,
+        scenario.pubkey("liquidity_supply")
+    );
+    assert_eq!(
+        token_amount(&account("liquidity_supply").data),
+        reserve.liquidity.available_amount
+    );
+    assert_eq!(token_owner(&account("liquidity_supply").data), authority);
+    assert_eq!(
+        mint_supply(&account("collateral_mint").data),
+        reserve.collateral.mint_total_supply
+    );
+    assert_eq!(reserve.total_liquidity_wads(), 1_000_000 * WAD);
+    assert_eq!(token_amount(&account("user_liquidity").data), 250_000);
+
+    let obligation = Obligation::unpack(&account("obligation").data).unwrap();
+    assert_eq!(obligation.owner, scenario.keypair("user").pubkey());
+    assert_eq!(
+        token_amount(&account("collateral_supply").data),
+        obligation.deposits[0].deposited_amount
+    );
+    assert_eq!(
+        obligation.borrows[0].borrowed_amount_wads,
+        reserve.liquidity.borrowed_amount_wads
+    );
+    // 1 token of 6 decimals at a price of 1
+    assert_eq!(obligation.deposited_value, WAD);
+    assert_eq!(obligation.borrowed_value, WAD / 4);
+    assert_eq!(obligation.allowed_borrow_value, WAD / 2);
+    assert!(obligation.borrowed_value < obligation.allowed_borrow_value);
+
+    // Beyond the 50% loan-to-value ratio
+    assert!(lending_market(&program_id, 1_000_000, 500_001).is_err());
+}
+
+#[test]
+#[should_panic(expected = "no account labeled \"treasury\"")]
+fn test_scenario_unknown_label() {
+    escrow(&Pubkey::new_unique(), 1, 1)
+        .unwrap()
+        .pubkey("treasury");
+}
+
+#[test]
+fn test_epoch_rewards_activation_curve() {
+    use solana_stake_interface::stake_history::StakeHistory;
+    use solana_stake_interface::state::StakeStateV2;
+    use solana_sysvar::epoch_rewards::EpochRewards;
+
+    let stake = 2_000_000_001;
+    let scenario = epoch_rewards(7, stake, 9_000).unwrap();
+    let account = |label| {
+        scenario
+            .accounts
+            .get_account(&scenario.pubkey(label))
+            .unwrap()
+    };
+    let history: StakeHistory = deserialize_account_data(account("stake_history")).unwrap();
+
+    let expected = [
+        ("bootstrap_stake", (stake, stake), (0, 0)),
+        ("warming_stake", (0, stake / 2), (stake - stake / 2, 0)),
+        ("activating_stake", (0, 0), (stake, 0)),
+        ("deactivating_stake", (stake, stake), (0, stake)),
+    ];
+    let mut points = 0;
+    for (label, (previous, effective), (activating, deactivating)) in expected {
+        let state: StakeStateV2 = deserialize_account_data(account(label)).unwrap();
+        let delegation = state.delegation().unwrap();
+        assert_eq!(delegation.voter_pubkey, scenario.pubkey("vote"));
+        assert_eq!(delegation.stake, stake);
+
+        let before = delegation.stake_activating_and_deactivating(6, &history, None);
+        assert_eq!(before.effective, previous, "{}", label);
+        let status = delegation.stake_activating_and_deactivating(7, &history, None);
+        assert_eq!(
+            (status.effective, status.activating, status.deactivating),
+            (effective, activating, deactivating),
+            "{}",
+            label
+        );
+        points += u128::from(before.effective) * 1_000;
+    }
+
+    let rewards: EpochRewards = deserialize_account_data(account("epoch_rewards")).unwrap();
+    assert!(rewards.active);
+    assert_eq!(rewards.total_rewards, 9_000);
+    assert_eq!(rewards.total_points, points);
+    let clock: Clock = deserialize_account_data(account("clock")).unwrap();
+    assert_eq!(clock.epoch, 7);
+
+    assert!(epoch_rewards(0, stake, 9_000).is_err());
+}
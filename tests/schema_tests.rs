@@ -0,0 +1,214 @@
+use serde_json::json;
+use solana_accountgen::serialization::schema::FixtureSchema;
+
+fn counter_schema() -> FixtureSchema {
+    serde_json::from_value(json!({
+        "version": 1,
+        "types": ["Counter"],
+        "definitions": {
+            "Counter": {"kind": "struct", "fields": [
+                {"name": "count", "type": "u64"},
+                {"name": "label", "type": "String"}
+            ]},
+            "String": {"kind": "sequence", "lengthWidth": 4, "lengthRange": [0, u32::MAX], "elements": "u8"},
+            "u64": {"kind": "primitive", "size": 8},
+            "u8": {"kind": "primitive", "size": 1}
+        }
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_decode_from_json_schema() {
+    let schema = counter_schema();
+    let mut data = 3u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&2u32.to_le_bytes());
+    data.extend_from_slice(b"hi");
+
+    assert_eq!(
+        schema.decode("Counter", &data).unwrap(),
+        json!({"count": 3, "label": "hi"})
+    );
+}
+
+#[test]
+fn test_decode_rejects_truncated_and_trailing_data() {
+    let schema = counter_schema();
+    let mut data = 3u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&5u32.to_le_bytes());
+    data.extend_from_slice(b"hi");
+    assert!(schema.decode("Counter", &data).is_err());
+
+    let mut data = 3u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.push(0);
+    assert!(schema.decode("Counter", &data).is_err());
+
+    assert!(schema.decode("Missing", &[]).is_err());
+}
+
+#[test]
+fn test_decode_rejects_zero_sized_sequence_elements() {
+    let schema: FixtureSchema = serde_json::from_value(json!({
+        "version": 1,
+        "types": ["Units", "Pair"],
+        "definitions": {
+            "Units": {"kind": "sequence", "lengthWidth": 4, "lengthRange": [0, u32::MAX], "elements": "()"},
+            "Pair": {"kind": "sequence", "lengthWidth": 0, "lengthRange": [2, 2], "elements": "()"},
+            "()": {"kind": "tuple", "elements": []}
+        }
+    }))
+    .unwrap();
+
+    // A forged length must fail fast rather than decode four billion units
+    let err = schema.decode("Units", &u32::MAX.to_le_bytes()).unwrap_err();
+    assert!(err.to_string().contains("zero-sized elements"));
+    assert_eq!(
+        schema.decode("Units", &0u32.to_le_bytes()).unwrap(),
+        json!([])
+    );
+    // Fixed lengths come from the schema, not the data, so they stay valid
+    assert_eq!(schema.decode("Pair", &[]).unwrap(), json!([null, null]));
+}
+
+#[cfg(feature = "schema")]
+mod generated {
+    use borsh::{BorshSchema, BorshSerialize};
+    use serde_json::json;
+    use solana_accountgen::serialization::schema::FixtureSchema;
+
+    #[derive(BorshSerialize, BorshSchema)]
+    enum Status {
+        Idle,
+        Locked { until: i64 },
+    }
+
+    #[derive(BorshSerialize, BorshSchema)]
+    struct Vault {
+        authority: [u8; 4],
+        balances: Vec<u64>,
+        status: Status,
+        previous: Status,
+        delegate: Option<u8>,
+        total: u128,
+        name: String,
+    }
+
+    #[test]
+    fn test_registered_schema_round_trips_through_json() {
+        let mut schema = FixtureSchema::new();
+        schema.register::<Vault>().register::<Vault>();
+        assert_eq!(schema.types, vec!["Vault".to_string()]);
+
+        let json = serde_json::to_string(&schema).unwrap();
+        let restored: FixtureSchema = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, schema);
+
+        let vault = Vault {
+            authority: [1, 2, 3, 4],
+            balances: vec![10, 20],
+            status: Status::Locked { until: -5 },
+            previous: Status::Idle,
+            delegate: None,
+            total: u128::MAX,
+            name: "main".to_string(),
+        };
+        let data = borsh::to_vec(&vault).unwrap();
+
+        let decoded = restored.decode("Vault", &data).unwrap();
+        assert_eq!(restored.encode("Vault", &decoded).unwrap(), data);
+        assert_eq!(
+            decoded,
+            json!({
+                "authority": [1, 2, 3, 4],
+                "balances": [10, 20],
+                "status": {"Locked": {"until": -5}},
+                "previous": {"Idle": {}},
+                "delegate": {"None": null},
+                "total": u128::MAX.to_string(),
+                "name": "main",
+            })
+        );
+    }
+
+    #[test]
+    fn test_editor_patches_borsh_schema_fields() {
+        use borsh::BorshDeserialize;
+        use solana_accountgen::{AccountBuilder, AccountEditor};
+
+        #[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+        struct Player {
+            name: String,
+            score: u64,
+        }
+
+        let mut account = AccountBuilder::new()
+            .data(Player {
+                name: "ann".to_string(),
+                score: 1,
+            })
+            .unwrap()
+            .build();
+        AccountEditor::new(&mut account)
+            .with_type::<Player>()
+            .patch_field("score", 250u64)
+            .unwrap()
+            .patch_field("name", "bob")
+            .unwrap();
+
+        assert_eq!(
+            Player::try_from_slice(&account.data).unwrap(),
+            Player {
+                name: "bob".to_string(),
+                score: 250,
+            }
+        );
+    }
+}
+
+#[test]
+fn test_encode_inverts_decode() {
+    let schema = counter_schema();
+    let value = json!({"count": 9, "label": "edit me"});
+
+    let data = schema.encode("Counter", &value).unwrap();
+    assert_eq!(schema.decode("Counter", &data).unwrap(), value);
+
+    assert!(schema.encode("Counter", &json!({"count": 9})).is_err());
+    assert!(
+        schema
+            .encode("Counter", &json!({"count": -1, "label": ""}))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_builder_data_json_value() {
+    use solana_accountgen::AccountBuilder;
+    use solana_accountgen::serialization::json::deserialize_account_data_json;
+
+    let value = json!({"count": 1, "label": "x"});
+    let plain = AccountBuilder::new()
+        .data_json_value(value.clone(), None)
+        .unwrap()
+        .build();
+    assert_eq!(
+        deserialize_account_data_json::<serde_json::Value>(&plain).unwrap(),
+        value
+    );
+
+    let schema = counter_schema();
+    let borsh = AccountBuilder::new()
+        .data_json_value(value.clone(), Some(&schema))
+        .unwrap()
+        .build();
+    assert_eq!(borsh.data.len(), 8 + 4 + 1);
+
+    let mut two_roots = schema.clone();
+    two_roots.types.push("u64".to_string());
+    assert!(
+        AccountBuilder::new()
+            .data_json_value(value, Some(&two_roots))
+            .is_err()
+    );
+}
@@ -0,0 +1,62 @@
+use solana_accountgen::instructions::system::transfer;
+use solana_accountgen::{AccountBuilder, AccountGenError, AccountMap, backend};
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+#[tokio::test]
+async fn test_verify_unchanged_outside_write_set() {
+    let mut context = ProgramTest::default().start_with_context().await;
+    let payer = context.payer.pubkey();
+    let (recipient, bystander, removed) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+
+    let mut accounts = AccountMap::new();
+    for pubkey in [recipient, bystander, removed] {
+        accounts
+            .add_with_builder(pubkey, AccountBuilder::new().balance(1_000_000))
+            .unwrap();
+    }
+    backend::load_accounts(&mut context, &accounts).unwrap();
+    let sealed = accounts.seal();
+    assert_eq!(sealed.len(), 3);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(&payer, &recipient, 500)],
+        Some(&payer),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // The payer was never sealed, and the recipient was expected to change
+    backend::verify_unchanged(&mut context, &sealed, &[recipient])
+        .await
+        .unwrap();
+    let error = backend::verify_unchanged(&mut context, &sealed, &[])
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains(&format!("{}: changed", recipient))
+    );
+
+    // Draining an account removes it
+    context.set_account(&removed, &Default::default());
+    match backend::verify_unchanged(&mut context, &sealed, &[recipient]).await {
+        Err(AccountGenError::ConstraintViolation(message)) => {
+            assert!(message.contains(&format!("{}: removed", removed)));
+            assert!(!message.contains(&bystander.to_string()));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
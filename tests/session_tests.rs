@@ -0,0 +1,75 @@
+use solana_accountgen::instructions::{memo, system};
+use solana_accountgen::session::SessionLog;
+use solana_program_test::ProgramTest;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+#[tokio::test]
+async fn test_session_log_records_transactions() {
+    let (mut banks_client, payer, blockhash) = ProgramTest::default().start().await;
+    let recipient = Pubkey::new_unique();
+    let mut session = SessionLog::new();
+
+    let paid = Transaction::new_signed_with_payer(
+        &[
+            system::transfer(&payer.pubkey(), &recipient, 5_000_000),
+            memo("paid", &[&payer.pubkey()]),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    let result = session
+        .process_transaction(&mut banks_client, paid.clone())
+        .await
+        .unwrap();
+    assert!(result.is_ok());
+
+    let overdrawn = Transaction::new_signed_with_payer(
+        &[system::transfer(&payer.pubkey(), &recipient, u64::MAX)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    let result = session
+        .process_transaction(&mut banks_client, overdrawn)
+        .await
+        .unwrap();
+    assert!(result.is_err());
+
+    assert_eq!(session.len(), 2);
+    let first = &session.transactions()[0];
+    assert_eq!(first.signature, paid.signatures[0]);
+    assert_eq!(first.instructions.len(), 2);
+    assert_eq!(first.instructions[1].data, b"paid");
+    assert!(first.is_ok());
+    assert!(first.parsed_logs().messages().any(|m| m.contains("paid")));
+    assert!(first.compute_units.unwrap() > 0);
+
+    let failures: Vec<_> = session.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert!(
+        failures[0]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("Instruction 0")
+    );
+    assert!(session.to_string().contains("#1 "));
+    assert!(session.to_string().contains("failed"));
+
+    let path = std::env::temp_dir().join(format!(
+        "solana-accountgen-session-{}.json",
+        std::process::id()
+    ));
+    session.save(&path).unwrap();
+    assert_eq!(SessionLog::from_file(&path).unwrap(), session);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_session_log_rejects_invalid_json() {
+    let err = SessionLog::from_json("{\"transactions\": 3}").unwrap_err();
+    assert!(err.to_string().contains("invalid session log"));
+}
@@ -0,0 +1,261 @@
+use solana_account_info::AccountInfo;
+use solana_accountgen::extensions::sysvars::{
+    SysvarBundle, SysvarInjector, Y2038_UNIX_TIMESTAMP, at_epoch_boundary, at_last_slot_of_epoch,
+    at_slot, create_sysvar_account, inject_sysvars,
+};
+use solana_accountgen::harness::LocalHarness;
+use solana_accountgen::{AccountBuilder, AccountMap};
+use solana_clock::Clock;
+use solana_epoch_schedule::EpochSchedule;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_program_error::{ProgramError, ProgramResult};
+use solana_program_test::{ProgramTest, ProgramTestContext, processor};
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+use solana_sdk_ids::sysvar;
+use solana_signer::Signer;
+use solana_slot_hashes::SlotHashes;
+use solana_slot_history::{Check, SlotHistory};
+use solana_sysvar::Sysvar;
+use solana_transaction::Transaction;
+
+#[test]
+fn test_epoch_boundary_is_one_slot_after_last_slot() {
+    let last = at_last_slot_of_epoch(3);
+    let boundary = at_epoch_boundary(4);
+
+    assert_eq!(last.clock.slot + 1, boundary.clock.slot);
+    assert_eq!(last.clock.epoch, 3);
+    assert_eq!(boundary.clock.epoch, 4);
+    assert!(last.clock.epoch_start_timestamp < boundary.clock.epoch_start_timestamp);
+    assert_eq!(
+        boundary.clock.leader_schedule_epoch,
+        boundary
+            .epoch_schedule
+            .get_leader_schedule_epoch(boundary.clock.slot)
+    );
+}
+
+#[test]
+fn test_epoch_boundary_with_warmup() {
+    let schedule = EpochSchedule::custom(8_192, 8_192, true);
+    let bundle = SysvarBundle::at_epoch_boundary(2, schedule.clone());
+
+    // Warmup epochs start at 32 slots and double: 0..32, 32..96, 96..224
+    assert_eq!(bundle.clock.slot, 96);
+    assert_eq!(bundle.clock.epoch, 2);
+    assert_eq!(schedule.get_slots_in_epoch(2), 128);
+}
+
+#[test]
+fn test_bundle_wrap_around_timestamp() {
+    let bundle = SysvarBundle::at_slot(1_000, EpochSchedule::without_warmup())
+        .with_unix_timestamp(Y2038_UNIX_TIMESTAMP);
+    let elapsed = 1_000 * 400 / 1_000;
+
+    assert_eq!(bundle.clock.unix_timestamp, Y2038_UNIX_TIMESTAMP);
+    assert_eq!(
+        bundle.clock.unix_timestamp - bundle.clock.epoch_start_timestamp,
+        elapsed
+    );
+
+    let mut accounts = AccountMap::new();
+    bundle.apply_to(&mut accounts);
+    let clock_account = accounts.get_account(&sysvar::clock::id()).unwrap();
+    let clock: Clock = bincode::deserialize(&clock_account.data).unwrap();
+    assert_eq!(clock, bundle.clock);
+    let schedule_account = accounts.get_account(&sysvar::epoch_schedule::id()).unwrap();
+    let schedule: EpochSchedule = bincode::deserialize(&schedule_account.data).unwrap();
+    assert_eq!(schedule, bundle.epoch_schedule);
+}
+
+#[test]
+fn test_bundle_slot_hashes_and_history_agree_with_clock() {
+    let bundle = at_slot(2_000, EpochSchedule::without_warmup());
+
+    let hashes: &[(u64, _)] = &bundle.slot_hashes;
+    assert_eq!(hashes.len(), 512);
+    assert_eq!(hashes[0].0, 1_999);
+    assert_eq!(hashes[511].0, 2_000 - 512);
+    assert_ne!(hashes[0].1, hashes[1].1);
+    assert!(bundle.slot_hashes.get(&2_000).is_none());
+
+    assert_eq!(bundle.slot_history.newest(), 2_000);
+    assert_eq!(bundle.slot_history.check(0), Check::Found);
+    assert_eq!(bundle.slot_history.check(1_999), Check::Found);
+    assert_eq!(bundle.slot_history.check(2_001), Check::Future);
+
+    // Near genesis there are fewer slots to hash
+    let early = at_slot(3, EpochSchedule::without_warmup());
+    assert_eq!(early.slot_hashes.len(), 3);
+    // Far from genesis the history only holds its last MAX_ENTRIES slots
+    let late = at_epoch_boundary(5);
+    assert_eq!(late.slot_history.check(0), Check::TooOld);
+    assert_eq!(late.slot_history.check(late.clock.slot - 1), Check::Found);
+
+    let mut accounts = AccountMap::new();
+    bundle.clone().apply_to(&mut accounts);
+    let account = accounts.get_account(&sysvar::slot_hashes::id()).unwrap();
+    let slot_hashes: SlotHashes = bincode::deserialize(&account.data).unwrap();
+    assert_eq!(slot_hashes, bundle.slot_hashes);
+    let account = accounts.get_account(&sysvar::slot_history::id()).unwrap();
+    let slot_history: SlotHistory = bincode::deserialize(&account.data).unwrap();
+    assert_eq!(slot_history, bundle.slot_history);
+}
+
+/// Test program: fails unless `Clock::get()` returns the slot in the data.
+fn check_clock_slot(_: &Pubkey, _: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let slot = u64::from_le_bytes(data.try_into().unwrap());
+    if Clock::get()?.slot != slot {
+        return Err(ProgramError::Custom(0));
+    }
+    Ok(())
+}
+
+/// Sends an instruction that checks the Clock a program sees is at `slot`.
+async fn assert_program_sees_slot(context: &mut ProgramTestContext, program_id: Pubkey, slot: u64) {
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &slot.to_le_bytes(),
+            vec![],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_bundle_applies_to_program_test_context() {
+    let program_id = Pubkey::new_unique();
+    let mut context = ProgramTest::new("clock_reader", program_id, processor!(check_clock_slot))
+        .start_with_context()
+        .await;
+    let bundle = at_slot(5_000, EpochSchedule::without_warmup());
+    bundle.apply_to(&mut context);
+
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    assert_eq!(clock, bundle.clock);
+    let slot_hashes: SlotHashes = context.banks_client.get_sysvar().await.unwrap();
+    assert_eq!(slot_hashes[0].0, 4_999);
+
+    // Programs read the sysvar cache, which must be refreshed too
+    assert_program_sees_slot(&mut context, program_id, 5_000).await;
+}
+
+#[tokio::test]
+async fn test_context_advances_slots_with_reproducible_sysvars() {
+    use solana_accountgen::extensions::program_test::ProgramTestContextExt;
+    use solana_accountgen::instructions::memo;
+
+    let program_id = Pubkey::new_unique();
+    let mut context = ProgramTest::new("clock_reader", program_id, processor!(check_clock_slot))
+        .start_with_context()
+        .await;
+    let start = context.current_slot().await.unwrap();
+    let epoch_schedule = context.genesis_config().epoch_schedule.clone();
+
+    let memo_transaction = |context: &solana_program_test::ProgramTestContext| {
+        Transaction::new_signed_with_payer(
+            &[memo("tick", &[])],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        )
+    };
+    let first = memo_transaction(&context);
+    context
+        .banks_client
+        .process_transaction(first.clone())
+        .await
+        .unwrap();
+
+    let blockhash = context.advance_slots(100).await.unwrap();
+    assert_eq!(blockhash, context.last_blockhash);
+    assert_eq!(context.current_slot().await.unwrap(), start + 100);
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    assert_eq!(clock, at_slot(start + 100, epoch_schedule).clock);
+    assert_program_sees_slot(&mut context, program_id, start + 100).await;
+
+    // The same instructions sign differently under the new blockhash
+    let second = memo_transaction(&context);
+    assert_ne!(second.signatures, first.signatures);
+    context
+        .banks_client
+        .process_transaction(second)
+        .await
+        .unwrap();
+
+    assert!(context.advance_to_slot(start + 50).await.is_err());
+}
+
+fn require_rent_exempt(_: &Pubkey, accounts: &[AccountInfo], _: &[u8]) -> ProgramResult {
+    let rent = Rent::from_account_info(&accounts[1])?;
+    let clock = Clock::from_account_info(&accounts[2])?;
+    if !rent.is_exempt(accounts[0].lamports(), accounts[0].data_len()) || clock.slot == 0 {
+        return Err(solana_program_error::ProgramError::AccountNotRentExempt);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sysvar_injection_for_local_harness() {
+    let program_id = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &[],
+        vec![
+            AccountMeta::new_readonly(vault, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+    );
+    let mut accounts = AccountMap::new();
+    accounts.set_account(
+        vault,
+        AccountBuilder::new()
+            .owner(program_id)
+            .data_raw(vec![0; 16])
+            .build(),
+    );
+
+    // Without injection, the sysvar accounts are empty
+    let mut harness =
+        LocalHarness::new(program_id, require_rent_exempt).with_accounts(accounts.clone());
+    assert!(harness.process_instruction(&instruction).is_err());
+
+    // A Clock already in the map is kept, the others are injected
+    let clock = Clock {
+        slot: 7,
+        ..Clock::default()
+    };
+    accounts.set_account(sysvar::clock::id(), create_sysvar_account(&clock));
+    let injected = SysvarInjector::new()
+        .with_bundle(&at_slot(100, EpochSchedule::default()))
+        .inject(&mut accounts, [&instruction]);
+    assert_eq!(
+        injected,
+        vec![sysvar::rent::id(), sysvar::instructions::id()]
+    );
+    let clock_account = accounts.get_account(&sysvar::clock::id()).unwrap();
+    assert_eq!(
+        bincode::deserialize::<Clock>(&clock_account.data).unwrap(),
+        clock
+    );
+
+    let mut harness = LocalHarness::new(program_id, require_rent_exempt).with_accounts(accounts);
+    harness.process_instruction(&instruction).unwrap();
+
+    // Unknown accounts are not sysvars and are left missing
+    let mut accounts = AccountMap::new();
+    assert_eq!(inject_sysvars(&mut accounts, [&instruction]).len(), 3);
+    assert!(accounts.get_account(&vault).is_none());
+}
@@ -0,0 +1,124 @@
+use solana_accountgen::AccountGenError;
+use solana_accountgen::extensions::token::{
+    AccountState, TOKEN_ACCOUNT_LEN, TokenAccount, create_token_account,
+    create_wrapped_sol_account, freeze, rotate_owner, set_delegate, sync_native, thaw,
+};
+use solana_accountgen::well_known::{NATIVE_MINT, TOKEN_2022_PROGRAM, TOKEN_PROGRAM};
+use solana_pubkey::Pubkey;
+use solana_rent::Rent;
+
+#[test]
+fn test_token_authority_transitions() {
+    let (mint, owner, delegate, new_owner) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut account = create_token_account(&mint, &owner, 1_000, &TOKEN_PROGRAM).unwrap();
+    set_delegate(&mut account, Some((delegate, 250))).unwrap();
+
+    // A frozen account keeps its delegate but cannot change hands
+    freeze(&mut account).unwrap();
+    assert!(matches!(
+        rotate_owner(&mut account, &new_owner),
+        Err(AccountGenError::ConstraintViolation(_))
+    ));
+    assert!(set_delegate(&mut account, None).is_err());
+    let frozen = TokenAccount::unpack(&account.data).unwrap();
+    assert_eq!(frozen.state, AccountState::Frozen);
+    assert_eq!(frozen.delegate, Some(delegate));
+
+    // Rotating the owner drops the delegate, as the token program does
+    thaw(&mut account).unwrap();
+    assert!(thaw(&mut account).is_err());
+    rotate_owner(&mut account, &new_owner).unwrap();
+    let rotated = TokenAccount::unpack(&account.data).unwrap();
+    assert_eq!(
+        rotated,
+        TokenAccount {
+            mint,
+            owner: new_owner,
+            amount: 1_000,
+            delegate: None,
+            state: AccountState::Initialized,
+            is_native: None,
+            delegated_amount: 0,
+            close_authority: None,
+        }
+    );
+}
+
+#[test]
+fn test_token_account_uses_spl_layout() {
+    let (mint, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let account = create_token_account(&mint, &owner, 1_000, &TOKEN_PROGRAM).unwrap();
+
+    // The offsets the token program reads, not a Borsh encoding
+    assert_eq!(account.data.len(), TOKEN_ACCOUNT_LEN);
+    assert_eq!(&account.data[..32], mint.as_ref());
+    assert_eq!(&account.data[32..64], owner.as_ref());
+    assert_eq!(account.data[64..72], 1_000u64.to_le_bytes());
+    assert_eq!(account.data[108], 1);
+    assert_eq!(account.owner, TOKEN_PROGRAM);
+    assert_eq!(account.lamports, 2_039_280);
+    assert_eq!(
+        account.lamports,
+        Rent::default().minimum_balance(TOKEN_ACCOUNT_LEN)
+    );
+}
+
+#[test]
+fn test_token_helpers_keep_extension_data() {
+    let mut account = create_token_account(
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        5,
+        &TOKEN_2022_PROGRAM,
+    )
+    .unwrap();
+    account.data.extend_from_slice(&[2, 7, 7, 7]);
+
+    freeze(&mut account).unwrap();
+    assert_eq!(account.data.len(), TOKEN_ACCOUNT_LEN + 4);
+    assert_eq!(&account.data[TOKEN_ACCOUNT_LEN..], &[2, 7, 7, 7]);
+
+    let mut uninitialized = account.clone();
+    uninitialized.data[108] = 0;
+    assert!(thaw(&mut uninitialized).is_err());
+    assert!(matches!(
+        TokenAccount::unpack(&account.data[..100]),
+        Err(AccountGenError::InvalidDataFormat(_))
+    ));
+}
+
+#[test]
+fn test_wrapped_sol_account() {
+    let owner = Pubkey::new_unique();
+    let mut account = create_wrapped_sol_account(&owner, 2_000_000).unwrap();
+    let reserve = Rent::default().minimum_balance(TOKEN_ACCOUNT_LEN);
+    assert_eq!(account.owner, TOKEN_PROGRAM);
+    assert_eq!(account.lamports, reserve + 2_000_000);
+
+    // Wrapped SOL cannot be frozen, and a new owner drops the close authority
+    assert!(freeze(&mut account).is_err());
+    let mut token_account = TokenAccount::unpack(&account.data).unwrap();
+    token_account.close_authority = Some(owner);
+    account.data = token_account.pack();
+    rotate_owner(&mut account, &Pubkey::new_unique()).unwrap();
+    assert_eq!(
+        TokenAccount::unpack(&account.data).unwrap().close_authority,
+        None
+    );
+
+    // Lamports withdrawn without burning tokens leave the account unsyncable
+    account.lamports -= 1;
+    assert!(matches!(
+        sync_native(&mut account),
+        Err(AccountGenError::ConstraintViolation(_))
+    ));
+
+    let mut plain = create_token_account(&NATIVE_MINT, &owner, 0, &TOKEN_PROGRAM).unwrap();
+    assert!(sync_native(&mut plain).is_err());
+    assert!(create_wrapped_sol_account(&owner, u64::MAX).is_err());
+}
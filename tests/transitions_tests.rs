@@ -0,0 +1,142 @@
+use solana_accountgen::cluster::MAX_REALLOC_INCREASE;
+use solana_accountgen::transitions::{TransitionChecker, TransitionViolation};
+use solana_accountgen::well_known::{BPF_LOADER, SYSTEM_PROGRAM};
+use solana_accountgen::{AccountBuilder, AccountMap};
+use solana_pubkey::Pubkey;
+
+fn state(accounts: &[(Pubkey, AccountBuilder)]) -> AccountMap {
+    let mut map = AccountMap::new();
+    for (pubkey, builder) in accounts {
+        map.add_with_builder(*pubkey, builder.clone()).unwrap();
+    }
+    map
+}
+
+#[test]
+fn test_transition_checker_accepts_reachable_sequence() {
+    let program_id = Pubkey::new_unique();
+    let (payer, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let wallet = || AccountBuilder::new().owner(SYSTEM_PROGRAM);
+    let vault_account = |data: Vec<u8>| AccountBuilder::new().owner(program_id).data_raw(data);
+
+    let setup = state(&[(payer, wallet().balance(10_000_000))]);
+    // The vault is created with zeroed data and initialized in one transaction
+    let created = state(&[
+        (payer, wallet().balance(10_000_000 - 2_000_000 - 5_000)),
+        (vault, vault_account(vec![1; 64]).balance(2_000_000)),
+    ]);
+    let grown = state(&[
+        (payer, wallet().balance(10_000_000 - 2_500_000 - 5_000)),
+        (
+            vault,
+            vault_account(vec![1; 64 + MAX_REALLOC_INCREASE]).balance(2_500_000),
+        ),
+    ]);
+
+    let checker = TransitionChecker::new()
+        .fees(5_000)
+        .programs(&[SYSTEM_PROGRAM, program_id]);
+    let report = checker.check_sequence(&[setup, created, grown]);
+    assert!(report.is_ok(), "{}", report);
+    assert!(report.into_result().is_ok());
+}
+
+#[test]
+fn test_transition_checker_reports_impossible_transitions() {
+    let program_id = Pubkey::new_unique();
+    let other_program = Pubkey::new_unique();
+    let (vault, foreign, program) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let program_account = |data: Vec<u8>| {
+        AccountBuilder::new()
+            .balance(1_000)
+            .owner(BPF_LOADER)
+            .executable(true)
+            .data_raw(data)
+    };
+
+    let before = state(&[
+        (
+            vault,
+            AccountBuilder::new()
+                .balance(1_000)
+                .owner(program_id)
+                .data_raw(vec![0; 8]),
+        ),
+        (
+            foreign,
+            AccountBuilder::new()
+                .balance(1_000)
+                .owner(other_program)
+                .data_raw(vec![0; 8]),
+        ),
+        (program, program_account(vec![1; 8])),
+    ]);
+    let after = state(&[
+        (
+            vault,
+            AccountBuilder::new()
+                .balance(1_000)
+                .owner(program_id)
+                .data_raw(vec![0; 9 + MAX_REALLOC_INCREASE]),
+        ),
+        (
+            foreign,
+            AccountBuilder::new()
+                .balance(500)
+                .owner(other_program)
+                .data_raw(vec![0; 8]),
+        ),
+        (program, program_account(vec![2; 8])),
+    ]);
+
+    let report = TransitionChecker::new()
+        .programs(&[program_id])
+        .check_sequence(&[before.clone(), before.clone(), after.clone()]);
+    let violations: Vec<_> = report.violations.iter().map(|(i, v)| (*i, v)).collect();
+    // The program account is also modified without the loader running
+    assert_eq!(violations.len(), 5);
+    assert!(violations.iter().all(|(index, _)| *index == 2));
+    assert!(violations.contains(&(
+        2,
+        &TransitionViolation::LamportsNotConserved {
+            before: 3_000,
+            after: 2_500
+        }
+    )));
+    assert!(violations.contains(&(
+        2,
+        &TransitionViolation::ExecutableModified { pubkey: program }
+    )));
+    assert!(violations.contains(&(
+        2,
+        &TransitionViolation::ReallocTooLarge {
+            pubkey: vault,
+            old_len: 8,
+            new_len: 9 + MAX_REALLOC_INCREASE,
+            max_increase: MAX_REALLOC_INCREASE,
+        }
+    )));
+    assert!(report.to_string().contains(&format!(
+        "state 2: {}: lamports were debited, but its owner {} did not run",
+        foreign, other_program
+    )));
+    assert!(report.into_result().is_err());
+
+    // Two instructions may grow the vault twice as much, and the burn is
+    // within the allowed fees
+    let report = TransitionChecker::new()
+        .instructions(2)
+        .fees(500)
+        .check(&before, &after);
+    assert_eq!(
+        report.violations,
+        vec![(
+            1,
+            TransitionViolation::ExecutableModified { pubkey: program }
+        )]
+    );
+}
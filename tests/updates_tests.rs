@@ -0,0 +1,109 @@
+use serde_json::json;
+use solana_accountgen::updates::GeyserAccountUpdate;
+use solana_accountgen::{AccountBuilder, AccountMap};
+use solana_pubkey::Pubkey;
+use std::time::Duration;
+
+#[test]
+fn test_subscribe_sees_sets_and_removes_in_order() {
+    let mut accounts = AccountMap::new();
+    let before = Pubkey::new_unique();
+    accounts.set_account(before, AccountBuilder::new().balance(1).build());
+
+    let updates = accounts.subscribe();
+    let pubkey = Pubkey::new_unique();
+    accounts
+        .add_with_builder(pubkey, AccountBuilder::new().balance(100))
+        .unwrap();
+    accounts.set_account(pubkey, AccountBuilder::new().balance(200).build());
+    accounts.remove_account(&pubkey);
+    // Removing an account that does not exist is not a change
+    accounts.remove_account(&Pubkey::new_unique());
+
+    let events = updates.drain();
+    assert_eq!(events.len(), 3);
+    assert!(events.iter().all(|event| event.pubkey == pubkey));
+    assert_eq!(events[0].account.as_ref().unwrap().lamports, 100);
+    assert_eq!(events[1].account.as_ref().unwrap().lamports, 200);
+    assert_eq!(events[2].account, None);
+    assert_eq!(
+        events.iter().map(|e| e.write_version).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    assert!(updates.try_recv().is_none());
+
+    // Clones do not share subscribers
+    let mut clone = accounts.clone();
+    clone.set_account(Pubkey::new_unique(), AccountBuilder::new().build());
+    assert!(updates.try_recv().is_none());
+}
+
+#[test]
+fn test_subscription_across_threads() {
+    let mut accounts = AccountMap::new();
+    let updates = accounts.subscribe();
+    let dropped = accounts.subscribe();
+    drop(dropped);
+
+    let pubkey = Pubkey::new_unique();
+    let handle = std::thread::spawn(move || {
+        let mut seen = Vec::new();
+        while let Some(update) = updates.recv() {
+            seen.push(update);
+        }
+        seen
+    });
+
+    accounts.set_account(pubkey, AccountBuilder::new().balance(5).build());
+    drop(accounts);
+
+    let seen = handle.join().unwrap();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0].pubkey, pubkey);
+}
+
+#[test]
+fn test_geyser_update_json() {
+    let mut accounts = AccountMap::new();
+    let updates = accounts.subscribe();
+    let pubkey = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    accounts.set_account(
+        pubkey,
+        AccountBuilder::new()
+            .balance(1_000)
+            .owner(owner)
+            .data_raw(vec![1, 2, 3])
+            .build(),
+    );
+    accounts.remove_account(&pubkey);
+
+    let set = updates.recv_timeout(Duration::from_secs(1)).unwrap();
+    let value = serde_json::to_value(set.to_geyser(9)).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "slot": 9,
+            "isStartup": false,
+            "account": {
+                "pubkey": pubkey.to_string(),
+                "lamports": 1_000,
+                "owner": owner.to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "data": ["AQID", "base64"],
+                "writeVersion": 1,
+                "txnSignature": null,
+            },
+        })
+    );
+    let parsed: GeyserAccountUpdate = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed, set.to_geyser(9));
+
+    // Removals look like a closed account
+    let removed = updates.try_recv().unwrap().to_geyser(10);
+    assert_eq!(removed.account.lamports, 0);
+    assert_eq!(removed.account.owner, Pubkey::default());
+    assert!(removed.account.data.is_empty());
+    assert_eq!(removed.account.write_version, 2);
+}